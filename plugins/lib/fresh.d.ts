@@ -600,6 +600,13 @@ interface EditorAPI {
    * @returns true if the context was updated
    */
   setContext(name: string, active: boolean): boolean;
+  /**
+   * Run a sequence of editor actions, as recorded in a macro
+   * Actions use the JSON form shown by "Edit Macro" (e.g. ["MoveDown", {"InsertChar": "x"}]).
+   * @param actions - JSON array of actions
+   * @returns true if the actions were parsed and queued
+   */
+  executeActions(actions: string): boolean;
   /**
    * Open a file in the editor, optionally at a specific location
    * @param path - File path to open
@@ -0,0 +1,23 @@
+; Functions
+(function_definition
+  body: (compound_statement) @function.inner) @function.outer
+
+; Type definitions
+(struct_specifier
+  body: (field_declaration_list) @class.inner) @class.outer
+
+(union_specifier
+  body: (field_declaration_list) @class.inner) @class.outer
+
+(enum_specifier
+  body: (enumerator_list) @class.inner) @class.outer
+
+; Parameters and arguments
+(parameter_list (_) @parameter.inner)
+(argument_list (_) @parameter.inner)
+
+; Comments
+(comment) @comment.outer
+
+; Blocks
+(compound_statement) @block.outer
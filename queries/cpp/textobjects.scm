@@ -0,0 +1,31 @@
+; Functions and lambdas
+(function_definition
+  body: (compound_statement) @function.inner) @function.outer
+
+(lambda_expression
+  body: (compound_statement) @function.inner) @function.outer
+
+; Type definitions
+(class_specifier
+  body: (field_declaration_list) @class.inner) @class.outer
+
+(struct_specifier
+  body: (field_declaration_list) @class.inner) @class.outer
+
+(union_specifier
+  body: (field_declaration_list) @class.inner) @class.outer
+
+(enum_specifier
+  body: (enumerator_list) @class.inner) @class.outer
+
+; Parameters and arguments
+(parameter_list (_) @parameter.inner)
+(argument_list (_) @parameter.inner)
+(template_parameter_list (_) @parameter.inner)
+(template_argument_list (_) @parameter.inner)
+
+; Comments
+(comment) @comment.outer
+
+; Blocks
+(compound_statement) @block.outer
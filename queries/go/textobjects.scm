@@ -0,0 +1,28 @@
+; Functions and methods
+(function_declaration
+  body: (block) @function.inner) @function.outer
+
+(method_declaration
+  body: (block) @function.inner) @function.outer
+
+(func_literal
+  body: (block) @function.inner) @function.outer
+
+; Type definitions
+(type_declaration
+  (type_spec
+    type: (struct_type) @class.inner)) @class.outer
+
+(type_declaration
+  (type_spec
+    type: (interface_type) @class.inner)) @class.outer
+
+; Parameters and arguments
+(parameter_list (_) @parameter.inner)
+(argument_list (_) @parameter.inner)
+
+; Comments
+(comment) @comment.outer
+
+; Blocks
+(block) @block.outer
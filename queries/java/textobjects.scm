@@ -0,0 +1,30 @@
+; Methods, constructors and lambdas
+(method_declaration
+  body: (block) @function.inner) @function.outer
+
+(constructor_declaration
+  body: (constructor_body) @function.inner) @function.outer
+
+(lambda_expression
+  body: (_) @function.inner) @function.outer
+
+; Type definitions
+(class_declaration
+  body: (class_body) @class.inner) @class.outer
+
+(interface_declaration
+  body: (interface_body) @class.inner) @class.outer
+
+(enum_declaration
+  body: (enum_body) @class.inner) @class.outer
+
+; Parameters and arguments
+(formal_parameters (_) @parameter.inner)
+(argument_list (_) @parameter.inner)
+
+; Comments
+(line_comment) @comment.outer
+(block_comment) @comment.outer
+
+; Blocks
+(block) @block.outer
@@ -0,0 +1,5 @@
+; SQL in string literals, recognized by a leading SQL keyword
+((string
+  (string_content) @injection.content)
+  (#match? @injection.content "^\\s*(SELECT|INSERT|UPDATE|DELETE|CREATE|ALTER|DROP|WITH)\\s")
+  (#set! injection.language "sql"))
@@ -0,0 +1,21 @@
+; Functions and lambdas
+(function_definition
+  body: (block) @function.inner) @function.outer
+
+(lambda
+  body: (_) @function.inner) @function.outer
+
+; Classes
+(class_definition
+  body: (block) @class.inner) @class.outer
+
+; Parameters and arguments
+(parameters (_) @parameter.inner)
+(lambda_parameters (_) @parameter.inner)
+(argument_list (_) @parameter.inner)
+
+; Comments
+(comment) @comment.outer
+
+; Blocks
+(block) @block.outer
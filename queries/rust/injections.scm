@@ -0,0 +1,11 @@
+; Doc comments are Markdown; code blocks in them are doc tests. The comment
+; lines are combined so a code block spanning several lines is parsed whole.
+((line_comment
+  (doc_comment) @injection.content)
+  (#set! injection.language "markdown")
+  (#set! injection.combined))
+
+((block_comment
+  (doc_comment) @injection.content)
+  (#set! injection.language "markdown")
+  (#set! injection.combined))
@@ -0,0 +1,4 @@
+; Tagged templates: html`...`, css`...`, sql`...`
+(call_expression
+  function: (identifier) @injection.language
+  arguments: (template_string) @injection.content)
@@ -0,0 +1,37 @@
+; Functions
+(function_declaration
+  body: (statement_block) @function.inner) @function.outer
+
+(function_expression
+  body: (statement_block) @function.inner) @function.outer
+
+(generator_function_declaration
+  body: (statement_block) @function.inner) @function.outer
+
+(arrow_function
+  body: (_) @function.inner) @function.outer
+
+(method_definition
+  body: (statement_block) @function.inner) @function.outer
+
+; Classes
+(class_declaration
+  body: (class_body) @class.inner) @class.outer
+
+(abstract_class_declaration
+  body: (class_body) @class.inner) @class.outer
+
+(class
+  body: (class_body) @class.inner) @class.outer
+
+; Parameters and arguments
+(formal_parameters (_) @parameter.inner)
+(arguments (_) @parameter.inner)
+(type_parameters (_) @parameter.inner)
+(type_arguments (_) @parameter.inner)
+
+; Comments
+(comment) @comment.outer
+
+; Blocks
+(statement_block) @block.outer
@@ -225,6 +225,9 @@ impl Editor {
         // This persists file positions across projects and editor instances
         self.restore_global_file_state(buffer_id, path, active_split);
 
+        // Show breakpoints set before the file was opened (or restored from the session)
+        self.refresh_breakpoint_indicators(buffer_id);
+
         // Emit control event
         self.emit_event(
             crate::model::control_event::events::FILE_OPENED.name,
@@ -897,6 +900,7 @@ impl Editor {
         self.buffers.remove(&id);
         self.event_logs.remove(&id);
         self.seen_byte_ranges.remove(&id);
+        self.cursor_snapshots.remove(&id);
         self.buffer_metadata.remove(&id);
 
         // Remove buffer from panel_ids mapping if it was a panel buffer
@@ -5,10 +5,11 @@
 //! - Copy with formatting (HTML with syntax highlighting)
 //! - Multi-cursor add above/below/at next match
 
+use crate::input::kill_ring::KillEntry;
 use crate::input::multi_cursor::{
     add_cursor_above, add_cursor_at_next_match, add_cursor_below, AddCursorResult,
 };
-use crate::model::event::{CursorId, Event};
+use crate::model::event::Event;
 
 use super::Editor;
 
@@ -26,8 +27,8 @@ use super::Editor;
 impl Editor {
     /// Copy the current selection to clipboard
     pub fn copy_selection(&mut self) {
-        // Collect ranges first
-        let ranges: Vec<_> = {
+        // Collect ranges first, in document order
+        let mut ranges: Vec<_> = {
             let state = self.active_state();
             state
                 .cursors
@@ -35,19 +36,21 @@ impl Editor {
                 .filter_map(|(_, cursor)| cursor.selection_range())
                 .collect()
         };
+        ranges.sort_by_key(|range| range.start);
 
-        let mut text = String::new();
         let state = self.active_state_mut();
-        for range in ranges {
-            if !text.is_empty() {
-                text.push('\n');
-            }
-            let range_text = state.get_text_range(range.start, range.end);
-            text.push_str(&range_text);
-        }
+        let pieces: Vec<String> = ranges
+            .into_iter()
+            .map(|range| state.get_text_range(range.start, range.end))
+            .collect();
 
+        let text = pieces.join("\n");
         if !text.is_empty() {
             self.clipboard.copy(text);
+            // Each selection becomes one piece of the recorded entry
+            let entry = KillEntry::new(pieces);
+            self.clipboard_history.push(entry.clone());
+            self.kill_ring.push(entry);
             self.status_message = Some("Copied".to_string());
         }
     }
@@ -124,6 +127,7 @@ impl Editor {
                     max_offset,
                     &theme,
                     0, // No context needed since we're copying exact selection
+                    state.syntax_tree.as_ref().and_then(|tree| tree.tree()),
                 );
                 (text, highlight_spans)
             }
@@ -264,6 +268,7 @@ impl Editor {
     /// Handles:
     /// - Single cursor paste
     /// - Multi-cursor paste (pastes at each cursor)
+    /// - Multi-cursor copy pasted into as many cursors (one piece per cursor)
     /// - Selection replacement (deletes selection before inserting)
     /// - Atomic undo (single undo step for entire operation)
     pub fn paste(&mut self) {
@@ -273,10 +278,65 @@ impl Editor {
             None => return,
         };
 
+        // The clipboard still holds our last multi-cursor copy: split it back up
+        if self.prompt.is_none() {
+            if let Some(entry) = self.clipboard_history.newest() {
+                let pieces = entry.pieces().len();
+                if pieces > 1
+                    && pieces == self.active_state().cursors.count()
+                    && entry.text() == KillEntry::new(vec![text.clone()]).text()
+                {
+                    let entry = entry.clone();
+                    self.insert_entry(&entry, "Paste");
+                    self.status_message = Some("Pasted".to_string());
+                    return;
+                }
+            }
+        }
+
         // Use paste_text which handles line ending normalization
         self.paste_text(text);
     }
 
+    /// Open a fuzzy picker over the clipboard history, newest first
+    pub fn start_paste_from_history_prompt(&mut self) {
+        use crate::view::prompt::PromptType;
+
+        if self.clipboard_history.is_empty() {
+            self.set_status_message("Clipboard history is empty".to_string());
+            return;
+        }
+
+        let suggestions = self
+            .clipboard_history
+            .entries()
+            .enumerate()
+            .map(|(index, entry)| super::kill_ring_actions::entry_suggestion(index, entry))
+            .collect();
+
+        self.prompt = Some(crate::view::prompt::Prompt::with_suggestions(
+            "Paste from history: ".to_string(),
+            PromptType::PasteFromHistory,
+            suggestions,
+        ));
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.selected_suggestion = Some(0);
+        }
+    }
+
+    /// Paste clipboard history entry `index` (0 is newest) at every cursor
+    ///
+    /// The entry also becomes the clipboard content again.
+    pub fn paste_from_history(&mut self, index: usize) {
+        let Some(entry) = self.clipboard_history.get(index).cloned() else {
+            return;
+        };
+        self.clipboard.copy(entry.text());
+        self.clipboard_history.push(entry.clone());
+        self.insert_entry(&entry, "Paste");
+        self.status_message = Some("Pasted".to_string());
+    }
+
     /// Paste text directly into the editor
     ///
     /// Handles:
@@ -303,13 +363,7 @@ impl Editor {
             return;
         }
 
-        // Convert to buffer's line ending format
-        let buffer_line_ending = self.active_state().buffer.line_ending();
-        let paste_text = match buffer_line_ending {
-            crate::model::buffer::LineEnding::LF => normalized,
-            crate::model::buffer::LineEnding::CRLF => normalized.replace('\n', "\r\n"),
-            crate::model::buffer::LineEnding::CR => normalized.replace('\n', "\r"),
-        };
+        let paste_text = self.to_buffer_line_endings(&normalized);
 
         let mut events = Vec::new();
 
@@ -375,6 +429,15 @@ impl Editor {
         self.status_message = Some("Pasted".to_string());
     }
 
+    /// Convert LF line endings to the active buffer's line ending format
+    pub(super) fn to_buffer_line_endings(&self, text: &str) -> String {
+        match self.active_state().buffer.line_ending() {
+            crate::model::buffer::LineEnding::LF => text.to_string(),
+            crate::model::buffer::LineEnding::CRLF => text.replace('\n', "\r\n"),
+            crate::model::buffer::LineEnding::CR => text.replace('\n', "\r"),
+        }
+    }
+
     /// Set clipboard content for testing purposes
     /// This sets the internal clipboard and enables internal-only mode to avoid
     /// system clipboard interference between parallel tests
@@ -407,8 +470,10 @@ impl Editor {
                 cursor,
                 total_cursors,
             } => {
+                self.remember_cursors();
+
                 // Create AddCursor event with the next cursor ID
-                let next_id = CursorId(self.active_state().cursors.count());
+                let next_id = self.active_state().cursors.next_id();
                 let event = Event::AddCursor {
                     cursor_id: next_id,
                     position: cursor.position,
@@ -435,8 +500,10 @@ impl Editor {
                 cursor,
                 total_cursors,
             } => {
+                self.remember_cursors();
+
                 // Create AddCursor event with the next cursor ID
-                let next_id = CursorId(self.active_state().cursors.count());
+                let next_id = self.active_state().cursors.next_id();
                 let event = Event::AddCursor {
                     cursor_id: next_id,
                     position: cursor.position,
@@ -463,8 +530,10 @@ impl Editor {
                 cursor,
                 total_cursors,
             } => {
+                self.remember_cursors();
+
                 // Create AddCursor event with the next cursor ID
-                let next_id = CursorId(self.active_state().cursors.count());
+                let next_id = self.active_state().cursors.next_id();
                 let event = Event::AddCursor {
                     cursor_id: next_id,
                     position: cursor.position,
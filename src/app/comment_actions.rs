@@ -0,0 +1,198 @@
+//! Comment actions for the Editor: toggle line comments on the lines of each
+//! cursor, or a block comment around each selection.
+//!
+//! The comment syntax is that of the language at the cursor, so a `<script>`
+//! in an HTML file gets JavaScript comments. It comes from the language
+//! config when set there, falling back to built-in defaults.
+
+use std::ops::Range;
+use std::path::Path;
+
+use crate::model::buffer::Buffer;
+use crate::model::event::CursorId;
+use crate::primitives::comment::{self, CommentToggle, CommentTokens};
+use crate::primitives::highlighter::{injected_language_at, Language};
+use crate::services::lsp::manager::detect_language;
+
+use super::transform_actions::Replacement;
+use super::Editor;
+
+/// Whole lines touched by `range`; a range ending at the start of a line
+/// doesn't include that line
+fn whole_lines(buffer: &Buffer, range: Range<usize>) -> Range<usize> {
+    let first = buffer.line_of(range.start);
+    let mut last = buffer.line_of(range.end);
+    if last > first && range.end == buffer.line_start(last) {
+        last -= 1;
+    }
+    buffer.line_start(first)..buffer.line_end(last)
+}
+
+/// `range` without leading and trailing whitespace
+fn trimmed(buffer: &Buffer, range: Range<usize>) -> Range<usize> {
+    let bytes = buffer.slice_bytes(range.clone());
+    let leading = bytes.iter().take_while(|b| b.is_ascii_whitespace()).count();
+    let trailing = bytes[leading..]
+        .iter()
+        .rev()
+        .take_while(|b| b.is_ascii_whitespace())
+        .count();
+    range.start + leading..range.end - trailing
+}
+
+impl Editor {
+    /// Comment or uncomment the lines of each cursor's selection, or the
+    /// cursor's line
+    pub(super) fn toggle_comment(&mut self) {
+        self.toggle_comments(false);
+    }
+
+    /// Wrap each selection (or the cursor's line) in a block comment, or
+    /// unwrap the one it's in
+    ///
+    /// Languages without block comments get line comments instead.
+    pub(super) fn toggle_block_comment(&mut self) {
+        self.toggle_comments(true);
+    }
+
+    fn toggle_comments(&mut self, block: bool) {
+        let state = self.active_state();
+        let mut targets: Vec<(CursorId, Range<usize>)> = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| {
+                let range = cursor
+                    .selection_range()
+                    .unwrap_or(cursor.position..cursor.position);
+                (cursor_id, range)
+            })
+            .collect();
+        targets.sort_by_key(|(_, range)| range.start);
+
+        let mut replacements = Vec::new();
+        let mut first_toggle: Option<(bool, bool)> = None;
+        let mut line_count = 0;
+        let mut done_until = None;
+        for (cursor_id, range) in targets {
+            let buffer = &self.active_state().buffer;
+            let lines = whole_lines(buffer, range.clone());
+            let region = if block && range.is_empty() {
+                trimmed(buffer, lines.clone())
+            } else if block {
+                range
+            } else {
+                lines.clone()
+            };
+            let first_char = trimmed(buffer, region.clone()).start;
+            let tokens = self.comment_tokens_at(first_char);
+            let (region, as_block) = if block && tokens.block.is_some() {
+                (region, true)
+            } else {
+                (lines, false)
+            };
+            // Cursors sharing lines are toggled once
+            if done_until.is_some_and(|end| region.start <= end) {
+                continue;
+            }
+            done_until = Some(region.end);
+
+            let text = self
+                .active_state_mut()
+                .get_text_range(region.start, region.end);
+            let toggle = if as_block {
+                comment::toggle_block_comment(&text, &tokens)
+            } else {
+                comment::toggle_line_comments(&text, &tokens)
+            };
+            let Some(CommentToggle { edits, commented }) = toggle else {
+                continue;
+            };
+            first_toggle.get_or_insert((as_block, commented));
+            line_count += text.lines().count();
+            replacements.extend(edits.into_iter().map(|edit| Replacement {
+                cursor_id,
+                range: region.start + edit.range.start..region.start + edit.range.end,
+                text: edit.text,
+            }));
+        }
+
+        let Some((as_block, commented)) = first_toggle else {
+            self.set_status_message("Nothing to comment".to_string());
+            return;
+        };
+        let action = if commented { "Comment" } else { "Uncomment" };
+        if as_block {
+            self.apply_replacements(replacements, &format!("{} block", action), false);
+            self.set_status_message(format!("{}ed block", action));
+        } else {
+            self.apply_replacements(replacements, &format!("{} lines", action), false);
+            self.set_status_message(format!("{}ed {} line(s)", action, line_count));
+        }
+    }
+
+    /// Comment syntax of the language at `pos` in the active buffer
+    ///
+    /// Code injected into another language (a `<script>` in HTML, say) uses
+    /// the injected language's comments. Markdown injected into doc comments
+    /// is left to the host language.
+    pub(super) fn comment_tokens_at(&mut self, pos: usize) -> CommentTokens {
+        let state = self.active_state_mut();
+        let path = state.buffer.file_path().map(Path::to_path_buf);
+        state.update_syntax_tree();
+        let injected = state
+            .syntax_tree
+            .as_ref()
+            .and_then(|syntax_tree| {
+                let tree = syntax_tree.tree()?;
+                injected_language_at(syntax_tree.language(), &state.buffer, tree, pos)
+            })
+            .filter(|name| !matches!(name.as_str(), "markdown" | "markdown_inline" | "md"));
+        if let Some(tokens) = injected.and_then(|name| self.comment_tokens_for(&name)) {
+            return tokens;
+        }
+
+        let path = path.as_deref();
+        path.and_then(|path| detect_language(path, &self.config.languages))
+            .and_then(|name| self.comment_tokens_for(&name))
+            .or_else(|| {
+                let path = path?;
+                let name = Language::from_path(path)
+                    .map(|language| language.name().to_string())
+                    .or_else(|| Some(path.extension()?.to_str()?.to_string()))?;
+                self.comment_tokens_for(&name)
+            })
+            .unwrap_or_else(|| CommentTokens {
+                line: Some("//".to_string()),
+                block: Some(("/*".to_string(), "*/".to_string())),
+            })
+    }
+
+    /// Comment syntax of language `name` (a config key, grammar name or file
+    /// extension), as configured or else built in
+    fn comment_tokens_for(&self, name: &str) -> Option<CommentTokens> {
+        let configured = self
+            .config
+            .languages
+            .get(name)
+            .or_else(|| {
+                self.config
+                    .languages
+                    .values()
+                    .find(|config| config.grammar == name)
+            })
+            .filter(|config| config.comment_prefix.is_some() || config.block_comment.is_some());
+        match configured {
+            Some(config) => Some(CommentTokens {
+                line: config
+                    .comment_prefix
+                    .as_ref()
+                    .map(|prefix| prefix.trim().to_string()),
+                block: config
+                    .block_comment
+                    .as_ref()
+                    .map(|block| (block.start.clone(), block.end.clone())),
+            }),
+            None => CommentTokens::builtin(name),
+        }
+    }
+}
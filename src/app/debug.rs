@@ -0,0 +1,1108 @@
+//! Debugger (DAP) integration.
+//!
+//! Breakpoint editing, session lifecycle, stepping and the debug panels.
+//! Protocol handling lives in `services::dap`; this module turns adapter
+//! responses and events into editor state: margin indicators, the
+//! current-line overlay and the content of the panel buffers.
+
+use super::{BufferId, Editor};
+use crate::model::event::SplitDirection;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::dap::launch::LaunchContext;
+use crate::services::dap::protocol::{EventMessage, OutputEventBody, Response, StoppedEventBody};
+use crate::services::dap::session::{
+    render_console_lines, render_watch_lines, EvaluateTarget, PendingRequest,
+};
+use crate::services::dap::{Breakpoint, DapHandle, DebugRequestKind, DebugSession, DebugState};
+use crate::view::margin::LineIndicator;
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+use crate::view::prompt::PromptType;
+use crate::view::split::SplitViewState;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Margin indicator namespace for breakpoints
+const BREAKPOINT_NAMESPACE: &str = "breakpoints";
+
+/// Overlay namespace for the line the debuggee is paused on
+const DEBUG_LINE_NAMESPACE: &str = "debug-line";
+
+/// Panel ids and buffer names of the debug panels, in tab order
+const DEBUG_PANELS: [(&str, &str); 4] = [
+    ("debug:call-stack", "*Debug Call Stack*"),
+    ("debug:variables", "*Debug Variables*"),
+    ("debug:watch", "*Debug Watch*"),
+    ("debug:console", "*Debug Console*"),
+];
+
+/// Maximum number of stack frames requested per stop
+const MAX_STACK_FRAMES: usize = 64;
+
+impl Editor {
+    // ==================== Breakpoints ====================
+
+    /// Toggle a breakpoint on the cursor line
+    pub fn toggle_breakpoint(&mut self) {
+        let Some((path, line)) = self.breakpoint_target() else {
+            return;
+        };
+        let added = self.breakpoints.toggle(&path, line);
+        self.breakpoints_changed(&path);
+        self.set_status_message(if added {
+            format!("Breakpoint set at line {}", line + 1)
+        } else {
+            format!("Breakpoint removed from line {}", line + 1)
+        });
+    }
+
+    /// Prompt for the condition of the breakpoint on the cursor line
+    pub fn prompt_conditional_breakpoint(&mut self) {
+        let Some((path, line)) = self.breakpoint_target() else {
+            return;
+        };
+        let current = self
+            .breakpoints
+            .get(&path, line)
+            .and_then(|bp| bp.condition.clone())
+            .unwrap_or_default();
+        self.start_prompt_with_initial_text(
+            format!("Breakpoint condition (line {}): ", line + 1),
+            PromptType::SetBreakpointCondition { path, line },
+            current,
+        );
+    }
+
+    /// Prompt for the message of a logpoint on the cursor line
+    pub fn prompt_logpoint(&mut self) {
+        let Some((path, line)) = self.breakpoint_target() else {
+            return;
+        };
+        let current = self
+            .breakpoints
+            .get(&path, line)
+            .and_then(|bp| bp.log_message.clone())
+            .unwrap_or_default();
+        self.start_prompt_with_initial_text(
+            format!("Log message (line {}, {{expr}} interpolates): ", line + 1),
+            PromptType::SetLogpointMessage { path, line },
+            current,
+        );
+    }
+
+    /// Set (or clear, for empty input) the condition of a breakpoint
+    pub(super) fn set_breakpoint_condition(&mut self, path: &Path, line: usize, input: &str) {
+        let condition = input.trim();
+        let mut breakpoint = self
+            .breakpoints
+            .get(path, line)
+            .cloned()
+            .unwrap_or_else(|| Breakpoint::new(line));
+        breakpoint.condition = (!condition.is_empty()).then(|| condition.to_string());
+        self.breakpoints.set(path, breakpoint);
+        self.breakpoints_changed(path);
+        self.set_status_message(if condition.is_empty() {
+            format!("Breakpoint at line {} is unconditional", line + 1)
+        } else {
+            format!("Conditional breakpoint set at line {}", line + 1)
+        });
+    }
+
+    /// Turn a breakpoint into a logpoint (or back, for empty input)
+    pub(super) fn set_logpoint_message(&mut self, path: &Path, line: usize, input: &str) {
+        let message = input.trim();
+        let mut breakpoint = self
+            .breakpoints
+            .get(path, line)
+            .cloned()
+            .unwrap_or_else(|| Breakpoint::new(line));
+        breakpoint.log_message = (!message.is_empty()).then(|| message.to_string());
+        self.breakpoints.set(path, breakpoint);
+        self.breakpoints_changed(path);
+        self.set_status_message(if message.is_empty() {
+            format!("Breakpoint at line {} no longer logs", line + 1)
+        } else {
+            format!("Logpoint set at line {}", line + 1)
+        });
+    }
+
+    /// Remove every breakpoint in every file
+    pub fn clear_all_breakpoints(&mut self) {
+        let count = self.breakpoints.len();
+        for path in self.breakpoints.clear() {
+            self.breakpoints_changed(&path);
+        }
+        self.set_status_message(format!("Cleared {} breakpoint(s)", count));
+    }
+
+    /// File and 0-indexed line of the cursor, with the file's breakpoints
+    /// brought up to date with edits
+    fn breakpoint_target(&mut self) -> Option<(PathBuf, usize)> {
+        let buffer_id = self.active_buffer();
+        let Some(path) = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.file_path())
+            .map(|p| p.to_path_buf())
+        else {
+            self.set_status_message("Breakpoints require a file-backed buffer".to_string());
+            return None;
+        };
+        self.sync_breakpoint_lines(buffer_id);
+        let state = self.active_state();
+        let line = state
+            .buffer
+            .get_line_number(state.cursors.primary().position);
+        Some((path, line))
+    }
+
+    /// Redraw a file's indicators and tell a running adapter about the change
+    fn breakpoints_changed(&mut self, path: &Path) {
+        if let Some(buffer_id) = self.buffer_for_path(path) {
+            self.refresh_breakpoint_indicators(buffer_id);
+        }
+        if self.debug_session_accepts_breakpoints() {
+            self.send_breakpoints(path);
+        }
+    }
+
+    /// Find the open buffer for a file
+    pub(super) fn buffer_for_path(&self, path: &Path) -> Option<BufferId> {
+        self.buffers
+            .iter()
+            .find(|(_, state)| state.buffer.file_path() == Some(path))
+            .map(|(id, _)| *id)
+    }
+
+    /// Update stored breakpoint lines from the margin markers of a buffer
+    ///
+    /// Indicators are anchored to markers, so they follow insertions and
+    /// deletions; the store only learns the new lines when asked.
+    pub(crate) fn sync_breakpoint_lines(&mut self, buffer_id: BufferId) {
+        let Some(state) = self.buffers.get(&buffer_id) else {
+            return;
+        };
+        let Some(path) = state.buffer.file_path() else {
+            return;
+        };
+        let lines: Vec<usize> = state
+            .margins
+            .line_indicator_positions(BREAKPOINT_NAMESPACE)
+            .into_iter()
+            .map(|pos| state.buffer.get_line_number(pos))
+            .collect();
+        let path = path.to_path_buf();
+        self.breakpoints.relocate(&path, &lines);
+    }
+
+    /// Sync the breakpoint lines of every open buffer (before persisting them)
+    pub(crate) fn sync_all_breakpoint_lines(&mut self) {
+        let buffer_ids: Vec<BufferId> = self.buffers.keys().copied().collect();
+        for buffer_id in buffer_ids {
+            self.sync_breakpoint_lines(buffer_id);
+        }
+    }
+
+    /// Recreate the breakpoint indicators of a buffer from the store
+    pub(crate) fn refresh_breakpoint_indicators(&mut self, buffer_id: BufferId) {
+        let Some(path) = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.file_path())
+            .map(|p| p.to_path_buf())
+        else {
+            return;
+        };
+        let breakpoints = self.breakpoints.for_file(&path).to_vec();
+        // Unverified breakpoints are dimmed while a session is running
+        let session_running = self.debug_session_accepts_breakpoints();
+        let verified_color = self.theme.diagnostic_error_fg;
+        let unverified_color = self.theme.line_number_fg;
+
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        state
+            .margins
+            .clear_line_indicators_for_namespace(BREAKPOINT_NAMESPACE);
+        for breakpoint in breakpoints {
+            // Breakpoints past the end of the file (e.g. restored from a
+            // session after the file shrank) stick to the last line
+            let byte_offset = state
+                .buffer
+                .line_start_offset(breakpoint.line)
+                .unwrap_or_else(|| state.buffer.len());
+            let color = if !session_running || breakpoint.verified {
+                verified_color
+            } else {
+                unverified_color
+            };
+            state.margins.set_line_indicator(
+                byte_offset,
+                BREAKPOINT_NAMESPACE.to_string(),
+                LineIndicator::new(breakpoint.symbol(), color, 20),
+            );
+        }
+    }
+
+    pub(crate) fn refresh_all_breakpoint_indicators(&mut self) {
+        let buffer_ids: Vec<BufferId> = self.buffers.keys().copied().collect();
+        for buffer_id in buffer_ids {
+            self.refresh_breakpoint_indicators(buffer_id);
+        }
+    }
+
+    /// Send `setBreakpoints` for one file (an empty list clears it)
+    fn send_breakpoints(&mut self, path: &Path) {
+        if let Some(buffer_id) = self.buffer_for_path(path) {
+            self.sync_breakpoint_lines(buffer_id);
+        }
+        let source_breakpoints = self.breakpoints.source_breakpoints(path);
+        let arguments = json!({
+            "source": {
+                "name": path.file_name().map(|n| n.to_string_lossy().to_string()),
+                "path": path.to_string_lossy(),
+            },
+            "breakpoints": source_breakpoints,
+            "lines": source_breakpoints.iter().map(|bp| bp.line).collect::<Vec<_>>(),
+        });
+        self.send_debug_request(
+            "setBreakpoints",
+            Some(arguments),
+            PendingRequest::SetBreakpoints {
+                path: path.to_path_buf(),
+            },
+        );
+    }
+
+    /// Whether breakpoint changes should be sent to the adapter now
+    ///
+    /// Before the `initialized` event the adapter receives all breakpoints at
+    /// once, so changes made while starting need not be sent separately.
+    fn debug_session_accepts_breakpoints(&self) -> bool {
+        self.debug_adapter.is_some()
+            && self
+                .debug_session
+                .as_ref()
+                .is_some_and(|s| !matches!(s.state, DebugState::Starting | DebugState::Terminated))
+    }
+
+    // ==================== Session lifecycle ====================
+
+    /// Start debugging the active file with the adapter configured for its language
+    pub fn start_debugging(&mut self) {
+        if self.debug_session_active() {
+            self.set_status_message("A debug session is already running".to_string());
+            return;
+        }
+
+        let file = self
+            .buffer_metadata
+            .get(&self.active_buffer())
+            .and_then(|m| m.file_path())
+            .cloned();
+        let Some(language) = file.as_ref().and_then(|path| {
+            crate::services::lsp::manager::detect_language(path, &self.config.languages)
+        }) else {
+            self.set_status_message("No debugger configured for this file type".to_string());
+            return;
+        };
+        let Some(config) = self
+            .config
+            .debuggers
+            .get(&language)
+            .filter(|c| c.enabled)
+            .cloned()
+        else {
+            self.set_status_message(format!("No debugger configured for {}", language));
+            return;
+        };
+        let (Some(runtime), Some(bridge)) =
+            (self.tokio_runtime.as_ref(), self.async_bridge.as_ref())
+        else {
+            self.set_status_message("Async runtime not available".to_string());
+            return;
+        };
+
+        let adapter = DapHandle::spawn(runtime.handle(), &config, bridge);
+        let context = LaunchContext {
+            file: file.as_deref(),
+            workspace_folder: &self.working_dir,
+        };
+        let request = match config.request {
+            DebugRequestKind::Launch => "launch",
+            DebugRequestKind::Attach => "attach",
+        };
+        let mut session = DebugSession::new(language.clone());
+        session.launch = Some((request.to_string(), context.expand(&config.configuration)));
+
+        self.sync_all_breakpoint_lines();
+        self.breakpoints.reset_verified();
+        self.clear_debug_line();
+        self.debug_adapter = Some(adapter);
+        self.debug_session = Some(session);
+        self.debug_source_split = Some(self.split_manager.active_split());
+
+        let adapter_id = config
+            .adapter_id
+            .clone()
+            .unwrap_or_else(|| language.clone());
+        self.send_debug_request(
+            "initialize",
+            Some(json!({
+                "clientID": "fresh",
+                "clientName": "Fresh",
+                "adapterID": adapter_id,
+                "linesStartAt1": true,
+                "columnsStartAt1": true,
+                "pathFormat": "path",
+                "supportsVariableType": true,
+            })),
+            PendingRequest::Initialize,
+        );
+
+        self.open_debug_panels(false);
+        self.set_status_message(format!("Debug: starting {} ({})", config.command, language));
+    }
+
+    /// End the debug session, terminating the debuggee
+    pub fn stop_debugging(&mut self) {
+        if !self.debug_session_active() {
+            self.set_status_message("No debug session running".to_string());
+            return;
+        }
+        self.send_debug_request(
+            "disconnect",
+            Some(json!({ "terminateDebuggee": true })),
+            PendingRequest::Disconnect,
+        );
+        self.end_debug_session();
+        self.set_status_message("Debug session stopped".to_string());
+    }
+
+    /// Continue the paused debuggee, or start a session if none is running
+    pub fn debug_continue(&mut self) {
+        if self.debug_session_active() {
+            self.debug_execution_request("continue");
+        } else {
+            self.start_debugging();
+        }
+    }
+
+    /// Send `continue`, `next`, `stepIn`, `stepOut` or `pause`
+    pub fn debug_execution_request(&mut self, command: &str) {
+        if !self.debug_session_active() {
+            self.set_status_message("No debug session running".to_string());
+            return;
+        }
+        let Some((stopped, thread_id)) = self
+            .debug_session
+            .as_ref()
+            .map(|s| (s.is_stopped(), s.thread_id()))
+        else {
+            return;
+        };
+        let is_pause = command == "pause";
+        if is_pause && stopped {
+            self.set_status_message("Debuggee is already paused".to_string());
+            return;
+        }
+        if !is_pause && !stopped {
+            self.set_status_message("Debuggee is not paused".to_string());
+            return;
+        }
+        match thread_id {
+            Some(thread_id) => {
+                self.send_debug_request(
+                    command,
+                    Some(json!({ "threadId": thread_id })),
+                    PendingRequest::Execution,
+                );
+            }
+            // No thread known yet; pause the first one once `threads` answers
+            None if is_pause => {
+                self.send_debug_request("threads", None, PendingRequest::Threads);
+            }
+            None => {
+                self.set_status_message("Debuggee has no stopped thread".to_string());
+            }
+        }
+    }
+
+    fn debug_session_active(&self) -> bool {
+        self.debug_adapter.is_some()
+            && self
+                .debug_session
+                .as_ref()
+                .is_some_and(|s| s.state != DebugState::Terminated)
+    }
+
+    /// Send a request to the adapter and remember what it was for
+    fn send_debug_request(
+        &mut self,
+        command: &str,
+        arguments: Option<Value>,
+        pending: PendingRequest,
+    ) {
+        let Some(adapter) = self.debug_adapter.as_ref() else {
+            return;
+        };
+        match adapter.send_request(command, arguments) {
+            Ok(seq) => {
+                if let Some(session) = self.debug_session.as_mut() {
+                    session.track(seq, pending);
+                }
+            }
+            Err(e) => self.set_status_message(e),
+        }
+    }
+
+    /// Mark the session terminated and drop all paused-state UI
+    ///
+    /// The session object is kept so the console stays readable.
+    fn end_debug_session(&mut self) {
+        if let Some(session) = self.debug_session.as_mut() {
+            session.on_continued();
+            session.state = DebugState::Terminated;
+        }
+        self.breakpoints.reset_verified();
+        self.refresh_all_breakpoint_indicators();
+        self.clear_debug_line();
+        self.refresh_debug_panels();
+    }
+
+    // ==================== Adapter messages ====================
+
+    /// Handle a response from the debug adapter
+    pub(super) fn handle_dap_response(&mut self, session_id: u64, response: Response) {
+        if self.debug_adapter.as_ref().map(|a| a.id()) != Some(session_id) {
+            return;
+        }
+        let Some(pending) = self
+            .debug_session
+            .as_mut()
+            .and_then(|s| s.take_pending(response.request_seq))
+        else {
+            return;
+        };
+        let body = response.body.clone().unwrap_or(Value::Null);
+        let error = response
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("{} failed", response.command));
+
+        match pending {
+            PendingRequest::Initialize => {
+                if !response.success {
+                    self.set_status_message(format!(
+                        "Debug adapter failed to initialize: {}",
+                        error
+                    ));
+                    self.debug_adapter = None;
+                    self.end_debug_session();
+                    return;
+                }
+                let launch = self.debug_session.as_mut().and_then(|session| {
+                    session.supports_configuration_done = body
+                        .get("supportsConfigurationDoneRequest")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    session.launch.take()
+                });
+                if let Some((command, arguments)) = launch {
+                    self.send_debug_request(
+                        &command,
+                        Some(arguments),
+                        PendingRequest::LaunchOrAttach,
+                    );
+                }
+            }
+            PendingRequest::LaunchOrAttach => {
+                if !response.success {
+                    self.debug_console_output("stderr", &error);
+                    self.set_status_message(format!("Debug launch failed: {}", error));
+                    self.send_debug_request("disconnect", None, PendingRequest::Disconnect);
+                    self.end_debug_session();
+                }
+            }
+            PendingRequest::ConfigurationDone => {}
+            PendingRequest::SetBreakpoints { path } => {
+                let verified: Vec<bool> = body
+                    .get("breakpoints")
+                    .and_then(|v| v.as_array())
+                    .map(|list| {
+                        list.iter()
+                            .map(|bp| {
+                                bp.get("verified")
+                                    .and_then(|v| v.as_bool())
+                                    .unwrap_or(false)
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                self.breakpoints.set_verified(&path, &verified);
+                if let Some(buffer_id) = self.buffer_for_path(&path) {
+                    self.refresh_breakpoint_indicators(buffer_id);
+                }
+            }
+            PendingRequest::Threads => {
+                let first_thread = body
+                    .get("threads")
+                    .and_then(|v| v.as_array())
+                    .and_then(|threads| threads.first())
+                    .and_then(|t| t.get("id"))
+                    .and_then(|v| v.as_i64());
+                let Some(thread_id) = first_thread else {
+                    return;
+                };
+                let stopped = match self.debug_session.as_mut() {
+                    Some(session) => {
+                        session.set_thread_id(thread_id);
+                        session.is_stopped()
+                    }
+                    None => return,
+                };
+                if stopped {
+                    self.request_stack_trace(thread_id);
+                } else {
+                    self.send_debug_request(
+                        "pause",
+                        Some(json!({ "threadId": thread_id })),
+                        PendingRequest::Execution,
+                    );
+                }
+            }
+            PendingRequest::StackTrace => {
+                if let Some(session) = self.debug_session.as_mut() {
+                    session.set_stack_trace(&body);
+                }
+                self.on_debug_frame_selected();
+            }
+            PendingRequest::Scopes => {
+                let references = self
+                    .debug_session
+                    .as_mut()
+                    .map(|s| s.set_scopes(&body))
+                    .unwrap_or_default();
+                for reference in references {
+                    self.request_variables(reference);
+                }
+                self.refresh_debug_panels();
+            }
+            PendingRequest::Variables { reference } => {
+                if let Some(session) = self.debug_session.as_mut() {
+                    session.set_variables(reference, &body);
+                }
+                self.refresh_debug_panels();
+            }
+            PendingRequest::Evaluate { target } => {
+                let result = if response.success {
+                    Ok(body
+                        .get("result")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string())
+                } else {
+                    Err(error)
+                };
+                match target {
+                    EvaluateTarget::Watch { expression } => {
+                        if let Some(session) = self.debug_session.as_mut() {
+                            session.watch_values.insert(expression, result);
+                        }
+                        self.refresh_debug_panels();
+                    }
+                    EvaluateTarget::Console => match result {
+                        Ok(value) => self.debug_console_output("result", &value),
+                        Err(error) => self.debug_console_output("stderr", &error),
+                    },
+                }
+            }
+            PendingRequest::Execution => {
+                if !response.success {
+                    self.set_status_message(format!("Debug: {}", error));
+                } else if response.command != "pause" {
+                    if let Some(session) = self.debug_session.as_mut() {
+                        session.on_continued();
+                    }
+                    self.clear_debug_line();
+                    self.refresh_debug_panels();
+                }
+            }
+            PendingRequest::Disconnect => {
+                // Dropping the handle kills the adapter
+                self.debug_adapter = None;
+            }
+        }
+    }
+
+    /// Handle an event from the debug adapter
+    pub(super) fn handle_dap_event(&mut self, session_id: u64, event: EventMessage) {
+        if self.debug_adapter.as_ref().map(|a| a.id()) != Some(session_id) {
+            return;
+        }
+        let body = event.body.unwrap_or(Value::Null);
+
+        match event.event.as_str() {
+            "initialized" => {
+                let files: Vec<PathBuf> = self.breakpoints.files().cloned().collect();
+                if let Some(session) = self.debug_session.as_mut() {
+                    session.state = DebugState::Running;
+                }
+                for path in files {
+                    self.send_breakpoints(&path);
+                }
+                if self
+                    .debug_session
+                    .as_ref()
+                    .is_some_and(|s| s.supports_configuration_done)
+                {
+                    self.send_debug_request(
+                        "configurationDone",
+                        None,
+                        PendingRequest::ConfigurationDone,
+                    );
+                }
+                self.refresh_all_breakpoint_indicators();
+                self.refresh_debug_panels();
+            }
+            "stopped" => {
+                let Ok(stopped) = serde_json::from_value::<StoppedEventBody>(body) else {
+                    return;
+                };
+                let thread_id = match self.debug_session.as_mut() {
+                    Some(session) => {
+                        session.on_stopped(stopped);
+                        session.thread_id()
+                    }
+                    None => return,
+                };
+                match thread_id {
+                    Some(thread_id) => self.request_stack_trace(thread_id),
+                    None => {
+                        self.send_debug_request("threads", None, PendingRequest::Threads);
+                    }
+                }
+                self.refresh_debug_panels();
+                self.update_debug_status();
+            }
+            "continued" => {
+                if let Some(session) = self.debug_session.as_mut() {
+                    session.on_continued();
+                }
+                self.clear_debug_line();
+                self.refresh_debug_panels();
+            }
+            "output" => {
+                if let Ok(output) = serde_json::from_value::<OutputEventBody>(body) {
+                    if let Some(session) = self.debug_session.as_mut() {
+                        session.append_output(output);
+                    }
+                    self.refresh_debug_panels();
+                }
+            }
+            "exited" => {
+                if let Some(code) = body.get("exitCode").and_then(|v| v.as_i64()) {
+                    self.debug_console_output(
+                        "console",
+                        &format!("Process exited with code {}", code),
+                    );
+                }
+            }
+            "terminated" => {
+                if self.debug_session_active() {
+                    self.send_debug_request("disconnect", None, PendingRequest::Disconnect);
+                    self.end_debug_session();
+                    self.set_status_message("Debug session ended".to_string());
+                }
+            }
+            other => {
+                tracing::debug!("Ignoring DAP event '{}'", other);
+            }
+        }
+    }
+
+    /// Handle the adapter failing or exiting
+    pub(super) fn handle_dap_error(&mut self, session_id: u64, error: String) {
+        if self.debug_adapter.as_ref().map(|a| a.id()) != Some(session_id) {
+            return;
+        }
+        let was_active = self.debug_session_active();
+        self.debug_adapter = None;
+        // The adapter closing its output after the session ended is expected
+        if was_active {
+            self.debug_console_output("stderr", &error);
+            self.end_debug_session();
+            self.set_status_message(format!("Debug adapter error: {}", error));
+        }
+    }
+
+    fn request_stack_trace(&mut self, thread_id: i64) {
+        self.send_debug_request(
+            "stackTrace",
+            Some(json!({
+                "threadId": thread_id,
+                "startFrame": 0,
+                "levels": MAX_STACK_FRAMES,
+            })),
+            PendingRequest::StackTrace,
+        );
+    }
+
+    fn request_variables(&mut self, reference: i64) {
+        self.send_debug_request(
+            "variables",
+            Some(json!({ "variablesReference": reference })),
+            PendingRequest::Variables { reference },
+        );
+    }
+
+    /// Show the selected frame and fetch its scopes and watch values
+    fn on_debug_frame_selected(&mut self) {
+        let frame_id = self
+            .debug_session
+            .as_ref()
+            .and_then(|s| s.current_frame())
+            .map(|f| f.id);
+        self.jump_to_debug_frame();
+        if let Some(frame_id) = frame_id {
+            self.send_debug_request(
+                "scopes",
+                Some(json!({ "frameId": frame_id })),
+                PendingRequest::Scopes,
+            );
+        }
+        self.evaluate_debug_watches();
+        self.refresh_debug_panels();
+        self.update_debug_status();
+    }
+
+    fn update_debug_status(&mut self) {
+        if let Some(text) = self.debug_session.as_ref().map(|s| s.status_text()) {
+            self.set_status_message(text);
+        }
+    }
+
+    /// Open the selected frame's source in the source split and highlight its line
+    fn jump_to_debug_frame(&mut self) {
+        let Some(frame) = self
+            .debug_session
+            .as_ref()
+            .and_then(|s| s.current_frame())
+            .cloned()
+        else {
+            return;
+        };
+        let Some(path) = frame.source.as_ref().and_then(|s| s.path.clone()) else {
+            self.clear_debug_line();
+            return;
+        };
+
+        self.focus_debug_source_split();
+        match self.open_file(Path::new(&path)) {
+            Ok(buffer_id) => {
+                let line = frame.line.max(1) as usize;
+                self.goto_line_col(line, Some(frame.column.max(1) as usize));
+                self.highlight_debug_line(buffer_id, line - 1);
+                self.debug_source_split = Some(self.split_manager.active_split());
+            }
+            Err(e) => {
+                self.set_status_message(format!("Cannot open {}: {}", path, e));
+            }
+        }
+    }
+
+    /// Make the split the session was started from active again
+    fn focus_debug_source_split(&mut self) {
+        let Some(split_id) = self.debug_source_split else {
+            return;
+        };
+        if split_id == self.split_manager.active_split() {
+            return;
+        }
+        // The split was closed; frames open in whichever split is active
+        if self.split_manager.buffer_for_split(split_id).is_none() {
+            self.debug_source_split = None;
+            return;
+        }
+        self.save_current_split_view_state();
+        self.split_manager.set_active_split(split_id);
+        self.restore_current_split_view_state();
+    }
+
+    /// Highlight the line the debuggee is paused on
+    fn highlight_debug_line(&mut self, buffer_id: BufferId, line: usize) {
+        self.clear_debug_line();
+        let color = self.theme.diagnostic_warning_bg;
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let len = state.buffer.len();
+        let start = state.buffer.line_start_offset(line).unwrap_or(len);
+        let end = state.buffer.line_start_offset(line + 1).unwrap_or(len);
+        let overlay = Overlay::with_namespace(
+            &mut state.marker_list,
+            start..end,
+            OverlayFace::Background { color },
+            OverlayNamespace::from_string(DEBUG_LINE_NAMESPACE.to_string()),
+        )
+        .with_priority_value(5);
+        state.overlays.add(overlay);
+        self.debug_line_buffer = Some(buffer_id);
+    }
+
+    fn clear_debug_line(&mut self) {
+        let Some(buffer_id) = self.debug_line_buffer.take() else {
+            return;
+        };
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.overlays.clear_namespace(
+                &OverlayNamespace::from_string(DEBUG_LINE_NAMESPACE.to_string()),
+                &mut state.marker_list,
+            );
+        }
+    }
+
+    // ==================== Watches and console ====================
+
+    /// Add a watch expression (evaluated whenever the debuggee stops)
+    pub(super) fn debug_add_watch(&mut self, input: &str) {
+        let expression = input.trim();
+        if expression.is_empty() {
+            return;
+        }
+        if !self.debug_watches.iter().any(|w| w == expression) {
+            self.debug_watches.push(expression.to_string());
+        }
+        if self.debug_session.as_ref().is_some_and(|s| s.is_stopped()) {
+            self.evaluate_debug_watch(expression.to_string());
+        }
+        self.refresh_debug_panels();
+        self.set_status_message(format!("Watching {}", expression));
+    }
+
+    /// Remove the watch expression under the cursor in the watch panel
+    pub fn debug_remove_watch_at_cursor(&mut self) {
+        let index = self.get_text_properties_at_cursor().and_then(|props| {
+            props
+                .iter()
+                .find_map(|p| p.get("watch").and_then(|v| v.as_u64()))
+        });
+        let Some(index) = index.map(|i| i as usize) else {
+            self.set_status_message("No watch expression at cursor".to_string());
+            return;
+        };
+        if index >= self.debug_watches.len() {
+            return;
+        }
+        let expression = self.debug_watches.remove(index);
+        if let Some(session) = self.debug_session.as_mut() {
+            session.watch_values.remove(&expression);
+        }
+        self.refresh_debug_panels();
+        self.set_status_message(format!("Removed watch {}", expression));
+    }
+
+    fn evaluate_debug_watches(&mut self) {
+        for expression in self.debug_watches.clone() {
+            self.evaluate_debug_watch(expression);
+        }
+    }
+
+    fn evaluate_debug_watch(&mut self, expression: String) {
+        let frame_id = self
+            .debug_session
+            .as_ref()
+            .and_then(|s| s.current_frame())
+            .map(|f| f.id);
+        self.send_debug_request(
+            "evaluate",
+            Some(json!({
+                "expression": expression,
+                "frameId": frame_id,
+                "context": "watch",
+            })),
+            PendingRequest::Evaluate {
+                target: EvaluateTarget::Watch { expression },
+            },
+        );
+    }
+
+    /// Evaluate an expression in the debug console
+    pub(super) fn debug_evaluate(&mut self, input: &str) {
+        let expression = input.trim();
+        if expression.is_empty() {
+            return;
+        }
+        if !self.debug_session_active() {
+            self.set_status_message("No debug session running".to_string());
+            return;
+        }
+        self.debug_console_output("input", expression);
+        let frame_id = self
+            .debug_session
+            .as_ref()
+            .and_then(|s| s.current_frame())
+            .map(|f| f.id);
+        self.send_debug_request(
+            "evaluate",
+            Some(json!({
+                "expression": expression,
+                "frameId": frame_id,
+                "context": "repl",
+            })),
+            PendingRequest::Evaluate {
+                target: EvaluateTarget::Console,
+            },
+        );
+    }
+
+    fn debug_console_output(&mut self, category: &str, text: &str) {
+        if let Some(session) = self.debug_session.as_mut() {
+            session.push_console(category, text);
+        }
+        self.refresh_debug_panels();
+    }
+
+    // ==================== Panels ====================
+
+    /// Show the debug panels and focus them
+    pub fn show_debug_panels(&mut self) {
+        self.open_debug_panels(true);
+    }
+
+    /// Open the debug panels below the source split, unless already visible
+    fn open_debug_panels(&mut self, focus: bool) {
+        let existing = DEBUG_PANELS
+            .iter()
+            .filter_map(|(id, _)| self.panel_ids.get(*id).copied())
+            .filter(|buffer_id| self.buffers.contains_key(buffer_id))
+            .find_map(|buffer_id| {
+                self.split_manager
+                    .splits_for_buffer(buffer_id)
+                    .first()
+                    .map(|&split_id| (split_id, buffer_id))
+            });
+        if let Some((split_id, buffer_id)) = existing {
+            self.refresh_debug_panels();
+            if focus {
+                self.focus_split(split_id, buffer_id);
+            }
+            return;
+        }
+
+        let source_split = self.split_manager.active_split();
+        let mut panel_buffers = Vec::new();
+        for (panel_id, name) in DEBUG_PANELS {
+            let buffer_id = match self.panel_ids.get(panel_id) {
+                Some(&id) if self.buffers.contains_key(&id) => id,
+                _ => {
+                    let id = self.create_virtual_buffer(
+                        name.to_string(),
+                        "debug-panel".to_string(),
+                        true,
+                    );
+                    if let Some(state) = self.buffers.get_mut(&id) {
+                        state.margins.set_line_numbers(false);
+                        state.editing_disabled = true;
+                    }
+                    self.panel_ids.insert(panel_id.to_string(), id);
+                    id
+                }
+            };
+            // create_virtual_buffer opened it as a tab of the source split
+            if let Some(view_state) = self.split_view_states.get_mut(&source_split) {
+                view_state.remove_buffer(buffer_id);
+            }
+            panel_buffers.push(buffer_id);
+        }
+        self.refresh_debug_panels();
+
+        self.save_current_split_view_state();
+        let first = panel_buffers[0];
+        match self
+            .split_manager
+            .split_active(SplitDirection::Horizontal, first, 0.7)
+        {
+            Ok(panel_split) => {
+                let mut view_state =
+                    SplitViewState::with_buffer(self.terminal_width, self.terminal_height, first);
+                view_state.viewport.line_wrap_enabled = self.config.editor.line_wrap;
+                for &buffer_id in &panel_buffers[1..] {
+                    view_state.add_buffer(buffer_id);
+                }
+                self.split_view_states.insert(panel_split, view_state);
+                // split_active focused the new split
+                if !focus {
+                    self.split_manager.set_active_split(source_split);
+                    self.restore_current_split_view_state();
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to create debug panel split: {}", e);
+                if focus {
+                    self.set_active_buffer(first);
+                }
+            }
+        }
+    }
+
+    /// Re-render the content of every open debug panel
+    pub(crate) fn refresh_debug_panels(&mut self) {
+        let session = self.debug_session.as_ref();
+        let not_debugging = || vec![TextPropertyEntry::text("(not debugging)\n")];
+        let contents = [
+            session
+                .map(|s| s.render_call_stack())
+                .unwrap_or_else(not_debugging),
+            session
+                .map(|s| s.render_variables())
+                .unwrap_or_else(not_debugging),
+            render_watch_lines(&self.debug_watches, session),
+            render_console_lines(session.map(|s| s.console.as_slice()).unwrap_or(&[])),
+        ];
+        for ((panel_id, _), entries) in DEBUG_PANELS.into_iter().zip(contents) {
+            let Some(&buffer_id) = self.panel_ids.get(panel_id) else {
+                continue;
+            };
+            if self.buffers.contains_key(&buffer_id) {
+                if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+                    tracing::error!("Failed to update debug panel '{}': {}", panel_id, e);
+                }
+            }
+        }
+    }
+
+    /// Enter in a debug panel: select a stack frame or expand a variable
+    pub fn debug_panel_activate(&mut self) {
+        let (frame, reference) = match self.get_text_properties_at_cursor() {
+            Some(props) => (
+                props
+                    .iter()
+                    .find_map(|p| p.get("frame").and_then(|v| v.as_u64())),
+                props
+                    .iter()
+                    .find_map(|p| p.get("variables_reference").and_then(|v| v.as_i64())),
+            ),
+            None => return,
+        };
+
+        if let Some(index) = frame {
+            let selected = self
+                .debug_session
+                .as_mut()
+                .is_some_and(|s| s.select_frame(index as usize));
+            if selected {
+                self.on_debug_frame_selected();
+            }
+        } else if let Some(reference) = reference {
+            let fetch = self
+                .debug_session
+                .as_mut()
+                .is_some_and(|s| s.toggle_expanded(reference));
+            if fetch {
+                self.request_variables(reference);
+            }
+            self.refresh_debug_panels();
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use super::*;
+use crate::primitives::jump_hints::HintTargets;
+use crate::primitives::word_navigation::is_word_char;
 use crate::services::plugins::hooks::HookArgs;
 impl Editor {
     /// Determine the current keybinding context based on UI state
@@ -73,11 +75,44 @@ impl Editor {
             }
         }
 
+        // Completion that opens while typing lets typing go on to the buffer
+        if matches!(context, crate::input::keybindings::KeyContext::Popup) {
+            let is_completion_popup = self
+                .active_state()
+                .popups
+                .top()
+                .is_some_and(|p| p.title.as_deref() == Some("Completion"));
+            let is_typing = match code {
+                KeyCode::Char(_) => modifiers.difference(KeyModifiers::SHIFT).is_empty(),
+                KeyCode::Backspace => modifiers.is_empty(),
+                _ => false,
+            };
+            if is_completion_popup && is_typing && self.completes_while_typing() {
+                self.hide_popup();
+                context = self.get_key_context();
+            }
+        }
+
         // Try hierarchical modal input dispatch first (Settings, Menu, Prompt, Popup)
         if self.dispatch_modal_input(&key_event).is_some() {
             return Ok(());
         }
 
+        // Tab and Shift+Tab move between the fields of an inserted snippet
+        if matches!(context, crate::input::keybindings::KeyContext::Normal) {
+            if let Some(result) = self.handle_snippet_key(key_event) {
+                return result;
+            }
+        }
+
+        // Vi modal editing parses keys before the keymap sees them
+        if matches!(context, crate::input::keybindings::KeyContext::Normal) {
+            if let Some(result) = self.handle_vi_key(key_event) {
+                self.update_snippet_session();
+                return result;
+            }
+        }
+
         // Only check buffer mode keybindings if we're not in a higher-priority context
         // (Menu, Prompt, Popup should take precedence over mode bindings)
         let should_check_mode_bindings = matches!(
@@ -149,7 +184,9 @@ impl Editor {
         // Note: Modal components (Settings, Menu, Prompt, Popup, File Browser) are now
         // handled by dispatch_modal_input using the InputHandler system.
         // All remaining actions delegate to handle_action.
-        self.handle_action(action)
+        let result = self.handle_action(action);
+        self.update_snippet_session();
+        result
     }
 
     /// Handle an action (for normal mode and command execution)
@@ -159,11 +196,23 @@ impl Editor {
         // Record action to macro if recording
         self.record_macro_action(&action);
 
+        // Anything but a kill or yank-pop ends the kill ring's kill/yank chains
+        self.start_kill_ring_command(&action);
+
         match action {
             Action::Quit => self.quit(),
             Action::Save => {
-                // Check if buffer has a file path - if not, redirect to SaveAs
-                if self.active_state().buffer.file_path().is_none() {
+                // Saving a macro buffer updates the macro instead of writing a file
+                if let Some(&key) = self.macro_buffers.get(&self.active_buffer()) {
+                    self.save_macro_buffer(key);
+                } else if self.is_project_results_buffer(self.active_buffer()) {
+                    // Saving project search results writes edited lines to their files
+                    self.save_project_results(self.active_buffer());
+                } else if self.occur_views.contains_key(&self.active_buffer()) {
+                    // Saving an occur buffer writes edited lines to its source
+                    self.save_occur_edits(self.active_buffer());
+                } else if self.active_state().buffer.file_path().is_none() {
+                    // No file path - redirect to SaveAs
                     self.start_prompt_with_initial_text(
                         "Save as: ".to_string(),
                         PromptType::SaveFileAs,
@@ -209,6 +258,10 @@ impl Editor {
                 self.init_folder_open_state();
             }
             Action::GotoLine => self.start_prompt("Go to line: ".to_string(), PromptType::GotoLine),
+            Action::JumpToChar => self.start_jump_hint_prompt(HintTargets::Chars(1), false),
+            Action::JumpToTwoChars => self.start_jump_hint_prompt(HintTargets::Chars(2), false),
+            Action::JumpToWord => self.start_jump_hint_prompt(HintTargets::WordStarts, false),
+            Action::JumpToLine => self.start_jump_hint_prompt(HintTargets::LineStarts, false),
             Action::New => {
                 self.new_buffer();
             }
@@ -263,10 +316,159 @@ impl Editor {
                 }
                 self.paste()
             }
+            Action::PasteFromHistory => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.start_paste_from_history_prompt()
+            }
+            Action::KillLine | Action::KillWordForward | Action::KillWordBackward => {
+                use crate::input::kill_ring::KillDirection;
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                let (delete, direction) = match action {
+                    Action::KillLine => (Action::DeleteToLineEnd, KillDirection::Forward),
+                    Action::KillWordForward => (Action::DeleteWordForward, KillDirection::Forward),
+                    _ => (Action::DeleteWordBackward, KillDirection::Backward),
+                };
+                self.kill_with(delete, direction);
+            }
+            Action::Yank => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.yank()
+            }
+            Action::YankPop => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.yank_pop()
+            }
+            Action::BrowseKillRing => self.start_browse_kill_ring_prompt(),
+            Action::InsertSnippet => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.start_insert_snippet_prompt()
+            }
+            Action::SurroundAdd => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.start_surround_add_prompt()
+            }
+            Action::SurroundChange => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.start_surround_change_prompt()
+            }
+            Action::SurroundDelete => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.surround_delete()
+            }
+            Action::MoveLinesUp => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.move_lines(true)
+            }
+            Action::MoveLinesDown => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.move_lines(false)
+            }
+            Action::DuplicateLines => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.duplicate_lines()
+            }
+            Action::JoinLines(separator) => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.join_lines(&separator)
+            }
+            Action::JoinLinesWithSeparator => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.start_join_lines_prompt()
+            }
+            Action::SortLines(options) => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.sort_lines(&options)
+            }
+            Action::SortLinesByRegex => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.start_sort_lines_by_regex_prompt()
+            }
+            Action::ReverseLines => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.reverse_lines()
+            }
+            Action::UniqueLines => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.unique_lines()
+            }
+            Action::ShuffleLines => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.shuffle_lines()
+            }
+            Action::TransformText(transform) => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.transform_text(transform)
+            }
+            Action::IncrementNumber(delta) => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.increment_numbers(delta)
+            }
             Action::Undo => {
+                self.snippet_session = None;
                 self.handle_undo();
             }
             Action::Redo => {
+                self.snippet_session = None;
                 self.handle_redo();
             }
             Action::ShowHelp => {
@@ -355,6 +557,7 @@ impl Editor {
                 );
             }
             Action::LspCompletion => {
+                self.show_local_completions();
                 self.request_completion()?;
             }
             Action::LspGotoDefinition => {
@@ -369,6 +572,12 @@ impl Editor {
             Action::LspReferences => {
                 self.request_references()?;
             }
+            Action::ExpandSelection => {
+                // The language server answers asynchronously; without one, expand right away
+                if !self.request_selection_range() {
+                    self.apply_action_as_events(action)?;
+                }
+            }
             Action::LspSignatureHelp => {
                 self.request_signature_help()?;
             }
@@ -426,6 +635,49 @@ impl Editor {
             Action::FindInSelection => {
                 self.start_search_prompt("Search: ".to_string(), PromptType::Search, true);
             }
+            Action::ProjectSearch => {
+                self.start_project_search_prompt(
+                    "Search in project: ".to_string(),
+                    PromptType::ProjectSearch,
+                );
+            }
+            Action::ProjectReplace => {
+                self.start_project_search_prompt(
+                    "Replace in project: ".to_string(),
+                    PromptType::ProjectReplaceSearch,
+                );
+            }
+            Action::UndoProjectReplace => self.undo_project_replace(),
+            Action::OpenProjectSearchResult => self.open_project_search_result(),
+            Action::Occur => {
+                self.start_project_search_prompt("Occur: ".to_string(), PromptType::Occur);
+            }
+            Action::OccurGoto => self.occur_goto(),
+            Action::SearchLines => self.start_search_lines_prompt(),
+            Action::StructuralSearch => {
+                self.start_project_search_prompt(
+                    "Structural search in project: ".to_string(),
+                    PromptType::StructuralSearch { in_buffer: false },
+                );
+            }
+            Action::StructuralReplace => {
+                self.start_project_search_prompt(
+                    "Structural replace in project: ".to_string(),
+                    PromptType::StructuralReplaceSearch { in_buffer: false },
+                );
+            }
+            Action::StructuralSearchBuffer => {
+                self.start_project_search_prompt(
+                    "Structural search: ".to_string(),
+                    PromptType::StructuralSearch { in_buffer: true },
+                );
+            }
+            Action::StructuralReplaceBuffer => {
+                self.start_project_search_prompt(
+                    "Structural replace: ".to_string(),
+                    PromptType::StructuralReplaceSearch { in_buffer: true },
+                );
+            }
             Action::FindNext => {
                 self.find_next();
             }
@@ -433,8 +685,24 @@ impl Editor {
                 self.find_previous();
             }
             Action::AddCursorNextMatch => self.add_cursor_at_next_match(),
+            Action::AddCursorAtHint => self.start_jump_hint_prompt(HintTargets::Chars(1), true),
             Action::AddCursorAbove => self.add_cursor_above(),
             Action::AddCursorBelow => self.add_cursor_below(),
+            Action::SelectAllMatches => self.select_all_matches(),
+            Action::SkipToNextMatch => self.skip_to_next_match(),
+            Action::SplitSelectionIntoLines => self.split_selections_into_lines(),
+            Action::AddCursorsAtRegex => self.start_add_cursors_at_regex_prompt(),
+            Action::AlignCursors => self.align_cursors(),
+            Action::CursorUndo => self.undo_cursor_operation(),
+            Action::RotateSelections => self.rotate_selections(),
+            Action::InsertSequence => self.start_insert_sequence_prompt(),
+            Action::DeleteRectangle => self.delete_rectangle(),
+            Action::KillRectangle => self.kill_rectangle(),
+            Action::CopyRectangle => self.copy_rectangle(),
+            Action::YankRectangle => self.yank_rectangle(),
+            Action::FillRectangle => self.start_fill_rectangle_prompt(),
+            Action::InsertRectangleText => self.start_insert_rectangle_text_prompt(),
+            Action::NumberRectangleLines => self.start_number_rectangle_lines_prompt(),
             Action::NextBuffer => self.next_buffer(),
             Action::PrevBuffer => self.prev_buffer(),
             Action::SwitchToPreviousTab => self.switch_to_previous_tab(),
@@ -483,6 +751,27 @@ impl Editor {
             Action::ToggleMouseCapture => self.toggle_mouse_capture(),
             Action::ToggleMouseHover => self.toggle_mouse_hover(),
             Action::ToggleDebugHighlights => self.toggle_debug_highlights(),
+            // Debugging (DAP)
+            Action::ToggleBreakpoint => self.toggle_breakpoint(),
+            Action::SetConditionalBreakpoint => self.prompt_conditional_breakpoint(),
+            Action::SetLogpoint => self.prompt_logpoint(),
+            Action::ClearAllBreakpoints => self.clear_all_breakpoints(),
+            Action::DebugStart => self.start_debugging(),
+            Action::DebugStop => self.stop_debugging(),
+            Action::DebugContinue => self.debug_continue(),
+            Action::DebugPause => self.debug_execution_request("pause"),
+            Action::DebugStepOver => self.debug_execution_request("next"),
+            Action::DebugStepInto => self.debug_execution_request("stepIn"),
+            Action::DebugStepOut => self.debug_execution_request("stepOut"),
+            Action::DebugShowPanels => self.show_debug_panels(),
+            Action::DebugAddWatch => {
+                self.start_prompt("Watch expression: ".to_string(), PromptType::DebugAddWatch);
+            }
+            Action::DebugRemoveWatch => self.debug_remove_watch_at_cursor(),
+            Action::DebugEvaluate => {
+                self.start_prompt("Evaluate: ".to_string(), PromptType::DebugEvaluate);
+            }
+            Action::DebugPanelActivate => self.debug_panel_activate(),
             // Buffer settings
             Action::SetTabSize => {
                 let current = self
@@ -521,6 +810,17 @@ impl Editor {
                     self.set_status_message(status.to_string());
                 }
             }
+            Action::ToggleVirtualSpace => {
+                if let Some(state) = self.buffers.get_mut(&self.active_buffer()) {
+                    state.virtual_space = !state.virtual_space;
+                    let status = if state.virtual_space {
+                        "Virtual space: On"
+                    } else {
+                        "Virtual space: Off"
+                    };
+                    self.set_status_message(status.to_string());
+                }
+            }
             Action::ResetBufferSettings => self.reset_buffer_settings(),
             Action::FocusFileExplorer => self.focus_file_explorer(),
             Action::FocusEditor => self.focus_editor(),
@@ -539,6 +839,9 @@ impl Editor {
             Action::FileExplorerToggleHidden => self.file_explorer_toggle_hidden(),
             Action::FileExplorerToggleGitignored => self.file_explorer_toggle_gitignored(),
             Action::RemoveSecondaryCursors => {
+                // Escape also stops a background search
+                self.cancel_streaming_search();
+                self.cancel_project_search();
                 // Convert action to events and apply them
                 if let Some(events) = self.action_to_events(Action::RemoveSecondaryCursors) {
                     // Wrap in batch for atomic undo
@@ -592,7 +895,7 @@ impl Editor {
 
             Action::SwitchKeybindingMap(map_name) => {
                 // Check if the map exists (either built-in or user-defined)
-                let is_builtin = matches!(map_name.as_str(), "default" | "emacs" | "vscode");
+                let is_builtin = matches!(map_name.as_str(), "default" | "emacs" | "vscode" | "vi");
                 let is_user_defined = self.config.keybinding_maps.contains_key(&map_name);
 
                 if is_builtin || is_user_defined {
@@ -602,6 +905,7 @@ impl Editor {
                     // Reload the keybinding resolver with the new map
                     self.keybindings =
                         crate::input::keybindings::KeybindingResolver::new(&self.config);
+                    self.refresh_vi_state();
 
                     self.set_status_message(format!("Switched to '{}' keybindings", map_name));
                 } else {
@@ -615,6 +919,20 @@ impl Editor {
             Action::ToggleComment => {
                 self.toggle_comment();
             }
+            Action::ToggleBlockComment => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.toggle_block_comment();
+            }
+            Action::ReflowParagraph => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.reflow_paragraph();
+            }
             Action::GoToMatchingBracket => {
                 self.goto_matching_bracket();
             }
@@ -660,6 +978,7 @@ impl Editor {
                     let query = search_state.query.clone();
                     self.perform_search(&query);
                 }
+                self.refresh_replace_preview();
             }
             Action::ToggleSearchWholeWord => {
                 self.search_whole_word = !self.search_whole_word;
@@ -685,6 +1004,7 @@ impl Editor {
                     let query = search_state.query.clone();
                     self.perform_search(&query);
                 }
+                self.refresh_replace_preview();
             }
             Action::ToggleSearchRegex => {
                 self.search_use_regex = !self.search_use_regex;
@@ -710,6 +1030,7 @@ impl Editor {
                     let query = search_state.query.clone();
                     self.perform_search(&query);
                 }
+                self.refresh_replace_preview();
             }
             Action::ToggleSearchConfirmEach => {
                 self.search_confirm_each = !self.search_confirm_each;
@@ -720,6 +1041,16 @@ impl Editor {
                 };
                 self.set_status_message(format!("Confirm each replacement {}", state));
             }
+            Action::ToggleSearchPreserveCase => {
+                self.search_preserve_case = !self.search_preserve_case;
+                let state = if self.search_preserve_case {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                self.set_status_message(format!("Preserve case {}", state));
+                self.refresh_replace_preview();
+            }
             Action::StartMacroRecording => {
                 // This is a no-op; use ToggleMacroRecording instead
                 self.set_status_message(
@@ -754,6 +1085,49 @@ impl Editor {
                     self.set_status_message("No macro has been recorded yet".to_string());
                 }
             }
+            Action::PlayMacroOnLines(key) => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                    return Ok(());
+                }
+                self.play_macro_on_lines(key);
+            }
+            Action::PlayMacroTimes(key, count) => {
+                self.play_macro_times(key, count);
+            }
+            Action::PlayMacroUntilFailure(key) => {
+                self.play_macro_until_failure(key);
+            }
+            Action::PromptEditMacro => {
+                self.start_prompt("Edit macro (0-9): ".to_string(), PromptType::EditMacro);
+            }
+            Action::PromptPlayMacroOnLines => {
+                self.start_prompt(
+                    "Play macro on selected lines (0-9): ".to_string(),
+                    PromptType::PlayMacroOnLines,
+                );
+            }
+            Action::PromptPlayMacroTimes => {
+                self.start_prompt(
+                    "Play macro N times (register count, e.g. 1 5): ".to_string(),
+                    PromptType::PlayMacroTimes,
+                );
+            }
+            Action::PromptPlayMacroUntilFailure => {
+                self.start_prompt(
+                    "Play macro until it fails (0-9): ".to_string(),
+                    PromptType::PlayMacroUntilFailure,
+                );
+            }
+            Action::PromptExportMacro => {
+                self.start_prompt(
+                    "Export macro as command (register name, e.g. 1 Add Semicolon): ".to_string(),
+                    PromptType::ExportMacro,
+                );
+            }
+            Action::Vi(command) => {
+                self.run_vi_command(command)?;
+            }
             Action::PromptSetBookmark => {
                 self.start_prompt("Set bookmark (0-9): ".to_string(), PromptType::SetBookmark);
             }
@@ -1832,7 +2206,7 @@ impl Editor {
     /// Start the keybinding map selection prompt with available maps
     fn start_select_keybinding_map_prompt(&mut self) {
         // Built-in keybinding maps
-        let builtin_maps = vec!["default", "emacs", "vscode"];
+        let builtin_maps = vec!["default", "emacs", "vscode", "vi"];
 
         // Collect user-defined keybinding maps from config
         let user_maps: Vec<&str> = self
@@ -1900,7 +2274,7 @@ impl Editor {
         }
 
         // Check if the map exists (either built-in or user-defined)
-        let is_builtin = matches!(map_name, "default" | "emacs" | "vscode");
+        let is_builtin = matches!(map_name, "default" | "emacs" | "vscode" | "vi");
         let is_user_defined = self.config.keybinding_maps.contains_key(map_name);
 
         if is_builtin || is_user_defined {
@@ -1909,6 +2283,7 @@ impl Editor {
 
             // Reload the keybinding resolver with the new map
             self.keybindings = crate::input::keybindings::KeybindingResolver::new(&self.config);
+            self.refresh_vi_state();
 
             // Persist to config file
             self.save_keybinding_map_to_config();
@@ -2077,10 +2452,20 @@ impl Editor {
         // This allows them to press Up to get back to history items
         if let Some(ref prompt) = self.prompt {
             match &prompt.prompt_type {
-                PromptType::Search | PromptType::ReplaceSearch | PromptType::QueryReplaceSearch => {
+                PromptType::Search
+                | PromptType::ReplaceSearch
+                | PromptType::QueryReplaceSearch
+                | PromptType::ProjectSearch
+                | PromptType::ProjectReplaceSearch
+                | PromptType::Occur
+                | PromptType::StructuralSearch { .. }
+                | PromptType::StructuralReplaceSearch { .. } => {
                     self.search_history.reset_navigation();
                 }
-                PromptType::Replace { .. } | PromptType::QueryReplace { .. } => {
+                PromptType::Replace { .. }
+                | PromptType::QueryReplace { .. }
+                | PromptType::ProjectReplace { .. }
+                | PromptType::StructuralReplace { .. } => {
                     self.replace_history.reset_navigation();
                 }
                 _ => {}
@@ -2125,11 +2510,19 @@ impl Editor {
             }
         }
 
+        if !c.is_whitespace() {
+            self.hard_wrap_cursor_lines();
+        }
+
         // Auto-trigger signature help on '(' and ','
         if c == '(' || c == ',' {
             let _ = self.request_signature_help();
         }
 
+        if c.is_ascii() && is_word_char(c as u8) {
+            self.complete_while_typing();
+        }
+
         Ok(())
     }
 
@@ -2138,7 +2531,7 @@ impl Editor {
     /// This is the catch-all handler for actions that can be converted to buffer events
     /// (cursor movements, text edits, etc.). It handles batching for multi-cursor,
     /// position history tracking, and editing permission checks.
-    fn apply_action_as_events(&mut self, action: Action) -> std::io::Result<()> {
+    pub(super) fn apply_action_as_events(&mut self, action: Action) -> std::io::Result<()> {
         // Get description before moving action
         let action_description = format!("{:?}", action);
 
@@ -2153,6 +2546,8 @@ impl Editor {
                 | Action::DeleteLine
                 | Action::DedentSelection
                 | Action::ToggleComment
+                | Action::SwapParameterNext
+                | Action::DeleteParameter
         );
 
         if is_editing_action && self.is_editing_disabled() {
@@ -2161,32 +2556,40 @@ impl Editor {
         }
 
         if let Some(events) = self.action_to_events(action) {
-            // Wrap multiple events (multi-cursor) in a Batch for atomic undo
-            if events.len() > 1 {
-                let batch = Event::Batch {
-                    events: events.clone(),
-                    description: action_description,
-                };
-                self.active_event_log_mut().append(batch.clone());
-                self.apply_event_to_active_buffer(&batch);
-
-                // Track position history for all events in the batch
-                for event in &events {
-                    self.track_cursor_movement(event);
-                }
-            } else {
-                // Single cursor - no need for batch
-                for event in events {
-                    self.active_event_log_mut().append(event.clone());
-                    self.apply_event_to_active_buffer(&event);
-                    self.track_cursor_movement(&event);
-                }
-            }
+            self.apply_events_as_action(events, action_description);
         }
 
         Ok(())
     }
 
+    /// Apply the events produced for an action to the active buffer
+    ///
+    /// Multi-cursor events are wrapped in a Batch for atomic undo, and cursor
+    /// movements are recorded in position history.
+    pub(super) fn apply_events_as_action(&mut self, events: Vec<Event>, description: String) {
+        // Wrap multiple events (multi-cursor) in a Batch for atomic undo
+        if events.len() > 1 {
+            let batch = Event::Batch {
+                events: events.clone(),
+                description,
+            };
+            self.active_event_log_mut().append(batch.clone());
+            self.apply_event_to_active_buffer(&batch);
+
+            // Track position history for all events in the batch
+            for event in &events {
+                self.track_cursor_movement(event);
+            }
+        } else {
+            // Single cursor - no need for batch
+            for event in events {
+                self.active_event_log_mut().append(event.clone());
+                self.apply_event_to_active_buffer(&event);
+                self.track_cursor_movement(&event);
+            }
+        }
+    }
+
     /// Track cursor movement in position history if applicable.
     fn track_cursor_movement(&mut self, event: &Event) {
         if self.in_navigation {
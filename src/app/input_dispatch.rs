@@ -125,8 +125,17 @@ impl Editor {
             }
 
             if let Some(ref mut prompt) = self.prompt {
+                let selected = prompt.selected_suggestion;
                 let result = prompt.dispatch_input(event, &mut ctx);
                 self.process_deferred_actions(ctx);
+                // Search Lines previews the selected line as the selection moves
+                let moved = self.prompt.as_ref().is_some_and(|prompt| {
+                    prompt.prompt_type == PromptType::SearchLines
+                        && prompt.selected_suggestion != selected
+                });
+                if moved {
+                    self.preview_search_lines();
+                }
                 return Some(result);
             }
         }
@@ -270,6 +279,7 @@ impl Editor {
             DeferredAction::CancelInteractiveReplace => {
                 self.cancel_prompt();
                 self.interactive_replace_state = None;
+                self.clear_replace_preview();
             }
 
             // Terminal mode actions
@@ -348,7 +358,14 @@ impl Editor {
             // Search prompts use search history
             if matches!(
                 prompt_type,
-                PromptType::Search | PromptType::ReplaceSearch | PromptType::QueryReplaceSearch
+                PromptType::Search
+                    | PromptType::ReplaceSearch
+                    | PromptType::QueryReplaceSearch
+                    | PromptType::ProjectSearch
+                    | PromptType::ProjectReplaceSearch
+                    | PromptType::Occur
+                    | PromptType::StructuralSearch { .. }
+                    | PromptType::StructuralReplaceSearch { .. }
             ) {
                 if let Some(entry) = self.search_history.navigate_prev(&current_input) {
                     if let Some(ref mut prompt) = self.prompt {
@@ -359,7 +376,10 @@ impl Editor {
             // Replacement prompts use replace history
             else if matches!(
                 prompt_type,
-                PromptType::Replace { .. } | PromptType::QueryReplace { .. }
+                PromptType::Replace { .. }
+                    | PromptType::QueryReplace { .. }
+                    | PromptType::ProjectReplace { .. }
+                    | PromptType::StructuralReplace { .. }
             ) {
                 if let Some(entry) = self.replace_history.navigate_prev(&current_input) {
                     if let Some(ref mut prompt) = self.prompt {
@@ -380,7 +400,14 @@ impl Editor {
             // Search prompts use search history
             if matches!(
                 prompt_type,
-                PromptType::Search | PromptType::ReplaceSearch | PromptType::QueryReplaceSearch
+                PromptType::Search
+                    | PromptType::ReplaceSearch
+                    | PromptType::QueryReplaceSearch
+                    | PromptType::ProjectSearch
+                    | PromptType::ProjectReplaceSearch
+                    | PromptType::Occur
+                    | PromptType::StructuralSearch { .. }
+                    | PromptType::StructuralReplaceSearch { .. }
             ) {
                 if let Some(entry) = self.search_history.navigate_next() {
                     if let Some(ref mut prompt) = self.prompt {
@@ -391,7 +418,10 @@ impl Editor {
             // Replacement prompts use replace history
             else if matches!(
                 prompt_type,
-                PromptType::Replace { .. } | PromptType::QueryReplace { .. }
+                PromptType::Replace { .. }
+                    | PromptType::QueryReplace { .. }
+                    | PromptType::ProjectReplace { .. }
+                    | PromptType::StructuralReplace { .. }
             ) {
                 if let Some(entry) = self.replace_history.navigate_next() {
                     if let Some(ref mut prompt) = self.prompt {
@@ -0,0 +1,400 @@
+//! Jump hints for the Editor
+//!
+//! A prompt that asks for the characters to jump to, then labels each place
+//! they appear on screen, in every split. Typing a label moves the cursor
+//! there, focusing its split; typing it in upper case extends the selection
+//! there instead. When adding cursors, each label typed adds a cursor and
+//! the other labels stay up until Enter or Escape.
+//!
+//! Targets are taken from the view lines of the last render, so exactly what
+//! is on screen gets labelled, however lines are wrapped or laid out. The
+//! labels are virtual text in front of their targets.
+
+use std::collections::HashSet;
+
+use ratatui::style::{Modifier, Style};
+
+use crate::model::event::{BufferId, Event, SplitId};
+use crate::primitives::jump_hints::{labels, HintTargets, MAX_HINTS};
+use crate::view::prompt::PromptType;
+use crate::view::virtual_text::VirtualTextPosition;
+
+use super::Editor;
+
+/// String id of the label virtual texts
+const JUMP_HINT_ID: &str = "jump-hint";
+
+/// A labelled target
+struct Hint {
+    label: String,
+    split_id: SplitId,
+    buffer_id: BufferId,
+    position: usize,
+}
+
+/// An open jump hint prompt
+pub(super) struct JumpHintState {
+    targets: HintTargets,
+    /// Add a cursor at each chosen target instead of jumping
+    add_cursor: bool,
+    /// The characters the hints were found for, once all are typed
+    query: Option<String>,
+    hints: Vec<Hint>,
+    /// Buffers showing labels
+    labelled: HashSet<BufferId>,
+}
+
+impl Editor {
+    /// Ask for the characters of the targets to label, or label the targets
+    /// right away if they need none
+    pub(super) fn start_jump_hint_prompt(&mut self, targets: HintTargets, add_cursor: bool) {
+        let what = match targets {
+            HintTargets::Chars(1) => "char",
+            HintTargets::Chars(_) => "chars",
+            HintTargets::WordStarts => "word starting with",
+            HintTargets::LineStarts => "line",
+        };
+        let message = if add_cursor {
+            format!("Add cursor at {}: ", what)
+        } else {
+            format!("Jump to {}: ", what)
+        };
+        self.start_prompt(message, PromptType::JumpHint);
+        self.jump_hints = Some(JumpHintState {
+            targets,
+            add_cursor,
+            query: None,
+            hints: Vec::new(),
+            labelled: HashSet::new(),
+        });
+        self.update_jump_hints("");
+    }
+
+    /// Label the targets once `input` holds the characters to look for, and
+    /// act on the hint whose label follows them
+    pub(super) fn update_jump_hints(&mut self, input: &str) {
+        let Some(state) = self.jump_hints.as_mut() else {
+            return;
+        };
+        let query_len = state.targets.query_len();
+        if input.chars().count() < query_len {
+            state.query = None;
+            state.hints.clear();
+            self.show_jump_hints("");
+            return;
+        }
+        let query_end = input
+            .char_indices()
+            .nth(query_len)
+            .map_or(input.len(), |(i, _)| i);
+        let (query, typed) = input.split_at(query_end);
+
+        if state.query.as_deref() != Some(query) {
+            let hints = self.find_jump_hints(query);
+            let Some(state) = self.jump_hints.as_mut() else {
+                return;
+            };
+            state.hints = hints;
+            state.query = Some(query.to_string());
+            match state.hints.len() {
+                0 => {
+                    self.cancel_jump_hints();
+                    self.prompt = None;
+                    self.set_status_message(if query.is_empty() {
+                        "Nothing to jump to".to_string()
+                    } else {
+                        format!("No matches for '{}'", query)
+                    });
+                    return;
+                }
+                1 if !state.add_cursor => {
+                    self.choose_jump_hint(0, false);
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        let label = typed.to_lowercase();
+        let extend = typed.chars().any(char::is_uppercase);
+        let Some(state) = self.jump_hints.as_ref() else {
+            return;
+        };
+        if let Some(index) = state.hints.iter().position(|hint| hint.label == label) {
+            self.choose_jump_hint(index, extend);
+        } else if state
+            .hints
+            .iter()
+            .any(|hint| hint.label.starts_with(&label))
+        {
+            self.show_jump_hints(&label);
+        } else {
+            // Not a label: forget the key that was typed last
+            let mut kept = input.to_string();
+            kept.pop();
+            if let Some(prompt) = self.prompt.as_mut() {
+                prompt.input = kept.clone();
+                prompt.cursor_pos = kept.len();
+            }
+            self.set_status_message(format!("No hint labelled '{}'", label));
+            self.update_jump_hints(&kept);
+        }
+    }
+
+    /// Act on the nearest hint whose label starts with what was typed
+    pub(super) fn confirm_jump_hint(&mut self, input: &str) {
+        let index = self.jump_hints.as_ref().and_then(|state| {
+            let query = state.query.as_deref()?;
+            let label = input.get(query.len()..)?.to_lowercase();
+            state
+                .hints
+                .iter()
+                .position(|hint| hint.label.starts_with(&label))
+        });
+        match index {
+            Some(index)
+                if !self
+                    .jump_hints
+                    .as_ref()
+                    .is_some_and(|state| state.add_cursor) =>
+            {
+                self.choose_jump_hint(index, false);
+            }
+            _ => self.cancel_jump_hints(),
+        }
+    }
+
+    /// Remove the labels and close jump hints
+    pub(super) fn cancel_jump_hints(&mut self) {
+        let Some(state) = self.jump_hints.take() else {
+            return;
+        };
+        for buffer_id in state.labelled {
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state
+                    .virtual_texts
+                    .remove_by_prefix(&mut state.marker_list, JUMP_HINT_ID);
+            }
+        }
+    }
+
+    /// Jump to the hint at `index`, or add a cursor there and keep the other
+    /// labels up
+    fn choose_jump_hint(&mut self, index: usize, extend: bool) {
+        let Some(state) = self.jump_hints.as_mut() else {
+            return;
+        };
+        let hint = state.hints.remove(index);
+        let (add_cursor, query, done) = (
+            state.add_cursor,
+            state.query.clone().unwrap_or_default(),
+            state.hints.is_empty(),
+        );
+        if !add_cursor {
+            self.cancel_jump_hints();
+            self.prompt = None;
+            self.jump_to_hint(&hint, extend);
+            return;
+        }
+
+        self.remember_cursors();
+        let cursor_id = self.active_state().cursors.next_id();
+        let event = Event::AddCursor {
+            cursor_id,
+            position: hint.position,
+            anchor: None,
+        };
+        self.active_event_log_mut().append(event.clone());
+        self.apply_event_to_active_buffer(&event);
+        let count = self.active_state().cursors.count();
+        self.set_status_message(format!("Added cursor ({} cursors)", count));
+
+        if done {
+            self.cancel_jump_hints();
+            self.prompt = None;
+            return;
+        }
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.cursor_pos = query.len();
+            prompt.input = query;
+        }
+        self.show_jump_hints("");
+    }
+
+    /// Move the cursor of the hint's split to it, as one cursor move
+    fn jump_to_hint(&mut self, hint: &Hint, extend: bool) {
+        if hint.split_id != self.split_manager.active_split() {
+            self.focus_split(hint.split_id, hint.buffer_id);
+        }
+        let buffer_id = self.active_buffer();
+        let state = self.active_state();
+        let cursor = state.cursors.primary();
+        let new_anchor = if extend {
+            Some(cursor.anchor.unwrap_or(cursor.position))
+        } else {
+            None
+        };
+        let event = Event::MoveCursor {
+            cursor_id: state.cursors.primary_id(),
+            old_position: cursor.position,
+            new_position: hint.position,
+            old_anchor: cursor.anchor,
+            new_anchor,
+            old_sticky_column: cursor.sticky_column,
+            new_sticky_column: 0,
+        };
+        self.active_event_log_mut().append(event.clone());
+        self.apply_event_to_active_buffer(&event);
+        if !self.in_navigation {
+            self.position_history
+                .record_movement(buffer_id, hint.position, new_anchor);
+        }
+    }
+
+    /// Show the rest of each label that starts with `typed`
+    fn show_jump_hints(&mut self, typed: &str) {
+        let style = Style::default()
+            .fg(self.theme.search_match_fg)
+            .bg(self.theme.search_match_bg)
+            .add_modifier(Modifier::BOLD);
+        let Some(hints) = self.jump_hints.as_mut() else {
+            return;
+        };
+        for buffer_id in hints.labelled.drain() {
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state
+                    .virtual_texts
+                    .remove_by_prefix(&mut state.marker_list, JUMP_HINT_ID);
+            }
+        }
+        for hint in &hints.hints {
+            let Some(rest) = hint.label.strip_prefix(typed) else {
+                continue;
+            };
+            let Some(state) = self.buffers.get_mut(&hint.buffer_id) else {
+                continue;
+            };
+            state.virtual_texts.add_with_id(
+                &mut state.marker_list,
+                hint.position,
+                rest.to_string(),
+                style,
+                VirtualTextPosition::BeforeChar,
+                0,
+                JUMP_HINT_ID.to_string(),
+            );
+            hints.labelled.insert(hint.buffer_id);
+        }
+    }
+
+    /// Label the targets for `query` on screen, in the active split first,
+    /// nearest the cursor first, then in the other splits
+    fn find_jump_hints(&mut self, query: &str) -> Vec<Hint> {
+        let Some(state) = self.jump_hints.as_ref() else {
+            return Vec::new();
+        };
+        let (targets, add_cursor) = (state.targets, state.add_cursor);
+        let active_split = self.split_manager.active_split();
+        let active_buffer = self.active_buffer();
+        let cursor = self.active_state().cursors.primary().position;
+
+        let mut splits: Vec<(SplitId, BufferId, u16)> = self
+            .cached_layout
+            .split_areas
+            .iter()
+            .map(|(split_id, buffer_id, rect, ..)| (*split_id, *buffer_id, rect.width))
+            .filter(|(_, buffer_id, _)| !add_cursor || *buffer_id == active_buffer)
+            .collect();
+        splits.sort_by_key(|(split_id, ..)| *split_id != active_split);
+
+        let mut seen = HashSet::new();
+        let mut found = Vec::new();
+        for (split_id, buffer_id, width) in splits {
+            if self.is_terminal_buffer(buffer_id) {
+                continue;
+            }
+            let visible = self.visible_source_bytes(split_id, buffer_id, width);
+            let (Some(&first), Some(&last)) = (visible.first(), visible.last()) else {
+                continue;
+            };
+            let Some(state) = self.buffers.get_mut(&buffer_id) else {
+                continue;
+            };
+            // One byte before to tell word and line starts, and enough after
+            // the last visible character for a query starting there
+            let start = first.saturating_sub(1);
+            let end = (last + 4 + query.len()).min(state.buffer.len());
+            let Ok(bytes) = state.buffer.get_text_range_mut(start, end - start) else {
+                continue;
+            };
+            let mut positions: Vec<usize> = targets
+                .find(&bytes, first - start, query)
+                .into_iter()
+                .map(|offset| start + offset)
+                .filter(|position| visible.binary_search(position).is_ok())
+                .collect();
+            if split_id == active_split {
+                positions.sort_by_key(|position| position.abs_diff(cursor));
+            }
+            for position in positions {
+                if seen.insert((buffer_id, position)) {
+                    found.push((split_id, buffer_id, position));
+                }
+            }
+        }
+
+        found.truncate(MAX_HINTS);
+        labels(found.len())
+            .into_iter()
+            .zip(found)
+            .map(|(label, (split_id, buffer_id, position))| Hint {
+                label,
+                split_id,
+                buffer_id,
+                position,
+            })
+            .collect()
+    }
+
+    /// Sorted source bytes of the characters on screen in a split, as of the
+    /// last render
+    fn visible_source_bytes(
+        &self,
+        split_id: SplitId,
+        buffer_id: BufferId,
+        width: u16,
+    ) -> Vec<usize> {
+        let Some(mappings) = self.cached_layout.view_line_mappings.get(&split_id) else {
+            return Vec::new();
+        };
+        // Unwrapped lines are scrolled sideways when drawn
+        let columns = match self.split_view_states.get(&split_id) {
+            Some(view_state) if !view_state.viewport.line_wrap_enabled => {
+                let gutter = self
+                    .buffers
+                    .get(&buffer_id)
+                    .map_or(0, |state| state.margins.left_total_width());
+                let left = view_state.viewport.left_column;
+                left..left + (width as usize).saturating_sub(gutter)
+            }
+            _ => 0..usize::MAX,
+        };
+
+        let mut bytes: Vec<usize> = mappings
+            .iter()
+            .flat_map(|mapping| {
+                mapping
+                    .visual_to_char
+                    .iter()
+                    .enumerate()
+                    .filter(|(column, _)| columns.contains(column))
+                    .filter_map(|(_, &char_index)| {
+                        mapping.char_source_bytes.get(char_index).copied().flatten()
+                    })
+            })
+            .collect();
+        bytes.sort_unstable();
+        bytes.dedup();
+        bytes
+    }
+}
@@ -0,0 +1,281 @@
+//! Kill ring actions for the Editor: kill, yank, yank-pop and browsing.
+//!
+//! Kills reuse the deletion actions (delete to line end, delete word) and save
+//! what they delete on the kill ring. The newest entry is always mirrored to
+//! the system clipboard, and text copied in other applications is picked up
+//! as a new entry the next time you yank.
+
+use crate::input::commands::Suggestion;
+use crate::input::keybindings::Action;
+use crate::input::kill_ring::{KillDirection, KillEntry, LastYank};
+use crate::model::event::{CursorId, Event};
+use crate::view::prompt::PromptType;
+
+use super::Editor;
+
+/// Longest entry preview shown in the kill ring and clipboard history pickers
+const PREVIEW_CHARS: usize = 60;
+
+impl Editor {
+    /// Start a new command for the kill ring's kill and yank chains
+    pub(super) fn start_kill_ring_command(&mut self, action: &Action) {
+        let is_kill = matches!(
+            action,
+            Action::KillLine | Action::KillWordForward | Action::KillWordBackward
+        );
+        let is_yank = matches!(action, Action::YankPop);
+        self.kill_ring.start_command(is_kill, is_yank);
+    }
+
+    /// Delete text with `delete` and save it on the kill ring
+    ///
+    /// Each cursor contributes one piece, so a later yank with the same
+    /// cursors puts every piece back at its own cursor.
+    pub(super) fn kill_with(&mut self, delete: Action, direction: KillDirection) {
+        let description = format!("{:?}", delete);
+        let Some(events) = self.action_to_events(delete) else {
+            return;
+        };
+
+        let mut cursors: Vec<(usize, CursorId)> = self
+            .active_state()
+            .cursors
+            .iter()
+            .map(|(id, cursor)| (cursor.position, id))
+            .collect();
+        cursors.sort_by_key(|(position, id)| (*position, id.0));
+        let pieces: Vec<String> = cursors
+            .iter()
+            .map(|(_, id)| {
+                events
+                    .iter()
+                    .filter_map(|event| match event {
+                        Event::Delete {
+                            deleted_text,
+                            cursor_id,
+                            ..
+                        } if cursor_id == id => Some(deleted_text.as_str()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        if pieces.iter().all(|piece| piece.is_empty()) {
+            return;
+        }
+        if let Some(entry) = self.kill_ring.kill(pieces, direction) {
+            self.clipboard.copy(entry.text());
+        }
+        self.apply_events_as_action(events, description);
+    }
+
+    /// Insert the newest kill ring entry at every cursor
+    pub(super) fn yank(&mut self) {
+        self.sync_kill_ring_from_clipboard();
+        self.yank_entry(0);
+    }
+
+    /// Insert kill ring entry `index` (0 is newest) at every cursor
+    pub(super) fn yank_entry(&mut self, index: usize) {
+        let Some(entry) = self.kill_ring.yank(index).cloned() else {
+            self.set_status_message("Kill ring is empty".to_string());
+            return;
+        };
+        let inserted = self.insert_entry(&entry, "Yank");
+        self.remember_yank(inserted);
+    }
+
+    /// Insert an entry at every cursor, replacing any selection
+    ///
+    /// With as many cursors as the entry has pieces, each cursor (in document
+    /// order) gets its own piece; otherwise every cursor gets the whole text.
+    /// Returns the text inserted for each cursor.
+    pub(super) fn insert_entry(
+        &mut self,
+        entry: &KillEntry,
+        description: &str,
+    ) -> Vec<(CursorId, String)> {
+        let mut cursors: Vec<_> = self
+            .active_state()
+            .cursors
+            .iter()
+            .map(|(id, cursor)| {
+                let selection = cursor.selection_range();
+                let position = selection.as_ref().map_or(cursor.position, |r| r.start);
+                (position, id, selection)
+            })
+            .collect();
+        cursors.sort_by_key(|(position, id, _)| (*position, id.0));
+
+        let pieces = entry.pieces_for(cursors.len());
+        let mut inserted = Vec::new();
+        let mut events = Vec::new();
+        for ((position, cursor_id, selection), piece) in cursors.into_iter().zip(pieces).rev() {
+            let piece = self.to_buffer_line_endings(&piece);
+            if let Some(range) = selection {
+                let deleted_text = self
+                    .active_state_mut()
+                    .get_text_range(range.start, range.end);
+                events.push(Event::Delete {
+                    range,
+                    deleted_text,
+                    cursor_id,
+                });
+            }
+            inserted.push((cursor_id, piece.clone()));
+            if !piece.is_empty() {
+                events.push(Event::Insert {
+                    position,
+                    text: piece,
+                    cursor_id,
+                });
+            }
+        }
+
+        self.apply_events_as_action(events, description.to_string());
+        inserted
+    }
+
+    /// Replace the text inserted by the previous yank with the next older entry
+    pub(super) fn yank_pop(&mut self) {
+        let Some(last) = self.kill_ring.take_last_yank() else {
+            self.set_status_message("Previous command was not a yank".to_string());
+            return;
+        };
+
+        // The yanked text must still be where the yank left it
+        let buffer_id = self.active_buffer();
+        let state = self.active_state_mut();
+        let intact = last.buffer_id == buffer_id
+            && last.ranges.iter().all(|(_, range, text)| {
+                range.end <= state.buffer.len()
+                    && state.get_text_range(range.start, range.end) == *text
+            });
+        if !intact {
+            self.set_status_message("Previous command was not a yank".to_string());
+            return;
+        }
+
+        let Some(entry) = self.kill_ring.rotate().cloned() else {
+            return;
+        };
+
+        let mut ranges = last.ranges;
+        ranges.sort_by_key(|(_, range, _)| range.start);
+        let pieces = entry.pieces_for(ranges.len());
+        let mut inserted = Vec::new();
+        let mut events = Vec::new();
+        for ((cursor_id, range, old_text), piece) in ranges.into_iter().zip(pieces).rev() {
+            let piece = self.to_buffer_line_endings(&piece);
+            let position = range.start;
+            if !range.is_empty() {
+                events.push(Event::Delete {
+                    range,
+                    deleted_text: old_text,
+                    cursor_id,
+                });
+            }
+            inserted.push((cursor_id, piece.clone()));
+            if !piece.is_empty() {
+                events.push(Event::Insert {
+                    position,
+                    text: piece,
+                    cursor_id,
+                });
+            }
+        }
+
+        self.apply_events_as_action(events, "Yank pop".to_string());
+        self.remember_yank(inserted);
+    }
+
+    /// Record where each cursor's yanked text ended up, for a following yank-pop
+    fn remember_yank(&mut self, inserted: Vec<(CursorId, String)>) {
+        let buffer_id = self.active_buffer();
+        let cursors = &self.active_state().cursors;
+        let ranges = inserted
+            .into_iter()
+            .filter_map(|(cursor_id, text)| {
+                // Insert leaves each cursor just after its text
+                let end = cursors.get(cursor_id)?.position;
+                Some((cursor_id, end.checked_sub(text.len())?..end, text))
+            })
+            .collect();
+        self.kill_ring.set_last_yank(LastYank { buffer_id, ranges });
+    }
+
+    /// Add text copied in another application to the kill ring
+    fn sync_kill_ring_from_clipboard(&mut self) {
+        let Some(text) = self.clipboard.paste() else {
+            return;
+        };
+        let entry = KillEntry::new(vec![text]);
+        // A multi-cursor kill reaches the clipboard joined into one string
+        let is_newest = self
+            .kill_ring
+            .newest()
+            .is_some_and(|newest| newest.text() == entry.text());
+        if !is_newest {
+            self.kill_ring.push(entry);
+        }
+    }
+
+    /// Open a picker listing the kill ring, newest first
+    pub(super) fn start_browse_kill_ring_prompt(&mut self) {
+        self.sync_kill_ring_from_clipboard();
+        if self.kill_ring.is_empty() {
+            self.set_status_message("Kill ring is empty".to_string());
+            return;
+        }
+
+        let suggestions = self
+            .kill_ring
+            .entries()
+            .enumerate()
+            .map(|(index, entry)| entry_suggestion(index, entry))
+            .collect();
+
+        self.prompt = Some(crate::view::prompt::Prompt::with_suggestions(
+            "Yank from kill ring: ".to_string(),
+            PromptType::BrowseKillRing,
+            suggestions,
+        ));
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.selected_suggestion = Some(0);
+        }
+    }
+}
+
+/// Picker row for a kill ring or clipboard history entry: a one-line preview,
+/// with the line and cursor counts as description and the index as value
+pub(super) fn entry_suggestion(index: usize, entry: &KillEntry) -> Suggestion {
+    let text = entry.text();
+    let trimmed = text.trim();
+    let mut preview: String = trimmed
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(PREVIEW_CHARS)
+        .collect();
+    if trimmed.chars().count() > PREVIEW_CHARS {
+        preview.push('…');
+    }
+
+    let mut details = Vec::new();
+    let lines = text.lines().count();
+    if lines > 1 {
+        details.push(format!("{} lines", lines));
+    }
+    if entry.pieces().len() > 1 {
+        details.push(format!("{} cursors", entry.pieces().len()));
+    }
+
+    Suggestion {
+        text: preview,
+        description: (!details.is_empty()).then(|| details.join(", ")),
+        value: Some(index.to_string()),
+        disabled: false,
+        keybinding: None,
+        source: None,
+    }
+}
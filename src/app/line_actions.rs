@@ -0,0 +1,312 @@
+//! Line actions for the Editor: move, duplicate, join, sort, reverse, unique
+//! and shuffle the lines under each cursor or selection.
+//!
+//! Cursors are grouped into blocks of whole lines. Each block is rewritten
+//! with one replacement, and every cursor and selection is moved to where its
+//! text went. The events form a single undo step, which also puts the cursors
+//! back where they were.
+
+use std::ops::Range;
+
+use crate::model::buffer::Buffer;
+use crate::model::event::Event;
+use crate::primitives::line_ops::{self, LineEdit, SortOptions};
+use crate::view::prompt::PromptType;
+
+use super::Editor;
+
+/// Lines a block rewrite covers: the block itself, or the block plus a
+/// neighbouring line. None skips the block.
+type RegionFn = fn(Range<usize>, usize) -> Option<Range<usize>>;
+
+/// A rewritten region: its byte range, line range and result
+struct Rewrite {
+    bytes: Range<usize>,
+    first_line: usize,
+    edit: LineEdit,
+    text: String,
+}
+
+impl Editor {
+    /// Move the lines of each block up or down past the neighbouring line
+    pub(super) fn move_lines(&mut self, up: bool) {
+        if up {
+            self.rewrite_lines(
+                "Move lines up",
+                false,
+                |block, _| (block.start > 0).then(|| block.start - 1..block.end),
+                |lines| Ok(line_ops::move_up(lines)),
+            );
+        } else {
+            self.rewrite_lines(
+                "Move lines down",
+                false,
+                |block, last| (block.end <= last).then(|| block.start..block.end + 1),
+                |lines| Ok(line_ops::move_down(lines)),
+            );
+        }
+    }
+
+    /// Duplicate the lines of each block below it
+    pub(super) fn duplicate_lines(&mut self) {
+        self.rewrite_lines(
+            "Duplicate lines",
+            false,
+            |block, _| Some(block),
+            |lines| Ok(line_ops::duplicate(lines)),
+        );
+    }
+
+    /// Join each block's lines, or a single line with the next one
+    pub(super) fn join_lines(&mut self, separator: &str) {
+        let separator = separator.to_string();
+        self.rewrite_lines(
+            "Join lines",
+            false,
+            |block, last| {
+                if block.len() > 1 {
+                    Some(block)
+                } else {
+                    (block.end <= last).then(|| block.start..block.end + 1)
+                }
+            },
+            move |lines| Ok(line_ops::join(lines, &separator)),
+        );
+    }
+
+    /// Sort each block's lines; without a selection, the whole buffer
+    pub(super) fn sort_lines(&mut self, options: &SortOptions) {
+        let options = options.clone();
+        self.rewrite_lines(
+            "Sort lines",
+            true,
+            |block, _| Some(block),
+            move |lines| line_ops::sort(lines, &options),
+        );
+    }
+
+    /// Reverse each block's lines; without a selection, the whole buffer
+    pub(super) fn reverse_lines(&mut self) {
+        self.rewrite_lines(
+            "Reverse lines",
+            true,
+            |block, _| Some(block),
+            |lines| Ok(line_ops::reverse(lines)),
+        );
+    }
+
+    /// Drop repeated lines from each block; without a selection, the whole buffer
+    pub(super) fn unique_lines(&mut self) {
+        self.rewrite_lines(
+            "Unique lines",
+            true,
+            |block, _| Some(block),
+            |lines| Ok(line_ops::unique(lines)),
+        );
+    }
+
+    /// Shuffle each block's lines; without a selection, the whole buffer
+    pub(super) fn shuffle_lines(&mut self) {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        self.rewrite_lines(
+            "Shuffle lines",
+            true,
+            |block, _| Some(block),
+            move |lines| Ok(line_ops::shuffle(lines, seed)),
+        );
+    }
+
+    /// Ask for the separator to join lines with
+    pub(super) fn start_join_lines_prompt(&mut self) {
+        self.start_prompt(
+            "Join lines with: ".to_string(),
+            PromptType::JoinLinesSeparator,
+        );
+    }
+
+    /// Ask for the regex whose first capture group lines are sorted by
+    pub(super) fn start_sort_lines_by_regex_prompt(&mut self) {
+        self.start_prompt(
+            "Sort lines by regex: ".to_string(),
+            PromptType::SortLinesPattern,
+        );
+    }
+
+    /// Rewrite the line blocks under the cursors with `op`
+    ///
+    /// `region` picks the lines each block rewrites (given the block and the
+    /// last line of the buffer). With `whole_buffer`, a single cursor without
+    /// a selection works on the whole buffer.
+    fn rewrite_lines(
+        &mut self,
+        description: &str,
+        whole_buffer: bool,
+        region: RegionFn,
+        op: impl Fn(&[String]) -> Result<LineEdit, String>,
+    ) {
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let last = buffer.last_line();
+        let line_ending = buffer.line_ending().as_str();
+
+        let single_cursor =
+            state.cursors.count() == 1 && state.cursors.primary().selection_range().is_none();
+        let blocks = if whole_buffer && single_cursor {
+            let all_lines = 0..last + 1;
+            vec![all_lines]
+        } else {
+            let ranges = state.cursors.iter().map(|(_, cursor)| {
+                cursor
+                    .selection_range()
+                    .unwrap_or(cursor.position..cursor.position)
+            });
+            line_blocks(buffer, ranges)
+        };
+
+        let regions = merge_overlapping(
+            blocks
+                .into_iter()
+                .filter_map(|block| region(block, last))
+                .collect(),
+        );
+        let rewrites: Result<Vec<Rewrite>, String> = regions
+            .into_iter()
+            .map(|lines| {
+                let bytes = buffer.line_start(lines.start)..buffer.line_end(lines.end - 1);
+                let old_text = String::from_utf8_lossy(&buffer.slice_bytes(bytes.clone()))
+                    .replace("\r\n", "\n")
+                    .replace('\r', "\n");
+                let old_lines: Vec<String> = old_text.split('\n').map(str::to_string).collect();
+                let edit = op(&old_lines)?;
+                let text = edit.lines.join(line_ending);
+                Ok(Rewrite {
+                    bytes,
+                    first_line: lines.start,
+                    edit,
+                    text,
+                })
+            })
+            .collect();
+        let rewrites = match rewrites {
+            Ok(rewrites) => rewrites,
+            Err(message) => {
+                self.set_status_message(message);
+                return;
+            }
+        };
+
+        let state = self.active_state();
+        let changed = rewrites
+            .iter()
+            .any(|r| r.text.as_bytes() != state.buffer.slice_bytes(r.bytes.clone()));
+        if !changed {
+            return;
+        }
+
+        // Where a position ends up once every region is rewritten
+        let map_position = |pos: usize| -> usize {
+            let mut shift: isize = 0;
+            for rewrite in &rewrites {
+                if pos < rewrite.bytes.start {
+                    break;
+                }
+                if pos <= rewrite.bytes.end {
+                    let line = state.buffer.line_of(pos);
+                    let column = pos - state.buffer.line_start(line);
+                    let (new_line, new_column) =
+                        rewrite.edit.map(line - rewrite.first_line, column);
+                    let offset: usize = rewrite.edit.lines[..new_line]
+                        .iter()
+                        .map(|l| l.len() + line_ending.len())
+                        .sum();
+                    return (rewrite.bytes.start as isize + shift) as usize + offset + new_column;
+                }
+                shift += rewrite.text.len() as isize - rewrite.bytes.len() as isize;
+            }
+            (pos as isize + shift) as usize
+        };
+
+        let primary_id = state.cursors.primary_id();
+        let mut restore = Vec::new();
+        let mut moves = Vec::new();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            // Recorded first, so undoing the batch puts the cursor back here
+            restore.push(Event::MoveCursor {
+                cursor_id,
+                old_position: cursor.position,
+                new_position: cursor.position,
+                old_anchor: cursor.anchor,
+                new_anchor: cursor.anchor,
+                old_sticky_column: cursor.sticky_column,
+                new_sticky_column: cursor.sticky_column,
+            });
+            moves.push(Event::MoveCursor {
+                cursor_id,
+                old_position: cursor.position,
+                new_position: map_position(cursor.position),
+                old_anchor: cursor.anchor,
+                new_anchor: cursor.anchor.map(map_position),
+                old_sticky_column: cursor.sticky_column,
+                new_sticky_column: cursor.sticky_column,
+            });
+        }
+
+        let state = self.active_state_mut();
+        let mut events = restore;
+        for rewrite in rewrites.into_iter().rev() {
+            if !rewrite.bytes.is_empty() {
+                events.push(Event::Delete {
+                    deleted_text: state.get_text_range(rewrite.bytes.start, rewrite.bytes.end),
+                    range: rewrite.bytes.clone(),
+                    cursor_id: primary_id,
+                });
+            }
+            if !rewrite.text.is_empty() {
+                events.push(Event::Insert {
+                    position: rewrite.bytes.start,
+                    text: rewrite.text,
+                    cursor_id: primary_id,
+                });
+            }
+        }
+        events.extend(moves);
+        self.apply_events_as_action(events, description.to_string());
+    }
+}
+
+/// Lines covered by each range
+///
+/// A selection ending at the start of a line doesn't include that line.
+fn line_blocks(buffer: &Buffer, ranges: impl Iterator<Item = Range<usize>>) -> Vec<Range<usize>> {
+    ranges
+        .map(|range| {
+            let first = buffer.line_of(range.start);
+            let mut last = buffer.line_of(range.end);
+            if last > first && range.end == buffer.line_start(last) {
+                last -= 1;
+            }
+            first..last + 1
+        })
+        .collect()
+}
+
+/// Line ranges in order, merged where they share a line
+///
+/// Ranges that only touch stay apart, so cursors on neighbouring lines each
+/// get their own rewrite.
+fn merge_overlapping(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(previous) if range.start < previous.end => {
+                previous.end = previous.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
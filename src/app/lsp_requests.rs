@@ -5,6 +5,7 @@
 //! - Go-to-definition
 //! - Hover documentation
 //! - Find references
+//! - Selection ranges
 //! - Signature help
 //! - Code actions
 //! - Rename operations
@@ -14,10 +15,14 @@ use std::io;
 
 use lsp_types::TextDocumentContentChangeEvent;
 
-use crate::model::event::{BufferId, Event};
+use crate::input::actions::expand_selection_events;
+use crate::input::keybindings::Action;
+use crate::model::cursor::Cursor;
+use crate::model::event::{BufferId, CursorId, Event};
 use crate::services::lsp::manager::detect_language;
 use crate::view::prompt::{Prompt, PromptType};
 
+use super::types::PendingSelectionRange;
 use super::{uri_to_path, Editor};
 
 impl Editor {
@@ -122,25 +127,52 @@ impl Editor {
             })
             .collect();
 
-        // Show the popup
-        use crate::model::event::{
-            PopupContentData, PopupData, PopupListItemData, PopupPositionData,
-        };
+        // Show the popup, with matching snippets and buffer words after the
+        // server's items
+        let mut list_items: Vec<crate::model::event::PopupListItemData> = popup_items
+            .into_iter()
+            .map(|item| crate::model::event::PopupListItemData {
+                text: item.text,
+                detail: item.detail,
+                icon: item.icon,
+                data: item.data,
+            })
+            .collect();
+        list_items.extend(self.snippet_completion_items());
+        // Buffer words the server didn't offer come last
+        let words: Vec<_> = self
+            .word_completion_items()
+            .into_iter()
+            .filter(|word| !list_items.iter().any(|item| item.text == word.text))
+            .collect();
+        list_items.extend(words);
+        self.show_completion_popup(list_items);
+
+        tracing::info!("Showing completion popup with {} items", items.len());
+
+        Ok(())
+    }
+
+    /// Show the completion popup, replacing one that is already open
+    pub(crate) fn show_completion_popup(
+        &mut self,
+        items: Vec<crate::model::event::PopupListItemData>,
+    ) {
+        use crate::model::event::{PopupContentData, PopupData, PopupPositionData};
+
+        let completion_open = self
+            .active_state()
+            .popups
+            .top()
+            .is_some_and(|popup| popup.title.as_deref() == Some("Completion"));
+        if completion_open {
+            self.hide_popup();
+        }
+
         let popup_data = PopupData {
             title: Some("Completion".to_string()),
             transient: false,
-            content: PopupContentData::List {
-                items: popup_items
-                    .into_iter()
-                    .map(|item| PopupListItemData {
-                        text: item.text,
-                        detail: item.detail,
-                        icon: item.icon,
-                        data: item.data,
-                    })
-                    .collect(),
-                selected: 0,
-            },
+            content: PopupContentData::List { items, selected: 0 },
             position: PopupPositionData::BelowCursor,
             width: 50,
             max_height: 15,
@@ -149,10 +181,6 @@ impl Editor {
 
         self.active_state_mut()
             .apply(&crate::model::event::Event::ShowPopup { popup: popup_data });
-
-        tracing::info!("Showing completion popup with {} items", items.len());
-
-        Ok(())
     }
 
     /// Handle LSP go-to-definition response
@@ -1118,6 +1146,120 @@ impl Editor {
         Ok(())
     }
 
+    /// Request LSP selection ranges for every cursor (ExpandSelection)
+    ///
+    /// Returns false if no language server is available for the buffer, in which
+    /// case the caller should expand using the syntax tree instead.
+    pub(crate) fn request_selection_range(&mut self) -> bool {
+        let buffer_id = self.active_buffer();
+        let state = self.active_state();
+        let cursors: Vec<(CursorId, Cursor)> = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| (cursor_id, *cursor))
+            .collect();
+        let positions: Vec<(u32, u32)> = cursors
+            .iter()
+            .map(|(_, cursor)| {
+                let (line, character) = state
+                    .buffer
+                    .position_to_lsp_position(cursor.selection_start());
+                (line as u32, character as u32)
+            })
+            .collect();
+        let request_id = self.next_lsp_request_id;
+
+        // Use helper to ensure didOpen is sent before the request
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                handle
+                    .selection_range(request_id, uri.clone(), positions)
+                    .is_ok()
+            })
+            .unwrap_or(false);
+
+        if sent {
+            self.next_lsp_request_id += 1;
+            self.pending_selection_range_request = Some(PendingSelectionRange {
+                request_id,
+                buffer_id,
+                cursors,
+            });
+        }
+        sent
+    }
+
+    /// Handle selection range response from LSP
+    pub(crate) fn handle_selection_range_response(
+        &mut self,
+        request_id: u64,
+        ranges: Option<Vec<lsp_types::SelectionRange>>,
+    ) -> io::Result<()> {
+        // Check if this response is for the current pending request
+        if self
+            .pending_selection_range_request
+            .as_ref()
+            .map(|pending| pending.request_id)
+            != Some(request_id)
+        {
+            tracing::debug!("Ignoring stale selection range response: {}", request_id);
+            return Ok(());
+        }
+        let Some(pending) = self.pending_selection_range_request.take() else {
+            return Ok(());
+        };
+
+        // Drop the response if the cursors moved while waiting for it
+        if pending.buffer_id != self.active_buffer() {
+            return Ok(());
+        }
+        let state = self.active_state_mut();
+        let unchanged = pending.cursors.len() == state.cursors.count()
+            && pending
+                .cursors
+                .iter()
+                .all(|(cursor_id, cursor)| state.cursors.get(*cursor_id) == Some(cursor));
+        if !unchanged {
+            tracing::debug!("Cursors moved, ignoring selection range response");
+            return Ok(());
+        }
+
+        // Server doesn't support selection ranges (or failed): use the syntax tree
+        let Some(ranges) = ranges.filter(|ranges| ranges.len() == pending.cursors.len()) else {
+            return self.apply_action_as_events(Action::ExpandSelection);
+        };
+
+        let mut targets = Vec::new();
+        for ((cursor_id, cursor), selection_range) in pending.cursors.iter().zip(&ranges) {
+            let selection = cursor
+                .selection_range()
+                .unwrap_or(cursor.position..cursor.position);
+
+            // The innermost range in the chain that is larger than the selection
+            let mut candidate = Some(selection_range);
+            while let Some(range) = candidate {
+                let start = state.buffer.lsp_position_to_byte(
+                    range.range.start.line as usize,
+                    range.range.start.character as usize,
+                );
+                let end = state.buffer.lsp_position_to_byte(
+                    range.range.end.line as usize,
+                    range.range.end.character as usize,
+                );
+                if start <= selection.start && end >= selection.end && end - start > selection.len()
+                {
+                    targets.push((*cursor_id, end, start));
+                    break;
+                }
+                candidate = range.parent.as_deref();
+            }
+        }
+
+        let events = expand_selection_events(state, targets);
+        self.apply_events_as_action(events, format!("{:?}", Action::ExpandSelection));
+        Ok(())
+    }
+
     /// Apply LSP text edits to a buffer and return the number of changes made.
     /// Edits are sorted in reverse order and applied as a batch.
     pub(crate) fn apply_lsp_text_edits(
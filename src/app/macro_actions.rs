@@ -0,0 +1,350 @@
+//! Macro actions for the Editor: persisting macros, saving edited macro
+//! buffers, repeated and per-line playback, and exporting a macro as a
+//! plugin command.
+//!
+//! Macros are kept in `macros.json` in the data directory, so they survive
+//! restarts. The file is rewritten whenever a macro is recorded or edited.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::input::keybindings::Action;
+
+use super::Editor;
+
+/// Most runs of "play macro until it fails", so a macro that never fails
+/// can't hang the editor
+const MAX_MACRO_RUNS: usize = 10_000;
+
+/// Read persisted macros, or none when the file is missing or unreadable
+pub(super) fn load_macros(path: &Path) -> HashMap<char, Vec<Action>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to read macros from {:?}: {}", path, e);
+            }
+            return HashMap::new();
+        }
+    };
+    match serde_json::from_str(&content) {
+        Ok(macros) => macros,
+        Err(e) => {
+            tracing::warn!("Failed to parse macros in {:?}: {}", path, e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Parse the actions of an edited macro buffer: a JSON array of actions,
+/// with `//` comment lines allowed
+pub(super) fn parse_macro_text(text: &str) -> Result<Vec<Action>, String> {
+    let json: String = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    serde_json::from_str(&json).map_err(|e| format!("Invalid macro: {}", e))
+}
+
+/// Whether `action` moves the cursor relative to where it is, so that not
+/// moving means it failed (as at the end of the buffer)
+fn is_relative_motion(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::MoveLeft
+            | Action::MoveRight
+            | Action::MoveUp
+            | Action::MoveDown
+            | Action::MoveWordLeft
+            | Action::MoveWordRight
+            | Action::MovePageUp
+            | Action::MovePageDown
+            | Action::SelectLeft
+            | Action::SelectRight
+            | Action::SelectUp
+            | Action::SelectDown
+            | Action::SelectWordLeft
+            | Action::SelectWordRight
+            | Action::FindNext
+            | Action::FindPrevious
+    )
+}
+
+/// Source of a plugin that registers `name` as a command playing `actions`
+fn macro_plugin_source(
+    key: char,
+    name: &str,
+    function: &str,
+    actions: &[Action],
+) -> Result<String, String> {
+    let actions = serde_json::to_string_pretty(actions)
+        .map_err(|e| format!("Failed to serialize macro: {}", e))?;
+    let name = serde_json::Value::String(name.to_string()).to_string();
+    let description = serde_json::Value::String(format!("Play exported macro '{}'", key));
+    Ok(format!(
+        "// Exported from macro '{key}'. Edit the actions to change what the command does.\n\
+         \n\
+         const actions = {actions};\n\
+         \n\
+         globalThis.{function} = function (): void {{\n  \
+           editor.executeActions(JSON.stringify(actions));\n\
+         }};\n\
+         \n\
+         editor.registerCommand({name}, {description}, \"{function}\", \"normal\", \"\");\n"
+    ))
+}
+
+impl Editor {
+    /// Write all macros to the macros file
+    pub(super) fn save_macros(&self) {
+        let path = self.dir_context.macros_path();
+        if let Err(e) = std::fs::create_dir_all(&self.dir_context.data_dir) {
+            tracing::warn!("Failed to create data directory: {}", e);
+            return;
+        }
+        let sorted: BTreeMap<&char, &Vec<Action>> = self.macros.iter().collect();
+        let result = serde_json::to_string_pretty(&sorted)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            tracing::warn!("Failed to save macros to {:?}: {}", path, e);
+        }
+    }
+
+    /// Store the actions in the active macro buffer as its macro
+    pub(super) fn save_macro_buffer(&mut self, key: char) {
+        let text = self.active_state().buffer.to_string().unwrap_or_default();
+        let actions = match parse_macro_text(&text) {
+            Ok(actions) => actions,
+            Err(message) => {
+                self.set_status_message(message);
+                return;
+            }
+        };
+        let action_count = actions.len();
+        self.macros.insert(key, actions);
+        self.save_macros();
+        self.active_state_mut().buffer.clear_modified();
+        self.active_event_log_mut().mark_saved();
+        self.set_status_message(format!("Macro '{}' saved ({} actions)", key, action_count));
+    }
+
+    /// Run `actions` once, stopping at the first one that fails
+    ///
+    /// An action fails when it returns an error, or when it is a relative
+    /// motion or search that leaves the cursor where it was. Returns whether
+    /// every action succeeded.
+    fn run_macro_actions(&mut self, actions: &[Action]) -> bool {
+        for action in actions {
+            let before = self.active_state().cursors.primary().position;
+            if self.handle_action(action.clone()).is_err() {
+                return false;
+            }
+            if is_relative_motion(action)
+                && self.active_state().cursors.primary().position == before
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The actions of macro `key`, or None after telling the user it's
+    /// missing or empty
+    fn macro_actions(&mut self, key: char) -> Option<Vec<Action>> {
+        match self.macros.get(&key) {
+            Some(actions) if !actions.is_empty() => Some(actions.clone()),
+            Some(_) => {
+                self.set_status_message(format!("Macro '{}' is empty", key));
+                None
+            }
+            None => {
+                self.set_status_message(format!("No macro recorded for '{}'", key));
+                None
+            }
+        }
+    }
+
+    /// Play macro `key` up to `count` times, stopping early when it fails
+    pub(super) fn play_macro_times(&mut self, key: char, count: usize) {
+        let Some(actions) = self.macro_actions(key) else {
+            return;
+        };
+        let was_recording = self.macro_recording.take();
+        let mut runs = 0;
+        let mut failed = false;
+        while runs < count && !failed {
+            runs += 1;
+            failed = !self.run_macro_actions(&actions);
+        }
+        self.macro_recording = was_recording;
+
+        if failed {
+            self.set_status_message(format!(
+                "Macro '{}' failed on run {} of {}",
+                key, runs, count
+            ));
+        } else {
+            self.set_status_message(format!("Played macro '{}' {} times", key, count));
+        }
+    }
+
+    /// Play macro `key` again and again until a run fails
+    pub(super) fn play_macro_until_failure(&mut self, key: char) {
+        let Some(actions) = self.macro_actions(key) else {
+            return;
+        };
+        let was_recording = self.macro_recording.take();
+        let mut runs = 0;
+        let mut failed = false;
+        while runs < MAX_MACRO_RUNS {
+            if !self.run_macro_actions(&actions) {
+                failed = true;
+                break;
+            }
+            runs += 1;
+        }
+        self.macro_recording = was_recording;
+
+        if failed {
+            self.set_status_message(format!("Macro '{}' failed on run {}", key, runs + 1));
+        } else {
+            self.set_status_message(format!(
+                "Stopped macro '{}' after {} runs",
+                key, MAX_MACRO_RUNS
+            ));
+        }
+    }
+
+    /// Play macro `key` once on each line of the selection, starting at the
+    /// beginning of the line; without a selection, on the cursor's line
+    ///
+    /// Lines added or removed by a run shift the lines still to come.
+    pub(super) fn play_macro_on_lines(&mut self, key: char) {
+        let Some(actions) = self.macro_actions(key) else {
+            return;
+        };
+        let state = self.active_state();
+        let cursor = state.cursors.primary();
+        let range = cursor
+            .selection_range()
+            .unwrap_or(cursor.position..cursor.position);
+        let first_line = state.buffer.line_of(range.start);
+        let mut end_line = state.buffer.line_of(range.end);
+        // A selection ending at the start of a line doesn't include that line
+        if end_line > first_line && range.end == state.buffer.line_start(end_line) {
+            end_line -= 1;
+        }
+
+        let was_recording = self.macro_recording.take();
+        let _ = self.handle_action(Action::RemoveSecondaryCursors);
+        let mut line = first_line as isize;
+        let mut end_line = end_line as isize;
+        let mut played = 0;
+        while line <= end_line {
+            let lines_before = self.active_state().buffer.last_line() as isize;
+            self.goto_line_col(line as usize + 1, None);
+            self.run_macro_actions(&actions);
+            played += 1;
+            let added = self.active_state().buffer.last_line() as isize - lines_before;
+            line = (line + 1 + added).max(0);
+            end_line += added;
+        }
+        self.macro_recording = was_recording;
+        self.set_status_message(format!("Played macro '{}' on {} lines", key, played));
+    }
+
+    /// Write a plugin that registers command `name` playing macro `key`
+    /// into the user plugins directory and load it
+    pub(super) fn export_macro(&mut self, key: char, name: &str) {
+        let Some(actions) = self.macro_actions(key) else {
+            return;
+        };
+        let slug: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let function = format!("macro_{}", slug);
+        let source = match macro_plugin_source(key, name, &function, &actions) {
+            Ok(source) => source,
+            Err(message) => {
+                self.set_status_message(message);
+                return;
+            }
+        };
+
+        let dir = self.dir_context.plugins_dir();
+        let path = dir.join(format!("{}.ts", function));
+        if let Err(e) = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, source)) {
+            self.set_status_message(format!("Failed to export macro: {}", e));
+            return;
+        }
+
+        if self.plugin_manager.is_active() {
+            if let Err(e) = self.plugin_manager.load_plugin(&path) {
+                self.set_status_message(format!(
+                    "Exported macro to {} but failed to load it: {}",
+                    path.display(),
+                    e
+                ));
+                return;
+            }
+        }
+        self.set_status_message(format!(
+            "Exported macro '{}' as command \"{}\" ({})",
+            key,
+            name,
+            path.display()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_macro_text_skips_comments() {
+        let text = "// Macro '1' (2 actions)\n// Save to update\n\n[\n  \"MoveDown\",\n  {\n    \"InsertChar\": \"x\"\n  }\n]\n";
+        assert_eq!(
+            parse_macro_text(text),
+            Ok(vec![Action::MoveDown, Action::InsertChar('x')])
+        );
+        assert!(parse_macro_text("[\"NotAnAction\"]").is_err());
+    }
+
+    #[test]
+    fn test_macros_round_trip_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("macros.json");
+        assert!(load_macros(&path).is_empty());
+
+        let mut macros = HashMap::new();
+        macros.insert('3', vec![Action::MoveLineEnd, Action::InsertChar(';')]);
+        std::fs::write(&path, serde_json::to_string(&macros).unwrap()).unwrap();
+        assert_eq!(load_macros(&path), macros);
+    }
+
+    #[test]
+    fn test_macro_plugin_source() {
+        let source = macro_plugin_source(
+            '1',
+            "Add \"Semicolon\"",
+            "macro_add__semicolon_",
+            &[Action::InsertChar(';')],
+        )
+        .unwrap();
+        assert!(source.contains("globalThis.macro_add__semicolon_ = function"));
+        assert!(source.contains("editor.executeActions(JSON.stringify(actions));"));
+        assert!(source.contains(
+            "editor.registerCommand(\"Add \\\"Semicolon\\\"\", \"Play exported macro '1'\", \"macro_add__semicolon_\", \"normal\", \"\");"
+        ));
+    }
+}
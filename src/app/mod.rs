@@ -1,6 +1,8 @@
 mod async_messages;
 mod buffer_management;
 mod clipboard;
+mod comment_actions;
+mod debug;
 mod file_explorer;
 pub mod file_open;
 mod file_open_input;
@@ -8,26 +10,43 @@ mod file_operations;
 mod help;
 mod input;
 mod input_dispatch;
+mod jump_hints;
+mod kill_ring_actions;
+mod line_actions;
 mod lsp_actions;
 mod lsp_requests;
+mod macro_actions;
 mod menu_actions;
 mod mouse_input;
+mod multi_cursor_actions;
+mod occur;
 mod on_save_actions;
 mod plugin_commands;
 mod popup_actions;
+mod project_search;
 mod prompt_actions;
 mod recovery_actions;
+mod rectangle_actions;
+mod reflow_actions;
 mod render;
+mod search_lines;
 pub mod session;
 mod settings_actions;
 mod shell_command;
+mod snippet_actions;
 mod split_actions;
+mod streaming_search;
+mod structural_search;
+mod surround_actions;
 mod terminal;
 mod terminal_input;
 mod toggle_actions;
+mod transform_actions;
 pub mod types;
 mod undo_actions;
+mod vi_actions;
 mod view_actions;
+mod word_completion;
 
 use std::path::Component;
 
@@ -65,7 +84,8 @@ pub(crate) fn normalize_path(path: &std::path::Path) -> std::path::PathBuf {
 
 use self::types::{
     Bookmark, CachedLayout, EventLineInfo, InteractiveReplaceState, LspMessageEntry,
-    LspProgressInfo, MacroRecordingState, MouseState, SearchState, DEFAULT_BACKGROUND_FILE,
+    LspProgressInfo, MacroRecordingState, MouseState, PendingSelectionRange, SearchState,
+    DEFAULT_BACKGROUND_FILE,
 };
 use crate::config::Config;
 use crate::config_io::DirectoryContext;
@@ -75,6 +95,7 @@ use crate::input::command_registry::CommandRegistry;
 use crate::input::commands::Suggestion;
 use crate::input::keybindings::{Action, KeyContext, KeybindingResolver};
 use crate::input::position_history::PositionHistory;
+use crate::input::vi::ViState;
 use crate::model::event::{Event, EventLog, SplitDirection, SplitId};
 use crate::services::async_bridge::{AsyncBridge, AsyncMessage};
 use crate::services::fs::{FsBackend, FsManager, LocalFsBackend};
@@ -82,6 +103,7 @@ use crate::services::lsp::manager::{detect_language, LspManager};
 use crate::services::plugins::api::{BufferSavedDiff, PluginCommand};
 use crate::services::plugins::PluginManager;
 use crate::services::recovery::{RecoveryConfig, RecoveryService};
+use crate::services::streaming_search::SearchDirection;
 use crate::services::time_source::{RealTimeSource, SharedTimeSource};
 use crate::state::EditorState;
 use crate::types::LspServerConfig;
@@ -157,6 +179,18 @@ pub struct Editor {
     /// Shared clipboard (handles both internal and system clipboard)
     clipboard: crate::services::clipboard::Clipboard,
 
+    /// Emacs-style kill ring of recent kills and copies
+    kill_ring: crate::input::kill_ring::KillRing,
+
+    /// The rectangle last killed or copied, one string per line
+    killed_rectangle: Vec<String>,
+
+    /// Recent copies and cuts for Paste from History (persisted in the session)
+    clipboard_history: crate::input::clipboard_history::ClipboardHistory,
+
+    /// Fields of the snippet being filled in, while tabbing through them
+    snippet_session: Option<crate::input::snippets::SnippetSession>,
+
     /// Should the editor quit?
     should_quit: bool,
 
@@ -271,6 +305,9 @@ pub struct Editor {
     /// Pending LSP signature help request ID (if any)
     pending_signature_help_request: Option<u64>,
 
+    /// Pending LSP selection range request (if any)
+    pending_selection_range_request: Option<PendingSelectionRange>,
+
     /// Pending LSP code actions request ID (if any)
     pending_code_actions_request: Option<u64>,
 
@@ -291,6 +328,33 @@ pub struct Editor {
     /// Search state (if search is active)
     search_state: Option<SearchState>,
 
+    /// Search or replace of a large-file buffer running in the background
+    streaming_search: Option<streaming_search::StreamingSearch>,
+
+    /// Project-wide search or replace running in the background
+    project_search: Option<project_search::PendingProjectSearch>,
+
+    /// Buffers showing project search results
+    project_results: HashMap<BufferId, project_search::ProjectResults>,
+
+    /// Structural search of the project running in the background
+    structural_search: Option<structural_search::PendingStructuralSearch>,
+
+    /// Project replaces that can be undone, oldest first
+    project_replace_history: Vec<project_search::ProjectReplace>,
+
+    /// Occur buffers and the source lines they show
+    occur_views: HashMap<BufferId, occur::OccurView>,
+
+    /// Lines of the buffer the Search Lines prompt is open over
+    search_lines: Option<search_lines::SearchLinesState>,
+
+    /// Targets labelled by the open jump hint prompt
+    jump_hints: Option<jump_hints::JumpHintState>,
+
+    /// Cursors before each multi-cursor operation, per buffer, for Cursor Undo
+    cursor_snapshots: HashMap<BufferId, Vec<multi_cursor_actions::CursorSnapshot>>,
+
     /// Search highlight namespace (for efficient bulk removal)
     search_namespace: crate::view::overlay::OverlayNamespace,
 
@@ -360,12 +424,32 @@ pub struct Editor {
     /// Bookmarks (character key -> bookmark)
     bookmarks: HashMap<char, Bookmark>,
 
+    /// Breakpoints by file (kept while no debugger runs, persisted in the session)
+    breakpoints: crate::services::dap::BreakpointStore,
+
+    /// Running debug adapter, if any
+    debug_adapter: Option<crate::services::dap::DapHandle>,
+
+    /// State of the running debug session
+    debug_session: Option<crate::services::dap::DebugSession>,
+
+    /// Debugger watch expressions (persisted in the session)
+    debug_watches: Vec<String>,
+
+    /// Split used to show the paused source location (never a debug panel split)
+    debug_source_split: Option<SplitId>,
+
+    /// Buffer holding the current-line highlight of the paused frame
+    debug_line_buffer: Option<BufferId>,
+
     /// Global search options (persist across searches)
     search_case_sensitive: bool,
     search_whole_word: bool,
     search_use_regex: bool,
     /// Whether to confirm each replacement (interactive/query-replace mode)
     search_confirm_each: bool,
+    /// Whether replacements take the case of the text they replace
+    search_preserve_case: bool,
 
     /// Macro storage (key -> list of recorded actions)
     macros: HashMap<char, Vec<Action>>,
@@ -376,6 +460,9 @@ pub struct Editor {
     /// Last recorded macro register (for F12 to replay)
     last_macro_register: Option<char>,
 
+    /// Buffers showing an editable macro (buffer -> macro register)
+    macro_buffers: HashMap<BufferId, char>,
+
     /// Pending plugin action receivers (for async action execution)
     #[cfg(feature = "plugins")]
     pending_plugin_actions: Vec<(
@@ -391,6 +478,9 @@ pub struct Editor {
     /// Stores the keys pressed so far in a chord sequence
     chord_state: Vec<(crossterm::event::KeyCode, crossterm::event::KeyModifiers)>,
 
+    /// Vi modal editing state, present while the active keymap is (or inherits) "vi"
+    vi: Option<ViState>,
+
     /// Pending LSP confirmation - language name awaiting user confirmation
     /// When Some, a confirmation popup is shown asking user to approve LSP spawn
     pending_lsp_confirmation: Option<String>,
@@ -609,6 +699,9 @@ impl Editor {
         );
 
         let keybindings = KeybindingResolver::new(&config);
+        let vi = config
+            .keymap_inherits(&config.active_keybinding_map, "vi")
+            .then(ViState::new);
 
         // Create an empty initial buffer
         let mut buffers = HashMap::new();
@@ -724,6 +817,12 @@ impl Editor {
                 );
             }
 
+            // User plugins (such as exported macros) load on top of the others
+            let user_plugin_dir = dir_context.plugins_dir();
+            if user_plugin_dir.exists() && !plugin_dirs.contains(&user_plugin_dir) {
+                plugin_dirs.push(user_plugin_dir);
+            }
+
             // Load from all found plugin directories
             for plugin_dir in plugin_dirs {
                 tracing::info!("Loading TypeScript plugins from: {:?}", plugin_dir);
@@ -775,6 +874,10 @@ impl Editor {
             background_fade: crate::primitives::ansi_background::DEFAULT_BACKGROUND_FADE,
             keybindings,
             clipboard: crate::services::clipboard::Clipboard::new(),
+            kill_ring: crate::input::kill_ring::KillRing::new(),
+            killed_rectangle: Vec::new(),
+            clipboard_history: crate::input::clipboard_history::ClipboardHistory::default(),
+            snippet_session: None,
             should_quit: false,
             restart_with_dir: None,
             status_message: None,
@@ -811,12 +914,22 @@ impl Editor {
             pending_references_request: None,
             pending_references_symbol: String::new(),
             pending_signature_help_request: None,
+            pending_selection_range_request: None,
             pending_code_actions_request: None,
             pending_inlay_hints_request: None,
             hover_symbol_range: None,
             hover_symbol_overlay: None,
             mouse_hover_screen_position: None,
             search_state: None,
+            streaming_search: None,
+            project_search: None,
+            project_results: HashMap::new(),
+            structural_search: None,
+            project_replace_history: Vec::new(),
+            occur_views: HashMap::new(),
+            search_lines: None,
+            jump_hints: None,
+            cursor_snapshots: HashMap::new(),
             search_namespace: crate::view::overlay::OverlayNamespace::from_string(
                 "search".to_string(),
             ),
@@ -860,18 +973,27 @@ impl Editor {
             stored_diagnostics: HashMap::new(),
             event_broadcaster: crate::model::control_event::EventBroadcaster::default(),
             bookmarks: HashMap::new(),
+            breakpoints: crate::services::dap::BreakpointStore::new(),
+            debug_adapter: None,
+            debug_session: None,
+            debug_watches: Vec::new(),
+            debug_source_split: None,
+            debug_line_buffer: None,
             search_case_sensitive: true,
             search_whole_word: false,
             search_use_regex: false,
             search_confirm_each: false,
-            macros: HashMap::new(),
+            search_preserve_case: false,
+            macros: macro_actions::load_macros(&dir_context.macros_path()),
             macro_recording: None,
             last_macro_register: None,
+            macro_buffers: HashMap::new(),
             #[cfg(feature = "plugins")]
             pending_plugin_actions: Vec::new(),
             #[cfg(feature = "plugins")]
             plugin_render_requested: false,
             chord_state: Vec::new(),
+            vi,
             pending_lsp_confirmation: None,
             pending_close_buffer: None,
             auto_revert_enabled: true,
@@ -1443,6 +1565,7 @@ impl Editor {
         let line_info = self.calculate_event_line_info(event);
 
         // 1. Apply the event to the buffer
+        self.adjust_snippet_session_for_event(event);
         self.active_state_mut().apply(event);
 
         // 1b. Sync cursors and viewport from EditorState to SplitViewState
@@ -1997,10 +2120,17 @@ impl Editor {
                 self.prompt = None;
                 // Also cancel interactive replace if active
                 self.interactive_replace_state = None;
+                self.clear_replace_preview();
                 // Clear search highlights from current buffer
                 let ns = self.search_namespace.clone();
                 let state = self.active_state_mut();
                 state.overlays.clear_namespace(&ns, &mut state.marker_list);
+            } else if prompt.prompt_type == PromptType::SearchLines {
+                self.prompt = None;
+                self.cancel_search_lines();
+            } else if prompt.prompt_type == PromptType::JumpHint {
+                self.prompt = None;
+                self.cancel_jump_hints();
             }
         }
     }
@@ -2142,8 +2272,25 @@ impl Editor {
                     self.search_history.reset_navigation();
                     self.clear_search_highlights();
                 }
+                PromptType::ProjectSearch
+                | PromptType::ProjectReplaceSearch
+                | PromptType::Occur
+                | PromptType::StructuralSearch { .. }
+                | PromptType::StructuralReplaceSearch { .. } => {
+                    self.search_history.reset_navigation();
+                }
                 PromptType::Replace { .. } | PromptType::QueryReplace { .. } => {
                     self.replace_history.reset_navigation();
+                    self.clear_replace_preview();
+                }
+                PromptType::ProjectReplace { .. } | PromptType::StructuralReplace { .. } => {
+                    self.replace_history.reset_navigation();
+                }
+                PromptType::SearchLines => {
+                    self.cancel_search_lines();
+                }
+                PromptType::JumpHint => {
+                    self.cancel_jump_hints();
                 }
                 PromptType::Plugin { custom_type } => {
                     // Fire plugin hook for prompt cancellation
@@ -2194,6 +2341,9 @@ impl Editor {
                     | PromptType::StopLspServer
                     | PromptType::SelectTheme
                     | PromptType::SwitchToTab
+                    | PromptType::BrowseKillRing
+                    | PromptType::PasteFromHistory
+                    | PromptType::InsertSnippet
             ) {
                 // Use the selected suggestion if any
                 if let Some(selected_idx) = prompt.selected_suggestion {
@@ -2237,12 +2387,22 @@ impl Editor {
 
             // Add to appropriate history based on prompt type
             match prompt.prompt_type {
-                PromptType::Search | PromptType::ReplaceSearch | PromptType::QueryReplaceSearch => {
+                PromptType::Search
+                | PromptType::ReplaceSearch
+                | PromptType::QueryReplaceSearch
+                | PromptType::ProjectSearch
+                | PromptType::ProjectReplaceSearch
+                | PromptType::Occur
+                | PromptType::StructuralSearch { .. }
+                | PromptType::StructuralReplaceSearch { .. } => {
                     self.search_history.push(final_input.clone());
                     // Reset navigation state
                     self.search_history.reset_navigation();
                 }
-                PromptType::Replace { .. } | PromptType::QueryReplace { .. } => {
+                PromptType::Replace { .. }
+                | PromptType::QueryReplace { .. }
+                | PromptType::ProjectReplace { .. }
+                | PromptType::StructuralReplace { .. } => {
                     self.replace_history.push(final_input.clone());
                     // Reset navigation state
                     self.replace_history.reset_navigation();
@@ -2332,10 +2492,32 @@ impl Editor {
                 // Reset history navigation when user types - allows Up to navigate history
                 self.search_history.reset_navigation();
             }
-            PromptType::Replace { .. } | PromptType::QueryReplace { .. } => {
+            PromptType::ProjectSearch
+            | PromptType::ProjectReplaceSearch
+            | PromptType::Occur
+            | PromptType::StructuralSearch { .. }
+            | PromptType::StructuralReplaceSearch { .. } => {
+                // Reset history navigation when user types - allows Up to navigate history
+                self.search_history.reset_navigation();
+            }
+            PromptType::Replace { search } | PromptType::QueryReplace { search } => {
+                // Preview the replacement of each visible match as user types
+                self.update_replace_preview(&search, &input);
                 // Reset history navigation when user types - allows Up to navigate history
                 self.replace_history.reset_navigation();
             }
+            PromptType::ProjectReplace { .. } | PromptType::StructuralReplace { .. } => {
+                // Reset history navigation when user types - allows Up to navigate history
+                self.replace_history.reset_navigation();
+            }
+            PromptType::SearchLines => {
+                // Rank the buffer's lines and preview the best match
+                self.update_search_lines(&input);
+            }
+            PromptType::JumpHint => {
+                // Label the targets, or act on a typed label
+                self.update_jump_hints(&input);
+            }
             PromptType::OpenFile | PromptType::SwitchProject => {
                 // For OpenFile/SwitchProject, update the file browser filter (native implementation)
                 self.update_file_open_filter();
@@ -2365,7 +2547,12 @@ impl Editor {
                     },
                 );
             }
-            PromptType::SwitchToTab | PromptType::SelectTheme | PromptType::StopLspServer => {
+            PromptType::SwitchToTab
+            | PromptType::SelectTheme
+            | PromptType::StopLspServer
+            | PromptType::BrowseKillRing
+            | PromptType::PasteFromHistory
+            | PromptType::InsertSnippet => {
                 // Filter suggestions using fuzzy matching
                 use crate::input::fuzzy::fuzzy_match;
 
@@ -2497,6 +2684,11 @@ impl Editor {
                         tracing::error!("Error handling references response: {}", e);
                     }
                 }
+                AsyncMessage::LspSelectionRange { request_id, ranges } => {
+                    if let Err(e) = self.handle_selection_range_response(request_id, ranges) {
+                        tracing::error!("Error handling selection range response: {}", e);
+                    }
+                }
                 AsyncMessage::LspSignatureHelp {
                     request_id,
                     signature_help,
@@ -2643,6 +2835,18 @@ impl Editor {
                     }
                     self.terminal_manager.close(terminal_id);
                 }
+                AsyncMessage::DapResponse {
+                    session_id,
+                    response,
+                } => {
+                    self.handle_dap_response(session_id, response);
+                }
+                AsyncMessage::DapEvent { session_id, event } => {
+                    self.handle_dap_event(session_id, event);
+                }
+                AsyncMessage::DapError { session_id, error } => {
+                    self.handle_dap_error(session_id, error);
+                }
             }
         }
 
@@ -2681,8 +2885,36 @@ impl Editor {
         let file_changes = self.poll_file_changes();
         let tree_changes = self.poll_file_tree_changes();
 
+        // Adopt finished background reparses and start new ones for edited buffers
+        let syntax_changes = self.poll_syntax_trees();
+
+        let search_changes = self.poll_streaming_search();
+        let project_search_changes = self.poll_project_search();
+        let structural_search_changes = self.poll_structural_search();
+        let occur_changes = self.poll_occur();
+
         // Trigger render if any async messages, plugin commands were processed, or plugin requested render
-        needs_render || processed_any_commands || plugin_render || file_changes || tree_changes
+        needs_render
+            || processed_any_commands
+            || plugin_render
+            || file_changes
+            || tree_changes
+            || syntax_changes
+            || search_changes
+            || project_search_changes
+            || structural_search_changes
+            || occur_changes
+    }
+
+    /// Update the full-document syntax trees of all buffers
+    ///
+    /// Returns true if any buffer got a newly parsed tree (requires re-render).
+    fn poll_syntax_trees(&mut self) -> bool {
+        let mut updated = false;
+        for state in self.buffers.values_mut() {
+            updated |= state.update_syntax_tree();
+        }
+        updated
     }
 
     /// Update LSP status bar string from active progress operations
@@ -3485,6 +3717,19 @@ impl Editor {
                     tracing::debug!("Unset custom context: {}", name);
                 }
             }
+
+            // ==================== Action Commands ====================
+            PluginCommand::ExecuteActions { actions } => {
+                // Playback shouldn't land in a macro being recorded
+                let was_recording = self.macro_recording.take();
+                for action in actions {
+                    if let Err(e) = self.handle_action(action) {
+                        tracing::warn!("Plugin action failed: {}", e);
+                        break;
+                    }
+                }
+                self.macro_recording = was_recording;
+            }
         }
         Ok(())
     }
@@ -4604,6 +4849,14 @@ mod tests {
             Action::from_str("toggle_comment", &args),
             Some(Action::ToggleComment)
         );
+        assert_eq!(
+            Action::from_str("toggle_block_comment", &args),
+            Some(Action::ToggleBlockComment)
+        );
+        assert_eq!(
+            Action::from_str("reflow_paragraph", &args),
+            Some(Action::ReflowParagraph)
+        );
         assert_eq!(
             Action::from_str("goto_matching_bracket", &args),
             Some(Action::GoToMatchingBracket)
@@ -4620,6 +4873,47 @@ mod tests {
             Action::from_str("toggle_search_whole_word", &args),
             Some(Action::ToggleSearchWholeWord)
         );
+        assert_eq!(
+            Action::from_str("project_search", &args),
+            Some(Action::ProjectSearch)
+        );
+        assert_eq!(
+            Action::from_str("undo_project_replace", &args),
+            Some(Action::UndoProjectReplace)
+        );
+        assert_eq!(Action::from_str("occur", &args), Some(Action::Occur));
+        assert_eq!(
+            Action::from_str("search_lines", &args),
+            Some(Action::SearchLines)
+        );
+        assert_eq!(
+            Action::from_str("structural_replace_buffer", &args),
+            Some(Action::StructuralReplaceBuffer)
+        );
+        assert_eq!(
+            Action::from_str("jump_to_two_chars", &args),
+            Some(Action::JumpToTwoChars)
+        );
+        assert_eq!(
+            Action::from_str("split_selection_into_lines", &args),
+            Some(Action::SplitSelectionIntoLines)
+        );
+        assert_eq!(
+            Action::from_str("cursor_undo", &args),
+            Some(Action::CursorUndo)
+        );
+        assert_eq!(
+            Action::from_str("kill_rectangle", &args),
+            Some(Action::KillRectangle)
+        );
+        assert_eq!(
+            Action::from_str("number_rectangle_lines", &args),
+            Some(Action::NumberRectangleLines)
+        );
+        assert_eq!(
+            Action::from_str("toggle_virtual_space", &args),
+            Some(Action::ToggleVirtualSpace)
+        );
 
         // Test bookmark actions with arguments
         let mut args_with_char = HashMap::new();
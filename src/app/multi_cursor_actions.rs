@@ -0,0 +1,354 @@
+//! Multi-cursor operations for the Editor
+//!
+//! Selecting every occurrence, skipping an occurrence, splitting selections
+//! into lines and adding cursors at regex matches change the cursors in one
+//! batch. They, and adding a single cursor, first remember the buffer's
+//! cursors, so Cursor Undo can bring back each earlier set in turn.
+//!
+//! Aligning cursors, rotating the selections' text and inserting a number
+//! sequence edit the buffer, each as one undoable action.
+
+use std::ops::Range;
+
+use crate::input::multi_cursor::{self, NumberSequence};
+use crate::model::cursor::Cursor;
+use crate::model::event::{CursorId, Event};
+use crate::primitives::visual_layout::visual_width;
+use crate::view::prompt::PromptType;
+
+use super::project_search::count_of;
+use super::transform_actions::Replacement;
+use super::Editor;
+
+/// Cursor sets remembered per buffer for Cursor Undo
+const MAX_CURSOR_SNAPSHOTS: usize = 50;
+
+/// A buffer's cursors before a multi-cursor operation, in document order
+pub(super) struct CursorSnapshot {
+    cursors: Vec<Cursor>,
+    primary: usize,
+}
+
+impl Editor {
+    /// Select every occurrence of the selection, or of the word under the
+    /// cursor
+    pub(super) fn select_all_matches(&mut self) {
+        match multi_cursor::select_all_matches(self.active_state_mut()) {
+            Ok(cursors) => {
+                let count = cursors.len();
+                self.replace_cursors(cursors, "Select all occurrences");
+                self.set_status_message(format!("Selected {}", count_of(count, "occurrence")));
+            }
+            Err(message) => self.set_status_message(message),
+        }
+    }
+
+    /// Move the newest selection on to the next occurrence not yet selected
+    pub(super) fn skip_to_next_match(&mut self) {
+        let cursor = match multi_cursor::skip_to_next_match(self.active_state_mut()) {
+            Ok(cursor) => cursor,
+            Err(message) => {
+                self.set_status_message(message);
+                return;
+            }
+        };
+        self.remember_cursors();
+        let state = self.active_state();
+        let old = state.cursors.primary();
+        let event = Event::MoveCursor {
+            cursor_id: state.cursors.primary_id(),
+            old_position: old.position,
+            new_position: cursor.position,
+            old_anchor: old.anchor,
+            new_anchor: cursor.anchor,
+            old_sticky_column: old.sticky_column,
+            new_sticky_column: 0,
+        };
+        self.apply_events_as_action(vec![event], "Skip occurrence".to_string());
+        self.set_status_message("Skipped to next occurrence".to_string());
+    }
+
+    /// Give each line of each selection a selection of its own
+    pub(super) fn split_selections_into_lines(&mut self) {
+        match multi_cursor::split_selections_into_lines(self.active_state_mut()) {
+            Ok(cursors) => {
+                let count = cursors.len();
+                self.replace_cursors(cursors, "Split selection into lines");
+                self.set_status_message(count_of(count, "cursor"));
+            }
+            Err(message) => self.set_status_message(message),
+        }
+    }
+
+    /// Select every match of `pattern` in the selections, or in the buffer
+    pub(super) fn add_cursors_at_regex(&mut self, pattern: &str) {
+        if pattern.is_empty() {
+            return;
+        }
+        match multi_cursor::regex_match_cursors(self.active_state_mut(), pattern) {
+            Ok(cursors) => {
+                let count = cursors.len();
+                self.replace_cursors(cursors, &format!("Add cursors at '{}'", pattern));
+                self.set_status_message(format!("Selected {}", count_of(count, "match")));
+            }
+            Err(message) => self.set_status_message(message),
+        }
+    }
+
+    /// Pad the text before the first cursor of each line with spaces, so
+    /// those cursors all reach the column of the rightmost one
+    pub(super) fn align_cursors(&mut self) {
+        if self.is_editing_disabled() {
+            self.set_status_message("Editing disabled in this buffer".to_string());
+            return;
+        }
+        let state = self.active_state_mut();
+        let mut cursors: Vec<(CursorId, usize)> = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| (cursor_id, cursor.selection_start()))
+            .collect();
+        cursors.sort_by_key(|(_, position)| *position);
+
+        // Column of the first cursor on each line
+        let mut firsts: Vec<(CursorId, usize, usize)> = Vec::new();
+        let mut last_line = None;
+        for (cursor_id, position) in cursors {
+            let (line, _) = state.buffer.position_to_line_col(position);
+            if last_line == Some(line) {
+                continue;
+            }
+            last_line = Some(line);
+            let line_start = state.buffer.line_start_offset(line).unwrap_or(position);
+            let before = state.get_text_range(line_start, position);
+            firsts.push((cursor_id, position, visual_width(&before, 0)));
+        }
+        if firsts.len() < 2 {
+            self.set_status_message("Need cursors on two or more lines to align".to_string());
+            return;
+        }
+
+        let column = firsts.iter().map(|(.., column)| *column).max().unwrap_or(0);
+        let replacements: Vec<Replacement> = firsts
+            .into_iter()
+            .filter(|(.., width)| *width < column)
+            .map(|(cursor_id, position, width)| Replacement {
+                cursor_id,
+                range: position..position,
+                text: " ".repeat(column - width),
+            })
+            .collect();
+        if replacements.is_empty() {
+            self.set_status_message("Cursors already aligned".to_string());
+            return;
+        }
+        self.apply_replacements(replacements, "Align cursors", false);
+        self.set_status_message(format!("Aligned cursors at column {}", column + 1));
+    }
+
+    /// Move the text of each selection into the next one, the last one's
+    /// into the first
+    pub(super) fn rotate_selections(&mut self) {
+        if self.is_editing_disabled() {
+            self.set_status_message("Editing disabled in this buffer".to_string());
+            return;
+        }
+        let state = self.active_state_mut();
+        let mut selections: Vec<(CursorId, Range<usize>)> = state
+            .cursors
+            .iter()
+            .filter_map(|(cursor_id, cursor)| Some((cursor_id, cursor.selection_range()?)))
+            .collect();
+        if selections.len() < 2 {
+            self.set_status_message("Need two or more selections to rotate".to_string());
+            return;
+        }
+        selections.sort_by_key(|(_, range)| range.start);
+        let count = selections.len();
+
+        let mut texts: Vec<String> = selections
+            .iter()
+            .map(|(_, range)| state.get_text_range(range.start, range.end))
+            .collect();
+        let old_texts = texts.clone();
+        texts.rotate_right(1);
+        let replacements = selections
+            .into_iter()
+            .zip(texts)
+            .zip(old_texts)
+            .filter(|((_, text), old_text)| text != old_text)
+            .map(|(((cursor_id, range), text), _)| Replacement {
+                cursor_id,
+                range,
+                text,
+            })
+            .collect();
+        self.apply_replacements(replacements, "Rotate selections", false);
+        self.set_status_message(format!("Rotated {}", count_of(count, "selection")));
+    }
+
+    /// Prompt for the regex to add cursors at
+    pub(super) fn start_add_cursors_at_regex_prompt(&mut self) {
+        self.start_prompt(
+            "Add cursors at regex: ".to_string(),
+            PromptType::AddCursorsAtRegex,
+        );
+    }
+
+    /// Prompt for the start and step of a number sequence
+    pub(super) fn start_insert_sequence_prompt(&mut self) {
+        if self.is_editing_disabled() {
+            self.set_status_message("Editing disabled in this buffer".to_string());
+            return;
+        }
+        self.start_prompt_with_initial_text(
+            "Insert sequence (start [step]): ".to_string(),
+            PromptType::InsertSequence,
+            "1".to_string(),
+        );
+    }
+
+    /// Replace each selection, or insert at each cursor, the numbers of the
+    /// sequence `input` describes, in document order
+    pub(super) fn insert_sequence(&mut self, input: &str) {
+        let sequence = match NumberSequence::parse(input) {
+            Ok(sequence) => sequence,
+            Err(message) => {
+                self.set_status_message(message);
+                return;
+            }
+        };
+        let mut targets: Vec<(CursorId, Range<usize>)> = self
+            .active_state()
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| {
+                let range = cursor
+                    .selection_range()
+                    .unwrap_or(cursor.position..cursor.position);
+                (cursor_id, range)
+            })
+            .collect();
+        targets.sort_by_key(|(_, range)| range.start);
+
+        let count = targets.len();
+        let replacements = targets
+            .into_iter()
+            .enumerate()
+            .map(|(index, (cursor_id, range))| Replacement {
+                cursor_id,
+                range,
+                text: sequence.nth(index),
+            })
+            .collect();
+        self.apply_replacements(replacements, "Insert sequence", false);
+        self.set_status_message(format!("Inserted {}", count_of(count, "number")));
+    }
+
+    /// Remember the active buffer's cursors for Cursor Undo
+    pub(super) fn remember_cursors(&mut self) {
+        let buffer_id = self.active_buffer();
+        let state = self.active_state();
+        let primary_id = state.cursors.primary_id();
+        let mut cursors: Vec<(CursorId, Cursor)> = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| (cursor_id, *cursor))
+            .collect();
+        cursors.sort_by_key(|(_, cursor)| cursor.selection_start());
+        let primary = cursors
+            .iter()
+            .position(|(cursor_id, _)| *cursor_id == primary_id)
+            .unwrap_or(0);
+
+        let snapshots = self.cursor_snapshots.entry(buffer_id).or_default();
+        snapshots.push(CursorSnapshot {
+            cursors: cursors.into_iter().map(|(_, cursor)| cursor).collect(),
+            primary,
+        });
+        if snapshots.len() > MAX_CURSOR_SNAPSHOTS {
+            snapshots.remove(0);
+        }
+    }
+
+    /// Bring back the cursors from before the last multi-cursor operation
+    pub(super) fn undo_cursor_operation(&mut self) {
+        let buffer_id = self.active_buffer();
+        let Some(snapshot) = self.cursor_snapshots.get_mut(&buffer_id).and_then(Vec::pop) else {
+            self.set_status_message("No cursor operation to undo".to_string());
+            return;
+        };
+        // The buffer may have shrunk since
+        let len = self.active_state().buffer.len();
+        let cursors: Vec<Cursor> = snapshot
+            .cursors
+            .into_iter()
+            .map(|mut cursor| {
+                cursor.position = cursor.position.min(len);
+                cursor.anchor = cursor.anchor.map(|anchor| anchor.min(len));
+                cursor
+            })
+            .collect();
+        let count = cursors.len();
+        self.set_cursors(cursors, snapshot.primary, "Cursor undo");
+        self.set_status_message(format!("Restored {}", count_of(count, "cursor")));
+    }
+
+    /// Remember the cursors, then replace them with `cursors`, the one
+    /// nearest the primary cursor becoming primary
+    fn replace_cursors(&mut self, cursors: Vec<Cursor>, description: &str) {
+        if cursors.is_empty() {
+            return;
+        }
+        self.remember_cursors();
+        let position = self.active_state().cursors.primary().position;
+        let primary = (0..cursors.len())
+            .min_by_key(|&index| cursors[index].position.abs_diff(position))
+            .unwrap_or(0);
+        self.set_cursors(cursors, primary, description);
+    }
+
+    /// Replace the active buffer's cursors with `cursors`, in one batch, with
+    /// `cursors[primary]` becoming the primary cursor
+    fn set_cursors(&mut self, cursors: Vec<Cursor>, primary: usize, description: &str) {
+        let state = self.active_state();
+        let primary_id = state.cursors.primary_id();
+        let old = state.cursors.primary();
+
+        // The primary cursor takes the place of another new cursor, if any,
+        // so the new primary can be added last and become primary
+        let reused = (0..cursors.len())
+            .find(|&index| index != primary)
+            .unwrap_or(primary);
+        let mut events = vec![Event::MoveCursor {
+            cursor_id: primary_id,
+            old_position: old.position,
+            new_position: cursors[reused].position,
+            old_anchor: old.anchor,
+            new_anchor: cursors[reused].anchor,
+            old_sticky_column: old.sticky_column,
+            new_sticky_column: 0,
+        }];
+        for (cursor_id, cursor) in state.cursors.iter() {
+            if cursor_id != primary_id {
+                events.push(Event::RemoveCursor {
+                    cursor_id,
+                    position: cursor.position,
+                    anchor: cursor.anchor,
+                });
+            }
+        }
+        let next_id = state.cursors.next_id().0;
+        let added = (0..cursors.len())
+            .filter(|&index| index != reused && index != primary)
+            .chain((primary != reused).then_some(primary));
+        for (offset, index) in added.enumerate() {
+            events.push(Event::AddCursor {
+                cursor_id: CursorId(next_id + offset),
+                position: cursors[index].position,
+                anchor: cursors[index].anchor,
+            });
+        }
+        self.apply_events_as_action(events, description.to_string());
+    }
+}
@@ -0,0 +1,394 @@
+//! Occur for the Editor
+//!
+//! Occur lists every line of a buffer matching a regex in an `*Occur*`
+//! buffer, opened in a split below the source. The list is scanned again
+//! whenever the source changes (large files in the background, see
+//! `services::occur`), except while the occur buffer has unsaved edits.
+//!
+//! Each listed line carries its source line number and offset as text
+//! properties, which Enter follows. Saving the occur buffer writes edited
+//! lines back to the source as one undoable edit.
+
+use std::sync::atomic::AtomicBool;
+
+use ratatui::style::Style;
+use regex::bytes::Regex;
+use serde_json::json;
+
+use crate::model::event::{BufferId, Event, EventLog, SplitDirection};
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::occur::{
+    header, line_at, line_prefix, number_width, parse_edits, scan, OccurJob, OccurScan,
+};
+use crate::services::project_search::changed_span;
+use crate::services::streaming_search::{build_regex, WINDOW_SIZE};
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+use crate::view::split::SplitViewState;
+
+use super::Editor;
+
+/// Overlay namespace for matches in occur buffers
+const OCCUR_NAMESPACE: &str = "occur";
+
+/// The lines an occur buffer lists
+pub(super) struct OccurView {
+    source: BufferId,
+    query: String,
+    regex: Regex,
+    /// Source version `scan` was taken from
+    version: u64,
+    scan: OccurScan,
+    /// Background scan of a large-file source, with the version it scans
+    job: Option<(u64, OccurJob)>,
+}
+
+fn line_count(count: usize) -> String {
+    format!("{} line{}", count, if count == 1 { "" } else { "s" })
+}
+
+impl Editor {
+    /// List the lines of the active buffer matching `query` in its occur
+    /// buffer, opening one if needed
+    pub(super) fn show_occur(&mut self, query: &str) {
+        if query.is_empty() {
+            self.set_status_message("Occur cancelled.".to_string());
+            return;
+        }
+        let regex = match build_regex(
+            query,
+            self.search_case_sensitive,
+            self.search_whole_word,
+            self.search_use_regex,
+        ) {
+            Ok(regex) => regex,
+            Err(e) => {
+                self.set_status_message(format!("Invalid regex: {}", e));
+                return;
+            }
+        };
+
+        let active = self.active_buffer();
+        // Occur from an occur buffer lists its source again
+        let source = self
+            .occur_views
+            .get(&active)
+            .map_or(active, |view| view.source);
+        let existing = self
+            .occur_views
+            .iter()
+            .find(|(id, view)| view.source == source && self.buffers.contains_key(id))
+            .map(|(&id, _)| id);
+        let occur_id = existing.unwrap_or_else(|| self.open_occur_buffer());
+        self.occur_views.insert(
+            occur_id,
+            OccurView {
+                source,
+                query: query.to_string(),
+                regex,
+                // Versions start at 1, so the first poll can't skip the scan
+                version: 0,
+                scan: OccurScan::default(),
+                job: None,
+            },
+        );
+        self.rescan_occur(occur_id);
+        if self.occur_views[&occur_id].job.is_some() {
+            self.set_status_message(format!("Scanning for '{}'...", query));
+        }
+
+        match self.split_manager.splits_for_buffer(occur_id).first() {
+            Some(&split_id) => self.focus_split(split_id, occur_id),
+            None => self.set_active_buffer(occur_id),
+        }
+    }
+
+    /// Create an occur buffer in a new split below the active one
+    fn open_occur_buffer(&mut self) -> BufferId {
+        let source_split = self.split_manager.active_split();
+        let buffer_id =
+            self.create_virtual_buffer("*Occur*".to_string(), "occur".to_string(), false);
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.margins.set_line_numbers(false);
+        }
+        // create_virtual_buffer opened it as a tab of the source split
+        if let Some(view_state) = self.split_view_states.get_mut(&source_split) {
+            view_state.remove_buffer(buffer_id);
+        }
+
+        self.save_current_split_view_state();
+        match self
+            .split_manager
+            .split_active(SplitDirection::Horizontal, buffer_id, 0.6)
+        {
+            Ok(occur_split) => {
+                let mut view_state = SplitViewState::with_buffer(
+                    self.terminal_width,
+                    self.terminal_height,
+                    buffer_id,
+                );
+                view_state.viewport.line_wrap_enabled = self.config.editor.line_wrap;
+                self.split_view_states.insert(occur_split, view_state);
+            }
+            Err(e) => {
+                tracing::error!("Failed to create occur split: {}", e);
+            }
+        }
+        buffer_id
+    }
+
+    /// Scan the source of an occur buffer again: in the background for
+    /// large files, right away for everything else
+    fn rescan_occur(&mut self, occur_id: BufferId) {
+        let Some(view) = self.occur_views.get(&occur_id) else {
+            return;
+        };
+        let Some(state) = self.buffers.get(&view.source) else {
+            return;
+        };
+        let version = state.buffer.version();
+        let mut snapshot = state.buffer.content_snapshot();
+        let regex = view.regex.clone();
+        if state.buffer.is_large_file() {
+            let view = self.occur_views.get_mut(&occur_id).unwrap();
+            view.job = Some((version, OccurJob::start(snapshot, regex)));
+            return;
+        }
+        let result = scan(&mut snapshot, &regex, WINDOW_SIZE, &AtomicBool::new(false));
+        self.finish_occur_scan(occur_id, version, result);
+    }
+
+    fn finish_occur_scan(
+        &mut self,
+        occur_id: BufferId,
+        version: u64,
+        result: std::io::Result<OccurScan>,
+    ) {
+        let Some(view) = self.occur_views.get_mut(&occur_id) else {
+            return;
+        };
+        view.version = version;
+        view.job = None;
+        match result {
+            Ok(scan) => view.scan = scan,
+            Err(e) => {
+                self.set_status_message(format!("Occur failed: {}", e));
+                return;
+            }
+        }
+        self.render_occur(occur_id);
+    }
+
+    /// Fill an occur buffer with its lines and highlight their matches
+    fn render_occur(&mut self, occur_id: BufferId) {
+        let view = &self.occur_views[&occur_id];
+        let source_name = self
+            .buffer_metadata
+            .get(&view.source)
+            .map(|metadata| metadata.display_name.clone())
+            .unwrap_or_default();
+        let header = header(&view.query, &source_name, &view.scan);
+        let width = number_width(&view.scan.lines);
+
+        let mut pos = header.len();
+        let mut entries = vec![TextPropertyEntry::text(header)];
+        let mut highlights = Vec::new();
+        for line in &view.scan.lines {
+            let prefix = line_prefix(line, width);
+            let text_start = pos + prefix.len();
+            highlights.extend(
+                line.matches
+                    .iter()
+                    .map(|m| text_start + m.start..text_start + m.end),
+            );
+            let text = format!("{}{}\n", prefix, line.text);
+            pos += text.len();
+            entries.push(
+                TextPropertyEntry::text(text)
+                    .with_property("line", json!(line.line))
+                    .with_property("offset", json!(line.offset)),
+            );
+        }
+        if let Err(e) = self.set_virtual_buffer_content(occur_id, entries) {
+            tracing::error!("Failed to update occur buffer: {}", e);
+            return;
+        }
+        // Undoing edits made to the old list would garble the new one
+        self.event_logs.insert(occur_id, EventLog::new());
+
+        let style = Style::default()
+            .fg(self.theme.search_match_fg)
+            .bg(self.theme.search_match_bg);
+        let namespace = OverlayNamespace::from_string(OCCUR_NAMESPACE.to_string());
+        let state = self.buffers.get_mut(&occur_id).unwrap();
+        state
+            .overlays
+            .clear_namespace(&namespace, &mut state.marker_list);
+        for range in highlights {
+            let overlay = Overlay::with_namespace(
+                &mut state.marker_list,
+                range,
+                OverlayFace::Style { style },
+                namespace.clone(),
+            )
+            .with_priority_value(10);
+            state.overlays.add(overlay);
+        }
+    }
+
+    /// Keep occur buffers in step with their sources
+    ///
+    /// Returns true if anything changed (requires re-render).
+    pub(super) fn poll_occur(&mut self) -> bool {
+        if self.occur_views.is_empty() {
+            return false;
+        }
+        self.occur_views
+            .retain(|occur_id, _| self.buffers.contains_key(occur_id));
+
+        let mut changed = false;
+        let occur_ids: Vec<BufferId> = self.occur_views.keys().copied().collect();
+        for occur_id in occur_ids {
+            // Leave the list alone while it has edits waiting to be saved
+            if self.buffers[&occur_id].buffer.is_modified() {
+                continue;
+            }
+            let view = &self.occur_views[&occur_id];
+            if let Some((version, job)) = &view.job {
+                if let Some(result) = job.poll() {
+                    let version = *version;
+                    self.finish_occur_scan(occur_id, version, result);
+                    changed = true;
+                }
+                continue;
+            }
+            let stale = self
+                .buffers
+                .get(&view.source)
+                .is_some_and(|source| source.buffer.version() != view.version);
+            if stale {
+                self.rescan_occur(occur_id);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Enter in an occur buffer: go to the source line on the cursor's line
+    pub(super) fn occur_goto(&mut self) {
+        let occur_id = self.active_buffer();
+        let Some(view) = self.occur_views.get(&occur_id) else {
+            self.set_status_message("Not an occur buffer".to_string());
+            return;
+        };
+        let source = view.source;
+        // Text properties aren't moved by edits, so once the list has been
+        // edited its lines are matched by their line number instead
+        let offset = if self.active_state().buffer.is_modified() {
+            let buffer = &self.active_state().buffer;
+            let line = buffer.line_of(self.active_state().cursors.primary().position);
+            let (start, end) = (buffer.line_start(line), buffer.line_end(line));
+            let row = self.active_state_mut().get_text_range(start, end);
+            line_at(&self.occur_views[&occur_id].scan.lines, &row).map(|line| line.offset)
+        } else {
+            self.get_text_properties_at_cursor().and_then(|props| {
+                props
+                    .iter()
+                    .find_map(|p| p.get("offset").and_then(|v| v.as_u64()))
+                    .map(|offset| offset as usize)
+            })
+        };
+        let Some(offset) = offset else {
+            self.set_status_message("No occur line here".to_string());
+            return;
+        };
+        let Some(len) = self.buffers.get(&source).map(|state| state.buffer.len()) else {
+            self.set_status_message("The occur source buffer was closed".to_string());
+            return;
+        };
+
+        match self.split_manager.splits_for_buffer(source).first() {
+            Some(&split_id) => self.focus_split(split_id, source),
+            None => self.set_active_buffer(source),
+        }
+        self.move_cursor_to_match(offset.min(len));
+    }
+
+    /// Write the lines edited in an occur buffer back to its source
+    pub(super) fn save_occur_edits(&mut self, occur_id: BufferId) {
+        let text = self.buffers[&occur_id]
+            .buffer
+            .to_string()
+            .unwrap_or_default();
+        let view = &self.occur_views[&occur_id];
+        let source = view.source;
+        let edits = parse_edits(&view.scan.lines, &text);
+        if edits.is_empty() {
+            self.set_status_message("No occur lines changed".to_string());
+            return;
+        }
+        let Some(state) = self.buffers.get_mut(&source) else {
+            self.set_status_message("The occur source buffer was closed".to_string());
+            return;
+        };
+
+        let cursor_id = state.cursors.primary_id();
+        let mut changes = Vec::new();
+        for edit in &edits {
+            // The line must still hold its old text and nothing more
+            let current = state
+                .buffer
+                .get_text_range_mut(edit.offset, edit.old_text.len() + 1)
+                .unwrap_or_default();
+            let unchanged = current.starts_with(edit.old_text.as_bytes())
+                && matches!(current.get(edit.old_text.len()), None | Some(b'\n' | b'\r'));
+            if unchanged {
+                changes.push(edit);
+            }
+        }
+        let skipped = edits.len() - changes.len();
+
+        // Applied from the end of the buffer back, so offsets stay valid
+        let mut events = Vec::new();
+        for edit in changes.iter().rev() {
+            let (range, new_text) = changed_span(&edit.old_text, &edit.new_text);
+            let start = edit.offset + range.start;
+            if !range.is_empty() {
+                events.push(Event::Delete {
+                    range: start..edit.offset + range.end,
+                    deleted_text: edit.old_text[range].to_string(),
+                    cursor_id,
+                });
+            }
+            if !new_text.is_empty() {
+                events.push(Event::Insert {
+                    position: start,
+                    text: new_text.to_string(),
+                    cursor_id,
+                });
+            }
+        }
+        if !events.is_empty() {
+            let batch = Event::Batch {
+                events,
+                description: "Occur edit".to_string(),
+            };
+            if let Err(e) = self.apply_rename_batch_to_buffer(source, batch) {
+                self.set_status_message(format!("Failed to apply occur edits: {}", e));
+                return;
+            }
+        }
+
+        let mut message = format!("Changed {}", line_count(changes.len()));
+        if skipped > 0 {
+            message.push_str(&format!(
+                "; skipped {} changed since the list was made",
+                line_count(skipped)
+            ));
+        }
+        self.set_status_message(message);
+        if let Some(state) = self.buffers.get_mut(&occur_id) {
+            state.buffer.clear_modified();
+        }
+        self.rescan_occur(occur_id);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! This module contains handlers for popup-related actions like confirmation and cancellation.
 
+use super::snippet_actions::{SNIPPET_CHOICE_TITLE, SNIPPET_ICON};
 use super::Editor;
 use crate::model::event::Event;
 use crate::primitives::word_navigation::find_completion_word_start;
@@ -41,11 +42,28 @@ impl Editor {
             return PopupConfirmResult::EarlyReturn;
         }
 
+        // A snippet choice replaces the current snippet field
+        let snippet_choice = self.active_state().popups.top().and_then(|popup| {
+            if popup.title.as_deref() == Some(SNIPPET_CHOICE_TITLE) {
+                popup.selected_item().and_then(|item| item.data.clone())
+            } else {
+                None
+            }
+        });
+        if let Some(choice) = snippet_choice {
+            self.hide_popup();
+            self.choose_snippet_choice(choice);
+            return PopupConfirmResult::EarlyReturn;
+        }
+
         // If it's a completion popup, insert the selected item
-        let completion_text = if let Some(popup) = self.active_state().popups.top() {
+        let completion = if let Some(popup) = self.active_state().popups.top() {
             if let Some(title) = &popup.title {
                 if title == "Completion" {
-                    popup.selected_item().and_then(|item| item.data.clone())
+                    popup.selected_item().and_then(|item| {
+                        let is_snippet = item.icon.as_deref() == Some(SNIPPET_ICON);
+                        item.data.clone().map(|data| (data, is_snippet))
+                    })
                 } else {
                     None
                 }
@@ -57,8 +75,14 @@ impl Editor {
         };
 
         // Perform the completion if we have text
-        if let Some(text) = completion_text {
-            self.insert_completion_text(text);
+        match completion {
+            Some((body, true)) => {
+                self.hide_popup();
+                self.insert_snippet_completion(&body);
+                return PopupConfirmResult::EarlyReturn;
+            }
+            Some((text, false)) => self.insert_completion_text(text),
+            None => {}
         }
 
         self.hide_popup();
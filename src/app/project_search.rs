@@ -0,0 +1,570 @@
+//! Project-wide search and replace for the Editor
+//!
+//! Searches run on a background thread over the working directory (see
+//! `services::project_search`), and their results open in a
+//! `*Project Search*` buffer. Saving that buffer writes edited result lines
+//! back to their files. Replace in Project replaces every match in every
+//! file the search found.
+//!
+//! Files open in a buffer are changed through the buffer, which is saved
+//! afterwards only if it had no unsaved changes before. Each set of changes
+//! is kept as a transaction that Undo Last Project Replace reverts, as long
+//! as the files haven't been changed since.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use regex::bytes::Regex;
+
+use crate::model::event::{BufferId, Event, EventLog};
+use crate::services::project_search::{
+    apply_line_edits, changed_span, format_results, parse_edits, replace_all, result_at_line,
+    summary, LineEdit, ProjectFiles, ProjectSearch, ResultLine, MAX_RESULT_LINES,
+};
+use crate::services::streaming_search::build_regex;
+use crate::state::EditorState;
+use crate::view::prompt::PromptType;
+
+use super::types::{BufferKind, BufferMetadata};
+use super::Editor;
+
+/// A project search running in the background
+pub(super) struct PendingProjectSearch {
+    receiver: Receiver<Vec<ResultLine>>,
+    cancelled: Arc<AtomicBool>,
+    root: PathBuf,
+    query: String,
+    regex: Regex,
+    /// Text to replace every match with, for Replace in Project
+    replacement: Option<String>,
+    /// Whether `$1`-style references in the replacement are expanded
+    expand: bool,
+}
+
+impl Drop for PendingProjectSearch {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The search shown in a results buffer
+pub(super) struct ProjectResults {
+    root: PathBuf,
+    results: Vec<ResultLine>,
+}
+
+/// A file's text before and after a project replace
+pub(super) struct FileChange {
+    pub(super) path: PathBuf,
+    pub(super) before: String,
+    pub(super) after: String,
+}
+
+/// Changes made to files by one project replace or results write-back
+pub(super) struct ProjectReplace {
+    description: String,
+    files: Vec<FileChange>,
+}
+
+pub(super) fn count_of(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+impl Editor {
+    /// Prompt for a project search, filled in with the selection or the
+    /// last search
+    pub(super) fn start_project_search_prompt(&mut self, message: String, prompt_type: PromptType) {
+        let selection_range = self.active_state().cursors.primary().selection_range();
+        let selected_text = selection_range
+            .map(|range| {
+                self.active_state_mut()
+                    .get_text_range(range.start, range.end)
+            })
+            .filter(|text| !text.contains('\n') && !text.is_empty());
+        let from_history = selected_text.is_none();
+        let initial_text =
+            selected_text.or_else(|| self.search_history.last().map(|s| s.to_string()));
+
+        self.start_prompt(message, prompt_type);
+        if let Some(text) = initial_text {
+            if let Some(prompt) = self.prompt.as_mut() {
+                prompt.set_input(text);
+                prompt.selection_anchor = Some(0);
+            }
+        }
+        if from_history {
+            self.search_history.init_at_last();
+        }
+    }
+
+    /// Search the project for `query` in the background, then show the
+    /// results, or replace every match with `replacement` if given
+    pub(super) fn start_project_search(&mut self, query: &str, replacement: Option<String>) {
+        self.project_search = None;
+        if query.is_empty() {
+            self.set_status_message("Search cancelled.".to_string());
+            return;
+        }
+        let regex = match build_regex(
+            query,
+            self.search_case_sensitive,
+            self.search_whole_word,
+            self.search_use_regex,
+        ) {
+            Ok(regex) => regex,
+            Err(e) => {
+                self.set_status_message(format!("Invalid regex: {}", e));
+                return;
+            }
+        };
+
+        let files = self.project_files();
+        let root = files.root.clone();
+        let search = ProjectSearch {
+            files,
+            regex: regex.clone(),
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = cancelled.clone();
+        thread::spawn(move || {
+            let results = search.run(&worker_cancelled);
+            let _ = sender.send(results);
+        });
+        self.project_search = Some(PendingProjectSearch {
+            receiver,
+            cancelled,
+            root,
+            query: query.to_string(),
+            regex,
+            replacement,
+            expand: self.search_use_regex,
+        });
+        self.set_status_message(format!("Searching project for '{}'...", query));
+    }
+
+    /// The files of the working directory to search, with the text of
+    /// open buffers that have unsaved changes
+    pub(super) fn project_files(&self) -> ProjectFiles {
+        let root = self
+            .working_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.working_dir.clone());
+        let unsaved = self
+            .buffers
+            .values()
+            .filter(|state| state.buffer.is_modified())
+            .filter_map(|state| {
+                let path = state.buffer.file_path()?.to_path_buf();
+                Some((path, state.buffer.to_string()?))
+            })
+            .collect();
+        ProjectFiles {
+            root,
+            respect_gitignore: self.config.file_explorer.respect_gitignore,
+            ignore_patterns: self.config.file_explorer.custom_ignore_patterns.clone(),
+            unsaved,
+        }
+    }
+
+    /// Stop a running project search or structural search, if any
+    pub(super) fn cancel_project_search(&mut self) {
+        let structural = self.structural_search.take().is_some();
+        if self.project_search.take().is_some() || structural {
+            self.set_status_message("Project search cancelled.".to_string());
+        }
+    }
+
+    /// Take in the results of a running project search
+    ///
+    /// Returns true if anything changed (requires re-render).
+    pub(super) fn poll_project_search(&mut self) -> bool {
+        let Some(pending) = &self.project_search else {
+            return false;
+        };
+        let results = match pending.receiver.try_recv() {
+            Ok(results) => results,
+            Err(TryRecvError::Empty) => return false,
+            Err(TryRecvError::Disconnected) => {
+                self.project_search = None;
+                self.set_status_message("Project search failed".to_string());
+                return true;
+            }
+        };
+        let pending = self.project_search.take().unwrap();
+        if results.is_empty() {
+            self.set_status_message(format!(
+                "No matches found for '{}' in project",
+                pending.query
+            ));
+        } else if let Some(replacement) = &pending.replacement {
+            self.replace_in_project(&pending, replacement, &results);
+        } else {
+            self.show_project_results(pending.root.clone(), &pending.query, results);
+        }
+        true
+    }
+
+    /// Open a results buffer for a finished search for `query`
+    pub(super) fn show_project_results(
+        &mut self,
+        root: PathBuf,
+        query: &str,
+        results: Vec<ResultLine>,
+    ) {
+        let content = format_results(&root, query, &results);
+        let mut message = format!("Found {} for '{}'", summary(&results), query);
+        if results.len() >= MAX_RESULT_LINES {
+            message.push_str(&format!(" (showing the first {} lines)", MAX_RESULT_LINES));
+        }
+
+        let buffer_id = BufferId(self.next_buffer_id);
+        self.next_buffer_id += 1;
+        let threshold = self.config.editor.large_file_threshold_bytes as usize;
+        let mut state = EditorState::new(self.terminal_width, self.terminal_height, threshold);
+        state.buffer = crate::model::buffer::Buffer::from_str(&content, threshold);
+        self.buffers.insert(buffer_id, state);
+        self.event_logs.insert(buffer_id, EventLog::new());
+        self.buffer_metadata.insert(
+            buffer_id,
+            BufferMetadata {
+                kind: BufferKind::Virtual {
+                    mode: "project-search".to_string(),
+                },
+                display_name: "*Project Search*".to_string(),
+                lsp_enabled: false,
+                lsp_disabled_reason: Some("Virtual project search buffer".to_string()),
+                read_only: false, // Saving writes edited lines back to their files
+                binary: false,
+                lsp_opened_with: std::collections::HashSet::new(),
+            },
+        );
+        self.project_results
+            .insert(buffer_id, ProjectResults { root, results });
+
+        self.set_active_buffer(buffer_id);
+        self.set_status_message(message);
+    }
+
+    /// Whether `buffer_id` shows project search results
+    pub(super) fn is_project_results_buffer(&self, buffer_id: BufferId) -> bool {
+        self.project_results.contains_key(&buffer_id)
+    }
+
+    /// Open the file of the result on the cursor's line in a results buffer
+    pub(super) fn open_project_search_result(&mut self) {
+        let buffer_id = self.active_buffer();
+        if !self.is_project_results_buffer(buffer_id) {
+            self.set_status_message("Not a project search results buffer".to_string());
+            return;
+        }
+        let buffer = &self.active_state().buffer;
+        let line = buffer.line_of(self.active_state().cursors.primary().position);
+        let (start, end) = (buffer.line_start(line), buffer.line_end(line));
+        let text = self.active_state_mut().get_text_range(start, end);
+
+        let results = &self.project_results[&buffer_id];
+        let Some(result) = result_at_line(&results.root, &results.results, &text) else {
+            self.set_status_message("No search result on this line".to_string());
+            return;
+        };
+        let (path, line) = (result.path.clone(), result.line + 1);
+        match self.open_file(&path) {
+            Ok(_) => self.goto_line_col(line, None),
+            Err(e) => {
+                self.set_status_message(format!("Failed to open {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    /// Write the result lines edited in a results buffer back to their files
+    pub(super) fn save_project_results(&mut self, buffer_id: BufferId) {
+        let text = self.buffers[&buffer_id]
+            .buffer
+            .to_string()
+            .unwrap_or_default();
+        let results = &self.project_results[&buffer_id];
+        let edits = parse_edits(&results.root, &results.results, &text);
+        if edits.is_empty() {
+            self.set_status_message("No result lines changed".to_string());
+            return;
+        }
+
+        let mut files = Vec::new();
+        let mut written: Vec<&LineEdit> = Vec::new();
+        let mut skipped = 0;
+        for group in edits.chunk_by(|a, b| a.path == b.path) {
+            let path = &group[0].path;
+            let group: Vec<&LineEdit> = group.iter().collect();
+            let Some(before) = self.project_file_text(path) else {
+                skipped += group.len();
+                continue;
+            };
+            let Some(after) = apply_line_edits(&before, &group) else {
+                skipped += group.len();
+                continue;
+            };
+            match self.set_project_file_text(path, &before, &after) {
+                Ok(true) => {
+                    written.extend(&group);
+                    files.push(FileChange {
+                        path: path.clone(),
+                        before,
+                        after,
+                    });
+                }
+                Ok(false) => skipped += group.len(),
+                Err(e) => {
+                    tracing::warn!("Failed to write {}: {}", path.display(), e);
+                    skipped += group.len();
+                }
+            }
+        }
+
+        // The written lines are what the files hold now
+        if let Some(results) = self.project_results.get_mut(&buffer_id) {
+            for edit in &written {
+                if let Some(result) = results
+                    .results
+                    .iter_mut()
+                    .find(|result| result.path == edit.path && result.line == edit.line)
+                {
+                    result.text = edit.new_text.clone();
+                }
+            }
+        }
+        let mut message = format!(
+            "Wrote {} to {}",
+            count_of(written.len(), "line"),
+            count_of(files.len(), "file")
+        );
+        if skipped == 0 {
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.buffer.clear_modified();
+            }
+            if let Some(event_log) = self.event_logs.get_mut(&buffer_id) {
+                event_log.mark_saved();
+            }
+        } else {
+            message.push_str(&format!(
+                ", skipped {} that changed since the search",
+                count_of(skipped, "line")
+            ));
+        }
+        if !files.is_empty() {
+            self.project_replace_history.push(ProjectReplace {
+                description: "Edit search results".to_string(),
+                files,
+            });
+        }
+        self.set_status_message(message);
+    }
+
+    /// Replace every match of a finished search in the files it found
+    fn replace_in_project(
+        &mut self,
+        pending: &PendingProjectSearch,
+        replacement: &str,
+        results: &[ResultLine],
+    ) {
+        let mut paths: Vec<&PathBuf> = results.iter().map(|result| &result.path).collect();
+        paths.dedup();
+
+        let mut changes = Vec::new();
+        let mut counts = Vec::new();
+        let mut failed = 0;
+        for path in paths {
+            let Some(before) = self.project_file_text(path) else {
+                failed += 1;
+                continue;
+            };
+            let (after, count) = replace_all(&before, &pending.regex, replacement, pending.expand);
+            if count > 0 {
+                changes.push(FileChange {
+                    path: path.clone(),
+                    before,
+                    after,
+                });
+                counts.push(count);
+            }
+        }
+        let description = format!("Replace '{}' with '{}'", pending.query, replacement);
+        let made = self.apply_file_changes(description, changes);
+        let replaced: usize = counts
+            .iter()
+            .zip(&made)
+            .filter(|(_, made)| **made)
+            .map(|(count, _)| count)
+            .sum();
+        let changed = made.iter().filter(|made| **made).count();
+        failed += made.len() - changed;
+
+        let mut message = format!(
+            "Replaced {} in {}",
+            count_of(replaced, "occurrence"),
+            count_of(changed, "file")
+        );
+        if failed > 0 {
+            message.push_str(&format!(", failed to change {}", count_of(failed, "file")));
+        }
+        if results.len() >= MAX_RESULT_LINES {
+            message.push_str(&format!(
+                " (search stopped at {} lines; run it again for the rest)",
+                MAX_RESULT_LINES
+            ));
+        }
+        self.set_status_message(message);
+    }
+
+    /// Change each file from its `before` to its `after` text, keeping the
+    /// changes made as one transaction for Undo Last Project Replace
+    ///
+    /// Returns whether each change was made: files whose text is no longer
+    /// `before` are left alone.
+    pub(super) fn apply_file_changes(
+        &mut self,
+        description: String,
+        changes: Vec<FileChange>,
+    ) -> Vec<bool> {
+        let mut made = Vec::new();
+        let mut files = Vec::new();
+        for change in changes {
+            match self.set_project_file_text(&change.path, &change.before, &change.after) {
+                Ok(true) => {
+                    made.push(true);
+                    files.push(change);
+                }
+                Ok(false) => made.push(false),
+                Err(e) => {
+                    tracing::warn!("Failed to write {}: {}", change.path.display(), e);
+                    made.push(false);
+                }
+            }
+        }
+        if !files.is_empty() {
+            self.project_replace_history
+                .push(ProjectReplace { description, files });
+        }
+        made
+    }
+
+    /// Revert the files changed by the last project replace or results
+    /// write-back, skipping any changed since
+    pub(super) fn undo_project_replace(&mut self) {
+        let Some(transaction) = self.project_replace_history.pop() else {
+            self.set_status_message("No project replace to undo".to_string());
+            return;
+        };
+        let mut restored = 0;
+        let mut skipped = 0;
+        for change in transaction.files.iter().rev() {
+            match self.set_project_file_text(&change.path, &change.after, &change.before) {
+                Ok(true) => restored += 1,
+                Ok(false) => skipped += 1,
+                Err(e) => {
+                    tracing::warn!("Failed to restore {}: {}", change.path.display(), e);
+                    skipped += 1;
+                }
+            }
+        }
+        let mut message = format!(
+            "Undid {}: restored {}",
+            transaction.description,
+            count_of(restored, "file")
+        );
+        if skipped > 0 {
+            message.push_str(&format!(
+                ", skipped {} changed since",
+                count_of(skipped, "file")
+            ));
+        }
+        self.set_status_message(message);
+    }
+
+    /// Text of a file as the editor has it: the open buffer's text if it's
+    /// open, else the text on disk
+    fn project_file_text(&self, path: &Path) -> Option<String> {
+        match self.buffer_for_path(path) {
+            Some(buffer_id) => self.buffers[&buffer_id].buffer.to_string(),
+            None => std::fs::read_to_string(path).ok(),
+        }
+    }
+
+    /// Change a file's text from `expected` to `text`
+    ///
+    /// Open files are changed in their buffer as one undoable edit, and the
+    /// buffer is saved if it had no unsaved changes. Returns false, changing
+    /// nothing, if the file's text isn't `expected`.
+    fn set_project_file_text(
+        &mut self,
+        path: &Path,
+        expected: &str,
+        text: &str,
+    ) -> std::io::Result<bool> {
+        let Some(buffer_id) = self.buffer_for_path(path) else {
+            if std::fs::read_to_string(path)? != expected {
+                return Ok(false);
+            }
+            std::fs::write(path, text)?;
+            return Ok(true);
+        };
+
+        let state = &self.buffers[&buffer_id];
+        if state.buffer.to_string().as_deref() != Some(expected) {
+            return Ok(false);
+        }
+        let was_modified = state.buffer.is_modified();
+        let cursor_id = state.cursors.primary_id();
+        let (range, new_text) = changed_span(expected, text);
+        let mut events = Vec::new();
+        if !range.is_empty() {
+            events.push(Event::Delete {
+                range: range.clone(),
+                deleted_text: expected[range.clone()].to_string(),
+                cursor_id,
+            });
+        }
+        if !new_text.is_empty() {
+            events.push(Event::Insert {
+                position: range.start,
+                text: new_text.to_string(),
+                cursor_id,
+            });
+        }
+        if events.is_empty() {
+            return Ok(true);
+        }
+        self.apply_rename_batch_to_buffer(
+            buffer_id,
+            Event::Batch {
+                events,
+                description: "Project replace".to_string(),
+            },
+        )?;
+        if !was_modified {
+            self.save_buffer_by_id(buffer_id)?;
+        }
+        Ok(true)
+    }
+
+    /// Save a buffer that may not be the active one
+    fn save_buffer_by_id(&mut self, buffer_id: BufferId) -> std::io::Result<()> {
+        let state = self.buffers.get_mut(&buffer_id).unwrap();
+        state.buffer.save()?;
+        let path = state.buffer.file_path().map(Path::to_path_buf);
+        if let Some(event_log) = self.event_logs.get_mut(&buffer_id) {
+            event_log.mark_saved();
+        }
+        if let Some(path) = path {
+            if let Ok(mtime) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+                self.file_mod_times.insert(path, mtime);
+            }
+        }
+        let _ = self.delete_buffer_recovery(buffer_id);
+        Ok(())
+    }
+}
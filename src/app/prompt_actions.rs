@@ -7,6 +7,7 @@ use super::BufferId;
 use super::BufferMetadata;
 use super::Editor;
 use crate::input::keybindings::Action;
+use crate::primitives::line_ops::SortOptions;
 use crate::services::plugins::hooks::HookArgs;
 use crate::view::prompt::PromptType;
 use std::path::Path;
@@ -79,6 +80,7 @@ impl Editor {
                 );
             }
             PromptType::Replace { search } => {
+                self.clear_replace_preview();
                 if self.search_confirm_each {
                     self.start_interactive_replace(&search, &input);
                 } else {
@@ -95,12 +97,44 @@ impl Editor {
                 );
             }
             PromptType::QueryReplace { search } => {
+                self.clear_replace_preview();
                 if self.search_confirm_each {
                     self.start_interactive_replace(&search, &input);
                 } else {
                     self.perform_replace(&search, &input);
                 }
             }
+            PromptType::ProjectSearch => {
+                self.start_project_search(&input, None);
+            }
+            PromptType::ProjectReplaceSearch => {
+                self.start_prompt(
+                    format!("Replace '{}' in project with: ", input),
+                    PromptType::ProjectReplace {
+                        search: input.clone(),
+                    },
+                );
+            }
+            PromptType::ProjectReplace { search } => {
+                self.start_project_search(&search, Some(input));
+            }
+            PromptType::Occur => self.show_occur(&input),
+            PromptType::StructuralSearch { in_buffer } => {
+                self.start_structural_search(&input, None, in_buffer);
+            }
+            PromptType::StructuralReplaceSearch { in_buffer } => {
+                let scope = if in_buffer { "" } else { " in project" };
+                self.start_prompt(
+                    format!("Replace '{}'{} with: ", input, scope),
+                    PromptType::StructuralReplace {
+                        pattern: input.clone(),
+                        in_buffer,
+                    },
+                );
+            }
+            PromptType::StructuralReplace { pattern, in_buffer } => {
+                self.start_structural_search(&pattern, Some(input), in_buffer);
+            }
             PromptType::Command => {
                 let commands = self.command_registry.read().unwrap().get_all();
                 if let Some(cmd) = commands.iter().find(|c| c.name == input) {
@@ -156,6 +190,50 @@ impl Editor {
             PromptType::PlayMacro => {
                 self.handle_register_input(&input, |editor, c| editor.play_macro(c), "Macro");
             }
+            PromptType::EditMacro => {
+                self.handle_register_input(
+                    &input,
+                    |editor, c| editor.show_macro_in_buffer(c),
+                    "Macro",
+                );
+            }
+            PromptType::PlayMacroOnLines => {
+                self.handle_register_input(
+                    &input,
+                    |editor, c| editor.play_macro_on_lines(c),
+                    "Macro",
+                );
+            }
+            PromptType::PlayMacroTimes => {
+                let count = input.trim().get(1..).unwrap_or("").trim();
+                match count.parse::<usize>() {
+                    Ok(count) if count > 0 => self.handle_register_input(
+                        &input,
+                        |editor, c| editor.play_macro_times(c, count),
+                        "Macro",
+                    ),
+                    _ => self.set_status_message(format!("Invalid repeat count: '{}'", count)),
+                }
+            }
+            PromptType::PlayMacroUntilFailure => {
+                self.handle_register_input(
+                    &input,
+                    |editor, c| editor.play_macro_until_failure(c),
+                    "Macro",
+                );
+            }
+            PromptType::ExportMacro => {
+                let name = input.trim().get(1..).unwrap_or("").trim().to_string();
+                if name.is_empty() {
+                    self.set_status_message("Command name required".to_string());
+                } else {
+                    self.handle_register_input(
+                        &input,
+                        |editor, c| editor.export_macro(c, &name),
+                        "Macro",
+                    );
+                }
+            }
             PromptType::SetBookmark => {
                 self.handle_register_input(&input, |editor, c| editor.set_bookmark(c), "Bookmark");
             }
@@ -240,6 +318,71 @@ impl Editor {
                     self.switch_to_tab(BufferId(id));
                 }
             }
+            PromptType::SearchLines => self.confirm_search_lines(selected_index),
+            PromptType::JumpHint => self.confirm_jump_hint(&input),
+            PromptType::AddCursorsAtRegex => self.add_cursors_at_regex(&input),
+            PromptType::InsertSequence => self.insert_sequence(&input),
+            PromptType::FillRectangle => self.fill_rectangle(&input),
+            PromptType::InsertRectangleText => self.insert_rectangle_text(&input),
+            PromptType::NumberRectangleLines => self.number_rectangle_lines(&input),
+            PromptType::BrowseKillRing => {
+                if let Ok(index) = input.trim().parse::<usize>() {
+                    if self.is_editing_disabled() {
+                        self.set_status_message("Editing disabled in this buffer".to_string());
+                    } else {
+                        self.yank_entry(index);
+                    }
+                }
+            }
+            PromptType::PasteFromHistory => {
+                if let Ok(index) = input.trim().parse::<usize>() {
+                    if self.is_editing_disabled() {
+                        self.set_status_message("Editing disabled in this buffer".to_string());
+                    } else {
+                        self.paste_from_history(index);
+                    }
+                }
+            }
+            PromptType::InsertSnippet => {
+                if let Ok(index) = input.trim().parse::<usize>() {
+                    if self.is_editing_disabled() {
+                        self.set_status_message("Editing disabled in this buffer".to_string());
+                    } else {
+                        self.insert_snippet_by_index(index);
+                    }
+                }
+            }
+            PromptType::SurroundAdd => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                } else {
+                    self.surround_add(&input);
+                }
+            }
+            PromptType::SurroundChange => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                } else {
+                    self.surround_change(&input);
+                }
+            }
+            PromptType::JoinLinesSeparator => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                } else {
+                    self.join_lines(&input);
+                }
+            }
+            PromptType::SortLinesPattern => {
+                if self.is_editing_disabled() {
+                    self.set_status_message("Editing disabled in this buffer".to_string());
+                } else if !input.is_empty() {
+                    self.sort_lines(&SortOptions {
+                        pattern: Some(input),
+                        ..Default::default()
+                    });
+                }
+            }
             PromptType::QueryReplaceConfirm => {
                 // This is handled by InsertChar, not PromptConfirm
                 // But if somehow Enter is pressed, treat it as skip (n)
@@ -256,6 +399,18 @@ impl Editor {
             PromptType::ShellCommand { replace } => {
                 self.handle_shell_command(&input, replace);
             }
+            PromptType::SetBreakpointCondition { path, line } => {
+                self.set_breakpoint_condition(&path, line, &input);
+            }
+            PromptType::SetLogpointMessage { path, line } => {
+                self.set_logpoint_message(&path, line, &input);
+            }
+            PromptType::DebugAddWatch => {
+                self.debug_add_watch(&input);
+            }
+            PromptType::DebugEvaluate => {
+                self.debug_evaluate(&input);
+            }
         }
         PromptResult::Done
     }
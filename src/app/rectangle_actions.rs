@@ -0,0 +1,223 @@
+//! Rectangle operations for the Editor
+//!
+//! Each works on the primary cursor's rectangle: its block selection, or the
+//! rectangle its selection spans from corner to corner. Killing or copying
+//! a rectangle keeps its lines apart from the kill ring, and yanking puts
+//! them back one per line, in the cursor's column.
+
+use crate::input::multi_cursor::NumberSequence;
+use crate::input::rectangle::{self, ColumnEdit, Rectangle};
+use crate::primitives::display_width::str_width;
+use crate::view::prompt::PromptType;
+
+use super::project_search::count_of;
+use super::Editor;
+
+impl Editor {
+    /// Delete the text of the rectangle, closing up each line
+    pub(super) fn delete_rectangle(&mut self) {
+        let Some(rect) = self.editable_rectangle() else {
+            return;
+        };
+        self.replace_rectangle(rect, |_| String::new(), "Delete rectangle");
+        self.set_status_message(format!(
+            "Deleted rectangle of {}",
+            count_of(rect.lines().count(), "line")
+        ));
+    }
+
+    /// Delete the rectangle, keeping its text for Yank Rectangle
+    pub(super) fn kill_rectangle(&mut self) {
+        let Some(rect) = self.editable_rectangle() else {
+            return;
+        };
+        self.killed_rectangle = rectangle::extract(self.active_state(), &rect);
+        self.replace_rectangle(rect, |_| String::new(), "Kill rectangle");
+        self.set_status_message(format!(
+            "Killed rectangle of {}",
+            count_of(rect.lines().count(), "line")
+        ));
+    }
+
+    /// Keep the rectangle's text for Yank Rectangle
+    pub(super) fn copy_rectangle(&mut self) {
+        let state = self.active_state();
+        let Some(rect) = Rectangle::of_cursor(state, state.cursors.primary()) else {
+            self.set_status_message("No rectangle selected".to_string());
+            return;
+        };
+        let lines = rectangle::extract(state, &rect);
+        self.killed_rectangle = lines;
+        self.set_status_message(format!(
+            "Copied rectangle of {}",
+            count_of(rect.lines().count(), "line")
+        ));
+    }
+
+    /// Insert the last killed or copied rectangle with its top left corner
+    /// at the cursor, adding lines at the end of the buffer if it needs them
+    pub(super) fn yank_rectangle(&mut self) {
+        if self.is_editing_disabled() {
+            self.set_status_message("Editing disabled in this buffer".to_string());
+            return;
+        }
+        if self.killed_rectangle.is_empty() {
+            self.set_status_message("No rectangle to yank".to_string());
+            return;
+        }
+        let lines = self.killed_rectangle.clone();
+        let state = self.active_state();
+        let cursor = state.cursors.primary();
+        let top = state.buffer.position_to_line_col(cursor.position).0;
+        let column = rectangle::cursor_column(state, cursor);
+
+        let edits: Vec<ColumnEdit> = lines
+            .iter()
+            .enumerate()
+            .map(|(offset, text)| ColumnEdit {
+                line: top + offset,
+                left: column,
+                right: column,
+                text: text.clone(),
+            })
+            .collect();
+        let bottom = top + lines.len() - 1;
+        let end = column + str_width(&lines[lines.len() - 1]);
+        let events = rectangle::edit_events(state, &edits, &[(bottom, end)], 0);
+        self.apply_events_as_action(events, "Yank rectangle".to_string());
+        self.set_status_message(format!(
+            "Yanked rectangle of {}",
+            count_of(lines.len(), "line")
+        ));
+    }
+
+    /// Prompt for the text to fill the rectangle with
+    pub(super) fn start_fill_rectangle_prompt(&mut self) {
+        if self.editable_rectangle().is_some() {
+            self.start_prompt(
+                "Fill rectangle with: ".to_string(),
+                PromptType::FillRectangle,
+            );
+        }
+    }
+
+    /// Replace the text of the rectangle on each line with `text`
+    pub(super) fn fill_rectangle(&mut self, text: &str) {
+        let Some(rect) = self.editable_rectangle() else {
+            return;
+        };
+        self.replace_rectangle(rect, |_| text.to_string(), "Fill rectangle");
+        self.set_status_message(format!("Filled {}", count_of(rect.lines().count(), "line")));
+    }
+
+    /// Prompt for the text to insert on each line of the rectangle
+    pub(super) fn start_insert_rectangle_text_prompt(&mut self) {
+        if self.editable_rectangle().is_some() {
+            self.start_prompt(
+                "Insert on each line: ".to_string(),
+                PromptType::InsertRectangleText,
+            );
+        }
+    }
+
+    /// Insert `text` at the rectangle's left edge on each of its lines
+    pub(super) fn insert_rectangle_text(&mut self, text: &str) {
+        let Some(rect) = self.editable_rectangle() else {
+            return;
+        };
+        self.insert_at_rectangle(rect, |_| text.to_string(), "Insert on rectangle lines");
+    }
+
+    /// Prompt for the start and step of the line numbers
+    pub(super) fn start_number_rectangle_lines_prompt(&mut self) {
+        if self.editable_rectangle().is_some() {
+            self.start_prompt_with_initial_text(
+                "Number lines (start [step]): ".to_string(),
+                PromptType::NumberRectangleLines,
+                "1".to_string(),
+            );
+        }
+    }
+
+    /// Insert the numbers of the sequence `input` describes at the
+    /// rectangle's left edge, right-aligned and followed by a space
+    pub(super) fn number_rectangle_lines(&mut self, input: &str) {
+        let sequence = match NumberSequence::parse(input) {
+            Ok(sequence) => sequence,
+            Err(message) => {
+                self.set_status_message(message);
+                return;
+            }
+        };
+        let Some(rect) = self.editable_rectangle() else {
+            return;
+        };
+        let numbers: Vec<String> = (0..rect.lines().count())
+            .map(|index| sequence.nth(index))
+            .collect();
+        let width = numbers.iter().map(|number| number.len()).max().unwrap_or(0);
+        self.insert_at_rectangle(
+            rect,
+            |index| format!("{:>width$} ", numbers[index], width = width),
+            "Number rectangle lines",
+        );
+    }
+
+    /// The primary cursor's rectangle, if there is one and the buffer can be
+    /// edited; otherwise says why not
+    fn editable_rectangle(&mut self) -> Option<Rectangle> {
+        if self.is_editing_disabled() {
+            self.set_status_message("Editing disabled in this buffer".to_string());
+            return None;
+        }
+        let state = self.active_state();
+        let rect = Rectangle::of_cursor(state, state.cursors.primary());
+        if rect.is_none() {
+            self.set_status_message("No rectangle selected".to_string());
+        }
+        rect
+    }
+
+    /// Replace the rectangle's columns on its `index`th line with
+    /// `text(index)`, leaving the cursor after the text on the last line
+    fn replace_rectangle(
+        &mut self,
+        rect: Rectangle,
+        text: impl Fn(usize) -> String,
+        description: &str,
+    ) {
+        let edits: Vec<ColumnEdit> = rect
+            .lines()
+            .enumerate()
+            .map(|(index, line)| ColumnEdit {
+                line,
+                left: rect.left,
+                right: rect.right,
+                text: text(index),
+            })
+            .collect();
+        let last = edits.last().map_or(0, |edit| str_width(&edit.text));
+        let state = self.active_state();
+        let events = rectangle::edit_events(state, &edits, &[(rect.bottom, rect.left + last)], 0);
+        self.apply_events_as_action(events, description.to_string());
+    }
+
+    /// Insert `text(index)` at the rectangle's left edge on its `index`th
+    /// line, leaving the cursor after the text on the last line
+    fn insert_at_rectangle(
+        &mut self,
+        rect: Rectangle,
+        text: impl Fn(usize) -> String,
+        description: &str,
+    ) {
+        let rect = Rectangle {
+            right: rect.left,
+            ..rect
+        };
+        self.replace_rectangle(rect, text, description);
+        self.set_status_message(format!(
+            "Inserted on {}",
+            count_of(rect.lines().count(), "line")
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+//! Reflow actions for the Editor: rewrap paragraphs at the wrap column, and
+//! break lines while typing in languages with `hard_wrap` set.
+//!
+//! The wrap column is the compose width when one is set, else the language's
+//! `wrap_column`, else `editor.wrap_column`.
+
+use std::ops::Range;
+
+use crate::config::LanguageConfig;
+use crate::model::buffer::Buffer;
+use crate::model::event::CursorId;
+use crate::primitives::comment::CommentTokens;
+use crate::primitives::reflow;
+use crate::services::lsp::manager::detect_language;
+
+use super::transform_actions::Replacement;
+use super::Editor;
+
+/// Most lines looked at on each side of the cursor for its paragraph
+const MAX_PARAGRAPH_LINES: usize = 1000;
+
+/// Comment markers that lines of a paragraph may start with
+fn comment_markers(tokens: &CommentTokens) -> Vec<String> {
+    let mut markers: Vec<String> = tokens.line.iter().cloned().collect();
+    // The lines of a `/* ... */` comment conventionally start with ` * `
+    if tokens
+        .block
+        .as_ref()
+        .is_some_and(|(start, _)| start.ends_with('*'))
+    {
+        markers.push("*".to_string());
+    }
+    markers
+}
+
+fn is_blank_line(buffer: &Buffer, line: usize) -> bool {
+    buffer
+        .get_line(line)
+        .is_none_or(|bytes| bytes.iter().all(u8::is_ascii_whitespace))
+}
+
+impl Editor {
+    /// Settings of the active buffer's language, if it has any
+    pub(super) fn active_language_config(&self) -> Option<&LanguageConfig> {
+        let path = self.active_state().buffer.file_path()?;
+        let language = detect_language(path, &self.config.languages)?;
+        self.config.languages.get(&language)
+    }
+
+    /// Column that reflow and hard wrapping break lines at
+    fn wrap_column(&self) -> usize {
+        let active_split = self.split_manager.active_split();
+        if let Some(width) = self
+            .split_view_states
+            .get(&active_split)
+            .and_then(|view_state| view_state.compose_width)
+        {
+            return width as usize;
+        }
+        self.active_language_config()
+            .and_then(|config| config.wrap_column)
+            .unwrap_or(self.config.editor.wrap_column)
+    }
+
+    /// Comment markers of the language at `pos`
+    fn comment_markers_at(&mut self, pos: usize) -> Vec<String> {
+        comment_markers(&self.comment_tokens_at(pos))
+    }
+
+    /// Lines around `pos` up to the nearest blank lines
+    fn lines_around(&self, pos: usize) -> Range<usize> {
+        let buffer = &self.active_state().buffer;
+        let line = buffer.line_of(pos);
+        let mut first = line;
+        while first > 0 && line - first < MAX_PARAGRAPH_LINES && !is_blank_line(buffer, first - 1) {
+            first -= 1;
+        }
+        let last_line = buffer.last_line();
+        let mut last = line;
+        while last < last_line
+            && last - line < MAX_PARAGRAPH_LINES
+            && !is_blank_line(buffer, last + 1)
+        {
+            last += 1;
+        }
+        buffer.line_start(first)..buffer.line_end(last)
+    }
+
+    /// Rewrap the paragraph around each cursor, or the paragraphs in each
+    /// selection, at the wrap column
+    pub(super) fn reflow_paragraph(&mut self) {
+        let width = self.wrap_column();
+        let tab_size = self.active_state().tab_size;
+        let state = self.active_state();
+        let mut targets: Vec<(CursorId, Range<usize>)> = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| {
+                let range = cursor
+                    .selection_range()
+                    .unwrap_or(cursor.position..cursor.position);
+                (cursor_id, range)
+            })
+            .collect();
+        targets.sort_by_key(|(_, range)| range.start);
+
+        let mut replacements = Vec::new();
+        let mut done_until = None;
+        for (cursor_id, range) in targets {
+            let markers = self.comment_markers_at(range.start);
+            let markers: Vec<&str> = markers.iter().map(String::as_str).collect();
+            let region = if range.is_empty() {
+                let block = self.lines_around(range.start);
+                let text = self
+                    .active_state_mut()
+                    .get_text_range(block.start, block.end);
+                let Some(paragraph) =
+                    reflow::paragraph_at(&text, range.start - block.start, &markers, tab_size)
+                else {
+                    continue;
+                };
+                block.start + paragraph.start..block.start + paragraph.end
+            } else {
+                let buffer = &self.active_state().buffer;
+                let first = buffer.line_of(range.start);
+                let mut last = buffer.line_of(range.end);
+                if last > first && range.end == buffer.line_start(last) {
+                    last -= 1;
+                }
+                buffer.line_start(first)..buffer.line_end(last)
+            };
+            if done_until.is_some_and(|end| region.start <= end) {
+                continue;
+            }
+            done_until = Some(region.end);
+
+            let text = self
+                .active_state_mut()
+                .get_text_range(region.start, region.end);
+            let reflowed = reflow::reflow(&text, &markers, width, tab_size);
+            if reflowed != text {
+                replacements.push(Replacement {
+                    cursor_id,
+                    range: region,
+                    text: reflowed,
+                });
+            }
+        }
+
+        if replacements.is_empty() {
+            self.set_status_message("Nothing to reflow".to_string());
+            return;
+        }
+        self.apply_replacements(replacements, "Reflow paragraph", false);
+        self.set_status_message(format!("Reflowed at column {}", width));
+    }
+
+    /// Break each cursor's line at the wrap column if the language hard-wraps
+    /// and typing took the line past it
+    pub(super) fn hard_wrap_cursor_lines(&mut self) {
+        if !self
+            .active_language_config()
+            .is_some_and(|config| config.hard_wrap)
+        {
+            return;
+        }
+        let width = self.wrap_column();
+        let state = self.active_state();
+        let tab_size = state.tab_size;
+        let eol = state.buffer.line_ending().as_str();
+        let mut lines: Vec<(CursorId, usize)> = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| (cursor_id, state.buffer.line_of(cursor.position)))
+            .collect();
+        lines.sort_by_key(|(_, line)| *line);
+        lines.dedup_by_key(|(_, line)| *line);
+
+        let mut replacements = Vec::new();
+        for (cursor_id, line) in lines {
+            let buffer = &self.active_state().buffer;
+            let start = buffer.line_start(line);
+            let end = buffer.line_end(line);
+            let text = self.active_state_mut().get_text_range(start, end);
+            if reflow::line_width(&text, tab_size) <= width {
+                continue;
+            }
+            let indent = text.len() - text.trim_start().len();
+            let markers = self.comment_markers_at(start + indent);
+            let markers: Vec<&str> = markers.iter().map(String::as_str).collect();
+            if let Some((space, text)) = reflow::hard_wrap(&text, &markers, width, tab_size, eol) {
+                replacements.push(Replacement {
+                    cursor_id,
+                    range: start + space.start..start + space.end,
+                    text,
+                });
+            }
+        }
+        if !replacements.is_empty() {
+            self.apply_replacements(replacements, "Hard wrap", false);
+        }
+    }
+}
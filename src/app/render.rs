@@ -1,4 +1,11 @@
 use super::*;
+use crate::primitives::brackets;
+use crate::services::replace::{Replacement, Replacer};
+use crate::services::streaming_search::{build_regex, LOOK_BEHIND, MATCH_OVERLAP};
+use crate::view::virtual_text::VirtualTextPosition;
+
+/// String id of the virtual texts previewing replacements
+const REPLACE_PREVIEW_ID: &str = "replace-preview";
 
 impl Editor {
     /// Render the editor to the terminal
@@ -424,6 +431,7 @@ impl Editor {
 
         // Get update availability info
         let update_available = self.latest_version().map(|v| v.to_string());
+        let mode_indicator = self.vi_mode_indicator();
 
         // Render status bar (hidden when suggestions or file browser popup is shown)
         if !has_suggestions && !has_file_browser {
@@ -439,25 +447,24 @@ impl Editor {
                 &keybindings_cloned,         // Pass the cloned keybindings
                 &chord_state_cloned,         // Pass the cloned chord state
                 update_available.as_deref(), // Pass update availability
+                mode_indicator.as_deref(),
             );
         }
 
         // Render search options bar when in search prompt
         if show_search_options {
-            // Show "Confirm" option only in replace modes
-            let confirm_each = self.prompt.as_ref().and_then(|p| {
-                if matches!(
+            // Show "Confirm" and "Preserve case" options only in replace modes
+            let replacing = self.prompt.as_ref().is_some_and(|p| {
+                matches!(
                     p.prompt_type,
                     PromptType::ReplaceSearch
                         | PromptType::Replace { .. }
                         | PromptType::QueryReplaceSearch
                         | PromptType::QueryReplace { .. }
-                ) {
-                    Some(self.search_confirm_each)
-                } else {
-                    None
-                }
+                )
             });
+            let confirm_each = replacing.then_some(self.search_confirm_each);
+            let preserve_case = replacing.then_some(self.search_preserve_case);
 
             StatusBarRenderer::render_search_options(
                 frame,
@@ -466,6 +473,7 @@ impl Editor {
                 self.search_whole_word,
                 self.search_use_regex,
                 confirm_each,
+                preserve_case,
                 &theme,
                 &keybindings_cloned,
             );
@@ -1547,6 +1555,36 @@ impl Editor {
 
         // Also clear search state
         self.search_state = None;
+        self.cancel_streaming_search();
+    }
+
+    /// Byte range of the text shown in the active split's viewport
+    fn visible_byte_range(&mut self) -> Range<usize> {
+        // Get viewport from active split's SplitViewState
+        let active_split = self.split_manager.active_split();
+        let (top_byte, visible_height) = self
+            .split_view_states
+            .get(&active_split)
+            .map(|vs| (vs.viewport.top_byte, vs.viewport.height.saturating_sub(2)))
+            .unwrap_or((0, 20));
+
+        let state = self.active_state_mut();
+
+        // Find the end of the visible content by iterating through visible lines
+        let mut visible_end = top_byte;
+        {
+            let mut line_iter = state.buffer.line_iterator(top_byte, 80);
+            for _ in 0..visible_height {
+                if let Some((line_start, line_content)) = line_iter.next() {
+                    visible_end = line_start + line_content.len();
+                } else {
+                    break;
+                }
+            }
+        }
+
+        // Ensure we don't go past buffer end
+        top_byte..visible_end.min(state.buffer.len())
     }
 
     /// Update search highlights in visible viewport only (for incremental search)
@@ -1582,9 +1620,11 @@ impl Editor {
             }
         };
 
-        // Build regex with case sensitivity
+        // Build regex with case sensitivity, `^`/`$` matching at every line
         let regex = regex::RegexBuilder::new(&regex_pattern)
             .case_insensitive(!case_sensitive)
+            .multi_line(true)
+            .crlf(true)
             .build();
 
         let regex = match regex {
@@ -1596,39 +1636,15 @@ impl Editor {
             }
         };
 
-        // Get viewport from active split's SplitViewState
-        let active_split = self.split_manager.active_split();
-        let (top_byte, visible_height) = self
-            .split_view_states
-            .get(&active_split)
-            .map(|vs| (vs.viewport.top_byte, vs.viewport.height.saturating_sub(2)))
-            .unwrap_or((0, 20));
-
+        let visible = self.visible_byte_range();
+        let visible_start = visible.start;
         let state = self.active_state_mut();
 
         // Clear any existing search highlights
         state.overlays.clear_namespace(&ns, &mut state.marker_list);
 
-        // Get the visible content by iterating through visible lines
-        let visible_start = top_byte;
-        let mut visible_end = top_byte;
-
-        {
-            let mut line_iter = state.buffer.line_iterator(top_byte, 80);
-            for _ in 0..visible_height {
-                if let Some((line_start, line_content)) = line_iter.next() {
-                    visible_end = line_start + line_content.len();
-                } else {
-                    break;
-                }
-            }
-        }
-
-        // Ensure we don't go past buffer end
-        visible_end = visible_end.min(state.buffer.len());
-
         // Get the visible text
-        let visible_text = state.get_text_range(visible_start, visible_end);
+        let visible_text = state.get_text_range(visible.start, visible.end);
 
         // Find all matches using regex
         for mat in regex.find_iter(&visible_text) {
@@ -1667,6 +1683,13 @@ impl Editor {
 
         let search_range = self.pending_search_range.take();
 
+        // Large files are searched in the background, a window at a time
+        if self.active_state().buffer.is_large_file() {
+            let cursor = self.active_state().cursors.primary().position;
+            self.start_streaming_search(query, search_range, cursor, SearchDirection::Forward);
+            return;
+        }
+
         let buffer_content = {
             let state = self.active_state();
             match state.buffer.to_string() {
@@ -1706,9 +1729,11 @@ impl Editor {
             }
         };
 
-        // Build regex with case sensitivity
+        // Build regex with case sensitivity, `^`/`$` matching at every line
         let regex = match regex::RegexBuilder::new(&regex_pattern)
             .case_insensitive(!case_sensitive)
+            .multi_line(true)
+            .crlf(true)
             .build()
         {
             Ok(r) => r,
@@ -1795,6 +1820,9 @@ impl Editor {
 
     /// Find the next match
     pub(super) fn find_next(&mut self) {
+        if self.streaming_search_step(SearchDirection::Forward) {
+            return;
+        }
         if let Some(ref mut search_state) = self.search_state {
             if search_state.matches.is_empty() {
                 return;
@@ -1837,6 +1865,9 @@ impl Editor {
 
     /// Find the previous match
     pub(super) fn find_previous(&mut self) {
+        if self.streaming_search_step(SearchDirection::Backward) {
+            return;
+        }
         if let Some(ref mut search_state) = self.search_state {
             if search_state.matches.is_empty() {
                 return;
@@ -1877,43 +1908,64 @@ impl Editor {
         }
     }
 
+    /// Build the replacer for `search` and `replacement` with the search
+    /// prompt's options
+    fn replacer_for(&self, search: &str, replacement: &str) -> Result<Replacer, regex::Error> {
+        let regex = build_regex(
+            search,
+            self.search_case_sensitive,
+            self.search_whole_word,
+            self.search_use_regex,
+        )?;
+        Ok(Replacer::new(
+            regex,
+            replacement,
+            self.search_use_regex,
+            self.search_preserve_case,
+        ))
+    }
+
     /// Perform a replace-all operation
-    /// Replaces all occurrences of the search query with the replacement text
+    /// Replaces all matches of the search query with the replacement text
     pub(super) fn perform_replace(&mut self, search: &str, replacement: &str) {
         if search.is_empty() {
             self.set_status_message("Replace: empty search query.".to_string());
             return;
         }
 
-        // Find all matches first (before making any modifications)
-        let matches = {
-            let state = self.active_state();
-            let buffer_len = state.buffer.len();
-            let mut matches = Vec::new();
-            let mut current_pos = 0;
-
-            while current_pos < buffer_len {
-                if let Some(offset) = state.buffer.find_next_in_range(
-                    search,
-                    current_pos,
-                    Some(current_pos..buffer_len),
-                ) {
-                    matches.push(offset);
-                    current_pos = offset + search.len();
-                } else {
-                    break;
-                }
+        let replacer = match self.replacer_for(search, replacement) {
+            Ok(replacer) => replacer,
+            Err(e) => {
+                self.set_status_message(format!("Invalid regex: {}", e));
+                return;
             }
-            matches
         };
 
-        let count = matches.len();
-
-        if count == 0 {
-            self.set_status_message(format!("No occurrences of '{}' found.", search));
+        // Large files are searched in the background and replaced once done
+        if self.active_state().buffer.is_large_file() {
+            self.start_streaming_replace(search, replacement, replacer);
             return;
         }
 
+        // Find all matches first (before making any modifications)
+        let replacements = match self.active_state().buffer.to_string() {
+            Some(text) => replacer.find_all(text.as_bytes()),
+            None => {
+                self.set_status_message("Buffer not fully loaded".to_string());
+                return;
+            }
+        };
+
+        self.replace_matches(search, replacement, replacements);
+    }
+
+    /// Apply `replacements` (non-overlapping, in any order) to the active
+    /// buffer as one undoable batch, leaving the cursor where it is
+    pub(super) fn apply_match_replacements(
+        &mut self,
+        mut replacements: Vec<Replacement>,
+        description: String,
+    ) {
         // Capture current cursor state for undo
         let cursor_id = self.active_state().cursors.primary_id();
         let cursor = self.active_state().cursors.get(cursor_id).unwrap().clone();
@@ -1935,39 +1987,55 @@ impl Editor {
             new_sticky_column: old_sticky_column,
         });
 
-        for match_pos in matches.into_iter().rev() {
-            let end = match_pos + search.len();
-            let range = match_pos..end;
-
-            // Get the text being deleted
-            let deleted_text = self
-                .active_state_mut()
-                .get_text_range(range.start, range.end);
-
-            // Add Delete event
-            events.push(Event::Delete {
-                range: range.clone(),
-                deleted_text,
-                cursor_id,
-            });
-
-            // Add Insert event
-            events.push(Event::Insert {
-                position: match_pos,
-                text: replacement.to_string(),
-                cursor_id,
-            });
+        replacements.sort_by_key(|r| std::cmp::Reverse(r.range.start));
+        for replacement in replacements {
+            // Empty matches (e.g. `^`) only insert, and empty replacements only delete
+            if !replacement.range.is_empty() {
+                events.push(Event::Delete {
+                    range: replacement.range.clone(),
+                    deleted_text: replacement.matched,
+                    cursor_id,
+                });
+            }
+            if !replacement.text.is_empty() {
+                events.push(Event::Insert {
+                    position: replacement.range.start,
+                    text: replacement.text,
+                    cursor_id,
+                });
+            }
         }
 
         // Wrap all replacement events in a single Batch for atomic undo
         let batch = Event::Batch {
             events,
-            description: format!("Replace all '{}' with '{}'", search, replacement),
+            description,
         };
 
         // Apply through event log for proper undo support
         self.active_event_log_mut().append(batch.clone());
         self.apply_event_to_active_buffer(&batch);
+    }
+
+    /// Replace each match of `search` with its expansion of `replacement`,
+    /// as one undoable batch
+    pub(super) fn replace_matches(
+        &mut self,
+        search: &str,
+        replacement: &str,
+        replacements: Vec<Replacement>,
+    ) {
+        let count = replacements.len();
+
+        if count == 0 {
+            self.set_status_message(format!("No occurrences of '{}' found.", search));
+            return;
+        }
+
+        self.apply_match_replacements(
+            replacements,
+            format!("Replace all '{}' with '{}'", search, replacement),
+        );
 
         // Clear search state since positions are now invalid
         self.search_state = None;
@@ -1994,47 +2062,45 @@ impl Editor {
             return;
         }
 
-        // Find the first match lazily (don't find all matches upfront)
-        let state = self.active_state();
-        let start_pos = state.cursors.primary().position;
-        let first_match = state.buffer.find_next(search, start_pos);
+        let replacer = match self.replacer_for(search, replacement) {
+            Ok(replacer) => replacer,
+            Err(e) => {
+                self.set_status_message(format!("Invalid regex: {}", e));
+                return;
+            }
+        };
 
-        let Some(first_match_pos) = first_match else {
+        // Find the first match lazily (don't find all matches upfront),
+        // from the cursor and wrapping around to the start
+        let start_pos = self.active_state().cursors.primary().position;
+        let buffer_len = self.active_state().buffer.len();
+        let first_match = self
+            .find_replace_match(&replacer, start_pos, buffer_len + 1, false)
+            .or_else(|| self.find_replace_match(&replacer, 0, start_pos, false));
+
+        let Some(first_match) = first_match else {
             self.set_status_message(format!("No occurrences of '{}' found.", search));
             return;
         };
 
         // Initialize interactive replace state with just the current match
-        self.interactive_replace_state = Some(InteractiveReplaceState {
+        let ir_state = InteractiveReplaceState {
             search: search.to_string(),
             replacement: replacement.to_string(),
-            current_match_pos: first_match_pos,
-            start_pos: first_match_pos,
+            replacer,
+            start_pos: first_match.range.start,
+            current: first_match,
             has_wrapped: false,
             replacements_made: 0,
-        });
-
-        // Move cursor to first match
-        let active_split = self.split_manager.active_split();
-        let active_buffer = self.active_buffer();
-        {
-            let state = self.active_state_mut();
-            state.cursors.primary_mut().position = first_match_pos;
-            state.cursors.primary_mut().anchor = None;
-        }
-        // Ensure cursor is visible - get viewport from SplitViewState
-        if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
-            let state = self.buffers.get_mut(&active_buffer).unwrap();
-            view_state
-                .viewport
-                .ensure_visible(&mut state.buffer, state.cursors.primary());
-        }
+        };
 
-        // Show the query-replace prompt
+        // Show the query-replace prompt, then move to the first match
         self.prompt = Some(Prompt::new(
             "Replace? (y)es (n)o (a)ll (c)ancel: ".to_string(),
             PromptType::QueryReplaceConfirm,
         ));
+        self.move_to_current_match(&ir_state);
+        self.interactive_replace_state = Some(ir_state);
     }
 
     /// Handle interactive replace key press (y/n/a/c)
@@ -2047,15 +2113,14 @@ impl Editor {
         match c {
             'y' | 'Y' => {
                 // Replace current match
-                self.replace_current_match(&ir_state)?;
-                ir_state.replacements_made += 1;
+                let search_pos = self.replace_current_match(&mut ir_state)?;
 
                 // Find next match lazily (after the replacement)
-                let search_pos = ir_state.current_match_pos + ir_state.replacement.len();
+                let skip_empty = ir_state.current.range.is_empty();
                 if let Some((next_match, wrapped)) =
-                    self.find_next_match_for_replace(&ir_state, search_pos)
+                    self.find_next_match_for_replace(&ir_state, search_pos, skip_empty)
                 {
-                    ir_state.current_match_pos = next_match;
+                    ir_state.current = next_match;
                     if wrapped {
                         ir_state.has_wrapped = true;
                     }
@@ -2067,11 +2132,12 @@ impl Editor {
             }
             'n' | 'N' => {
                 // Skip current match and find next
-                let search_pos = ir_state.current_match_pos + ir_state.search.len();
+                let search_pos = ir_state.current.range.end;
+                let skip_empty = ir_state.current.range.is_empty();
                 if let Some((next_match, wrapped)) =
-                    self.find_next_match_for_replace(&ir_state, search_pos)
+                    self.find_next_match_for_replace(&ir_state, search_pos, skip_empty)
                 {
-                    ir_state.current_match_pos = next_match;
+                    ir_state.current = next_match;
                     if wrapped {
                         ir_state.has_wrapped = true;
                     }
@@ -2084,33 +2150,27 @@ impl Editor {
             'a' | 'A' | '!' => {
                 // Replace all remaining matches with SINGLE confirmation
                 // Undo behavior: ONE undo step undoes ALL remaining replacements
-                // Uses streaming search (doesn't materialize file), but collects positions for batch
+                // Reads the text a window at a time (doesn't materialize file), but collects matches for batch
 
                 // First replace the current match
-                self.replace_current_match(&ir_state)?;
-                ir_state.replacements_made += 1;
+                let search_pos = self.replace_current_match(&mut ir_state)?;
 
-                // Find all remaining matches using streaming search
-                // Collecting positions (Vec<usize>) is low memory cost even for huge files
-                let search_pos = ir_state.current_match_pos + ir_state.replacement.len();
+                // Find all remaining matches, one at a time
                 let remaining_matches = {
                     let mut matches = Vec::new();
                     let mut current_pos = search_pos;
+                    let mut skip_empty = ir_state.current.range.is_empty();
                     let mut temp_state = ir_state.clone();
 
-                    // Find matches lazily one at a time, collect positions
-                    loop {
-                        if let Some((next_match, wrapped)) =
-                            self.find_next_match_for_replace(&temp_state, current_pos)
-                        {
-                            matches.push(next_match);
-                            current_pos = next_match + temp_state.search.len();
-                            if wrapped {
-                                temp_state.has_wrapped = true;
-                            }
-                        } else {
-                            break;
+                    while let Some((next_match, wrapped)) =
+                        self.find_next_match_for_replace(&temp_state, current_pos, skip_empty)
+                    {
+                        current_pos = next_match.range.end;
+                        skip_empty = next_match.range.is_empty();
+                        if wrapped {
+                            temp_state.has_wrapped = true;
                         }
+                        matches.push(next_match);
                     }
                     matches
                 };
@@ -2118,58 +2178,14 @@ impl Editor {
                 let remaining_count = remaining_matches.len();
 
                 if remaining_count > 0 {
-                    // Capture current cursor state for undo
-                    let cursor_id = self.active_state().cursors.primary_id();
-                    let cursor = self.active_state().cursors.get(cursor_id).unwrap().clone();
-                    let old_position = cursor.position;
-                    let old_anchor = cursor.anchor;
-                    let old_sticky_column = cursor.sticky_column;
-
-                    // Create events for all remaining replacements (reverse order preserves positions)
-                    let mut events = Vec::new();
-
-                    // Add MoveCursor at the beginning to save cursor position for undo
-                    events.push(Event::MoveCursor {
-                        cursor_id,
-                        old_position,
-                        new_position: old_position, // Keep cursor where it is
-                        old_anchor,
-                        new_anchor: old_anchor,
-                        old_sticky_column,
-                        new_sticky_column: old_sticky_column,
-                    });
-
-                    for match_pos in remaining_matches.into_iter().rev() {
-                        let end = match_pos + ir_state.search.len();
-                        let range = match_pos..end;
-                        let deleted_text = self
-                            .active_state_mut()
-                            .get_text_range(range.start, range.end);
-
-                        events.push(Event::Delete {
-                            range: range.clone(),
-                            deleted_text,
-                            cursor_id,
-                        });
-
-                        events.push(Event::Insert {
-                            position: match_pos,
-                            text: ir_state.replacement.clone(),
-                            cursor_id,
-                        });
-                    }
-
                     // Single Batch = single undo step for all remaining replacements
-                    let batch = Event::Batch {
-                        events,
-                        description: format!(
+                    self.apply_match_replacements(
+                        remaining_matches,
+                        format!(
                             "Query replace remaining '{}' with '{}'",
                             ir_state.search, ir_state.replacement
                         ),
-                    };
-
-                    self.active_event_log_mut().append(batch.clone());
-                    self.apply_event_to_active_buffer(&batch);
+                    );
 
                     ir_state.replacements_made += remaining_count;
                 }
@@ -2188,67 +2204,70 @@ impl Editor {
         Ok(())
     }
 
+    /// The first match starting in `from..until` in the active buffer,
+    /// read a window at a time so large files aren't loaded whole
+    fn find_replace_match(
+        &mut self,
+        replacer: &Replacer,
+        from: usize,
+        until: usize,
+        skip_empty: bool,
+    ) -> Option<Replacement> {
+        let state = self.active_state_mut();
+        let buffer_len = state.buffer.len();
+        let found = replacer.find_next(from, until, buffer_len, skip_empty, |range| {
+            state
+                .buffer
+                .get_text_range_mut(range.start, range.len())
+                .map_err(std::io::Error::other)
+        });
+        match found {
+            Ok(found) => found,
+            Err(e) => {
+                tracing::warn!("Query replace failed to read the buffer: {}", e);
+                None
+            }
+        }
+    }
+
     /// Find the next match for interactive replace (lazy search with wrap-around)
     pub(super) fn find_next_match_for_replace(
-        &self,
+        &mut self,
         ir_state: &InteractiveReplaceState,
         start_pos: usize,
-    ) -> Option<(usize, bool)> {
-        let state = self.active_state();
+        skip_empty: bool,
+    ) -> Option<(Replacement, bool)> {
+        let replacer = &ir_state.replacer;
 
         if ir_state.has_wrapped {
             // We've already wrapped - only search from start_pos up to (but not including) the original start position
-            // Use find_next_in_range to avoid wrapping again
-            let search_range = Some(start_pos..ir_state.start_pos);
-            if let Some(match_pos) =
-                state
-                    .buffer
-                    .find_next_in_range(&ir_state.search, start_pos, search_range)
-            {
-                return Some((match_pos, true));
-            }
-            None // No more matches before original start position
-        } else {
-            // Haven't wrapped yet - search normally from start_pos
-            // First try from start_pos to end of buffer
-            let buffer_len = state.buffer.len();
-            let search_range = Some(start_pos..buffer_len);
-            if let Some(match_pos) =
-                state
-                    .buffer
-                    .find_next_in_range(&ir_state.search, start_pos, search_range)
-            {
-                return Some((match_pos, false));
-            }
-
-            // No match from start_pos to end - wrap to beginning
-            // Search from 0 to start_pos (original position)
-            let wrap_range = Some(0..ir_state.start_pos);
-            if let Some(match_pos) =
-                state
-                    .buffer
-                    .find_next_in_range(&ir_state.search, 0, wrap_range)
-            {
-                return Some((match_pos, true)); // Found match after wrapping
-            }
+            return self
+                .find_replace_match(replacer, start_pos, ir_state.start_pos, skip_empty)
+                .map(|found| (found, true));
+        }
 
-            None // No matches found anywhere
+        // Haven't wrapped yet - first try from start_pos to end of buffer
+        let buffer_len = self.active_state().buffer.len();
+        if let Some(found) =
+            self.find_replace_match(replacer, start_pos, buffer_len + 1, skip_empty)
+        {
+            return Some((found, false));
         }
+
+        // No match from start_pos to end - wrap to beginning
+        // Search from 0 to start_pos (original position)
+        self.find_replace_match(replacer, 0, ir_state.start_pos, false)
+            .map(|found| (found, true)) // Found match after wrapping
     }
 
-    /// Replace the current match in interactive replace mode
+    /// Replace the current match in interactive replace mode, returning the
+    /// position just after the replacement
     pub(super) fn replace_current_match(
         &mut self,
-        ir_state: &InteractiveReplaceState,
-    ) -> std::io::Result<()> {
-        let match_pos = ir_state.current_match_pos;
-        let search_len = ir_state.search.len();
-        let range = match_pos..(match_pos + search_len);
-
-        // Get the deleted text for the event
-        let deleted_text = self
-            .active_state_mut()
-            .get_text_range(range.start, range.end);
+        ir_state: &mut InteractiveReplaceState,
+    ) -> std::io::Result<usize> {
+        let current = ir_state.current.clone();
+        let match_pos = current.range.start;
 
         // Capture current cursor state for undo
         let cursor_id = self.active_state().cursors.primary_id();
@@ -2259,27 +2278,29 @@ impl Editor {
 
         // Create events: MoveCursor, Delete, Insert
         // The MoveCursor saves the cursor position so undo can restore it
-        let events = vec![
-            Event::MoveCursor {
-                cursor_id,
-                old_position,
-                new_position: match_pos,
-                old_anchor,
-                new_anchor: None,
-                old_sticky_column,
-                new_sticky_column: 0,
-            },
-            Event::Delete {
-                range: range.clone(),
-                deleted_text,
+        let mut events = vec![Event::MoveCursor {
+            cursor_id,
+            old_position,
+            new_position: match_pos,
+            old_anchor,
+            new_anchor: None,
+            old_sticky_column,
+            new_sticky_column: 0,
+        }];
+        if !current.range.is_empty() {
+            events.push(Event::Delete {
+                range: current.range.clone(),
+                deleted_text: current.matched.clone(),
                 cursor_id,
-            },
-            Event::Insert {
+            });
+        }
+        if !current.text.is_empty() {
+            events.push(Event::Insert {
                 position: match_pos,
-                text: ir_state.replacement.clone(),
+                text: current.text.clone(),
                 cursor_id,
-            },
-        ];
+            });
+        }
 
         // Wrap in batch for atomic undo
         let batch = Event::Batch {
@@ -2294,12 +2315,19 @@ impl Editor {
         self.active_event_log_mut().append(batch.clone());
         self.apply_event_to_active_buffer(&batch);
 
-        Ok(())
+        // Matches before the original start move with the replacement, and
+        // so does the point where the search wraps around to stop
+        if ir_state.has_wrapped {
+            ir_state.start_pos = ir_state.start_pos + current.text.len() - current.range.len();
+        }
+        ir_state.replacements_made += 1;
+
+        Ok(match_pos + current.text.len())
     }
 
     /// Move cursor to the current match in interactive replace
     pub(super) fn move_to_current_match(&mut self, ir_state: &InteractiveReplaceState) {
-        let match_pos = ir_state.current_match_pos;
+        let match_pos = ir_state.current.range.start;
         let active_split = self.split_manager.active_split();
         let active_buffer = self.active_buffer();
         {
@@ -2315,6 +2343,9 @@ impl Editor {
                 .ensure_visible(&mut state.buffer, state.cursors.primary());
         }
 
+        // Preview what this match would be replaced with
+        self.show_replace_preview(std::slice::from_ref(&ir_state.current));
+
         // Update the prompt message (show [Wrapped] if we've wrapped around)
         let msg = if ir_state.has_wrapped {
             "[Wrapped] Replace? (y)es (n)o (a)ll (c)ancel: ".to_string()
@@ -2334,6 +2365,7 @@ impl Editor {
     pub(super) fn finish_interactive_replace(&mut self, replacements_made: usize) {
         self.interactive_replace_state = None;
         self.prompt = None; // Clear the query-replace prompt
+        self.clear_replace_preview();
 
         // Clear search highlights
         let ns = self.search_namespace.clone();
@@ -2347,6 +2379,96 @@ impl Editor {
         ));
     }
 
+    /// Show each replacement's text as virtual text after its match, in
+    /// place of any previous preview
+    fn show_replace_preview(&mut self, replacements: &[Replacement]) {
+        let style = ratatui::style::Style::default()
+            .fg(self.theme.search_match_bg)
+            .add_modifier(ratatui::style::Modifier::ITALIC);
+        let state = self.active_state_mut();
+        state
+            .virtual_texts
+            .remove_by_prefix(&mut state.marker_list, REPLACE_PREVIEW_ID);
+        for replacement in replacements {
+            let text = if replacement.text.is_empty() {
+                "→ (delete)".to_string()
+            } else {
+                format!("→ {}", replacement.text.replace('\n', "⏎"))
+            };
+            // After the last character of the match, or before an empty one
+            let (position, placement) = match replacement.matched.char_indices().last() {
+                Some((last, _)) => (
+                    replacement.range.start + last,
+                    VirtualTextPosition::AfterChar,
+                ),
+                None => (replacement.range.start, VirtualTextPosition::BeforeChar),
+            };
+            state.virtual_texts.add_with_id(
+                &mut state.marker_list,
+                position,
+                text,
+                style,
+                placement,
+                0,
+                REPLACE_PREVIEW_ID.to_string(),
+            );
+        }
+    }
+
+    /// Remove the replacement preview from the active buffer
+    pub(super) fn clear_replace_preview(&mut self) {
+        let state = self.active_state_mut();
+        state
+            .virtual_texts
+            .remove_by_prefix(&mut state.marker_list, REPLACE_PREVIEW_ID);
+    }
+
+    /// Preview the replacement of each visible match of `search` as the
+    /// user types `replacement`
+    pub(super) fn update_replace_preview(&mut self, search: &str, replacement: &str) {
+        let replacer = match self.replacer_for(search, replacement) {
+            Ok(replacer) if !search.is_empty() => replacer,
+            _ => {
+                self.clear_replace_preview();
+                return;
+            }
+        };
+        let visible = self.visible_byte_range();
+        let replacements: Vec<Replacement> = {
+            let state = self.active_state_mut();
+            let base = visible.start.saturating_sub(LOOK_BEHIND);
+            let end = (visible.end + MATCH_OVERLAP).min(state.buffer.len());
+            let text = state.buffer.get_text_range_mut(base, end - base);
+            text.map(|text| {
+                replacer
+                    .find_all(&text)
+                    .into_iter()
+                    .map(|found| Replacement {
+                        range: base + found.range.start..base + found.range.end,
+                        ..found
+                    })
+                    .filter(|found| visible.contains(&found.range.start))
+                    .collect()
+            })
+            .unwrap_or_default()
+        };
+        self.show_replace_preview(&replacements);
+    }
+
+    /// Redo the replacement preview after a search option changed
+    pub(super) fn refresh_replace_preview(&mut self) {
+        let Some(prompt) = &self.prompt else {
+            return;
+        };
+        match &prompt.prompt_type {
+            PromptType::Replace { search } | PromptType::QueryReplace { search } => {
+                let (search, input) = (search.clone(), prompt.input.clone());
+                self.update_replace_preview(&search, &input);
+            }
+            _ => {}
+        }
+    }
+
     /// Smart home: toggle between line start and first non-whitespace character
     pub(super) fn smart_home(&mut self) {
         let estimated_line_length = self.config.editor.estimated_line_length;
@@ -2389,152 +2511,6 @@ impl Editor {
         }
     }
 
-    /// Toggle comment on the current line or selection
-    pub(super) fn toggle_comment(&mut self) {
-        // Determine comment prefix based on file extension
-        let comment_prefix = if let Some(metadata) = self.buffer_metadata.get(&self.active_buffer())
-        {
-            if let Some(path) = metadata.file_path() {
-                match path.extension().and_then(|e| e.to_str()) {
-                    Some("rs") | Some("c") | Some("cpp") | Some("h") | Some("hpp") | Some("js")
-                    | Some("ts") | Some("jsx") | Some("tsx") | Some("java") | Some("go")
-                    | Some("swift") | Some("kt") | Some("scala") => "// ",
-                    Some("py") | Some("rb") | Some("sh") | Some("bash") | Some("zsh")
-                    | Some("pl") | Some("r") | Some("yml") | Some("yaml") | Some("toml") => "# ",
-                    Some("lua") | Some("sql") => "-- ",
-                    Some("html") | Some("xml") => "<!-- ",
-                    Some("css") | Some("scss") | Some("sass") => "/* ",
-                    Some("vim") => "\" ",
-                    Some("lisp") | Some("el") | Some("clj") => ";; ",
-                    _ => "// ",
-                }
-            } else {
-                "// "
-            }
-        } else {
-            "// "
-        };
-
-        let estimated_line_length = self.config.editor.estimated_line_length;
-
-        let state = self.active_state_mut();
-        let cursor = state.cursors.primary().clone();
-        let cursor_id = state.cursors.primary_id();
-
-        let (start_pos, end_pos) = if let Some(range) = cursor.selection_range() {
-            (range.start, range.end)
-        } else {
-            let iter = state
-                .buffer
-                .line_iterator(cursor.position, estimated_line_length);
-            let line_start = iter.current_position();
-            (line_start, cursor.position)
-        };
-
-        // Find all line starts in the range
-        let buffer_len = state.buffer.len();
-        let mut line_starts = Vec::new();
-        let mut iter = state.buffer.line_iterator(start_pos, estimated_line_length);
-        let mut current_pos = iter.current_position();
-        line_starts.push(current_pos);
-
-        loop {
-            if let Some((_, content)) = iter.next() {
-                current_pos += content.len();
-                if current_pos > end_pos || current_pos > buffer_len {
-                    break;
-                }
-                let next_iter = state
-                    .buffer
-                    .line_iterator(current_pos, estimated_line_length);
-                let next_start = next_iter.current_position();
-                if next_start != *line_starts.last().unwrap() {
-                    line_starts.push(next_start);
-                }
-                iter = state
-                    .buffer
-                    .line_iterator(current_pos, estimated_line_length);
-            } else {
-                break;
-            }
-        }
-
-        // Determine if we should comment or uncomment
-        // If all lines are commented, uncomment; otherwise comment
-        let all_commented = line_starts.iter().all(|&line_start| {
-            let line_bytes = state
-                .buffer
-                .slice_bytes(line_start..buffer_len.min(line_start + comment_prefix.len() + 10));
-            let line_str = String::from_utf8_lossy(&line_bytes);
-            let trimmed = line_str.trim_start();
-            trimmed.starts_with(comment_prefix.trim())
-        });
-
-        let mut events = Vec::new();
-
-        if all_commented {
-            // Uncomment: remove comment prefix from each line
-            for &line_start in line_starts.iter().rev() {
-                let line_bytes = state
-                    .buffer
-                    .slice_bytes(line_start..buffer_len.min(line_start + 100));
-                let line_str = String::from_utf8_lossy(&line_bytes);
-
-                // Find where the comment prefix starts (after leading whitespace)
-                let leading_ws: usize = line_str
-                    .chars()
-                    .take_while(|c| c.is_whitespace() && *c != '\n')
-                    .map(|c| c.len_utf8())
-                    .sum();
-                let rest = &line_str[leading_ws..];
-
-                if rest.starts_with(comment_prefix.trim()) {
-                    let remove_len = if rest.starts_with(comment_prefix) {
-                        comment_prefix.len()
-                    } else {
-                        comment_prefix.trim().len()
-                    };
-                    let deleted_text = String::from_utf8_lossy(&state.buffer.slice_bytes(
-                        line_start + leading_ws..line_start + leading_ws + remove_len,
-                    ))
-                    .to_string();
-                    events.push(Event::Delete {
-                        range: (line_start + leading_ws)..(line_start + leading_ws + remove_len),
-                        deleted_text,
-                        cursor_id,
-                    });
-                }
-            }
-        } else {
-            // Comment: add comment prefix to each line
-            for &line_start in line_starts.iter().rev() {
-                events.push(Event::Insert {
-                    position: line_start,
-                    text: comment_prefix.to_string(),
-                    cursor_id,
-                });
-            }
-        }
-
-        if events.is_empty() {
-            return;
-        }
-
-        let action_desc = if all_commented {
-            "Uncomment"
-        } else {
-            "Comment"
-        };
-        let batch = Event::Batch {
-            events,
-            description: format!("{} lines", action_desc),
-        };
-
-        self.active_event_log_mut().append(batch.clone());
-        self.apply_event_to_active_buffer(&batch);
-        self.set_status_message(format!("{}ed {} line(s)", action_desc, line_starts.len()));
-    }
-
     /// Go to matching bracket
     pub(super) fn goto_matching_bracket(&mut self) {
         let state = self.active_state_mut();
@@ -2542,80 +2518,18 @@ impl Editor {
         let cursor_id = state.cursors.primary_id();
 
         let pos = cursor.position;
-        if pos >= state.buffer.len() {
-            self.set_status_message("No bracket at cursor".to_string());
-            return;
-        }
-
-        let bytes = state.buffer.slice_bytes(pos..pos + 1);
-        if bytes.is_empty() {
+        let at_bracket = pos < state.buffer.len()
+            && state
+                .buffer
+                .slice_bytes(pos..pos + 1)
+                .first()
+                .is_some_and(|&b| brackets::bracket_pair(b).is_some());
+        if !at_bracket {
             self.set_status_message("No bracket at cursor".to_string());
             return;
         }
 
-        let ch = bytes[0] as char;
-        let (opening, closing, forward) = match ch {
-            '(' => ('(', ')', true),
-            ')' => ('(', ')', false),
-            '[' => ('[', ']', true),
-            ']' => ('[', ']', false),
-            '{' => ('{', '}', true),
-            '}' => ('{', '}', false),
-            '<' => ('<', '>', true),
-            '>' => ('<', '>', false),
-            _ => {
-                self.set_status_message("No bracket at cursor".to_string());
-                return;
-            }
-        };
-
-        // Find matching bracket
-        let buffer_len = state.buffer.len();
-        let mut depth = 1;
-        let matching_pos = if forward {
-            let mut search_pos = pos + 1;
-            let mut found = None;
-            while search_pos < buffer_len && depth > 0 {
-                let b = state.buffer.slice_bytes(search_pos..search_pos + 1);
-                if !b.is_empty() {
-                    let c = b[0] as char;
-                    if c == opening {
-                        depth += 1;
-                    } else if c == closing {
-                        depth -= 1;
-                        if depth == 0 {
-                            found = Some(search_pos);
-                        }
-                    }
-                }
-                search_pos += 1;
-            }
-            found
-        } else {
-            let mut search_pos = pos.saturating_sub(1);
-            let mut found = None;
-            loop {
-                let b = state.buffer.slice_bytes(search_pos..search_pos + 1);
-                if !b.is_empty() {
-                    let c = b[0] as char;
-                    if c == closing {
-                        depth += 1;
-                    } else if c == opening {
-                        depth -= 1;
-                        if depth == 0 {
-                            found = Some(search_pos);
-                            break;
-                        }
-                    }
-                }
-                if search_pos == 0 {
-                    break;
-                }
-                search_pos -= 1;
-            }
-            found
-        };
-
+        let matching_pos = brackets::matching_bracket(&state.buffer, pos);
         if let Some(new_pos) = matching_pos {
             let event = Event::MoveCursor {
                 cursor_id,
@@ -2804,6 +2718,7 @@ impl Editor {
             let key = state.key;
             self.macros.insert(key, state.actions);
             self.last_macro_register = Some(key);
+            self.save_macros();
             self.set_status_message(format!("Macro '{}' saved ({} actions)", key, action_count));
         } else {
             self.set_status_message("Not recording a macro".to_string());
@@ -2848,7 +2763,15 @@ impl Editor {
                 | Action::ListMacros
                 | Action::PromptRecordMacro
                 | Action::PromptPlayMacro
-                | Action::PlayLastMacro => {}
+                | Action::PlayLastMacro
+                | Action::PlayMacroOnLines(_)
+                | Action::PlayMacroTimes(_, _)
+                | Action::PlayMacroUntilFailure(_)
+                | Action::PromptEditMacro
+                | Action::PromptPlayMacroOnLines
+                | Action::PromptPlayMacroTimes
+                | Action::PromptPlayMacroUntilFailure
+                | Action::PromptExportMacro => {}
                 _ => {
                     state.actions.push(action.clone());
                 }
@@ -2878,7 +2801,7 @@ impl Editor {
 
         // Create header with macro info
         let content = format!(
-            "// Macro '{}' ({} actions)\n// This buffer can be saved as a .json file for persistence\n\n{}",
+            "// Macro '{}' ({} actions)\n// Edit the actions and save (Ctrl+S) to update the macro\n\n{}",
             key,
             actions_len,
             json
@@ -2913,16 +2836,17 @@ impl Editor {
             display_name: format!("*Macro {}*", key),
             lsp_enabled: false,
             lsp_disabled_reason: Some("Virtual macro buffer".to_string()),
-            read_only: false, // Allow editing for saving
+            read_only: false, // Saving re-parses the edited macro
             binary: false,
             lsp_opened_with: std::collections::HashSet::new(),
         };
         self.buffer_metadata.insert(buffer_id, metadata);
+        self.macro_buffers.insert(buffer_id, key);
 
         // Switch to the new buffer
         self.set_active_buffer(buffer_id);
         self.set_status_message(format!(
-            "Macro '{}' shown in buffer ({} actions) - save as .json for persistence",
+            "Macro '{}' shown in buffer ({} actions) - save to update it",
             key, actions_len
         ));
     }
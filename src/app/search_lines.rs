@@ -0,0 +1,262 @@
+//! Search Lines for the Editor
+//!
+//! A prompt that fuzzy-finds a line of the active buffer. The matching
+//! lines are listed as suggestions, best first; the selected one is shown
+//! in the editor, with its matched characters highlighted, as the selection
+//! moves. Enter keeps the cursor there and Escape puts the cursor and the
+//! view back where they were.
+
+use ratatui::style::Style;
+
+use crate::input::commands::Suggestion;
+use crate::model::event::{BufferId, Event};
+use crate::services::search_lines::{line_label, LineMatch, LineSearch};
+use crate::services::streaming_search::WINDOW_SIZE;
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+use crate::view::prompt::{Prompt, PromptType};
+
+use super::Editor;
+
+/// Overlay namespace for the matched characters of the previewed line
+const SEARCH_LINES_NAMESPACE: &str = "search-lines";
+
+/// An open Search Lines prompt
+pub(super) struct SearchLinesState {
+    buffer_id: BufferId,
+    search: LineSearch,
+    /// Matches for the current query, one per suggestion
+    results: Vec<LineMatch>,
+    /// Primary cursor position and anchor when the prompt opened
+    cursor: (usize, Option<usize>),
+    /// Viewport top byte, top view line offset and left column when the
+    /// prompt opened
+    viewport: (usize, usize, usize),
+}
+
+impl Editor {
+    /// Open the Search Lines prompt over the active buffer
+    pub(super) fn start_search_lines_prompt(&mut self) {
+        let buffer_id = self.active_buffer();
+        let mut snapshot = self.active_state().buffer.content_snapshot();
+        let search = match LineSearch::read(&mut snapshot, WINDOW_SIZE) {
+            Ok(search) => search,
+            Err(e) => {
+                self.set_status_message(format!("Failed to read buffer: {}", e));
+                return;
+            }
+        };
+        if search.lines().is_empty() {
+            self.set_status_message("Buffer is empty".to_string());
+            return;
+        }
+        if search.limited() {
+            self.set_status_message(format!(
+                "Searching the first {} lines only",
+                search.lines().len()
+            ));
+        }
+
+        let cursor = self.active_state().cursors.primary();
+        let cursor = (cursor.position, cursor.anchor);
+        let viewport = self
+            .split_view_states
+            .get(&self.split_manager.active_split())
+            .map_or((0, 0, 0), |view_state| {
+                let viewport = &view_state.viewport;
+                (
+                    viewport.top_byte,
+                    viewport.top_view_line_offset,
+                    viewport.left_column,
+                )
+            });
+
+        self.search_lines = Some(SearchLinesState {
+            buffer_id,
+            search,
+            results: Vec::new(),
+            cursor,
+            viewport,
+        });
+        self.prompt = Some(Prompt::with_suggestions(
+            "Search lines: ".to_string(),
+            PromptType::SearchLines,
+            Vec::new(),
+        ));
+        self.update_search_lines("");
+    }
+
+    /// Rank the lines against `query` and list them in the prompt. With no
+    /// query every line is listed in order, with the cursor's line selected.
+    pub(super) fn update_search_lines(&mut self, query: &str) {
+        let Some(state) = self.search_lines.as_mut() else {
+            return;
+        };
+        state.results = state.search.search(query);
+
+        let lines = state.search.lines();
+        let width = lines.len().to_string().len();
+        let suggestions: Vec<Suggestion> = state
+            .results
+            .iter()
+            .map(|result| {
+                let line = &lines[result.index];
+                Suggestion {
+                    text: line_label(line, width),
+                    description: None,
+                    value: Some((line.line + 1).to_string()),
+                    disabled: false,
+                    keybinding: None,
+                    source: None,
+                }
+            })
+            .collect();
+        let selected = if suggestions.is_empty() {
+            None
+        } else if query.is_empty() {
+            let cursor = state.cursor.0;
+            let current = lines.partition_point(|line| line.offset <= cursor);
+            state
+                .results
+                .iter()
+                .position(|result| result.index + 1 == current)
+                .or(Some(0))
+        } else {
+            Some(0)
+        };
+
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.suggestions = suggestions;
+            prompt.selected_suggestion = selected;
+        }
+        self.preview_search_lines();
+    }
+
+    /// Show the selected line in the editor, highlighting its matched
+    /// characters; with nothing selected, go back to where the prompt opened
+    pub(super) fn preview_search_lines(&mut self) {
+        let selected = self
+            .prompt
+            .as_ref()
+            .filter(|prompt| prompt.prompt_type == PromptType::SearchLines)
+            .and_then(|prompt| prompt.selected_suggestion);
+        let Some(state) = self.search_lines.as_ref() else {
+            return;
+        };
+        if state.buffer_id != self.active_buffer() {
+            return;
+        }
+        let Some(result) = selected.and_then(|index| state.results.get(index)) else {
+            self.restore_search_lines_view();
+            self.clear_search_lines_highlights();
+            return;
+        };
+        let offset = state.search.lines()[result.index].offset;
+        let ranges: Vec<_> = result
+            .ranges
+            .iter()
+            .map(|range| offset + range.start..offset + range.end)
+            .collect();
+
+        self.move_cursor_to_match(offset);
+        let style = Style::default()
+            .fg(self.theme.search_match_fg)
+            .bg(self.theme.search_match_bg);
+        let namespace = OverlayNamespace::from_string(SEARCH_LINES_NAMESPACE.to_string());
+        let state = self.active_state_mut();
+        state
+            .overlays
+            .clear_namespace(&namespace, &mut state.marker_list);
+        for range in ranges {
+            let overlay = Overlay::with_namespace(
+                &mut state.marker_list,
+                range,
+                OverlayFace::Style { style },
+                namespace.clone(),
+            )
+            .with_priority_value(10);
+            state.overlays.add(overlay);
+        }
+    }
+
+    /// Jump to the line of the suggestion at `selected`, as one cursor move
+    /// from where the prompt opened
+    pub(super) fn confirm_search_lines(&mut self, selected: Option<usize>) {
+        let Some(target) = self.search_lines.as_ref().and_then(|state| {
+            let result = state.results.get(selected?)?;
+            Some(state.search.lines()[result.index].clone())
+        }) else {
+            self.cancel_search_lines();
+            self.set_status_message("No matching line".to_string());
+            return;
+        };
+        self.clear_search_lines_highlights();
+        let Some(state) = self.search_lines.take() else {
+            return;
+        };
+        if state.buffer_id != self.active_buffer() {
+            return;
+        }
+
+        let (old_position, old_anchor) = state.cursor;
+        let active = self.active_state_mut();
+        let cursor_id = active.cursors.primary_id();
+        let old_sticky_column = active.cursors.primary().sticky_column;
+        let event = Event::MoveCursor {
+            cursor_id,
+            old_position,
+            new_position: target.offset,
+            old_anchor,
+            new_anchor: None,
+            old_sticky_column,
+            new_sticky_column: 0,
+        };
+        active.apply(&event);
+        self.move_cursor_to_match(target.offset);
+        self.set_status_message(format!("Jumped to line {}", target.line + 1));
+    }
+
+    /// Close Search Lines, putting the cursor and view back where they were
+    pub(super) fn cancel_search_lines(&mut self) {
+        self.restore_search_lines_view();
+        self.clear_search_lines_highlights();
+        self.search_lines = None;
+    }
+
+    fn restore_search_lines_view(&mut self) {
+        let Some(state) = self.search_lines.as_ref() else {
+            return;
+        };
+        if state.buffer_id != self.active_buffer() {
+            return;
+        }
+        let (position, anchor) = state.cursor;
+        let (top_byte, top_view_line_offset, left_column) = state.viewport;
+
+        let cursor = self.active_state_mut().cursors.primary_mut();
+        cursor.position = position;
+        cursor.anchor = anchor;
+        if let Some(view_state) = self
+            .split_view_states
+            .get_mut(&self.split_manager.active_split())
+        {
+            let viewport = &mut view_state.viewport;
+            viewport.top_byte = top_byte;
+            viewport.top_view_line_offset = top_view_line_offset;
+            viewport.left_column = left_column;
+            viewport.set_skip_ensure_visible();
+        }
+    }
+
+    fn clear_search_lines_highlights(&mut self) {
+        let namespace = OverlayNamespace::from_string(SEARCH_LINES_NAMESPACE.to_string());
+        let buffer_id = self
+            .search_lines
+            .as_ref()
+            .map_or(self.active_buffer(), |state| state.buffer_id);
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state
+                .overlays
+                .clear_namespace(&namespace, &mut state.marker_list);
+        }
+    }
+}
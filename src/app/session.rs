@@ -31,10 +31,11 @@ use crate::state::EditorState;
 use crate::model::event::{BufferId, SplitDirection, SplitId};
 use crate::services::terminal::TerminalId;
 use crate::session::{
-    FileExplorerState, PersistedFileSession, SearchOptions, SerializedBookmark, SerializedCursor,
-    SerializedFileState, SerializedScroll, SerializedSplitDirection, SerializedSplitNode,
-    SerializedSplitViewState, SerializedTabRef, SerializedTerminalSession, SerializedViewMode,
-    Session, SessionConfigOverrides, SessionError, SessionHistories, SESSION_VERSION,
+    FileExplorerState, PersistedFileSession, SearchOptions, SerializedBookmark,
+    SerializedBreakpoint, SerializedCursor, SerializedFileState, SerializedScroll,
+    SerializedSplitDirection, SerializedSplitNode, SerializedSplitViewState, SerializedTabRef,
+    SerializedTerminalSession, SerializedViewMode, Session, SessionConfigOverrides, SessionError,
+    SessionHistories, SESSION_VERSION,
 };
 use crate::state::ViewMode;
 use crate::view::split::{SplitNode, SplitViewState};
@@ -221,7 +222,7 @@ impl Editor {
         };
 
         // Capture histories using the items() accessor
-        // Note: Only search and replace histories exist in Editor currently.
+        // Note: Only search, replace and clipboard histories exist in Editor currently.
         // Other history fields are placeholders for future features.
         let histories = SessionHistories {
             search: self.search_history.items().to_vec(),
@@ -229,6 +230,11 @@ impl Editor {
             command_palette: Vec::new(), // Future: when command palette has history
             goto_line: Vec::new(),       // Future: when goto line prompt has history
             open_file: Vec::new(),       // Future: when file open prompt has history
+            clipboard: self
+                .clipboard_history
+                .entries()
+                .map(|entry| entry.pieces().to_vec())
+                .collect(),
         };
         tracing::trace!(
             "Captured histories: {} search, {} replace",
@@ -242,12 +248,16 @@ impl Editor {
             whole_word: self.search_whole_word,
             use_regex: self.search_use_regex,
             confirm_each: self.search_confirm_each,
+            preserve_case: self.search_preserve_case,
         };
 
         // Capture bookmarks
         let bookmarks =
             serialize_bookmarks(&self.bookmarks, &self.buffer_metadata, &self.working_dir);
 
+        // Capture debugger breakpoints (lines are synced from markers in save_session)
+        let breakpoints = serialize_breakpoints(&self.breakpoints, &self.working_dir);
+
         Session {
             version: SESSION_VERSION,
             working_dir: self.working_dir.clone(),
@@ -260,6 +270,8 @@ impl Editor {
             search_options,
             bookmarks,
             terminals,
+            breakpoints,
+            watch_expressions: self.debug_watches.clone(),
             saved_at: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
@@ -279,6 +291,9 @@ impl Editor {
         // Save global file states for all open file buffers
         self.save_all_global_file_states();
 
+        // Breakpoint lines follow edits through margin markers; store them
+        self.sync_all_breakpoint_lines();
+
         let session = self.capture_session();
         session.save()
     }
@@ -437,6 +452,7 @@ impl Editor {
         self.search_whole_word = session.search_options.whole_word;
         self.search_use_regex = session.search_options.use_regex;
         self.search_confirm_each = session.search_options.confirm_each;
+        self.search_preserve_case = session.search_options.preserve_case;
 
         // 3. Restore histories (merge with any existing)
         tracing::debug!(
@@ -450,6 +466,15 @@ impl Editor {
         for item in &session.histories.replace {
             self.replace_history.push(item.clone());
         }
+        if !session.histories.clipboard.is_empty() {
+            self.clipboard_history.restore(
+                session
+                    .histories
+                    .clipboard
+                    .iter()
+                    .map(|pieces| crate::input::kill_ring::KillEntry::new(pieces.clone())),
+            );
+        }
 
         // 4. Restore file explorer state
         self.file_explorer_visible = session.file_explorer.visible;
@@ -545,6 +570,11 @@ impl Editor {
             }
         }
 
+        // 8. Restore debugger breakpoints and watch expressions
+        self.breakpoints = deserialize_breakpoints(&session.breakpoints, &self.working_dir);
+        self.refresh_all_breakpoint_indicators();
+        self.debug_watches = session.watch_expressions.clone();
+
         tracing::debug!(
             "Session restore complete: {} splits, {} buffers",
             self.split_view_states.len(),
@@ -1166,6 +1196,45 @@ fn serialize_bookmarks(
         .collect()
 }
 
+fn serialize_breakpoints(
+    breakpoints: &crate::services::dap::BreakpointStore,
+    working_dir: &Path,
+) -> Vec<SerializedBreakpoint> {
+    breakpoints
+        .iter()
+        .map(|(path, bp)| SerializedBreakpoint {
+            file_path: path
+                .strip_prefix(working_dir)
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|_| path.clone()),
+            line: bp.line,
+            condition: bp.condition.clone(),
+            log_message: bp.log_message.clone(),
+        })
+        .collect()
+}
+
+fn deserialize_breakpoints(
+    breakpoints: &[SerializedBreakpoint],
+    working_dir: &Path,
+) -> crate::services::dap::BreakpointStore {
+    let mut store = crate::services::dap::BreakpointStore::new();
+    for bp in breakpoints {
+        // Buffers use canonical paths; `join` keeps absolute paths as they are
+        let path = working_dir.join(&bp.file_path);
+        let path = path.canonicalize().unwrap_or(path);
+        store.set(
+            &path,
+            crate::services::dap::Breakpoint {
+                condition: bp.condition.clone(),
+                log_message: bp.log_message.clone(),
+                ..crate::services::dap::Breakpoint::new(bp.line)
+            },
+        );
+    }
+    store
+}
+
 /// Collect all unique file paths from split_states
 fn collect_file_paths_from_states(
     split_states: &HashMap<usize, SerializedSplitViewState>,
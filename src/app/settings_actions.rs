@@ -82,6 +82,7 @@ impl Editor {
 
         // Update keybindings
         self.keybindings = KeybindingResolver::new(&self.config);
+        self.refresh_vi_state();
 
         // Save to disk
         if let Err(e) = std::fs::create_dir_all(&self.dir_context.config_dir) {
@@ -0,0 +1,536 @@
+//! Snippet actions for the Editor: inserting snippets and filling in their fields.
+//!
+//! Snippets come from the user and project snippet directories (see
+//! `input::snippets`). Inserting one selects its first field; Tab and
+//! Shift+Tab move between fields until the final cursor position is reached,
+//! and mirrors of the current field are updated after every edit.
+
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::input::commands::Suggestion;
+use crate::input::snippets::{load_snippets, Snippet, SnippetSession};
+use crate::model::event::{
+    Event, PopupContentData, PopupData, PopupListItemData, PopupPositionData,
+};
+use crate::primitives::snippet::SnippetBody;
+use crate::primitives::word_navigation::find_completion_word_start;
+use crate::services::lsp::manager::detect_language;
+use crate::view::prompt::PromptType;
+
+use super::Editor;
+
+/// Icon marking snippets in the completion popup
+pub(super) const SNIPPET_ICON: &str = "✂";
+
+/// Title of the popup listing a choice field's alternatives
+pub(super) const SNIPPET_CHOICE_TITLE: &str = "Snippet Choice";
+
+impl Editor {
+    /// Snippets for the active buffer's language, user snippets first and
+    /// project snippets (`.fresh/snippets/`) overriding them
+    fn active_snippets(&self) -> Vec<Snippet> {
+        let language = self
+            .active_state()
+            .buffer
+            .file_path()
+            .and_then(|path| detect_language(path, &self.config.languages));
+        let dirs = [
+            self.dir_context.snippets_dir(),
+            self.working_dir.join(".fresh").join("snippets"),
+        ];
+        load_snippets(&dirs, language.as_deref())
+    }
+
+    /// Completion popup items for snippets whose prefix starts with the word
+    /// before the cursor
+    pub(super) fn snippet_completion_items(&mut self) -> Vec<PopupListItemData> {
+        let (word_start, cursor_pos) = self.completion_word_range();
+        let word = self
+            .active_state_mut()
+            .get_text_range(word_start, cursor_pos)
+            .to_lowercase();
+
+        self.active_snippets()
+            .into_iter()
+            .filter_map(|snippet| {
+                let prefix = snippet
+                    .prefixes
+                    .iter()
+                    .find(|prefix| prefix.to_lowercase().starts_with(&word))?
+                    .clone();
+                Some(PopupListItemData {
+                    text: prefix,
+                    detail: Some(snippet.description.unwrap_or(snippet.name)),
+                    icon: Some(SNIPPET_ICON.to_string()),
+                    data: Some(snippet.body),
+                })
+            })
+            .collect()
+    }
+
+    /// Range of the word the completion popup completes
+    pub(super) fn completion_word_range(&self) -> (usize, usize) {
+        let state = self.active_state();
+        let cursor_pos = state.cursors.primary().position;
+        (
+            find_completion_word_start(&state.buffer, cursor_pos),
+            cursor_pos,
+        )
+    }
+
+    /// Insert a snippet picked in the completion popup in place of its prefix
+    pub(super) fn insert_snippet_completion(&mut self, body: &str) {
+        let (word_start, cursor_pos) = self.completion_word_range();
+        self.insert_snippet(body, word_start..cursor_pos, "");
+    }
+
+    /// Open a picker listing the snippets for the active buffer
+    pub(super) fn start_insert_snippet_prompt(&mut self) {
+        let snippets = self.active_snippets();
+        if snippets.is_empty() {
+            self.set_status_message("No snippets for this buffer".to_string());
+            return;
+        }
+
+        let suggestions = snippets
+            .into_iter()
+            .enumerate()
+            .map(|(index, snippet)| {
+                let prefixes = snippet.prefixes.join(", ");
+                let description = match snippet.description {
+                    Some(description) if !prefixes.is_empty() => {
+                        Some(format!("{}  {}", prefixes, description))
+                    }
+                    Some(description) => Some(description),
+                    None => (!prefixes.is_empty()).then_some(prefixes),
+                };
+                Suggestion {
+                    text: snippet.name,
+                    description,
+                    value: Some(index.to_string()),
+                    disabled: false,
+                    keybinding: None,
+                    source: None,
+                }
+            })
+            .collect();
+
+        self.prompt = Some(crate::view::prompt::Prompt::with_suggestions(
+            "Insert snippet: ".to_string(),
+            PromptType::InsertSnippet,
+            suggestions,
+        ));
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.selected_suggestion = Some(0);
+        }
+    }
+
+    /// Insert snippet `index` from the picker at the cursor
+    ///
+    /// A selection is replaced by the snippet and available to it as
+    /// `$SELECTED_TEXT`, so snippets using it surround the selection.
+    pub(super) fn insert_snippet_by_index(&mut self, index: usize) {
+        let Some(snippet) = self.active_snippets().into_iter().nth(index) else {
+            return;
+        };
+        let cursor = self.active_state().cursors.primary();
+        let range = cursor
+            .selection_range()
+            .unwrap_or(cursor.position..cursor.position);
+        let selected = self
+            .active_state_mut()
+            .get_text_range(range.start, range.end);
+        self.insert_snippet(&snippet.body, range, &selected);
+    }
+
+    /// Replace `range` with the expansion of `body` and select its first field
+    fn insert_snippet(&mut self, body: &str, range: Range<usize>, selected_text: &str) {
+        self.snippet_session = None;
+
+        let indent = self.snippet_line_indent(range.start);
+        let newline = format!("{}{}", self.to_buffer_line_endings("\n"), indent);
+        // The expansion re-indents every line, so strip the selection's own
+        // indentation from its continuation lines
+        let selected_text = selected_text
+            .replace("\r\n", "\n")
+            .split('\n')
+            .enumerate()
+            .map(|(i, line)| match line.strip_prefix(indent.as_str()) {
+                Some(rest) if i > 0 => rest,
+                _ => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let variables = self.snippet_variables(range.start, selected_text);
+        let expansion =
+            SnippetBody::parse(body).expand(&|name: &str| variables.get(name).cloned(), &newline);
+
+        let cursor_id = self.active_state().cursors.primary_id();
+        let mut events = Vec::new();
+        if !range.is_empty() {
+            let deleted_text = self
+                .active_state_mut()
+                .get_text_range(range.start, range.end);
+            events.push(Event::Delete {
+                range: range.clone(),
+                deleted_text,
+                cursor_id,
+            });
+        }
+        if !expansion.text.is_empty() {
+            events.push(Event::Insert {
+                position: range.start,
+                text: expansion.text.clone(),
+                cursor_id,
+            });
+        }
+        self.apply_events_as_action(events, "Insert snippet".to_string());
+
+        self.snippet_session = Some(SnippetSession::new(
+            self.active_buffer(),
+            &expansion,
+            range.start,
+        ));
+        self.select_snippet_field();
+    }
+
+    /// Leading whitespace of the line containing `position`
+    fn snippet_line_indent(&mut self, position: usize) -> String {
+        let state = self.active_state_mut();
+        let line = state.buffer.position_to_line_col(position).0;
+        let line_start = state.buffer.line_start_offset(line).unwrap_or(position);
+        state
+            .get_text_range(line_start, position)
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect()
+    }
+
+    /// Values of the snippet variables for an insertion at `position`
+    fn snippet_variables(
+        &mut self,
+        position: usize,
+        selected_text: String,
+    ) -> HashMap<&'static str, String> {
+        let mut variables = HashMap::new();
+        variables.insert("TM_SELECTED_TEXT", selected_text.clone());
+        variables.insert("SELECTED_TEXT", selected_text);
+        variables.insert("CLIPBOARD", self.clipboard.paste().unwrap_or_default());
+
+        let state = self.active_state_mut();
+        let line = state.buffer.position_to_line_col(position).0;
+        let line_text = state
+            .buffer
+            .get_line(line)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+        variables.insert(
+            "TM_CURRENT_LINE",
+            line_text.trim_end_matches(['\n', '\r']).to_string(),
+        );
+        variables.insert("TM_LINE_INDEX", line.to_string());
+        variables.insert("TM_LINE_NUMBER", (line + 1).to_string());
+
+        if let Some(path) = self.active_state().buffer.file_path() {
+            let lossy = |s: Option<&std::ffi::OsStr>| {
+                s.map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            };
+            variables.insert("TM_FILENAME", lossy(path.file_name()));
+            variables.insert("TM_FILENAME_BASE", lossy(path.file_stem()));
+            variables.insert(
+                "TM_DIRECTORY",
+                lossy(path.parent().map(|dir| dir.as_os_str())),
+            );
+            variables.insert("TM_FILEPATH", lossy(Some(path.as_os_str())));
+            let relative = path.strip_prefix(&self.working_dir).unwrap_or(path);
+            variables.insert("RELATIVE_FILEPATH", lossy(Some(relative.as_os_str())));
+        }
+        variables.insert(
+            "WORKSPACE_NAME",
+            self.working_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        );
+        variables.insert(
+            "WORKSPACE_FOLDER",
+            self.working_dir.to_string_lossy().into_owned(),
+        );
+
+        let now = chrono::Local::now();
+        for (name, format) in [
+            ("CURRENT_YEAR", "%Y"),
+            ("CURRENT_YEAR_SHORT", "%y"),
+            ("CURRENT_MONTH", "%m"),
+            ("CURRENT_MONTH_NAME", "%B"),
+            ("CURRENT_MONTH_NAME_SHORT", "%b"),
+            ("CURRENT_DATE", "%d"),
+            ("CURRENT_DAY_NAME", "%A"),
+            ("CURRENT_DAY_NAME_SHORT", "%a"),
+            ("CURRENT_HOUR", "%H"),
+            ("CURRENT_MINUTE", "%M"),
+            ("CURRENT_SECOND", "%S"),
+        ] {
+            variables.insert(name, now.format(format).to_string());
+        }
+        variables.insert("CURRENT_SECONDS_UNIX", now.timestamp().to_string());
+        variables
+    }
+
+    /// Select the current snippet field, ending the session at the final
+    /// cursor position
+    fn select_snippet_field(&mut self) {
+        let Some(session) = &self.snippet_session else {
+            return;
+        };
+        let Some(field) = session.current_field() else {
+            self.snippet_session = None;
+            return;
+        };
+        let range = field.range.clone();
+        let choices = field.choices.clone();
+        if session.at_final() {
+            self.snippet_session = None;
+        }
+
+        let cursor_id = self.active_state().cursors.primary_id();
+        let cursor = self.active_state().cursors.primary();
+        let event = Event::MoveCursor {
+            cursor_id,
+            old_position: cursor.position,
+            new_position: range.end,
+            old_anchor: cursor.anchor,
+            new_anchor: (!range.is_empty()).then_some(range.start),
+            old_sticky_column: cursor.sticky_column,
+            new_sticky_column: 0,
+        };
+        self.apply_events_as_action(vec![event], "Snippet field".to_string());
+
+        if self.snippet_session.is_some() && !choices.is_empty() {
+            self.show_snippet_choices(choices);
+        }
+    }
+
+    /// Offer the alternatives of a choice field in a popup
+    fn show_snippet_choices(&mut self, choices: Vec<String>) {
+        let popup = PopupData {
+            title: Some(SNIPPET_CHOICE_TITLE.to_string()),
+            transient: false,
+            content: PopupContentData::List {
+                items: choices
+                    .into_iter()
+                    .map(|choice| PopupListItemData {
+                        text: choice.clone(),
+                        detail: None,
+                        icon: None,
+                        data: Some(choice),
+                    })
+                    .collect(),
+                selected: 0,
+            },
+            position: PopupPositionData::BelowCursor,
+            width: 30,
+            max_height: 10,
+            bordered: true,
+        };
+        self.active_state_mut().apply(&Event::ShowPopup { popup });
+    }
+
+    /// Replace the current snippet field with the alternative picked from its
+    /// choice popup
+    pub(super) fn choose_snippet_choice(&mut self, choice: String) {
+        let Some(field) = self
+            .snippet_session
+            .as_ref()
+            .and_then(SnippetSession::current_field)
+        else {
+            return;
+        };
+        let range = field.range.clone();
+        let cursor_id = self.active_state().cursors.primary_id();
+        let deleted_text = self
+            .active_state_mut()
+            .get_text_range(range.start, range.end);
+        let mut events = Vec::new();
+        if !range.is_empty() {
+            events.push(Event::Delete {
+                range: range.clone(),
+                deleted_text,
+                cursor_id,
+            });
+        }
+        if !choice.is_empty() {
+            events.push(Event::Insert {
+                position: range.start,
+                text: choice,
+                cursor_id,
+            });
+        }
+        self.apply_events_as_action(events, "Snippet choice".to_string());
+        self.sync_snippet_mirrors();
+    }
+
+    /// Tab and Shift+Tab move between snippet fields; Escape ends the
+    /// session and is then handled as usual
+    pub(super) fn handle_snippet_key(&mut self, key: KeyEvent) -> Option<io::Result<()>> {
+        self.snippet_session.as_ref()?;
+        let forward = match (key.code, key.modifiers) {
+            (KeyCode::Tab, KeyModifiers::NONE) => true,
+            (KeyCode::BackTab, _) | (KeyCode::Tab, KeyModifiers::SHIFT) => false,
+            (KeyCode::Esc, _) => {
+                self.snippet_session = None;
+                return None;
+            }
+            _ => return None,
+        };
+        if let Some(session) = self.snippet_session.as_mut() {
+            session.advance(forward);
+        }
+        self.select_snippet_field();
+        Some(Ok(()))
+    }
+
+    /// Keep the snippet session's fields in step with a buffer edit
+    pub(super) fn adjust_snippet_session_for_event(&mut self, event: &Event) {
+        let active_buffer = self.active_buffer();
+        if let Some(session) = self
+            .snippet_session
+            .as_mut()
+            .filter(|session| session.buffer_id == active_buffer)
+        {
+            adjust_session(session, event);
+        }
+    }
+
+    /// After a command: end the snippet session once the cursor leaves the
+    /// snippet or another buffer is active, otherwise update mirrors
+    pub(super) fn update_snippet_session(&mut self) {
+        let Some(session) = &self.snippet_session else {
+            return;
+        };
+        let extent = session.extent();
+        let cursor = self.active_state().cursors.primary().position;
+        let in_snippet = extent.start <= cursor && cursor <= extent.end;
+        if session.buffer_id != self.active_buffer() || !in_snippet {
+            self.snippet_session = None;
+            return;
+        }
+        self.sync_snippet_mirrors();
+    }
+
+    /// Copy the current field's text, transformed as needed, into its mirrors
+    fn sync_snippet_mirrors(&mut self) {
+        let Some(field) = self
+            .snippet_session
+            .as_ref()
+            .and_then(SnippetSession::current_field)
+        else {
+            return;
+        };
+        let range = field.range.clone();
+        let text = self
+            .active_state_mut()
+            .get_text_range(range.start, range.end);
+        let Some(session) = &self.snippet_session else {
+            return;
+        };
+        let mut updates: Vec<(usize, Range<usize>, String)> = session
+            .mirror_updates(&text)
+            .into_iter()
+            .map(|(i, text)| (i, session.fields()[i].range.clone(), text))
+            .collect();
+        let state = self.active_state_mut();
+        updates.retain(|(_, range, text)| state.get_text_range(range.start, range.end) != *text);
+        if updates.is_empty() {
+            return;
+        }
+        // From the end of the buffer backwards, so earlier ranges stay valid
+        updates.sort_by_key(|(_, range, _)| std::cmp::Reverse(range.start));
+
+        let cursor_id = state.cursors.primary_id();
+        let cursor = *state.cursors.primary();
+        let shift = |offset: usize| {
+            let delta: isize = updates
+                .iter()
+                .filter(|(_, range, _)| range.end <= offset)
+                .map(|(_, range, text)| text.len() as isize - range.len() as isize)
+                .sum();
+            offset.saturating_add_signed(delta)
+        };
+        // The edits move the cursor, so put it back where the user left it
+        let restore_cursor = Event::MoveCursor {
+            cursor_id,
+            old_position: cursor.position,
+            new_position: shift(cursor.position),
+            old_anchor: cursor.anchor,
+            new_anchor: cursor.anchor.map(shift),
+            old_sticky_column: cursor.sticky_column,
+            new_sticky_column: cursor.sticky_column,
+        };
+
+        let mut steps = Vec::new();
+        for (i, range, text) in updates {
+            let mut events = Vec::new();
+            if !range.is_empty() {
+                let deleted_text = self
+                    .active_state_mut()
+                    .get_text_range(range.start, range.end);
+                events.push(Event::Delete {
+                    range: range.clone(),
+                    deleted_text,
+                    cursor_id,
+                });
+            }
+            if !text.is_empty() {
+                events.push(Event::Insert {
+                    position: range.start,
+                    text,
+                    cursor_id,
+                });
+            }
+            steps.push((i, events));
+        }
+
+        // One undo step, but applied event by event: each mirror is rewritten
+        // as the field being edited, so it grows with the inserted text
+        let mut batch: Vec<Event> = steps
+            .iter()
+            .flat_map(|(_, events)| events.iter().cloned())
+            .collect();
+        batch.push(restore_cursor.clone());
+        self.active_event_log_mut().append(Event::Batch {
+            events: batch,
+            description: "Update snippet mirrors".to_string(),
+        });
+        for (i, events) in steps {
+            if let Some(session) = self.snippet_session.as_mut() {
+                session.set_editing(Some(i));
+            }
+            for event in events {
+                self.apply_event_to_active_buffer(&event);
+            }
+        }
+        if let Some(session) = self.snippet_session.as_mut() {
+            session.set_editing(None);
+        }
+        self.apply_event_to_active_buffer(&restore_cursor);
+    }
+}
+
+fn adjust_session(session: &mut SnippetSession, event: &Event) {
+    match event {
+        Event::Insert { position, text, .. } => session.adjust_for_insert(*position, text.len()),
+        Event::Delete { range, .. } => session.adjust_for_delete(range.clone()),
+        Event::Batch { events, .. } => {
+            for event in events {
+                adjust_session(session, event);
+            }
+        }
+        _ => {}
+    }
+}
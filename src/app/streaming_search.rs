@@ -0,0 +1,340 @@
+//! Search and replace in large-file buffers
+//!
+//! Large files aren't held in memory as one string, so their searches run
+//! on a background thread over a snapshot of the buffer (see
+//! `services::streaming_search`). Matches are merged into the search state
+//! as they arrive, and the cursor moves to the first one found. Replace All
+//! collects every match the same way and then replaces them in one undoable
+//! batch, reading only the text of the matches themselves.
+
+use std::io;
+use std::ops::Range;
+
+use crate::model::event::BufferId;
+use crate::services::replace::{Replacement, Replacer};
+use crate::services::streaming_search::{
+    build_regex, SearchDirection, SearchJob, SearchMatch, SearchUpdate, LOOK_BEHIND,
+};
+
+use super::types::SearchState;
+use super::Editor;
+
+/// A search of a large-file buffer running in the background
+pub(super) struct StreamingSearch {
+    job: SearchJob,
+    buffer_id: BufferId,
+    query: String,
+    direction: SearchDirection,
+    /// Matches found so far, only kept for Replace All
+    found: Vec<SearchMatch>,
+    /// Replacement typed, and how to expand it for each match, once all
+    /// are found
+    replacement: Option<(String, Replacer)>,
+    /// Where the cursor should go: the first match found from here on, if
+    /// it hasn't moved yet
+    pending_jump: Option<usize>,
+    searched: usize,
+}
+
+impl StreamingSearch {
+    /// Whether `pos` is past the jump origin in the search direction
+    fn ahead_of(&self, origin: usize, pos: usize) -> bool {
+        match self.direction {
+            SearchDirection::Forward => pos >= origin,
+            SearchDirection::Backward => pos < origin,
+        }
+    }
+
+    fn percent_done(&self) -> usize {
+        (self.searched * 100)
+            .checked_div(self.job.total())
+            .unwrap_or(100)
+    }
+}
+
+fn match_count(count: usize) -> String {
+    format!("{} match{}", count, if count == 1 { "" } else { "es" })
+}
+
+impl Editor {
+    pub(super) fn move_cursor_to_match(&mut self, pos: usize) {
+        let active_split = self.split_manager.active_split();
+        let active_buffer = self.active_buffer();
+        let state = self.active_state_mut();
+        state.cursors.primary_mut().position = pos;
+        state.cursors.primary_mut().anchor = None;
+        if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
+            let state = self.buffers.get_mut(&active_buffer).unwrap();
+            view_state
+                .viewport
+                .ensure_visible(&mut state.buffer, state.cursors.primary());
+        }
+    }
+
+    /// Search the active (large-file) buffer in the background from
+    /// `origin` in `direction`, moving the cursor to the first match found
+    pub(super) fn start_streaming_search(
+        &mut self,
+        query: &str,
+        search_range: Option<Range<usize>>,
+        origin: usize,
+        direction: SearchDirection,
+    ) {
+        self.streaming_search = None;
+        let regex = match build_regex(
+            query,
+            self.search_case_sensitive,
+            self.search_whole_word,
+            self.search_use_regex,
+        ) {
+            Ok(regex) => regex,
+            Err(e) => {
+                self.search_state = None;
+                self.set_status_message(format!("Invalid regex: {}", e));
+                return;
+            }
+        };
+
+        let state = self.active_state();
+        let range = search_range.clone().unwrap_or(0..state.buffer.len());
+        let job = SearchJob::start(
+            state.buffer.content_snapshot(),
+            regex,
+            range,
+            origin,
+            direction,
+        );
+        self.search_state = Some(SearchState {
+            query: query.to_string(),
+            matches: Vec::new(),
+            current_match_index: None,
+            wrap_search: search_range.is_none(),
+            search_range,
+        });
+        self.streaming_search = Some(StreamingSearch {
+            job,
+            buffer_id: self.active_buffer(),
+            query: query.to_string(),
+            direction,
+            found: Vec::new(),
+            replacement: None,
+            pending_jump: Some(origin),
+            searched: 0,
+        });
+        self.set_status_message(format!("Searching for '{}'...", query));
+    }
+
+    /// Replace every match of `replacer` in the active (large-file) buffer,
+    /// once a background search has found them all
+    pub(super) fn start_streaming_replace(
+        &mut self,
+        search: &str,
+        replacement: &str,
+        replacer: Replacer,
+    ) {
+        self.streaming_search = None;
+        self.clear_search_highlights();
+        let state = self.active_state();
+        let job = SearchJob::start(
+            state.buffer.content_snapshot(),
+            replacer.regex().clone(),
+            0..state.buffer.len(),
+            0,
+            SearchDirection::Forward,
+        );
+        self.streaming_search = Some(StreamingSearch {
+            job,
+            buffer_id: self.active_buffer(),
+            query: search.to_string(),
+            direction: SearchDirection::Forward,
+            found: Vec::new(),
+            replacement: Some((replacement.to_string(), replacer)),
+            pending_jump: None,
+            searched: 0,
+        });
+        self.set_status_message(format!("Finding '{}' to replace...", search));
+    }
+
+    /// Stop a running background search or replace, if any
+    pub(super) fn cancel_streaming_search(&mut self) {
+        if let Some(search) = self.streaming_search.take() {
+            let what = if search.replacement.is_some() {
+                "Replace"
+            } else {
+                "Search"
+            };
+            self.set_status_message(format!("{} cancelled.", what));
+        }
+    }
+
+    /// The replacement for each match found by a background search,
+    /// reading just the text of the match and a few bytes before it
+    fn streamed_replacements(
+        &self,
+        replacer: &Replacer,
+        matches: &[SearchMatch],
+    ) -> io::Result<Vec<Replacement>> {
+        let mut snapshot = self.active_state().buffer.content_snapshot();
+        let mut replacements = Vec::with_capacity(matches.len());
+        for found in matches {
+            let base = found.start.saturating_sub(LOOK_BEHIND);
+            let bytes = snapshot.read(base..found.end)?;
+            if let Some(replacement) = replacer.replacement_at(&bytes, found.start - base) {
+                replacements.push(Replacement {
+                    range: base + replacement.range.start..base + replacement.range.end,
+                    ..replacement
+                });
+            }
+        }
+        Ok(replacements)
+    }
+
+    /// Find next/previous while a background search is still running
+    ///
+    /// Matches that haven't been found yet may lie between the cursor and
+    /// the nearest known one, so the search restarts from the cursor in
+    /// `direction` (keeping what it found) and the cursor moves to the
+    /// first new match. Returns false if no search is running.
+    pub(super) fn streaming_search_step(&mut self, direction: SearchDirection) -> bool {
+        let Some(search) = &self.streaming_search else {
+            return false;
+        };
+        if search.replacement.is_some() {
+            return false;
+        }
+        let Some(search_state) = &self.search_state else {
+            return false;
+        };
+        let query = search.query.clone();
+        let matches = search_state.matches.clone();
+        let current_match_index = search_state.current_match_index;
+        let search_range = search_state.search_range.clone();
+        let cursor = self.active_state().cursors.primary().position;
+
+        // Step off a match the cursor is on when searching forward
+        let origin = match direction {
+            SearchDirection::Forward => cursor + 1,
+            SearchDirection::Backward => cursor,
+        };
+        self.start_streaming_search(&query, search_range, origin, direction);
+        if let Some(search_state) = self.search_state.as_mut() {
+            search_state.matches = matches;
+            search_state.current_match_index = current_match_index;
+        }
+        true
+    }
+
+    /// Take in the results of a running background search
+    ///
+    /// Returns true if anything changed (requires re-render).
+    pub(super) fn poll_streaming_search(&mut self) -> bool {
+        let Some(search) = &self.streaming_search else {
+            return false;
+        };
+        if search.buffer_id != self.active_buffer() {
+            self.cancel_streaming_search();
+            return true;
+        }
+        let updates = search.job.poll();
+        if updates.is_empty() {
+            return false;
+        }
+
+        let mut search = self.streaming_search.take().unwrap();
+        let mut new_matches = Vec::new();
+        let mut finished = false;
+        for update in updates {
+            match update {
+                SearchUpdate::Found { matches, searched } => {
+                    search.searched = searched;
+                    new_matches.extend(matches);
+                }
+                SearchUpdate::Done => finished = true,
+                SearchUpdate::Failed(e) => {
+                    self.set_status_message(format!("Search failed: {}", e));
+                    return true;
+                }
+            }
+        }
+
+        if let Some((replacement, replacer)) = &search.replacement {
+            search.found.extend(new_matches);
+            if finished {
+                match self.streamed_replacements(replacer, &search.found) {
+                    Ok(replacements) => {
+                        self.replace_matches(&search.query, replacement, replacements)
+                    }
+                    Err(e) => self.set_status_message(format!("Replace failed: {}", e)),
+                }
+            } else {
+                self.set_status_message(format!(
+                    "Finding '{}' to replace... {}% ({} found)",
+                    search.query,
+                    search.percent_done(),
+                    match_count(search.found.len())
+                ));
+                self.streaming_search = Some(search);
+            }
+            return true;
+        }
+
+        let Some(search_state) = self.search_state.as_mut() else {
+            return true;
+        };
+        let mut jump_to = None;
+        for found in new_matches {
+            if let Some(origin) = search.pending_jump {
+                if search_state.wrap_search || search.ahead_of(origin, found.start) {
+                    search.pending_jump = None;
+                    jump_to = Some(found.start);
+                }
+            }
+            if let Err(index) = search_state.matches.binary_search(&found.start) {
+                search_state.matches.insert(index, found.start);
+                if let Some(current) = search_state.current_match_index.as_mut() {
+                    if index <= *current {
+                        *current += 1;
+                    }
+                }
+            }
+        }
+        if let Some(pos) = jump_to {
+            search_state.current_match_index = search_state.matches.binary_search(&pos).ok();
+            self.move_cursor_to_match(pos);
+        }
+
+        let search_state = self.search_state.as_ref().unwrap();
+        let count = search_state.matches.len();
+        let in_selection = if search_state.search_range.is_some() {
+            " in selection"
+        } else {
+            ""
+        };
+        if !finished {
+            self.set_status_message(format!(
+                "Searching for '{}'{}... {}% ({} found)",
+                search.query,
+                in_selection,
+                search.percent_done(),
+                match_count(count)
+            ));
+            self.streaming_search = Some(search);
+        } else if count == 0 {
+            self.search_state = None;
+            self.set_status_message(format!(
+                "No matches found for '{}'{}",
+                search.query, in_selection
+            ));
+        } else if search.pending_jump.is_some() {
+            self.set_status_message("No more matches.".to_string());
+        } else {
+            self.set_status_message(format!(
+                "Found {} for '{}'{}",
+                match_count(count),
+                search.query,
+                in_selection
+            ));
+        }
+        true
+    }
+}
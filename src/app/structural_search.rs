@@ -0,0 +1,225 @@
+//! Structural search and replace for the Editor
+//!
+//! Searches for a structural pattern (see `services::structural_search`)
+//! in the active buffer or in every project file with a tree-sitter
+//! grammar. Matches open in the `*Project Search*` results buffer, like a
+//! project search. A replace rewrites each match with a template: in the
+//! buffer as one undoable edit, or across the project as one transaction
+//! that Undo Last Project Replace reverts.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use crate::primitives::highlighter::Language;
+use crate::services::project_search::MAX_RESULT_LINES;
+use crate::services::structural_search::{
+    replacements, result_lines, rewrite, search_project, FileMatches, StructuralPattern,
+};
+
+use super::project_search::{count_of, FileChange};
+use super::Editor;
+
+/// A structural search of the project running in the background
+pub(super) struct PendingStructuralSearch {
+    receiver: Receiver<Vec<FileMatches>>,
+    cancelled: Arc<AtomicBool>,
+    root: PathBuf,
+    pattern: Arc<StructuralPattern>,
+    /// Template to rewrite every match with, for a replace
+    template: Option<String>,
+}
+
+impl Drop for PendingStructuralSearch {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Editor {
+    /// Search for `pattern` in the active buffer, or in the project in the
+    /// background, then show the matches, or rewrite them with `template`
+    /// if given
+    pub(super) fn start_structural_search(
+        &mut self,
+        pattern: &str,
+        template: Option<String>,
+        in_buffer: bool,
+    ) {
+        self.structural_search = None;
+        if pattern.trim().is_empty() {
+            self.set_status_message("Search cancelled.".to_string());
+            return;
+        }
+        let pattern = StructuralPattern::new(pattern);
+        if in_buffer {
+            self.structural_search_buffer(&pattern, template);
+            return;
+        }
+
+        let files = self.project_files();
+        let root = files.root.clone();
+        let pattern = Arc::new(pattern);
+        let worker_pattern = pattern.clone();
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = cancelled.clone();
+        thread::spawn(move || {
+            let found = search_project(&files, &worker_pattern, &worker_cancelled);
+            let _ = sender.send(found);
+        });
+        self.set_status_message(format!(
+            "Searching project for structure '{}'...",
+            pattern.source()
+        ));
+        self.structural_search = Some(PendingStructuralSearch {
+            receiver,
+            cancelled,
+            root,
+            pattern,
+            template,
+        });
+    }
+
+    /// Search the active buffer, which is parsed as the language of its file
+    fn structural_search_buffer(&mut self, pattern: &StructuralPattern, template: Option<String>) {
+        if template.is_some() && self.is_editing_disabled() {
+            self.set_status_message("Editing disabled in this buffer".to_string());
+            return;
+        }
+        let state = self.active_state();
+        let Some(path) = state.buffer.file_path().map(|path| path.to_path_buf()) else {
+            self.set_status_message("Structural search needs a file".to_string());
+            return;
+        };
+        let Some(language) = Language::from_path(&path) else {
+            self.set_status_message(format!(
+                "No syntax tree for {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            return;
+        };
+        let Some(text) = state.buffer.to_string() else {
+            self.set_status_message("Buffer is too large for structural search".to_string());
+            return;
+        };
+        let matches = match pattern.find_all(language, &text) {
+            Ok(matches) => matches,
+            Err(e) => {
+                self.set_status_message(e);
+                return;
+            }
+        };
+        if matches.is_empty() {
+            self.set_status_message(format!("No matches found for '{}'", pattern.source()));
+            return;
+        }
+
+        match template {
+            Some(template) => {
+                let count = matches.len();
+                self.apply_match_replacements(
+                    replacements(&text, &matches, &template),
+                    format!("Replace '{}' with '{}'", pattern.source(), template),
+                );
+                self.set_status_message(format!("Replaced {}", count_of(count, "occurrence")));
+            }
+            None => {
+                let root = self
+                    .working_dir
+                    .canonicalize()
+                    .unwrap_or_else(|_| self.working_dir.clone());
+                let results = result_lines(&path, &text, &matches);
+                self.show_project_results(root, pattern.source(), results);
+            }
+        }
+    }
+
+    /// Take in the matches of a running structural search of the project
+    ///
+    /// Returns true if anything changed (requires re-render).
+    pub(super) fn poll_structural_search(&mut self) -> bool {
+        let Some(pending) = &self.structural_search else {
+            return false;
+        };
+        let found = match pending.receiver.try_recv() {
+            Ok(found) => found,
+            Err(TryRecvError::Empty) => return false,
+            Err(TryRecvError::Disconnected) => {
+                self.structural_search = None;
+                self.set_status_message("Structural search failed".to_string());
+                return true;
+            }
+        };
+        let pending = self.structural_search.take().unwrap();
+        if found.is_empty() {
+            // An invalid pattern matches nothing; say why
+            let message = match pending.pattern.errors().into_iter().next() {
+                Some(error) => error,
+                None => format!(
+                    "No matches found for '{}' in project",
+                    pending.pattern.source()
+                ),
+            };
+            self.set_status_message(message);
+        } else if let Some(template) = &pending.template {
+            self.structural_replace_in_project(&pending, template, found);
+        } else {
+            let results = found
+                .iter()
+                .flat_map(|file| result_lines(&file.path, &file.text, &file.matches))
+                .collect();
+            self.show_project_results(pending.root.clone(), pending.pattern.source(), results);
+        }
+        true
+    }
+
+    /// Rewrite every match of a finished structural search in its file
+    fn structural_replace_in_project(
+        &mut self,
+        pending: &PendingStructuralSearch,
+        template: &str,
+        found: Vec<FileMatches>,
+    ) {
+        let counts: Vec<usize> = found.iter().map(|file| file.matches.len()).collect();
+        let searched = counts.iter().sum::<usize>();
+        let changes = found
+            .into_iter()
+            .map(|file| FileChange {
+                after: rewrite(&file.text, &file.matches, template),
+                path: file.path,
+                before: file.text,
+            })
+            .collect();
+        let description = format!("Replace '{}' with '{}'", pending.pattern.source(), template);
+        let made = self.apply_file_changes(description, changes);
+        let replaced: usize = counts
+            .iter()
+            .zip(&made)
+            .filter(|(_, made)| **made)
+            .map(|(count, _)| count)
+            .sum();
+        let changed = made.iter().filter(|made| **made).count();
+
+        let mut message = format!(
+            "Replaced {} in {}",
+            count_of(replaced, "occurrence"),
+            count_of(changed, "file")
+        );
+        if changed < made.len() {
+            message.push_str(&format!(
+                ", skipped {} changed since the search",
+                count_of(made.len() - changed, "file")
+            ));
+        }
+        if searched >= MAX_RESULT_LINES {
+            message.push_str(&format!(
+                " (search stopped at {} matches; run it again for the rest)",
+                MAX_RESULT_LINES
+            ));
+        }
+        self.set_status_message(message);
+    }
+}
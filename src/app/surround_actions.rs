@@ -0,0 +1,182 @@
+//! Surround actions for the Editor: wrap selections in a pair, and change or
+//! delete the pair enclosing each cursor.
+//!
+//! All cursors are edited in one batch, so a single undo reverts the whole
+//! operation.
+
+use std::ops::Range;
+
+use crate::model::event::{CursorId, Event};
+use crate::primitives::surround::{self, Delimiters, Pair};
+use crate::view::prompt::PromptType;
+
+use super::Editor;
+
+/// Replacement of a buffer range, made on behalf of a cursor
+type SurroundEdit = (Range<usize>, String, CursorId);
+
+impl Editor {
+    /// Ask for the pair to wrap each selection in
+    pub(super) fn start_surround_add_prompt(&mut self) {
+        self.start_prompt(
+            "Surround with (character or <tag>): ".to_string(),
+            PromptType::SurroundAdd,
+        );
+    }
+
+    /// Ask for the pair to replace the surrounding pair with
+    pub(super) fn start_surround_change_prompt(&mut self) {
+        if self.enclosing_pairs().is_empty() {
+            self.set_status_message("No surrounding pair".to_string());
+            return;
+        }
+        self.start_prompt(
+            "Change surrounding pair to: ".to_string(),
+            PromptType::SurroundChange,
+        );
+    }
+
+    /// Wrap each selection in `input`'s pair; a cursor without a selection
+    /// gets an empty pair with the cursor inside
+    pub(super) fn surround_add(&mut self, input: &str) {
+        let Some(pair) = Pair::parse(input) else {
+            self.set_status_message(format!("Not a surround pair: {}", input));
+            return;
+        };
+        let mut ranges: Vec<(CursorId, Range<usize>)> = self
+            .active_state()
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| {
+                let range = cursor
+                    .selection_range()
+                    .unwrap_or(cursor.position..cursor.position);
+                (cursor_id, range)
+            })
+            .collect();
+        ranges.sort_by_key(|(_, range)| range.start);
+
+        let edits = ranges
+            .into_iter()
+            .flat_map(|(cursor_id, range)| {
+                [
+                    (range.start..range.start, pair.open.clone(), cursor_id),
+                    (range.end..range.end, pair.close.clone(), cursor_id),
+                ]
+            })
+            .collect();
+        self.apply_surround_edits(edits, "Surround");
+    }
+
+    /// Replace the pair enclosing each cursor with `input`'s pair
+    pub(super) fn surround_change(&mut self, input: &str) {
+        let Some(pair) = Pair::parse(input) else {
+            self.set_status_message(format!("Not a surround pair: {}", input));
+            return;
+        };
+        let edits: Vec<SurroundEdit> = self
+            .enclosing_pairs()
+            .into_iter()
+            .flat_map(|(cursor_id, delimiters)| {
+                [
+                    (delimiters.open, pair.open.clone(), cursor_id),
+                    (delimiters.close, pair.close.clone(), cursor_id),
+                ]
+            })
+            .collect();
+        if edits.is_empty() {
+            self.set_status_message("No surrounding pair".to_string());
+            return;
+        }
+        self.apply_surround_edits(edits, "Change surround");
+    }
+
+    /// Remove the pair enclosing each cursor
+    pub(super) fn surround_delete(&mut self) {
+        let edits: Vec<SurroundEdit> = self
+            .enclosing_pairs()
+            .into_iter()
+            .flat_map(|(cursor_id, delimiters)| {
+                [
+                    (delimiters.open, String::new(), cursor_id),
+                    (delimiters.close, String::new(), cursor_id),
+                ]
+            })
+            .collect();
+        if edits.is_empty() {
+            self.set_status_message("No surrounding pair".to_string());
+            return;
+        }
+        self.apply_surround_edits(edits, "Delete surround");
+    }
+
+    /// The pair enclosing each cursor's selection (or position), from the
+    /// syntax tree when the buffer has one and by bracket matching otherwise
+    ///
+    /// Cursors inside the same pair share one entry.
+    fn enclosing_pairs(&mut self) -> Vec<(CursorId, Delimiters)> {
+        let state = self.active_state_mut();
+        let tree = state.latest_syntax_tree().cloned();
+        let ranges: Vec<(CursorId, Range<usize>)> = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| {
+                let range = cursor
+                    .selection_range()
+                    .unwrap_or(cursor.position..cursor.position);
+                (cursor_id, range)
+            })
+            .collect();
+
+        let mut pairs: Vec<(CursorId, Delimiters)> = Vec::new();
+        for (cursor_id, range) in ranges {
+            let found = tree
+                .as_ref()
+                .and_then(|tree| surround::enclosing_in_tree(tree, &state.buffer, range.clone()))
+                .or_else(|| surround::enclosing_by_matching(&state.buffer, range));
+            let Some(delimiters) = found else {
+                continue;
+            };
+            let overlaps = |a: &Range<usize>, b: &Range<usize>| a.start < b.end && b.start < a.end;
+            let shared = pairs.iter().any(|(_, other)| {
+                [&other.open, &other.close]
+                    .into_iter()
+                    .any(|r| overlaps(r, &delimiters.open) || overlaps(r, &delimiters.close))
+            });
+            if !shared {
+                pairs.push((cursor_id, delimiters));
+            }
+        }
+        pairs
+    }
+
+    /// Apply replacements as one undoable action
+    ///
+    /// Edits are made back to front so earlier positions stay valid. Of two
+    /// edits at the same position, the one listed later is made first and so
+    /// ends up after the other.
+    fn apply_surround_edits(&mut self, edits: Vec<SurroundEdit>, description: &str) {
+        let mut edits: Vec<(usize, SurroundEdit)> = edits.into_iter().enumerate().collect();
+        edits.sort_by_key(|(index, (range, _, _))| std::cmp::Reverse((range.start, *index)));
+
+        let state = self.active_state_mut();
+        let mut events = Vec::new();
+        for (_, (range, text, cursor_id)) in edits {
+            if !range.is_empty() {
+                events.push(Event::Delete {
+                    deleted_text: state.get_text_range(range.start, range.end),
+                    range: range.clone(),
+                    cursor_id,
+                });
+            }
+            if !text.is_empty() {
+                events.push(Event::Insert {
+                    position: range.start,
+                    text,
+                    cursor_id,
+                });
+            }
+        }
+        self.apply_events_as_action(events, description.to_string());
+    }
+}
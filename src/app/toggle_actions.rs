@@ -228,6 +228,7 @@ impl Editor {
 
         // Always reload keybindings (complex types don't implement PartialEq)
         self.keybindings = KeybindingResolver::new(&self.config);
+        self.refresh_vi_state();
 
         // Update LSP configs
         if let Some(ref mut lsp) = self.lsp {
@@ -0,0 +1,241 @@
+//! Transform actions for the Editor: case conversion, encodings, math and
+//! number increment on every selection, or on the text under each cursor.
+//!
+//! All replacements are made in one batch, so a single undo reverts them and
+//! puts the cursors back.
+
+use std::ops::Range;
+
+use crate::model::buffer::Buffer;
+use crate::model::event::{CursorId, Event};
+use crate::primitives::text_transform::{self, TextTransform};
+use crate::primitives::word_navigation::{find_word_end, find_word_start, is_word_char};
+
+use super::Editor;
+
+/// Replacement of a buffer range on behalf of a cursor
+pub(super) struct Replacement {
+    pub(super) cursor_id: CursorId,
+    pub(super) range: Range<usize>,
+    pub(super) text: String,
+}
+
+impl Editor {
+    /// Replace each selection with `transform` applied to it
+    ///
+    /// A cursor without a selection transforms the word under it, or its
+    /// line when evaluating math.
+    pub(super) fn transform_text(&mut self, transform: TextTransform) {
+        let state = self.active_state_mut();
+        let targets: Vec<(CursorId, Range<usize>)> = state
+            .cursors
+            .iter()
+            .filter_map(|(cursor_id, cursor)| {
+                let range = match cursor.selection_range() {
+                    Some(range) => range,
+                    None if transform == TextTransform::EvaluateMath => {
+                        let line = state.buffer.line_of(cursor.position);
+                        state.buffer.line_start(line)..state.buffer.line_end(line)
+                    }
+                    None => word_at(&state.buffer, cursor.position)?,
+                };
+                Some((cursor_id, range))
+            })
+            .collect();
+        if targets.is_empty() {
+            self.set_status_message("No text to transform".to_string());
+            return;
+        }
+
+        let mut replacements = Vec::with_capacity(targets.len());
+        for (cursor_id, range) in targets {
+            let text = self
+                .active_state_mut()
+                .get_text_range(range.start, range.end);
+            match transform.apply(&text) {
+                Ok(converted) if converted != text => replacements.push(Replacement {
+                    cursor_id,
+                    range,
+                    text: converted,
+                }),
+                Ok(_) => {}
+                Err(message) => {
+                    self.set_status_message(message);
+                    return;
+                }
+            }
+        }
+        self.apply_replacements(replacements, transform.label(), false);
+    }
+
+    /// Add `delta` to the number or date at or after each cursor on its line,
+    /// or to every number in each selection
+    pub(super) fn increment_numbers(&mut self, delta: i64) {
+        let state = self.active_state_mut();
+        let cursors: Vec<(CursorId, Option<Range<usize>>, usize)> = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| (cursor_id, cursor.selection_range(), cursor.position))
+            .collect();
+
+        let mut replacements = Vec::new();
+        for (cursor_id, selection, position) in cursors {
+            let state = self.active_state_mut();
+            let replacement = match selection {
+                Some(range) => {
+                    let text = state.get_text_range(range.start, range.end);
+                    let stepped = text_transform::increment_all(&text, delta);
+                    (stepped != text).then_some(Replacement {
+                        cursor_id,
+                        range,
+                        text: stepped,
+                    })
+                }
+                None => {
+                    let line = state.buffer.line_of(position);
+                    let start = state.buffer.line_start(line);
+                    let end = state.buffer.line_end(line);
+                    let text = state.get_text_range(start, end);
+                    text_transform::increment_at(&text, position - start, delta).map(
+                        |(range, stepped)| Replacement {
+                            cursor_id,
+                            range: start + range.start..start + range.end,
+                            text: stepped,
+                        },
+                    )
+                }
+            };
+            replacements.extend(replacement);
+        }
+
+        if replacements.is_empty() {
+            self.set_status_message("No number under cursor".to_string());
+            return;
+        }
+        let description = if delta >= 0 {
+            "Increment number"
+        } else {
+            "Decrement number"
+        };
+        self.apply_replacements(replacements, description, true);
+    }
+
+    /// Make `replacements` as one undoable action and carry every cursor and
+    /// selection over to the new text
+    ///
+    /// Positions at the end of a replaced range stay at its end, and those
+    /// inside keep their offset, clamped to the new text. With
+    /// `cursor_to_last`, a cursor without a selection moves onto the last
+    /// character of its own replacement instead, ready to be stepped again.
+    pub(super) fn apply_replacements(
+        &mut self,
+        mut replacements: Vec<Replacement>,
+        description: &str,
+        cursor_to_last: bool,
+    ) {
+        // Two cursors on the same word share one replacement
+        replacements.sort_by_key(|r| r.range.start);
+        replacements.dedup_by(|later, earlier| later.range.start < earlier.range.end);
+        if replacements.is_empty() {
+            return;
+        }
+
+        let map_position = |pos: usize| -> usize {
+            let mut shift: isize = 0;
+            for replacement in &replacements {
+                let range = &replacement.range;
+                if pos < range.start {
+                    break;
+                }
+                let new_start = (range.start as isize + shift) as usize;
+                if pos == range.end {
+                    return new_start + replacement.text.len();
+                }
+                if pos < range.end {
+                    return new_start + (pos - range.start).min(replacement.text.len());
+                }
+                shift += replacement.text.len() as isize - range.len() as isize;
+            }
+            (pos as isize + shift) as usize
+        };
+        let last_of_own = |cursor_id: CursorId| -> Option<usize> {
+            let mut shift: isize = 0;
+            for replacement in &replacements {
+                if replacement.cursor_id == cursor_id {
+                    let new_start = (replacement.range.start as isize + shift) as usize;
+                    return Some(new_start + replacement.text.len().saturating_sub(1));
+                }
+                shift += replacement.text.len() as isize - replacement.range.len() as isize;
+            }
+            None
+        };
+
+        let state = self.active_state_mut();
+        let primary_id = state.cursors.primary_id();
+        let mut events = Vec::new();
+        let mut moves = Vec::new();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            // Recorded first, so undoing the batch puts the cursor back here
+            events.push(Event::MoveCursor {
+                cursor_id,
+                old_position: cursor.position,
+                new_position: cursor.position,
+                old_anchor: cursor.anchor,
+                new_anchor: cursor.anchor,
+                old_sticky_column: cursor.sticky_column,
+                new_sticky_column: cursor.sticky_column,
+            });
+            let new_position = match last_of_own(cursor_id) {
+                Some(last) if cursor_to_last && cursor.selection_range().is_none() => last,
+                _ => map_position(cursor.position),
+            };
+            moves.push(Event::MoveCursor {
+                cursor_id,
+                old_position: cursor.position,
+                new_position,
+                old_anchor: cursor.anchor,
+                new_anchor: cursor.anchor.map(map_position),
+                old_sticky_column: cursor.sticky_column,
+                new_sticky_column: cursor.sticky_column,
+            });
+        }
+
+        for replacement in replacements.into_iter().rev() {
+            let range = replacement.range;
+            if !range.is_empty() {
+                events.push(Event::Delete {
+                    deleted_text: state.get_text_range(range.start, range.end),
+                    range: range.clone(),
+                    cursor_id: primary_id,
+                });
+            }
+            if !replacement.text.is_empty() {
+                events.push(Event::Insert {
+                    position: range.start,
+                    text: replacement.text,
+                    cursor_id: primary_id,
+                });
+            }
+        }
+        events.extend(moves);
+        self.apply_events_as_action(events, description.to_string());
+    }
+}
+
+/// The word under or just before `pos`
+fn word_at(buffer: &Buffer, pos: usize) -> Option<Range<usize>> {
+    let is_word_at = |p: usize| {
+        buffer
+            .slice_bytes(p..(p + 1).min(buffer.len()))
+            .first()
+            .is_some_and(|&b| is_word_char(b))
+    };
+    let inside = if is_word_at(pos) {
+        pos
+    } else if pos > 0 && is_word_at(pos - 1) {
+        pos - 1
+    } else {
+        return None;
+    };
+    Some(find_word_start(buffer, inside)..find_word_end(buffer, inside))
+}
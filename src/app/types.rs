@@ -1,7 +1,9 @@
 use crate::app::file_open::SortMode;
 use crate::input::keybindings::Action;
-use crate::model::event::{BufferId, SplitDirection, SplitId};
+use crate::model::cursor::Cursor;
+use crate::model::event::{BufferId, CursorId, SplitDirection, SplitId};
 use crate::services::async_bridge::LspMessageType;
+use crate::services::replace::{Replacement, Replacer};
 use ratatui::layout::Rect;
 use std::collections::{HashMap, HashSet};
 use std::ops::Range;
@@ -52,8 +54,10 @@ pub(super) struct InteractiveReplaceState {
     pub search: String,
     /// The replacement text
     pub replacement: String,
-    /// Current match position (byte offset of the match we're at)
-    pub current_match_pos: usize,
+    /// Finds the matches and expands the replacement for each
+    pub replacer: Replacer,
+    /// The match we're at, with the text that would replace it
+    pub current: Replacement,
     /// Starting position (to detect when we've wrapped around full circle)
     pub start_pos: usize,
     /// Whether we've wrapped around to the beginning
@@ -267,6 +271,17 @@ pub(super) struct MacroRecordingState {
     pub actions: Vec<Action>,
 }
 
+/// Pending LSP selection range request for ExpandSelection
+///
+/// The cursors are remembered so a late response can be discarded if the
+/// user has moved on in the meantime.
+#[derive(Debug, Clone)]
+pub(super) struct PendingSelectionRange {
+    pub request_id: u64,
+    pub buffer_id: BufferId,
+    pub cursors: Vec<(CursorId, Cursor)>,
+}
+
 /// LSP progress information
 #[derive(Debug, Clone)]
 pub(super) struct LspProgressInfo {
@@ -0,0 +1,1016 @@
+//! Vi command execution
+//!
+//! `input::vi::ViState` parses keys into `ViCommand`s; the handlers here apply
+//! a command to every cursor of the active buffer through events, so each
+//! command is a single undo step.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::ops::Range;
+
+use crossterm::event::KeyEvent;
+
+use crate::input::actions::clear_block_selection_if_active;
+use crate::input::keybindings::Action;
+use crate::input::vi::{
+    self, CursorShape, InsertAt, Motion, Operator, Register, Target, TextObject, ViCommand,
+    ViInput, ViMode, ViState,
+};
+use crate::model::buffer::Buffer;
+use crate::model::cursor::{Cursor, SelectionMode};
+use crate::model::event::{CursorId, Event};
+
+use super::Editor;
+
+/// Text an operator applies to, for one cursor
+struct Span {
+    cursor_id: CursorId,
+    range: Range<usize>,
+    /// Whole lines (`dd`, `yj`, `V`)
+    linewise: bool,
+}
+
+/// Where a cursor goes once its edit is applied
+#[derive(Debug, Clone, Copy)]
+enum CursorAfter {
+    /// Where the edit left it (insert mode)
+    Stay,
+    /// Back onto the last character of the line if it ended up past it
+    Clamp,
+    /// First non-blank character of the line `back` bytes before the cursor
+    FirstNonBlank { back: usize },
+    /// On the last inserted character
+    PrevChar,
+    /// `n` bytes back
+    Back(usize),
+}
+
+/// Replace `range` with `text` on behalf of one cursor
+struct ViEdit {
+    cursor_id: CursorId,
+    range: Range<usize>,
+    text: String,
+    after: CursorAfter,
+}
+
+/// Cursor movement: (cursor, position, anchor, sticky column)
+type CursorMove = (CursorId, usize, Option<usize>, usize);
+
+impl Editor {
+    /// Feed a key to the vi parser
+    ///
+    /// Returns None when vi mode is off or the key isn't a vi key, leaving it
+    /// to the regular keymap.
+    pub(super) fn handle_vi_key(&mut self, key: KeyEvent) -> Option<io::Result<()>> {
+        let result = match self.vi.as_mut()?.handle_key(key) {
+            ViInput::Unhandled => return None,
+            ViInput::Pending => Ok(()),
+            ViInput::Command(command) => self.handle_action(Action::Vi(command)),
+            ViInput::Actions(actions) => actions
+                .into_iter()
+                .try_for_each(|action| self.handle_action(action)),
+            ViInput::Repeat(keys) => keys
+                .into_iter()
+                .try_for_each(|key| self.handle_key(key.code, key.modifiers)),
+        };
+        Some(result)
+    }
+
+    /// Turn vi mode on or off to match the active keymap
+    pub(super) fn refresh_vi_state(&mut self) {
+        let enabled = self
+            .config
+            .keymap_inherits(&self.config.active_keybinding_map, "vi");
+        if enabled != self.vi.is_some() {
+            self.vi = enabled.then(ViState::new);
+        }
+    }
+
+    /// Vi mode shown in the status bar, with any partially typed command
+    pub(super) fn vi_mode_indicator(&self) -> Option<String> {
+        let vi = self.vi.as_ref()?;
+        let label = vi.mode().label();
+        let pending = vi.pending_keys();
+        Some(if pending.is_empty() {
+            label.to_string()
+        } else {
+            format!("{label} {pending}")
+        })
+    }
+
+    /// Shape the terminal cursor should have (always a block without vi mode)
+    pub fn cursor_shape(&self) -> CursorShape {
+        self.vi
+            .as_ref()
+            .map_or(CursorShape::Block, |vi| vi.mode().cursor_shape())
+    }
+
+    /// Run a vi command on the active buffer
+    pub(super) fn run_vi_command(&mut self, command: ViCommand) -> io::Result<()> {
+        let Some(mode) = self.vi.as_ref().map(ViState::mode) else {
+            return Ok(());
+        };
+        if command.is_change() && self.is_editing_disabled() {
+            self.set_status_message("Editing disabled in this buffer".to_string());
+            return Ok(());
+        }
+
+        match command {
+            ViCommand::Move { motion, count } => self.vi_move(mode, &motion, count),
+            ViCommand::Operate {
+                operator,
+                target,
+                count,
+                register,
+            } => {
+                let spans = self.vi_target_spans(&target, count);
+                self.vi_operate(operator, spans, register, false);
+            }
+            ViCommand::OperateSelection { operator, register } => {
+                let spans = self.vi_selection_spans(mode);
+                self.vi_operate(operator, spans, register, mode == ViMode::VisualBlock);
+            }
+            ViCommand::SelectTextObject(object) => self.vi_select_text_object(mode, &object),
+            ViCommand::SwapSelectionEnds => self.vi_swap_selection_ends(),
+            ViCommand::Insert(at) => self.vi_insert(at),
+            ViCommand::BlockInsert { append } => self.vi_block_insert(append),
+            ViCommand::SetMode(new_mode) => self.vi_set_mode(mode, new_mode),
+            ViCommand::Put {
+                before,
+                register,
+                count,
+            } => self.vi_put(before, register, count),
+            ViCommand::ReplaceChar { ch, count } => self.vi_replace_char(ch, count),
+            ViCommand::JoinLines { count } => self.vi_join_lines(count),
+        }
+        Ok(())
+    }
+
+    fn vi_enter(&mut self, mode: ViMode) {
+        if let Some(vi) = &mut self.vi {
+            vi.set_mode(mode);
+        }
+    }
+
+    fn vi_move(&mut self, mode: ViMode, motion: &Motion, count: usize) {
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let vertical = matches!(motion, Motion::Up | Motion::Down);
+        let moves = state
+            .cursors
+            .iter()
+            .filter_map(|(cursor_id, cursor)| {
+                let mut target = vi::motion_target(buffer, cursor.position, motion, count)?;
+                let mut sticky_column = 0;
+                if vertical {
+                    // Aim for the column the vertical motion started from
+                    let column = if cursor.sticky_column > 0 {
+                        cursor.sticky_column
+                    } else {
+                        cursor.position - buffer.line_start(buffer.line_of(cursor.position))
+                    };
+                    let line = buffer.line_of(target);
+                    let goal = (buffer.line_start(line) + column).min(buffer.line_end(line));
+                    target = buffer.snap_to_char_boundary(goal);
+                    sticky_column = column;
+                }
+                let position = vi::clamp_to_line(buffer, target);
+                let anchor = match mode {
+                    ViMode::Visual | ViMode::VisualBlock => cursor.anchor.or(Some(cursor.position)),
+                    ViMode::VisualLine => cursor
+                        .anchor
+                        .map(|anchor| line_anchor(buffer, anchor, position)),
+                    _ => None,
+                };
+                Some((cursor_id, position, anchor, sticky_column))
+            })
+            .collect();
+        self.vi_move_cursors(moves);
+    }
+
+    fn vi_move_cursors(&mut self, moves: Vec<CursorMove>) {
+        let state = self.active_state();
+        let events: Vec<Event> = moves
+            .into_iter()
+            .filter_map(|(cursor_id, new_position, new_anchor, new_sticky_column)| {
+                let cursor = state.cursors.get(cursor_id)?;
+                Some(Event::MoveCursor {
+                    cursor_id,
+                    old_position: cursor.position,
+                    new_position,
+                    old_anchor: cursor.anchor,
+                    new_anchor,
+                    old_sticky_column: cursor.sticky_column,
+                    new_sticky_column,
+                })
+            })
+            .collect();
+        if !events.is_empty() {
+            self.apply_events_as_action(events, "Vi move".to_string());
+        }
+    }
+
+    /// Drop every selection, including block selections
+    fn vi_clear_selections(&mut self) {
+        let state = self.active_state_mut();
+        clear_block_selection_if_active(state);
+        let moves = state
+            .cursors
+            .iter()
+            .filter(|(_, cursor)| cursor.anchor.is_some())
+            .map(|(cursor_id, cursor)| (cursor_id, cursor.position, None, 0))
+            .collect();
+        self.vi_move_cursors(moves);
+    }
+
+    /// Apply edits from the end of the buffer backwards as one undo step, then
+    /// place each editing cursor
+    ///
+    /// An edit overlapping one further on (two cursors on the same line for
+    /// `dd`) is skipped.
+    fn vi_apply_edits(&mut self, mut edits: Vec<ViEdit>, description: &str) {
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        let state = self.active_state_mut();
+        let mut events = Vec::new();
+        let mut placements = HashMap::new();
+        let mut edited_from = usize::MAX;
+        for edit in edits {
+            if edit.range.end > edited_from {
+                continue;
+            }
+            edited_from = edit.range.start;
+            if !edit.range.is_empty() {
+                let deleted_text = state.get_text_range(edit.range.start, edit.range.end);
+                events.push(Event::Delete {
+                    range: edit.range.clone(),
+                    deleted_text,
+                    cursor_id: edit.cursor_id,
+                });
+            }
+            if !edit.text.is_empty() {
+                events.push(Event::Insert {
+                    position: edit.range.start,
+                    text: edit.text,
+                    cursor_id: edit.cursor_id,
+                });
+            }
+            // The lowest edit of a cursor is applied last and decides where it ends up
+            placements.insert(edit.cursor_id, edit.after);
+        }
+        if !events.is_empty() {
+            self.apply_events_as_action(events, description.to_string());
+        }
+
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let moves = placements
+            .into_iter()
+            .filter_map(|(cursor_id, after)| {
+                let pos = state.cursors.get(cursor_id)?.position;
+                let position = match after {
+                    CursorAfter::Stay => pos,
+                    CursorAfter::Clamp => vi::clamp_to_line(buffer, pos),
+                    CursorAfter::FirstNonBlank { back } => {
+                        let line = buffer.line_of(pos.saturating_sub(back));
+                        vi::first_non_blank(buffer, line.min(buffer.last_line()))
+                    }
+                    CursorAfter::PrevChar => {
+                        let line_start = buffer.line_start(buffer.line_of(pos));
+                        buffer.prev_char_boundary(pos).max(line_start)
+                    }
+                    CursorAfter::Back(n) => pos.saturating_sub(n),
+                };
+                Some((cursor_id, position, None, 0))
+            })
+            .collect();
+        self.vi_move_cursors(moves);
+    }
+
+    /// Replace all cursors with one cursor per position
+    fn vi_spread_cursors(&mut self, positions: Vec<usize>) {
+        let state = self.active_state();
+        let primary_id = state.cursors.primary_id();
+        let Some((&first, rest)) = positions.split_first() else {
+            return;
+        };
+        let primary = state.cursors.primary();
+        let mut events = vec![Event::MoveCursor {
+            cursor_id: primary_id,
+            old_position: primary.position,
+            new_position: first,
+            old_anchor: primary.anchor,
+            new_anchor: None,
+            old_sticky_column: primary.sticky_column,
+            new_sticky_column: 0,
+        }];
+        for (cursor_id, cursor) in state.cursors.iter() {
+            if cursor_id != primary_id {
+                events.push(Event::RemoveCursor {
+                    cursor_id,
+                    position: cursor.position,
+                    anchor: cursor.anchor,
+                });
+            }
+        }
+        let next_id = state
+            .cursors
+            .ids()
+            .iter()
+            .map(|id| id.0 + 1)
+            .max()
+            .unwrap_or(0);
+        for (i, &position) in rest.iter().enumerate() {
+            events.push(Event::AddCursor {
+                cursor_id: CursorId(next_id + i),
+                position,
+                anchor: None,
+            });
+        }
+        self.apply_events_as_action(events, "Vi cursors".to_string());
+    }
+
+    /// What an operator covers from each cursor
+    fn vi_target_spans(&mut self, target: &Target, count: usize) -> Vec<Span> {
+        if let Target::TextObject(TextObject::Syntax(object)) = target {
+            return self.vi_syntax_spans(object, false);
+        }
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        state
+            .cursors
+            .iter()
+            .filter_map(|(cursor_id, cursor)| {
+                let pos = cursor.position;
+                let (range, linewise) = match target {
+                    Target::Motion(motion) => {
+                        let to = vi::motion_target(buffer, pos, motion, count)?;
+                        vi::motion_range(buffer, pos, to, motion)
+                    }
+                    Target::Lines => {
+                        let line = buffer.line_of(pos);
+                        let last = (line + count - 1).min(buffer.last_line());
+                        (vi::lines_range(buffer, line, last), true)
+                    }
+                    Target::TextObject(object) => vi::text_object_range(buffer, pos, object)?,
+                };
+                Some(Span {
+                    cursor_id,
+                    range,
+                    linewise,
+                })
+            })
+            .collect()
+    }
+
+    /// Tree-sitter text object around each cursor (or its selection)
+    fn vi_syntax_spans(&mut self, object: &str, from_selection: bool) -> Vec<Span> {
+        let state = self.active_state_mut();
+        let Some(tree) = state.latest_syntax_tree().cloned() else {
+            return Vec::new();
+        };
+        let Some(objects) = &state.textobjects else {
+            return Vec::new();
+        };
+        state
+            .cursors
+            .iter()
+            .filter_map(|(cursor_id, cursor)| {
+                let selection = cursor
+                    .selection_range()
+                    .filter(|_| from_selection)
+                    .unwrap_or(cursor.position..cursor.position);
+                let range = objects.enclosing(&tree, &state.buffer, object, selection)?;
+                Some(Span {
+                    cursor_id,
+                    range,
+                    linewise: false,
+                })
+            })
+            .collect()
+    }
+
+    /// The visual selection of each cursor; a block gives one span per line
+    fn vi_selection_spans(&self, mode: ViMode) -> Vec<Span> {
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let mut spans = Vec::new();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            let anchor = cursor.anchor.unwrap_or(cursor.position);
+            let start = anchor.min(cursor.position);
+            let end = anchor.max(cursor.position);
+            match mode {
+                ViMode::VisualLine => spans.push(Span {
+                    cursor_id,
+                    range: vi::lines_range(buffer, buffer.line_of(start), buffer.line_of(end)),
+                    linewise: true,
+                }),
+                ViMode::VisualBlock => {
+                    let (lines, columns) = block_bounds(buffer, cursor);
+                    for line in lines {
+                        let line_start = buffer.line_start(line);
+                        let line_end = buffer.line_end(line);
+                        let column = |column: usize| {
+                            buffer.snap_to_char_boundary((line_start + column).min(line_end))
+                        };
+                        spans.push(Span {
+                            cursor_id,
+                            range: column(columns.start)..column(columns.end),
+                            linewise: false,
+                        });
+                    }
+                }
+                // Charwise visual selections include the character under the cursor
+                _ => spans.push(Span {
+                    cursor_id,
+                    range: start..buffer.next_char_boundary(end),
+                    linewise: false,
+                }),
+            }
+        }
+        spans
+    }
+
+    /// Apply an operator to spans of text and leave visual/operator-pending mode
+    fn vi_operate(
+        &mut self,
+        operator: Operator,
+        mut spans: Vec<Span>,
+        register: Option<char>,
+        block: bool,
+    ) {
+        spans.sort_by_key(|span| span.range.start);
+        let linewise = spans.iter().any(|span| span.linewise);
+
+        if !spans.is_empty() && !matches!(operator, Operator::Indent | Operator::Dedent) {
+            let state = self.active_state_mut();
+            let mut text = String::new();
+            for span in &spans {
+                if !text.is_empty() && !linewise {
+                    text.push('\n');
+                }
+                text.push_str(&state.get_text_range(span.range.start, span.range.end));
+                if linewise && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            let text = text.replace("\r\n", "\n");
+            self.vi_store_register(
+                register,
+                Register { text, linewise },
+                operator == Operator::Yank,
+            );
+        }
+
+        match operator {
+            Operator::Delete => {
+                let edits = self.vi_delete_edits(&spans);
+                self.vi_apply_edits(edits, "Vi delete");
+            }
+            Operator::Change => self.vi_change(&spans, block),
+            Operator::Yank => self.vi_yank_moves(&spans),
+            Operator::Indent | Operator::Dedent => {
+                let edits = self.vi_indent_edits(&spans, operator == Operator::Indent);
+                self.vi_apply_edits(edits, "Vi indent");
+            }
+        }
+
+        self.vi_clear_selections();
+        if operator == Operator::Change {
+            self.vi_enter(ViMode::Insert);
+        } else {
+            self.vi_enter(ViMode::Normal);
+        }
+    }
+
+    /// Put deleted or yanked text into a register (`"+` and `"*` also set the
+    /// system clipboard)
+    fn vi_store_register(&mut self, name: Option<char>, register: Register, yank: bool) {
+        if matches!(name, Some('+' | '*')) {
+            self.clipboard.copy(register.text.clone());
+        }
+        if let Some(vi) = &mut self.vi {
+            vi.set_register(name, register, yank);
+        }
+    }
+
+    fn vi_delete_edits(&self, spans: &[Span]) -> Vec<ViEdit> {
+        let buffer = &self.active_state().buffer;
+        spans
+            .iter()
+            .map(|span| {
+                let mut range = span.range.clone();
+                // Deleting the last lines of a buffer without a final newline
+                // takes the line break before them instead
+                let ends_with_newline = range.end > range.start
+                    && buffer.slice_bytes(range.end - 1..range.end) == b"\n";
+                if span.linewise && range.end >= buffer.len() && !ends_with_newline {
+                    let line = buffer.line_of(range.start);
+                    if line > 0 {
+                        range.start = buffer.line_end(line - 1);
+                    }
+                }
+                ViEdit {
+                    cursor_id: span.cursor_id,
+                    range,
+                    text: String::new(),
+                    after: if span.linewise {
+                        CursorAfter::FirstNonBlank { back: 0 }
+                    } else {
+                        CursorAfter::Clamp
+                    },
+                }
+            })
+            .collect()
+    }
+
+    fn vi_change(&mut self, spans: &[Span], block: bool) {
+        let buffer = &self.active_state().buffer;
+        let edits: Vec<ViEdit> = spans
+            .iter()
+            .map(|span| {
+                let mut range = span.range.clone();
+                // `cc` keeps the line break and the indentation
+                if span.linewise && !range.is_empty() {
+                    let first = buffer.line_of(range.start);
+                    let last = buffer.line_of(range.end - 1);
+                    range = vi::first_non_blank(buffer, first)..buffer.line_end(last);
+                    range.end = range.end.max(range.start);
+                }
+                ViEdit {
+                    cursor_id: span.cursor_id,
+                    range,
+                    text: String::new(),
+                    after: CursorAfter::Stay,
+                }
+            })
+            .collect();
+
+        // A block change types on every line of the block
+        let block_cursors: Vec<(usize, usize)> = if block {
+            spans
+                .iter()
+                .map(|span| {
+                    let line = buffer.line_of(span.range.start);
+                    (line, span.range.start - buffer.line_start(line))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.vi_apply_edits(edits, "Vi change");
+
+        if !block_cursors.is_empty() {
+            let buffer = &self.active_state().buffer;
+            let positions = block_cursors
+                .into_iter()
+                .map(|(line, column)| (buffer.line_start(line) + column).min(buffer.line_end(line)))
+                .collect();
+            self.vi_spread_cursors(positions);
+        }
+    }
+
+    /// A yank moves the cursor to the start of the yanked text, except for
+    /// whole lines at or below the cursor (`yy`, `yj`)
+    fn vi_yank_moves(&mut self, spans: &[Span]) {
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let mut moves = Vec::new();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            let Some(span) = spans.iter().find(|span| span.cursor_id == cursor_id) else {
+                continue;
+            };
+            let cursor_line_start = buffer.line_start(buffer.line_of(cursor.position));
+            let position = if span.linewise && span.range.start >= cursor_line_start {
+                cursor.position
+            } else {
+                span.range.start
+            };
+            moves.push((cursor_id, position, None, 0));
+        }
+        self.vi_move_cursors(moves);
+    }
+
+    fn vi_indent_edits(&self, spans: &[Span], indent: bool) -> Vec<ViEdit> {
+        let tab_size = self.config.editor.tab_size;
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let unit = if state.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(tab_size)
+        };
+
+        let mut lines = BTreeMap::new();
+        for span in spans {
+            let first = buffer.line_of(span.range.start);
+            let last = buffer.line_of(span.range.end.max(span.range.start + 1) - 1);
+            for line in first..=last.min(buffer.last_line()) {
+                lines.entry(line).or_insert(span.cursor_id);
+            }
+        }
+
+        lines
+            .into_iter()
+            .filter_map(|(line, cursor_id)| {
+                let start = buffer.line_start(line);
+                let leading = vi::first_non_blank(buffer, line) - start;
+                let (range, text) = if indent {
+                    // Blank lines stay empty
+                    if buffer.line_end(line) == start {
+                        return None;
+                    }
+                    (start..start, unit.clone())
+                } else {
+                    let bytes = buffer.slice_bytes(start..start + leading);
+                    let remove = if bytes.first() == Some(&b'\t') {
+                        1
+                    } else {
+                        bytes
+                            .iter()
+                            .take(tab_size)
+                            .take_while(|&&b| b == b' ')
+                            .count()
+                    };
+                    if remove == 0 {
+                        return None;
+                    }
+                    (start..start + remove, String::new())
+                };
+                Some(ViEdit {
+                    cursor_id,
+                    range,
+                    text,
+                    after: CursorAfter::FirstNonBlank { back: 0 },
+                })
+            })
+            .collect()
+    }
+
+    fn vi_select_text_object(&mut self, mode: ViMode, object: &TextObject) {
+        let spans = match object {
+            TextObject::Syntax(name) => self.vi_syntax_spans(name, true),
+            _ => {
+                let state = self.active_state();
+                state
+                    .cursors
+                    .iter()
+                    .filter_map(|(cursor_id, cursor)| {
+                        let (range, linewise) =
+                            vi::text_object_range(&state.buffer, cursor.position, object)?;
+                        Some(Span {
+                            cursor_id,
+                            range,
+                            linewise,
+                        })
+                    })
+                    .collect()
+            }
+        };
+        if spans.iter().any(|span| span.linewise) && mode == ViMode::Visual {
+            self.vi_enter(ViMode::VisualLine);
+        }
+
+        let buffer = &self.active_state().buffer;
+        let moves = spans
+            .into_iter()
+            .filter(|span| !span.range.is_empty())
+            .map(|span| {
+                // The cursor sits on the last character of the object
+                let position = vi::clamp_to_line(buffer, buffer.prev_char_boundary(span.range.end));
+                (span.cursor_id, position, Some(span.range.start), 0)
+            })
+            .collect();
+        self.vi_move_cursors(moves);
+    }
+
+    fn vi_swap_selection_ends(&mut self) {
+        let state = self.active_state_mut();
+        let buffer = &state.buffer;
+        let mut moves = Vec::new();
+        let mut block_anchors = Vec::new();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            if let Some(block_anchor) = cursor.block_anchor {
+                if cursor.selection_mode == SelectionMode::Block {
+                    let (line, column) = buffer.position_to_line_col(cursor.position);
+                    let position =
+                        buffer.line_col_to_position(block_anchor.line, block_anchor.column);
+                    moves.push((cursor_id, position, Some(cursor.position), 0));
+                    block_anchors.push((cursor_id, line, column));
+                    continue;
+                }
+            }
+            if let Some(anchor) = cursor.anchor {
+                moves.push((
+                    cursor_id,
+                    vi::clamp_to_line(buffer, anchor),
+                    Some(cursor.position),
+                    0,
+                ));
+            }
+        }
+        for (cursor_id, line, column) in block_anchors {
+            if let Some(cursor) = state.cursors.get_mut(cursor_id) {
+                cursor.start_block_selection(line, column);
+            }
+        }
+        self.vi_move_cursors(moves);
+    }
+
+    fn vi_insert(&mut self, at: InsertAt) {
+        let auto_indent = self.config.editor.auto_indent;
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let line_ending = buffer.line_ending().as_str();
+        let mut moves = Vec::new();
+        let mut edits = Vec::new();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            let pos = cursor.position;
+            let line = buffer.line_of(pos);
+            let line_start = buffer.line_start(line);
+            let line_end = buffer.line_end(line);
+            let indent = if auto_indent {
+                let indent_end = vi::first_non_blank(buffer, line);
+                String::from_utf8_lossy(&buffer.slice_bytes(line_start..indent_end)).into_owned()
+            } else {
+                String::new()
+            };
+            let position = match at {
+                InsertAt::Cursor => pos,
+                InsertAt::AfterCursor if pos < line_end => buffer.next_char_boundary(pos),
+                InsertAt::AfterCursor => pos,
+                InsertAt::LineStart => vi::first_non_blank(buffer, line),
+                InsertAt::LineEnd => line_end,
+                InsertAt::LineBelow => {
+                    edits.push(ViEdit {
+                        cursor_id,
+                        range: line_end..line_end,
+                        text: format!("{line_ending}{indent}"),
+                        after: CursorAfter::Stay,
+                    });
+                    continue;
+                }
+                InsertAt::LineAbove => {
+                    edits.push(ViEdit {
+                        cursor_id,
+                        range: line_start..line_start,
+                        text: format!("{indent}{line_ending}"),
+                        after: CursorAfter::Back(line_ending.len()),
+                    });
+                    continue;
+                }
+            };
+            moves.push((cursor_id, position, None, 0));
+        }
+        self.vi_move_cursors(moves);
+        if !edits.is_empty() {
+            self.vi_apply_edits(edits, "Vi open line");
+        }
+        self.vi_enter(ViMode::Insert);
+    }
+
+    fn vi_block_insert(&mut self, append: bool) {
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let mut positions = Vec::new();
+        for (_, cursor) in state.cursors.iter() {
+            let (lines, columns) = block_bounds(buffer, cursor);
+            let column = if append { columns.end } else { columns.start };
+            for line in lines {
+                let line_start = buffer.line_start(line);
+                let line_end = buffer.line_end(line);
+                positions.push(buffer.snap_to_char_boundary((line_start + column).min(line_end)));
+            }
+        }
+        clear_block_selection_if_active(self.active_state_mut());
+        self.vi_spread_cursors(positions);
+        self.vi_enter(ViMode::Insert);
+    }
+
+    fn vi_set_mode(&mut self, old: ViMode, new: ViMode) {
+        match new {
+            ViMode::Normal if old == ViMode::Insert => {
+                // Leaving insert mode steps back onto the last typed character
+                let state = self.active_state();
+                let buffer = &state.buffer;
+                let moves = state
+                    .cursors
+                    .iter()
+                    .map(|(cursor_id, cursor)| {
+                        let line_start = buffer.line_start(buffer.line_of(cursor.position));
+                        let position = if cursor.position > line_start {
+                            buffer.prev_char_boundary(cursor.position)
+                        } else {
+                            cursor.position
+                        };
+                        (cursor_id, vi::clamp_to_line(buffer, position), None, 0)
+                    })
+                    .collect();
+                self.vi_move_cursors(moves);
+            }
+            ViMode::Normal => self.vi_clear_selections(),
+            ViMode::Visual | ViMode::VisualLine | ViMode::VisualBlock => {
+                let state = self.active_state_mut();
+                if new != ViMode::VisualBlock {
+                    clear_block_selection_if_active(state);
+                }
+                let buffer = &state.buffer;
+                let moves = state
+                    .cursors
+                    .iter()
+                    .map(|(cursor_id, cursor)| {
+                        let anchor = if old.is_visual() {
+                            cursor.anchor.unwrap_or(cursor.position)
+                        } else {
+                            cursor.position
+                        };
+                        let anchor = if new == ViMode::VisualLine {
+                            line_anchor(buffer, anchor, cursor.position)
+                        } else {
+                            anchor
+                        };
+                        (
+                            cursor_id,
+                            cursor.position,
+                            Some(anchor),
+                            cursor.sticky_column,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if new == ViMode::VisualBlock {
+                    let anchors: Vec<(CursorId, usize, usize)> = moves
+                        .iter()
+                        .map(|&(cursor_id, _, anchor, _)| {
+                            let (line, column) = buffer.position_to_line_col(anchor.unwrap_or(0));
+                            (cursor_id, line, column)
+                        })
+                        .collect();
+                    for (cursor_id, line, column) in anchors {
+                        if let Some(cursor) = state.cursors.get_mut(cursor_id) {
+                            if cursor.selection_mode != SelectionMode::Block {
+                                cursor.start_block_selection(line, column);
+                            }
+                        }
+                    }
+                }
+                self.vi_move_cursors(moves);
+            }
+            ViMode::Insert | ViMode::OperatorPending => {}
+        }
+        self.vi_enter(new);
+    }
+
+    fn vi_put(&mut self, before: bool, register: Option<char>, count: usize) {
+        let name = register.unwrap_or('"');
+        let register = match name {
+            '+' | '*' => self.clipboard.paste().map(|text| Register {
+                linewise: text.ends_with('\n'),
+                text: text.replace("\r\n", "\n"),
+            }),
+            _ => self.vi.as_ref().and_then(|vi| vi.register(name)).cloned(),
+        };
+        let Some(Register { text, linewise }) = register else {
+            self.set_status_message(format!("Register \"{name} is empty"));
+            return;
+        };
+
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let line_ending = buffer.line_ending().as_str();
+        let mut text = text;
+        if linewise && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        let text = text.repeat(count).replace('\n', line_ending);
+        let edits = state
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| {
+                let pos = cursor.position;
+                let line = buffer.line_of(pos);
+                if !linewise {
+                    let at = if before || pos >= buffer.line_end(line) {
+                        pos
+                    } else {
+                        buffer.next_char_boundary(pos)
+                    };
+                    return ViEdit {
+                        cursor_id,
+                        range: at..at,
+                        text: text.clone(),
+                        after: CursorAfter::PrevChar,
+                    };
+                }
+                // `back` leads from where the insert leaves the cursor to the
+                // first pasted line
+                let lines = vi::lines_range(buffer, line, line);
+                let (at, text, back) = if before {
+                    (lines.start, text.clone(), text.len())
+                } else if lines.end == buffer.len()
+                    && buffer.slice_bytes(lines.start..lines.end).last() != Some(&b'\n')
+                {
+                    // Below a last line without a line break
+                    let body = text.strip_suffix(line_ending).unwrap_or(&text);
+                    (lines.end, format!("{line_ending}{body}"), body.len())
+                } else {
+                    (lines.end, text.clone(), text.len())
+                };
+                ViEdit {
+                    cursor_id,
+                    range: at..at,
+                    text,
+                    after: CursorAfter::FirstNonBlank { back },
+                }
+            })
+            .collect();
+        self.vi_apply_edits(edits, "Vi put");
+    }
+
+    fn vi_replace_char(&mut self, ch: char, count: usize) {
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let edits = state
+            .cursors
+            .iter()
+            .filter_map(|(cursor_id, cursor)| {
+                let line_end = buffer.line_end(buffer.line_of(cursor.position));
+                let mut end = cursor.position;
+                for _ in 0..count {
+                    if end >= line_end {
+                        return None;
+                    }
+                    end = buffer.next_char_boundary(end);
+                }
+                Some(ViEdit {
+                    cursor_id,
+                    range: cursor.position..end,
+                    text: ch.to_string().repeat(count),
+                    after: CursorAfter::PrevChar,
+                })
+            })
+            .collect();
+        self.vi_apply_edits(edits, "Vi replace");
+    }
+
+    fn vi_join_lines(&mut self, count: usize) {
+        let state = self.active_state();
+        let buffer = &state.buffer;
+        let last_line = buffer.last_line();
+        let mut edits = Vec::new();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            let first = buffer.line_of(cursor.position);
+            // `J` and `2J` both join two lines
+            for line in first..first + count.max(2) - 1 {
+                if line >= last_line {
+                    break;
+                }
+                let end = buffer.line_end(line);
+                let next_start = vi::first_non_blank(buffer, line + 1);
+                let next_empty = next_start == buffer.line_end(line + 1);
+                let ends_blank = end == buffer.line_start(line)
+                    || buffer.slice_bytes(end - 1..end) == b" "
+                    || buffer.slice_bytes(end - 1..end) == b"\t";
+                let closes = buffer.slice_bytes(next_start..next_start + 1) == b")";
+                let separator = if next_empty || ends_blank || closes {
+                    ""
+                } else {
+                    " "
+                };
+                edits.push(ViEdit {
+                    cursor_id,
+                    range: end..next_start,
+                    text: separator.to_string(),
+                    after: CursorAfter::Back(separator.len()),
+                });
+            }
+        }
+        self.vi_apply_edits(edits, "Vi join lines");
+    }
+}
+
+/// Anchor of a linewise visual selection: the start of the anchor's line
+/// while the cursor is at or below it, the end of that line while above
+fn line_anchor(buffer: &Buffer, anchor: usize, position: usize) -> usize {
+    let line = buffer.line_of(anchor);
+    let start = buffer.line_start(line);
+    if position >= start {
+        start
+    } else {
+        buffer.line_end(line)
+    }
+}
+
+/// Lines and (end-exclusive) byte columns of a cursor's block selection
+fn block_bounds(
+    buffer: &Buffer,
+    cursor: &Cursor,
+) -> (std::ops::RangeInclusive<usize>, Range<usize>) {
+    let (line, column) = buffer.position_to_line_col(cursor.position);
+    let (anchor_line, anchor_column) = cursor
+        .block_anchor
+        .map_or((line, column), |anchor| (anchor.line, anchor.column));
+    (
+        line.min(anchor_line)..=line.max(anchor_line),
+        column.min(anchor_column)..column.max(anchor_column) + 1,
+    )
+}
@@ -0,0 +1,155 @@
+//! Word completion for the Editor
+//!
+//! Offers the words of the open buffers that complete the word before the
+//! cursor, in the same popup as the language server's items and snippets.
+//! Each buffer's words are indexed the first time they are needed and kept
+//! up to date by its edits. Words near the cursor come first, then those
+//! used most often across the buffers.
+//!
+//! A language can turn word completion off (`word_completion`), or have
+//! the popup open while typing (`completion_trigger`) once the word is
+//! `completion_min_prefix` characters long.
+
+use std::collections::HashMap;
+
+use crate::config::{CompletionTrigger, DEFAULT_COMPLETION_MIN_PREFIX};
+use crate::model::event::PopupListItemData;
+use crate::primitives::word_completion::{self, is_word_byte, WordIndex};
+use crate::services::lsp::manager::detect_language;
+
+use super::Editor;
+
+/// Icon marking buffer words in the completion popup
+pub(super) const WORD_ICON: &str = "w";
+
+/// Lines above and below the cursor whose words rank by their distance
+const NEARBY_LINES: usize = 100;
+
+/// Longest stretch after the cursor scanned for the rest of its word
+const WORD_SCAN_BYTES: usize = 256;
+
+impl Editor {
+    /// Completion popup items for the words of the open buffers that
+    /// complete the word before the cursor
+    pub(super) fn word_completion_items(&mut self) -> Vec<PopupListItemData> {
+        if !self
+            .active_language_config()
+            .is_none_or(|config| config.word_completion)
+        {
+            return Vec::new();
+        }
+        let (word_start, cursor_pos) = self.completion_word_range();
+        if word_start == cursor_pos {
+            return Vec::new();
+        }
+
+        let active = self.active_buffer();
+        let others: Vec<_> = self
+            .buffers
+            .keys()
+            .copied()
+            .filter(|id| {
+                *id != active
+                    && !self
+                        .buffer_metadata
+                        .get(id)
+                        .is_some_and(|metadata| metadata.is_virtual())
+            })
+            .collect();
+        for id in others.iter().chain([&active]) {
+            if let Some(state) = self.buffers.get_mut(id) {
+                state.ensure_word_index();
+            }
+        }
+
+        let state = self.active_state_mut();
+        let end = (cursor_pos + WORD_SCAN_BYTES).min(state.buffer.len());
+        let rest = state
+            .buffer
+            .slice_bytes(cursor_pos..end)
+            .iter()
+            .take_while(|&&byte| is_word_byte(byte))
+            .count();
+        let prefix = state.get_text_range(word_start, cursor_pos);
+        let typed = state.get_text_range(word_start, cursor_pos + rest);
+
+        let state = self.active_state();
+        let cursor_line = state.buffer.position_to_line_col(cursor_pos).0;
+        let lines: Vec<(usize, Vec<u8>)> = (cursor_line.saturating_sub(NEARBY_LINES)
+            ..=cursor_line + NEARBY_LINES)
+            .map_while(|line| Some((line, state.buffer.get_line(line)?)))
+            .collect();
+        let nearby: HashMap<String, usize> = word_completion::line_distances(
+            lines.iter().map(|(line, bytes)| (*line, bytes.as_slice())),
+            cursor_line,
+        );
+
+        let indexes: Vec<&WordIndex> = others
+            .iter()
+            .chain([&active])
+            .filter_map(|id| self.buffers.get(id)?.word_index.as_ref())
+            .collect();
+        word_completion::rank(&prefix, &typed, &nearby, &indexes)
+            .into_iter()
+            .map(|word| PopupListItemData {
+                text: word.clone(),
+                detail: None,
+                icon: Some(WORD_ICON.to_string()),
+                data: Some(word),
+            })
+            .collect()
+    }
+
+    /// Show matching snippets and buffer words in the completion popup right
+    /// away; the language server's items join them when its answer arrives
+    pub(super) fn show_local_completions(&mut self) {
+        let mut items = self.snippet_completion_items();
+        items.extend(self.word_completion_items());
+        if !items.is_empty() {
+            self.show_completion_popup(items);
+        }
+    }
+
+    /// Whether the active buffer's language opens the completion popup
+    /// while typing
+    pub(super) fn completes_while_typing(&self) -> bool {
+        self.active_language_config()
+            .is_some_and(|config| config.completion_trigger == CompletionTrigger::Typing)
+    }
+
+    /// Open the completion popup after a word character is typed, if the
+    /// language asks for it and the word is long enough; the language server
+    /// is asked too if it is already running
+    pub(super) fn complete_while_typing(&mut self) {
+        if !self.completes_while_typing() {
+            return;
+        }
+        let min_prefix = self
+            .active_language_config()
+            .map_or(DEFAULT_COMPLETION_MIN_PREFIX, |config| {
+                config.completion_min_prefix
+            });
+        let (word_start, cursor_pos) = self.completion_word_range();
+        let prefix = self
+            .active_state_mut()
+            .get_text_range(word_start, cursor_pos);
+        if prefix.chars().count() < min_prefix.max(1) {
+            return;
+        }
+
+        self.show_local_completions();
+        let language = self
+            .active_state()
+            .buffer
+            .file_path()
+            .and_then(|path| detect_language(path, &self.config.languages));
+        let server_ready = language.is_some_and(|language| {
+            self.lsp
+                .as_ref()
+                .is_some_and(|lsp| lsp.is_server_ready(&language))
+        });
+        if server_ready {
+            let _ = self.request_completion();
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use crate::types::{context_keys, LspServerConfig, ProcessLimits};
+use crate::types::{
+    context_keys, DebugAdapterConfig, DebugRequestKind, LspServerConfig, ProcessLimits,
+};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -70,7 +72,7 @@ pub struct KeybindingMapName(pub String);
 
 impl KeybindingMapName {
     /// Built-in keybinding map options shown in the settings dropdown
-    pub const BUILTIN_OPTIONS: &'static [&'static str] = &["default", "emacs", "vscode"];
+    pub const BUILTIN_OPTIONS: &'static [&'static str] = &["default", "emacs", "vscode", "vi"];
 }
 
 impl Deref for KeybindingMapName {
@@ -162,6 +164,10 @@ pub struct Config {
     #[serde(default)]
     pub lsp: HashMap<String, LspServerConfig>,
 
+    /// Debug adapter (DAP) configurations by language
+    #[serde(default)]
+    pub debuggers: HashMap<String, DebugAdapterConfig>,
+
     /// Menu bar configuration
     #[serde(default)]
     pub menu: MenuConfig,
@@ -283,6 +289,12 @@ pub struct EditorConfig {
     /// Default: 3000ms (3 seconds)
     #[serde(default = "default_file_tree_poll_interval")]
     pub file_tree_poll_interval_ms: u64,
+
+    /// Column that "Reflow Paragraph" and hard wrapping break lines at,
+    /// unless the language or the compose width sets another.
+    /// Default: 80
+    #[serde(default = "default_wrap_column")]
+    pub wrap_column: usize,
 }
 
 fn default_tab_size() -> usize {
@@ -322,6 +334,10 @@ fn default_estimated_line_length() -> usize {
     80
 }
 
+fn default_wrap_column() -> usize {
+    80
+}
+
 fn default_auto_save_interval() -> u32 {
     2 // Auto-save every 2 seconds for fast recovery
 }
@@ -369,6 +385,7 @@ impl Default for EditorConfig {
             double_click_time_ms: default_double_click_time(),
             auto_revert_poll_interval_ms: default_auto_revert_poll_interval(),
             file_tree_poll_interval_ms: default_file_tree_poll_interval(),
+            wrap_column: default_wrap_column(),
         }
     }
 }
@@ -532,6 +549,14 @@ fn default_on_save_timeout() -> u64 {
     10000
 }
 
+/// Characters of a word typed before completion opens, unless the language
+/// sets another number
+pub const DEFAULT_COMPLETION_MIN_PREFIX: usize = 3;
+
+fn default_completion_min_prefix() -> usize {
+    DEFAULT_COMPLETION_MIN_PREFIX
+}
+
 /// Language-specific configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[schemars(extend("x-display-field" = "/grammar"))]
@@ -552,6 +577,11 @@ pub struct LanguageConfig {
     #[serde(default)]
     pub comment_prefix: Option<String>,
 
+    /// Block comment delimiters (e.g., `/*` and `*/`)
+    /// Used by "Toggle Block Comment", and for line comments when there is no comment prefix
+    #[serde(default)]
+    pub block_comment: Option<BlockComment>,
+
     /// Whether to auto-indent
     #[serde(default = "default_true")]
     pub auto_indent: bool,
@@ -581,12 +611,68 @@ pub struct LanguageConfig {
     #[serde(default)]
     pub tab_size: Option<usize>,
 
+    /// Column that "Reflow Paragraph" and hard wrapping break lines at.
+    /// If not specified, falls back to the global editor.wrap_column setting.
+    /// The compose width takes precedence when it is set.
+    #[serde(default)]
+    pub wrap_column: Option<usize>,
+
+    /// Whether to break lines while typing once they pass the wrap column,
+    /// keeping comment prefixes, list and quote markers, and indentation.
+    #[serde(default = "default_false")]
+    pub hard_wrap: bool,
+
+    /// Whether completion offers the words of this and the other open buffers,
+    /// nearest the cursor first, then the most frequent. A language server's
+    /// items come before them.
+    #[serde(default = "default_true")]
+    pub word_completion: bool,
+
+    /// When the completion popup opens: "manual" (only on Show Completions)
+    /// or "typing" (also while typing a word, once it is
+    /// `completion_min_prefix` characters long)
+    #[serde(default)]
+    pub completion_trigger: CompletionTrigger,
+
+    /// Characters of a word to type before completion opens while typing
+    #[serde(default = "default_completion_min_prefix")]
+    pub completion_min_prefix: usize,
+
     /// Actions to run when a file of this language is saved
     /// Actions are run in order; if any fails (non-zero exit), subsequent actions don't run
     #[serde(default)]
     pub on_save: Vec<OnSaveAction>,
 }
 
+/// Start and end delimiters of a block comment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BlockComment {
+    /// Opening delimiter (e.g., "/*")
+    pub start: String,
+    /// Closing delimiter (e.g., "*/")
+    pub end: String,
+}
+
+impl BlockComment {
+    pub fn new(start: &str, end: &str) -> Self {
+        Self {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+}
+
+/// When the completion popup opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionTrigger {
+    /// Only on Show Completions
+    #[default]
+    Manual,
+    /// Also while typing a word, once it is long enough
+    Typing,
+}
+
 /// Preference for which syntax highlighting backend to use
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
@@ -714,6 +800,7 @@ impl Default for Config {
             active_keybinding_map: default_keybinding_map_name(),
             languages: Self::default_languages(),
             lsp: Self::default_lsp_config(),
+            debuggers: Self::default_debugger_config(),
             menu: MenuConfig::default(),
         }
     }
@@ -759,6 +846,7 @@ impl Config {
     ///
     /// This is called after deserializing user config to ensure that:
     /// - Default LSP servers are present even if user only customizes one
+    /// - Default debug adapters are present even if user only customizes one
     /// - Default language configs are present even if user only customizes one
     ///
     /// User entries override defaults when keys collide.
@@ -772,6 +860,13 @@ impl Config {
             self.lsp.insert(key, value);
         }
 
+        // Merge debug adapter configs the same way
+        let user_debuggers = std::mem::take(&mut self.debuggers);
+        self.debuggers = defaults.debuggers;
+        for (key, value) in user_debuggers {
+            self.debuggers.insert(key, value);
+        }
+
         // Merge language configs: start with defaults, overlay user entries
         let user_languages = std::mem::take(&mut self.languages);
         self.languages = defaults.languages;
@@ -790,6 +885,7 @@ impl Config {
             "default" => include_str!("../keymaps/default.json"),
             "emacs" => include_str!("../keymaps/emacs.json"),
             "vscode" => include_str!("../keymaps/vscode.json"),
+            "vi" => include_str!("../keymaps/vi.json"),
             _ => return None,
         };
 
@@ -803,6 +899,32 @@ impl Config {
         self.resolve_keymap_recursive(map_name, &mut visited)
     }
 
+    /// Whether `map_name` is `ancestor` or inherits from it
+    ///
+    /// Used to enable modal editing for user keymaps built on top of "vi".
+    pub fn keymap_inherits(&self, map_name: &str, ancestor: &str) -> bool {
+        let mut visited = std::collections::HashSet::new();
+        let mut name = map_name.to_string();
+        loop {
+            if name == ancestor {
+                return true;
+            }
+            if !visited.insert(name.clone()) {
+                return false;
+            }
+            let parent = self
+                .keybinding_maps
+                .get(&name)
+                .cloned()
+                .or_else(|| Self::load_builtin_keymap(&name))
+                .and_then(|keymap| keymap.inherits);
+            match parent {
+                Some(parent) => name = parent,
+                None => return false,
+            }
+        }
+    }
+
     /// Recursive helper for resolve_keymap
     fn resolve_keymap_recursive(
         &self,
@@ -853,12 +975,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "rust".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: Some(BlockComment::new("/*", "*/")),
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "rustfmt".to_string(),
                     args: vec!["--edition".to_string(), "2021".to_string()],
@@ -879,12 +1007,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "javascript".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: Some(BlockComment::new("/*", "*/")),
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "prettier".to_string(),
                     args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
@@ -905,12 +1039,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "typescript".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: Some(BlockComment::new("/*", "*/")),
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "prettier".to_string(),
                     args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
@@ -931,12 +1071,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "python".to_string(),
                 comment_prefix: Some("#".to_string()),
+                block_comment: None,
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "ruff".to_string(),
                     args: vec![
@@ -961,12 +1107,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "c".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: Some(BlockComment::new("/*", "*/")),
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "clang-format".to_string(),
                     args: vec![],
@@ -994,12 +1146,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "cpp".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: Some(BlockComment::new("/*", "*/")),
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "clang-format".to_string(),
                     args: vec![],
@@ -1020,12 +1178,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "c_sharp".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: Some(BlockComment::new("/*", "*/")),
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: Vec::new(),
             },
         );
@@ -1048,12 +1212,18 @@ impl Config {
                 ],
                 grammar: "bash".to_string(),
                 comment_prefix: Some("#".to_string()),
+                block_comment: None,
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: Vec::new(),
             },
         );
@@ -1069,12 +1239,18 @@ impl Config {
                 ],
                 grammar: "make".to_string(),
                 comment_prefix: Some("#".to_string()),
+                block_comment: None,
                 auto_indent: false,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: true,    // Makefiles require tabs for recipes
                 tab_size: Some(8), // Makefiles traditionally use 8-space tabs
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: Vec::new(),
             },
         );
@@ -1086,12 +1262,18 @@ impl Config {
                 filenames: vec!["Dockerfile".to_string(), "Containerfile".to_string()],
                 grammar: "dockerfile".to_string(),
                 comment_prefix: Some("#".to_string()),
+                block_comment: None,
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: Vec::new(),
             },
         );
@@ -1103,12 +1285,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "json".to_string(),
                 comment_prefix: None,
+                block_comment: None,
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "prettier".to_string(),
                     args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
@@ -1129,12 +1317,18 @@ impl Config {
                 filenames: vec!["Cargo.lock".to_string()],
                 grammar: "toml".to_string(),
                 comment_prefix: Some("#".to_string()),
+                block_comment: None,
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: Vec::new(),
             },
         );
@@ -1146,12 +1340,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "yaml".to_string(),
                 comment_prefix: Some("#".to_string()),
+                block_comment: None,
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "prettier".to_string(),
                     args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
@@ -1172,12 +1372,42 @@ impl Config {
                 filenames: vec!["README".to_string()],
                 grammar: "markdown".to_string(),
                 comment_prefix: None,
+                block_comment: Some(BlockComment::new("<!--", "-->")),
                 auto_indent: false,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
+                on_save: Vec::new(),
+            },
+        );
+
+        // Commit messages are hard-wrapped at 72 columns, as git convention expects
+        languages.insert(
+            "git-commit".to_string(),
+            LanguageConfig {
+                extensions: vec![],
+                filenames: vec!["COMMIT_EDITMSG".to_string(), "TAG_EDITMSG".to_string()],
+                grammar: "git-commit".to_string(),
+                comment_prefix: Some("#".to_string()),
+                block_comment: None,
+                auto_indent: false,
+                highlighter: HighlighterPreference::Auto,
+                textmate_grammar: None,
+                show_whitespace_tabs: true,
+                use_tabs: false,
+                tab_size: None,
+                wrap_column: Some(72),
+                hard_wrap: true,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: Vec::new(),
             },
         );
@@ -1190,12 +1420,18 @@ impl Config {
                 filenames: vec![],
                 grammar: "go".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: Some(BlockComment::new("/*", "*/")),
                 auto_indent: true,
                 highlighter: HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: false,
                 use_tabs: true,    // Go convention is to use tabs
                 tab_size: Some(8), // Go convention is 8-space tab width
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: CompletionTrigger::Manual,
+                completion_min_prefix: DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![OnSaveAction {
                     command: "gofmt".to_string(),
                     args: vec![],
@@ -1373,6 +1609,60 @@ impl Config {
         lsp
     }
 
+    /// Create default debug adapter configurations
+    ///
+    /// None of these adapters ship with the editor; a debug session fails with
+    /// a clear message if the command is not installed.
+    fn default_debugger_config() -> HashMap<String, DebugAdapterConfig> {
+        let mut debuggers = HashMap::new();
+
+        // debugpy (installed via pip)
+        debuggers.insert(
+            "python".to_string(),
+            DebugAdapterConfig {
+                command: "python3".to_string(),
+                args: vec!["-m".to_string(), "debugpy.adapter".to_string()],
+                enabled: true,
+                adapter_id: Some("debugpy".to_string()),
+                request: DebugRequestKind::Launch,
+                configuration: serde_json::json!({
+                    "program": "${file}",
+                    "cwd": "${workspaceFolder}",
+                    "console": "internalConsole",
+                    "justMyCode": true
+                }),
+            },
+        );
+
+        // lldb-dap (ships with LLVM, formerly lldb-vscode)
+        // Debugs the binary cargo builds for the workspace; run `cargo build` first
+        let lldb = |program: &str| DebugAdapterConfig {
+            command: "lldb-dap".to_string(),
+            args: vec![],
+            enabled: true,
+            adapter_id: Some("lldb".to_string()),
+            request: DebugRequestKind::Launch,
+            configuration: serde_json::json!({
+                "program": program,
+                "cwd": "${workspaceFolder}"
+            }),
+        };
+        debuggers.insert(
+            "rust".to_string(),
+            lldb("${workspaceFolder}/target/debug/${workspaceFolderBasename}"),
+        );
+        debuggers.insert(
+            "c".to_string(),
+            lldb("${fileDirname}/${fileBasenameNoExtension}"),
+        );
+        debuggers.insert(
+            "cpp".to_string(),
+            lldb("${fileDirname}/${fileBasenameNoExtension}"),
+        );
+
+        debuggers
+    }
+
     /// Create default menu bar configuration
     fn default_menus() -> Vec<Menu> {
         vec![
@@ -1718,6 +2008,17 @@ impl Config {
                                 when: None,
                                 checkbox: None,
                             },
+                            MenuItem::Action {
+                                label: "Vi".to_string(),
+                                action: "switch_keybinding_map".to_string(),
+                                args: {
+                                    let mut map = HashMap::new();
+                                    map.insert("map".to_string(), serde_json::json!("vi"));
+                                    map
+                                },
+                                when: None,
+                                checkbox: None,
+                            },
                         ],
                     },
                 ],
@@ -1754,6 +2055,13 @@ impl Config {
                         when: None,
                         checkbox: None,
                     },
+                    MenuItem::Action {
+                        label: "Shrink Selection".to_string(),
+                        action: "shrink_selection".to_string(),
+                        args: HashMap::new(),
+                        when: None,
+                        checkbox: None,
+                    },
                     MenuItem::Separator { separator: true },
                     MenuItem::Action {
                         label: "Add Cursor Above".to_string(),
@@ -2083,6 +2391,23 @@ mod tests {
         assert!(!resolved.is_empty());
     }
 
+    #[test]
+    fn test_keymap_inherits() {
+        let mut config = Config::default();
+        config.keybinding_maps.insert(
+            "my-vi".to_string(),
+            KeymapConfig {
+                inherits: Some("vi".to_string()),
+                bindings: Vec::new(),
+            },
+        );
+        assert!(config.keymap_inherits("vi", "vi"));
+        assert!(config.keymap_inherits("my-vi", "vi"));
+        assert!(config.keymap_inherits("my-vi", "default"));
+        assert!(!config.keymap_inherits("emacs", "vi"));
+        assert!(!config.resolve_keymap("vi").is_empty());
+    }
+
     #[test]
     fn test_config_validation() {
         let mut config = Config::default();
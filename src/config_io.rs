@@ -274,6 +274,11 @@ impl DirectoryContext {
         self.data_dir.join("replace_history.json")
     }
 
+    /// Get the saved keyboard macros file path
+    pub fn macros_path(&self) -> std::path::PathBuf {
+        self.data_dir.join("macros.json")
+    }
+
     /// Get the terminals root directory
     pub fn terminals_dir(&self) -> std::path::PathBuf {
         self.data_dir.join("terminals")
@@ -304,4 +309,9 @@ impl DirectoryContext {
     pub fn plugins_dir(&self) -> std::path::PathBuf {
         self.config_dir.join("plugins")
     }
+
+    /// Get the user snippets directory path
+    pub fn snippets_dir(&self) -> std::path::PathBuf {
+        self.config_dir.join("snippets")
+    }
 }
@@ -1,15 +1,19 @@
 //! Action to event conversion - translates high-level actions into buffer events
 
 use crate::input::keybindings::Action;
+use crate::input::rectangle;
 use crate::model::buffer::{Buffer, LineEnding};
-use crate::model::cursor::{Position2D, SelectionMode};
+use crate::model::cursor::{Cursor, Position2D, SelectionMode};
 use crate::model::event::{CursorId, Event};
 use crate::primitives::display_width::{byte_offset_at_visual_column, str_width};
+use crate::primitives::syntax_tree::enclosing_node_range;
+use crate::primitives::textobjects::TextObjects;
 use crate::primitives::word_navigation::{
     find_word_end, find_word_start, find_word_start_left, find_word_start_right,
 };
 use crate::state::EditorState;
 use std::ops::Range;
+use tree_sitter::Tree;
 
 /// Direction for block selection movement
 #[derive(Debug, Clone, Copy)]
@@ -20,27 +24,12 @@ enum BlockDirection {
     Down,
 }
 
-/// Convert byte offset to 2D position (line, column)
-fn byte_to_2d(buffer: &Buffer, byte_pos: usize) -> Position2D {
-    let line = buffer.get_line_number(byte_pos);
-    let line_start = buffer.line_start_offset(line).unwrap_or(0);
-    let column = byte_pos.saturating_sub(line_start);
-    Position2D { line, column }
-}
-
-/// Convert 2D position to byte offset
-fn pos_2d_to_byte(buffer: &Buffer, pos: Position2D) -> usize {
-    let line_start = buffer.line_start_offset(pos.line).unwrap_or(0);
-    // Get line content to check bounds
-    let line_content = buffer.get_line(pos.line).unwrap_or_default();
-    // Clamp column to line length (excluding newline)
-    let line_len = if line_content.last() == Some(&b'\n') {
-        line_content.len().saturating_sub(1)
-    } else {
-        line_content.len()
-    };
-    let clamped_col = pos.column.min(line_len);
-    line_start + clamped_col
+/// Byte offset of a (line, display column) position, clamped to the line
+fn column_to_byte(state: &EditorState, pos: Position2D) -> usize {
+    match rectangle::line_text(state, pos.line) {
+        Some((line_start, text)) => line_start + byte_offset_at_visual_column(&text, pos.column),
+        None => state.buffer.len(),
+    }
 }
 
 /// Calculate the visual column (display width) at the cursor position.
@@ -108,12 +97,14 @@ fn next_position_for_crlf(buffer: &Buffer, pos: usize, max_pos: usize) -> usize
 /// Convert deletion ranges to Delete events
 ///
 /// This is a common pattern used across many deletion actions.
-/// It reads the text from each range and creates Delete events.
+/// It reads the text from each range and creates Delete events, back to
+/// front so the ranges of other cursors stay valid.
 fn apply_deletions(
     state: &mut EditorState,
-    deletions: Vec<(CursorId, Range<usize>)>,
+    mut deletions: Vec<(CursorId, Range<usize>)>,
     events: &mut Vec<Event>,
 ) {
+    deletions.sort_by_key(|(_, range)| std::cmp::Reverse(range.start));
     for (cursor_id, range) in deletions {
         let deleted_text = state.get_text_range(range.start, range.end);
         events.push(Event::Delete {
@@ -206,6 +197,18 @@ fn add_move_cursor_event(
     });
 }
 
+/// Display column just right of `cursor`, when it is at the end of its line
+/// in virtual-space mode, where moving right goes on past the end
+fn virtual_column_right(state: &EditorState, cursor: &Cursor) -> Option<usize> {
+    if !state.virtual_space {
+        return None;
+    }
+    let line = state.buffer.get_line_number(cursor.position);
+    let (line_start, text) = rectangle::line_text(state, line)?;
+    (cursor.position == line_start + text.len())
+        .then(|| rectangle::cursor_column(state, cursor) + 1)
+}
+
 /// Handle block selection movement
 fn block_select_action(
     state: &mut EditorState,
@@ -223,7 +226,11 @@ fn block_select_action(
     };
 
     for (cursor_id, cursor) in state.cursors.iter() {
-        let current_2d = byte_to_2d(&state.buffer, cursor.position);
+        // Block columns are display columns, and count virtual space
+        let current_2d = Position2D {
+            line: state.buffer.get_line_number(cursor.position),
+            column: rectangle::cursor_column(state, cursor),
+        };
 
         // If not in block mode, start block selection
         let block_anchor =
@@ -240,16 +247,19 @@ fn block_select_action(
                 column: current_2d.column.saturating_sub(1),
             },
             BlockDirection::Right => {
-                // Get current line length to bound the column
-                let line_content = state.buffer.get_line(current_2d.line).unwrap_or_default();
-                let line_len = if line_content.last() == Some(&b'\n') {
-                    line_content.len().saturating_sub(1)
-                } else {
-                    line_content.len()
-                };
+                // Bound the column by the line's width, unless in virtual space
+                // or already past it
+                let line_width = rectangle::line_text(state, current_2d.line)
+                    .map(|(_, text)| str_width(&text))
+                    .unwrap_or(0);
+                let column = current_2d.column + 1;
                 Position2D {
                     line: current_2d.line,
-                    column: (current_2d.column + 1).min(line_len),
+                    column: if state.virtual_space {
+                        column
+                    } else {
+                        column.min(line_width.max(current_2d.column))
+                    },
                 }
             }
             BlockDirection::Up => {
@@ -275,10 +285,10 @@ fn block_select_action(
         };
 
         // Convert new 2D position back to byte offset
-        let new_byte_pos = pos_2d_to_byte(&state.buffer, new_2d);
+        let new_byte_pos = column_to_byte(state, new_2d);
 
         // Store the byte anchor for the event system (for undo/redo compatibility)
-        let byte_anchor = pos_2d_to_byte(&state.buffer, block_anchor);
+        let byte_anchor = column_to_byte(state, block_anchor);
 
         events.push(Event::MoveCursor {
             cursor_id,
@@ -298,13 +308,22 @@ fn block_select_action(
     // We need to do this directly since Event::MoveCursor doesn't support selection mode changes
     // Note: We update the cursors here to set block_anchor BEFORE the events are applied
     // This way the events will move the cursor, but the anchor remains fixed
-    let buffer_ref = &state.buffer;
-    state.cursors.map(|cursor| {
-        if cursor.selection_mode != SelectionMode::Block || cursor.block_anchor.is_none() {
-            let current_2d = byte_to_2d(buffer_ref, cursor.position);
-            cursor.start_block_selection(current_2d.line, current_2d.column);
+    let starts: Vec<(CursorId, usize, usize)> = state
+        .cursors
+        .iter()
+        .filter(|(_, cursor)| {
+            cursor.selection_mode != SelectionMode::Block || cursor.block_anchor.is_none()
+        })
+        .map(|(cursor_id, cursor)| {
+            let line = state.buffer.get_line_number(cursor.position);
+            (cursor_id, line, rectangle::cursor_column(state, cursor))
+        })
+        .collect();
+    for (cursor_id, line, column) in starts {
+        if let Some(cursor) = state.cursors.get_mut(cursor_id) {
+            cursor.start_block_selection(line, column);
         }
-    });
+    }
 }
 
 /// Clear block selection when performing normal operations
@@ -340,11 +359,20 @@ fn calculate_closing_delimiter_indent(
     ch: char,
     tab_size: usize,
 ) -> usize {
+    // Pick up a finished reparse, without waiting for one in flight
+    state.update_syntax_tree();
     if let Some(language) = state.highlighter.language() {
         state
             .indent_calculator
             .borrow_mut()
-            .calculate_dedent_for_delimiter(&state.buffer, insert_position, ch, language, tab_size)
+            .calculate_dedent_for_delimiter_with_tree(
+                &state.buffer,
+                state.syntax_tree.as_ref(),
+                insert_position,
+                ch,
+                language,
+                tab_size,
+            )
             .unwrap_or(0)
     } else {
         0
@@ -508,6 +536,8 @@ struct InsertCursorData {
     only_spaces: bool,
     char_after: Option<u8>,
     deleted_text: Option<String>,
+    /// Columns the cursor sits past the end of its line
+    virtual_columns: usize,
 }
 
 /// Collect cursor data needed for character insertion.
@@ -528,7 +558,12 @@ fn collect_insert_cursor_data(state: &mut EditorState) -> Vec<InsertCursorData>
                 .as_ref()
                 .map(|r| r.start)
                 .unwrap_or(cursor.position);
-            (*cursor_id, selection, insert_position)
+            let virtual_columns = if selection.is_none() {
+                state.virtual_columns(cursor)
+            } else {
+                0
+            };
+            (*cursor_id, selection, insert_position, virtual_columns)
         })
         .collect();
 
@@ -537,7 +572,7 @@ fn collect_insert_cursor_data(state: &mut EditorState) -> Vec<InsertCursorData>
     // Collect all cursor data with buffer access
     cursor_info
         .into_iter()
-        .map(|(cursor_id, selection, insert_position)| {
+        .map(|(cursor_id, selection, insert_position, virtual_columns)| {
             // Calculate line start for auto-dedent
             let mut line_start = insert_position;
             while line_start > 0 {
@@ -574,6 +609,7 @@ fn collect_insert_cursor_data(state: &mut EditorState) -> Vec<InsertCursorData>
                 only_spaces,
                 char_after,
                 deleted_text,
+                virtual_columns,
             }
         })
         .collect()
@@ -591,7 +627,7 @@ fn insert_char_events(
     let auto_close_char = get_auto_close_char(ch, auto_indent);
     let cursor_data = collect_insert_cursor_data(state);
 
-    for data in cursor_data {
+    for mut data in cursor_data {
         // Delete selection if present
         if let (Some(range), Some(text)) = (data.selection, data.deleted_text) {
             events.push(Event::Delete {
@@ -601,6 +637,17 @@ fn insert_char_events(
             });
         }
 
+        // Pad the line with spaces up to a cursor in virtual space
+        if data.virtual_columns > 0 {
+            events.push(Event::Insert {
+                position: data.insert_position,
+                text: " ".repeat(data.virtual_columns),
+                cursor_id: data.cursor_id,
+            });
+            data.insert_position += data.virtual_columns;
+            data.only_spaces = false;
+        }
+
         // Try skip-over logic for closing brackets/quotes
         if auto_indent && matches!(ch, ')' | ']' | '}' | '"' | '\'' | '`') {
             if let Some(next_byte) = data.char_after {
@@ -699,7 +746,11 @@ pub fn action_to_events(
     match action {
         // Character input - insert at each cursor
         Action::InsertChar(ch) => {
-            insert_char_events(state, &mut events, ch, tab_size, auto_indent);
+            if let Some(block_events) = rectangle::type_into_blocks(state, &ch.to_string()) {
+                events.extend(block_events);
+            } else {
+                insert_char_events(state, &mut events, ch, tab_size, auto_indent);
+            }
         }
 
         Action::InsertNewline => {
@@ -738,7 +789,10 @@ pub fn action_to_events(
                 });
             }
 
-            // Now process insertions
+            // Now process insertions, picking up a finished reparse for the indent
+            if auto_indent {
+                state.update_syntax_tree();
+            }
             let line_ending = state.buffer.line_ending().as_str();
             for (cursor_id, indent_position) in indent_positions {
                 // Calculate indent for new line
@@ -747,11 +801,18 @@ pub fn action_to_events(
                 if auto_indent {
                     let use_tabs = state.use_tabs;
                     if let Some(language) = state.highlighter.language() {
-                        // Use tree-sitter-based indent when we have a highlighter
+                        // Use tree-sitter-based indent when we have a highlighter,
+                        // querying the document tree if there is one
                         if let Some(indent_width) = state
                             .indent_calculator
                             .borrow_mut()
-                            .calculate_indent(&state.buffer, indent_position, language, tab_size)
+                            .calculate_indent_with_tree(
+                                &state.buffer,
+                                state.syntax_tree.as_ref(),
+                                indent_position,
+                                language,
+                                tab_size,
+                            )
                         {
                             text.push_str(&indent_to_string(indent_width, use_tabs, tab_size));
                         }
@@ -1007,6 +1068,13 @@ pub fn action_to_events(
             for (cursor_id, cursor) in state.cursors.iter() {
                 let new_pos = state.buffer.prev_char_boundary(cursor.position);
                 let new_pos = adjust_position_for_crlf_left(&state.buffer, new_pos);
+                // In virtual space, move back a column without leaving the line end
+                let in_virtual_space = state.virtual_space && state.virtual_columns(cursor) > 0;
+                let (new_pos, new_sticky_column) = if in_virtual_space {
+                    (cursor.position, cursor.sticky_column - 1)
+                } else {
+                    (new_pos, 0)
+                };
 
                 // Preserve anchor if deselect_on_move is false (Emacs mark mode)
                 let new_anchor = if cursor.deselect_on_move {
@@ -1021,7 +1089,7 @@ pub fn action_to_events(
                     old_anchor: cursor.anchor,
                     new_anchor,
                     old_sticky_column: cursor.sticky_column,
-                    new_sticky_column: 0, // Reset sticky column on horizontal movement
+                    new_sticky_column,
                 });
             }
         }
@@ -1030,6 +1098,11 @@ pub fn action_to_events(
             for (cursor_id, cursor) in state.cursors.iter() {
                 let max_pos = max_cursor_position(&state.buffer);
                 let new_pos = next_position_for_crlf(&state.buffer, cursor.position, max_pos);
+                // At the end of a line, go on into virtual space if it is on
+                let (new_pos, new_sticky_column) = match virtual_column_right(state, cursor) {
+                    Some(column) => (cursor.position, column),
+                    None => (new_pos, 0),
+                };
 
                 // Preserve anchor if deselect_on_move is false (Emacs mark mode)
                 let new_anchor = if cursor.deselect_on_move {
@@ -1044,7 +1117,7 @@ pub fn action_to_events(
                     old_anchor: cursor.anchor,
                     new_anchor,
                     old_sticky_column: cursor.sticky_column,
-                    new_sticky_column: 0, // Reset sticky column on horizontal movement
+                    new_sticky_column,
                 });
             }
         }
@@ -1718,16 +1791,37 @@ pub fn action_to_events(
         }
 
         Action::DeleteBackward => {
+            if let Some(block_events) = rectangle::delete_in_blocks(state, false) {
+                return Some(block_events);
+            }
+
             // Sort cursors by position (reverse order) to avoid position shifts
             let mut cursor_vec: Vec<_> = state.cursors.iter().collect();
             cursor_vec.sort_by_key(|(_, c)| std::cmp::Reverse(c.position));
 
+            // A cursor in virtual space just moves back a column
+            for (cursor_id, cursor) in &cursor_vec {
+                if cursor.selection_range().is_none() && state.virtual_columns(cursor) > 0 {
+                    events.push(Event::MoveCursor {
+                        cursor_id: *cursor_id,
+                        old_position: cursor.position,
+                        new_position: cursor.position,
+                        old_anchor: cursor.anchor,
+                        new_anchor: cursor.anchor,
+                        old_sticky_column: cursor.sticky_column,
+                        new_sticky_column: cursor.sticky_column - 1,
+                    });
+                }
+            }
+
             // Collect all deletions first, checking for auto-pair deletion
             let deletions: Vec<_> = cursor_vec
                 .iter()
                 .filter_map(|(cursor_id, cursor)| {
                     if let Some(range) = cursor.selection_range() {
                         Some((*cursor_id, range))
+                    } else if state.virtual_columns(cursor) > 0 {
+                        None
                     } else if cursor.position > 0 {
                         // Use prev_char_boundary to properly handle multi-byte UTF-8 characters
                         // In CRLF files, this also ensures we delete \r\n as a unit
@@ -1779,6 +1873,10 @@ pub fn action_to_events(
         }
 
         Action::DeleteForward => {
+            if let Some(block_events) = rectangle::delete_in_blocks(state, true) {
+                return Some(block_events);
+            }
+
             // Sort cursors by position (reverse order) to avoid position shifts
             let mut cursor_vec: Vec<_> = state.cursors.iter().collect();
             cursor_vec.sort_by_key(|(_, c)| std::cmp::Reverse(c.position));
@@ -2010,6 +2108,10 @@ pub fn action_to_events(
         | Action::Close
         | Action::CloseTab
         | Action::GotoLine
+        | Action::JumpToChar
+        | Action::JumpToTwoChars
+        | Action::JumpToWord
+        | Action::JumpToLine
         | Action::NextBuffer
         | Action::PrevBuffer
         | Action::SwitchToPreviousTab
@@ -2025,9 +2127,48 @@ pub fn action_to_events(
         | Action::CopyWithTheme(_)
         | Action::Cut
         | Action::Paste
+        | Action::PasteFromHistory
+        | Action::KillLine
+        | Action::KillWordForward
+        | Action::KillWordBackward
+        | Action::Yank
+        | Action::YankPop
+        | Action::BrowseKillRing
+        | Action::InsertSnippet
+        | Action::SurroundAdd
+        | Action::SurroundChange
+        | Action::SurroundDelete
+        | Action::MoveLinesUp
+        | Action::MoveLinesDown
+        | Action::DuplicateLines
+        | Action::JoinLines(_)
+        | Action::JoinLinesWithSeparator
+        | Action::SortLines(_)
+        | Action::SortLinesByRegex
+        | Action::ReverseLines
+        | Action::UniqueLines
+        | Action::ShuffleLines
+        | Action::TransformText(_)
+        | Action::IncrementNumber(_)
         | Action::AddCursorNextMatch
+        | Action::AddCursorAtHint
+        | Action::SelectAllMatches
+        | Action::SkipToNextMatch
+        | Action::SplitSelectionIntoLines
+        | Action::AddCursorsAtRegex
+        | Action::AlignCursors
+        | Action::CursorUndo
+        | Action::RotateSelections
+        | Action::InsertSequence
         | Action::AddCursorAbove
         | Action::AddCursorBelow
+        | Action::DeleteRectangle
+        | Action::KillRectangle
+        | Action::CopyRectangle
+        | Action::YankRectangle
+        | Action::FillRectangle
+        | Action::InsertRectangleText
+        | Action::NumberRectangleLines
         | Action::CommandPalette
         | Action::ShowHelp
         | Action::ToggleLineWrap
@@ -2044,6 +2185,8 @@ pub fn action_to_events(
         | Action::ShowKeyboardShortcuts
         | Action::SmartHome
         | Action::ToggleComment
+        | Action::ToggleBlockComment
+        | Action::ReflowParagraph
         | Action::SetBookmark(_)
         | Action::JumpToBookmark(_)
         | Action::ClearBookmark(_)
@@ -2052,6 +2195,7 @@ pub fn action_to_events(
         | Action::ToggleSearchWholeWord
         | Action::ToggleSearchRegex
         | Action::ToggleSearchConfirmEach
+        | Action::ToggleSearchPreserveCase
         | Action::StartMacroRecording
         | Action::StopMacroRecording
         | Action::PlayMacro(_)
@@ -2061,6 +2205,14 @@ pub fn action_to_events(
         | Action::PromptRecordMacro
         | Action::PromptPlayMacro
         | Action::PlayLastMacro
+        | Action::PlayMacroOnLines(_)
+        | Action::PlayMacroTimes(_, _)
+        | Action::PlayMacroUntilFailure(_)
+        | Action::PromptEditMacro
+        | Action::PromptPlayMacroOnLines
+        | Action::PromptPlayMacroTimes
+        | Action::PromptPlayMacroUntilFailure
+        | Action::PromptExportMacro
         | Action::PromptSetBookmark
         | Action::PromptJumpToBookmark
         | Action::PromptConfirm
@@ -2137,6 +2289,17 @@ pub fn action_to_events(
         | Action::FindPrevious
         | Action::Replace
         | Action::QueryReplace
+        | Action::ProjectSearch
+        | Action::ProjectReplace
+        | Action::UndoProjectReplace
+        | Action::OpenProjectSearchResult
+        | Action::Occur
+        | Action::OccurGoto
+        | Action::SearchLines
+        | Action::StructuralSearch
+        | Action::StructuralReplace
+        | Action::StructuralSearchBuffer
+        | Action::StructuralReplaceBuffer
         | Action::MenuActivate
         | Action::MenuClose
         | Action::MenuLeft
@@ -2174,10 +2337,28 @@ pub fn action_to_events(
         | Action::SetLineEnding
         | Action::ToggleIndentationStyle
         | Action::ToggleTabIndicators
+        | Action::ToggleVirtualSpace
         | Action::ToggleDebugHighlights
         | Action::ResetBufferSettings
         | Action::ShellCommand
-        | Action::ShellCommandReplace => return None,
+        | Action::ShellCommandReplace
+        | Action::ToggleBreakpoint
+        | Action::SetConditionalBreakpoint
+        | Action::SetLogpoint
+        | Action::ClearAllBreakpoints
+        | Action::DebugStart
+        | Action::DebugStop
+        | Action::DebugContinue
+        | Action::DebugPause
+        | Action::DebugStepOver
+        | Action::DebugStepInto
+        | Action::DebugStepOut
+        | Action::DebugShowPanels
+        | Action::DebugAddWatch
+        | Action::DebugRemoveWatch
+        | Action::DebugEvaluate
+        | Action::DebugPanelActivate
+        | Action::Vi(_) => return None,
 
         // Block/rectangular selection actions
         Action::BlockSelectLeft => {
@@ -2221,58 +2402,277 @@ pub fn action_to_events(
         }
 
         Action::ExpandSelection => {
-            // Expand selection for each cursor
+            // Expand to the enclosing syntax node when the language has a
+            // tree, otherwise grow word by word
+            let tree = state.latest_syntax_tree().cloned();
+            let mut targets = Vec::new();
             for (cursor_id, cursor) in state.cursors.iter() {
-                if let Some(anchor) = cursor.anchor {
-                    // Already have a selection - expand by one word to the right
-                    // First move to the start of the next word, then to its end
-                    let next_word_start = find_word_start_right(&state.buffer, cursor.position);
-                    let new_end = find_word_end(&state.buffer, next_word_start);
-                    events.push(Event::MoveCursor {
-                        cursor_id,
-                        old_position: cursor.position,
-                        new_position: new_end,
-                        old_anchor: cursor.anchor,
-                        new_anchor: Some(anchor),
-                        old_sticky_column: cursor.sticky_column,
-                        new_sticky_column: 0, // Reset sticky column
-                    });
-                } else {
-                    // No selection - select from cursor to end of current word
-                    let word_start = find_word_start(&state.buffer, cursor.position);
-                    let word_end = find_word_end(&state.buffer, cursor.position);
-
-                    // If cursor is on non-word char OR at the end of a word,
-                    // select from current position to end of next word
-                    let (final_start, final_end) =
-                        if word_start == word_end || cursor.position == word_end {
-                            // Find the next word (skip non-word characters to find it)
-                            let next_start = find_word_start_right(&state.buffer, cursor.position);
-                            let next_end = find_word_end(&state.buffer, next_start);
-                            // Select FROM cursor position TO the end of next word
-                            (cursor.position, next_end)
-                        } else {
-                            // On a word char - select from cursor to end of current word
-                            (cursor.position, word_end)
-                        };
+                let target = match &tree {
+                    Some(tree) => {
+                        let selection = cursor
+                            .selection_range()
+                            .unwrap_or(cursor.position..cursor.position);
+                        enclosing_node_range(tree, selection).map(|range| (range.end, range.start))
+                    }
+                    None => Some(expand_selection_by_word(&state.buffer, cursor)),
+                };
+                if let Some((new_position, new_anchor)) = target {
+                    targets.push((cursor_id, new_position, new_anchor));
+                }
+            }
+            events.extend(expand_selection_events(state, targets));
+        }
 
+        Action::ShrinkSelection => {
+            // Walk back down each cursor's expansion history
+            for (cursor_id, cursor) in state.cursors.iter() {
+                let Some(history) = state.selection_history.get_mut(&cursor_id) else {
+                    continue;
+                };
+                // The selection was changed by something else since expanding
+                if history.len() < 2 || history.last() != Some(&(cursor.position, cursor.anchor)) {
+                    continue;
+                }
+                history.pop();
+                if let Some(&(new_position, new_anchor)) = history.last() {
                     events.push(Event::MoveCursor {
                         cursor_id,
                         old_position: cursor.position,
-                        new_position: final_end,
+                        new_position,
                         old_anchor: cursor.anchor,
-                        new_anchor: Some(final_start),
+                        new_anchor,
                         old_sticky_column: cursor.sticky_column,
                         new_sticky_column: 0, // Reset sticky column
                     });
                 }
             }
         }
+
+        Action::SelectTextObject(object) => {
+            let targets = textobject_targets(state, |objects, tree, buffer, cursor| {
+                let selection = cursor
+                    .selection_range()
+                    .unwrap_or(cursor.position..cursor.position);
+                objects.enclosing(tree, buffer, &object, selection)
+            });
+            let targets = targets
+                .into_iter()
+                .map(|(cursor_id, _, range)| (cursor_id, range.end, range.start))
+                .collect();
+            events.extend(expand_selection_events(state, targets));
+        }
+
+        Action::SelectNextTextObject(object) => {
+            let targets = textobject_targets(state, |objects, tree, buffer, cursor| {
+                let after = cursor
+                    .selection_range()
+                    .map_or(cursor.position, |range| range.end);
+                objects.next(tree, buffer, &object, after)
+            });
+            for (cursor_id, cursor, range) in targets {
+                events.push(Event::MoveCursor {
+                    cursor_id,
+                    old_position: cursor.position,
+                    new_position: range.end,
+                    old_anchor: cursor.anchor,
+                    new_anchor: Some(range.start),
+                    old_sticky_column: cursor.sticky_column,
+                    new_sticky_column: 0, // Reset sticky column
+                });
+            }
+        }
+
+        Action::GotoNextTextObject(object) => {
+            goto_textobject_action(state, &mut events, &object, true);
+        }
+
+        Action::GotoPreviousTextObject(object) => {
+            goto_textobject_action(state, &mut events, &object, false);
+        }
+
+        Action::SwapParameterNext => {
+            let mut swaps = textobject_targets(state, |objects, tree, buffer, cursor| {
+                objects.parameter_and_next(tree, buffer, cursor.position)
+            });
+            // Edit from the end of the buffer so earlier ranges stay valid,
+            // skipping cursors whose parameters overlap an edit already made
+            swaps.sort_by_key(|(_, _, (current, _))| std::cmp::Reverse(current.start));
+            let mut edited_from = usize::MAX;
+            for (cursor_id, cursor, (current, next)) in swaps {
+                if next.end > edited_from {
+                    continue;
+                }
+                edited_from = current.start;
+
+                let first = state.get_text_range(current.start, current.end);
+                let between = state.get_text_range(current.end, next.start);
+                let second = state.get_text_range(next.start, next.end);
+                let deleted_text = state.get_text_range(current.start, next.end);
+                let text = format!("{}{}{}", second, between, first);
+                let inserted_end = current.start + text.len();
+
+                events.push(Event::Delete {
+                    range: current.start..next.end,
+                    deleted_text,
+                    cursor_id,
+                });
+                events.push(Event::Insert {
+                    position: current.start,
+                    text,
+                    cursor_id,
+                });
+                // Follow the parameter to its new place so repeated swaps
+                // keep moving it along the list
+                let offset = (cursor.position - current.start).min(first.len());
+                events.push(Event::MoveCursor {
+                    cursor_id,
+                    old_position: inserted_end,
+                    new_position: inserted_end - first.len() + offset,
+                    old_anchor: None,
+                    new_anchor: None,
+                    old_sticky_column: cursor.sticky_column,
+                    new_sticky_column: 0, // Reset sticky column
+                });
+            }
+        }
+
+        Action::DeleteParameter => {
+            let mut targets = textobject_targets(state, |objects, tree, buffer, cursor| {
+                objects.parameter_at(tree, buffer, cursor.position)
+            });
+            // Delete from the end of the buffer, dropping overlapping ranges
+            targets.sort_by_key(|(_, _, range)| std::cmp::Reverse(range.start));
+            let mut deleted_from = usize::MAX;
+            let mut deletions = Vec::new();
+            for (cursor_id, _, range) in targets {
+                if range.end <= deleted_from {
+                    deleted_from = range.start;
+                    deletions.push((cursor_id, range));
+                }
+            }
+            apply_deletions(state, deletions, &mut events);
+        }
     }
 
     Some(events)
 }
 
+/// Move each cursor to the start of the next (or previous) text object
+fn goto_textobject_action(
+    state: &mut EditorState,
+    events: &mut Vec<Event>,
+    object: &str,
+    forward: bool,
+) {
+    let targets = textobject_targets(state, |objects, tree, buffer, cursor| {
+        if forward {
+            objects.next(tree, buffer, object, cursor.position)
+        } else {
+            objects.previous(tree, buffer, object, cursor.position)
+        }
+    });
+    for (cursor_id, cursor, range) in targets {
+        events.push(Event::MoveCursor {
+            cursor_id,
+            old_position: cursor.position,
+            new_position: range.start,
+            old_anchor: cursor.anchor,
+            new_anchor: None,
+            old_sticky_column: cursor.sticky_column,
+            new_sticky_column: 0, // Reset sticky column
+        });
+    }
+}
+
+/// Look up a text object for each cursor
+///
+/// Returns nothing when the buffer has no syntax tree or its language has no
+/// text object query.
+fn textobject_targets<T>(
+    state: &mut EditorState,
+    find: impl Fn(&TextObjects, &Tree, &Buffer, &Cursor) -> Option<T>,
+) -> Vec<(CursorId, Cursor, T)> {
+    let Some(tree) = state.latest_syntax_tree().cloned() else {
+        return Vec::new();
+    };
+    let Some(objects) = &state.textobjects else {
+        return Vec::new();
+    };
+    state
+        .cursors
+        .iter()
+        .filter_map(|(cursor_id, cursor)| {
+            find(objects, &tree, &state.buffer, cursor).map(|target| (cursor_id, *cursor, target))
+        })
+        .collect()
+}
+
+/// Grow a cursor's selection by one word to the right
+///
+/// Returns the new (position, anchor).
+fn expand_selection_by_word(buffer: &Buffer, cursor: &Cursor) -> (usize, usize) {
+    if let Some(anchor) = cursor.anchor {
+        // Already have a selection - expand by one word to the right
+        // First move to the start of the next word, then to its end
+        let next_word_start = find_word_start_right(buffer, cursor.position);
+        return (find_word_end(buffer, next_word_start), anchor);
+    }
+
+    // No selection - select from cursor to end of current word
+    let word_start = find_word_start(buffer, cursor.position);
+    let word_end = find_word_end(buffer, cursor.position);
+
+    // If cursor is on non-word char OR at the end of a word,
+    // select from current position to end of next word
+    if word_start == word_end || cursor.position == word_end {
+        // Find the next word (skip non-word characters to find it)
+        let next_start = find_word_start_right(buffer, cursor.position);
+        (find_word_end(buffer, next_start), cursor.position)
+    } else {
+        // On a word char - select from cursor to end of current word
+        (word_end, cursor.position)
+    }
+}
+
+/// Move cursors to expanded selections, recording the previous selection of
+/// each cursor so that ShrinkSelection can restore it
+///
+/// `targets` holds (cursor, new position, new anchor) triples.
+pub fn expand_selection_events(
+    state: &mut EditorState,
+    targets: Vec<(CursorId, usize, usize)>,
+) -> Vec<Event> {
+    let mut events = Vec::new();
+    for (cursor_id, new_position, new_anchor) in targets {
+        let Some(cursor) = state.cursors.get(cursor_id).copied() else {
+            continue;
+        };
+        let current = (cursor.position, cursor.anchor);
+        if current == (new_position, Some(new_anchor)) {
+            continue;
+        }
+
+        // Start a fresh history if the selection was changed by something else
+        let history = state.selection_history.entry(cursor_id).or_default();
+        if history.last() != Some(&current) {
+            history.clear();
+            history.push(current);
+        }
+        history.push((new_position, Some(new_anchor)));
+
+        events.push(Event::MoveCursor {
+            cursor_id,
+            old_position: cursor.position,
+            new_position,
+            old_anchor: cursor.anchor,
+            new_anchor: Some(new_anchor),
+            old_sticky_column: cursor.sticky_column,
+            new_sticky_column: 0, // Reset sticky column
+        });
+    }
+    events
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,6 +92,24 @@ impl ModeRegistry {
 
         registry.register(special_mode);
 
+        // Built-in mode for the debugger's call stack / variables / watch / console panels
+        let debug_panel_mode = BufferMode::new("debug-panel")
+            .with_parent("special")
+            .with_read_only(true)
+            .with_binding(KeyCode::Enter, KeyModifiers::NONE, "debug_panel_activate")
+            .with_binding(KeyCode::Char('a'), KeyModifiers::NONE, "debug_add_watch")
+            .with_binding(KeyCode::Char('d'), KeyModifiers::NONE, "debug_remove_watch")
+            .with_binding(KeyCode::Char('e'), KeyModifiers::NONE, "debug_evaluate");
+
+        registry.register(debug_panel_mode);
+
+        // Built-in mode for occur buffers. They stay editable (saving writes
+        // lines back to the source), so only Enter is bound
+        let occur_mode =
+            BufferMode::new("occur").with_binding(KeyCode::Enter, KeyModifiers::NONE, "occur_goto");
+
+        registry.register(occur_mode);
+
         registry
     }
 
@@ -225,6 +243,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_debug_panel_mode_keybindings() {
+        let registry = ModeRegistry::new();
+        assert_eq!(
+            registry.resolve_keybinding("debug-panel", KeyCode::Enter, KeyModifiers::NONE),
+            Some("debug_panel_activate".to_string())
+        );
+        // 'q' closes the panel like any special buffer
+        assert_eq!(
+            registry.resolve_keybinding("debug-panel", KeyCode::Char('q'), KeyModifiers::NONE),
+            Some("close".to_string())
+        );
+    }
+
+    #[test]
+    fn test_occur_mode_keybindings() {
+        let registry = ModeRegistry::new();
+        assert_eq!(
+            registry.resolve_keybinding("occur", KeyCode::Enter, KeyModifiers::NONE),
+            Some("occur_goto".to_string())
+        );
+        // Occur buffers are edited like any other buffer
+        assert!(!registry.is_read_only("occur"));
+        assert_eq!(
+            registry.resolve_keybinding("occur", KeyCode::Char('q'), KeyModifiers::NONE),
+            None
+        );
+    }
+
     #[test]
     fn test_mode_inheritance() {
         let mut registry = ModeRegistry::new();
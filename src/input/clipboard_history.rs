@@ -0,0 +1,154 @@
+//! Clipboard history
+//!
+//! Every copy and cut is recorded here, newest first, so earlier clipboard
+//! contents can be pasted again from a picker. Entries keep the text of each
+//! cursor separately (see [`KillEntry`]), which lets a multi-cursor copy be
+//! pasted back one piece per cursor.
+//!
+//! The history is capped both by number of entries and by total size, since
+//! it is persisted in the session file.
+
+use crate::input::kill_ring::KillEntry;
+use std::collections::VecDeque;
+
+/// Maximum number of entries kept
+pub const MAX_ENTRIES: usize = 50;
+
+/// Maximum total size of all entries, in bytes
+pub const MAX_BYTES: usize = 512 * 1024;
+
+/// Recent copies and cuts, newest first
+#[derive(Debug)]
+pub struct ClipboardHistory {
+    entries: VecDeque<KillEntry>,
+    max_entries: usize,
+    max_bytes: usize,
+}
+
+impl Default for ClipboardHistory {
+    fn default() -> Self {
+        Self::new(MAX_ENTRIES, MAX_BYTES)
+    }
+}
+
+impl ClipboardHistory {
+    /// Create an empty history with the given caps
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            max_entries,
+            max_bytes,
+        }
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing has been copied yet
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries, newest first
+    pub fn entries(&self) -> impl Iterator<Item = &KillEntry> {
+        self.entries.iter()
+    }
+
+    /// Entry at `index` (0 is newest)
+    pub fn get(&self, index: usize) -> Option<&KillEntry> {
+        self.entries.get(index)
+    }
+
+    /// Newest entry
+    pub fn newest(&self) -> Option<&KillEntry> {
+        self.entries.front()
+    }
+
+    /// Record a copy or cut
+    ///
+    /// Copying text that is already in the history moves it to the front.
+    /// Empty entries and entries larger than the whole size cap are ignored.
+    pub fn push(&mut self, entry: KillEntry) {
+        if entry.is_empty() || entry_size(&entry) > self.max_bytes {
+            return;
+        }
+        self.entries.retain(|existing| *existing != entry);
+        self.entries.push_front(entry);
+        self.entries.truncate(self.max_entries);
+
+        let mut total = 0;
+        let keep = self
+            .entries
+            .iter()
+            .take_while(|entry| {
+                total += entry_size(entry);
+                total <= self.max_bytes
+            })
+            .count();
+        self.entries.truncate(keep);
+    }
+
+    /// Replace the history with entries restored from a session, newest first
+    pub fn restore(&mut self, entries: impl IntoIterator<Item = KillEntry>) {
+        let entries: Vec<_> = entries.into_iter().collect();
+        self.entries.clear();
+        // Push oldest first so the caps drop the oldest entries
+        for entry in entries.into_iter().rev() {
+            self.push(entry);
+        }
+    }
+}
+
+fn entry_size(entry: &KillEntry) -> usize {
+    entry.pieces().iter().map(String::len).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str) -> KillEntry {
+        KillEntry::new(vec![text.to_string()])
+    }
+
+    #[test]
+    fn test_push_newest_first_and_dedup() {
+        let mut history = ClipboardHistory::default();
+        history.push(entry("a"));
+        history.push(entry("b"));
+        history.push(entry("a"));
+        let texts: Vec<_> = history.entries().map(KillEntry::text).collect();
+        assert_eq!(texts, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_caps_by_count_and_size() {
+        let mut history = ClipboardHistory::new(2, 10);
+        history.push(entry("one"));
+        history.push(entry("two"));
+        history.push(entry("six"));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.get(1).unwrap().text(), "two");
+
+        // 8 + 3 bytes is over the size cap, so "six" is dropped
+        history.push(entry("eightchr"));
+        assert_eq!(history.len(), 1);
+
+        // Larger than the whole cap: not recorded at all
+        history.push(entry("much too long"));
+        assert_eq!(history.newest().unwrap().text(), "eightchr");
+    }
+
+    #[test]
+    fn test_restore_keeps_order() {
+        let mut history = ClipboardHistory::default();
+        history.restore(vec![
+            entry("newest"),
+            KillEntry::new(vec!["x".into(), "y".into()]),
+        ]);
+        assert_eq!(history.newest().unwrap().text(), "newest");
+        assert_eq!(history.get(1).unwrap().pieces().len(), 2);
+    }
+}
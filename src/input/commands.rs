@@ -1,6 +1,8 @@
 //! Command palette system for executing editor actions by name
 
 use crate::input::keybindings::{Action, KeyContext};
+use crate::primitives::line_ops::{SortMode, SortOptions};
+use crate::primitives::text_transform::TextTransform;
 
 /// Source of a command (builtin or from a plugin)
 #[derive(Debug, Clone, PartialEq)]
@@ -256,6 +258,78 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: "Paste from History".to_string(),
+            description: "Pick an earlier copy or cut to paste".to_string(),
+            action: Action::PasteFromHistory,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Kill Line".to_string(),
+            description: "Delete to the end of the line, saving it on the kill ring".to_string(),
+            action: Action::KillLine,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Yank".to_string(),
+            description: "Insert the most recent kill".to_string(),
+            action: Action::Yank,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Yank Pop".to_string(),
+            description: "Replace the text just yanked with an older kill".to_string(),
+            action: Action::YankPop,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Browse Kill Ring".to_string(),
+            description: "Pick an earlier kill or copy to insert".to_string(),
+            action: Action::BrowseKillRing,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Insert Snippet".to_string(),
+            description: "Pick a snippet to insert, or to surround the selection with".to_string(),
+            action: Action::InsertSnippet,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Surround Selection".to_string(),
+            description: "Wrap each selection in brackets, quotes or an HTML tag".to_string(),
+            action: Action::SurroundAdd,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Change Surround".to_string(),
+            description: "Replace the brackets, quotes or tags around each cursor".to_string(),
+            action: Action::SurroundChange,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Delete Surround".to_string(),
+            description: "Remove the brackets, quotes or tags around each cursor".to_string(),
+            action: Action::SurroundDelete,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: "Delete Line".to_string(),
             description: "Delete the current line".to_string(),
@@ -265,123 +339,623 @@ pub fn get_all_commands() -> Vec<Command> {
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Delete Word Backward".to_string(),
-            description: "Delete the word before the cursor".to_string(),
-            action: Action::DeleteWordBackward,
+            name: "Delete Word Backward".to_string(),
+            description: "Delete the word before the cursor".to_string(),
+            action: Action::DeleteWordBackward,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Delete Word Forward".to_string(),
+            description: "Delete the word after the cursor".to_string(),
+            action: Action::DeleteWordForward,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Delete to End of Line".to_string(),
+            description: "Delete from cursor to the end of the line".to_string(),
+            action: Action::DeleteToLineEnd,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transpose Characters".to_string(),
+            description: "Swap the character before cursor with the one at cursor".to_string(),
+            action: Action::TransposeChars,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Open Line".to_string(),
+            description: "Insert newline at cursor without moving cursor".to_string(),
+            action: Action::OpenLine,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Move Lines Up".to_string(),
+            description: "Move the current or selected lines up one line".to_string(),
+            action: Action::MoveLinesUp,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Move Lines Down".to_string(),
+            description: "Move the current or selected lines down one line".to_string(),
+            action: Action::MoveLinesDown,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Duplicate Lines".to_string(),
+            description: "Copy the current or selected lines below themselves".to_string(),
+            action: Action::DuplicateLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Join Lines".to_string(),
+            description:
+                "Join the selected lines, or the current line with the next, separated by a space"
+                    .to_string(),
+            action: Action::JoinLines(" ".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Join Lines With Separator".to_string(),
+            description: "Join lines with a separator you enter".to_string(),
+            action: Action::JoinLinesWithSeparator,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Sort Lines".to_string(),
+            description: "Sort the selected lines, or the whole buffer".to_string(),
+            action: Action::SortLines(SortOptions::default()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Sort Lines Descending".to_string(),
+            description: "Sort the selected lines, or the whole buffer, in reverse order"
+                .to_string(),
+            action: Action::SortLines(SortOptions {
+                reverse: true,
+                ..Default::default()
+            }),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Sort Lines Naturally".to_string(),
+            description: "Sort lines comparing numbers within them by value (file2 before file10)"
+                .to_string(),
+            action: Action::SortLines(SortOptions {
+                mode: SortMode::Natural,
+                ..Default::default()
+            }),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Sort Lines Numerically".to_string(),
+            description: "Sort lines by the first number on each line".to_string(),
+            action: Action::SortLines(SortOptions {
+                mode: SortMode::Numeric,
+                ..Default::default()
+            }),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Sort Lines Case-Insensitively".to_string(),
+            description: "Sort lines ignoring case".to_string(),
+            action: Action::SortLines(SortOptions {
+                case_insensitive: true,
+                ..Default::default()
+            }),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Sort Lines by Regex".to_string(),
+            description: "Sort lines by the first capture group of a regex you enter".to_string(),
+            action: Action::SortLinesByRegex,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Reverse Lines".to_string(),
+            description: "Reverse the order of the selected lines, or the whole buffer".to_string(),
+            action: Action::ReverseLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Remove Duplicate Lines".to_string(),
+            description:
+                "Keep only the first of identical lines in the selection, or the whole buffer"
+                    .to_string(),
+            action: Action::UniqueLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Shuffle Lines".to_string(),
+            description: "Put the selected lines, or the whole buffer, in random order".to_string(),
+            action: Action::ShuffleLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Upper Case".to_string(),
+            description: "Convert the selection or word to UPPER CASE".to_string(),
+            action: Action::TransformText(TextTransform::UpperCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Lower Case".to_string(),
+            description: "Convert the selection or word to lower case".to_string(),
+            action: Action::TransformText(TextTransform::LowerCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Title Case".to_string(),
+            description: "Capitalize The First Letter Of Every Word".to_string(),
+            action: Action::TransformText(TextTransform::TitleCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: snake_case".to_string(),
+            description: "Convert the selection or word to snake_case".to_string(),
+            action: Action::TransformText(TextTransform::SnakeCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: camelCase".to_string(),
+            description: "Convert the selection or word to camelCase".to_string(),
+            action: Action::TransformText(TextTransform::CamelCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: PascalCase".to_string(),
+            description: "Convert the selection or word to PascalCase".to_string(),
+            action: Action::TransformText(TextTransform::PascalCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: kebab-case".to_string(),
+            description: "Convert the selection or word to kebab-case".to_string(),
+            action: Action::TransformText(TextTransform::KebabCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: SCREAMING_CASE".to_string(),
+            description: "Convert the selection or word to SCREAMING_CASE".to_string(),
+            action: Action::TransformText(TextTransform::ScreamingCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Toggle Case".to_string(),
+            description: "Swap upper and lower case letters".to_string(),
+            action: Action::TransformText(TextTransform::ToggleCase),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Increment Number".to_string(),
+            description: "Add one to the number or date at the cursor, or every number in the selection".to_string(),
+            action: Action::IncrementNumber(1),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Decrement Number".to_string(),
+            description: "Subtract one from the number or date at the cursor, or every number in the selection".to_string(),
+            action: Action::IncrementNumber(-1),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Base64 Encode".to_string(),
+            description: "Encode the selection as base64".to_string(),
+            action: Action::TransformText(TextTransform::Base64Encode),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Base64 Decode".to_string(),
+            description: "Decode base64 in the selection".to_string(),
+            action: Action::TransformText(TextTransform::Base64Decode),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: URL Encode".to_string(),
+            description: "Percent-encode the selection".to_string(),
+            action: Action::TransformText(TextTransform::UrlEncode),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: URL Decode".to_string(),
+            description: "Decode percent escapes in the selection".to_string(),
+            action: Action::TransformText(TextTransform::UrlDecode),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: JSON Escape".to_string(),
+            description: "Escape the selection for use inside a JSON string".to_string(),
+            action: Action::TransformText(TextTransform::JsonEscape),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: JSON Unescape".to_string(),
+            description: "Unescape the JSON string in the selection".to_string(),
+            action: Action::TransformText(TextTransform::JsonUnescape),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Transform: Evaluate Math Expression".to_string(),
+            description: "Replace the selected expression, or the line, with its result".to_string(),
+            action: Action::TransformText(TextTransform::EvaluateMath),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Recenter".to_string(),
+            description: "Center the view on the cursor".to_string(),
+            action: Action::Recenter,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Set Mark".to_string(),
+            description: "Set selection anchor to start a selection".to_string(),
+            action: Action::SetMark,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        // Selection
+        Command {
+            name: "Select All".to_string(),
+            description: "Select all text in the buffer".to_string(),
+            action: Action::SelectAll,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Select Word".to_string(),
+            description: "Select the word under the cursor".to_string(),
+            action: Action::SelectWord,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Select Line".to_string(),
+            description: "Select the current line".to_string(),
+            action: Action::SelectLine,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Expand Selection".to_string(),
+            description: "Expand the selection to the enclosing syntax node".to_string(),
+            action: Action::ExpandSelection,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Shrink Selection".to_string(),
+            description: "Undo the last selection expansion".to_string(),
+            action: Action::ShrinkSelection,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        // Text objects
+        Command {
+            name: "Select Function".to_string(),
+            description: "Select the enclosing function".to_string(),
+            action: Action::SelectTextObject("function.outer".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Select Function Body".to_string(),
+            description: "Select the body of the enclosing function".to_string(),
+            action: Action::SelectTextObject("function.inner".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Select Class".to_string(),
+            description: "Select the enclosing class, struct or type definition".to_string(),
+            action: Action::SelectTextObject("class.outer".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Select Parameter".to_string(),
+            description: "Select the parameter or argument under the cursor".to_string(),
+            action: Action::SelectTextObject("parameter.inner".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Select Comment".to_string(),
+            description: "Select the comment under the cursor".to_string(),
+            action: Action::SelectTextObject("comment.outer".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Select Block".to_string(),
+            description: "Select the enclosing block".to_string(),
+            action: Action::SelectTextObject("block.outer".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Select Next Parameter".to_string(),
+            description: "Select the next parameter or argument".to_string(),
+            action: Action::SelectNextTextObject("parameter.inner".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Go to Next Function".to_string(),
+            description: "Move the cursor to the start of the next function".to_string(),
+            action: Action::GotoNextTextObject("function.outer".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Go to Previous Function".to_string(),
+            description: "Move the cursor to the start of the previous function".to_string(),
+            action: Action::GotoPreviousTextObject("function.outer".to_string()),
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Swap Parameter with Next".to_string(),
+            description: "Swap the parameter under the cursor with the following one".to_string(),
+            action: Action::SwapParameterNext,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Delete Argument".to_string(),
+            description: "Delete the argument under the cursor along with its comma".to_string(),
+            action: Action::DeleteParameter,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        // Multi-cursor
+        Command {
+            name: "Add Cursor Above".to_string(),
+            description: "Add a cursor on the line above".to_string(),
+            action: Action::AddCursorAbove,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Add Cursor Below".to_string(),
+            description: "Add a cursor on the line below".to_string(),
+            action: Action::AddCursorBelow,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Delete Word Forward".to_string(),
-            description: "Delete the word after the cursor".to_string(),
-            action: Action::DeleteWordForward,
+            name: "Add Cursor at Next Match".to_string(),
+            description: "Add a cursor at the next occurrence of the selection".to_string(),
+            action: Action::AddCursorNextMatch,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Delete to End of Line".to_string(),
-            description: "Delete from cursor to the end of the line".to_string(),
-            action: Action::DeleteToLineEnd,
+            name: "Add Cursor at Hint".to_string(),
+            description: "Label each visible occurrence of a character and add a cursor at each label typed".to_string(),
+            action: Action::AddCursorAtHint,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Transpose Characters".to_string(),
-            description: "Swap the character before cursor with the one at cursor".to_string(),
-            action: Action::TransposeChars,
+            name: "Select All Occurrences".to_string(),
+            description: "Select every occurrence of the selection, or of the word under the cursor".to_string(),
+            action: Action::SelectAllMatches,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Open Line".to_string(),
-            description: "Insert newline at cursor without moving cursor".to_string(),
-            action: Action::OpenLine,
+            name: "Skip to Next Occurrence".to_string(),
+            description: "Move the newest selection on to the next occurrence of its text, skipping this one".to_string(),
+            action: Action::SkipToNextMatch,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Recenter".to_string(),
-            description: "Center the view on the cursor".to_string(),
-            action: Action::Recenter,
+            name: "Split Selection into Lines".to_string(),
+            description: "Give each line of each selection a cursor of its own".to_string(),
+            action: Action::SplitSelectionIntoLines,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Set Mark".to_string(),
-            description: "Set selection anchor to start a selection".to_string(),
-            action: Action::SetMark,
+            name: "Add Cursors at Regex Matches".to_string(),
+            description: "Select every match of a regex in the selections, or in the whole buffer".to_string(),
+            action: Action::AddCursorsAtRegex,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
-        // Selection
         Command {
-            name: "Select All".to_string(),
-            description: "Select all text in the buffer".to_string(),
-            action: Action::SelectAll,
+            name: "Align Cursors".to_string(),
+            description: "Pad the text before the cursors with spaces so they line up in one column".to_string(),
+            action: Action::AlignCursors,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Select Word".to_string(),
-            description: "Select the word under the cursor".to_string(),
-            action: Action::SelectWord,
+            name: "Cursor Undo".to_string(),
+            description: "Bring back the cursors from before the last multi-cursor operation".to_string(),
+            action: Action::CursorUndo,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Select Line".to_string(),
-            description: "Select the current line".to_string(),
-            action: Action::SelectLine,
+            name: "Rotate Selections".to_string(),
+            description: "Move the text of each selection into the next one, and the last one's into the first".to_string(),
+            action: Action::RotateSelections,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Expand Selection".to_string(),
-            description: "Expand the current selection by one word".to_string(),
-            action: Action::ExpandSelection,
+            name: "Insert Number Sequence".to_string(),
+            description: "Insert increasing numbers at the cursors, in document order".to_string(),
+            action: Action::InsertSequence,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
-        // Multi-cursor
         Command {
-            name: "Add Cursor Above".to_string(),
-            description: "Add a cursor on the line above".to_string(),
-            action: Action::AddCursorAbove,
+            name: "Remove Secondary Cursors".to_string(),
+            description: "Remove all cursors except the primary".to_string(),
+            action: Action::RemoveSecondaryCursors,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        // Rectangles
         Command {
-            name: "Add Cursor Below".to_string(),
-            description: "Add a cursor on the line below".to_string(),
-            action: Action::AddCursorBelow,
+            name: "Delete Rectangle".to_string(),
+            description: "Delete the columns of the block selection, or of the rectangle the selection spans".to_string(),
+            action: Action::DeleteRectangle,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Add Cursor at Next Match".to_string(),
-            description: "Add a cursor at the next occurrence of the selection".to_string(),
-            action: Action::AddCursorNextMatch,
+            name: "Kill Rectangle".to_string(),
+            description: "Delete the rectangle, keeping it for Yank Rectangle".to_string(),
+            action: Action::KillRectangle,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
         Command {
-            name: "Remove Secondary Cursors".to_string(),
-            description: "Remove all cursors except the primary".to_string(),
-            action: Action::RemoveSecondaryCursors,
+            name: "Copy Rectangle".to_string(),
+            description: "Keep the rectangle for Yank Rectangle without deleting it".to_string(),
+            action: Action::CopyRectangle,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Yank Rectangle".to_string(),
+            description: "Insert the last killed or copied rectangle at the cursor, one line of it per line".to_string(),
+            action: Action::YankRectangle,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Fill Rectangle".to_string(),
+            description: "Replace each line of the rectangle with the same text".to_string(),
+            action: Action::FillRectangle,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Insert Text on Rectangle Lines".to_string(),
+            description: "Insert the same text at the rectangle's left edge on each of its lines".to_string(),
+            action: Action::InsertRectangleText,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Number Rectangle Lines".to_string(),
+            description: "Insert increasing numbers at the rectangle's left edge on each of its lines".to_string(),
+            action: Action::NumberRectangleLines,
             contexts: vec![KeyContext::Normal],
             custom_contexts: vec![],
             source: CommandSource::Builtin,
@@ -535,6 +1109,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: "Toggle Virtual Space".to_string(),
+            description: "Let the cursor move past the end of a line, padding the line when typing there".to_string(),
+            action: Action::ToggleVirtualSpace,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: "Reset Buffer Settings".to_string(),
             description: "Reset buffer settings (tab size, indentation) to config defaults"
@@ -758,6 +1340,87 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: "Search in Project".to_string(),
+            description: "Search all project files and show the matching lines".to_string(),
+            action: Action::ProjectSearch,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Replace in Project".to_string(),
+            description: "Replace text in all project files".to_string(),
+            action: Action::ProjectReplace,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Undo Last Project Replace".to_string(),
+            description: "Revert the files changed by the last project replace".to_string(),
+            action: Action::UndoProjectReplace,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Open Project Search Result".to_string(),
+            description: "Open the file of the search result on the cursor's line".to_string(),
+            action: Action::OpenProjectSearchResult,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Occur".to_string(),
+            description: "List the lines of the buffer matching a regex in a side buffer"
+                .to_string(),
+            action: Action::Occur,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Search Lines".to_string(),
+            description: "Fuzzy-find a line of the buffer, previewing each match".to_string(),
+            action: Action::SearchLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Structural Search in Project".to_string(),
+            description: "Find code matching a pattern like `$X.unwrap()` or a tree-sitter query in every project file".to_string(),
+            action: Action::StructuralSearch,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Structural Replace in Project".to_string(),
+            description: "Rewrite code matching a syntax pattern in every project file".to_string(),
+            action: Action::StructuralReplace,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Structural Search in Buffer".to_string(),
+            description: "Find code matching a pattern like `$X.unwrap()` or a tree-sitter query in the buffer".to_string(),
+            action: Action::StructuralSearchBuffer,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Structural Replace in Buffer".to_string(),
+            description: "Rewrite code matching a syntax pattern in the buffer".to_string(),
+            action: Action::StructuralReplaceBuffer,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Navigation
         Command {
             name: "Go to Line".to_string(),
@@ -767,6 +1430,38 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: "Jump to Character".to_string(),
+            description: "Label each visible occurrence of a character, in every split, and jump to the label typed".to_string(),
+            action: Action::JumpToChar,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Jump to Two Characters".to_string(),
+            description: "Label each visible occurrence of two characters and jump to the label typed".to_string(),
+            action: Action::JumpToTwoChars,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Jump to Word".to_string(),
+            description: "Label each visible word starting with a character and jump to the label typed".to_string(),
+            action: Action::JumpToWord,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Jump to Line".to_string(),
+            description: "Label each visible line and jump to the label typed".to_string(),
+            action: Action::JumpToLine,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: "Smart Home".to_string(),
             description: "Move to first non-whitespace character, or line start if already there"
@@ -848,6 +1543,118 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: "Debug: Toggle Breakpoint".to_string(),
+            description: "Toggle a breakpoint on the current line".to_string(),
+            action: Action::ToggleBreakpoint,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Set Conditional Breakpoint".to_string(),
+            description: "Set a breakpoint that only stops when a condition holds".to_string(),
+            action: Action::SetConditionalBreakpoint,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Set Logpoint".to_string(),
+            description: "Log a message instead of stopping on the current line".to_string(),
+            action: Action::SetLogpoint,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Clear All Breakpoints".to_string(),
+            description: "Remove breakpoints from all files".to_string(),
+            action: Action::ClearAllBreakpoints,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Start".to_string(),
+            description: "Start debugging the current file".to_string(),
+            action: Action::DebugStart,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Stop".to_string(),
+            description: "Stop the debug session".to_string(),
+            action: Action::DebugStop,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Continue".to_string(),
+            description: "Continue execution (starts a session if none is running)".to_string(),
+            action: Action::DebugContinue,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Pause".to_string(),
+            description: "Pause the running program".to_string(),
+            action: Action::DebugPause,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Step Over".to_string(),
+            description: "Run to the next line".to_string(),
+            action: Action::DebugStepOver,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Step Into".to_string(),
+            description: "Step into the function call".to_string(),
+            action: Action::DebugStepInto,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Step Out".to_string(),
+            description: "Run until the current function returns".to_string(),
+            action: Action::DebugStepOut,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Show Panels".to_string(),
+            description: "Show the call stack, variables, watch and console panels".to_string(),
+            action: Action::DebugShowPanels,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Add Watch".to_string(),
+            description: "Add a watch expression".to_string(),
+            action: Action::DebugAddWatch,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Debug: Evaluate".to_string(),
+            description: "Evaluate an expression in the debug console".to_string(),
+            action: Action::DebugEvaluate,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: "Navigate Back".to_string(),
             description: "Go back in navigation history".to_string(),
@@ -873,6 +1680,22 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: "Toggle Block Comment".to_string(),
+            description: "Wrap the selection in a block comment, or unwrap it".to_string(),
+            action: Action::ToggleBlockComment,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Reflow Paragraph".to_string(),
+            description: "Rewrap the paragraph or selection at the wrap column".to_string(),
+            action: Action::ReflowParagraph,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: "Dedent Selection".to_string(),
             description: "Decrease indentation of selected lines".to_string(),
@@ -964,6 +1787,46 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: "Play Macro on Selected Lines".to_string(),
+            description: "Play a macro once at the start of each selected line".to_string(),
+            action: Action::PromptPlayMacroOnLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Play Macro N Times".to_string(),
+            description: "Play a macro repeatedly, e.g. \"1 5\" plays macro 1 five times".to_string(),
+            action: Action::PromptPlayMacroTimes,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Play Macro Until Failure".to_string(),
+            description: "Play a macro until a motion or search in it fails".to_string(),
+            action: Action::PromptPlayMacroUntilFailure,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Edit Macro".to_string(),
+            description: "Open a macro in a buffer; saving it updates the macro".to_string(),
+            action: Action::PromptEditMacro,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: "Export Macro as Command".to_string(),
+            description: "Save a macro as a plugin command, e.g. \"1 Add Semicolon\"".to_string(),
+            action: Action::PromptExportMacro,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: "Set Bookmark".to_string(),
             description: "Set a bookmark at current position (0-9)".to_string(),
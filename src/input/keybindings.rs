@@ -1,4 +1,7 @@
 use crate::config::Config;
+use crate::input::vi::ViCommand;
+use crate::primitives::line_ops::{SortMode, SortOptions};
+use crate::primitives::text_transform::TextTransform;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -242,6 +245,15 @@ pub enum Action {
     SelectWord,
     SelectLine,
     ExpandSelection,
+    ShrinkSelection,
+
+    // Tree-sitter text objects (e.g., "function.outer", "parameter.inner")
+    SelectTextObject(String),
+    SelectNextTextObject(String),
+    GotoNextTextObject(String),
+    GotoPreviousTextObject(String),
+    SwapParameterNext,
+    DeleteParameter,
 
     // Block/rectangular selection (column-wise)
     BlockSelectLeft,
@@ -249,6 +261,15 @@ pub enum Action {
     BlockSelectUp,
     BlockSelectDown,
 
+    // Rectangles (the block selection, or the one a selection spans)
+    DeleteRectangle,
+    KillRectangle,
+    CopyRectangle,
+    YankRectangle,
+    FillRectangle,
+    InsertRectangleText,
+    NumberRectangleLines,
+
     // Editing
     DeleteBackward,
     DeleteForward,
@@ -259,6 +280,22 @@ pub enum Action {
     TransposeChars,
     OpenLine,
 
+    // Lines
+    MoveLinesUp,
+    MoveLinesDown,
+    DuplicateLines,
+    JoinLines(String),
+    JoinLinesWithSeparator,
+    SortLines(SortOptions),
+    SortLinesByRegex,
+    ReverseLines,
+    UniqueLines,
+    ShuffleLines,
+
+    // Transforms
+    TransformText(TextTransform),
+    IncrementNumber(i64),
+
     // View
     Recenter,
 
@@ -270,11 +307,37 @@ pub enum Action {
     CopyWithTheme(String),
     Cut,
     Paste,
+    PasteFromHistory,
+
+    // Kill ring
+    KillLine,
+    KillWordForward,
+    KillWordBackward,
+    Yank,
+    YankPop,
+    BrowseKillRing,
+
+    // Snippets
+    InsertSnippet,
+
+    // Surround
+    SurroundAdd,
+    SurroundChange,
+    SurroundDelete,
 
     // Multi-cursor
     AddCursorAbove,
     AddCursorBelow,
     AddCursorNextMatch,
+    AddCursorAtHint,
+    SelectAllMatches,
+    SkipToNextMatch,
+    SplitSelectionIntoLines,
+    AddCursorsAtRegex,
+    AlignCursors,
+    CursorUndo,
+    RotateSelections,
+    InsertSequence,
     RemoveSecondaryCursors,
 
     // File operations
@@ -294,11 +357,17 @@ pub enum Action {
     GoToMatchingBracket,
     JumpToNextError,
     JumpToPreviousError,
+    JumpToChar,
+    JumpToTwoChars,
+    JumpToWord,
+    JumpToLine,
 
     // Smart editing
     SmartHome,
     DedentSelection,
     ToggleComment,
+    ToggleBlockComment,
+    ReflowParagraph,
 
     // Bookmarks
     SetBookmark(char),
@@ -311,6 +380,7 @@ pub enum Action {
     ToggleSearchWholeWord,
     ToggleSearchRegex,
     ToggleSearchConfirmEach,
+    ToggleSearchPreserveCase,
 
     // Macros
     StartMacroRecording,
@@ -322,6 +392,20 @@ pub enum Action {
     PromptRecordMacro,
     PromptPlayMacro,
     PlayLastMacro,
+    /// Play a macro once on each line of the selection
+    PlayMacroOnLines(char),
+    /// Play a macro up to N times, stopping early if it fails
+    PlayMacroTimes(char, usize),
+    /// Play a macro repeatedly until it fails
+    PlayMacroUntilFailure(char),
+    PromptEditMacro,
+    PromptPlayMacroOnLines,
+    PromptPlayMacroTimes,
+    PromptPlayMacroUntilFailure,
+    PromptExportMacro,
+
+    // Vi modal editing: commands parsed by `input::vi::ViState`, never bound directly
+    Vi(ViCommand),
 
     // Bookmarks (prompt-based)
     PromptSetBookmark,
@@ -441,6 +525,24 @@ pub enum Action {
     ToggleInlayHints,
     ToggleMouseHover,
 
+    // Debugging (DAP)
+    ToggleBreakpoint,
+    SetConditionalBreakpoint, // Prompt for a condition on the current line
+    SetLogpoint,              // Prompt for a log message on the current line
+    ClearAllBreakpoints,
+    DebugStart,
+    DebugStop,
+    DebugContinue, // Continue, or start a session if none is running
+    DebugPause,
+    DebugStepOver,
+    DebugStepInto,
+    DebugStepOut,
+    DebugShowPanels,    // Open call stack / variables / watch / console panels
+    DebugAddWatch,      // Prompt for a watch expression
+    DebugRemoveWatch,   // Remove the watch expression under the cursor
+    DebugEvaluate,      // Evaluate an expression in the debug console
+    DebugPanelActivate, // Enter in a debug panel (select frame, expand variable)
+
     // View toggles
     ToggleLineNumbers,
     ToggleMouseCapture,
@@ -453,6 +555,7 @@ pub enum Action {
     SetLineEnding,
     ToggleIndentationStyle,
     ToggleTabIndicators,
+    ToggleVirtualSpace,
     ResetBufferSettings,
 
     // Config operations
@@ -465,6 +568,17 @@ pub enum Action {
     FindPrevious,
     Replace,
     QueryReplace, // Interactive replace (y/n/!/q for each match)
+    ProjectSearch,
+    ProjectReplace,
+    UndoProjectReplace,
+    OpenProjectSearchResult,
+    Occur,
+    OccurGoto,
+    SearchLines,
+    StructuralSearch,
+    StructuralReplace,
+    StructuralSearchBuffer,
+    StructuralReplaceBuffer,
 
     // Menu navigation
     MenuActivate,     // Open menu bar (Alt or F10)
@@ -477,7 +591,7 @@ pub enum Action {
     MenuOpen(String), // Open a specific menu by name (e.g., "File", "Edit")
 
     // Keybinding map switching
-    SwitchKeybindingMap(String), // Switch to a named keybinding map (e.g., "default", "emacs", "vscode")
+    SwitchKeybindingMap(String), // Switch to a named keybinding map (e.g., "default", "emacs", "vscode", "vi")
 
     // Plugin custom actions
     PluginAction(String),
@@ -553,6 +667,27 @@ impl Action {
             "select_word" => Some(Action::SelectWord),
             "select_line" => Some(Action::SelectLine),
             "expand_selection" => Some(Action::ExpandSelection),
+            "shrink_selection" => Some(Action::ShrinkSelection),
+
+            // Text objects
+            "select_textobject" => {
+                let object = args.get("object")?.as_str()?;
+                Some(Action::SelectTextObject(object.to_string()))
+            }
+            "select_next_textobject" => {
+                let object = args.get("object")?.as_str()?;
+                Some(Action::SelectNextTextObject(object.to_string()))
+            }
+            "goto_next_textobject" => {
+                let object = args.get("object")?.as_str()?;
+                Some(Action::GotoNextTextObject(object.to_string()))
+            }
+            "goto_previous_textobject" => {
+                let object = args.get("object")?.as_str()?;
+                Some(Action::GotoPreviousTextObject(object.to_string()))
+            }
+            "swap_parameter_next" => Some(Action::SwapParameterNext),
+            "delete_parameter" => Some(Action::DeleteParameter),
 
             // Block/rectangular selection
             "block_select_left" => Some(Action::BlockSelectLeft),
@@ -560,6 +695,15 @@ impl Action {
             "block_select_up" => Some(Action::BlockSelectUp),
             "block_select_down" => Some(Action::BlockSelectDown),
 
+            // Rectangles
+            "delete_rectangle" => Some(Action::DeleteRectangle),
+            "kill_rectangle" => Some(Action::KillRectangle),
+            "copy_rectangle" => Some(Action::CopyRectangle),
+            "yank_rectangle" => Some(Action::YankRectangle),
+            "fill_rectangle" => Some(Action::FillRectangle),
+            "insert_rectangle_text" => Some(Action::InsertRectangleText),
+            "number_rectangle_lines" => Some(Action::NumberRectangleLines),
+
             "delete_backward" => Some(Action::DeleteBackward),
             "delete_forward" => Some(Action::DeleteForward),
             "delete_word_backward" => Some(Action::DeleteWordBackward),
@@ -568,6 +712,34 @@ impl Action {
             "delete_to_line_end" => Some(Action::DeleteToLineEnd),
             "transpose_chars" => Some(Action::TransposeChars),
             "open_line" => Some(Action::OpenLine),
+
+            "move_lines_up" => Some(Action::MoveLinesUp),
+            "move_lines_down" => Some(Action::MoveLinesDown),
+            "duplicate_lines" => Some(Action::DuplicateLines),
+            "join_lines" => {
+                let separator = args
+                    .get("separator")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(" ");
+                Some(Action::JoinLines(separator.to_string()))
+            }
+            "join_lines_with_separator" => Some(Action::JoinLinesWithSeparator),
+            "sort_lines" => SortOptions::from_args(args).map(Action::SortLines),
+            "sort_lines_by_regex" => Some(Action::SortLinesByRegex),
+            "reverse_lines" => Some(Action::ReverseLines),
+            "unique_lines" => Some(Action::UniqueLines),
+            "shuffle_lines" => Some(Action::ShuffleLines),
+            "transform_text" => args
+                .get("transform")
+                .and_then(|v| v.as_str())
+                .and_then(TextTransform::from_name)
+                .map(Action::TransformText),
+            "increment_number" => Some(Action::IncrementNumber(
+                args.get("amount").and_then(|v| v.as_i64()).unwrap_or(1),
+            )),
+            "decrement_number" => Some(Action::IncrementNumber(
+                -args.get("amount").and_then(|v| v.as_i64()).unwrap_or(1),
+            )),
             "recenter" => Some(Action::Recenter),
             "set_mark" => Some(Action::SetMark),
 
@@ -579,10 +751,31 @@ impl Action {
             }
             "cut" => Some(Action::Cut),
             "paste" => Some(Action::Paste),
+            "paste_from_history" => Some(Action::PasteFromHistory),
+
+            "kill_line" => Some(Action::KillLine),
+            "kill_word_forward" => Some(Action::KillWordForward),
+            "kill_word_backward" => Some(Action::KillWordBackward),
+            "yank" => Some(Action::Yank),
+            "yank_pop" => Some(Action::YankPop),
+            "browse_kill_ring" => Some(Action::BrowseKillRing),
+            "insert_snippet" => Some(Action::InsertSnippet),
+            "surround_add" => Some(Action::SurroundAdd),
+            "surround_change" => Some(Action::SurroundChange),
+            "surround_delete" => Some(Action::SurroundDelete),
 
             "add_cursor_above" => Some(Action::AddCursorAbove),
             "add_cursor_below" => Some(Action::AddCursorBelow),
             "add_cursor_next_match" => Some(Action::AddCursorNextMatch),
+            "add_cursor_at_hint" => Some(Action::AddCursorAtHint),
+            "select_all_matches" => Some(Action::SelectAllMatches),
+            "skip_to_next_match" => Some(Action::SkipToNextMatch),
+            "split_selection_into_lines" => Some(Action::SplitSelectionIntoLines),
+            "add_cursors_at_regex" => Some(Action::AddCursorsAtRegex),
+            "align_cursors" => Some(Action::AlignCursors),
+            "cursor_undo" => Some(Action::CursorUndo),
+            "rotate_selections" => Some(Action::RotateSelections),
+            "insert_sequence" => Some(Action::InsertSequence),
             "remove_secondary_cursors" => Some(Action::RemoveSecondaryCursors),
 
             "save" => Some(Action::Save),
@@ -599,10 +792,16 @@ impl Action {
             "goto_matching_bracket" => Some(Action::GoToMatchingBracket),
             "jump_to_next_error" => Some(Action::JumpToNextError),
             "jump_to_previous_error" => Some(Action::JumpToPreviousError),
+            "jump_to_char" => Some(Action::JumpToChar),
+            "jump_to_two_chars" => Some(Action::JumpToTwoChars),
+            "jump_to_word" => Some(Action::JumpToWord),
+            "jump_to_line" => Some(Action::JumpToLine),
 
             "smart_home" => Some(Action::SmartHome),
             "dedent_selection" => Some(Action::DedentSelection),
             "toggle_comment" => Some(Action::ToggleComment),
+            "toggle_block_comment" => Some(Action::ToggleBlockComment),
+            "reflow_paragraph" => Some(Action::ReflowParagraph),
 
             "set_bookmark" => {
                 if let Some(serde_json::Value::String(c)) = args.get("char") {
@@ -631,6 +830,7 @@ impl Action {
             "toggle_search_whole_word" => Some(Action::ToggleSearchWholeWord),
             "toggle_search_regex" => Some(Action::ToggleSearchRegex),
             "toggle_search_confirm_each" => Some(Action::ToggleSearchConfirmEach),
+            "toggle_search_preserve_case" => Some(Action::ToggleSearchPreserveCase),
 
             "start_macro_recording" => Some(Action::StartMacroRecording),
             "stop_macro_recording" => Some(Action::StopMacroRecording),
@@ -659,6 +859,33 @@ impl Action {
             "prompt_record_macro" => Some(Action::PromptRecordMacro),
             "prompt_play_macro" => Some(Action::PromptPlayMacro),
             "play_last_macro" => Some(Action::PlayLastMacro),
+            "play_macro_on_lines" => {
+                if let Some(serde_json::Value::String(c)) = args.get("char") {
+                    c.chars().next().map(Action::PlayMacroOnLines)
+                } else {
+                    None
+                }
+            }
+            "play_macro_times" => {
+                let count = args.get("count").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+                if let Some(serde_json::Value::String(c)) = args.get("char") {
+                    c.chars().next().map(|c| Action::PlayMacroTimes(c, count))
+                } else {
+                    None
+                }
+            }
+            "play_macro_until_failure" => {
+                if let Some(serde_json::Value::String(c)) = args.get("char") {
+                    c.chars().next().map(Action::PlayMacroUntilFailure)
+                } else {
+                    None
+                }
+            }
+            "prompt_edit_macro" => Some(Action::PromptEditMacro),
+            "prompt_play_macro_on_lines" => Some(Action::PromptPlayMacroOnLines),
+            "prompt_play_macro_times" => Some(Action::PromptPlayMacroTimes),
+            "prompt_play_macro_until_failure" => Some(Action::PromptPlayMacroUntilFailure),
+            "prompt_export_macro" => Some(Action::PromptExportMacro),
             "prompt_set_bookmark" => Some(Action::PromptSetBookmark),
             "prompt_jump_to_bookmark" => Some(Action::PromptJumpToBookmark),
 
@@ -756,6 +983,23 @@ impl Action {
             "toggle_inlay_hints" => Some(Action::ToggleInlayHints),
             "toggle_mouse_hover" => Some(Action::ToggleMouseHover),
 
+            "toggle_breakpoint" => Some(Action::ToggleBreakpoint),
+            "set_conditional_breakpoint" => Some(Action::SetConditionalBreakpoint),
+            "set_logpoint" => Some(Action::SetLogpoint),
+            "clear_all_breakpoints" => Some(Action::ClearAllBreakpoints),
+            "debug_start" => Some(Action::DebugStart),
+            "debug_stop" => Some(Action::DebugStop),
+            "debug_continue" => Some(Action::DebugContinue),
+            "debug_pause" => Some(Action::DebugPause),
+            "debug_step_over" => Some(Action::DebugStepOver),
+            "debug_step_into" => Some(Action::DebugStepInto),
+            "debug_step_out" => Some(Action::DebugStepOut),
+            "debug_show_panels" => Some(Action::DebugShowPanels),
+            "debug_add_watch" => Some(Action::DebugAddWatch),
+            "debug_remove_watch" => Some(Action::DebugRemoveWatch),
+            "debug_evaluate" => Some(Action::DebugEvaluate),
+            "debug_panel_activate" => Some(Action::DebugPanelActivate),
+
             "toggle_line_numbers" => Some(Action::ToggleLineNumbers),
             "toggle_mouse_capture" => Some(Action::ToggleMouseCapture),
             "toggle_debug_highlights" => Some(Action::ToggleDebugHighlights),
@@ -769,6 +1013,7 @@ impl Action {
             "set_line_ending" => Some(Action::SetLineEnding),
             "toggle_indentation_style" => Some(Action::ToggleIndentationStyle),
             "toggle_tab_indicators" => Some(Action::ToggleTabIndicators),
+            "toggle_virtual_space" => Some(Action::ToggleVirtualSpace),
             "reset_buffer_settings" => Some(Action::ResetBufferSettings),
 
             "dump_config" => Some(Action::DumpConfig),
@@ -779,6 +1024,17 @@ impl Action {
             "find_previous" => Some(Action::FindPrevious),
             "replace" => Some(Action::Replace),
             "query_replace" => Some(Action::QueryReplace),
+            "project_search" => Some(Action::ProjectSearch),
+            "project_replace" => Some(Action::ProjectReplace),
+            "undo_project_replace" => Some(Action::UndoProjectReplace),
+            "open_project_search_result" => Some(Action::OpenProjectSearchResult),
+            "occur" => Some(Action::Occur),
+            "occur_goto" => Some(Action::OccurGoto),
+            "search_lines" => Some(Action::SearchLines),
+            "structural_search" => Some(Action::StructuralSearch),
+            "structural_replace" => Some(Action::StructuralReplace),
+            "structural_search_buffer" => Some(Action::StructuralSearchBuffer),
+            "structural_replace_buffer" => Some(Action::StructuralReplaceBuffer),
 
             "menu_activate" => Some(Action::MenuActivate),
             "menu_close" => Some(Action::MenuClose),
@@ -1551,10 +1807,24 @@ impl KeybindingResolver {
             Action::SelectWord => "Select word under cursor".to_string(),
             Action::SelectLine => "Select current line".to_string(),
             Action::ExpandSelection => "Expand selection".to_string(),
+            Action::ShrinkSelection => "Shrink selection".to_string(),
+            Action::SelectTextObject(object) => format!("Select {}", object),
+            Action::SelectNextTextObject(object) => format!("Select next {}", object),
+            Action::GotoNextTextObject(object) => format!("Go to next {}", object),
+            Action::GotoPreviousTextObject(object) => format!("Go to previous {}", object),
+            Action::SwapParameterNext => "Swap parameter with next".to_string(),
+            Action::DeleteParameter => "Delete parameter".to_string(),
             Action::BlockSelectLeft => "Block select left".to_string(),
             Action::BlockSelectRight => "Block select right".to_string(),
             Action::BlockSelectUp => "Block select up".to_string(),
             Action::BlockSelectDown => "Block select down".to_string(),
+            Action::DeleteRectangle => "Delete rectangle".to_string(),
+            Action::KillRectangle => "Kill rectangle".to_string(),
+            Action::CopyRectangle => "Copy rectangle".to_string(),
+            Action::YankRectangle => "Yank rectangle".to_string(),
+            Action::FillRectangle => "Fill rectangle with text".to_string(),
+            Action::InsertRectangleText => "Insert text on each line of rectangle".to_string(),
+            Action::NumberRectangleLines => "Number lines of rectangle".to_string(),
             Action::DeleteBackward => "Delete backward".to_string(),
             Action::DeleteForward => "Delete forward".to_string(),
             Action::DeleteWordBackward => "Delete word backward".to_string(),
@@ -1563,6 +1833,45 @@ impl KeybindingResolver {
             Action::DeleteToLineEnd => "Delete to end of line".to_string(),
             Action::TransposeChars => "Transpose characters".to_string(),
             Action::OpenLine => "Open line below".to_string(),
+            Action::MoveLinesUp => "Move lines up".to_string(),
+            Action::MoveLinesDown => "Move lines down".to_string(),
+            Action::DuplicateLines => "Duplicate lines".to_string(),
+            Action::JoinLines(separator) if separator == " " => "Join lines".to_string(),
+            Action::JoinLines(separator) => format!("Join lines with {:?}", separator),
+            Action::JoinLinesWithSeparator => "Join lines with separator".to_string(),
+            Action::SortLines(options) => {
+                let mut qualifiers = Vec::new();
+                match options.mode {
+                    SortMode::Lexical => {}
+                    SortMode::Natural => qualifiers.push("natural"),
+                    SortMode::Numeric => qualifiers.push("numeric"),
+                }
+                if options.case_insensitive {
+                    qualifiers.push("case-insensitive");
+                }
+                if options.pattern.is_some() {
+                    qualifiers.push("by regex");
+                }
+                if options.reverse {
+                    qualifiers.push("descending");
+                }
+                if qualifiers.is_empty() {
+                    "Sort lines".to_string()
+                } else {
+                    format!("Sort lines ({})", qualifiers.join(", "))
+                }
+            }
+            Action::SortLinesByRegex => "Sort lines by regex".to_string(),
+            Action::ReverseLines => "Reverse lines".to_string(),
+            Action::UniqueLines => "Remove duplicate lines".to_string(),
+            Action::ShuffleLines => "Shuffle lines".to_string(),
+            Action::TransformText(transform) => format!("Transform: {}", transform.label()),
+            Action::IncrementNumber(1) => "Increment number".to_string(),
+            Action::IncrementNumber(-1) => "Decrement number".to_string(),
+            Action::IncrementNumber(amount) if *amount < 0 => {
+                format!("Decrement number by {}", amount.unsigned_abs())
+            }
+            Action::IncrementNumber(amount) => format!("Increment number by {}", amount),
             Action::Recenter => "Recenter view on cursor".to_string(),
             Action::SetMark => "Set mark (start selection)".to_string(),
             Action::Copy => "Copy".to_string(),
@@ -1570,9 +1879,29 @@ impl KeybindingResolver {
             Action::CopyWithTheme(theme) => format!("Copy with {} theme", theme),
             Action::Cut => "Cut".to_string(),
             Action::Paste => "Paste".to_string(),
+            Action::PasteFromHistory => "Paste from history".to_string(),
+            Action::KillLine => "Kill to end of line".to_string(),
+            Action::KillWordForward => "Kill word forward".to_string(),
+            Action::KillWordBackward => "Kill word backward".to_string(),
+            Action::Yank => "Yank from kill ring".to_string(),
+            Action::YankPop => "Replace yank with older kill".to_string(),
+            Action::BrowseKillRing => "Browse kill ring".to_string(),
+            Action::InsertSnippet => "Insert snippet".to_string(),
+            Action::SurroundAdd => "Surround selection with a pair".to_string(),
+            Action::SurroundChange => "Change surrounding pair".to_string(),
+            Action::SurroundDelete => "Delete surrounding pair".to_string(),
             Action::AddCursorAbove => "Add cursor above".to_string(),
             Action::AddCursorBelow => "Add cursor below".to_string(),
             Action::AddCursorNextMatch => "Add cursor at next match".to_string(),
+            Action::AddCursorAtHint => "Add cursors at labelled characters".to_string(),
+            Action::SelectAllMatches => "Select all occurrences".to_string(),
+            Action::SkipToNextMatch => "Move newest selection to next occurrence".to_string(),
+            Action::SplitSelectionIntoLines => "Split selection into lines".to_string(),
+            Action::AddCursorsAtRegex => "Add cursors at regex matches".to_string(),
+            Action::AlignCursors => "Align cursors".to_string(),
+            Action::CursorUndo => "Undo last cursor operation".to_string(),
+            Action::RotateSelections => "Rotate selections".to_string(),
+            Action::InsertSequence => "Insert number sequence".to_string(),
             Action::RemoveSecondaryCursors => "Remove secondary cursors".to_string(),
             Action::Save => "Save file".to_string(),
             Action::SaveAs => "Save file as...".to_string(),
@@ -1588,11 +1917,17 @@ impl KeybindingResolver {
             Action::GoToMatchingBracket => "Go to matching bracket".to_string(),
             Action::JumpToNextError => "Jump to next error/diagnostic".to_string(),
             Action::JumpToPreviousError => "Jump to previous error/diagnostic".to_string(),
+            Action::JumpToChar => "Jump to a labelled character".to_string(),
+            Action::JumpToTwoChars => "Jump to a labelled pair of characters".to_string(),
+            Action::JumpToWord => "Jump to a labelled word start".to_string(),
+            Action::JumpToLine => "Jump to a labelled line start".to_string(),
             Action::SmartHome => {
                 "Smart home (toggle line start / first non-whitespace)".to_string()
             }
             Action::DedentSelection => "Dedent selection".to_string(),
             Action::ToggleComment => "Toggle comment".to_string(),
+            Action::ToggleBlockComment => "Toggle block comment".to_string(),
+            Action::ReflowParagraph => "Reflow paragraph".to_string(),
             Action::SetBookmark(c) => format!("Set bookmark '{}'", c),
             Action::JumpToBookmark(c) => format!("Jump to bookmark '{}'", c),
             Action::ClearBookmark(c) => format!("Clear bookmark '{}'", c),
@@ -1601,6 +1936,7 @@ impl KeybindingResolver {
             Action::ToggleSearchWholeWord => "Toggle search whole word matching".to_string(),
             Action::ToggleSearchRegex => "Toggle search regex mode".to_string(),
             Action::ToggleSearchConfirmEach => "Toggle confirm each replacement".to_string(),
+            Action::ToggleSearchPreserveCase => "Toggle case-preserving replace".to_string(),
             Action::StartMacroRecording => "Start macro recording".to_string(),
             Action::StopMacroRecording => "Stop macro recording".to_string(),
             Action::PlayMacro(c) => format!("Play macro '{}'", c),
@@ -1610,6 +1946,23 @@ impl KeybindingResolver {
             Action::PromptRecordMacro => "Record macro (prompts for register)".to_string(),
             Action::PromptPlayMacro => "Play macro (prompts for register)".to_string(),
             Action::PlayLastMacro => "Play last recorded macro".to_string(),
+            Action::PlayMacroOnLines(c) => format!("Play macro '{}' on each selected line", c),
+            Action::PlayMacroTimes(c, n) => format!("Play macro '{}' {} times", c, n),
+            Action::PlayMacroUntilFailure(c) => format!("Play macro '{}' until it fails", c),
+            Action::PromptEditMacro => "Edit macro (prompts for register)".to_string(),
+            Action::PromptPlayMacroOnLines => {
+                "Play macro on each selected line (prompts for register)".to_string()
+            }
+            Action::PromptPlayMacroTimes => {
+                "Play macro N times (prompts for register and count)".to_string()
+            }
+            Action::PromptPlayMacroUntilFailure => {
+                "Play macro until it fails (prompts for register)".to_string()
+            }
+            Action::PromptExportMacro => {
+                "Export macro as a plugin command (prompts for register and name)".to_string()
+            }
+            Action::Vi(command) => format!("Vi {:?}", command),
             Action::PromptSetBookmark => "Set bookmark (prompts for register)".to_string(),
             Action::PromptJumpToBookmark => "Jump to bookmark (prompts for register)".to_string(),
             Action::Undo => "Undo".to_string(),
@@ -1699,6 +2052,22 @@ impl KeybindingResolver {
             Action::LspStop => "LSP: Stop a running server".to_string(),
             Action::ToggleInlayHints => "Toggle inlay hints".to_string(),
             Action::ToggleMouseHover => "Toggle LSP hover on mouse".to_string(),
+            Action::ToggleBreakpoint => "Debug: Toggle breakpoint".to_string(),
+            Action::SetConditionalBreakpoint => "Debug: Set conditional breakpoint".to_string(),
+            Action::SetLogpoint => "Debug: Set logpoint".to_string(),
+            Action::ClearAllBreakpoints => "Debug: Remove all breakpoints".to_string(),
+            Action::DebugStart => "Debug: Start debugging".to_string(),
+            Action::DebugStop => "Debug: Stop debugging".to_string(),
+            Action::DebugContinue => "Debug: Continue".to_string(),
+            Action::DebugPause => "Debug: Pause".to_string(),
+            Action::DebugStepOver => "Debug: Step over".to_string(),
+            Action::DebugStepInto => "Debug: Step into".to_string(),
+            Action::DebugStepOut => "Debug: Step out".to_string(),
+            Action::DebugShowPanels => "Debug: Show debug panels".to_string(),
+            Action::DebugAddWatch => "Debug: Add watch expression".to_string(),
+            Action::DebugRemoveWatch => "Debug: Remove watch expression".to_string(),
+            Action::DebugEvaluate => "Debug: Evaluate expression".to_string(),
+            Action::DebugPanelActivate => "Debug: Activate panel item".to_string(),
             Action::ToggleLineNumbers => "Toggle line numbers".to_string(),
             Action::ToggleMouseCapture => "Toggle mouse support".to_string(),
             Action::ToggleDebugHighlights => {
@@ -1710,6 +2079,7 @@ impl KeybindingResolver {
             Action::SetLineEnding => "Set line ending format (LF/CRLF)".to_string(),
             Action::ToggleIndentationStyle => "Toggle indentation style (spaces/tabs)".to_string(),
             Action::ToggleTabIndicators => "Toggle tab indicator visibility".to_string(),
+            Action::ToggleVirtualSpace => "Toggle virtual space".to_string(),
             Action::ResetBufferSettings => "Reset buffer settings to config".to_string(),
             Action::DumpConfig => "Dump config to file".to_string(),
             Action::Search => "Search for text in buffer".to_string(),
@@ -1718,6 +2088,17 @@ impl KeybindingResolver {
             Action::FindPrevious => "Find previous search match".to_string(),
             Action::Replace => "Replace text in buffer".to_string(),
             Action::QueryReplace => "Interactive replace (y/n/!/q for each match)".to_string(),
+            Action::ProjectSearch => "Search in all project files".to_string(),
+            Action::ProjectReplace => "Replace in all project files".to_string(),
+            Action::UndoProjectReplace => "Undo the last project replace".to_string(),
+            Action::OpenProjectSearchResult => "Open project search result".to_string(),
+            Action::Occur => "List lines matching a regex".to_string(),
+            Action::OccurGoto => "Go to the occur line".to_string(),
+            Action::SearchLines => "Fuzzy-find a line of the buffer".to_string(),
+            Action::StructuralSearch => "Search all project files by syntax".to_string(),
+            Action::StructuralReplace => "Rewrite syntax matches in all project files".to_string(),
+            Action::StructuralSearchBuffer => "Search the buffer by syntax".to_string(),
+            Action::StructuralReplaceBuffer => "Rewrite syntax matches in the buffer".to_string(),
             Action::MenuActivate => "Activate menu bar".to_string(),
             Action::MenuClose => "Close menu".to_string(),
             Action::MenuLeft => "Navigate to previous menu".to_string(),
@@ -1938,6 +2319,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_textobject_action_from_str() {
+        let mut args = HashMap::new();
+        // The text object is required
+        assert_eq!(Action::from_str("select_textobject", &args), None);
+
+        args.insert("object".to_string(), serde_json::json!("function.inner"));
+        assert_eq!(
+            Action::from_str("select_textobject", &args),
+            Some(Action::SelectTextObject("function.inner".to_string()))
+        );
+        assert_eq!(
+            Action::from_str("goto_previous_textobject", &args),
+            Some(Action::GotoPreviousTextObject("function.inner".to_string()))
+        );
+        assert_eq!(
+            Action::from_str("delete_parameter", &args),
+            Some(Action::DeleteParameter)
+        );
+    }
+
+    #[test]
+    fn test_transform_action_from_str() {
+        let mut args = HashMap::new();
+        // The transform is required
+        assert_eq!(Action::from_str("transform_text", &args), None);
+        assert_eq!(
+            Action::from_str("decrement_number", &args),
+            Some(Action::IncrementNumber(-1))
+        );
+
+        args.insert("transform".to_string(), serde_json::json!("kebab_case"));
+        args.insert("amount".to_string(), serde_json::json!(10));
+        assert_eq!(
+            Action::from_str("transform_text", &args),
+            Some(Action::TransformText(TextTransform::KebabCase))
+        );
+        assert_eq!(
+            Action::from_str("increment_number", &args),
+            Some(Action::IncrementNumber(10))
+        );
+    }
+
+    #[test]
+    fn test_macro_playback_action_from_str() {
+        let mut args = HashMap::new();
+        // The register is required
+        assert_eq!(Action::from_str("play_macro_times", &args), None);
+
+        args.insert("char".to_string(), serde_json::json!("4"));
+        assert_eq!(
+            Action::from_str("play_macro_times", &args),
+            Some(Action::PlayMacroTimes('4', 1))
+        );
+        assert_eq!(
+            Action::from_str("play_macro_until_failure", &args),
+            Some(Action::PlayMacroUntilFailure('4'))
+        );
+
+        args.insert("count".to_string(), serde_json::json!(25));
+        assert_eq!(
+            Action::from_str("play_macro_times", &args),
+            Some(Action::PlayMacroTimes('4', 25))
+        );
+    }
+
     #[test]
     fn test_key_context_from_when_clause() {
         assert_eq!(
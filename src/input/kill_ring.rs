@@ -0,0 +1,305 @@
+//! Emacs-style kill ring
+//!
+//! Kills (kill line, kill word, cut, copy) push their text onto a ring of
+//! recent kills. A run of consecutive kill commands merges into a single
+//! entry, so pressing C-k three times and then C-y yanks all three lines
+//! back at once. After a yank, yank-pop replaces the just-inserted text with
+//! the next older entry.
+//!
+//! Each entry keeps one piece of text per cursor, in document order. A
+//! multi-cursor kill followed by a yank with the same number of cursors puts
+//! every piece back at its own cursor; with a different number of cursors
+//! every cursor gets the whole entry.
+
+use crate::model::event::{BufferId, CursorId};
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Maximum number of entries kept (same as Emacs' `kill-ring-max`)
+pub const KILL_RING_MAX: usize = 120;
+
+/// One kill ring entry: the text killed at each cursor
+///
+/// Line endings are stored as LF and converted to the target buffer's
+/// format when yanked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillEntry {
+    pieces: Vec<String>,
+}
+
+impl KillEntry {
+    /// Create an entry from the text of each cursor, in document order
+    pub fn new(pieces: Vec<String>) -> Self {
+        let pieces = pieces
+            .into_iter()
+            .map(|piece| piece.replace("\r\n", "\n").replace('\r', "\n"))
+            .collect();
+        Self { pieces }
+    }
+
+    /// The per-cursor pieces, in document order
+    pub fn pieces(&self) -> &[String] {
+        &self.pieces
+    }
+
+    /// The whole entry as one string, pieces separated by newlines
+    ///
+    /// This is what goes to the system clipboard.
+    pub fn text(&self) -> String {
+        self.pieces.join("\n")
+    }
+
+    /// Whether every piece is empty
+    pub fn is_empty(&self) -> bool {
+        self.pieces.iter().all(|piece| piece.is_empty())
+    }
+
+    /// Text to insert at each of `cursor_count` cursors (in document order)
+    pub fn pieces_for(&self, cursor_count: usize) -> Vec<String> {
+        if cursor_count == self.pieces.len() {
+            self.pieces.clone()
+        } else {
+            vec![self.text(); cursor_count]
+        }
+    }
+
+    /// Merge a following kill into this entry
+    ///
+    /// Forward kills (C-k, M-d) append, backward kills (M-DEL) prepend. Returns
+    /// false when the cursor counts differ and the kill can't be merged.
+    fn merge(&mut self, kill: &KillEntry, direction: KillDirection) -> bool {
+        if kill.pieces.len() != self.pieces.len() {
+            return false;
+        }
+        for (existing, piece) in self.pieces.iter_mut().zip(&kill.pieces) {
+            match direction {
+                KillDirection::Forward => existing.push_str(piece),
+                KillDirection::Backward => existing.insert_str(0, piece),
+            }
+        }
+        true
+    }
+}
+
+/// Which side of the cursor a kill removed text from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillDirection {
+    Forward,
+    Backward,
+}
+
+/// Text inserted by the last yank, so yank-pop can replace it
+#[derive(Debug, Clone)]
+pub struct LastYank {
+    /// Buffer the yank went into
+    pub buffer_id: BufferId,
+    /// Inserted range and text for each cursor
+    pub ranges: Vec<(CursorId, Range<usize>, String)>,
+}
+
+/// Ring of recently killed text, newest first
+#[derive(Debug, Default)]
+pub struct KillRing {
+    entries: VecDeque<KillEntry>,
+    /// Entry inserted by the last yank or yank-pop
+    yank_index: usize,
+    /// The previous command was a kill, so the next kill merges into the newest entry
+    appending: bool,
+    /// The previous command was a yank
+    last_yank: Option<LastYank>,
+}
+
+impl KillRing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing has been killed yet
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries, newest first
+    pub fn entries(&self) -> impl Iterator<Item = &KillEntry> {
+        self.entries.iter()
+    }
+
+    /// Newest entry
+    pub fn newest(&self) -> Option<&KillEntry> {
+        self.entries.front()
+    }
+
+    /// Called before every command
+    ///
+    /// Anything but a kill ends the run of merging kills, and anything but a
+    /// yank forgets the last yank so yank-pop can't replace unrelated text.
+    pub fn start_command(&mut self, is_kill: bool, is_yank: bool) {
+        if !is_kill {
+            self.appending = false;
+        }
+        if !is_yank {
+            self.last_yank = None;
+        }
+    }
+
+    /// Record a kill, merging it into the newest entry after another kill
+    ///
+    /// Returns the entry that now holds the killed text.
+    pub fn kill(&mut self, pieces: Vec<String>, direction: KillDirection) -> Option<&KillEntry> {
+        let kill = KillEntry::new(pieces);
+        let merged = self.appending
+            && self
+                .entries
+                .front_mut()
+                .is_some_and(|newest| newest.merge(&kill, direction));
+        if !merged {
+            self.push(kill);
+        }
+        self.appending = true;
+        self.yank_index = 0;
+        self.entries.front()
+    }
+
+    /// Push a new entry without merging (copies, text from the system clipboard)
+    ///
+    /// Empty entries and repeats of the newest entry are ignored.
+    pub fn push(&mut self, entry: KillEntry) {
+        if entry.is_empty() || self.entries.front() == Some(&entry) {
+            return;
+        }
+        self.entries.push_front(entry);
+        self.entries.truncate(KILL_RING_MAX);
+        self.yank_index = 0;
+    }
+
+    /// Entry at `index` (0 is newest), which later yank-pops continue from
+    pub fn yank(&mut self, index: usize) -> Option<&KillEntry> {
+        let entry = self.entries.get(index)?;
+        self.yank_index = index;
+        Some(entry)
+    }
+
+    /// The next older entry after the last yank, wrapping around to the newest
+    pub fn rotate(&mut self) -> Option<&KillEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.yank_index = (self.yank_index + 1) % self.entries.len();
+        self.entries.get(self.yank_index)
+    }
+
+    /// Remember what the last yank inserted
+    pub fn set_last_yank(&mut self, last_yank: LastYank) {
+        self.last_yank = Some(last_yank);
+    }
+
+    /// Take what the last yank inserted, if the previous command was a yank
+    pub fn take_last_yank(&mut self) -> Option<LastYank> {
+        self.last_yank.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kill(ring: &mut KillRing, text: &str, direction: KillDirection) {
+        ring.start_command(true, false);
+        ring.kill(vec![text.to_string()], direction);
+    }
+
+    #[test]
+    fn test_entries_store_lf_line_endings() {
+        let entry = KillEntry::new(vec!["a\r\nb\rc".to_string()]);
+        assert_eq!(entry.text(), "a\nb\nc");
+    }
+
+    #[test]
+    fn test_consecutive_kills_merge() {
+        let mut ring = KillRing::new();
+        kill(&mut ring, "one", KillDirection::Forward);
+        kill(&mut ring, "\n", KillDirection::Forward);
+        kill(&mut ring, "two", KillDirection::Forward);
+        assert_eq!(ring.len(), 1);
+        assert_eq!(ring.newest().unwrap().text(), "one\ntwo");
+
+        kill(&mut ring, "zero ", KillDirection::Backward);
+        assert_eq!(ring.newest().unwrap().text(), "zero one\ntwo");
+    }
+
+    #[test]
+    fn test_other_command_starts_new_entry() {
+        let mut ring = KillRing::new();
+        kill(&mut ring, "one", KillDirection::Forward);
+        ring.start_command(false, false);
+        kill(&mut ring, "two", KillDirection::Forward);
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.newest().unwrap().text(), "two");
+    }
+
+    #[test]
+    fn test_rotate_wraps_around() {
+        let mut ring = KillRing::new();
+        for text in ["a", "b", "c"] {
+            ring.push(KillEntry::new(vec![text.to_string()]));
+        }
+        assert_eq!(ring.yank(0).unwrap().text(), "c");
+        assert_eq!(ring.rotate().unwrap().text(), "b");
+        assert_eq!(ring.rotate().unwrap().text(), "a");
+        assert_eq!(ring.rotate().unwrap().text(), "c");
+    }
+
+    #[test]
+    fn test_push_skips_empty_and_duplicates() {
+        let mut ring = KillRing::new();
+        ring.push(KillEntry::new(vec![String::new()]));
+        ring.push(KillEntry::new(vec!["a".to_string()]));
+        ring.push(KillEntry::new(vec!["a".to_string()]));
+        assert_eq!(ring.len(), 1);
+    }
+
+    #[test]
+    fn test_pieces_per_cursor() {
+        let entry = KillEntry::new(vec!["x".to_string(), "y".to_string()]);
+        assert_eq!(entry.pieces_for(2), vec!["x", "y"]);
+        assert_eq!(entry.pieces_for(3), vec!["x\ny"; 3]);
+
+        // Multi-cursor kills only merge with an entry from the same number of cursors
+        let mut ring = KillRing::new();
+        ring.start_command(true, false);
+        ring.kill(
+            vec!["a".to_string(), "b".to_string()],
+            KillDirection::Forward,
+        );
+        ring.kill(
+            vec!["1".to_string(), "2".to_string()],
+            KillDirection::Forward,
+        );
+        assert_eq!(ring.newest().unwrap().pieces(), ["a1", "b2"]);
+        ring.kill(vec!["z".to_string()], KillDirection::Forward);
+        assert_eq!(ring.len(), 2);
+    }
+
+    #[test]
+    fn test_last_yank_cleared_by_other_commands() {
+        let mut ring = KillRing::new();
+        ring.set_last_yank(LastYank {
+            buffer_id: BufferId(0),
+            ranges: vec![],
+        });
+        ring.start_command(false, true);
+        assert!(ring.take_last_yank().is_some());
+
+        ring.set_last_yank(LastYank {
+            buffer_id: BufferId(0),
+            ranges: vec![],
+        });
+        ring.start_command(false, false);
+        assert!(ring.take_last_yank().is_none());
+    }
+}
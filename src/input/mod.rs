@@ -4,11 +4,16 @@
 
 pub mod actions;
 pub mod buffer_mode;
+pub mod clipboard_history;
 pub mod command_registry;
 pub mod commands;
 pub mod fuzzy;
 pub mod handler;
 pub mod input_history;
 pub mod keybindings;
+pub mod kill_ring;
 pub mod multi_cursor;
 pub mod position_history;
+pub mod rectangle;
+pub mod snippets;
+pub mod vi;
@@ -1,8 +1,16 @@
 //! Multi-cursor operations for adding cursors at various positions
 
+use std::ops::Range;
+
+use regex::bytes::Regex;
+
 use crate::model::cursor::Cursor;
+use crate::primitives::word_navigation::{find_word_end, find_word_start, is_word_char};
 use crate::state::EditorState;
 
+/// Most cursors a single operation creates
+pub const MAX_CURSORS: usize = 10_000;
+
 /// Result of attempting to add a cursor
 pub enum AddCursorResult {
     /// Cursor was added successfully
@@ -172,3 +180,269 @@ pub fn add_cursor_below(state: &mut EditorState) -> AddCursorResult {
         }
     }
 }
+
+/// A cursor selecting `range`, at its start if `backward`; a plain cursor
+/// if the range is empty
+fn selection(range: Range<usize>, backward: bool) -> Cursor {
+    if range.is_empty() {
+        Cursor::new(range.start)
+    } else if backward {
+        let mut cursor = Cursor::new(range.start);
+        cursor.set_anchor(range.end);
+        cursor
+    } else {
+        Cursor::with_selection(range.start, range.end)
+    }
+}
+
+/// Whether the byte at `pos` is a word character
+fn is_word_at(state: &EditorState, pos: usize) -> bool {
+    state
+        .buffer
+        .slice_bytes(pos..(pos + 1).min(state.buffer.len()))
+        .first()
+        .is_some_and(|&b| is_word_char(b))
+}
+
+/// The word under or just before `pos`
+fn word_at(state: &EditorState, pos: usize) -> Option<Range<usize>> {
+    let inside = if is_word_at(state, pos) {
+        pos
+    } else if pos > 0 && is_word_at(state, pos - 1) {
+        pos - 1
+    } else {
+        return None;
+    };
+    Some(find_word_start(&state.buffer, inside)..find_word_end(&state.buffer, inside))
+}
+
+/// Select every occurrence of the primary selection, or of the whole word
+/// under the primary cursor
+///
+/// Returns the selections in document order, facing the same way as the
+/// primary selection.
+pub fn select_all_matches(state: &mut EditorState) -> Result<Vec<Cursor>, String> {
+    let primary = *state.cursors.primary();
+    let (range, whole_word) = match primary.selection_range() {
+        Some(range) => (range, false),
+        None => match word_at(state, primary.position) {
+            Some(range) => (range, true),
+            None => return Err("No selection or word to match".to_string()),
+        },
+    };
+    let backward = primary.anchor.is_some() && primary.position == range.start;
+    let pattern = state.get_text_range(range.start, range.end);
+
+    let len = state.buffer.len();
+    let mut cursors = Vec::new();
+    let mut from = 0;
+    while cursors.len() < MAX_CURSORS {
+        let Some(start) = state
+            .buffer
+            .find_next_in_range(&pattern, from, Some(from..len))
+        else {
+            break;
+        };
+        let end = start + pattern.len();
+        let bounded = !whole_word
+            || ((start == 0 || !is_word_at(state, start - 1)) && !is_word_at(state, end));
+        if bounded {
+            cursors.push(selection(start..end, backward));
+        }
+        from = end;
+    }
+    Ok(cursors)
+}
+
+/// Move the primary selection on to the next occurrence of its text that
+/// no other cursor has selected, wrapping at the end of the buffer
+pub fn skip_to_next_match(state: &mut EditorState) -> Result<Cursor, String> {
+    let primary = *state.cursors.primary();
+    let Some(range) = primary.selection_range() else {
+        return Err("No selection to match".to_string());
+    };
+    let backward = primary.position == range.start;
+    let pattern = state.get_text_range(range.start, range.end);
+    let taken: Vec<Range<usize>> = state
+        .cursors
+        .iter()
+        .filter_map(|(_, cursor)| cursor.selection_range())
+        .collect();
+
+    let mut from = range.end;
+    let mut wrapped = false;
+    while let Some(start) = state.buffer.find_next(&pattern, from) {
+        wrapped |= start < from;
+        if wrapped && start >= range.start {
+            // Back round to the primary selection
+            break;
+        }
+        let found = start..start + pattern.len();
+        if !taken.contains(&found) {
+            return Ok(selection(found, backward));
+        }
+        from = found.end;
+    }
+    Err("No more matches".to_string())
+}
+
+/// Split each selection into a selection of each line it covers; cursors
+/// without a selection stay as they are
+pub fn split_selections_into_lines(state: &mut EditorState) -> Result<Vec<Cursor>, String> {
+    let mut cursors: Vec<Cursor> = state.cursors.iter().map(|(_, cursor)| *cursor).collect();
+    if cursors
+        .iter()
+        .all(|cursor| cursor.selection_range().is_none())
+    {
+        return Err("No selection to split".to_string());
+    }
+    cursors.sort_by_key(Cursor::selection_start);
+
+    let mut split = Vec::new();
+    for cursor in cursors {
+        let Some(range) = cursor.selection_range() else {
+            split.push(cursor);
+            continue;
+        };
+        let backward = cursor.position == range.start;
+        let text = state.get_text_range(range.start, range.end);
+        split.extend(
+            line_pieces(&text, range.start)
+                .into_iter()
+                .map(|piece| selection(piece, backward)),
+        );
+    }
+    split.truncate(MAX_CURSORS);
+    Ok(split)
+}
+
+/// The range of each line of `text`, which starts at `offset`, without its
+/// line ending. A last line the text only reaches the start of is left out.
+fn line_pieces(text: &str, offset: usize) -> Vec<Range<usize>> {
+    let mut pieces = Vec::new();
+    let mut start = offset;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        pieces.push(start..start + content.len());
+        start += line.len();
+    }
+    pieces
+}
+
+/// Select every match of `pattern` inside the selections, or in the whole
+/// buffer if nothing is selected; an empty match gets a plain cursor
+pub fn regex_match_cursors(state: &mut EditorState, pattern: &str) -> Result<Vec<Cursor>, String> {
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+    let mut ranges: Vec<Range<usize>> = state
+        .cursors
+        .iter()
+        .filter_map(|(_, cursor)| cursor.selection_range())
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    if ranges.is_empty() {
+        ranges.push(0..state.buffer.len());
+    }
+
+    let mut cursors = Vec::new();
+    for range in ranges {
+        let bytes = state
+            .buffer
+            .get_text_range_mut(range.start, range.len())
+            .map_err(|e| e.to_string())?;
+        cursors.extend(regex.find_iter(&bytes).map(|found| {
+            selection(
+                range.start + found.start()..range.start + found.end(),
+                false,
+            )
+        }));
+    }
+    if cursors.is_empty() {
+        return Err(format!("No matches for '{}'", pattern));
+    }
+    cursors.truncate(MAX_CURSORS);
+    Ok(cursors)
+}
+
+/// Numbers to insert at the cursors, in document order: `start`, then one
+/// `step` on for each cursor after the first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberSequence {
+    pub start: i64,
+    pub step: i64,
+    /// Digits to pad the numbers to with zeros
+    pub width: usize,
+}
+
+impl NumberSequence {
+    /// Parse `start [step]`, where a start written with leading zeros, like
+    /// `001`, pads every number to as many digits
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let parse = |text: &str| {
+            text.parse::<i64>()
+                .map_err(|_| format!("Not a number: '{}'", text))
+        };
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let (start_text, step) = match words[..] {
+            [] => ("1", 1),
+            [start] => (start, 1),
+            [start, step] => (start, parse(step)?),
+            _ => return Err("Expected a start and an optional step".to_string()),
+        };
+        let start = parse(start_text)?;
+        let digits = start_text.trim_start_matches(['-', '+']);
+        let width = if digits.len() > 1 && digits.starts_with('0') {
+            digits.len()
+        } else {
+            0
+        };
+        Ok(Self { start, step, width })
+    }
+
+    /// The number for the cursor at `index`
+    pub fn nth(&self, index: usize) -> String {
+        let value = self
+            .start
+            .saturating_add(self.step.saturating_mul(index as i64));
+        let digits = format!("{:0width$}", value.unsigned_abs(), width = self.width);
+        if value < 0 {
+            format!("-{}", digits)
+        } else {
+            digits
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_pieces() {
+        assert_eq!(line_pieces("ab\ncd\n", 10), vec![10..12, 13..15]);
+        assert_eq!(line_pieces("ab\r\n\ncd", 0), vec![0..2, 4..4, 5..7]);
+        assert_eq!(line_pieces("abc", 3), vec![3..6]);
+    }
+
+    #[test]
+    fn test_number_sequence() {
+        let plain = NumberSequence::parse("1").unwrap();
+        assert_eq!(
+            (0..3).map(|i| plain.nth(i)).collect::<Vec<_>>(),
+            ["1", "2", "3"]
+        );
+        assert_eq!(NumberSequence::parse("  ").unwrap(), plain);
+
+        let padded = NumberSequence::parse("08 -3").unwrap();
+        assert_eq!(padded.width, 2);
+        assert_eq!(
+            (0..5).map(|i| padded.nth(i)).collect::<Vec<_>>(),
+            ["08", "05", "02", "-01", "-04"]
+        );
+        assert_eq!(NumberSequence::parse("0").unwrap().width, 0);
+        assert_eq!(NumberSequence::parse("-007 10").unwrap().nth(1), "003");
+
+        assert!(NumberSequence::parse("x").is_err());
+        assert!(NumberSequence::parse("1 x").is_err());
+        assert!(NumberSequence::parse("1 2 3").is_err());
+    }
+}
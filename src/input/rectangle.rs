@@ -0,0 +1,421 @@
+//! Rectangles: the same columns of a run of lines
+//!
+//! A rectangle covers display columns `left..right` of lines `top..=bottom`,
+//! whether the text of a line reaches those columns or not. It is either a
+//! block selection, or the rectangle with the ends of a selection at its
+//! corners. Edits that put text past the end of a line pad the line with
+//! spaces first, so rectangles over short and empty lines can be typed
+//! into, filled and yanked like any other.
+
+use std::ops::{Range, RangeInclusive};
+
+use crate::model::cursor::{Cursor, SelectionMode};
+use crate::model::event::{CursorId, Event};
+use crate::primitives::display_width::{byte_offset_at_visual_column, str_width};
+use crate::state::EditorState;
+
+/// Display columns `left..right` of lines `top..=bottom`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rectangle {
+    pub top: usize,
+    pub bottom: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Rectangle {
+    /// The rectangle with opposite corners at two (line, column) points
+    pub fn from_corners(a: (usize, usize), b: (usize, usize)) -> Self {
+        Self {
+            top: a.0.min(b.0),
+            bottom: a.0.max(b.0),
+            left: a.1.min(b.1),
+            right: a.1.max(b.1),
+        }
+    }
+
+    /// The block selection of `cursor`, if it has one
+    pub fn of_block(state: &EditorState, cursor: &Cursor) -> Option<Self> {
+        if cursor.selection_mode != SelectionMode::Block {
+            return None;
+        }
+        let anchor = cursor.block_anchor?;
+        let line = state.buffer.get_line_number(cursor.position);
+        Some(Self::from_corners(
+            (anchor.line, anchor.column),
+            (line, cursor_column(state, cursor)),
+        ))
+    }
+
+    /// The rectangle `cursor` selects: its block selection, or the one its
+    /// selection spans from corner to corner
+    pub fn of_cursor(state: &EditorState, cursor: &Cursor) -> Option<Self> {
+        if cursor.selection_mode == SelectionMode::Block {
+            return Self::of_block(state, cursor);
+        }
+        let anchor = cursor.anchor.filter(|&anchor| anchor != cursor.position)?;
+        Some(Self::from_corners(
+            (
+                state.buffer.get_line_number(anchor),
+                column_at(state, anchor),
+            ),
+            (
+                state.buffer.get_line_number(cursor.position),
+                cursor_column(state, cursor),
+            ),
+        ))
+    }
+
+    pub fn lines(&self) -> RangeInclusive<usize> {
+        self.top..=self.bottom
+    }
+
+    pub fn width(&self) -> usize {
+        self.right - self.left
+    }
+}
+
+/// Replacement of display columns `left..right` of `line` with `text`
+///
+/// A line shorter than `left` is padded with spaces before `text`; a line
+/// past the end of the buffer is added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnEdit {
+    pub line: usize,
+    pub left: usize,
+    pub right: usize,
+    pub text: String,
+}
+
+/// Start offset of `line`, and its text without the line ending
+pub fn line_text(state: &EditorState, line: usize) -> Option<(usize, String)> {
+    let start = state.buffer.line_start_offset(line)?;
+    let content = state.buffer.get_line(line)?;
+    let text = String::from_utf8_lossy(&content)
+        .trim_end_matches(['\n', '\r'])
+        .to_string();
+    Some((start, text))
+}
+
+/// Display column of `position` within its line
+pub fn column_at(state: &EditorState, position: usize) -> usize {
+    let line = state.buffer.get_line_number(position);
+    match line_text(state, line) {
+        Some((start, text)) => {
+            let end = position.saturating_sub(start).min(text.len());
+            str_width(text.get(..end).unwrap_or(&text))
+        }
+        None => 0,
+    }
+}
+
+/// Display column of `cursor`, counting the columns it sits past the end of
+/// its line
+pub fn cursor_column(state: &EditorState, cursor: &Cursor) -> usize {
+    column_at(state, cursor.position) + state.virtual_columns(cursor)
+}
+
+/// Bytes of `text` that display columns `left..right` cover, and the spaces
+/// needed after `text` to reach column `left`
+pub fn column_span(text: &str, left: usize, right: usize) -> (Range<usize>, usize) {
+    let start = byte_offset_at_visual_column(text, left);
+    let end = byte_offset_at_visual_column(text, right).max(start);
+    (start..end, left.saturating_sub(str_width(text)))
+}
+
+/// Text of each line of `rect`, padded with spaces to its width
+pub fn extract(state: &EditorState, rect: &Rectangle) -> Vec<String> {
+    rect.lines()
+        .map(|line| {
+            let text = line_text(state, line)
+                .map(|(_, text)| text)
+                .unwrap_or_default();
+            let (range, _) = column_span(&text, rect.left, rect.right);
+            let piece = &text[range];
+            let padding = rect.width().saturating_sub(str_width(piece));
+            format!("{}{}", piece, " ".repeat(padding))
+        })
+        .collect()
+}
+
+/// The rectangles of all block selections
+pub fn block_rectangles(state: &EditorState) -> Vec<(CursorId, Rectangle)> {
+    state
+        .cursors
+        .iter()
+        .filter_map(|(cursor_id, cursor)| Some((cursor_id, Rectangle::of_block(state, cursor)?)))
+        .collect()
+}
+
+/// Events for typing `text` into the block selections: it replaces each
+/// line's columns of the rectangle, and a cursor is left after it on each
+/// line. None without a block selection.
+pub fn type_into_blocks(state: &EditorState, text: &str) -> Option<Vec<Event>> {
+    let column = |rect: &Rectangle| rect.left + str_width(text);
+    block_events(state, |rect| (rect.left, rect.right, column(rect)), text)
+}
+
+/// Events for Backspace or Delete in the block selections: they delete the
+/// rectangle, or the column before or after an empty one, leaving a cursor
+/// on each line. None without a block selection.
+pub fn delete_in_blocks(state: &EditorState, forward: bool) -> Option<Vec<Event>> {
+    block_events(
+        state,
+        |rect| match (rect.width(), forward) {
+            (0, false) => {
+                let left = rect.left.saturating_sub(1);
+                (left, rect.left, left)
+            }
+            (0, true) => (rect.left, rect.left + 1, rect.left),
+            _ => (rect.left, rect.right, rect.left),
+        },
+        "",
+    )
+}
+
+/// Edit of each line of the block selections, from the columns `columns`
+/// gives for its rectangle to replace and the column to leave a cursor at
+fn block_events(
+    state: &EditorState,
+    columns: impl Fn(&Rectangle) -> (usize, usize, usize),
+    text: &str,
+) -> Option<Vec<Event>> {
+    let rects = block_rectangles(state);
+    if rects.is_empty() {
+        return None;
+    }
+    let mut edits: Vec<ColumnEdit> = Vec::new();
+    let mut cursors = Vec::new();
+    for (_, rect) in &rects {
+        let (left, right, column) = columns(rect);
+        for line in rect.lines() {
+            if edits.iter().any(|edit| edit.line == line) {
+                continue;
+            }
+            edits.push(ColumnEdit {
+                line,
+                left,
+                right,
+                text: text.to_string(),
+            });
+            cursors.push((line, column));
+        }
+    }
+    edits.sort_by_key(|edit| edit.line);
+    cursors.sort();
+
+    let primary_line = state
+        .buffer
+        .get_line_number(state.cursors.primary().position);
+    let primary = cursors
+        .iter()
+        .position(|&(line, _)| line == primary_line)
+        .unwrap_or(0);
+    Some(edit_events(state, &edits, &cursors, primary))
+}
+
+/// Events making `edits`, given in line order with one per line, then
+/// leaving a cursor at each (line, column) of `cursors` in the edited text,
+/// with `cursors[primary]` the primary cursor
+///
+/// A cursor past the end of its line sits there in virtual space, if that
+/// is on, and at the end of the line if not.
+pub fn edit_events(
+    state: &EditorState,
+    edits: &[ColumnEdit],
+    cursors: &[(usize, usize)],
+    primary: usize,
+) -> Vec<Event> {
+    let primary_id = state.cursors.primary_id();
+    let old = *state.cursors.primary();
+    let buffer_len = state.buffer.len();
+    let line_ending = state.buffer.line_ending().as_str();
+
+    // Each edit as a replaced byte range, with where its line starts and
+    // what it holds afterwards
+    struct Resolved {
+        line: usize,
+        range: Range<usize>,
+        text: String,
+        new_start: usize,
+        new_line: String,
+    }
+    let mut resolved: Vec<Resolved> = Vec::new();
+    let mut shift: isize = 0;
+    for edit in edits {
+        let (range, text, new_line, line_start) = match line_text(state, edit.line) {
+            Some((start, line)) => {
+                let (span, padding) = column_span(&line, edit.left, edit.right);
+                let text = if edit.text.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}{}", " ".repeat(padding), edit.text)
+                };
+                let new_line = format!("{}{}{}", &line[..span.start], text, &line[span.end..]);
+                (start + span.start..start + span.end, text, new_line, start)
+            }
+            None => {
+                let new_line = format!("{}{}", " ".repeat(edit.left), edit.text);
+                let text = format!("{}{}", line_ending, new_line);
+                (
+                    buffer_len..buffer_len,
+                    text,
+                    new_line,
+                    buffer_len + line_ending.len(),
+                )
+            }
+        };
+        resolved.push(Resolved {
+            line: edit.line,
+            new_start: (line_start as isize + shift) as usize,
+            range: range.clone(),
+            new_line,
+            text: text.clone(),
+        });
+        shift += text.len() as isize - range.len() as isize;
+    }
+
+    // Where each cursor goes, and the sticky column keeping it in virtual
+    // space past the end of its line
+    let targets: Vec<(usize, usize)> = cursors
+        .iter()
+        .map(|&(line, column)| {
+            let (start, text) = match resolved.iter().find(|edit| edit.line == line) {
+                Some(edit) => (edit.new_start, edit.new_line.clone()),
+                None => match line_text(state, line) {
+                    Some((start, text)) => {
+                        let shift: isize = resolved
+                            .iter()
+                            .filter(|edit| edit.line < line)
+                            .map(|edit| edit.text.len() as isize - edit.range.len() as isize)
+                            .sum();
+                        ((start as isize + shift) as usize, text)
+                    }
+                    None => ((buffer_len as isize + shift) as usize, String::new()),
+                },
+            };
+            let position = start + byte_offset_at_visual_column(&text, column);
+            let sticky = if column > str_width(&text) { column } else { 0 };
+            (position, sticky)
+        })
+        .collect();
+
+    let mut events = Vec::new();
+    for (cursor_id, cursor) in state.cursors.iter() {
+        if cursor_id != primary_id {
+            events.push(Event::RemoveCursor {
+                cursor_id,
+                position: cursor.position,
+                anchor: cursor.anchor,
+            });
+        }
+    }
+    events.push(Event::MoveCursor {
+        cursor_id: primary_id,
+        old_position: old.position,
+        new_position: old.position,
+        old_anchor: old.anchor,
+        new_anchor: None,
+        old_sticky_column: old.sticky_column,
+        new_sticky_column: 0,
+    });
+
+    // Bottom up, so each edit's offsets still hold when it is made; the
+    // primary cursor ends up after the topmost one
+    let mut position = old.position;
+    for edit in resolved.iter().rev() {
+        if !edit.range.is_empty() {
+            events.push(Event::Delete {
+                range: edit.range.clone(),
+                deleted_text: String::from_utf8_lossy(
+                    &state.buffer.slice_bytes(edit.range.clone()),
+                )
+                .into_owned(),
+                cursor_id: primary_id,
+            });
+            position = edit.range.start;
+        }
+        if !edit.text.is_empty() {
+            events.push(Event::Insert {
+                position: edit.range.start,
+                text: edit.text.clone(),
+                cursor_id: primary_id,
+            });
+            position = edit.range.start + edit.text.len();
+        }
+    }
+    if targets.is_empty() {
+        return events;
+    }
+
+    // The primary cursor takes the place of another cursor, if any, so the
+    // new primary can be added last and become primary
+    let reused = (0..targets.len())
+        .find(|&index| index != primary)
+        .unwrap_or(primary);
+    events.push(Event::MoveCursor {
+        cursor_id: primary_id,
+        old_position: position,
+        new_position: targets[reused].0,
+        old_anchor: None,
+        new_anchor: None,
+        old_sticky_column: 0,
+        new_sticky_column: targets[reused].1,
+    });
+    let next_id = state.cursors.next_id().0;
+    let added = (0..targets.len())
+        .filter(|&index| index != reused && index != primary)
+        .chain((primary != reused).then_some(primary));
+    for (offset, index) in added.enumerate() {
+        let cursor_id = CursorId(next_id + offset);
+        let (position, sticky) = targets[index];
+        events.push(Event::AddCursor {
+            cursor_id,
+            position,
+            anchor: None,
+        });
+        if sticky > 0 {
+            events.push(Event::MoveCursor {
+                cursor_id,
+                old_position: position,
+                new_position: position,
+                old_anchor: None,
+                new_anchor: None,
+                old_sticky_column: 0,
+                new_sticky_column: sticky,
+            });
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_span() {
+        assert_eq!(column_span("abcdef", 1, 3), (1..3, 0));
+        // Columns past the end cover nothing, and need padding to reach
+        assert_eq!(column_span("ab", 1, 5), (1..2, 0));
+        assert_eq!(column_span("ab", 4, 6), (2..2, 2));
+        // A wide character counts two columns
+        assert_eq!(column_span("月x", 2, 3), (3..4, 0));
+    }
+
+    #[test]
+    fn test_from_corners() {
+        let rect = Rectangle::from_corners((4, 2), (1, 7));
+        assert_eq!(
+            rect,
+            Rectangle {
+                top: 1,
+                bottom: 4,
+                left: 2,
+                right: 7
+            }
+        );
+        assert_eq!(rect.width(), 5);
+        assert_eq!(rect.lines().count(), 4);
+    }
+}
@@ -0,0 +1,463 @@
+//! User-defined snippets
+//!
+//! Snippets are read from VS Code-compatible JSON files in the user config
+//! directory (`snippets/`) and in the project (`.fresh/snippets/`):
+//!
+//! - `<language>.json` holds snippets for one language, e.g. `rust.json`
+//! - `*.code-snippets` holds snippets for the languages listed in each
+//!   snippet's `scope`, or for every language when it has none
+//!
+//! Each file maps a snippet name to `{ "prefix", "body", "description" }`,
+//! where `prefix` and `body` may be a string or an array of strings (body
+//! lines). Files may contain comments, like VS Code's.
+//!
+//! [`SnippetSession`] tracks the fields of an inserted snippet while you tab
+//! through them, keeping their ranges in step with edits.
+
+use crate::model::event::BufferId;
+use crate::primitives::snippet::{Expansion, Transform};
+use serde::Deserialize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A snippet definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    pub name: String,
+    /// Words that offer the snippet in the completion popup
+    pub prefixes: Vec<String>,
+    pub body: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnippetDefinition {
+    #[serde(default)]
+    prefix: Option<OneOrMany>,
+    body: OneOrMany,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+/// Load the snippets for `language` from each directory in turn
+///
+/// A snippet in a later directory replaces one with the same name from an
+/// earlier directory, so project snippets override user snippets. Snippets
+/// are sorted by name.
+pub fn load_snippets(dirs: &[PathBuf], language: Option<&str>) -> Vec<Snippet> {
+    let mut snippets: Vec<Snippet> = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        paths.sort();
+
+        for path in paths {
+            let check_scope = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("code-snippets") => true,
+                Some("json") if path.file_stem().and_then(|stem| stem.to_str()) == language => {
+                    false
+                }
+                _ => continue,
+            };
+            for snippet in load_file(&path, check_scope, language) {
+                snippets.retain(|existing| existing.name != snippet.name);
+                snippets.push(snippet);
+            }
+        }
+    }
+    snippets.sort_by(|a, b| a.name.cmp(&b.name));
+    snippets
+}
+
+/// Read one snippet file, keeping snippets whose scope matches `language`
+fn load_file(path: &Path, check_scope: bool, language: Option<&str>) -> Vec<Snippet> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            tracing::warn!("Failed to read snippets from {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    let definitions: Vec<(String, SnippetDefinition)> = match serde_json::from_str::<
+        serde_json::Map<String, serde_json::Value>,
+    >(&strip_json_comments(&contents))
+    {
+        Ok(map) => map
+            .into_iter()
+            .filter_map(|(name, value)| match serde_json::from_value(value) {
+                Ok(definition) => Some((name, definition)),
+                Err(e) => {
+                    tracing::warn!("Skipping snippet '{}' in {}: {}", name, path.display(), e);
+                    None
+                }
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to parse snippets from {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+
+    definitions
+        .into_iter()
+        .filter(|(_, definition)| {
+            !check_scope
+                || definition.scope.as_deref().is_none_or(|scope| {
+                    scope.split(',').any(|scope| Some(scope.trim()) == language)
+                })
+        })
+        .map(|(name, definition)| Snippet {
+            name,
+            prefixes: definition
+                .prefix
+                .map(OneOrMany::into_vec)
+                .unwrap_or_default(),
+            body: definition.body.into_vec().join("\n"),
+            description: definition.description,
+        })
+        .collect()
+}
+
+/// Remove `//` and `/* */` comments outside of strings
+fn strip_json_comments(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// One field of an inserted snippet, with its range in the buffer
+#[derive(Debug, Clone)]
+pub struct SnippetField {
+    pub index: u32,
+    pub range: Range<usize>,
+    pub choices: Vec<String>,
+    pub is_mirror: bool,
+    pub transform: Option<Transform>,
+    /// Positions in the session's fields of the fields nested in this one
+    pub nested: Range<usize>,
+}
+
+/// An inserted snippet whose fields are being tabbed through
+#[derive(Debug, Clone)]
+pub struct SnippetSession {
+    pub buffer_id: BufferId,
+    fields: Vec<SnippetField>,
+    /// Tabstop numbers in navigation order, ending with 0
+    order: Vec<u32>,
+    /// Position in `order` of the current tabstop
+    current: usize,
+    /// Field (position in `fields`) that edits are expected in: the current
+    /// field while typing, or a mirror while it is being updated
+    editing: Option<usize>,
+}
+
+impl SnippetSession {
+    /// Start a session for `expansion` inserted at `offset`
+    pub fn new(buffer_id: BufferId, expansion: &Expansion, offset: usize) -> Self {
+        let fields = expansion
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| SnippetField {
+                index: field.index,
+                range: field.range.start + offset..field.range.end + offset,
+                choices: field.choices.clone(),
+                is_mirror: field.is_mirror,
+                transform: expansion.transform(i).cloned(),
+                nested: field.nested.clone(),
+            })
+            .collect();
+        let mut session = Self {
+            buffer_id,
+            fields,
+            order: expansion.tab_order(),
+            current: 0,
+            editing: None,
+        };
+        session.editing = session.current_field_position();
+        session
+    }
+
+    /// All fields, in the order they appear in the snippet
+    pub fn fields(&self) -> &[SnippetField] {
+        &self.fields
+    }
+
+    /// Tabstop number of the current field
+    pub fn current_index(&self) -> u32 {
+        self.order[self.current]
+    }
+
+    /// Whether the current field is the final cursor position (`$0`)
+    pub fn at_final(&self) -> bool {
+        self.current_index() == 0
+    }
+
+    fn current_field_position(&self) -> Option<usize> {
+        let index = self.current_index();
+        self.fields
+            .iter()
+            .position(|field| field.index == index && !field.is_mirror)
+            .or_else(|| self.fields.iter().position(|field| field.index == index))
+    }
+
+    /// The field the cursor is in
+    pub fn current_field(&self) -> Option<&SnippetField> {
+        self.current_field_position().map(|i| &self.fields[i])
+    }
+
+    /// Move to the next (or previous) tabstop
+    ///
+    /// Returns false when already at the first tabstop and moving backward.
+    pub fn advance(&mut self, forward: bool) -> bool {
+        if forward {
+            if self.current + 1 >= self.order.len() {
+                return false;
+            }
+            self.current += 1;
+        } else {
+            if self.current == 0 {
+                return false;
+            }
+            self.current -= 1;
+        }
+        self.editing = self.current_field_position();
+        true
+    }
+
+    /// Range spanned by all fields
+    pub fn extent(&self) -> Range<usize> {
+        let start = self.fields.iter().map(|f| f.range.start).min().unwrap_or(0);
+        let end = self.fields.iter().map(|f| f.range.end).max().unwrap_or(0);
+        start..end
+    }
+
+    /// Mirrors of the current tabstop, with the text each one should show
+    pub fn mirror_updates(&self, current_text: &str) -> Vec<(usize, String)> {
+        let index = self.current_index();
+        let Some(primary) = self.current_field_position() else {
+            return Vec::new();
+        };
+        self.fields
+            .iter()
+            .enumerate()
+            .filter(|(i, field)| *i != primary && field.index == index)
+            .map(|(i, field)| {
+                let text = match &field.transform {
+                    Some(transform) => transform.apply(current_text),
+                    None => current_text.to_string(),
+                };
+                (i, text)
+            })
+            .collect()
+    }
+
+    /// Expect the following edits in field `field` (a position in `fields`),
+    /// or in the current field when `None`
+    pub fn set_editing(&mut self, field: Option<usize>) {
+        self.editing = field.or_else(|| self.current_field_position());
+    }
+
+    /// Keep field ranges in step with an insertion
+    ///
+    /// The field being edited and the fields containing it grow. An empty
+    /// field exactly at the insertion point stays put if it comes before the
+    /// edited field in the snippet, and moves along otherwise.
+    pub fn adjust_for_insert(&mut self, position: usize, len: usize) {
+        let editing = self.editing;
+        for (i, field) in self.fields.iter_mut().enumerate() {
+            let contains_edit =
+                Some(i) == editing || editing.is_some_and(|e| field.nested.contains(&e));
+            let range = &mut field.range;
+            let grows = if contains_edit {
+                range.start <= position && position <= range.end
+            } else {
+                range.start < position && position < range.end
+            };
+            if grows {
+                range.end += len;
+            } else if position < range.start
+                || (position == range.start
+                    && (range.start != range.end || editing.is_none_or(|e| i > e)))
+            {
+                range.start += len;
+                range.end += len;
+            }
+        }
+    }
+
+    /// Keep field ranges in step with a deletion
+    pub fn adjust_for_delete(&mut self, deleted: Range<usize>) {
+        let map = |offset: usize| {
+            if offset <= deleted.start {
+                offset
+            } else if offset < deleted.end {
+                deleted.start
+            } else {
+                offset - deleted.len()
+            }
+        };
+        for field in &mut self.fields {
+            field.range = map(field.range.start)..map(field.range.end);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::snippet::SnippetBody;
+
+    fn session(body: &str) -> SnippetSession {
+        let expansion = SnippetBody::parse(body).expand(&|_| None, "\n");
+        SnippetSession::new(BufferId(0), &expansion, 10)
+    }
+
+    fn ranges(session: &SnippetSession) -> Vec<Range<usize>> {
+        session.fields().iter().map(|f| f.range.clone()).collect()
+    }
+
+    #[test]
+    fn test_typing_grows_current_field() {
+        // "(" $1 ")" $0
+        let mut s = session("($1)$0");
+        s.adjust_for_insert(11, 3);
+        assert_eq!(ranges(&s), vec![11..14, 15..15]);
+
+        // Replacing a placeholder: delete it, then type at its start
+        let mut s = session("${1:ab}${2:cd}");
+        s.adjust_for_delete(10..12);
+        s.adjust_for_insert(10, 1);
+        assert_eq!(ranges(&s)[..2], [10..11, 11..13]);
+    }
+
+    #[test]
+    fn test_empty_field_before_edited_one_stays() {
+        let mut s = session("$2$1");
+        assert_eq!(s.current_index(), 1);
+        s.adjust_for_insert(10, 2);
+        assert_eq!(ranges(&s)[..2], [10..10, 10..12]);
+    }
+
+    #[test]
+    fn test_outer_placeholder_grows_with_nested_field() {
+        let mut s = session("${1:a ${2:b}}");
+        assert!(s.advance(true));
+        assert_eq!(s.current_index(), 2);
+        s.adjust_for_insert(13, 1);
+        // The nested field comes first
+        assert_eq!(ranges(&s)[..2], [12..14, 10..14]);
+    }
+
+    #[test]
+    fn test_navigation_and_mirrors() {
+        let mut s = session("${1:x} ${1/(.*)/${1:/upcase}/}$0");
+        assert_eq!(s.mirror_updates("ab"), vec![(1, "AB".to_string())]);
+        assert!(!s.advance(false));
+        assert!(s.advance(true));
+        assert!(s.at_final());
+        assert!(!s.advance(true));
+    }
+
+    #[test]
+    fn test_strip_json_comments() {
+        let json = "{\n  // note\n  \"a\": \"x // y\", /* block */ \"b\": 1\n}";
+        let value: serde_json::Value = serde_json::from_str(&strip_json_comments(json)).unwrap();
+        assert_eq!(value["a"], "x // y");
+        assert_eq!(value["b"], 1);
+    }
+
+    #[test]
+    fn test_load_snippets_by_language_and_scope() {
+        let user = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            user.path().join("rust.json"),
+            r##"{ "Main": { "prefix": "main", "body": ["fn main() {", "\t$0", "}"] },
+                 "Test": { "prefix": ["test", "tst"], "body": "#[test]" } }"##,
+        )
+        .unwrap();
+        std::fs::write(
+            user.path().join("python.json"),
+            r#"{ "Def": { "prefix": "def", "body": "def $1():" } }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            project.path().join("shared.code-snippets"),
+            r#"{ "Main": { "prefix": "main", "body": "project main", "scope": "rust,go" },
+                 "Py": { "prefix": "py", "body": "x", "scope": "python" },
+                 "Any": { "prefix": "any", "body": "y" } }"#,
+        )
+        .unwrap();
+
+        let dirs = [user.path().to_path_buf(), project.path().to_path_buf()];
+        let snippets = load_snippets(&dirs, Some("rust"));
+        let names: Vec<_> = snippets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Any", "Main", "Test"]);
+        assert_eq!(snippets[1].body, "project main");
+        assert_eq!(snippets[2].prefixes, vec!["test", "tst"]);
+
+        let names: Vec<_> = load_snippets(&dirs, None)
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["Any"]);
+    }
+}
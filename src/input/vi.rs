@@ -0,0 +1,1604 @@
+//! Vi-style modal editing
+//!
+//! The keybinding resolver maps each chord to a single `Action`, which can't
+//! express vi's grammar of counts, operators and motions. While the `vi` keymap
+//! is active, `ViState` sits in front of it and parses key sequences such as
+//! `d2w`, `ci"` or `>ip` into a `ViCommand`. The editor runs each command as
+//! `Action::Vi`, so it is undoable, applies to every cursor and is recorded in
+//! macros like any other action. In insert mode keys fall through to the
+//! regular keymap, which the `vi` keymap inherits from `default`.
+//!
+//! The motion and text object functions at the bottom of this module work on
+//! byte offsets in a `Buffer`; the editor turns their results into events.
+
+use crate::input::keybindings::Action;
+use crate::model::buffer::Buffer;
+use crate::primitives::brackets::{self, find_close, find_open};
+use crate::primitives::word_navigation::is_word_char;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Largest count accepted (`99999dd` shouldn't loop for ages)
+const MAX_COUNT: usize = 9999;
+
+/// How far word motions look around the cursor
+const SCAN_WINDOW: usize = 64 * 1024;
+
+/// Vi editing mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViMode {
+    Normal,
+    Insert,
+    Visual,
+    VisualLine,
+    VisualBlock,
+    /// An operator was typed and waits for its motion (`d` in `dw`)
+    OperatorPending,
+}
+
+impl ViMode {
+    /// Mode indicator shown in the status bar
+    pub fn label(&self) -> &'static str {
+        match self {
+            ViMode::Normal => "NORMAL",
+            ViMode::Insert => "INSERT",
+            ViMode::Visual => "VISUAL",
+            ViMode::VisualLine => "V-LINE",
+            ViMode::VisualBlock => "V-BLOCK",
+            ViMode::OperatorPending => "O-PENDING",
+        }
+    }
+
+    /// Terminal cursor shape for this mode
+    pub fn cursor_shape(&self) -> CursorShape {
+        match self {
+            ViMode::Insert => CursorShape::Bar,
+            ViMode::OperatorPending => CursorShape::Underline,
+            _ => CursorShape::Block,
+        }
+    }
+
+    pub fn is_visual(&self) -> bool {
+        matches!(
+            self,
+            ViMode::Visual | ViMode::VisualLine | ViMode::VisualBlock
+        )
+    }
+}
+
+/// Shape of the terminal cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorShape {
+    Block,
+    Bar,
+    Underline,
+}
+
+/// Operators that act on the text covered by a motion or text object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operator {
+    Delete,
+    Change,
+    Yank,
+    Indent,
+    Dedent,
+}
+
+/// Cursor motions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// `w` / `W`
+    WordForward {
+        big: bool,
+    },
+    /// `b` / `B`
+    WordBackward {
+        big: bool,
+    },
+    /// `e` / `E`
+    WordEnd {
+        big: bool,
+    },
+    /// `0`
+    LineStart,
+    /// `^`
+    FirstNonBlank,
+    /// `$`
+    LineEnd,
+    /// `gg` / `G`: the given line (1-based), or the last line
+    GotoLine(Option<usize>),
+    /// `}`
+    ParagraphForward,
+    /// `{`
+    ParagraphBackward,
+    /// `f`, `F`, `t` and `T`: a character on the current line
+    FindChar {
+        ch: char,
+        forward: bool,
+        till: bool,
+    },
+    /// `%`
+    MatchingBracket,
+}
+
+impl Motion {
+    /// Whether an operator with this motion covers whole lines (`dj`, `yG`)
+    pub fn is_linewise(&self) -> bool {
+        matches!(self, Motion::Up | Motion::Down | Motion::GotoLine(_))
+    }
+
+    /// Whether an operator includes the character the motion lands on (`de`, `d$`, `dfx`)
+    pub fn is_inclusive(&self) -> bool {
+        matches!(
+            self,
+            Motion::WordEnd { .. }
+                | Motion::LineEnd
+                | Motion::MatchingBracket
+                | Motion::FindChar { forward: true, .. }
+        )
+    }
+}
+
+/// Text objects, typed after `i` (inner) or `a` (around) in operator-pending
+/// and visual mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextObject {
+    /// `iw` / `aw` / `iW` / `aW`
+    Word { inner: bool, big: bool },
+    /// `i"` / `a'` / ...
+    Quote { inner: bool, quote: char },
+    /// `i(` / `a{` / `ib` / `aB` / ...
+    Bracket {
+        inner: bool,
+        open: char,
+        close: char,
+    },
+    /// `ip` / `ap`
+    Paragraph { inner: bool },
+    /// A tree-sitter text object (`if` is "function.inner", `ac` is "class")
+    Syntax(String),
+}
+
+/// What an operator applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Target {
+    Motion(Motion),
+    /// Whole lines: the doubled operators `dd`, `cc`, `yy`, `>>`, `<<`
+    Lines,
+    TextObject(TextObject),
+}
+
+/// Where `i`, `a`, `I`, `A`, `o` and `O` start inserting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InsertAt {
+    Cursor,
+    AfterCursor,
+    LineStart,
+    LineEnd,
+    LineBelow,
+    LineAbove,
+}
+
+/// A complete vi command, run by the editor as `Action::Vi`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViCommand {
+    Move {
+        motion: Motion,
+        count: usize,
+    },
+    Operate {
+        operator: Operator,
+        target: Target,
+        count: usize,
+        register: Option<char>,
+    },
+    /// Apply an operator to the visual selection
+    OperateSelection {
+        operator: Operator,
+        register: Option<char>,
+    },
+    /// Extend the visual selection over a text object
+    SelectTextObject(TextObject),
+    /// Move to the other end of the visual selection
+    SwapSelectionEnds,
+    Insert(InsertAt),
+    /// `I` / `A` in visual block mode: one cursor per line of the block
+    BlockInsert {
+        append: bool,
+    },
+    SetMode(ViMode),
+    Put {
+        before: bool,
+        register: Option<char>,
+        count: usize,
+    },
+    ReplaceChar {
+        ch: char,
+        count: usize,
+    },
+    JoinLines {
+        count: usize,
+    },
+}
+
+impl ViCommand {
+    /// Whether the command modifies text (and is repeated by `.`)
+    pub fn is_change(&self) -> bool {
+        match self {
+            ViCommand::Operate { operator, .. } | ViCommand::OperateSelection { operator, .. } => {
+                *operator != Operator::Yank
+            }
+            ViCommand::Insert(_)
+            | ViCommand::BlockInsert { .. }
+            | ViCommand::Put { .. }
+            | ViCommand::ReplaceChar { .. }
+            | ViCommand::JoinLines { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the command leaves the editor in insert mode
+    fn enters_insert(&self) -> bool {
+        match self {
+            ViCommand::Operate { operator, .. } | ViCommand::OperateSelection { operator, .. } => {
+                *operator == Operator::Change
+            }
+            ViCommand::Insert(_) | ViCommand::BlockInsert { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+/// Result of feeding one key to `ViState`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViInput {
+    /// The key was consumed; the command isn't complete (or was cancelled)
+    Pending,
+    /// Not a vi key; handle it with the regular keymap
+    Unhandled,
+    Command(ViCommand),
+    /// Plain editor actions (`u`, `n`, `/`, ...)
+    Actions(Vec<Action>),
+    /// `.`: replay these keys
+    Repeat(Vec<KeyEvent>),
+}
+
+/// Text held in a register
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Register {
+    pub text: String,
+    /// Whole lines (put above/below the cursor line instead of at the cursor)
+    pub linewise: bool,
+}
+
+/// Keys that need another key to complete them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Pending {
+    #[default]
+    None,
+    /// After `"`
+    Register,
+    /// After `g`
+    G,
+    /// After `z`
+    Z,
+    /// After `f`, `F`, `t` or `T`
+    Find { forward: bool, till: bool },
+    /// After `r`
+    Replace,
+    /// After `i` or `a` in operator-pending or visual mode
+    TextObject { inner: bool },
+}
+
+/// Parser state for vi key sequences, plus the registers
+#[derive(Debug, Clone)]
+pub struct ViState {
+    mode: ViMode,
+    count: Option<usize>,
+    /// Operator waiting for a motion, with the count typed before it
+    operator: Option<(Operator, usize)>,
+    register: Option<char>,
+    pending: Pending,
+    /// Last `f`/`t` search, for `;` and `,`: (character, forward, till)
+    last_find: Option<(char, bool, bool)>,
+    /// Keys of the command being typed
+    keys: Vec<KeyEvent>,
+    /// Keys of the last change, replayed by `.`
+    last_change: Vec<KeyEvent>,
+    /// Whether insert mode keys are part of the change being recorded
+    recording_insert: bool,
+    registers: HashMap<char, Register>,
+}
+
+impl Default for ViState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ViState {
+    pub fn new() -> Self {
+        Self {
+            mode: ViMode::Normal,
+            count: None,
+            operator: None,
+            register: None,
+            pending: Pending::None,
+            last_find: None,
+            keys: Vec::new(),
+            last_change: Vec::new(),
+            recording_insert: false,
+            registers: HashMap::new(),
+        }
+    }
+
+    /// Current mode; operator-pending while an operator waits for its motion
+    pub fn mode(&self) -> ViMode {
+        if self.operator.is_some() {
+            ViMode::OperatorPending
+        } else {
+            self.mode
+        }
+    }
+
+    /// Switch modes (done by the editor when it runs a command)
+    pub fn set_mode(&mut self, mode: ViMode) {
+        self.mode = mode;
+        self.reset();
+    }
+
+    /// Keys typed so far for an incomplete command, for the status bar
+    pub fn pending_keys(&self) -> String {
+        if self.pending == Pending::None && self.operator.is_none() && self.count.is_none() {
+            return String::new();
+        }
+        let start = self
+            .keys
+            .iter()
+            .rposition(|key| key.code == KeyCode::Esc)
+            .map_or(0, |i| i + 1);
+        self.keys[start..]
+            .iter()
+            .filter_map(|key| match key.code {
+                KeyCode::Char(c) => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Contents of a register
+    pub fn register(&self, name: char) -> Option<&Register> {
+        self.registers.get(&name)
+    }
+
+    /// Store deleted or yanked text
+    ///
+    /// Text always goes to the unnamed register `"` as well as the named one,
+    /// and yanks also go to `0`. Uppercase names append to their lowercase
+    /// register; `_` discards the text.
+    pub fn set_register(&mut self, name: Option<char>, register: Register, yank: bool) {
+        match name {
+            Some('_') => return,
+            Some(name) if name.is_ascii_uppercase() => {
+                let existing = self.registers.entry(name.to_ascii_lowercase()).or_default();
+                if existing.linewise && !existing.text.ends_with('\n') {
+                    existing.text.push('\n');
+                }
+                existing.text.push_str(&register.text);
+                existing.linewise |= register.linewise;
+            }
+            Some(name) if name != '"' => {
+                self.registers.insert(name, register.clone());
+            }
+            _ => {}
+        }
+        if yank {
+            self.registers.insert('0', register.clone());
+        }
+        self.registers.insert('"', register);
+    }
+
+    /// Feed one key
+    pub fn handle_key(&mut self, key: KeyEvent) -> ViInput {
+        if self.mode == ViMode::Insert {
+            return self.insert_key(key);
+        }
+
+        // A new command in normal mode starts a new recording for `.`; visual
+        // mode keeps the keys that made the selection
+        if self.mode == ViMode::Normal && self.is_idle() {
+            self.keys.clear();
+        }
+        self.keys.push(key);
+
+        let input = self.command_key(key);
+        match &input {
+            ViInput::Pending => {}
+            ViInput::Command(command) => {
+                if command.is_change() {
+                    if command.enters_insert() {
+                        self.recording_insert = true;
+                    } else {
+                        self.last_change = self.keys.clone();
+                    }
+                }
+                self.reset();
+            }
+            _ => self.reset(),
+        }
+        input
+    }
+
+    fn insert_key(&mut self, key: KeyEvent) -> ViInput {
+        if self.recording_insert {
+            self.keys.push(key);
+        }
+        if key.code == KeyCode::Esc {
+            if self.recording_insert {
+                self.last_change = std::mem::take(&mut self.keys);
+                self.recording_insert = false;
+            }
+            return ViInput::Command(ViCommand::SetMode(ViMode::Normal));
+        }
+        ViInput::Unhandled
+    }
+
+    fn is_idle(&self) -> bool {
+        self.pending == Pending::None
+            && self.operator.is_none()
+            && self.count.is_none()
+            && self.register.is_none()
+    }
+
+    /// Forget a partially typed command
+    fn reset(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.register = None;
+        self.pending = Pending::None;
+    }
+
+    /// A key in normal, visual or operator-pending mode
+    fn command_key(&mut self, key: KeyEvent) -> ViInput {
+        let ch = match key.code {
+            KeyCode::Char(c) => Some(c),
+            _ => None,
+        };
+
+        match std::mem::take(&mut self.pending) {
+            Pending::None => {}
+            Pending::Register => {
+                return match ch {
+                    Some(c) if c.is_ascii_alphanumeric() || "\"+*_".contains(c) => {
+                        self.register = Some(c);
+                        ViInput::Pending
+                    }
+                    _ => self.cancel(),
+                };
+            }
+            Pending::G => {
+                return match ch {
+                    Some('g') => {
+                        let line = self.count.take().unwrap_or(1);
+                        self.motion(Motion::GotoLine(Some(line)))
+                    }
+                    _ => self.cancel(),
+                };
+            }
+            Pending::Z => {
+                return match ch {
+                    Some('z') => ViInput::Actions(vec![Action::Recenter]),
+                    _ => self.cancel(),
+                };
+            }
+            Pending::Find { forward, till } => {
+                let Some(c) = ch else {
+                    return self.cancel();
+                };
+                self.last_find = Some((c, forward, till));
+                return self.motion(Motion::FindChar {
+                    ch: c,
+                    forward,
+                    till,
+                });
+            }
+            Pending::Replace => {
+                let Some(c) = ch else {
+                    return self.cancel();
+                };
+                let count = self.count.take().unwrap_or(1);
+                return ViInput::Command(ViCommand::ReplaceChar { ch: c, count });
+            }
+            Pending::TextObject { inner } => {
+                return match ch.and_then(|c| text_object(c, inner)) {
+                    Some(object) => self.text_object(object),
+                    None => self.cancel(),
+                };
+            }
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return match key.code {
+                KeyCode::Char('r') => self.repeated(Action::Redo),
+                KeyCode::Char('d') => self.repeated(Action::MovePageDown),
+                KeyCode::Char('u') => self.repeated(Action::MovePageUp),
+                KeyCode::Char('v') => self.toggle_visual(ViMode::VisualBlock),
+                // Everything else (Ctrl+S, Ctrl+P, ...) keeps its keymap binding
+                _ => ViInput::Unhandled,
+            };
+        }
+        if key.modifiers.contains(KeyModifiers::ALT) {
+            return ViInput::Unhandled;
+        }
+
+        let Some(ch) = ch else {
+            return match key.code {
+                KeyCode::Esc => {
+                    if self.mode.is_visual() {
+                        ViInput::Command(ViCommand::SetMode(ViMode::Normal))
+                    } else if self.is_idle() {
+                        ViInput::Actions(vec![Action::RemoveSecondaryCursors])
+                    } else {
+                        self.cancel()
+                    }
+                }
+                KeyCode::Left | KeyCode::Backspace => self.motion(Motion::Left),
+                KeyCode::Right => self.motion(Motion::Right),
+                KeyCode::Up => self.motion(Motion::Up),
+                KeyCode::Down | KeyCode::Enter => self.motion(Motion::Down),
+                KeyCode::Home => self.motion(Motion::LineStart),
+                KeyCode::End => self.motion(Motion::LineEnd),
+                KeyCode::Delete if self.mode.is_visual() => self.operator_key(Operator::Delete),
+                KeyCode::Delete if self.operator.is_none() => {
+                    self.operate(Operator::Delete, Target::Motion(Motion::Right))
+                }
+                // Don't let the keymap insert a tab in normal mode
+                KeyCode::Tab | KeyCode::BackTab => self.cancel(),
+                _ => ViInput::Unhandled,
+            };
+        };
+
+        if ch.is_ascii_digit() && (ch != '0' || self.count.is_some()) {
+            let digit = ch as usize - '0' as usize;
+            let count = self.count.unwrap_or(0) * 10 + digit;
+            self.count = Some(count.min(MAX_COUNT));
+            return ViInput::Pending;
+        }
+
+        let motion = match ch {
+            'h' => Some(Motion::Left),
+            'l' | ' ' => Some(Motion::Right),
+            'j' => Some(Motion::Down),
+            'k' => Some(Motion::Up),
+            'w' | 'W' => Some(Motion::WordForward { big: ch == 'W' }),
+            'b' | 'B' => Some(Motion::WordBackward { big: ch == 'B' }),
+            'e' | 'E' => Some(Motion::WordEnd { big: ch == 'E' }),
+            '0' => Some(Motion::LineStart),
+            '^' => Some(Motion::FirstNonBlank),
+            '$' => Some(Motion::LineEnd),
+            '}' => Some(Motion::ParagraphForward),
+            '{' => Some(Motion::ParagraphBackward),
+            '%' => Some(Motion::MatchingBracket),
+            'G' => Some(Motion::GotoLine(self.count.take())),
+            _ => None,
+        };
+        if let Some(motion) = motion {
+            return self.motion(motion);
+        }
+
+        match ch {
+            'f' | 'F' | 't' | 'T' => {
+                self.pending = Pending::Find {
+                    forward: ch.is_ascii_lowercase(),
+                    till: ch.eq_ignore_ascii_case(&'t'),
+                };
+                ViInput::Pending
+            }
+            ';' | ',' => match self.last_find {
+                Some((c, forward, till)) => self.motion(Motion::FindChar {
+                    ch: c,
+                    forward: forward == (ch == ';'),
+                    till,
+                }),
+                None => self.cancel(),
+            },
+            'g' => {
+                self.pending = Pending::G;
+                ViInput::Pending
+            }
+            'i' | 'a' if self.operator.is_some() || self.mode.is_visual() => {
+                self.pending = Pending::TextObject { inner: ch == 'i' };
+                ViInput::Pending
+            }
+            'd' => self.operator_key(Operator::Delete),
+            'c' => self.operator_key(Operator::Change),
+            'y' => self.operator_key(Operator::Yank),
+            '>' => self.operator_key(Operator::Indent),
+            '<' => self.operator_key(Operator::Dedent),
+            // Nothing below combines with a pending operator
+            _ if self.operator.is_some() => self.cancel(),
+            'x' if self.mode.is_visual() => self.operator_key(Operator::Delete),
+            's' if self.mode.is_visual() => self.operator_key(Operator::Change),
+            'o' if self.mode.is_visual() => ViInput::Command(ViCommand::SwapSelectionEnds),
+            'I' | 'A' if self.mode == ViMode::VisualBlock => {
+                ViInput::Command(ViCommand::BlockInsert { append: ch == 'A' })
+            }
+            'v' => self.toggle_visual(ViMode::Visual),
+            'V' => self.toggle_visual(ViMode::VisualLine),
+            _ if self.mode.is_visual() => self.cancel(),
+            '"' => {
+                self.pending = Pending::Register;
+                ViInput::Pending
+            }
+            'z' => {
+                self.pending = Pending::Z;
+                ViInput::Pending
+            }
+            'r' => {
+                self.pending = Pending::Replace;
+                ViInput::Pending
+            }
+            'x' => self.operate(Operator::Delete, Target::Motion(Motion::Right)),
+            'X' => self.operate(Operator::Delete, Target::Motion(Motion::Left)),
+            'D' => self.operate(Operator::Delete, Target::Motion(Motion::LineEnd)),
+            's' => self.operate(Operator::Change, Target::Motion(Motion::Right)),
+            'S' => self.operate(Operator::Change, Target::Lines),
+            'C' => self.operate(Operator::Change, Target::Motion(Motion::LineEnd)),
+            'Y' => self.operate(Operator::Yank, Target::Lines),
+            'i' => ViInput::Command(ViCommand::Insert(InsertAt::Cursor)),
+            'a' => ViInput::Command(ViCommand::Insert(InsertAt::AfterCursor)),
+            'I' => ViInput::Command(ViCommand::Insert(InsertAt::LineStart)),
+            'A' => ViInput::Command(ViCommand::Insert(InsertAt::LineEnd)),
+            'o' => ViInput::Command(ViCommand::Insert(InsertAt::LineBelow)),
+            'O' => ViInput::Command(ViCommand::Insert(InsertAt::LineAbove)),
+            'p' | 'P' => ViInput::Command(ViCommand::Put {
+                before: ch == 'P',
+                register: self.register.take(),
+                count: self.count.take().unwrap_or(1),
+            }),
+            'J' => ViInput::Command(ViCommand::JoinLines {
+                count: self.count.take().unwrap_or(1),
+            }),
+            'u' => self.repeated(Action::Undo),
+            'n' => self.repeated(Action::FindNext),
+            'N' => self.repeated(Action::FindPrevious),
+            '/' | '?' => ViInput::Actions(vec![Action::Search]),
+            ':' => ViInput::Actions(vec![Action::CommandPalette]),
+            '.' => {
+                let count = self.count.take();
+                self.repeat(count)
+            }
+            _ => self.cancel(),
+        }
+    }
+
+    fn cancel(&mut self) -> ViInput {
+        self.reset();
+        ViInput::Pending
+    }
+
+    /// An action run `count` times
+    fn repeated(&mut self, action: Action) -> ViInput {
+        let count = self.count.take().unwrap_or(1);
+        ViInput::Actions(vec![action; count])
+    }
+
+    fn toggle_visual(&mut self, mode: ViMode) -> ViInput {
+        if self.operator.is_some() {
+            return self.cancel();
+        }
+        let mode = if self.mode == mode {
+            ViMode::Normal
+        } else {
+            mode
+        };
+        ViInput::Command(ViCommand::SetMode(mode))
+    }
+
+    fn operator_key(&mut self, operator: Operator) -> ViInput {
+        if self.mode.is_visual() {
+            return ViInput::Command(ViCommand::OperateSelection {
+                operator,
+                register: self.register.take(),
+            });
+        }
+        match self.operator {
+            // Doubled operator: whole lines
+            Some((pending, _)) if pending == operator => self.target(Target::Lines),
+            Some(_) => self.cancel(),
+            None => {
+                self.operator = Some((operator, self.count.take().unwrap_or(1)));
+                ViInput::Pending
+            }
+        }
+    }
+
+    fn operate(&mut self, operator: Operator, target: Target) -> ViInput {
+        self.operator = Some((operator, 1));
+        self.target(target)
+    }
+
+    fn motion(&mut self, motion: Motion) -> ViInput {
+        if self.operator.is_some() {
+            return self.target(Target::Motion(motion));
+        }
+        let count = self.count.take().unwrap_or(1);
+        ViInput::Command(ViCommand::Move { motion, count })
+    }
+
+    fn text_object(&mut self, object: TextObject) -> ViInput {
+        if self.operator.is_some() {
+            return self.target(Target::TextObject(object));
+        }
+        ViInput::Command(ViCommand::SelectTextObject(object))
+    }
+
+    /// Complete the pending operator
+    fn target(&mut self, target: Target) -> ViInput {
+        let Some((operator, operator_count)) = self.operator.take() else {
+            return self.cancel();
+        };
+        // `cw` changes to the end of the word, leaving the space after it
+        let target = match target {
+            Target::Motion(Motion::WordForward { big }) if operator == Operator::Change => {
+                Target::Motion(Motion::WordEnd { big })
+            }
+            target => target,
+        };
+        let count = operator_count * self.count.take().unwrap_or(1);
+        ViInput::Command(ViCommand::Operate {
+            operator,
+            target,
+            count: count.min(MAX_COUNT),
+            register: self.register.take(),
+        })
+    }
+
+    /// The keys of the last change, with `count` replacing its original count
+    fn repeat(&mut self, count: Option<usize>) -> ViInput {
+        if self.last_change.is_empty() {
+            return ViInput::Pending;
+        }
+        let Some(count) = count else {
+            return ViInput::Repeat(self.last_change.clone());
+        };
+        let is_digit = |key: &KeyEvent| matches!(key.code, KeyCode::Char(c) if c.is_ascii_digit());
+        let keys = count
+            .to_string()
+            .chars()
+            .map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+            .chain(
+                self.last_change
+                    .iter()
+                    .skip_while(|key| is_digit(key))
+                    .copied(),
+            )
+            .collect();
+        ViInput::Repeat(keys)
+    }
+}
+
+/// The text object named by the key after `i` / `a`
+fn text_object(ch: char, inner: bool) -> Option<TextObject> {
+    let bracket = |open, close| TextObject::Bracket { inner, open, close };
+    let syntax = |name: &str| {
+        TextObject::Syntax(if inner {
+            format!("{name}.inner")
+        } else {
+            name.to_string()
+        })
+    };
+    Some(match ch {
+        'w' | 'W' => TextObject::Word {
+            inner,
+            big: ch == 'W',
+        },
+        '"' | '\'' | '`' => TextObject::Quote { inner, quote: ch },
+        '(' | ')' | 'b' => bracket('(', ')'),
+        '[' | ']' => bracket('[', ']'),
+        '{' | '}' | 'B' => bracket('{', '}'),
+        '<' | '>' => bracket('<', '>'),
+        'p' => TextObject::Paragraph { inner },
+        'f' => syntax("function"),
+        'c' => syntax("class"),
+        'a' => syntax("parameter"),
+        _ => return None,
+    })
+}
+
+// ============================================================================
+// Motions and text objects
+// ============================================================================
+
+/// Character class for word motions: blank, punctuation or word
+///
+/// Non-ASCII bytes count as word characters so that motions never stop
+/// inside a multi-byte character.
+fn char_class(byte: u8, big: bool) -> u8 {
+    if byte.is_ascii_whitespace() {
+        0
+    } else if big || is_word_char(byte) || !byte.is_ascii() {
+        2
+    } else {
+        1
+    }
+}
+
+/// Bytes of `range` (clamped to the buffer)
+fn bytes(buffer: &Buffer, range: Range<usize>) -> Vec<u8> {
+    let end = range.end.min(buffer.len());
+    if range.start >= end {
+        return Vec::new();
+    }
+    buffer.slice_bytes(range.start..end)
+}
+
+fn line_count(buffer: &Buffer) -> usize {
+    buffer.line_count().unwrap_or(1).max(1)
+}
+
+/// Offset of the first non-blank character of `line`
+pub fn first_non_blank(buffer: &Buffer, line: usize) -> usize {
+    let start = buffer.line_start(line);
+    let content = bytes(buffer, start..buffer.line_end(line));
+    start
+        + content
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(content.len())
+}
+
+/// Whether `line` contains only whitespace
+fn is_blank_line(buffer: &Buffer, line: usize) -> bool {
+    first_non_blank(buffer, line) == buffer.line_end(line)
+}
+
+/// Range covering whole lines `first..=last`, including the final line terminator
+pub fn lines_range(buffer: &Buffer, first: usize, last: usize) -> Range<usize> {
+    let end = if last + 1 < line_count(buffer) {
+        buffer.line_start(last + 1)
+    } else {
+        buffer.len()
+    };
+    buffer.line_start(first)..end
+}
+
+/// Keep a normal-mode cursor on a character: not past the last one on its line
+pub fn clamp_to_line(buffer: &Buffer, pos: usize) -> usize {
+    let line = buffer.line_of(pos);
+    let start = buffer.line_start(line);
+    let end = buffer.line_end(line);
+    if pos >= end && end > start {
+        buffer.prev_char_boundary(end)
+    } else {
+        pos
+    }
+}
+
+/// Where `motion` moves the cursor from `pos`, or None if it can't move
+/// (e.g. `fx` without an `x` on the line)
+pub fn motion_target(buffer: &Buffer, pos: usize, motion: &Motion, count: usize) -> Option<usize> {
+    let line = buffer.line_of(pos);
+    let target = match motion {
+        Motion::Left => {
+            let start = buffer.line_start(line);
+            let mut target = pos;
+            for _ in 0..count {
+                if target <= start {
+                    break;
+                }
+                target = buffer.prev_char_boundary(target);
+            }
+            target
+        }
+        Motion::Right => {
+            let end = buffer.line_end(line);
+            let mut target = pos;
+            for _ in 0..count {
+                if target >= end {
+                    break;
+                }
+                target = buffer.next_char_boundary(target);
+            }
+            target
+        }
+        Motion::Up | Motion::Down => {
+            let column = pos - buffer.line_start(line);
+            let target_line = if *motion == Motion::Up {
+                line.checked_sub(count)?
+            } else {
+                let target_line = (line + count).min(buffer.last_line());
+                if target_line == line {
+                    return None;
+                }
+                target_line
+            };
+            let start = buffer.line_start(target_line);
+            buffer.snap_to_char_boundary((start + column).min(buffer.line_end(target_line)))
+        }
+        Motion::WordForward { big } => {
+            let base = pos;
+            let text = bytes(buffer, pos..pos + SCAN_WINDOW);
+            let mut i = 0;
+            for _ in 0..count {
+                i = word_forward(&text, i, *big);
+            }
+            base + i
+        }
+        Motion::WordEnd { big } => {
+            let text = bytes(buffer, pos..pos + SCAN_WINDOW);
+            let mut i = 0;
+            for _ in 0..count {
+                i = word_end(&text, i, *big);
+            }
+            buffer.snap_to_char_boundary(pos + i)
+        }
+        Motion::WordBackward { big } => {
+            let base = pos.saturating_sub(SCAN_WINDOW);
+            let text = bytes(buffer, base..pos);
+            let mut i = text.len();
+            for _ in 0..count {
+                i = word_backward(&text, i, *big);
+            }
+            base + i
+        }
+        Motion::LineStart => buffer.line_start(line),
+        Motion::FirstNonBlank => first_non_blank(buffer, line),
+        Motion::LineEnd => {
+            let target_line = (line + count - 1).min(buffer.last_line());
+            let end = buffer.line_end(target_line);
+            if end > buffer.line_start(target_line) {
+                buffer.prev_char_boundary(end)
+            } else {
+                end
+            }
+        }
+        Motion::GotoLine(target_line) => {
+            let last = buffer.last_line();
+            let target_line = target_line.map_or(last, |n| n.saturating_sub(1).min(last));
+            first_non_blank(buffer, target_line)
+        }
+        Motion::ParagraphForward => {
+            // From a blank line, skip the blank lines first; then move to the
+            // blank line after the paragraph
+            let last = buffer.last_line();
+            let mut target_line = line;
+            for _ in 0..count {
+                while target_line <= last && is_blank_line(buffer, target_line) {
+                    target_line += 1;
+                }
+                while target_line <= last && !is_blank_line(buffer, target_line) {
+                    target_line += 1;
+                }
+                if target_line > last {
+                    return Some(clamp_to_line(buffer, buffer.len()));
+                }
+            }
+            buffer.line_start(target_line)
+        }
+        Motion::ParagraphBackward => {
+            let mut target_line = line;
+            for _ in 0..count {
+                while target_line > 0 && is_blank_line(buffer, target_line) {
+                    target_line -= 1;
+                }
+                while target_line > 0 && !is_blank_line(buffer, target_line) {
+                    target_line -= 1;
+                }
+            }
+            buffer.line_start(target_line)
+        }
+        Motion::FindChar { ch, forward, till } => {
+            find_char(buffer, pos, line, *ch, *forward, *till, count)?
+        }
+        Motion::MatchingBracket => matching_bracket(buffer, pos, line)?,
+    };
+    Some(target)
+}
+
+/// Start of the next word (`w`), stopping at empty lines
+fn word_forward(text: &[u8], mut i: usize, big: bool) -> usize {
+    let n = text.len();
+    if i >= n {
+        return n;
+    }
+    let class = char_class(text[i], big);
+    if class != 0 {
+        while i < n && char_class(text[i], big) == class {
+            i += 1;
+        }
+    }
+    while i < n && char_class(text[i], big) == 0 {
+        if text[i] == b'\n' && text.get(i + 1) == Some(&b'\n') {
+            return i + 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Last byte of the current or next word (`e`)
+fn word_end(text: &[u8], mut i: usize, big: bool) -> usize {
+    let n = text.len();
+    i += 1;
+    while i < n && char_class(text[i], big) == 0 {
+        i += 1;
+    }
+    if i >= n {
+        return n.saturating_sub(1);
+    }
+    let class = char_class(text[i], big);
+    while i + 1 < n && char_class(text[i + 1], big) == class {
+        i += 1;
+    }
+    i
+}
+
+/// Start of the current or previous word (`b`)
+fn word_backward(text: &[u8], mut i: usize, big: bool) -> usize {
+    if i == 0 {
+        return 0;
+    }
+    i -= 1;
+    while i > 0 && char_class(text[i], big) == 0 {
+        i -= 1;
+    }
+    let class = char_class(text[i], big);
+    while i > 0 && char_class(text[i - 1], big) == class {
+        i -= 1;
+    }
+    i
+}
+
+/// The `count`th `ch` after (or before) `pos` on its line
+fn find_char(
+    buffer: &Buffer,
+    pos: usize,
+    line: usize,
+    ch: char,
+    forward: bool,
+    till: bool,
+    count: usize,
+) -> Option<usize> {
+    let start = buffer.line_start(line);
+    let text = String::from_utf8(bytes(buffer, start..buffer.line_end(line))).ok()?;
+    let col = pos - start;
+    let found = if forward {
+        // `t` starts one further on, so repeating `;` doesn't get stuck
+        let from = text[col..]
+            .chars()
+            .next()
+            .map_or(col, |c| col + c.len_utf8());
+        let from = if till {
+            text[from..]
+                .chars()
+                .next()
+                .map_or(from, |c| from + c.len_utf8())
+        } else {
+            from
+        };
+        text.get(from..)?
+            .match_indices(ch)
+            .nth(count - 1)
+            .map(|(i, _)| from + i)?
+    } else {
+        let to = if till {
+            text[..col]
+                .chars()
+                .next_back()
+                .map_or(col, |c| col - c.len_utf8())
+        } else {
+            col
+        };
+        text[..to]
+            .rmatch_indices(ch)
+            .nth(count - 1)
+            .map(|(i, _)| i)?
+    };
+    let target = match (till, forward) {
+        (false, _) => found,
+        (true, true) => found - text[..found].chars().next_back()?.len_utf8(),
+        (true, false) => found + ch.len_utf8(),
+    };
+    Some(start + target)
+}
+
+/// Bracket matching the one at (or first after) `pos` on its line
+fn matching_bracket(buffer: &Buffer, pos: usize, line: usize) -> Option<usize> {
+    let end = buffer.line_end(line);
+    let line_text = bytes(buffer, pos..end);
+    let offset = line_text.iter().position(|b| b"()[]{}".contains(b))?;
+    brackets::matching_bracket(buffer, pos + offset)
+}
+
+/// Range an operator covers when `motion` moves the cursor from `pos` to
+/// `target`, and whether it is whole lines
+pub fn motion_range(
+    buffer: &Buffer,
+    pos: usize,
+    target: usize,
+    motion: &Motion,
+) -> (Range<usize>, bool) {
+    if motion.is_linewise() {
+        let first = buffer.line_of(pos.min(target));
+        let last = buffer.line_of(pos.max(target));
+        return (lines_range(buffer, first, last), true);
+    }
+    let start = pos.min(target);
+    let mut end = pos.max(target);
+    if motion.is_inclusive() {
+        // Not over the line break when the motion ends on an empty line
+        if end < buffer.line_end(buffer.line_of(end)) {
+            end = buffer.next_char_boundary(end);
+        }
+    } else {
+        // An exclusive motion ending at the start of a later line stops at
+        // the end of the line before (`dw` on the last word of a line)
+        let end_line = buffer.line_of(end);
+        if end_line > buffer.line_of(start) && end == buffer.line_start(end_line) {
+            end = buffer.line_end(end_line - 1).max(start);
+        }
+    }
+    (start..end, false)
+}
+
+/// Range of a text object around `pos`, and whether it is whole lines
+///
+/// Tree-sitter objects (`TextObject::Syntax`) need the syntax tree and are
+/// resolved by the editor.
+pub fn text_object_range(
+    buffer: &Buffer,
+    pos: usize,
+    object: &TextObject,
+) -> Option<(Range<usize>, bool)> {
+    match object {
+        TextObject::Word { inner, big } => Some((word_object(buffer, pos, *inner, *big), false)),
+        TextObject::Quote { inner, quote } => {
+            quote_object(buffer, pos, *inner, *quote).map(|range| (range, false))
+        }
+        TextObject::Bracket { inner, open, close } => {
+            bracket_object(buffer, pos, *inner, *open as u8, *close as u8)
+                .map(|range| (range, false))
+        }
+        TextObject::Paragraph { inner } => Some((paragraph_object(buffer, pos, *inner), true)),
+        TextObject::Syntax(_) => None,
+    }
+}
+
+/// The word (or run of blanks) at `pos`; `aw` adds the blanks after it, or
+/// before it when there are none after
+fn word_object(buffer: &Buffer, pos: usize, inner: bool, big: bool) -> Range<usize> {
+    let line = buffer.line_of(pos);
+    let start = buffer.line_start(line);
+    let text = bytes(buffer, start..buffer.line_end(line));
+    let i = pos - start;
+    if i >= text.len() {
+        return pos..pos;
+    }
+    let class = char_class(text[i], big);
+    let mut from = i;
+    while from > 0 && char_class(text[from - 1], big) == class {
+        from -= 1;
+    }
+    let mut to = i + 1;
+    while to < text.len() && char_class(text[to], big) == class {
+        to += 1;
+    }
+    if !inner && class != 0 {
+        let blanks_after = text[to..]
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count();
+        if blanks_after > 0 {
+            to += blanks_after;
+        } else {
+            from -= text[..from]
+                .iter()
+                .rev()
+                .take_while(|b| b.is_ascii_whitespace())
+                .count();
+        }
+    }
+    start + from..start + to
+}
+
+/// The quoted string containing `pos` on its line, or the next one after it
+fn quote_object(buffer: &Buffer, pos: usize, inner: bool, quote: char) -> Option<Range<usize>> {
+    let line = buffer.line_of(pos);
+    let start = buffer.line_start(line);
+    let text = bytes(buffer, start..buffer.line_end(line));
+    let col = pos - start;
+    let quote = quote as u8;
+    let quotes: Vec<usize> = text
+        .iter()
+        .enumerate()
+        .filter(|&(i, &b)| b == quote && (i == 0 || text[i - 1] != b'\\'))
+        .map(|(i, _)| i)
+        .collect();
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(_, close)| col <= close)?;
+    Some(if inner {
+        start + open + 1..start + close
+    } else {
+        start + open..start + close + 1
+    })
+}
+
+/// The brackets around `pos`; `i(` leaves out the brackets, and for a block
+/// spanning several lines also the line breaks after `(` and before `)`
+fn bracket_object(
+    buffer: &Buffer,
+    pos: usize,
+    inner: bool,
+    open: u8,
+    close: u8,
+) -> Option<Range<usize>> {
+    let at = bytes(buffer, pos..pos + 1).first().copied();
+    let open_at = if at == Some(open) {
+        pos
+    } else {
+        find_open(buffer, pos, open, close)?
+    };
+    let close_at = find_close(buffer, open_at + 1, open, close)?;
+    if !inner {
+        return Some(open_at..close_at + 1);
+    }
+
+    let mut start = open_at + 1;
+    let mut end = close_at;
+    let close_line = buffer.line_of(close_at);
+    if close_line > buffer.line_of(open_at) {
+        if start == buffer.line_end(buffer.line_of(open_at)) {
+            start = buffer.line_start(buffer.line_of(open_at) + 1);
+        }
+        if first_non_blank(buffer, close_line) == close_at {
+            end = buffer.line_start(close_line).max(start);
+        }
+    }
+    Some(start..end)
+}
+
+/// Lines around `pos` that are all blank or all non-blank; `ap` adds the
+/// blank lines after them (or before, at the end of the buffer)
+fn paragraph_object(buffer: &Buffer, pos: usize, inner: bool) -> Range<usize> {
+    let line = buffer.line_of(pos);
+    let last = buffer.last_line();
+    let blank = is_blank_line(buffer, line);
+    let mut first = line;
+    while first > 0 && is_blank_line(buffer, first - 1) == blank {
+        first -= 1;
+    }
+    let mut end = line;
+    while end < last && is_blank_line(buffer, end + 1) == blank {
+        end += 1;
+    }
+    if !inner {
+        if end < last {
+            end += 1;
+            while end < last && is_blank_line(buffer, end + 1) != blank {
+                end += 1;
+            }
+        } else {
+            while first > 0 && is_blank_line(buffer, first - 1) != blank {
+                first -= 1;
+            }
+        }
+    }
+    lines_range(buffer, first, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(state: &mut ViState, typed: &str) -> Vec<ViInput> {
+        typed
+            .chars()
+            .map(|c| state.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)))
+            .collect()
+    }
+
+    /// The command completed by the last key of `typed`
+    fn command(state: &mut ViState, typed: &str) -> ViCommand {
+        match keys(state, typed).pop() {
+            Some(ViInput::Command(command)) => command,
+            other => panic!("{typed:?} gave {other:?}"),
+        }
+    }
+
+    fn target(text: &str, pos: usize, motion: Motion, count: usize) -> Option<usize> {
+        motion_target(&Buffer::from_str_test(text), pos, &motion, count)
+    }
+
+    #[test]
+    fn test_operator_motion_and_counts() {
+        let mut state = ViState::new();
+        assert_eq!(
+            command(&mut state, "d2w"),
+            ViCommand::Operate {
+                operator: Operator::Delete,
+                target: Target::Motion(Motion::WordForward { big: false }),
+                count: 2,
+                register: None,
+            }
+        );
+        // Counts before the operator and the motion multiply
+        assert_eq!(
+            command(&mut state, "2d3j"),
+            ViCommand::Operate {
+                operator: Operator::Delete,
+                target: Target::Motion(Motion::Down),
+                count: 6,
+                register: None,
+            }
+        );
+        assert_eq!(
+            command(&mut state, "\"ayy"),
+            ViCommand::Operate {
+                operator: Operator::Yank,
+                target: Target::Lines,
+                count: 1,
+                register: Some('a'),
+            }
+        );
+        // `cw` works like `ce`
+        assert_eq!(
+            command(&mut state, "cw"),
+            ViCommand::Operate {
+                operator: Operator::Change,
+                target: Target::Motion(Motion::WordEnd { big: false }),
+                count: 1,
+                register: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_text_objects_and_visual_mode() {
+        let mut state = ViState::new();
+        assert_eq!(
+            command(&mut state, "ci\""),
+            ViCommand::Operate {
+                operator: Operator::Change,
+                target: Target::TextObject(TextObject::Quote {
+                    inner: true,
+                    quote: '"'
+                }),
+                count: 1,
+                register: None,
+            }
+        );
+        assert_eq!(
+            command(&mut state, ">ip"),
+            ViCommand::Operate {
+                operator: Operator::Indent,
+                target: Target::TextObject(TextObject::Paragraph { inner: true }),
+                count: 1,
+                register: None,
+            }
+        );
+
+        assert_eq!(
+            command(&mut state, "V"),
+            ViCommand::SetMode(ViMode::VisualLine)
+        );
+        state.set_mode(ViMode::VisualLine);
+        assert_eq!(
+            command(&mut state, "af"),
+            ViCommand::SelectTextObject(TextObject::Syntax("function".to_string()))
+        );
+        assert_eq!(
+            command(&mut state, "d"),
+            ViCommand::OperateSelection {
+                operator: Operator::Delete,
+                register: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_mode_reporting() {
+        let mut state = ViState::new();
+        keys(&mut state, "2d");
+        assert_eq!(state.mode(), ViMode::OperatorPending);
+        assert_eq!(state.pending_keys(), "2d");
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
+            ViInput::Pending
+        );
+        assert_eq!(state.mode(), ViMode::Normal);
+        assert_eq!(state.mode().cursor_shape(), CursorShape::Block);
+
+        // Insert mode leaves typing to the keymap
+        assert_eq!(
+            command(&mut state, "i"),
+            ViCommand::Insert(InsertAt::Cursor)
+        );
+        state.set_mode(ViMode::Insert);
+        assert_eq!(keys(&mut state, "x"), vec![ViInput::Unhandled]);
+        let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        state.set_mode(ViMode::Normal);
+        assert_eq!(state.handle_key(ctrl_s), ViInput::Unhandled);
+    }
+
+    #[test]
+    fn test_dot_repeats_last_change_with_new_count() {
+        let mut state = ViState::new();
+        command(&mut state, "2dw");
+        command(&mut state, "j");
+        let replay: String = match keys(&mut state, ".").pop() {
+            Some(ViInput::Repeat(keys)) => keys
+                .iter()
+                .filter_map(|key| match key.code {
+                    KeyCode::Char(c) => Some(c),
+                    _ => None,
+                })
+                .collect(),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(replay, "2dw");
+
+        let replay = match keys(&mut state, "3.").pop() {
+            Some(ViInput::Repeat(keys)) => keys.len(),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(replay, 3); // "3dw"
+
+        // An insertion is repeated together with the typed text
+        command(&mut state, "A");
+        state.set_mode(ViMode::Insert);
+        keys(&mut state, ";");
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        state.handle_key(esc);
+        state.set_mode(ViMode::Normal);
+        match keys(&mut state, ".").pop() {
+            Some(ViInput::Repeat(keys)) => assert_eq!(keys.len(), 3),
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_registers() {
+        let mut state = ViState::new();
+        let text = |text: &str| Register {
+            text: text.to_string(),
+            linewise: false,
+        };
+        state.set_register(Some('a'), text("one"), true);
+        state.set_register(Some('A'), text(" two"), false);
+        assert_eq!(state.register('a').unwrap().text, "one two");
+        assert_eq!(state.register('0').unwrap().text, "one");
+        assert_eq!(state.register('"').unwrap().text, " two");
+
+        state.set_register(Some('_'), text("gone"), false);
+        assert_eq!(state.register('"').unwrap().text, " two");
+    }
+
+    #[test]
+    fn test_word_motions() {
+        let text = "foo.bar baz\n\nqux";
+        assert_eq!(
+            target(text, 0, Motion::WordForward { big: false }, 1),
+            Some(3)
+        );
+        assert_eq!(
+            target(text, 0, Motion::WordForward { big: true }, 1),
+            Some(8)
+        );
+        // Stops at the empty line
+        assert_eq!(
+            target(text, 8, Motion::WordForward { big: false }, 1),
+            Some(12)
+        );
+        assert_eq!(target(text, 0, Motion::WordEnd { big: false }, 2), Some(3));
+        assert_eq!(
+            target(text, 8, Motion::WordBackward { big: false }, 2),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_line_motions() {
+        let text = "  one\ntwo\n\nthree\n";
+        assert_eq!(target(text, 4, Motion::FirstNonBlank, 1), Some(2));
+        assert_eq!(target(text, 0, Motion::LineEnd, 1), Some(4));
+        assert_eq!(target(text, 3, Motion::Down, 1), Some(9));
+        assert_eq!(target(text, 0, Motion::Up, 1), None);
+        // The empty line after the final newline isn't a line of its own
+        assert_eq!(target(text, 0, Motion::GotoLine(None), 1), Some(11));
+        assert_eq!(target(text, 11, Motion::GotoLine(Some(2)), 1), Some(6));
+        assert_eq!(target(text, 0, Motion::ParagraphForward, 1), Some(10));
+        assert_eq!(target(text, 12, Motion::ParagraphBackward, 1), Some(10));
+    }
+
+    #[test]
+    fn test_find_and_bracket_motions() {
+        let text = "call(a, (b), c);";
+        let find = |ch, forward, till| Motion::FindChar { ch, forward, till };
+        assert_eq!(target(text, 0, find(',', true, false), 2), Some(11));
+        assert_eq!(target(text, 0, find(',', true, true), 1), Some(5));
+        assert_eq!(target(text, 14, find('(', false, false), 1), Some(8));
+        assert_eq!(target(text, 14, find('(', false, true), 1), Some(9));
+        assert_eq!(target(text, 0, find('z', true, false), 1), None);
+        assert_eq!(target(text, 0, Motion::MatchingBracket, 1), Some(14));
+        assert_eq!(target(text, 14, Motion::MatchingBracket, 1), Some(4));
+    }
+
+    #[test]
+    fn test_motion_ranges() {
+        let buffer = Buffer::from_str_test("one two\nthree\n");
+        let range = |pos, motion: Motion| {
+            let target = motion_target(&buffer, pos, &motion, 1).unwrap();
+            motion_range(&buffer, pos, target, &motion)
+        };
+        assert_eq!(range(0, Motion::WordForward { big: false }), (0..4, false));
+        assert_eq!(range(0, Motion::WordEnd { big: false }), (0..3, false));
+        // `dw` on the last word of a line keeps the line break
+        assert_eq!(range(4, Motion::WordForward { big: false }), (4..7, false));
+        assert_eq!(range(2, Motion::Down), (0..14, true));
+        assert_eq!(range(4, Motion::LineEnd), (4..7, false));
+    }
+
+    #[test]
+    fn test_text_object_ranges() {
+        let text = "let s = f(\"a b\", [1, 2]);\n";
+        let buffer = Buffer::from_str_test(text);
+        let object = |pos, object| {
+            let (range, _) = text_object_range(&buffer, pos, &object).unwrap();
+            &text[range]
+        };
+        let quote = |inner| TextObject::Quote { inner, quote: '"' };
+        let bracket = |inner| TextObject::Bracket {
+            inner,
+            open: '(',
+            close: ')',
+        };
+        assert_eq!(object(12, quote(true)), "a b");
+        assert_eq!(object(12, quote(false)), "\"a b\"");
+        // Before the string on the same line
+        assert_eq!(object(0, quote(true)), "a b");
+        assert_eq!(object(19, bracket(true)), "\"a b\", [1, 2]");
+        assert_eq!(object(19, bracket(false)), "(\"a b\", [1, 2])");
+        assert_eq!(
+            object(
+                4,
+                TextObject::Word {
+                    inner: false,
+                    big: false
+                }
+            ),
+            "s "
+        );
+
+        let text = "fn f() {\n    body\n}\n";
+        let buffer = Buffer::from_str_test(text);
+        let block = TextObject::Bracket {
+            inner: true,
+            open: '{',
+            close: '}',
+        };
+        let (range, _) = text_object_range(&buffer, 12, &block).unwrap();
+        assert_eq!(&text[range], "    body\n");
+    }
+
+    #[test]
+    fn test_paragraph_object() {
+        let text = "a\nb\n\nc\n";
+        let buffer = Buffer::from_str_test(text);
+        let (range, linewise) =
+            text_object_range(&buffer, 0, &TextObject::Paragraph { inner: true }).unwrap();
+        assert!(linewise);
+        assert_eq!(&text[range], "a\nb\n");
+        let (range, _) =
+            text_object_range(&buffer, 0, &TextObject::Paragraph { inner: false }).unwrap();
+        assert_eq!(&text[range], "a\nb\n\n");
+    }
+}
@@ -13,8 +13,8 @@ use crossterm::{
 use fresh::services::gpm::{gpm_to_crossterm, GpmClient};
 use fresh::services::tracing_setup;
 use fresh::{
-    app::Editor, config, config_io::DirectoryContext, services::release_checker,
-    services::signal_handler, services::warning_log::WarningLogHandle,
+    app::Editor, config, config_io::DirectoryContext, input::vi::CursorShape,
+    services::release_checker, services::signal_handler, services::warning_log::WarningLogHandle,
 };
 use ratatui::Terminal;
 use std::{
@@ -802,6 +802,7 @@ where
     let mut last_render = Instant::now();
     let mut needs_render = true;
     let mut pending_event: Option<CrosstermEvent> = None;
+    let mut cursor_shape = CursorShape::Block;
 
     loop {
         // Process async messages and poll for file changes (auto-revert, file tree)
@@ -843,6 +844,17 @@ where
             terminal.draw(|frame| editor.render(frame))?;
             last_render = Instant::now();
             needs_render = false;
+
+            // Vi mode switches between block, bar and underline cursors
+            if editor.cursor_shape() != cursor_shape {
+                cursor_shape = editor.cursor_shape();
+                let style = match cursor_shape {
+                    CursorShape::Block => SetCursorStyle::BlinkingBlock,
+                    CursorShape::Bar => SetCursorStyle::BlinkingBar,
+                    CursorShape::Underline => SetCursorStyle::BlinkingUnderScore,
+                };
+                let _ = stdout().execute(style);
+            }
         }
 
         let event = if let Some(e) = pending_event.take() {
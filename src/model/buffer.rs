@@ -10,6 +10,7 @@ use regex::bytes::Regex;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 // Large file support configuration
@@ -101,6 +102,13 @@ impl LineNumber {
     }
 }
 
+/// Source of `TextBuffer::version` values
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
+fn next_version() -> u64 {
+    NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
 /// A text buffer that manages document content using a piece table
 /// with integrated line tracking
 pub struct TextBuffer {
@@ -124,6 +132,9 @@ pub struct TextBuffer {
     /// Has the buffer been modified since last save?
     modified: bool,
 
+    /// Changes whenever the content changes (see `version()`)
+    version: u64,
+
     /// Does the buffer have unsaved changes for recovery auto-save?
     /// This is separate from `modified` because recovery auto-save doesn't
     /// clear `modified` (buffer still differs from on-disk file).
@@ -157,6 +168,7 @@ impl TextBuffer {
             next_buffer_id: 1,
             file_path: None,
             modified: false,
+            version: next_version(),
             recovery_pending: false,
             large_file: false,
             is_binary: false,
@@ -192,6 +204,7 @@ impl TextBuffer {
             next_buffer_id: 1,
             file_path: None,
             modified: false,
+            version: next_version(),
             recovery_pending: false,
             large_file: false,
             is_binary: false,
@@ -215,6 +228,7 @@ impl TextBuffer {
             next_buffer_id: 1,
             file_path: None,
             modified: false,
+            version: next_version(),
             recovery_pending: false,
             large_file: false,
             is_binary: false,
@@ -322,6 +336,7 @@ impl TextBuffer {
             next_buffer_id: 1,
             file_path: Some(path.to_path_buf()),
             modified: false,
+            version: next_version(),
             recovery_pending: false,
             large_file: true,
             is_binary,
@@ -726,6 +741,7 @@ impl TextBuffer {
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.version = next_version();
         self.recovery_pending = true;
 
         // Count line feeds in the text to insert
@@ -769,6 +785,7 @@ impl TextBuffer {
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.version = next_version();
         self.recovery_pending = true;
 
         // Find the piece containing the byte just before the insertion point
@@ -820,6 +837,7 @@ impl TextBuffer {
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.version = next_version();
         self.recovery_pending = true;
 
         // Count line feeds in the text to insert
@@ -854,6 +872,7 @@ impl TextBuffer {
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.version = next_version();
         self.recovery_pending = true;
     }
 
@@ -877,6 +896,7 @@ impl TextBuffer {
         );
         // Mark as modified and needing recovery
         self.modified = true;
+        self.version = next_version();
         self.recovery_pending = true;
     }
 
@@ -1221,6 +1241,7 @@ impl TextBuffer {
             None, // line_feed_cnt unknown for unloaded chunk
             &self.buffers,
         );
+        self.version = next_version();
     }
 
     /// Check if the buffer has been modified since last save
@@ -1228,6 +1249,14 @@ impl TextBuffer {
         self.modified
     }
 
+    /// A number that changes whenever the content does.
+    ///
+    /// Versions come from a process-wide counter, so a buffer replaced by a
+    /// freshly loaded one (on revert, say) never repeats an earlier version.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
     /// Clear the modified flag (after save)
     pub fn clear_modified(&mut self) {
         self.modified = false;
@@ -1263,6 +1292,45 @@ impl TextBuffer {
         self.saved_file_size
     }
 
+    /// Snapshot of where the buffer's text comes from, for reading it away
+    /// from the buffer (on another thread, say)
+    ///
+    /// Loaded text is copied, while unloaded regions are kept as file
+    /// offsets and read from disk when the snapshot is read.
+    pub fn content_snapshot(&self) -> ContentSnapshot {
+        let total = self.total_bytes();
+        let mut pieces = Vec::new();
+        for piece in self.piece_tree.iter_pieces_in_range(0, total) {
+            let Some(buffer) = self.buffers.get(piece.location.buffer_id()) else {
+                continue;
+            };
+            let source = match &buffer.data {
+                BufferData::Loaded { data, .. } => {
+                    let end = (piece.buffer_offset + piece.bytes).min(data.len());
+                    ContentSource::Bytes(data[piece.buffer_offset.min(end)..end].to_vec())
+                }
+                BufferData::Unloaded {
+                    file_path,
+                    file_offset,
+                    ..
+                } => ContentSource::File {
+                    path: file_path.clone(),
+                    offset: file_offset + piece.buffer_offset,
+                },
+            };
+            pieces.push(SnapshotPiece {
+                start: piece.doc_offset,
+                len: piece.bytes,
+                source,
+            });
+        }
+        ContentSnapshot {
+            pieces,
+            len: total,
+            open_file: None,
+        }
+    }
+
     /// Get recovery chunks for this buffer (only modified portions)
     ///
     /// For large files, this returns only the pieces that come from Added buffers
@@ -1516,6 +1584,37 @@ impl TextBuffer {
         Some(start)
     }
 
+    /// Byte offset where `line` starts, or the end of the buffer past its
+    /// last line
+    pub fn line_start(&self, line: usize) -> usize {
+        self.line_start_offset(line).unwrap_or(self.len())
+    }
+
+    /// Byte offset of the end of `line`'s content, before its line terminator
+    pub fn line_end(&self, line: usize) -> usize {
+        let content = self.get_line(line).unwrap_or_default();
+        let len = content
+            .iter()
+            .rposition(|&b| b != b'\n' && b != b'\r')
+            .map_or(0, |i| i + 1);
+        self.line_start(line) + len
+    }
+
+    /// Line containing byte offset `pos`
+    pub fn line_of(&self, pos: usize) -> usize {
+        self.position_to_line_col(pos).0
+    }
+
+    /// Index of the last line, ignoring the empty "line" after a final newline
+    pub fn last_line(&self) -> usize {
+        let last = self.line_count().unwrap_or(1).max(1) - 1;
+        if last > 0 && self.line_start(last) >= self.len() {
+            last - 1
+        } else {
+            last
+        }
+    }
+
     /// Get piece information at a byte offset
     pub fn piece_info_at_offset(&self, offset: usize) -> Option<PieceInfo> {
         self.piece_tree.find_by_offset(offset)
@@ -2191,6 +2290,78 @@ pub type Buffer = TextBuffer;
 // Re-export LineIterator from the line_iterator module
 pub use crate::primitives::line_iterator::LineIterator;
 
+// ============================================================================
+// Content Snapshots
+// ============================================================================
+
+/// Where the text of a snapshot piece is read from
+#[derive(Debug, Clone)]
+enum ContentSource {
+    /// Text that was loaded when the snapshot was taken
+    Bytes(Vec<u8>),
+    /// Text still on disk, starting at `offset` in the file
+    File { path: PathBuf, offset: usize },
+}
+
+#[derive(Debug, Clone)]
+struct SnapshotPiece {
+    /// Document offset of the piece
+    start: usize,
+    len: usize,
+    source: ContentSource,
+}
+
+/// A buffer's text as of `TextBuffer::content_snapshot`, readable without
+/// the buffer and without loading all of it at once
+#[derive(Debug)]
+pub struct ContentSnapshot {
+    pieces: Vec<SnapshotPiece>,
+    len: usize,
+    /// File last read from, kept open for the next read
+    open_file: Option<(PathBuf, std::fs::File)>,
+}
+
+impl ContentSnapshot {
+    /// Length of the text in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Read the text in `range`, clamped to the end of the text
+    pub fn read(&mut self, range: Range<usize>) -> io::Result<Vec<u8>> {
+        let end = range.end.min(self.len);
+        let mut out = Vec::with_capacity(end.saturating_sub(range.start));
+        let first = self
+            .pieces
+            .partition_point(|piece| piece.start + piece.len <= range.start);
+        for piece in &self.pieces[first..] {
+            if piece.start >= end {
+                break;
+            }
+            let from = range.start.max(piece.start) - piece.start;
+            let to = end.min(piece.start + piece.len) - piece.start;
+            match &piece.source {
+                ContentSource::Bytes(data) => out.extend_from_slice(&data[from..to]),
+                ContentSource::File { path, offset } => {
+                    if self.open_file.as_ref().is_none_or(|(open, _)| open != path) {
+                        self.open_file = Some((path.clone(), std::fs::File::open(path)?));
+                    }
+                    let (_, file) = self.open_file.as_mut().unwrap();
+                    file.seek(SeekFrom::Start((offset + from) as u64))?;
+                    let filled = out.len();
+                    out.resize(filled + (to - from), 0);
+                    file.read_exact(&mut out[filled..])?;
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
 // ============================================================================
 // Overlapping Chunks Iterator for Efficient Search
 // ============================================================================
@@ -2473,6 +2644,22 @@ mod tests {
         assert_eq!(buffer.get_all_text().unwrap(), b"hello\nworld");
     }
 
+    #[test]
+    fn test_version_changes_with_content() {
+        let mut buffer = TextBuffer::from_bytes(b"hello".to_vec());
+        let other = TextBuffer::from_bytes(b"hello".to_vec());
+        assert_ne!(buffer.version(), other.version());
+
+        let version = buffer.version();
+        buffer.clear_modified();
+        assert_eq!(buffer.version(), version);
+        buffer.insert_bytes(5, b"!".to_vec());
+        let inserted = buffer.version();
+        assert_ne!(inserted, version);
+        buffer.delete_bytes(0, 1);
+        assert_ne!(buffer.version(), inserted);
+    }
+
     #[test]
     fn test_insert_at_start() {
         let mut buffer = TextBuffer::from_bytes(b"world".to_vec());
@@ -2588,6 +2775,19 @@ mod tests {
         assert_eq!(buffer.get_line(3), None);
     }
 
+    #[test]
+    fn test_line_bounds() {
+        let buffer = TextBuffer::from_bytes(b"hello\r\nworld\n".to_vec());
+
+        assert_eq!(buffer.line_start(1), 7);
+        assert_eq!(buffer.line_end(0), 5);
+        assert_eq!(buffer.line_end(1), 12);
+        assert_eq!(buffer.line_of(9), 1);
+        // The empty line after the final newline isn't counted
+        assert_eq!(buffer.last_line(), 1);
+        assert_eq!(buffer.line_start(5), buffer.len());
+    }
+
     #[test]
     fn test_multiple_operations() {
         let mut buffer = TextBuffer::from_bytes(b"line1\nline2\nline3".to_vec());
@@ -3215,6 +3415,40 @@ mod tests {
                 "Length should be original + edits"
             );
         }
+
+        #[test]
+        fn test_content_snapshot_reads_loaded_and_unloaded_text() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("snapshot.txt");
+            let content: Vec<u8> = (0..3 * LOAD_CHUNK_SIZE)
+                .map(|i| b'a' + (i % 26) as u8)
+                .collect();
+            std::fs::write(&file_path, &content).unwrap();
+
+            let mut buffer = TextBuffer::load_from_file(&file_path, 1).unwrap();
+            // Load the middle chunk and edit inside it, leaving the rest on disk
+            buffer.get_text_range_mut(LOAD_CHUNK_SIZE, 10).unwrap();
+            buffer.insert_bytes(LOAD_CHUNK_SIZE + 5, b"EDIT".to_vec());
+            let mut expected = content.clone();
+            expected.splice(
+                LOAD_CHUNK_SIZE + 5..LOAD_CHUNK_SIZE + 5,
+                b"EDIT".iter().copied(),
+            );
+
+            let mut snapshot = buffer.content_snapshot();
+            assert_eq!(snapshot.len(), expected.len());
+            assert_eq!(snapshot.read(0..expected.len()).unwrap(), expected);
+            let around_edit = LOAD_CHUNK_SIZE - 3..LOAD_CHUNK_SIZE + 20;
+            assert_eq!(
+                snapshot.read(around_edit.clone()).unwrap(),
+                &expected[around_edit]
+            );
+            let tail = expected.len() - 5..expected.len() + 10;
+            assert_eq!(
+                snapshot.read(tail).unwrap(),
+                &expected[expected.len() - 5..]
+            );
+        }
     }
 
     // ===== Offset to Position Tests =====
@@ -236,6 +236,11 @@ impl Cursors {
         self.primary_id
     }
 
+    /// ID the next added cursor gets
+    pub fn next_id(&self) -> CursorId {
+        CursorId(self.next_id)
+    }
+
     /// Get a cursor by ID
     pub fn get(&self, id: CursorId) -> Option<&Cursor> {
         self.cursors.get(&id)
@@ -233,12 +233,26 @@ impl IntervalTree {
             None => return false,
         };
 
-        if !self.marker_map.contains_key(&id) {
-            return false;
-        }
+        let node_rc = match self.marker_map.get(&id) {
+            Some(node_rc) => Rc::clone(node_rc),
+            None => return false,
+        };
+
+        // A node with two children takes over its successor's marker, so the
+        // successor's ID must then map to this node
+        let successor_id = {
+            let node = node_rc.borrow();
+            match (&node.left, &node.right) {
+                (Some(_), Some(right)) => Some(Self::min_node(right).borrow().marker.id),
+                _ => None,
+            }
+        };
 
         self.root = Self::delete_recursive(self.root.take(), start, id);
 
+        if let Some(successor_id) = successor_id {
+            self.marker_map.insert(successor_id, node_rc);
+        }
         self.marker_map.remove(&id).is_some()
     }
 
@@ -381,14 +395,13 @@ impl IntervalTree {
         } else {
             let successor_rc = Self::min_node(&node.right.as_ref().unwrap());
 
-            let (successor_start, successor_id) = {
-                let s = successor_rc.borrow();
-                (s.marker.interval.start, s.marker.id)
-            };
+            // Move the successor's marker here, then remove the successor's
+            // node, which now holds the deleted marker
+            let (deleted_start, deleted_id) = (node.marker.interval.start, node.marker.id);
 
             mem::swap(&mut node.marker, &mut successor_rc.borrow_mut().marker);
 
-            node.right = Self::delete_recursive(node.right.take(), successor_start, successor_id);
+            node.right = Self::delete_recursive(node.right.take(), deleted_start, deleted_id);
 
             drop(node);
             Node::update_stats(&node_rc);
@@ -604,6 +617,31 @@ mod tests {
         assert_eq!(get_pos(&tree, id2), (30, 40));
     }
 
+    #[test]
+    fn test_delete_node_with_two_children() {
+        let mut tree = IntervalTree::new();
+        let ids: Vec<MarkerId> = (0..7)
+            .map(|i| insert_marker(&mut tree, i * 10, i * 10 + 5))
+            .collect();
+
+        // The middle markers sit on inner nodes of the balanced tree
+        for &deleted in &[ids[3], ids[1]] {
+            assert!(tree.delete(deleted));
+            assert_eq!(tree.get_position(deleted), None);
+        }
+
+        for (i, &id) in ids.iter().enumerate() {
+            if id == ids[3] || id == ids[1] {
+                continue;
+            }
+            let start = i as u64 * 10;
+            assert_eq!(get_pos(&tree, id), (start, start + 5));
+        }
+        let found: Vec<MarkerId> = tree.query(0, 100).into_iter().map(|m| m.id).collect();
+        assert_eq!(found.len(), 5);
+        assert!(!found.contains(&ids[3]) && !found.contains(&ids[1]));
+    }
+
     #[test]
     fn test_basic_edit_adjustment() {
         let mut tree = IntervalTree::new();
@@ -0,0 +1,115 @@
+//! Bracket matching
+//!
+//! Brackets are matched by counting: ones inside strings and comments count
+//! like any other. Searches read the buffer a chunk at a time, so a match
+//! far from the cursor doesn't load the whole file at once.
+
+use crate::model::buffer::Buffer;
+
+/// Bytes read from the buffer at a time while searching
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The pair `bracket` belongs to, as (open, close), and whether it opens it
+pub fn bracket_pair(bracket: u8) -> Option<(u8, u8, bool)> {
+    match bracket {
+        b'(' => Some((b'(', b')', true)),
+        b')' => Some((b'(', b')', false)),
+        b'[' => Some((b'[', b']', true)),
+        b']' => Some((b'[', b']', false)),
+        b'{' => Some((b'{', b'}', true)),
+        b'}' => Some((b'{', b'}', false)),
+        b'<' => Some((b'<', b'>', true)),
+        b'>' => Some((b'<', b'>', false)),
+        _ => None,
+    }
+}
+
+/// Position of the bracket matching the one at `pos`
+pub fn matching_bracket(buffer: &Buffer, pos: usize) -> Option<usize> {
+    if pos >= buffer.len() {
+        return None;
+    }
+    let bracket = *buffer.slice_bytes(pos..pos + 1).first()?;
+    let (open, close, forward) = bracket_pair(bracket)?;
+    if forward {
+        find_close(buffer, pos + 1, open, close)
+    } else {
+        find_open(buffer, pos, open, close)
+    }
+}
+
+/// The unmatched `close` at or after `from`
+pub fn find_close(buffer: &Buffer, from: usize, open: u8, close: u8) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut start = from;
+    while start < buffer.len() {
+        let end = (start + CHUNK_SIZE).min(buffer.len());
+        for (i, &b) in buffer.slice_bytes(start..end).iter().enumerate() {
+            if b == open {
+                depth += 1;
+            } else if b == close {
+                if depth == 0 {
+                    return Some(start + i);
+                }
+                depth -= 1;
+            }
+        }
+        start = end;
+    }
+    None
+}
+
+/// The unmatched `open` before `to`
+pub fn find_open(buffer: &Buffer, to: usize, open: u8, close: u8) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut end = to.min(buffer.len());
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK_SIZE);
+        for (i, &b) in buffer.slice_bytes(start..end).iter().enumerate().rev() {
+            if b == close {
+                depth += 1;
+            } else if b == open {
+                if depth == 0 {
+                    return Some(start + i);
+                }
+                depth -= 1;
+            }
+        }
+        end = start;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_bracket() {
+        let buffer = Buffer::from_str_test("f(a[0], {b}) <c>");
+        assert_eq!(matching_bracket(&buffer, 1), Some(11));
+        assert_eq!(matching_bracket(&buffer, 11), Some(1));
+        assert_eq!(matching_bracket(&buffer, 3), Some(5));
+        assert_eq!(matching_bracket(&buffer, 10), Some(8));
+        assert_eq!(matching_bracket(&buffer, 13), Some(15));
+        assert_eq!(matching_bracket(&buffer, 0), None);
+        assert_eq!(matching_bracket(&buffer, 99), None);
+    }
+
+    #[test]
+    fn test_find_unmatched() {
+        let buffer = Buffer::from_str_test("(a (b) c)");
+        assert_eq!(find_open(&buffer, 7, b'(', b')'), Some(0));
+        assert_eq!(find_close(&buffer, 1, b'(', b')'), Some(8));
+        assert_eq!(find_open(&buffer, 0, b'(', b')'), None);
+        assert_eq!(find_close(&buffer, 9, b'(', b')'), None);
+    }
+
+    #[test]
+    fn test_match_across_chunks() {
+        let text = format!("({})", "x".repeat(CHUNK_SIZE * 2));
+        let buffer = Buffer::from_str_test(&text);
+        assert_eq!(matching_bracket(&buffer, 0), Some(text.len() - 1));
+        assert_eq!(matching_bracket(&buffer, text.len() - 1), Some(0));
+    }
+}
@@ -0,0 +1,366 @@
+//! Line and block comment toggling
+//!
+//! The functions here look at the text being toggled and return the edits
+//! that comment or uncomment it, leaving the caller to apply them as one
+//! undoable action. Uncommenting recognizes both line comments and block
+//! comments, whichever the text uses.
+
+use std::ops::Range;
+
+/// Comment syntax of a language
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CommentTokens {
+    /// Line comment prefix (e.g. `//`)
+    pub line: Option<String>,
+    /// Block comment start and end (e.g. `/*` and `*/`)
+    pub block: Option<(String, String)>,
+}
+
+/// Replace `range` of the toggled text with `text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Edits that toggle comments, in document order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentToggle {
+    pub edits: Vec<CommentEdit>,
+    /// Whether the edits add comments (false when they remove them)
+    pub commented: bool,
+}
+
+impl CommentTokens {
+    fn new(line: Option<&str>, block: Option<(&str, &str)>) -> Self {
+        Self {
+            line: line.map(str::to_string),
+            block: block.map(|(start, end)| (start.to_string(), end.to_string())),
+        }
+    }
+
+    /// Built-in comment syntax for a language, by name (`javascript`, `css`)
+    /// or file extension (`js`, `scss`)
+    pub fn builtin(language: &str) -> Option<Self> {
+        const C_BLOCK: Option<(&str, &str)> = Some(("/*", "*/"));
+        const XML_BLOCK: Option<(&str, &str)> = Some(("<!--", "-->"));
+        let tokens = match language.to_ascii_lowercase().as_str() {
+            "rust" | "rs" | "c" | "h" | "cpp" | "c++" | "cc" | "cxx" | "hpp" | "hh" | "hxx"
+            | "javascript" | "js" | "jsx" | "mjs" | "typescript" | "ts" | "tsx" | "mts"
+            | "java" | "go" | "swift" | "kotlin" | "kt" | "scala" | "c_sharp" | "csharp" | "cs"
+            | "php" | "dart" | "scss" | "less" | "json" | "jsonc" | "zig" => {
+                Self::new(Some("//"), C_BLOCK)
+            }
+            "python" | "py" | "pyi" | "ruby" | "rb" | "bash" | "sh" | "zsh" | "shell" | "fish"
+            | "perl" | "pl" | "r" | "yaml" | "yml" | "toml" | "make" | "makefile" | "mk"
+            | "dockerfile" | "cmake" | "nix" | "elixir" | "ex" | "exs" | "ini" | "conf" => {
+                Self::new(Some("#"), None)
+            }
+            "lua" => Self::new(Some("--"), Some(("--[[", "]]"))),
+            "sql" => Self::new(Some("--"), C_BLOCK),
+            "haskell" | "hs" => Self::new(Some("--"), Some(("{-", "-}"))),
+            "html" | "htm" | "xml" | "svg" | "vue" | "markdown" | "md" => {
+                Self::new(None, XML_BLOCK)
+            }
+            "css" => Self::new(None, C_BLOCK),
+            "pascal" | "pas" => Self::new(Some("//"), Some(("{", "}"))),
+            "ocaml" | "ml" => Self::new(None, Some(("(*", "*)"))),
+            "vim" => Self::new(Some("\""), None),
+            "lisp" | "el" | "clj" | "clojure" | "scheme" | "scm" => Self::new(Some(";;"), None),
+            _ => return None,
+        };
+        Some(tokens)
+    }
+}
+
+/// A line of the toggled text, without its line ending
+struct Line<'a> {
+    /// Offset of the line in the toggled text
+    start: usize,
+    content: &'a str,
+    /// Length of the leading whitespace
+    indent: usize,
+}
+
+impl Line<'_> {
+    fn is_blank(&self) -> bool {
+        self.content.trim().is_empty()
+    }
+
+    /// The line without its indentation or trailing whitespace
+    fn body(&self) -> &str {
+        self.content[self.indent..].trim_end()
+    }
+}
+
+fn lines(text: &str) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for raw in text.split_inclusive('\n') {
+        let content = raw.trim_end_matches('\n').trim_end_matches('\r');
+        let indent = content.len() - content.trim_start_matches([' ', '\t']).len();
+        lines.push(Line {
+            start,
+            content,
+            indent,
+        });
+        start += raw.len();
+    }
+    lines
+}
+
+/// Remove `token` at `pos` of `text`, with one space after it
+fn remove_opening(text: &str, pos: usize, token: &str) -> Range<usize> {
+    let end = pos + token.len();
+    if text[end..].starts_with(' ') {
+        pos..end + 1
+    } else {
+        pos..end
+    }
+}
+
+/// Remove `token` ending at `end` of `text`, with one space before it,
+/// but nothing before `min`
+fn remove_closing(text: &str, end: usize, token: &str, min: usize) -> Range<usize> {
+    let start = end - token.len();
+    if start > min && text[..start].ends_with(' ') {
+        start - 1..end
+    } else {
+        start..end
+    }
+}
+
+fn removal(range: Range<usize>) -> CommentEdit {
+    CommentEdit {
+        range,
+        text: String::new(),
+    }
+}
+
+fn insertion(pos: usize, text: String) -> CommentEdit {
+    CommentEdit {
+        range: pos..pos,
+        text,
+    }
+}
+
+/// Whether `body` is wrapped in the block comment `start`..`end`
+fn is_block(body: &str, start: &str, end: &str) -> bool {
+    body.len() >= start.len() + end.len() && body.starts_with(start) && body.ends_with(end)
+}
+
+/// Edits that unwrap the block comment around `body`, which starts at `pos`
+fn unwrap_block(text: &str, pos: usize, body: &str, start: &str, end: &str) -> Vec<CommentEdit> {
+    let opening = remove_opening(text, pos, start);
+    let closing = remove_closing(text, pos + body.len(), end, opening.end);
+    let opening = opening.start..opening.end.min(closing.start);
+    vec![removal(opening), removal(closing)]
+}
+
+/// Edits that remove the line comment (or single-line block comment) of
+/// every non-blank line, or None if some line isn't commented
+fn uncomment_lines(text: &str, lines: &[Line], tokens: &CommentTokens) -> Option<Vec<CommentEdit>> {
+    let mut edits = Vec::new();
+    for line in lines.iter().filter(|line| !line.is_blank()) {
+        let pos = line.start + line.indent;
+        let body = line.body();
+        if let Some(prefix) = tokens.line.as_deref().filter(|p| body.starts_with(*p)) {
+            edits.push(removal(remove_opening(text, pos, prefix)));
+        } else if let Some((start, end)) = tokens
+            .block
+            .as_ref()
+            .filter(|(start, end)| is_block(body, start, end))
+        {
+            edits.extend(unwrap_block(text, pos, body, start, end));
+        } else {
+            return None;
+        }
+    }
+    Some(edits)
+}
+
+/// Comment or uncomment `text`, which holds whole lines
+///
+/// When every non-blank line is commented, with a line comment or a block
+/// comment of its own, they are all uncommented, and so is a block comment
+/// around the whole text. Otherwise the non-blank lines are commented with
+/// the line comment, or a block comment per line if the language has none,
+/// inserted at the smallest indentation among them so the markers line up.
+/// Returns None for blank text or a language without comments.
+pub fn toggle_line_comments(text: &str, tokens: &CommentTokens) -> Option<CommentToggle> {
+    let lines = lines(text);
+    if lines.iter().all(Line::is_blank) {
+        return None;
+    }
+
+    if let Some(edits) = uncomment_lines(text, &lines, tokens) {
+        return Some(CommentToggle {
+            edits,
+            commented: false,
+        });
+    }
+    if let Some(toggle) = unwrap_region(text, tokens) {
+        return Some(toggle);
+    }
+
+    let column = lines
+        .iter()
+        .filter(|line| !line.is_blank())
+        .map(|line| line.indent)
+        .min()
+        .unwrap_or(0);
+    let mut edits = Vec::new();
+    for line in lines.iter().filter(|line| !line.is_blank()) {
+        let pos = line.start + column;
+        if let Some(prefix) = &tokens.line {
+            edits.push(insertion(pos, format!("{} ", prefix)));
+        } else if let Some((start, end)) = &tokens.block {
+            edits.push(insertion(pos, format!("{} ", start)));
+            let body_end = line.start + line.indent + line.body().len();
+            edits.push(insertion(body_end, format!(" {}", end)));
+        } else {
+            return None;
+        }
+    }
+    Some(CommentToggle {
+        edits,
+        commented: true,
+    })
+}
+
+/// Edits that unwrap a block comment around all of `text` (ignoring
+/// surrounding whitespace)
+fn unwrap_region(text: &str, tokens: &CommentTokens) -> Option<CommentToggle> {
+    let (start, end) = tokens.block.as_ref()?;
+    let lead = text.len() - text.trim_start().len();
+    let body = text.trim();
+    if !is_block(body, start, end) {
+        return None;
+    }
+    Some(CommentToggle {
+        edits: unwrap_block(text, lead, body, start, end),
+        commented: false,
+    })
+}
+
+/// Wrap `text` in a block comment, or uncomment it when it already is one
+///
+/// Whitespace around the text stays outside the comment. Text made of line
+/// comments is uncommented too. Returns None for blank text or a language
+/// without block comments.
+pub fn toggle_block_comment(text: &str, tokens: &CommentTokens) -> Option<CommentToggle> {
+    let (start, end) = tokens.block.as_ref()?;
+    let body = text.trim();
+    if body.is_empty() {
+        return None;
+    }
+    if let Some(toggle) = unwrap_region(text, tokens) {
+        return Some(toggle);
+    }
+
+    let line_tokens = CommentTokens {
+        line: tokens.line.clone(),
+        block: None,
+    };
+    if line_tokens.line.is_some() {
+        if let Some(edits) = uncomment_lines(text, &lines(text), &line_tokens) {
+            return Some(CommentToggle {
+                edits,
+                commented: false,
+            });
+        }
+    }
+
+    let lead = text.len() - text.trim_start().len();
+    Some(CommentToggle {
+        edits: vec![
+            insertion(lead, format!("{} ", start)),
+            insertion(lead + body.len(), format!(" {}", end)),
+        ],
+        commented: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_edits(text: &str, edits: &[CommentEdit]) -> String {
+        let mut result = text.to_string();
+        for edit in edits.iter().rev() {
+            result.replace_range(edit.range.clone(), &edit.text);
+        }
+        result
+    }
+
+    fn toggle_lines(text: &str, language: &str) -> String {
+        let tokens = CommentTokens::builtin(language).unwrap();
+        match toggle_line_comments(text, &tokens) {
+            Some(toggle) => apply_edits(text, &toggle.edits),
+            None => text.to_string(),
+        }
+    }
+
+    fn toggle_block(text: &str, language: &str) -> String {
+        let tokens = CommentTokens::builtin(language).unwrap();
+        match toggle_block_comment(text, &tokens) {
+            Some(toggle) => apply_edits(text, &toggle.edits),
+            None => text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_line_comments_align_at_smallest_indent() {
+        let text = "    if x {\n\n        y();\n    }\n";
+        let commented = toggle_lines(text, "rust");
+        assert_eq!(commented, "    // if x {\n\n    //     y();\n    // }\n");
+        assert_eq!(toggle_lines(&commented, "rust"), text);
+
+        // Without a space after the prefix
+        assert_eq!(toggle_lines("#a\n  #b", "py"), "a\n  b");
+        // One uncommented line means commenting them all
+        assert_eq!(toggle_lines("// a\nb", "rs"), "// // a\n// b");
+        assert_eq!(toggle_lines("x = 1\r\n", "toml"), "# x = 1\r\n");
+    }
+
+    #[test]
+    fn test_line_comments_use_block_without_prefix() {
+        let text = "<p>\n  <b>hi</b>  \n</p>";
+        let commented = toggle_lines(text, "html");
+        assert_eq!(
+            commented,
+            "<!-- <p> -->\n<!--   <b>hi</b> -->  \n<!-- </p> -->"
+        );
+        assert_eq!(toggle_lines(&commented, "html"), text);
+        assert_eq!(toggle_lines("a {}", "css"), "/* a {} */");
+    }
+
+    #[test]
+    fn test_uncomment_recognizes_either_style() {
+        assert_eq!(toggle_lines("/* a */\n// b\n", "c"), "a\nb\n");
+        assert_eq!(toggle_lines("  /* a\n  b */\n", "c"), "  a\n  b\n");
+        assert_eq!(toggle_lines("/**/", "c"), "");
+        assert_eq!(toggle_block("  // a\n  // b", "js"), "  a\n  b");
+    }
+
+    #[test]
+    fn test_block_comments() {
+        assert_eq!(toggle_block("  x + y  ", "rust"), "  /* x + y */  ");
+        assert_eq!(toggle_block("  /* x + y */  ", "rust"), "  x + y  ");
+        assert_eq!(toggle_block("/*x*/", "rust"), "x");
+        assert_eq!(toggle_block("a\nb", "lua"), "--[[ a\nb ]]");
+        assert_eq!(toggle_block("<!--a-->", "md"), "a");
+        // No block comments in Python
+        assert_eq!(toggle_block("a", "python"), "a");
+        assert_eq!(toggle_block("   ", "rust"), "   ");
+    }
+
+    #[test]
+    fn test_builtin_tokens() {
+        let js = CommentTokens::builtin("JavaScript").unwrap();
+        assert_eq!(js.line.as_deref(), Some("//"));
+        assert_eq!(js.block, Some(("/*".to_string(), "*/".to_string())));
+        assert_eq!(CommentTokens::builtin("css").unwrap().line, None);
+        assert_eq!(CommentTokens::builtin("klingon"), None);
+    }
+}
@@ -17,6 +17,7 @@ use syntect::parsing::{SyntaxDefinition, SyntaxReference, SyntaxSet, SyntaxSetBu
 const TOML_GRAMMAR: &str = include_str!("../grammars/toml.sublime-syntax");
 
 /// Registry of all available TextMate grammars
+#[derive(Clone)]
 pub struct GrammarRegistry {
     /// Combined syntax set (built-in + embedded + user grammars)
     syntax_set: Arc<SyntaxSet>,
@@ -267,6 +268,22 @@ impl GrammarRegistry {
         self.syntax_set.find_syntax_by_scope(scope)
     }
 
+    /// Find syntax for a language token, such as a code fence info string or
+    /// an injected language name (`sql`, `toml`, `Python`)
+    ///
+    /// Checks user grammar extensions first, then built-in grammars by
+    /// extension and by case-insensitive name.
+    pub fn find_syntax_by_token(&self, token: &str) -> Option<&SyntaxReference> {
+        if let Some(syntax) = self
+            .user_extensions
+            .get(token)
+            .and_then(|scope| self.find_syntax_by_scope(scope))
+        {
+            return Some(syntax);
+        }
+        self.syntax_set.find_syntax_by_token(token)
+    }
+
     /// Find syntax by name
     pub fn find_syntax_by_name(&self, name: &str) -> Option<&SyntaxReference> {
         self.syntax_set.find_syntax_by_name(name)
@@ -384,6 +401,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_find_syntax_by_token() {
+        let registry = GrammarRegistry::load();
+
+        // By extension, by case-insensitive name, and embedded grammars
+        assert_eq!(registry.find_syntax_by_token("sql").unwrap().name, "SQL");
+        assert_eq!(
+            registry.find_syntax_by_token("python").unwrap().name,
+            "Python"
+        );
+        assert_eq!(registry.find_syntax_by_token("toml").unwrap().name, "TOML");
+        assert!(registry.find_syntax_by_token("no_such_language").is_none());
+    }
+
     #[test]
     fn test_syntax_set_arc() {
         let registry = GrammarRegistry::load();
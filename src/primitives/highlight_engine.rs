@@ -18,12 +18,16 @@
 
 use crate::model::buffer::Buffer;
 use crate::primitives::grammar_registry::GrammarRegistry;
-use crate::primitives::highlighter::{HighlightCategory, HighlightSpan, Highlighter, Language};
+use crate::primitives::highlighter::{
+    fenced_code_blocks, overlay_spans, CachedSpan, HighlightCategory, HighlightSpan, Highlighter,
+    Language,
+};
 use crate::view::theme::Theme;
 use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
-use syntect::parsing::SyntaxSet;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use tree_sitter::Tree;
 
 /// Map TextMate scope to highlight category
 fn scope_to_category(scope: &str) -> Option<HighlightCategory> {
@@ -167,6 +171,127 @@ fn scope_to_category(scope: &str) -> Option<HighlightCategory> {
     None
 }
 
+/// Highlight `text` with a TextMate grammar, as if it started at byte `offset`
+/// of the buffer
+pub(crate) fn highlight_text(
+    syntax_set: &SyntaxSet,
+    syntax: &SyntaxReference,
+    text: &str,
+    offset: usize,
+) -> Vec<CachedSpan> {
+    use syntect::parsing::{ParseState, ScopeStack};
+
+    let mut state = ParseState::new(syntax);
+    let mut spans = Vec::new();
+
+    // Parse line by line - manually track line boundaries to handle CRLF correctly
+    // str::lines() strips both \n and \r\n, losing the distinction
+    let content_bytes = text.as_bytes();
+    let mut pos = 0;
+    let mut current_offset = offset;
+    let mut current_scopes = ScopeStack::new();
+
+    while pos < content_bytes.len() {
+        let line_start = pos;
+        let mut line_end = pos;
+
+        // Scan for line ending (find \n or \r\n or end of content)
+        while line_end < content_bytes.len() {
+            if content_bytes[line_end] == b'\n' {
+                line_end += 1;
+                break;
+            } else if content_bytes[line_end] == b'\r' {
+                if line_end + 1 < content_bytes.len() && content_bytes[line_end + 1] == b'\n' {
+                    line_end += 2; // CRLF
+                } else {
+                    line_end += 1; // CR only
+                }
+                break;
+            }
+            line_end += 1;
+        }
+
+        // Get the line content and actual byte length
+        let line_bytes = &content_bytes[line_start..line_end];
+        let actual_line_byte_len = line_bytes.len();
+
+        // Create line string for syntect - strip CR if present, ensure single \n
+        let line_str = match std::str::from_utf8(line_bytes) {
+            Ok(s) => s,
+            Err(_) => {
+                pos = line_end;
+                current_offset += actual_line_byte_len;
+                continue;
+            }
+        };
+
+        // Remove trailing \r\n or \n, then add single \n for syntect
+        let line_content = line_str.trim_end_matches(&['\r', '\n'][..]);
+        let line_for_syntect = if line_end < content_bytes.len() || line_str.ends_with('\n') {
+            format!("{}\n", line_content)
+        } else {
+            line_content.to_string()
+        };
+
+        let ops = match state.parse_line(&line_for_syntect, syntax_set) {
+            Ok(ops) => ops,
+            Err(_) => {
+                pos = line_end;
+                current_offset += actual_line_byte_len;
+                continue;
+            }
+        };
+
+        // Convert operations to spans
+        // Note: syntect offsets are relative to line_for_syntect, but we need
+        // to map them to the actual buffer positions
+        let mut syntect_offset = 0;
+        let line_content_len = line_content.len();
+
+        for (op_offset, op) in ops {
+            // Handle any text before this operation (but only within content, not newline)
+            let clamped_op_offset = op_offset.min(line_content_len);
+            if clamped_op_offset > syntect_offset {
+                if let Some(category) = TextMateEngine::scope_stack_to_category(&current_scopes) {
+                    let byte_start = current_offset + syntect_offset;
+                    let byte_end = current_offset + clamped_op_offset;
+                    if byte_start < byte_end {
+                        spans.push(CachedSpan {
+                            range: byte_start..byte_end,
+                            category,
+                        });
+                    }
+                }
+            }
+            syntect_offset = clamped_op_offset;
+
+            let _ = current_scopes.apply(&op);
+        }
+
+        // Handle remaining text on line (content only, not line ending)
+        if syntect_offset < line_content_len {
+            if let Some(category) = TextMateEngine::scope_stack_to_category(&current_scopes) {
+                let byte_start = current_offset + syntect_offset;
+                let byte_end = current_offset + line_content_len;
+                if byte_start < byte_end {
+                    spans.push(CachedSpan {
+                        range: byte_start..byte_end,
+                        category,
+                    });
+                }
+            }
+        }
+
+        // Advance by actual byte length (including real line terminator)
+        pos = line_end;
+        current_offset += actual_line_byte_len;
+    }
+
+    // Merge adjacent spans
+    TextMateEngine::merge_adjacent_spans(&mut spans);
+    spans
+}
+
 /// Preference for which highlighting backend to use
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum HighlighterPreference {
@@ -202,6 +327,8 @@ pub struct TextMateEngine {
     /// Tree-sitter language for non-highlighting features (indentation, semantic highlighting)
     /// Even when using syntect for highlighting, we track the language for other features
     ts_language: Option<Language>,
+    /// Grammars for the languages of Markdown code fences
+    registry: Option<GrammarRegistry>,
 }
 
 #[derive(Debug, Clone)]
@@ -210,12 +337,6 @@ struct TextMateCache {
     spans: Vec<CachedSpan>,
 }
 
-#[derive(Debug, Clone)]
-struct CachedSpan {
-    range: Range<usize>,
-    category: crate::primitives::highlighter::HighlightCategory,
-}
-
 /// Maximum bytes to parse in a single operation
 const MAX_PARSE_BYTES: usize = 1024 * 1024;
 
@@ -228,6 +349,7 @@ impl TextMateEngine {
             cache: None,
             last_buffer_len: 0,
             ts_language: None,
+            registry: None,
         }
     }
 
@@ -243,9 +365,15 @@ impl TextMateEngine {
             cache: None,
             last_buffer_len: 0,
             ts_language,
+            registry: None,
         }
     }
 
+    /// Resolve embedded languages (Markdown code fences) through `registry`
+    pub fn set_grammar_registry(&mut self, registry: &GrammarRegistry) {
+        self.registry = Some(registry.clone());
+    }
+
     /// Get the tree-sitter language (for indentation, semantic highlighting, etc.)
     pub fn language(&self) -> Option<&Language> {
         self.ts_language.as_ref()
@@ -263,8 +391,6 @@ impl TextMateEngine {
         theme: &Theme,
         context_bytes: usize,
     ) -> Vec<HighlightSpan> {
-        use syntect::parsing::{ParseState, ScopeStack};
-
         // Check cache validity
         if let Some(cache) = &self.cache {
             if cache.range.start <= viewport_start
@@ -293,10 +419,6 @@ impl TextMateEngine {
             return Vec::new();
         }
 
-        let syntax = &self.syntax_set.syntaxes()[self.syntax_index];
-        let mut state = ParseState::new(syntax);
-        let mut spans = Vec::new();
-
         // Get content
         let content = buffer.slice_bytes(parse_start..parse_end);
         let content_str = match std::str::from_utf8(&content) {
@@ -304,112 +426,35 @@ impl TextMateEngine {
             Err(_) => return Vec::new(),
         };
 
-        // Parse line by line - manually track line boundaries to handle CRLF correctly
-        // str::lines() strips both \n and \r\n, losing the distinction
-        let content_bytes = content_str.as_bytes();
-        let mut pos = 0;
-        let mut current_offset = parse_start;
-        let mut current_scopes = ScopeStack::new();
-
-        while pos < content_bytes.len() {
-            let line_start = pos;
-            let mut line_end = pos;
-
-            // Scan for line ending (find \n or \r\n or end of content)
-            while line_end < content_bytes.len() {
-                if content_bytes[line_end] == b'\n' {
-                    line_end += 1;
-                    break;
-                } else if content_bytes[line_end] == b'\r' {
-                    if line_end + 1 < content_bytes.len() && content_bytes[line_end + 1] == b'\n' {
-                        line_end += 2; // CRLF
-                    } else {
-                        line_end += 1; // CR only
-                    }
-                    break;
-                }
-                line_end += 1;
-            }
-
-            // Get the line content and actual byte length
-            let line_bytes = &content_bytes[line_start..line_end];
-            let actual_line_byte_len = line_bytes.len();
-
-            // Create line string for syntect - strip CR if present, ensure single \n
-            let line_str = match std::str::from_utf8(line_bytes) {
-                Ok(s) => s,
-                Err(_) => {
-                    pos = line_end;
-                    current_offset += actual_line_byte_len;
-                    continue;
-                }
-            };
-
-            // Remove trailing \r\n or \n, then add single \n for syntect
-            let line_content = line_str.trim_end_matches(&['\r', '\n'][..]);
-            let line_for_syntect = if line_end < content_bytes.len() || line_str.ends_with('\n') {
-                format!("{}\n", line_content)
-            } else {
-                line_content.to_string()
-            };
-
-            let ops = match state.parse_line(&line_for_syntect, &self.syntax_set) {
-                Ok(ops) => ops,
-                Err(_) => {
-                    pos = line_end;
-                    current_offset += actual_line_byte_len;
-                    continue;
-                }
-            };
-
-            // Convert operations to spans
-            // Note: syntect offsets are relative to line_for_syntect, but we need
-            // to map them to the actual buffer positions
-            let mut syntect_offset = 0;
-            let line_content_len = line_content.len();
-
-            for (op_offset, op) in ops {
-                // Handle any text before this operation (but only within content, not newline)
-                let clamped_op_offset = op_offset.min(line_content_len);
-                if clamped_op_offset > syntect_offset {
-                    if let Some(category) = Self::scope_stack_to_category(&current_scopes) {
-                        let byte_start = current_offset + syntect_offset;
-                        let byte_end = current_offset + clamped_op_offset;
-                        if byte_start < byte_end {
-                            spans.push(CachedSpan {
-                                range: byte_start..byte_end,
-                                category,
-                            });
-                        }
-                    }
-                }
-                syntect_offset = clamped_op_offset;
-
-                let _ = current_scopes.apply(&op);
-            }
-
-            // Handle remaining text on line (content only, not line ending)
-            if syntect_offset < line_content_len {
-                if let Some(category) = Self::scope_stack_to_category(&current_scopes) {
-                    let byte_start = current_offset + syntect_offset;
-                    let byte_end = current_offset + line_content_len;
-                    if byte_start < byte_end {
-                        spans.push(CachedSpan {
-                            range: byte_start..byte_end,
-                            category,
-                        });
-                    }
+        let syntax = &self.syntax_set.syntaxes()[self.syntax_index];
+        let mut spans = highlight_text(&self.syntax_set, syntax, content_str, parse_start);
+
+        // Code fences in Markdown are highlighted with the grammar their info
+        // string names, including grammars the Markdown syntax doesn't embed
+        if let Some(registry) = &self.registry {
+            if syntax
+                .scope
+                .build_string()
+                .starts_with("text.html.markdown")
+            {
+                for block in fenced_code_blocks(content_str) {
+                    let Some(embedded) = block
+                        .language()
+                        .and_then(|token| registry.find_syntax_by_token(token))
+                    else {
+                        continue;
+                    };
+                    let injected = highlight_text(
+                        registry.syntax_set(),
+                        embedded,
+                        &content_str[block.body.clone()],
+                        parse_start + block.body.start,
+                    );
+                    spans = overlay_spans(spans, injected);
                 }
             }
-
-            // Advance by actual byte length (including real line terminator)
-            pos = line_end;
-            current_offset += actual_line_byte_len;
         }
 
-        // Merge adjacent spans
-        Self::merge_adjacent_spans(&mut spans);
-
         // Update cache
         self.cache = Some(TextMateCache {
             range: parse_start..parse_end,
@@ -504,7 +549,8 @@ impl HighlightEngine {
             }
             HighlighterPreference::TreeSitter => {
                 if let Some(lang) = Language::from_path(path) {
-                    if let Ok(highlighter) = Highlighter::new(lang) {
+                    if let Ok(mut highlighter) = Highlighter::new(lang) {
+                        highlighter.set_grammar_registry(registry);
                         return Self::TreeSitter(highlighter);
                     }
                 }
@@ -528,18 +574,17 @@ impl HighlightEngine {
                 .iter()
                 .position(|s| s.name == syntax.name)
             {
-                return Self::TextMate(TextMateEngine::with_language(
-                    syntax_set,
-                    index,
-                    ts_language,
-                ));
+                let mut engine = TextMateEngine::with_language(syntax_set, index, ts_language);
+                engine.set_grammar_registry(registry);
+                return Self::TextMate(engine);
             }
         }
 
         // No TextMate grammar found - fall back to tree-sitter if available
         // This handles languages like TypeScript that syntect doesn't include by default
         if let Some(lang) = ts_language {
-            if let Ok(highlighter) = Highlighter::new(lang) {
+            if let Ok(mut highlighter) = Highlighter::new(lang) {
+                highlighter.set_grammar_registry(registry);
                 tracing::debug!(
                     "No TextMate grammar for {:?}, falling back to tree-sitter",
                     path.extension()
@@ -555,6 +600,8 @@ impl HighlightEngine {
     ///
     /// `context_bytes` controls how far before/after the viewport to parse for accurate
     /// highlighting of multi-line constructs (strings, comments, nested blocks).
+    /// `tree` is the buffer's full-document syntax tree, if it has one; the
+    /// tree-sitter backend highlights from it instead of parsing the window.
     pub fn highlight_viewport(
         &mut self,
         buffer: &Buffer,
@@ -562,11 +609,17 @@ impl HighlightEngine {
         viewport_end: usize,
         theme: &Theme,
         context_bytes: usize,
+        tree: Option<&Tree>,
     ) -> Vec<HighlightSpan> {
         match self {
-            Self::TreeSitter(h) => {
-                h.highlight_viewport(buffer, viewport_start, viewport_end, theme, context_bytes)
-            }
+            Self::TreeSitter(h) => h.highlight_viewport(
+                buffer,
+                viewport_start,
+                viewport_end,
+                theme,
+                context_bytes,
+                tree,
+            ),
             Self::TextMate(h) => {
                 h.highlight_viewport(buffer, viewport_start, viewport_end, theme, context_bytes)
             }
@@ -797,4 +850,20 @@ mod tests {
             panic!("Expected TextMate engine for .java file");
         }
     }
+
+    #[test]
+    fn test_markdown_fence_uses_registry_grammar() {
+        let registry = GrammarRegistry::load();
+        let mut engine = HighlightEngine::for_file(Path::new("README.md"), &registry);
+        let content = "# Config\n\n```toml\n# a comment\nkey = 1\n```\n";
+        let buffer = Buffer::from_str(content, 0);
+        let theme = Theme::default();
+
+        let spans = engine.highlight_viewport(&buffer, 0, content.len(), &theme, 0, None);
+        // Syntect's Markdown has no TOML; the fence body comes from the TOML grammar
+        let comment = content.find("# a comment").unwrap();
+        assert!(spans
+            .iter()
+            .any(|s| s.range.contains(&comment) && s.color == theme.syntax_comment));
+    }
 }
@@ -1,9 +1,16 @@
 //! Syntax highlighting with tree-sitter
 //!
 //! # Design
-//! - **Viewport-only parsing**: Only highlights visible lines for instant performance with large files
+//! - **Full-document tree when available**: Buffers with a persistent `SyntaxTree` are
+//!   highlighted by running the highlights query on that tree
+//! - **Viewport-only parsing**: Otherwise only highlights visible lines for instant performance with large files
 //! - **Incremental updates**: Re-parses only edited regions
 //! - **Lazy initialization**: Parsing happens on first render
+//! - **Injections**: Each language's `injections.scm` marks ranges written in another
+//!   language (HTML scripts, doc tests in Rust doc comments, SQL strings). With a
+//!   full-document tree they are parsed on their own and layered over the host's
+//!   highlights, falling back to TextMate grammars for languages without a
+//!   tree-sitter grammar; the viewport-only path handles tree-sitter languages only
 //!
 //! # Performance
 //! Must work instantly when loading a 1GB file and jumping to an arbitrary offset.
@@ -11,16 +18,66 @@
 
 use crate::config::LARGE_FILE_THRESHOLD_BYTES;
 use crate::model::buffer::Buffer;
+use crate::primitives::grammar_registry::GrammarRegistry;
+use crate::primitives::highlight_engine::highlight_text;
 use crate::view::theme::Theme;
 use ratatui::style::Color;
+use std::collections::HashMap;
 use std::ops::Range;
+use tree_sitter::{Parser, Point, Query, QueryCursor, StreamingIterator, Tree};
 use tree_sitter_highlight::{HighlightConfiguration, HighlightEvent, Highlighter as TSHighlighter};
 
 /// Maximum bytes to parse in a single operation (for viewport highlighting)
 const MAX_PARSE_BYTES: usize = LARGE_FILE_THRESHOLD_BYTES as usize; // 1MB
 
+/// How deeply injections may nest (HTML → script → tagged template → ...)
+const MAX_INJECTION_DEPTH: usize = 4;
+
+/// Highlight names recognized for default languages, in `HighlightCategory::from_default_index` order
+const HIGHLIGHT_NAMES: &[&str] = &[
+    "attribute",
+    "comment",
+    "constant",
+    "function",
+    "keyword",
+    "number",
+    "operator",
+    "property",
+    "string",
+    "type",
+    "variable",
+];
+
+/// Highlight names recognized for TypeScript, in `HighlightCategory::from_typescript_index` order.
+/// Must include all captures from both the JavaScript and TypeScript queries.
+const TYPESCRIPT_HIGHLIGHT_NAMES: &[&str] = &[
+    "attribute",
+    "comment",
+    "constant",
+    "constant.builtin",
+    "constructor",
+    "embedded",
+    "function",
+    "function.builtin",
+    "function.method",
+    "keyword",
+    "number",
+    "operator",
+    "property",
+    "punctuation.bracket",
+    "punctuation.delimiter",
+    "punctuation.special",
+    "string",
+    "string.special",
+    "type",
+    "type.builtin",
+    "variable",
+    "variable.builtin",
+    "variable.parameter",
+];
+
 /// Highlight category names used for default languages.
-/// The order matches the `configure()` call in `highlight_config()`.
+/// The order matches `HIGHLIGHT_NAMES`.
 /// Index 0 = attribute, 1 = comment, 2 = constant, 3 = function, 4 = keyword,
 /// 5 = number, 6 = operator, 7 = property, 8 = string, 9 = type, 10 = variable
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -117,15 +174,15 @@ pub struct HighlightSpan {
 
 /// Internal span used for caching (stores category instead of color)
 #[derive(Debug, Clone)]
-struct CachedSpan {
+pub(crate) struct CachedSpan {
     /// Byte range in the buffer
-    range: Range<usize>,
+    pub(crate) range: Range<usize>,
     /// Highlight category for this span
-    category: HighlightCategory,
+    pub(crate) category: HighlightCategory,
 }
 
 /// Language configuration for syntax highlighting
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Language {
     Rust,
     Python,
@@ -173,33 +230,104 @@ impl Language {
         }
     }
 
+    /// Resolve a language name as written in injection queries and code fence
+    /// info strings (`javascript`, `js`, `c++`, ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Some(Language::Rust),
+            "python" | "py" | "python3" => Some(Language::Python),
+            "javascript" | "js" | "jsx" => Some(Language::JavaScript),
+            "typescript" | "ts" | "tsx" => Some(Language::TypeScript),
+            "html" | "htm" => Some(Language::HTML),
+            "css" => Some(Language::CSS),
+            "c" | "h" => Some(Language::C),
+            "cpp" | "c++" | "cc" | "cxx" => Some(Language::Cpp),
+            "go" | "golang" => Some(Language::Go),
+            "json" => Some(Language::Json),
+            "java" => Some(Language::Java),
+            "c_sharp" | "csharp" | "cs" | "c#" => Some(Language::CSharp),
+            "php" => Some(Language::Php),
+            "ruby" | "rb" => Some(Language::Ruby),
+            "bash" | "sh" | "shell" | "zsh" => Some(Language::Bash),
+            "lua" => Some(Language::Lua),
+            "pascal" | "pas" => Some(Language::Pascal),
+            _ => None,
+        }
+    }
+
+    /// Canonical name of this language (accepted by `from_name`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+            Language::HTML => "html",
+            Language::CSS => "css",
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::Go => "go",
+            Language::Json => "json",
+            Language::Java => "java",
+            Language::CSharp => "c_sharp",
+            Language::Php => "php",
+            Language::Ruby => "ruby",
+            Language::Bash => "bash",
+            Language::Lua => "lua",
+            Language::Pascal => "pascal",
+        }
+    }
+
+    /// Get the tree-sitter grammar for this language
+    pub fn tree_sitter_language(&self) -> tree_sitter::Language {
+        match self {
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+            Language::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Language::HTML => tree_sitter_html::LANGUAGE.into(),
+            Language::CSS => tree_sitter_css::LANGUAGE.into(),
+            Language::C => tree_sitter_c::LANGUAGE.into(),
+            Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+            Language::Go => tree_sitter_go::LANGUAGE.into(),
+            Language::Json => tree_sitter_json::LANGUAGE.into(),
+            Language::Java => tree_sitter_java::LANGUAGE.into(),
+            Language::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
+            Language::Php => tree_sitter_php::LANGUAGE_PHP.into(),
+            Language::Ruby => tree_sitter_ruby::LANGUAGE.into(),
+            Language::Bash => tree_sitter_bash::LANGUAGE.into(),
+            Language::Lua => tree_sitter_lua::LANGUAGE.into(),
+            Language::Pascal => tree_sitter_pascal::LANGUAGE.into(),
+        }
+    }
+
+    /// Bundled `injections.scm` query, marking ranges written in another language
+    fn injections_query(&self) -> &'static str {
+        match self {
+            Language::Rust => include_str!("../../queries/rust/injections.scm"),
+            Language::Python => include_str!("../../queries/python/injections.scm"),
+            Language::JavaScript => include_str!("../../queries/javascript/injections.scm"),
+            Language::TypeScript => include_str!("../../queries/typescript/injections.scm"),
+            Language::HTML => include_str!("../../queries/html/injections.scm"),
+            _ => "",
+        }
+    }
+
     /// Get tree-sitter highlight configuration for this language
-    fn highlight_config(&self) -> Result<HighlightConfiguration, String> {
+    fn highlight_config(&self, injections_query: &str) -> Result<HighlightConfiguration, String> {
         match self {
             Language::Rust => {
                 let mut config = HighlightConfiguration::new(
                     tree_sitter_rust::LANGUAGE.into(),
                     "rust",
                     tree_sitter_rust::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create Rust highlight config: {e}"))?;
 
                 // Configure highlight names
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -208,25 +336,13 @@ impl Language {
                     tree_sitter_python::LANGUAGE.into(),
                     "python",
                     tree_sitter_python::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create Python highlight config: {e}"))?;
 
                 // Configure highlight names
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -235,25 +351,13 @@ impl Language {
                     tree_sitter_javascript::LANGUAGE.into(),
                     "javascript",
                     tree_sitter_javascript::HIGHLIGHT_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create JavaScript highlight config: {e}"))?;
 
                 // Configure highlight names
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -271,37 +375,12 @@ impl Language {
                     tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
                     "typescript",
                     &combined_highlights,
-                    "",                                   // injections query
+                    injections_query,
                     tree_sitter_typescript::LOCALS_QUERY, // locals query for proper scoping
                 )
                 .map_err(|e| format!("Failed to create TypeScript highlight config: {e}"))?;
 
-                // Configure highlight names - must include all captures from both JS and TS queries
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "constant.builtin",
-                    "constructor",
-                    "embedded",
-                    "function",
-                    "function.builtin",
-                    "function.method",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "punctuation.bracket",
-                    "punctuation.delimiter",
-                    "punctuation.special",
-                    "string",
-                    "string.special",
-                    "type",
-                    "type.builtin",
-                    "variable",
-                    "variable.builtin",
-                    "variable.parameter",
-                ]);
+                config.configure(TYPESCRIPT_HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -310,24 +389,12 @@ impl Language {
                     tree_sitter_html::LANGUAGE.into(),
                     "html",
                     tree_sitter_html::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create HTML highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -336,24 +403,12 @@ impl Language {
                     tree_sitter_css::LANGUAGE.into(),
                     "css",
                     tree_sitter_css::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create CSS highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -362,24 +417,12 @@ impl Language {
                     tree_sitter_c::LANGUAGE.into(),
                     "c",
                     tree_sitter_c::HIGHLIGHT_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create C highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -388,24 +431,12 @@ impl Language {
                     tree_sitter_cpp::LANGUAGE.into(),
                     "cpp",
                     tree_sitter_cpp::HIGHLIGHT_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create C++ highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -414,24 +445,12 @@ impl Language {
                     tree_sitter_go::LANGUAGE.into(),
                     "go",
                     tree_sitter_go::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create Go highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -440,24 +459,12 @@ impl Language {
                     tree_sitter_json::LANGUAGE.into(),
                     "json",
                     tree_sitter_json::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create JSON highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -466,24 +473,12 @@ impl Language {
                     tree_sitter_java::LANGUAGE.into(),
                     "java",
                     tree_sitter_java::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create Java highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -494,24 +489,12 @@ impl Language {
                     tree_sitter_c_sharp::LANGUAGE.into(),
                     "c_sharp",
                     "", // No HIGHLIGHTS_QUERY exported in 0.23.1
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create C# highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -520,24 +503,12 @@ impl Language {
                     tree_sitter_php::LANGUAGE_PHP.into(),
                     "php",
                     tree_sitter_php::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create PHP highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -546,24 +517,12 @@ impl Language {
                     tree_sitter_ruby::LANGUAGE.into(),
                     "ruby",
                     tree_sitter_ruby::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create Ruby highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -572,24 +531,12 @@ impl Language {
                     tree_sitter_bash::LANGUAGE.into(),
                     "bash",
                     tree_sitter_bash::HIGHLIGHT_QUERY, // Note: singular, not plural
-                    "",                                // injections query
-                    "",                                // locals query
+                    injections_query,
+                    "", // locals query
                 )
                 .map_err(|e| format!("Failed to create Bash highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -598,24 +545,12 @@ impl Language {
                     tree_sitter_lua::LANGUAGE.into(),
                     "lua",
                     tree_sitter_lua::HIGHLIGHTS_QUERY,
-                    "", // injections query
+                    injections_query,
                     "", // locals query
                 )
                 .map_err(|e| format!("Failed to create Lua highlight config: {e}"))?;
 
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             }
@@ -630,25 +565,13 @@ impl Language {
                     tree_sitter_pascal::LANGUAGE.into(),
                     "pascal",
                     "", // No highlights query - syntect handles highlighting
-                    "", // injections query
+                    injections_query,
                     locals_query,
                 )
                 .map_err(|e| format!("Failed to create Pascal highlight config: {e}"))?;
 
                 // Configure highlight names (even though we don't use highlights query)
-                config.configure(&[
-                    "attribute",
-                    "comment",
-                    "constant",
-                    "function",
-                    "keyword",
-                    "number",
-                    "operator",
-                    "property",
-                    "string",
-                    "type",
-                    "variable",
-                ]);
+                config.configure(HIGHLIGHT_NAMES);
 
                 Ok(config)
             } // Language::Markdown => {
@@ -665,6 +588,34 @@ impl Language {
             _ => HighlightCategory::from_default_index(index),
         }
     }
+
+    /// Highlight names passed to `configure()` for this language
+    fn highlight_names(&self) -> &'static [&'static str] {
+        match self {
+            Language::TypeScript => TYPESCRIPT_HIGHLIGHT_NAMES,
+            _ => HIGHLIGHT_NAMES,
+        }
+    }
+
+    /// Map a highlights-query capture name to a category
+    ///
+    /// Uses the same matching rule as `HighlightConfiguration::configure()`: the
+    /// recognized name with the most dot-separated parts, all of which appear in
+    /// the capture name, wins (`function.method.builtin` matches `function.method`).
+    fn capture_category(&self, capture_name: &str) -> Option<HighlightCategory> {
+        let capture_parts: Vec<&str> = capture_name.split('.').collect();
+        let mut best_index = None;
+        let mut best_len = 0;
+        for (index, name) in self.highlight_names().iter().enumerate() {
+            let len = name.split('.').count();
+            let matches = name.split('.').all(|part| capture_parts.contains(&part));
+            if matches && len > best_len {
+                best_index = Some(index);
+                best_len = len;
+            }
+        }
+        best_index.and_then(|index| self.highlight_category(index))
+    }
 }
 
 /// Cache of highlighted spans for a specific byte range
@@ -684,6 +635,16 @@ pub struct Highlighter {
     language: Language,
     /// Highlight configuration for the language
     config: HighlightConfiguration,
+    /// Category for each capture of the highlights query (for highlighting from a syntax tree)
+    capture_categories: Vec<Option<HighlightCategory>>,
+    /// The injections query on its own: `config.query` leaves out the
+    /// `injection.combined` patterns (tree-sitter-highlight runs those apart)
+    injections_query: Option<Query>,
+    /// Highlighters for languages injected into this one, created on first use
+    /// (`None` if the language failed to load)
+    injections: HashMap<Language, Option<Highlighter>>,
+    /// TextMate grammars for injected languages without a tree-sitter grammar
+    registry: Option<GrammarRegistry>,
     /// Cache of highlighted spans (only for visible viewport)
     cache: Option<HighlightCache>,
     /// Last known buffer length (for detecting complete buffer changes)
@@ -693,22 +654,56 @@ pub struct Highlighter {
 impl Highlighter {
     /// Create a new highlighter for the given language
     pub fn new(language: Language) -> Result<Self, String> {
-        let config = language.highlight_config()?;
+        // A broken injections query shouldn't cost the language its highlighting
+        let config = language
+            .highlight_config(language.injections_query())
+            .or_else(|e| {
+                tracing::warn!("{}; highlighting {:?} without injections", e, language);
+                language.highlight_config("")
+            })?;
+        let capture_categories = config
+            .query
+            .capture_names()
+            .iter()
+            .map(|name| language.capture_category(name))
+            .collect();
+        let injections_query = Query::new(
+            &language.tree_sitter_language(),
+            language.injections_query(),
+        )
+        .ok()
+        .filter(|query| query.pattern_count() > 0);
         Ok(Self {
             ts_highlighter: TSHighlighter::new(),
             language,
             config,
+            capture_categories,
+            injections_query,
+            injections: HashMap::new(),
+            registry: None,
             cache: None,
             last_buffer_len: 0,
         })
     }
 
+    /// Use the TextMate grammars of `registry` for injected languages that
+    /// have no tree-sitter grammar (e.g. SQL)
+    pub fn set_grammar_registry(&mut self, registry: &GrammarRegistry) {
+        self.registry = Some(registry.clone());
+        for highlighter in self.injections.values_mut().flatten() {
+            highlighter.set_grammar_registry(registry);
+        }
+    }
+
     /// Highlight the visible viewport range
     ///
-    /// This only parses the visible lines for instant performance with large files.
     /// Returns highlighted spans for the requested byte range, colored according to the theme.
     ///
-    /// `context_bytes` controls how far before/after the viewport to parse for accurate
+    /// With a full-document `tree` (see `SyntaxTree`), the highlights query runs
+    /// on that tree, so constructs starting far above the viewport are highlighted
+    /// correctly. Without one (large files, first parse still running), only the
+    /// visible lines are parsed for instant performance, and `context_bytes`
+    /// controls how far before/after the viewport to parse for accurate
     /// highlighting of multi-line constructs (strings, comments, nested blocks).
     pub fn highlight_viewport(
         &mut self,
@@ -717,6 +712,7 @@ impl Highlighter {
         viewport_end: usize,
         theme: &Theme,
         context_bytes: usize,
+        tree: Option<&Tree>,
     ) -> Vec<HighlightSpan> {
         // Check if cache is valid for this range
         if let Some(cache) = &self.cache {
@@ -745,6 +741,18 @@ impl Highlighter {
         let parse_end = (viewport_end + context_bytes).min(buffer.len());
         let parse_range = parse_start..parse_end;
 
+        if let Some(tree) = tree {
+            let cached_spans = self.highlight_tree(buffer, tree, parse_range.clone(), 0);
+            return self.cache_and_resolve(
+                buffer,
+                parse_range,
+                cached_spans,
+                viewport_start,
+                viewport_end,
+                theme,
+            );
+        }
+
         // Limit parse size for safety
         if parse_range.len() > MAX_PARSE_BYTES {
             tracing::warn!(
@@ -760,12 +768,55 @@ impl Highlighter {
         let source = buffer.slice_bytes(parse_range.clone());
 
         // Highlight the source - store categories for theme-independent caching
+        let (mut cached_spans, missing) = self.highlight_source(&source, parse_start);
+        if !missing.is_empty() {
+            // First time these languages are injected: load them and redo the window
+            for language in missing {
+                self.injection_highlighter(language);
+            }
+            cached_spans = self.highlight_source(&source, parse_start).0;
+        }
+
+        self.cache_and_resolve(
+            buffer,
+            parse_range,
+            cached_spans,
+            viewport_start,
+            viewport_end,
+            theme,
+        )
+    }
+
+    /// Highlight `source`, which starts at `offset` in the buffer, with
+    /// tree-sitter-highlight
+    ///
+    /// Also returns the injected languages that had no highlighter yet; they
+    /// are left unhighlighted until the caller loads them.
+    fn highlight_source(
+        &mut self,
+        source: &[u8],
+        offset: usize,
+    ) -> (Vec<CachedSpan>, Vec<Language>) {
         let mut cached_spans = Vec::new();
+        let mut missing = Vec::new();
+        let language = self.language;
+        let injections = &self.injections;
+        let injection_callback = |name: &str| {
+            let injected = Language::from_name(name)?;
+            match injections.get(&injected) {
+                Some(highlighter) => highlighter.as_ref().map(|h| &h.config),
+                None => {
+                    missing.push(injected);
+                    None
+                }
+            }
+        };
+
         match self.ts_highlighter.highlight(
             &self.config,
-            &source,
-            None,     // cancellation flag
-            |_| None, // injection callback
+            source,
+            None, // cancellation flag
+            injection_callback,
         ) {
             Ok(highlights) => {
                 let mut current_highlight: Option<usize> = None;
@@ -773,13 +824,13 @@ impl Highlighter {
                 for event in highlights {
                     match event {
                         Ok(HighlightEvent::Source { start, end }) => {
-                            let span_start = parse_start + start;
-                            let span_end = parse_start + end;
+                            let span_start = offset + start;
+                            let span_end = offset + end;
 
                             if let Some(highlight_idx) = current_highlight {
-                                if let Some(category) =
-                                    self.language.highlight_category(highlight_idx)
-                                {
+                                // Injected configurations use this language's
+                                // highlight names, so one mapping fits all
+                                if let Some(category) = language.highlight_category(highlight_idx) {
                                     cached_spans.push(CachedSpan {
                                         range: span_start..span_end,
                                         category,
@@ -805,9 +856,284 @@ impl Highlighter {
             }
         }
 
+        (cached_spans, missing)
+    }
+
+    /// Run the highlights query over `range` of a full-document syntax tree,
+    /// then highlight the languages injected into it (up to `MAX_INJECTION_DEPTH`
+    /// levels deep)
+    fn highlight_tree(
+        &mut self,
+        buffer: &Buffer,
+        tree: &Tree,
+        range: Range<usize>,
+        depth: usize,
+    ) -> Vec<CachedSpan> {
+        let mut query_cursor = QueryCursor::new();
+        query_cursor.set_byte_range(range.clone());
+
+        // Predicates (#eq?, #match?) read node text straight from the buffer
+        let text_provider =
+            |node: tree_sitter::Node| std::iter::once(buffer.slice_bytes(node.byte_range()));
+        let mut captures =
+            query_cursor.captures(&self.config.query, tree.root_node(), text_provider);
+
+        // (node range, pattern index, category) for every recognized capture
+        let mut nodes: Vec<(Range<usize>, usize, HighlightCategory)> = Vec::new();
+        while let Some((query_match, capture_index)) = captures.next() {
+            let capture = query_match.captures[*capture_index];
+            let Some(category) = self
+                .capture_categories
+                .get(capture.index as usize)
+                .copied()
+                .flatten()
+            else {
+                continue;
+            };
+            let node_range = capture.node.byte_range();
+            if !node_range.is_empty() {
+                nodes.push((node_range, query_match.pattern_index, category));
+            }
+        }
+
+        // Outer nodes before inner ones; for the same node the earliest pattern
+        // wins, as with tree-sitter-highlight
+        nodes.sort_by(|a, b| {
+            a.0.start
+                .cmp(&b.0.start)
+                .then(b.0.end.cmp(&a.0.end))
+                .then(a.1.cmp(&b.1))
+        });
+        nodes.dedup_by(|later, earlier| later.0 == earlier.0);
+
+        // Flatten nested captures so the innermost one colors each byte
+        let mut spans = Vec::new();
+        let push = |spans: &mut Vec<CachedSpan>, start: usize, end: usize, category| {
+            let start = start.max(range.start);
+            let end = end.min(range.end);
+            if start < end {
+                spans.push(CachedSpan {
+                    range: start..end,
+                    category,
+                });
+            }
+        };
+        let mut enclosing: Vec<(usize, HighlightCategory)> = Vec::new();
+        let mut pos = 0;
+        for (node_range, _, category) in nodes {
+            while let Some(&(end, outer)) = enclosing.last() {
+                if end > node_range.start {
+                    break;
+                }
+                push(&mut spans, pos, end, outer);
+                pos = pos.max(end);
+                enclosing.pop();
+            }
+            if let Some(&(_, outer)) = enclosing.last() {
+                push(&mut spans, pos, node_range.start, outer);
+            }
+            pos = pos.max(node_range.start);
+            let end = enclosing.last().map_or(node_range.end, |&(outer_end, _)| {
+                node_range.end.min(outer_end)
+            });
+            enclosing.push((end, category));
+        }
+        while let Some((end, category)) = enclosing.pop() {
+            push(&mut spans, pos, end, category);
+            pos = pos.max(end);
+        }
+
+        if depth < MAX_INJECTION_DEPTH {
+            for (language, ranges) in self.injection_ranges(buffer, tree, range.clone()) {
+                let injected =
+                    self.highlight_injection(buffer, &language, ranges, range.clone(), depth);
+                spans = overlay_spans(spans, injected);
+            }
+        }
+
+        spans
+    }
+
+    /// Ranges of `tree` that the injections query assigns to another language
+    /// (see `injection_ranges`)
+    fn injection_ranges(
+        &self,
+        buffer: &Buffer,
+        tree: &Tree,
+        range: Range<usize>,
+    ) -> Vec<(String, Vec<tree_sitter::Range>)> {
+        match &self.injections_query {
+            Some(query) => injection_ranges(query, buffer, tree, range),
+            None => Vec::new(),
+        }
+    }
+
+    /// Highlight the `ranges` injected as `language`, clipped to `window`
+    ///
+    /// Languages with a tree-sitter grammar are parsed over just those ranges.
+    /// Markdown (doc comments) contributes its fenced code blocks, and anything
+    /// else is tried against the TextMate grammars of the registry.
+    fn highlight_injection(
+        &mut self,
+        buffer: &Buffer,
+        language: &str,
+        ranges: Vec<tree_sitter::Range>,
+        window: Range<usize>,
+        depth: usize,
+    ) -> Vec<CachedSpan> {
+        if let Some(injected) = Language::from_name(language) {
+            let Some(highlighter) = self.injection_highlighter(injected) else {
+                return Vec::new();
+            };
+            let Some(tree) = highlighter.parse_ranges(buffer, &ranges) else {
+                return Vec::new();
+            };
+            return highlighter.highlight_tree(buffer, &tree, window, depth + 1);
+        }
+
+        if matches!(language.to_ascii_lowercase().as_str(), "markdown" | "md") {
+            return self.highlight_code_blocks(buffer, &ranges, window, depth);
+        }
+
+        let Some(registry) = &self.registry else {
+            return Vec::new();
+        };
+        let Some(syntax) = registry.find_syntax_by_token(language) else {
+            return Vec::new();
+        };
+        let mut spans = Vec::new();
+        for range in ranges {
+            if range.end_byte <= window.start || range.start_byte >= window.end {
+                continue;
+            }
+            let bytes = buffer.slice_bytes(range.start_byte..range.end_byte);
+            let Ok(text) = std::str::from_utf8(&bytes) else {
+                continue;
+            };
+            spans.extend(
+                highlight_text(registry.syntax_set(), syntax, text, range.start_byte)
+                    .into_iter()
+                    .filter_map(|span| {
+                        let start = span.range.start.max(window.start);
+                        let end = span.range.end.min(window.end);
+                        (start < end).then_some(CachedSpan {
+                            range: start..end,
+                            category: span.category,
+                        })
+                    }),
+            );
+        }
+        spans
+    }
+
+    /// Highlight the fenced code blocks of Markdown spread over `ranges`
+    ///
+    /// Blocks without a known language are taken to be in this highlighter's
+    /// language, as rustdoc does for doc tests (` ```ignore `, ` ```no_run `).
+    fn highlight_code_blocks(
+        &mut self,
+        buffer: &Buffer,
+        ranges: &[tree_sitter::Range],
+        window: Range<usize>,
+        depth: usize,
+    ) -> Vec<CachedSpan> {
+        // Join the pieces into one text, remembering where each one came from
+        let mut text = String::new();
+        let mut pieces: Vec<(usize, Range<usize>)> = Vec::new();
+        for range in ranges {
+            let bytes = buffer.slice_bytes(range.start_byte..range.end_byte);
+            let Ok(piece) = std::str::from_utf8(&bytes) else {
+                continue;
+            };
+            pieces.push((text.len(), range.start_byte..range.end_byte));
+            text.push_str(piece);
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+
+        let mut spans = Vec::new();
+        for block in fenced_code_blocks(&text) {
+            let language = match block.language() {
+                Some("text" | "plain" | "txt") => continue,
+                Some(token)
+                    if Language::from_name(token).is_some()
+                        || self.registry.as_ref().is_some_and(|registry| {
+                            registry.find_syntax_by_token(token).is_some()
+                        }) =>
+                {
+                    token.to_string()
+                }
+                _ => self.language.name().to_string(),
+            };
+
+            // The block's body, mapped back to buffer ranges
+            let body_ranges: Vec<tree_sitter::Range> = pieces
+                .iter()
+                .filter_map(|(offset, range)| {
+                    let start = block.body.start.max(*offset);
+                    let end = block.body.end.min(offset + range.len());
+                    (start < end).then(|| {
+                        buffer_range(
+                            buffer,
+                            range.start + start - offset..range.start + end - offset,
+                        )
+                    })
+                })
+                .collect();
+            if !body_ranges.is_empty() {
+                spans.extend(self.highlight_injection(
+                    buffer,
+                    &language,
+                    body_ranges,
+                    window.clone(),
+                    depth + 1,
+                ));
+            }
+        }
+        spans
+    }
+
+    /// Highlighter for an injected language, created on first use
+    fn injection_highlighter(&mut self, language: Language) -> Option<&mut Highlighter> {
+        let names = self.language.highlight_names();
+        let registry = self.registry.clone();
+        self.injections
+            .entry(language)
+            .or_insert_with(|| match Highlighter::new(language) {
+                Ok(mut highlighter) => {
+                    // tree-sitter-highlight reports injected highlights by index
+                    // into the host configuration's names
+                    highlighter.config.configure(names);
+                    highlighter.registry = registry;
+                    Some(highlighter)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to load injected language {:?}: {}", language, e);
+                    None
+                }
+            })
+            .as_mut()
+    }
+
+    /// Parse only the `ranges` of the buffer with this highlighter's language
+    fn parse_ranges(&self, buffer: &Buffer, ranges: &[tree_sitter::Range]) -> Option<Tree> {
+        parse_ranges(self.language, buffer, ranges)
+    }
+
+    /// Store freshly computed spans in the cache and resolve those in the viewport
+    fn cache_and_resolve(
+        &mut self,
+        buffer: &Buffer,
+        range: Range<usize>,
+        cached_spans: Vec<CachedSpan>,
+        viewport_start: usize,
+        viewport_end: usize,
+        theme: &Theme,
+    ) -> Vec<HighlightSpan> {
         // Update cache
         self.cache = Some(HighlightCache {
-            range: parse_range,
+            range,
             spans: cached_spans.clone(),
         });
         self.last_buffer_len = buffer.len();
@@ -846,10 +1172,267 @@ impl Highlighter {
     }
 }
 
+/// Ranges of `tree` that the injections in `query` assign to another language
+///
+/// Each entry is one injection: a single match, or all matches of a pattern
+/// marked `injection.combined` (e.g. consecutive doc comment lines).
+fn injection_ranges(
+    query: &Query,
+    buffer: &Buffer,
+    tree: &Tree,
+    range: Range<usize>,
+) -> Vec<(String, Vec<tree_sitter::Range>)> {
+    let Some(content_index) = query.capture_index_for_name("injection.content") else {
+        return Vec::new();
+    };
+    let language_index = query.capture_index_for_name("injection.language");
+
+    let mut query_cursor = QueryCursor::new();
+    query_cursor.set_byte_range(range);
+    let text_provider =
+        |node: tree_sitter::Node| std::iter::once(buffer.slice_bytes(node.byte_range()));
+    let mut matches = query_cursor.matches(query, tree.root_node(), text_provider);
+
+    let mut injections: Vec<(String, Vec<tree_sitter::Range>)> = Vec::new();
+    // Pattern index -> injection collecting that pattern's combined matches
+    let mut combined: HashMap<usize, usize> = HashMap::new();
+    while let Some(query_match) = matches.next() {
+        let mut language = None;
+        let mut content = Vec::new();
+        for capture in query_match.captures {
+            if capture.index == content_index {
+                content.push(capture.node.range());
+            } else if Some(capture.index) == language_index {
+                language = String::from_utf8(buffer.slice_bytes(capture.node.byte_range())).ok();
+            }
+        }
+
+        let mut is_combined = false;
+        for property in query.property_settings(query_match.pattern_index) {
+            match property.key.as_ref() {
+                "injection.language" if language.is_none() => {
+                    language = property.value.as_deref().map(str::to_string);
+                }
+                "injection.combined" => is_combined = true,
+                _ => {}
+            }
+        }
+        let Some(language) = language else {
+            continue;
+        };
+        if content.is_empty() {
+            continue;
+        }
+
+        if is_combined {
+            if let Some(&index) = combined.get(&query_match.pattern_index) {
+                injections[index].1.extend(content);
+                continue;
+            }
+            combined.insert(query_match.pattern_index, injections.len());
+        }
+        injections.push((language, content));
+    }
+
+    // Parsers require included ranges in document order
+    for (_, ranges) in &mut injections {
+        ranges.sort_by_key(|range| range.start_byte);
+    }
+    injections
+}
+
+/// Parse only the `ranges` of the buffer as `language`
+fn parse_ranges(
+    language: Language,
+    buffer: &Buffer,
+    ranges: &[tree_sitter::Range],
+) -> Option<Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(&language.tree_sitter_language()).ok()?;
+    parser.set_included_ranges(ranges).ok()?;
+
+    // Feed the parser up to the end of the range it is reading from
+    let mut read = |offset: usize, _: Point| {
+        let end = ranges
+            .iter()
+            .find(|range| range.end_byte > offset)
+            .map_or(offset, |range| range.end_byte);
+        buffer.slice_bytes(offset..end)
+    };
+    parser.parse_with_options(&mut read, None, None)
+}
+
+/// Name of the language injected at `pos` of a `language` document parsed
+/// as `tree`, as written in the injections query (`javascript`, `css`,
+/// `sql`, ...)
+///
+/// Nested injections report the innermost language. Returns None where
+/// `pos` is written in `language` itself.
+pub fn injected_language_at(
+    language: Language,
+    buffer: &Buffer,
+    tree: &Tree,
+    pos: usize,
+) -> Option<String> {
+    injected_language_at_depth(language, buffer, tree, pos, 0)
+}
+
+fn injected_language_at_depth(
+    language: Language,
+    buffer: &Buffer,
+    tree: &Tree,
+    pos: usize,
+    depth: usize,
+) -> Option<String> {
+    if depth >= MAX_INJECTION_DEPTH {
+        return None;
+    }
+    let query = Query::new(
+        &language.tree_sitter_language(),
+        language.injections_query(),
+    )
+    .ok()?;
+    let (name, ranges) = injection_ranges(&query, buffer, tree, pos..pos + 1)
+        .into_iter()
+        .find(|(_, ranges)| {
+            ranges
+                .iter()
+                .any(|range| range.start_byte <= pos && pos < range.end_byte)
+        })?;
+
+    if let Some(injected) = Language::from_name(&name) {
+        let inner = parse_ranges(injected, buffer, &ranges)
+            .and_then(|tree| injected_language_at_depth(injected, buffer, &tree, pos, depth + 1));
+        if inner.is_some() {
+            return inner;
+        }
+    }
+    Some(name)
+}
+
+/// A tree-sitter range covering `range` of the buffer
+fn buffer_range(buffer: &Buffer, range: Range<usize>) -> tree_sitter::Range {
+    let point = |offset| {
+        buffer
+            .offset_to_position(offset)
+            .map_or(Point::new(0, 0), |pos| Point::new(pos.line, pos.column))
+    };
+    tree_sitter::Range {
+        start_byte: range.start,
+        end_byte: range.end,
+        start_point: point(range.start),
+        end_point: point(range.end),
+    }
+}
+
+/// Lay `top` over `base`, so that `top` colors every byte it covers
+///
+/// Spans within each list must not overlap; the result is sorted.
+pub(crate) fn overlay_spans(base: Vec<CachedSpan>, mut top: Vec<CachedSpan>) -> Vec<CachedSpan> {
+    if top.is_empty() {
+        return base;
+    }
+    top.sort_by_key(|span| span.range.start);
+
+    let mut spans = Vec::with_capacity(base.len() + top.len());
+    let mut first = 0;
+    for span in base {
+        while first < top.len() && top[first].range.end <= span.range.start {
+            first += 1;
+        }
+        let mut start = span.range.start;
+        for covering in top[first..]
+            .iter()
+            .take_while(|covering| covering.range.start < span.range.end)
+        {
+            if covering.range.start > start {
+                spans.push(CachedSpan {
+                    range: start..covering.range.start,
+                    category: span.category,
+                });
+            }
+            start = start.max(covering.range.end);
+        }
+        if start < span.range.end {
+            spans.push(CachedSpan {
+                range: start..span.range.end,
+                category: span.category,
+            });
+        }
+    }
+    spans.extend(top);
+    spans.sort_by_key(|span| span.range.start);
+    spans
+}
+
+/// A fenced code block in Markdown text
+pub(crate) struct FencedCode {
+    /// Info string after the opening fence (e.g. "rust,ignore")
+    pub(crate) info: String,
+    /// Byte range of the code between the fences
+    pub(crate) body: Range<usize>,
+}
+
+impl FencedCode {
+    /// Language token of the info string ("rust" for "rust,ignore"), if any
+    pub(crate) fn language(&self) -> Option<&str> {
+        self.info
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .next()
+            .map(|token| token.trim_start_matches(['{', '.']))
+            .filter(|token| !token.is_empty())
+    }
+}
+
+/// Find the ``` and ~~~ fenced code blocks of Markdown text
+///
+/// A block is closed by a bare fence of the same character that is at least
+/// as long as the opening one; a block left open runs to the end of the text.
+pub(crate) fn fenced_code_blocks(text: &str) -> Vec<FencedCode> {
+    let fence = |line: &str, c: char| line.chars().take_while(|&x| x == c).count();
+
+    let mut blocks = Vec::new();
+    // (fence character, fence length, info string, body start)
+    let mut open: Option<(char, usize, String, usize)> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+
+        match open.take() {
+            None => {
+                open = ['`', '~'].into_iter().find_map(|c| {
+                    let len = fence(trimmed, c);
+                    (len >= 3).then(|| (c, len, trimmed[len..].trim().to_string(), offset))
+                });
+            }
+            Some((c, len, info, body_start)) => {
+                if fence(trimmed, c) >= len && trimmed.chars().all(|x| x == c) {
+                    blocks.push(FencedCode {
+                        info,
+                        body: body_start..line_start,
+                    });
+                } else {
+                    open = Some((c, len, info, body_start));
+                }
+            }
+        }
+    }
+    if let Some((_, _, info, body_start)) = open {
+        blocks.push(FencedCode {
+            info,
+            body: body_start..text.len(),
+        });
+    }
+    blocks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::model::buffer::Buffer;
+    use crate::primitives::syntax_tree::SyntaxTree;
 
     #[test]
     fn test_language_detection() {
@@ -961,7 +1544,7 @@ mod tests {
         let theme = Theme::dark();
 
         // Highlight entire buffer
-        let spans = highlighter.highlight_viewport(&buffer, 0, buffer.len(), &theme, 100_000);
+        let spans = highlighter.highlight_viewport(&buffer, 0, buffer.len(), &theme, 100_000, None);
 
         // Should have some highlighted spans
         assert!(!spans.is_empty());
@@ -986,8 +1569,14 @@ mod tests {
         // Highlight only a small viewport in the middle
         let viewport_start = 10000;
         let viewport_end = 10500;
-        let spans =
-            highlighter.highlight_viewport(&buffer, viewport_start, viewport_end, &theme, 100_000);
+        let spans = highlighter.highlight_viewport(
+            &buffer,
+            viewport_start,
+            viewport_end,
+            &theme,
+            100_000,
+            None,
+        );
 
         // Should have some spans in the viewport
         assert!(!spans.is_empty());
@@ -1003,6 +1592,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_highlight_from_syntax_tree() {
+        // A block comment that starts far above the highlighted window
+        let mut content = String::from("/*\n");
+        for i in 0..200 {
+            content.push_str(&format!("fn not_code_{i}() {{}}\n"));
+        }
+        content.push_str("*/\nfn main() {}\n");
+        let buffer = Buffer::from_str_test(&content);
+        let theme = Theme::dark();
+        let viewport_start = content.find("fn not_code_150").unwrap();
+        let viewport_end = viewport_start + 20;
+
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+        assert!(syntax_tree.wait_for_parse(&buffer));
+
+        let mut highlighter = Highlighter::new(Language::Rust).unwrap();
+        let spans = highlighter.highlight_viewport(
+            &buffer,
+            viewport_start,
+            viewport_end,
+            &theme,
+            100,
+            syntax_tree.tree(),
+        );
+        assert!(!spans.is_empty());
+        assert!(spans.iter().all(|s| s.color == theme.syntax_comment));
+
+        // Parsing only the window misses the comment start
+        highlighter.invalidate_all();
+        let spans = highlighter.highlight_viewport(
+            &buffer,
+            viewport_start,
+            viewport_end,
+            &theme,
+            100,
+            None,
+        );
+        assert!(spans.iter().any(|s| s.color == theme.syntax_keyword));
+
+        // Code after the comment is still highlighted from the tree
+        highlighter.invalidate_all();
+        let main_start = content.find("fn main").unwrap();
+        let spans = highlighter.highlight_viewport(
+            &buffer,
+            main_start,
+            buffer.len(),
+            &theme,
+            100,
+            syntax_tree.tree(),
+        );
+        assert!(spans
+            .iter()
+            .any(|s| s.range == (main_start..main_start + 2) && s.color == theme.syntax_keyword));
+    }
+
     #[test]
     fn test_cache_invalidation() {
         let buffer = Buffer::from_str_test("fn main() {\n    println!(\"Hello\");\n}");
@@ -1010,7 +1655,7 @@ mod tests {
         let theme = Theme::dark();
 
         // First highlight
-        highlighter.highlight_viewport(&buffer, 0, buffer.len(), &theme, 100_000);
+        highlighter.highlight_viewport(&buffer, 0, buffer.len(), &theme, 100_000, None);
         assert!(highlighter.cache.is_some());
 
         // Invalidate a range
@@ -1018,7 +1663,7 @@ mod tests {
         assert!(highlighter.cache.is_none());
 
         // Highlight again to rebuild cache
-        highlighter.highlight_viewport(&buffer, 0, buffer.len(), &theme, 100_000);
+        highlighter.highlight_viewport(&buffer, 0, buffer.len(), &theme, 100_000, None);
         assert!(highlighter.cache.is_some());
 
         // Invalidate all
@@ -1034,12 +1679,12 @@ mod tests {
         // Highlight with dark theme
         let dark_theme = Theme::dark();
         let dark_spans =
-            highlighter.highlight_viewport(&buffer, 0, buffer.len(), &dark_theme, 100_000);
+            highlighter.highlight_viewport(&buffer, 0, buffer.len(), &dark_theme, 100_000, None);
 
         // Highlight with light theme (cache should still work, colors should change)
         let light_theme = Theme::light();
         let light_spans =
-            highlighter.highlight_viewport(&buffer, 0, buffer.len(), &light_theme, 100_000);
+            highlighter.highlight_viewport(&buffer, 0, buffer.len(), &light_theme, 100_000, None);
 
         // Both should have spans
         assert!(!dark_spans.is_empty());
@@ -1062,4 +1707,145 @@ mod tests {
             "Themes should have different keyword colors"
         );
     }
+
+    /// Highlight all of `content` from a full-document syntax tree
+    fn highlight_all(highlighter: &mut Highlighter, content: &str) -> Vec<HighlightSpan> {
+        let buffer = Buffer::from_str_test(content);
+        let mut syntax_tree = SyntaxTree::new(highlighter.language(), &buffer).unwrap();
+        assert!(syntax_tree.wait_for_parse(&buffer));
+        highlighter.highlight_viewport(
+            &buffer,
+            0,
+            buffer.len(),
+            &Theme::dark(),
+            0,
+            syntax_tree.tree(),
+        )
+    }
+
+    fn has_span(spans: &[HighlightSpan], range: Range<usize>, color: Color) -> bool {
+        spans.iter().any(|s| s.range == range && s.color == color)
+    }
+
+    #[test]
+    fn test_html_script_injection() {
+        let content = "<p>return</p>\n<script>\nconst x = 1;\n</script>\n";
+        let theme = Theme::dark();
+        let const_start = content.find("const").unwrap();
+        let text_start = content.find("return").unwrap();
+
+        let mut highlighter = Highlighter::new(Language::HTML).unwrap();
+        let spans = highlight_all(&mut highlighter, content);
+        assert!(has_span(
+            &spans,
+            const_start..const_start + 5,
+            theme.syntax_keyword
+        ));
+        // Only the script is JavaScript
+        assert!(!spans
+            .iter()
+            .any(|s| s.range.start == text_start && s.color == theme.syntax_keyword));
+
+        // Parsing just the window injects through tree-sitter-highlight
+        highlighter.invalidate_all();
+        let buffer = Buffer::from_str_test(content);
+        let spans = highlighter.highlight_viewport(&buffer, 0, buffer.len(), &theme, 0, None);
+        assert!(has_span(
+            &spans,
+            const_start..const_start + 5,
+            theme.syntax_keyword
+        ));
+    }
+
+    #[test]
+    fn test_rust_doc_test_injection() {
+        let content = "/// Adds one\n///\n/// ```\n/// let x = 1;\n/// ```\nfn add() {}\n";
+        let theme = Theme::dark();
+        let let_start = content.find("let").unwrap();
+
+        let mut highlighter = Highlighter::new(Language::Rust).unwrap();
+        let spans = highlight_all(&mut highlighter, content);
+        assert!(has_span(
+            &spans,
+            let_start..let_start + 3,
+            theme.syntax_keyword
+        ));
+
+        // Prose around the code block stays a comment
+        let prose = content.find("Adds").unwrap();
+        assert!(spans
+            .iter()
+            .any(|s| s.range.contains(&prose) && s.color == theme.syntax_comment));
+    }
+
+    #[test]
+    fn test_injected_language_at() {
+        let content =
+            "<p>hi</p>\n<script>\nconst x = 1;\n</script>\n<style>\np { color: red; }\n</style>\n";
+        let buffer = Buffer::from_str_test(content);
+        let mut syntax_tree = SyntaxTree::new(&Language::HTML, &buffer).unwrap();
+        assert!(syntax_tree.wait_for_parse(&buffer));
+        let tree = syntax_tree.tree().unwrap();
+        let language_at = |text: &str| {
+            injected_language_at(Language::HTML, &buffer, tree, content.find(text).unwrap())
+        };
+
+        assert_eq!(language_at("const").as_deref(), Some("javascript"));
+        assert_eq!(language_at("color").as_deref(), Some("css"));
+        assert_eq!(language_at("hi"), None);
+    }
+
+    #[test]
+    fn test_textmate_injection_fallback() {
+        let content = "query = \"SELECT name FROM users\"\n";
+        let theme = Theme::dark();
+        let select_start = content.find("SELECT").unwrap();
+
+        // SQL has no tree-sitter grammar here, only a TextMate one
+        let mut highlighter = Highlighter::new(Language::Python).unwrap();
+        let spans = highlight_all(&mut highlighter, content);
+        assert!(!has_span(
+            &spans,
+            select_start..select_start + 6,
+            theme.syntax_keyword
+        ));
+
+        let mut highlighter = Highlighter::new(Language::Python).unwrap();
+        highlighter.set_grammar_registry(&GrammarRegistry::load());
+        let spans = highlight_all(&mut highlighter, content);
+        assert!(has_span(
+            &spans,
+            select_start..select_start + 6,
+            theme.syntax_keyword
+        ));
+    }
+
+    #[test]
+    fn test_fenced_code_blocks() {
+        let text =
+            "intro\n```rust,ignore\nlet a = 1;\n```\n~~~~\n```\nnot a fence\n~~~~\n```py\nopen\n";
+        let blocks = fenced_code_blocks(text);
+        assert_eq!(blocks.len(), 3);
+
+        assert_eq!(blocks[0].language(), Some("rust"));
+        assert_eq!(&text[blocks[0].body.clone()], "let a = 1;\n");
+
+        // A shorter or different fence doesn't close a block
+        assert_eq!(blocks[1].language(), None);
+        assert_eq!(&text[blocks[1].body.clone()], "```\nnot a fence\n");
+
+        // An unclosed block runs to the end
+        assert_eq!(blocks[2].language(), Some("py"));
+        assert_eq!(&text[blocks[2].body.clone()], "open\n");
+    }
+
+    #[test]
+    fn test_language_from_name() {
+        assert_eq!(Language::from_name("JS"), Some(Language::JavaScript));
+        assert_eq!(Language::from_name("c++"), Some(Language::Cpp));
+        assert_eq!(Language::from_name("sql"), None);
+        for language in [Language::Rust, Language::CSharp, Language::Cpp] {
+            assert_eq!(Language::from_name(language.name()), Some(language));
+        }
+    }
 }
@@ -42,12 +42,58 @@
 
 use crate::model::buffer::Buffer;
 use crate::primitives::highlighter::Language;
+use crate::primitives::syntax_tree::SyntaxTree;
 use std::collections::HashMap;
-use tree_sitter::{Parser, Query, QueryCursor, StreamingIterator};
+use std::ops::Range;
+use tree_sitter::{InputEdit, Parser, Point, Query, QueryCursor, StreamingIterator, Tree};
 
 /// Maximum bytes to parse before cursor for indent calculation
 const MAX_PARSE_BYTES: usize = 2000;
 
+/// Parse the text in `window`, reusing the buffer's syntax tree if there is one
+///
+/// The document tree is cut down to the window first, so the parse only
+/// redoes the nodes the cuts run through and gives the same tree as parsing
+/// the window from scratch.
+fn parse_window(
+    parser: &mut Parser,
+    syntax_tree: Option<&SyntaxTree>,
+    buffer: &Buffer,
+    source: &[u8],
+    window: &Range<usize>,
+) -> Option<Tree> {
+    let old_tree = syntax_tree
+        .and_then(SyntaxTree::tree)
+        // A tree that lost track of the buffer can't be cut at buffer offsets
+        .filter(|tree| tree.root_node().end_byte() == buffer.len())
+        .map(|tree| {
+            let point = |offset: usize| {
+                let (line, column) = buffer.position_to_line_col(offset);
+                Point::new(line, column)
+            };
+            let mut tree = tree.clone();
+            // Drop the text after the window, then the text before it
+            tree.edit(&InputEdit {
+                start_byte: window.end,
+                old_end_byte: buffer.len(),
+                new_end_byte: window.end,
+                start_position: point(window.end),
+                old_end_position: point(buffer.len()),
+                new_end_position: point(window.end),
+            });
+            tree.edit(&InputEdit {
+                start_byte: 0,
+                old_end_byte: window.start,
+                new_end_byte: 0,
+                start_position: Point::new(0, 0),
+                old_end_position: point(window.start),
+                new_end_position: Point::new(0, 0),
+            });
+            tree
+        });
+    parser.parse(source, old_tree.as_ref())
+}
+
 /// Indent calculator using tree-sitter queries
 pub struct IndentCalculator {
     /// Map of language to (parser, query)
@@ -187,10 +233,23 @@ impl IndentCalculator {
         position: usize,
         language: &Language,
         tab_size: usize,
+    ) -> Option<usize> {
+        self.calculate_indent_with_tree(buffer, None, position, language, tab_size)
+    }
+
+    /// Calculate indent for a new line, parsing the text before the cursor
+    /// incrementally from the buffer's syntax tree
+    pub fn calculate_indent_with_tree(
+        &mut self,
+        buffer: &Buffer,
+        syntax_tree: Option<&SyntaxTree>,
+        position: usize,
+        language: &Language,
+        tab_size: usize,
     ) -> Option<usize> {
         // Try tree-sitter-based indent
         if let Some(indent) =
-            self.calculate_indent_tree_sitter(buffer, position, language, tab_size)
+            self.calculate_indent_tree_sitter(buffer, syntax_tree, position, language, tab_size)
         {
             return Some(indent);
         }
@@ -255,6 +314,22 @@ impl IndentCalculator {
         &mut self,
         buffer: &Buffer,
         position: usize,
+        delimiter: char,
+        language: &Language,
+        tab_size: usize,
+    ) -> Option<usize> {
+        self.calculate_dedent_for_delimiter_with_tree(
+            buffer, None, position, delimiter, language, tab_size,
+        )
+    }
+
+    /// Calculate the indent for a closing delimiter, parsing the text before
+    /// the cursor incrementally from the buffer's syntax tree
+    pub fn calculate_dedent_for_delimiter_with_tree(
+        &mut self,
+        buffer: &Buffer,
+        syntax_tree: Option<&SyntaxTree>,
+        position: usize,
         _delimiter: char,
         language: &Language,
         tab_size: usize,
@@ -273,7 +348,7 @@ impl IndentCalculator {
         let source = buffer.slice_bytes(parse_range.clone());
 
         // Parse the source
-        let tree = parser.parse(&source, None)?;
+        let tree = parse_window(parser, syntax_tree, buffer, &source, &parse_range)?;
         let root = tree.root_node();
 
         // Find capture index for @indent
@@ -676,6 +751,7 @@ impl IndentCalculator {
     fn calculate_indent_tree_sitter(
         &mut self,
         buffer: &Buffer,
+        syntax_tree: Option<&SyntaxTree>,
         position: usize,
         language: &Language,
         tab_size: usize,
@@ -694,7 +770,7 @@ impl IndentCalculator {
         let source = buffer.slice_bytes(parse_range.clone());
 
         // Parse the source
-        let tree = parser.parse(&source, None)?;
+        let tree = parse_window(parser, syntax_tree, buffer, &source, &parse_range)?;
         let root = tree.root_node();
 
         // Find capture indices for @indent and @dedent
@@ -1048,7 +1124,8 @@ mod tests {
 
         // Tree-sitter should recognize this is a complete block
         // Pattern matching would see '}' and not indent, but tree-sitter context should work
-        let ts_result = calc.calculate_indent_tree_sitter(&buffer, position, &Language::Rust, 4);
+        let ts_result =
+            calc.calculate_indent_tree_sitter(&buffer, None, position, &Language::Rust, 4);
 
         // Tree-sitter should return Some (even if it's 0 indent)
         assert!(
@@ -1057,6 +1134,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_indent_from_document_tree() {
+        // The block is complete in the document, but not in the text before the cursor
+        let mut calc = IndentCalculator::new();
+        let buffer =
+            Buffer::from_str_test("fn main() {\n    if x {\n        let y = 1;\n    }\n}\n");
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+        assert!(syntax_tree.wait_for_parse(&buffer));
+
+        let text = buffer.to_string().unwrap();
+        for (needle, expected) in [("{", 4), (";", 8)] {
+            let position = text.find(needle).unwrap() + needle.len();
+            let with_tree = calc.calculate_indent_with_tree(
+                &buffer,
+                Some(&syntax_tree),
+                position,
+                &Language::Rust,
+                4,
+            );
+            let without_tree = calc.calculate_indent(&buffer, position, &Language::Rust, 4);
+            assert_eq!(with_tree, without_tree, "after {:?}", needle);
+            assert_eq!(with_tree, Some(expected), "after {:?}", needle);
+        }
+    }
+
+    #[test]
+    fn test_indent_after_edit_not_yet_reparsed() {
+        let mut calc = IndentCalculator::new();
+        let mut buffer = Buffer::from_str_test("fn main() ");
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+        assert!(syntax_tree.wait_for_parse(&buffer));
+
+        // The brace is typed but the tree doesn't have it yet
+        let position = buffer.len();
+        let start = buffer.position_to_line_col(position);
+        buffer.insert(position, "{");
+        syntax_tree.edit_insert(position, start, "{");
+
+        let indent = calc.calculate_indent_with_tree(
+            &buffer,
+            Some(&syntax_tree),
+            buffer.len(),
+            &Language::Rust,
+            4,
+        );
+        assert_eq!(indent, Some(4));
+    }
+
     #[test]
     fn test_nested_indent_maintained() {
         // Test that we maintain nested indentation correctly
@@ -1157,7 +1282,8 @@ mod tests {
         );
 
         // Verify tree-sitter is being used (not just pattern fallback)
-        let ts_result = calc.calculate_indent_tree_sitter(&buffer, position, &Language::Rust, 4);
+        let ts_result =
+            calc.calculate_indent_tree_sitter(&buffer, None, position, &Language::Rust, 4);
         assert!(ts_result.is_some(), "Tree-sitter should handle this case");
     }
 
@@ -0,0 +1,142 @@
+//! Targets and labels for jump hints
+//!
+//! A jump hint marks a place the cursor can jump to with a short label.
+//! `HintTargets` finds the places in a piece of text, and `labels` makes
+//! the labels: single keys while they last, then two-key labels whose
+//! first key is never a label of its own, so typing a label is never
+//! ambiguous.
+
+use crate::primitives::word_navigation::is_word_char;
+
+/// Keys labels are made of, home row first
+pub const LABEL_KEYS: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+/// Most targets that can be labelled
+pub const MAX_HINTS: usize = LABEL_KEYS.len() * LABEL_KEYS.len();
+
+/// What a jump hint can point at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintTargets {
+    /// Each place the given number of typed characters appear
+    Chars(usize),
+    /// Each start of a word beginning with the typed character
+    WordStarts,
+    /// Each start of a line
+    LineStarts,
+}
+
+impl HintTargets {
+    /// How many characters to ask for before the targets are labelled
+    pub fn query_len(self) -> usize {
+        match self {
+            HintTargets::Chars(count) => count,
+            HintTargets::WordStarts => 1,
+            HintTargets::LineStarts => 0,
+        }
+    }
+
+    /// Offsets of the targets for `query` in `bytes`, from `from` on; the
+    /// bytes before `from` only tell whether a word or line starts there.
+    /// The query ignores case unless it has an upper-case letter.
+    pub fn find(self, bytes: &[u8], from: usize, query: &str) -> Vec<usize> {
+        let query = query.as_bytes();
+        let ignore_case = !query.iter().any(u8::is_ascii_uppercase);
+        let matches_at = |offset: usize| {
+            bytes
+                .get(offset..offset + query.len())
+                .is_some_and(|found| {
+                    if ignore_case {
+                        found.eq_ignore_ascii_case(query)
+                    } else {
+                        found == query
+                    }
+                })
+        };
+        let word_start = |offset: usize| {
+            is_word_char(bytes[offset]) && (offset == 0 || !is_word_char(bytes[offset - 1]))
+        };
+        let line_start = |offset: usize| offset == 0 || bytes[offset - 1] == b'\n';
+
+        (from..bytes.len())
+            .filter(|&offset| match self {
+                HintTargets::Chars(_) => !query.is_empty() && matches_at(offset),
+                HintTargets::WordStarts => word_start(offset) && matches_at(offset),
+                HintTargets::LineStarts => line_start(offset),
+            })
+            .collect()
+    }
+}
+
+/// Labels for `count` targets, shortest first; at most `MAX_HINTS`
+pub fn labels(count: usize) -> Vec<String> {
+    let keys: Vec<char> = LABEL_KEYS.chars().collect();
+    let count = count.min(MAX_HINTS);
+    // Every key that doesn't label a target by itself starts a two-key label
+    let singles = if count <= keys.len() {
+        count
+    } else {
+        (MAX_HINTS - count) / (keys.len() - 1)
+    };
+
+    let mut labels: Vec<String> = keys[..singles].iter().map(char::to_string).collect();
+    for first in &keys[singles..] {
+        for second in &keys {
+            if labels.len() == count {
+                return labels;
+            }
+            labels.push(format!("{}{}", first, second));
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_targets() {
+        let text = b"Foo foo(bar)\n  fob_fo\n";
+        assert_eq!(
+            HintTargets::Chars(2).find(text, 0, "fo"),
+            vec![0, 4, 15, 19]
+        );
+        // An upper-case letter makes the query match case
+        assert_eq!(HintTargets::Chars(1).find(text, 0, "F"), vec![0]);
+        assert_eq!(HintTargets::WordStarts.find(text, 0, "f"), vec![0, 4, 15]);
+        assert_eq!(HintTargets::WordStarts.find(text, 0, "b"), vec![8]);
+        assert_eq!(HintTargets::LineStarts.find(text, 0, ""), vec![0, 13]);
+    }
+
+    #[test]
+    fn test_find_targets_after_context() {
+        // The bytes before `from` only decide where words and lines start
+        let text = b"xfoo\nfoo";
+        assert_eq!(HintTargets::Chars(1).find(text, 1, "f"), vec![1, 5]);
+        assert_eq!(HintTargets::WordStarts.find(text, 1, "f"), vec![5]);
+        assert_eq!(HintTargets::LineStarts.find(text, 1, ""), vec![5]);
+    }
+
+    #[test]
+    fn test_labels() {
+        assert_eq!(labels(3), vec!["a", "s", "d"]);
+        assert_eq!(labels(26).len(), 26);
+        assert!(labels(26).iter().all(|label| label.len() == 1));
+
+        // Past 26 targets, the last keys start two-key labels
+        let many = labels(30);
+        assert_eq!(many.len(), 30);
+        assert_eq!(many[..25].concat(), LABEL_KEYS[..25]);
+        assert_eq!(many[25..], ["ma", "ms", "md", "mf", "mg"]);
+
+        let all = labels(MAX_HINTS + 10);
+        assert_eq!(all.len(), MAX_HINTS);
+        for label in &all {
+            let prefixed = all
+                .iter()
+                .filter(|other| other.starts_with(label.as_str()))
+                .count();
+            assert_eq!(prefixed, 1, "{} is the start of another label", label);
+        }
+    }
+}
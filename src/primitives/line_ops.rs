@@ -0,0 +1,484 @@
+//! Line operations: move, duplicate, join, sort, reverse, unique and shuffle
+//!
+//! Each operation takes a block of lines (without their terminators) and
+//! returns a `LineEdit`: the new lines, plus where the text of every old line
+//! ended up, so cursors and selections can be carried over. Moving and
+//! duplicating carry positions along with their line; operations that
+//! reorder or drop lines keep positions on the same line number.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// How lines are compared when sorting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    /// Plain string comparison
+    #[default]
+    Lexical,
+    /// Runs of digits compare as numbers, so "file2" sorts before "file10"
+    Natural,
+    /// By the first number on the line
+    Numeric,
+}
+
+/// Options for sorting lines
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortOptions {
+    pub mode: SortMode,
+    /// Compare text ignoring case
+    pub case_insensitive: bool,
+    /// Sort in descending order
+    pub reverse: bool,
+    /// Compare by the first capture group of this regex (or the whole match
+    /// when it has no groups)
+    pub pattern: Option<String>,
+}
+
+impl SortOptions {
+    /// Options from keybinding arguments: `mode` (`"lexical"`, `"natural"` or
+    /// `"numeric"`), `case_insensitive`, `reverse` and `pattern`
+    ///
+    /// Returns None for an unknown mode.
+    pub fn from_args(args: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        let mode = match args.get("mode").and_then(|v| v.as_str()) {
+            None | Some("lexical") => SortMode::Lexical,
+            Some("natural") => SortMode::Natural,
+            Some("numeric") => SortMode::Numeric,
+            Some(_) => return None,
+        };
+        let flag = |name: &str| args.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+        Some(Self {
+            mode,
+            case_insensitive: flag("case_insensitive"),
+            reverse: flag("reverse"),
+            pattern: args
+                .get("pattern")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// Where the text of an old line went: `len` bytes at `offset` in new line
+/// `line`, after dropping `trim` leading bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Placement {
+    line: usize,
+    offset: usize,
+    trim: usize,
+    len: usize,
+}
+
+/// Result of a line operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineEdit {
+    /// The new lines, without terminators
+    pub lines: Vec<String>,
+    placements: Vec<Placement>,
+    old_lens: Vec<usize>,
+}
+
+impl LineEdit {
+    /// New lines where new line `i` is old line `order[i]`; positions follow
+    /// their line (to its last copy when a line appears more than once)
+    fn following(lines: &[String], order: &[usize]) -> Self {
+        let mut placements = vec![
+            Placement {
+                line: 0,
+                offset: 0,
+                trim: 0,
+                len: 0,
+            };
+            lines.len()
+        ];
+        for (new_index, &old_index) in order.iter().enumerate() {
+            placements[old_index] = Placement {
+                line: new_index,
+                offset: 0,
+                trim: 0,
+                len: lines[old_index].len(),
+            };
+        }
+        Self {
+            lines: order.iter().map(|&i| lines[i].clone()).collect(),
+            placements,
+            old_lens: lines.iter().map(String::len).collect(),
+        }
+    }
+
+    /// `new_lines` in place of `lines`; positions stay on the same line
+    /// number, or the last line when there are fewer lines now
+    fn in_place(lines: &[String], new_lines: Vec<String>) -> Self {
+        let last = new_lines.len().saturating_sub(1);
+        let placements = (0..lines.len())
+            .map(|i| {
+                let line = i.min(last);
+                Placement {
+                    line,
+                    offset: 0,
+                    trim: 0,
+                    len: new_lines.get(line).map_or(0, String::len),
+                }
+            })
+            .collect();
+        Self {
+            lines: new_lines,
+            placements,
+            old_lens: lines.iter().map(String::len).collect(),
+        }
+    }
+
+    /// Where `column` of old line `line` is now, as (line, column)
+    ///
+    /// A position at the end of its line stays at the end of its text.
+    pub fn map(&self, line: usize, column: usize) -> (usize, usize) {
+        let placement = self.placements[line];
+        let column = if column >= self.old_lens[line] {
+            placement.len
+        } else {
+            column.saturating_sub(placement.trim).min(placement.len)
+        };
+        let text = &self.lines[placement.line];
+        let mut column = (placement.offset + column).min(text.len());
+        while !text.is_char_boundary(column) {
+            column -= 1;
+        }
+        (placement.line, column)
+    }
+}
+
+/// Move lines `1..` above line 0
+pub fn move_up(lines: &[String]) -> LineEdit {
+    let order: Vec<usize> = (1..lines.len()).chain([0]).collect();
+    LineEdit::following(lines, &order)
+}
+
+/// Move lines `..len - 1` below the last line
+pub fn move_down(lines: &[String]) -> LineEdit {
+    let last = lines.len() - 1;
+    let order: Vec<usize> = [last].into_iter().chain(0..last).collect();
+    LineEdit::following(lines, &order)
+}
+
+/// Repeat the lines below themselves; positions move to the copy
+pub fn duplicate(lines: &[String]) -> LineEdit {
+    let order: Vec<usize> = (0..lines.len()).chain(0..lines.len()).collect();
+    LineEdit::following(lines, &order)
+}
+
+/// Join the lines into one, with `separator` between them
+///
+/// Indentation of the joined lines and trailing whitespace are dropped, and
+/// blank lines are skipped.
+pub fn join(lines: &[String], separator: &str) -> LineEdit {
+    let mut joined = String::new();
+    let mut placements = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = if i == 0 {
+            line.as_str()
+        } else {
+            line.trim_start()
+        };
+        let text = trimmed.trim_end();
+        if i > 0 && !text.is_empty() && !joined.is_empty() {
+            joined.push_str(separator);
+        }
+        placements.push(Placement {
+            line: 0,
+            offset: joined.len(),
+            trim: line.len() - trimmed.len(),
+            len: text.len(),
+        });
+        joined.push_str(text);
+    }
+    LineEdit {
+        lines: vec![joined],
+        placements,
+        old_lens: lines.iter().map(String::len).collect(),
+    }
+}
+
+/// Sort the lines (stable); lines without a sort key (no match for the
+/// pattern, or no number in numeric mode) go last in their original order
+pub fn sort(lines: &[String], options: &SortOptions) -> Result<LineEdit, String> {
+    let pattern = match &options.pattern {
+        Some(pattern) => {
+            Some(Regex::new(pattern).map_err(|e| format!("Invalid sort pattern: {}", e))?)
+        }
+        None => None,
+    };
+    let keys: Vec<Option<&str>> = lines
+        .iter()
+        .map(|line| match &pattern {
+            Some(regex) => regex
+                .captures(line)
+                .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+                .map(|m| m.as_str()),
+            None => Some(line.as_str()),
+        })
+        .map(|key| match options.mode {
+            SortMode::Numeric => key.filter(|key| first_number(key).is_some()),
+            _ => key,
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..lines.len()).collect();
+    order.sort_by(|&a, &b| match (keys[a], keys[b]) {
+        (Some(a), Some(b)) => {
+            let ordering = compare_keys(a, b, options);
+            if options.reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    let sorted = order.iter().map(|&i| lines[i].clone()).collect();
+    Ok(LineEdit::in_place(lines, sorted))
+}
+
+fn compare_keys(a: &str, b: &str, options: &SortOptions) -> Ordering {
+    match options.mode {
+        SortMode::Numeric => {
+            let (a, b) = (first_number(a), first_number(b));
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        }
+        SortMode::Natural => natural_cmp(a, b, options.case_insensitive),
+        SortMode::Lexical if options.case_insensitive => a.to_lowercase().cmp(&b.to_lowercase()),
+        SortMode::Lexical => a.cmp(b),
+    }
+}
+
+/// First (optionally signed, optionally fractional) number in `text`
+fn first_number(text: &str) -> Option<f64> {
+    let bytes = text.as_bytes();
+    let digit = bytes.iter().position(u8::is_ascii_digit)?;
+    let start = if digit > 0 && bytes[digit - 1] == b'-' {
+        digit - 1
+    } else {
+        digit
+    };
+    let mut end = digit;
+    while end < bytes.len() && bytes[end].is_ascii_digit() {
+        end += 1;
+    }
+    if end + 1 < bytes.len() && bytes[end] == b'.' && bytes[end + 1].is_ascii_digit() {
+        end += 1;
+        while end < bytes.len() && bytes[end].is_ascii_digit() {
+            end += 1;
+        }
+    }
+    text[start..end].parse().ok()
+}
+
+/// Compare with runs of ASCII digits taken as numbers
+fn natural_cmp(a: &str, b: &str, case_insensitive: bool) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x_len = a.bytes().take_while(u8::is_ascii_digit).count();
+                let y_len = b.bytes().take_while(u8::is_ascii_digit).count();
+                let x_digits = a[..x_len].trim_start_matches('0');
+                let y_digits = b[..y_len].trim_start_matches('0');
+                let ordering = x_digits
+                    .len()
+                    .cmp(&y_digits.len())
+                    .then_with(|| x_digits.cmp(y_digits));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[x_len..];
+                b = &b[y_len..];
+            }
+            (Some(x), Some(y)) => {
+                let ordering = if case_insensitive {
+                    x.to_lowercase().cmp(y.to_lowercase())
+                } else {
+                    x.cmp(&y)
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[x.len_utf8()..];
+                b = &b[y.len_utf8()..];
+            }
+        }
+    }
+}
+
+/// Lines in reverse order
+pub fn reverse(lines: &[String]) -> LineEdit {
+    LineEdit::in_place(lines, lines.iter().rev().cloned().collect())
+}
+
+/// Lines with repeats of an earlier line removed
+pub fn unique(lines: &[String]) -> LineEdit {
+    let mut seen = HashSet::new();
+    let kept = lines
+        .iter()
+        .filter(|line| seen.insert(line.as_str()))
+        .cloned()
+        .collect();
+    LineEdit::in_place(lines, kept)
+}
+
+/// Lines in a random order determined by `seed`
+pub fn shuffle(lines: &[String], seed: u64) -> LineEdit {
+    // xorshift64*, which is plenty for shuffling lines
+    let mut state = seed | 1;
+    let mut next = move || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    };
+    let mut shuffled = lines.to_vec();
+    for i in (1..shuffled.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        shuffled.swap(i, j);
+    }
+    LineEdit::in_place(lines, shuffled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn sorted(text: &[&str], options: SortOptions) -> Vec<String> {
+        sort(&lines(text), &options).unwrap().lines
+    }
+
+    #[test]
+    fn test_move_carries_positions_with_the_line() {
+        let edit = move_up(&lines(&["above", "one", "two"]));
+        assert_eq!(edit.lines, lines(&["one", "two", "above"]));
+        assert_eq!(edit.map(1, 2), (0, 2));
+        assert_eq!(edit.map(2, 3), (1, 3));
+
+        let edit = move_down(&lines(&["one", "two", "below"]));
+        assert_eq!(edit.lines, lines(&["below", "one", "two"]));
+        assert_eq!(edit.map(0, 1), (1, 1));
+    }
+
+    #[test]
+    fn test_duplicate_moves_positions_to_the_copy() {
+        let edit = duplicate(&lines(&["a", "bc"]));
+        assert_eq!(edit.lines, lines(&["a", "bc", "a", "bc"]));
+        assert_eq!(edit.map(0, 0), (2, 0));
+        assert_eq!(edit.map(1, 2), (3, 2));
+    }
+
+    #[test]
+    fn test_join_trims_and_skips_blank_lines() {
+        let edit = join(&lines(&["let x = [", "    1,", "", "    2 ", "];"]), " ");
+        assert_eq!(edit.lines, lines(&["let x = [ 1, 2 ];"]));
+        // Column 5 of "    1," is the comma
+        assert_eq!(edit.map(1, 5), (0, 11));
+        // The end of a line is the end of its text
+        assert_eq!(edit.map(3, 6), (0, 14));
+
+        let edit = join(&lines(&["a", "b", "c"]), ", ");
+        assert_eq!(edit.lines, lines(&["a, b, c"]));
+    }
+
+    #[test]
+    fn test_sort_modes() {
+        let text = ["file10", "File2", "file1"];
+        assert_eq!(
+            sorted(&text, SortOptions::default()),
+            lines(&["File2", "file1", "file10"])
+        );
+        assert_eq!(
+            sorted(
+                &text,
+                SortOptions {
+                    mode: SortMode::Natural,
+                    case_insensitive: true,
+                    ..Default::default()
+                }
+            ),
+            lines(&["file1", "File2", "file10"])
+        );
+        assert_eq!(
+            sorted(
+                &["x 10", "none", "y -2.5", "z 3"],
+                SortOptions {
+                    mode: SortMode::Numeric,
+                    reverse: true,
+                    ..Default::default()
+                }
+            ),
+            lines(&["x 10", "z 3", "y -2.5", "none"])
+        );
+    }
+
+    #[test]
+    fn test_sort_by_regex_capture() {
+        let options = SortOptions {
+            pattern: Some(r"id=(\w+)".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            sorted(&["b id=2", "no id", "a id=3", "c id=1"], options),
+            lines(&["c id=1", "b id=2", "a id=3", "no id"])
+        );
+
+        let invalid = SortOptions {
+            pattern: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(sort(&lines(&["a"]), &invalid).is_err());
+    }
+
+    #[test]
+    fn test_reverse_unique_and_shuffle() {
+        let text = lines(&["a", "b", "a", "c", "b"]);
+        assert_eq!(reverse(&text).lines, lines(&["b", "c", "a", "b", "a"]));
+
+        let edit = unique(&text);
+        assert_eq!(edit.lines, lines(&["a", "b", "c"]));
+        // Positions past the remaining lines clamp to the last one
+        assert_eq!(edit.map(4, 1), (2, 1));
+
+        let mut shuffled = shuffle(&text, 42).lines;
+        assert_eq!(shuffled, shuffle(&text, 42).lines);
+        shuffled.sort();
+        assert_eq!(shuffled, lines(&["a", "a", "b", "b", "c"]));
+    }
+
+    #[test]
+    fn test_sort_options_from_args() {
+        let mut args = HashMap::new();
+        assert_eq!(SortOptions::from_args(&args), Some(SortOptions::default()));
+
+        args.insert("mode".to_string(), serde_json::json!("natural"));
+        args.insert("reverse".to_string(), serde_json::json!(true));
+        args.insert("pattern".to_string(), serde_json::json!("(\\d+)"));
+        assert_eq!(
+            SortOptions::from_args(&args),
+            Some(SortOptions {
+                mode: SortMode::Natural,
+                case_insensitive: false,
+                reverse: true,
+                pattern: Some("(\\d+)".to_string()),
+            })
+        );
+
+        args.insert("mode".to_string(), serde_json::json!("random"));
+        assert_eq!(SortOptions::from_args(&args), None);
+    }
+}
@@ -5,14 +5,25 @@
 
 pub mod ansi;
 pub mod ansi_background;
+pub mod brackets;
+pub mod comment;
 pub mod display_width;
 pub mod grammar_registry;
 pub mod highlight_engine;
 pub mod highlighter;
 pub mod indent;
+pub mod jump_hints;
 pub mod line_iterator;
+pub mod line_ops;
 pub mod line_wrapping;
+pub mod reflow;
 pub mod semantic_highlight;
+pub mod snippet;
+pub mod surround;
+pub mod syntax_tree;
 pub mod text_property;
+pub mod text_transform;
+pub mod textobjects;
 pub mod visual_layout;
+pub mod word_completion;
 pub mod word_navigation;
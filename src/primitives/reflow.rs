@@ -0,0 +1,377 @@
+//! Paragraph reflow and hard wrapping
+//!
+//! A paragraph is a run of lines with the same prefix: indentation, a comment
+//! marker (`//`, `#`, the `*` of a block comment), blockquote markers (`>`)
+//! and, on its first line, a list marker (`-`, `1.`). Reflowing rewraps the
+//! words of each paragraph to a column and repeats the prefix on every line,
+//! with spaces in place of the list marker after the first.
+//!
+//! Blank lines, lines holding only a prefix, headings, table rows and fenced
+//! code blocks separate paragraphs and are left alone.
+
+use std::ops::Range;
+
+use crate::primitives::display_width::str_width;
+
+/// A line split into its prefix and body
+struct Prefixed<'a> {
+    /// Indentation, comment and blockquote markers, and the spaces after them
+    lead: &'a str,
+    /// List marker and the spaces after it, if the line starts a list item
+    item: &'a str,
+    /// The rest of the line, without trailing whitespace
+    body: &'a str,
+}
+
+impl Prefixed<'_> {
+    /// Prefix of the lines after the first when this line starts a paragraph
+    fn continuation(&self, tab_size: usize) -> String {
+        format!(
+            "{}{}",
+            self.lead,
+            " ".repeat(line_width(self.item, tab_size))
+        )
+    }
+
+    fn is_fence(&self) -> bool {
+        self.body.starts_with("```") || self.body.starts_with("~~~")
+    }
+
+    /// Whether the line can't be part of a paragraph
+    fn is_break(&self) -> bool {
+        self.body.is_empty()
+            || self.is_fence()
+            || self.body.starts_with('#')
+            || self.body.starts_with('|')
+    }
+}
+
+/// Display width of `text`, with tabs advancing to the next tab stop
+pub fn line_width(text: &str, tab_size: usize) -> usize {
+    let tab_size = tab_size.max(1);
+    text.split('\t').enumerate().fold(0, |column, (i, part)| {
+        let column = if i > 0 {
+            (column / tab_size + 1) * tab_size
+        } else {
+            column
+        };
+        column + str_width(part)
+    })
+}
+
+fn skip_blanks(line: &str, pos: usize) -> usize {
+    pos + (line[pos..].len() - line[pos..].trim_start_matches([' ', '\t']).len())
+}
+
+/// Length of the list marker at the start of `text` with the spaces and task
+/// box after it, or 0
+fn list_marker_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let marker = match bytes.first() {
+        Some(b'-' | b'*' | b'+') => 1,
+        Some(b'0'..=b'9') => {
+            let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+            match bytes.get(digits) {
+                Some(b'.' | b')') if digits <= 9 => digits + 1,
+                _ => return 0,
+            }
+        }
+        _ => return 0,
+    };
+    if !matches!(bytes.get(marker), Some(b' ' | b'\t')) {
+        return 0;
+    }
+    let mut len = skip_blanks(text, marker);
+    for task in ["[ ]", "[x]", "[X]"] {
+        if text[len..].starts_with(task) && matches!(bytes.get(len + 3), Some(b' ' | b'\t')) {
+            len = skip_blanks(text, len + 3);
+        }
+    }
+    len
+}
+
+/// Split `line` (without its line ending) at the end of its prefix, with
+/// `markers` as the comment markers to recognize
+///
+/// A marker of two or more characters also matches longer runs of its last
+/// character and a trailing `!`, so `//` covers `///` and `//!`.
+fn split_prefix<'a>(line: &'a str, markers: &[&str]) -> Prefixed<'a> {
+    let mut pos = skip_blanks(line, 0);
+    if let Some(marker) = markers
+        .iter()
+        .filter(|marker| !marker.is_empty())
+        .find(|marker| line[pos..].starts_with(**marker))
+    {
+        pos += marker.len();
+        if marker.len() >= 2 {
+            let last = marker.chars().last().unwrap_or(' ');
+            pos += line[pos..].len() - line[pos..].trim_start_matches([last, '!']).len();
+        }
+        pos = skip_blanks(line, pos);
+    }
+    while line[pos..].starts_with('>') {
+        pos = skip_blanks(line, pos + 1);
+    }
+    let item = list_marker_len(&line[pos..]);
+    Prefixed {
+        lead: &line[..pos],
+        item: &line[pos..pos + item],
+        body: line[pos + item..].trim_end(),
+    }
+}
+
+/// A line of text: its offset, and its content without the line ending
+fn lines(text: &str) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for raw in text.split_inclusive('\n') {
+        lines.push((start, raw.trim_end_matches('\n').trim_end_matches('\r')));
+        start += raw.len();
+    }
+    lines
+}
+
+/// Line indices of the paragraphs among `lines`
+fn paragraphs(lines: &[Prefixed], tab_size: usize) -> Vec<Range<usize>> {
+    let mut paragraphs: Vec<Range<usize>> = Vec::new();
+    let mut continuation: Option<String> = None;
+    let mut in_fence = false;
+    for (i, line) in lines.iter().enumerate() {
+        if line.is_fence() {
+            in_fence = !in_fence;
+        }
+        if in_fence || line.is_break() {
+            continuation = None;
+            continue;
+        }
+        match &continuation {
+            Some(prefix) if line.item.is_empty() && line.lead == prefix => {
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.end = i + 1;
+                }
+            }
+            _ => {
+                paragraphs.push(i..i + 1);
+                continuation = Some(line.continuation(tab_size));
+            }
+        }
+    }
+    paragraphs
+}
+
+/// Fill `words` into lines of at most `width` columns where possible, the
+/// first starting with `first` and the others with `rest`
+fn fill(first: &str, rest: &str, words: &[&str], width: usize, tab_size: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = first.to_string();
+    let mut column = line_width(first, tab_size);
+    let mut empty = true;
+    for word in words {
+        let word_width = str_width(word);
+        if !empty && column + 1 + word_width > width {
+            lines.push(std::mem::replace(&mut line, rest.to_string()));
+            column = line_width(rest, tab_size);
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+            column += 1;
+        }
+        line.push_str(word);
+        column += word_width;
+        empty = false;
+    }
+    lines.push(line);
+    lines
+}
+
+/// Byte range of the paragraph holding `pos` in `text`, from the start of
+/// its first line to the end of its last line's content
+///
+/// Returns None when `pos` is on a line that isn't part of a paragraph.
+pub fn paragraph_at(
+    text: &str,
+    pos: usize,
+    markers: &[&str],
+    tab_size: usize,
+) -> Option<Range<usize>> {
+    let lines = lines(text);
+    let prefixed: Vec<Prefixed> = lines
+        .iter()
+        .map(|(_, line)| split_prefix(line, markers))
+        .collect();
+    let line = lines
+        .iter()
+        .rposition(|(start, _)| *start <= pos)
+        .unwrap_or(0);
+    let paragraph = paragraphs(&prefixed, tab_size)
+        .into_iter()
+        .find(|paragraph| paragraph.contains(&line))?;
+    let (start, _) = lines[paragraph.start];
+    let (last_start, last) = lines[paragraph.end - 1];
+    Some(start..last_start + last.len())
+}
+
+/// Rewrap every paragraph of `text`, which holds whole lines, to `width`
+/// columns
+///
+/// Words longer than the width get a line of their own. Everything between
+/// the paragraphs, and the line endings, are kept.
+pub fn reflow(text: &str, markers: &[&str], width: usize, tab_size: usize) -> String {
+    let lines = lines(text);
+    let prefixed: Vec<Prefixed> = lines
+        .iter()
+        .map(|(_, line)| split_prefix(line, markers))
+        .collect();
+    let eol = if text.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    for paragraph in paragraphs(&prefixed, tab_size) {
+        let (start, _) = lines[paragraph.start];
+        let (last_start, last) = lines[paragraph.end - 1];
+        result.push_str(&text[copied..start]);
+
+        let first = &prefixed[paragraph.start];
+        let head = format!("{}{}", first.lead, first.item);
+        let words: Vec<&str> = prefixed[paragraph.clone()]
+            .iter()
+            .flat_map(|line| line.body.split_whitespace())
+            .collect();
+        let filled = fill(
+            &head,
+            &first.continuation(tab_size),
+            &words,
+            width,
+            tab_size,
+        );
+        result.push_str(&filled.join(eol));
+        copied = last_start + last.len();
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+/// Where to break `line` (without its line ending) so that it fits `width`
+/// columns: the whitespace to replace, and the line break and prefix to put
+/// there
+///
+/// The break goes at the last space that keeps the text before it within the
+/// width, or after the first word if that alone is too long. Returns None if
+/// the line fits or has nowhere to break.
+pub fn hard_wrap(
+    line: &str,
+    markers: &[&str],
+    width: usize,
+    tab_size: usize,
+    eol: &str,
+) -> Option<(Range<usize>, String)> {
+    let content = line.trim_end();
+    if line_width(content, tab_size) <= width {
+        return None;
+    }
+    let prefixed = split_prefix(line, markers);
+    if prefixed.is_break() {
+        return None;
+    }
+    let body_start = prefixed.lead.len() + prefixed.item.len();
+
+    let mut spaces = Vec::new();
+    let mut run_start = None;
+    for (i, c) in content[body_start..].char_indices() {
+        let i = body_start + i;
+        match (c == ' ' || c == '\t', run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                spaces.push(start..i);
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    let space = spaces
+        .iter()
+        .rev()
+        .find(|space| line_width(&content[..space.start], tab_size) <= width)
+        .or_else(|| spaces.first())?
+        .clone();
+    Some((space, format!("{}{}", eol, prefixed.continuation(tab_size))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reflow_plain_paragraphs() {
+        let text = "one two three four five six\nseven\n\neight nine\n";
+        assert_eq!(
+            reflow(text, &[], 14, 4),
+            "one two three\nfour five six\nseven\n\neight nine\n"
+        );
+        // A word longer than the width gets a line of its own
+        assert_eq!(reflow("a abcdefgh b", &[], 4, 4), "a\nabcdefgh\nb");
+        assert_eq!(reflow("a b\r\nc d\r\n", &[], 3, 4), "a b\r\nc d\r\n");
+    }
+
+    #[test]
+    fn test_reflow_keeps_comment_prefixes() {
+        let text =
+            "    /// Doc comment that runs\n    /// long.\n    //\n    // Next\n    // one\n";
+        assert_eq!(
+            reflow(text, &["//"], 24, 4),
+            "    /// Doc comment that\n    /// runs long.\n    //\n    // Next one\n"
+        );
+        let text = "# a b c\n# d\n";
+        assert_eq!(reflow(text, &["#"], 5, 4), "# a b\n# c d\n");
+        let text = " * a b c\n * d\n */";
+        assert_eq!(reflow(text, &["//", "*"], 6, 4), " * a b\n * c d\n */");
+    }
+
+    #[test]
+    fn test_reflow_markdown_lists_and_quotes() {
+        let text = "- one two\n  three\n- four\n> quoted text\n> here\n";
+        assert_eq!(
+            reflow(text, &[], 9, 4),
+            "- one two\n  three\n- four\n> quoted\n> text\n> here\n"
+        );
+        assert_eq!(reflow("1. a b c d", &[], 6, 4), "1. a b\n   c d");
+        assert_eq!(reflow("- [ ] a b c", &[], 9, 4), "- [ ] a b\n      c");
+        // Headings, tables and code blocks are left alone
+        let text = "# A long heading\n| a | b |\n```\nlong code line\n```\n";
+        assert_eq!(reflow(text, &[], 4, 4), text);
+    }
+
+    #[test]
+    fn test_paragraph_at() {
+        let text = "// a\n// b\n//\n// c\n\nd\n";
+        assert_eq!(paragraph_at(text, 6, &["//"], 4), Some(0..9));
+        assert_eq!(paragraph_at(text, 10, &["//"], 4), None);
+        assert_eq!(paragraph_at(text, 14, &["//"], 4), Some(13..17));
+        assert_eq!(paragraph_at(text, 20, &["//"], 4), Some(19..20));
+        // Each list item is its own paragraph
+        let text = "- a\n  b\n- c\n";
+        assert_eq!(paragraph_at(text, 5, &[], 4), Some(0..7));
+        assert_eq!(paragraph_at(text, 9, &[], 4), Some(8..11));
+    }
+
+    #[test]
+    fn test_hard_wrap() {
+        assert_eq!(
+            hard_wrap("    // one two three", &["//"], 16, 4, "\n"),
+            Some((14..15, "\n    // ".to_string()))
+        );
+        assert_eq!(
+            hard_wrap("- item text", &[], 8, 4, "\n"),
+            Some((6..7, "\n  ".to_string()))
+        );
+        // A first word longer than the width breaks after it
+        assert_eq!(
+            hard_wrap("abcdefgh ij", &[], 4, 4, "\r\n"),
+            Some((8..9, "\r\n".to_string()))
+        );
+        assert_eq!(hard_wrap("short", &[], 10, 4, "\n"), None);
+        assert_eq!(hard_wrap("abcdefghijkl", &[], 4, 4, "\n"), None);
+        assert_eq!(hard_wrap("// abcdefghijkl", &["//"], 4, 4, "\n"), None);
+    }
+}
@@ -26,7 +26,7 @@ use crate::primitives::highlighter::{HighlightSpan, Language};
 use crate::primitives::word_navigation::{find_word_end, find_word_start, is_word_char};
 use ratatui::style::Color;
 use std::ops::Range;
-use tree_sitter::{Parser, Query, QueryCursor, StreamingIterator};
+use tree_sitter::{Node, Parser, Query, QueryCursor, QueryMatches, StreamingIterator, Tree};
 
 /// Default subtle background color for occurrence highlights
 /// A dark gray that's visible but not distracting
@@ -172,6 +172,83 @@ const C_LOCALS_QUERY: &str = r#"
 (identifier) @local.reference
 "#;
 
+/// Syntax tree covering the parsed window: either the buffer's full-document
+/// tree or a fresh parse of just the window
+enum WindowTree<'a> {
+    /// Full-document tree (node offsets are buffer offsets)
+    Document {
+        tree: &'a Tree,
+        window: Range<usize>,
+    },
+    /// Tree parsed from the window (node offsets are relative to `start`)
+    Window { tree: Tree, start: usize },
+}
+
+impl<'a> WindowTree<'a> {
+    fn new(
+        parser: &mut Parser,
+        tree: Option<&'a Tree>,
+        source: &[u8],
+        window: Range<usize>,
+    ) -> Option<Self> {
+        match tree {
+            Some(tree) => Some(Self::Document { tree, window }),
+            None => Some(Self::Window {
+                tree: parser.parse(source, None)?,
+                start: window.start,
+            }),
+        }
+    }
+
+    /// Offset to add to node offsets to get buffer offsets
+    fn base(&self) -> usize {
+        match self {
+            Self::Document { .. } => 0,
+            Self::Window { start, .. } => *start,
+        }
+    }
+
+    /// Buffer byte range of a node
+    fn node_range(&self, node: &Node) -> Range<usize> {
+        node.start_byte() + self.base()..node.end_byte() + self.base()
+    }
+
+    /// Text of a node, if it lies within the window `source` was sliced from
+    fn node_text(&self, source: &[u8], node: &Node) -> Option<String> {
+        let window_start = match self {
+            Self::Document { window, .. } => window.start,
+            Self::Window { start, .. } => *start,
+        };
+        let range = self.node_range(node);
+        let bytes = source
+            .get(range.start.checked_sub(window_start)?..range.end.checked_sub(window_start)?)?;
+        std::str::from_utf8(bytes).ok().map(|s| s.to_string())
+    }
+
+    /// Run `query` over the window
+    fn matches<'c, 'q>(
+        &'q self,
+        query_cursor: &'c mut QueryCursor,
+        query: &'q Query,
+        buffer: &'q Buffer,
+    ) -> QueryMatches<'q, 'q, impl tree_sitter::TextProvider<Vec<u8>> + 'q, Vec<u8>>
+    where
+        'c: 'q,
+    {
+        let (root, base) = match self {
+            Self::Document { tree, window } => {
+                query_cursor.set_byte_range(window.clone());
+                (tree.root_node(), 0)
+            }
+            Self::Window { tree, start } => (tree.root_node(), *start),
+        };
+        let text_provider = move |node: Node| {
+            std::iter::once(buffer.slice_bytes(node.start_byte() + base..node.end_byte() + base))
+        };
+        query_cursor.matches(query, root, text_provider)
+    }
+}
+
 impl SemanticHighlighter {
     /// Create a new semantic highlighter with default settings
     pub fn new() -> Self {
@@ -203,25 +280,7 @@ impl SemanticHighlighter {
     /// This enables syntax-aware identifier matching for the given language.
     /// If the language is not supported or parsing fails, falls back to text matching.
     pub fn set_language(&mut self, language: &Language) {
-        let ts_language = match language {
-            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
-            Language::Python => tree_sitter_python::LANGUAGE.into(),
-            Language::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
-            Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
-            Language::Go => tree_sitter_go::LANGUAGE.into(),
-            Language::C => tree_sitter_c::LANGUAGE.into(),
-            Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
-            Language::Java => tree_sitter_java::LANGUAGE.into(),
-            Language::Php => tree_sitter_php::LANGUAGE_PHP.into(),
-            Language::Ruby => tree_sitter_ruby::LANGUAGE.into(),
-            Language::Bash => tree_sitter_bash::LANGUAGE.into(),
-            Language::Lua => tree_sitter_lua::LANGUAGE.into(),
-            Language::Pascal => tree_sitter_pascal::LANGUAGE.into(),
-            Language::Json => tree_sitter_json::LANGUAGE.into(),
-            Language::HTML => tree_sitter_html::LANGUAGE.into(),
-            Language::CSS => tree_sitter_css::LANGUAGE.into(),
-            Language::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
-        };
+        let ts_language = language.tree_sitter_language();
 
         // Create parser
         let mut parser = Parser::new();
@@ -323,6 +382,28 @@ impl SemanticHighlighter {
         viewport_start: usize,
         viewport_end: usize,
         context_bytes: usize,
+    ) -> Vec<HighlightSpan> {
+        self.highlight_occurrences_with_tree(
+            buffer,
+            None,
+            cursor_position,
+            viewport_start,
+            viewport_end,
+            context_bytes,
+        )
+    }
+
+    /// Get highlights for word occurrences, reading scopes from the buffer's
+    /// full-document syntax tree when one is given instead of parsing the
+    /// viewport window
+    pub fn highlight_occurrences_with_tree(
+        &mut self,
+        buffer: &Buffer,
+        tree: Option<&Tree>,
+        cursor_position: usize,
+        viewport_start: usize,
+        viewport_end: usize,
+        context_bytes: usize,
     ) -> Vec<HighlightSpan> {
         if !self.enabled {
             return Vec::new();
@@ -332,6 +413,7 @@ impl SemanticHighlighter {
         if self.has_locals() {
             return self.highlight_with_locals(
                 buffer,
+                tree,
                 cursor_position,
                 viewport_start,
                 viewport_end,
@@ -343,6 +425,7 @@ impl SemanticHighlighter {
         if self.has_tree_sitter() {
             return self.highlight_with_tree_sitter(
                 buffer,
+                tree,
                 cursor_position,
                 viewport_start,
                 viewport_end,
@@ -368,6 +451,7 @@ impl SemanticHighlighter {
     fn highlight_with_locals(
         &mut self,
         buffer: &Buffer,
+        tree: Option<&Tree>,
         cursor_position: usize,
         viewport_start: usize,
         viewport_end: usize,
@@ -397,12 +481,13 @@ impl SemanticHighlighter {
         let parse_end = (viewport_end + context_bytes).min(buffer.len());
         let source = buffer.slice_bytes(parse_start..parse_end);
 
-        // Parse the source
-        let tree = match parser.parse(&source, None) {
-            Some(t) => t,
+        // Use the full-document tree if there is one, otherwise parse the window
+        let window = match WindowTree::new(parser, tree, &source, parse_start..parse_end) {
+            Some(window) => window,
             None => {
                 return self.highlight_with_tree_sitter(
                     buffer,
+                    tree,
                     cursor_position,
                     viewport_start,
                     viewport_end,
@@ -413,7 +498,7 @@ impl SemanticHighlighter {
 
         // Run the locals query
         let mut query_cursor = QueryCursor::new();
-        let mut matches = query_cursor.matches(query, tree.root_node(), source.as_slice());
+        let mut matches = window.matches(&mut query_cursor, query, buffer);
 
         // Collect scopes, definitions, and references
         let mut scopes: Vec<Range<usize>> = Vec::new();
@@ -423,16 +508,14 @@ impl SemanticHighlighter {
         // Build scope stack for each position
         while let Some(m) = matches.next() {
             for capture in m.captures {
-                let node = capture.node;
-                let start = parse_start + node.start_byte();
-                let end = parse_start + node.end_byte();
-                let range = start..end;
-
-                // Get the text
-                let text_bytes = &source[node.start_byte()..node.end_byte()];
-                let text = match std::str::from_utf8(text_bytes) {
-                    Ok(s) => s.to_string(),
-                    Err(_) => continue,
+                let range = window.node_range(&capture.node);
+                let (start, end) = (range.start, range.end);
+
+                // Get the text (scopes may extend past the window; their text is unused)
+                let text = match window.node_text(&source, &capture.node) {
+                    Some(text) => text,
+                    None if Some(capture.index) == scope_idx => String::new(),
+                    None => continue,
                 };
 
                 if Some(capture.index) == scope_idx {
@@ -577,6 +660,7 @@ impl SemanticHighlighter {
     fn highlight_with_tree_sitter(
         &mut self,
         buffer: &Buffer,
+        tree: Option<&Tree>,
         cursor_position: usize,
         viewport_start: usize,
         viewport_end: usize,
@@ -596,9 +680,9 @@ impl SemanticHighlighter {
         let parse_end = (viewport_end + context_bytes).min(buffer.len());
         let source = buffer.slice_bytes(parse_start..parse_end);
 
-        // Parse the source
-        let tree = match parser.parse(&source, None) {
-            Some(t) => t,
+        // Use the full-document tree if there is one, otherwise parse the window
+        let window = match WindowTree::new(parser, tree, &source, parse_start..parse_end) {
+            Some(window) => window,
             None => {
                 tracing::debug!("Tree-sitter parsing failed, falling back to text matching");
                 return self.highlight_with_text_matching(
@@ -615,7 +699,7 @@ impl SemanticHighlighter {
 
         // Find all identifier nodes using the query
         let mut query_cursor = QueryCursor::new();
-        let mut matches = query_cursor.matches(query, tree.root_node(), source.as_slice());
+        let mut matches = window.matches(&mut query_cursor, query, buffer);
 
         // Collect all identifier ranges and their text
         let mut identifiers: Vec<(Range<usize>, String)> = Vec::new();
@@ -623,15 +707,13 @@ impl SemanticHighlighter {
 
         while let Some(m) = matches.next() {
             for capture in m.captures {
-                let node = capture.node;
-                let start = parse_start + node.start_byte();
-                let end = parse_start + node.end_byte();
+                let range = window.node_range(&capture.node);
+                let (start, end) = (range.start, range.end);
 
                 // Get the identifier text
-                let text_bytes = &source[node.start_byte()..node.end_byte()];
-                let text = match std::str::from_utf8(text_bytes) {
-                    Ok(s) => s.to_string(),
-                    Err(_) => continue,
+                let text = match window.node_text(&source, &capture.node) {
+                    Some(text) => text,
+                    None => continue,
                 };
 
                 // Check minimum length
@@ -1031,6 +1113,46 @@ fn second() {
         );
     }
 
+    #[test]
+    fn test_highlighting_from_syntax_tree() {
+        use crate::primitives::highlighter::Language;
+        use crate::primitives::syntax_tree::SyntaxTree;
+
+        let mut code = String::from("fn first() {\n    let foo = 1;\n");
+        for _ in 0..50 {
+            code.push_str("    println!(\"{}\", foo);\n");
+        }
+        code.push_str("}\n\nfn second() {\n    let foo = 2;\n    println!(\"{}\", foo);\n}\n");
+        let buffer = Buffer::from_str_test(&code);
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+        assert!(syntax_tree.wait_for_parse(&buffer));
+
+        let mut highlighter = SemanticHighlighter::new();
+        highlighter.set_language(&Language::Rust);
+
+        // Viewport on the end of `first` and all of `second`, cursor on the last `foo` in `first`
+        let second_start = code.find("fn second").unwrap();
+        let viewport_start = second_start - 60;
+        let cursor = code[..second_start].rfind("foo").unwrap();
+
+        let spans = highlighter.highlight_occurrences_with_tree(
+            &buffer,
+            syntax_tree.tree(),
+            cursor,
+            viewport_start,
+            buffer.len(),
+            100_000,
+        );
+
+        // Scopes come from the document tree: `second` has its own `foo`
+        assert!(spans.iter().any(|s| s.range == (cursor..cursor + 3)));
+        assert!(
+            spans.iter().all(|s| s.range.end <= second_start),
+            "{:?}",
+            spans.iter().map(|s| s.range.clone()).collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn test_shadowing_in_nested_scope() {
         use crate::primitives::highlighter::Language;
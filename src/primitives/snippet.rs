@@ -0,0 +1,868 @@
+//! Snippet body syntax
+//!
+//! Parses snippet bodies in the TextMate / VS Code syntax and expands them
+//! into plain text plus the positions of their fields:
+//!
+//! - tabstops: `$1`, `${1}`, with `$0` as the final cursor position
+//! - placeholders: `${1:default text}`, which may nest other fields
+//! - choices: `${1|one,two,three|}`
+//! - variables: `$NAME`, `${NAME}`, `${NAME:default}`
+//! - transforms: `${1/regex/format/flags}` and `${NAME/regex/format/flags}`
+//!
+//! A tabstop that appears more than once is mirrored: every occurrence shows
+//! the text of the first one, optionally passed through a transform.
+
+use regex::{Captures, Regex, RegexBuilder};
+use std::ops::Range;
+
+/// A parsed snippet body
+#[derive(Debug, Clone)]
+pub struct SnippetBody {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    Tabstop {
+        index: u32,
+        placeholder: Vec<Part>,
+        choices: Vec<String>,
+        transform: Option<Transform>,
+    },
+    Variable {
+        name: String,
+        default: Option<Vec<Part>>,
+        transform: Option<Transform>,
+    },
+}
+
+/// A regex replacement applied to a mirrored tabstop or a variable
+#[derive(Debug, Clone)]
+pub struct Transform {
+    regex: Regex,
+    format: String,
+    global: bool,
+}
+
+/// One field of an expanded snippet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// Tabstop number; 0 is the final cursor position
+    pub index: u32,
+    /// Byte range within the expanded text
+    pub range: Range<usize>,
+    /// Alternatives offered for a choice field
+    pub choices: Vec<String>,
+    /// Whether this occurrence mirrors another field with the same index
+    pub is_mirror: bool,
+    /// Positions in `Expansion::fields` of the fields inside this one's
+    /// placeholder (nested fields come before the field containing them)
+    pub nested: Range<usize>,
+}
+
+/// The result of expanding a snippet
+#[derive(Debug, Clone)]
+pub struct Expansion {
+    pub text: String,
+    /// Fields in the order they appear in the text
+    pub fields: Vec<Field>,
+    /// Transform for each mirror field, by position in `fields`
+    transforms: Vec<Option<Transform>>,
+}
+
+impl Expansion {
+    /// Transform of the field at `field` (a position in `fields`), if any
+    pub fn transform(&self, field: usize) -> Option<&Transform> {
+        self.transforms.get(field).and_then(Option::as_ref)
+    }
+
+    /// Tabstop numbers in navigation order: 1, 2, ... and finally 0
+    pub fn tab_order(&self) -> Vec<u32> {
+        let mut order: Vec<u32> = self
+            .fields
+            .iter()
+            .map(|field| field.index)
+            .filter(|&index| index != 0)
+            .collect();
+        order.sort_unstable();
+        order.dedup();
+        order.push(0);
+        order
+    }
+}
+
+impl SnippetBody {
+    /// Parse a snippet body
+    ///
+    /// Malformed constructs are kept as literal text rather than rejected.
+    pub fn parse(body: &str) -> Self {
+        let mut parser = Parser {
+            chars: body.chars().collect(),
+            pos: 0,
+        };
+        Self {
+            parts: parser.parse_parts(false),
+        }
+    }
+
+    /// Expand the body
+    ///
+    /// `resolve` returns the value of a variable, or `None` for unknown
+    /// variables, which expand to their default or their own name. Every
+    /// newline in the output is replaced by `newline` (line ending plus
+    /// indentation of the line the snippet is inserted on).
+    pub fn expand(&self, resolve: &dyn Fn(&str) -> Option<String>, newline: &str) -> Expansion {
+        let mut primaries = Vec::new();
+        let mut occurrence = 0;
+        find_primaries(&self.parts, &mut occurrence, &mut primaries);
+
+        let mut expander = Expander {
+            root: &self.parts,
+            resolve,
+            newline,
+            primaries: &primaries,
+            computing: Vec::new(),
+            occurrence: 0,
+            text: String::new(),
+            fields: Vec::new(),
+            transforms: Vec::new(),
+            record_fields: true,
+        };
+        expander.expand_parts(&self.parts);
+
+        let mut expansion = Expansion {
+            text: expander.text,
+            fields: expander.fields,
+            transforms: expander.transforms,
+        };
+        if !expansion.fields.iter().any(|field| field.index == 0) {
+            let end = expansion.text.len();
+            expansion.fields.push(Field {
+                index: 0,
+                range: end..end,
+                choices: Vec::new(),
+                is_mirror: false,
+                nested: 0..0,
+            });
+            expansion.transforms.push(None);
+        }
+        expansion
+    }
+}
+
+/// For each tabstop index, the occurrence number (in document order) that
+/// holds its text: the first one with a placeholder or choices, else the first
+fn find_primaries(parts: &[Part], occurrence: &mut usize, primaries: &mut Vec<(u32, usize, bool)>) {
+    for part in parts {
+        match part {
+            Part::Tabstop {
+                index,
+                placeholder,
+                choices,
+                transform,
+            } => {
+                let this = *occurrence;
+                *occurrence += 1;
+                let has_text =
+                    (!placeholder.is_empty() || !choices.is_empty()) && transform.is_none();
+                match primaries.iter_mut().find(|(i, _, _)| i == index) {
+                    Some(entry) if !entry.2 && has_text => *entry = (*index, this, true),
+                    Some(_) => {}
+                    None => primaries.push((*index, this, has_text)),
+                }
+                find_primaries(placeholder, occurrence, primaries);
+            }
+            Part::Variable {
+                default: Some(default),
+                ..
+            } => find_primaries(default, occurrence, primaries),
+            _ => {}
+        }
+    }
+}
+
+/// The tabstop with occurrence number `target`, counted as in `find_primaries`
+fn find_occurrence<'a>(
+    parts: &'a [Part],
+    target: usize,
+    occurrence: &mut usize,
+) -> Option<&'a Part> {
+    for part in parts {
+        match part {
+            Part::Tabstop { placeholder, .. } => {
+                if *occurrence == target {
+                    return Some(part);
+                }
+                *occurrence += 1;
+                if let Some(found) = find_occurrence(placeholder, target, occurrence) {
+                    return Some(found);
+                }
+            }
+            Part::Variable {
+                default: Some(default),
+                ..
+            } => {
+                if let Some(found) = find_occurrence(default, target, occurrence) {
+                    return Some(found);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Number of tabstops in `parts`, including nested ones
+fn count_tabstops(parts: &[Part]) -> usize {
+    parts
+        .iter()
+        .map(|part| match part {
+            Part::Text(_) => 0,
+            Part::Tabstop { placeholder, .. } => 1 + count_tabstops(placeholder),
+            Part::Variable { default, .. } => default.as_deref().map_or(0, count_tabstops),
+        })
+        .sum()
+}
+
+struct Expander<'a> {
+    root: &'a [Part],
+    resolve: &'a dyn Fn(&str) -> Option<String>,
+    newline: &'a str,
+    primaries: &'a [(u32, usize, bool)],
+    /// Tabstops whose text is being computed for a mirror, to stop a
+    /// placeholder that mirrors itself from recursing forever
+    computing: Vec<u32>,
+    occurrence: usize,
+    text: String,
+    fields: Vec<Field>,
+    transforms: Vec<Option<Transform>>,
+    /// False while computing a primary's text for one of its mirrors
+    record_fields: bool,
+}
+
+impl Expander<'_> {
+    fn push_text(&mut self, text: &str) {
+        let mut lines = text.split('\n');
+        if let Some(first) = lines.next() {
+            self.text.push_str(first);
+        }
+        for line in lines {
+            self.text.push_str(self.newline);
+            self.text.push_str(line);
+        }
+    }
+
+    fn expand_parts(&mut self, parts: &[Part]) {
+        for part in parts {
+            match part {
+                Part::Text(text) => self.push_text(text),
+                Part::Tabstop {
+                    index,
+                    placeholder,
+                    choices,
+                    transform,
+                } => self.expand_tabstop(*index, placeholder, choices, transform.as_ref()),
+                Part::Variable {
+                    name,
+                    default,
+                    transform,
+                } => {
+                    let value = (self.resolve)(name);
+                    match (value, default) {
+                        (Some(value), default) if !value.is_empty() => {
+                            let value = match transform {
+                                Some(transform) => transform.apply(&value),
+                                None => value,
+                            };
+                            self.push_text(&value);
+                            self.occurrence += default.as_deref().map_or(0, count_tabstops);
+                        }
+                        (_, Some(default)) => self.expand_parts(default),
+                        (Some(_), None) => {}
+                        (None, None) => self.push_text(name),
+                    }
+                }
+            }
+        }
+    }
+
+    fn expand_tabstop(
+        &mut self,
+        index: u32,
+        placeholder: &[Part],
+        choices: &[String],
+        transform: Option<&Transform>,
+    ) {
+        let this = self.occurrence;
+        self.occurrence += 1;
+        let primary = self
+            .primaries
+            .iter()
+            .find(|(i, _, _)| *i == index)
+            .map_or(this, |(_, occurrence, _)| *occurrence);
+        let is_mirror = primary != this;
+
+        let start = self.text.len();
+        let first_nested = self.fields.len();
+        if is_mirror {
+            let value = self.primary_text(index, primary);
+            let value = match transform {
+                Some(transform) => transform.apply(&value),
+                None => value,
+            };
+            self.text.push_str(&value);
+            // A mirror shows its primary's text, not its own placeholder
+            self.occurrence += count_tabstops(placeholder);
+        } else if let Some(first) = choices.first() {
+            self.push_text(first);
+        } else {
+            self.expand_parts(placeholder);
+        }
+
+        if self.record_fields {
+            self.fields.push(Field {
+                index,
+                range: start..self.text.len(),
+                choices: choices.to_vec(),
+                is_mirror,
+                nested: first_nested..self.fields.len(),
+            });
+            self.transforms
+                .push(transform.filter(|_| is_mirror).cloned());
+        }
+    }
+
+    /// Text of a tabstop's primary occurrence, without recording fields
+    fn primary_text(&self, index: u32, primary: usize) -> String {
+        if self.computing.contains(&index) {
+            return String::new();
+        }
+        let Some(Part::Tabstop {
+            placeholder,
+            choices,
+            ..
+        }) = find_occurrence(self.root, primary, &mut 0)
+        else {
+            return String::new();
+        };
+
+        let mut computing = self.computing.clone();
+        computing.push(index);
+        let mut sub = Expander {
+            root: self.root,
+            resolve: self.resolve,
+            newline: self.newline,
+            primaries: self.primaries,
+            computing,
+            occurrence: primary + 1,
+            text: String::new(),
+            fields: Vec::new(),
+            transforms: Vec::new(),
+            record_fields: false,
+        };
+        match choices.first() {
+            Some(first) => sub.push_text(first),
+            None => sub.expand_parts(placeholder),
+        }
+        sub.text
+    }
+}
+
+impl Transform {
+    fn new(pattern: &str, format: String, flags: &str) -> Option<Self> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(flags.contains('i'))
+            .multi_line(flags.contains('m'))
+            .dot_matches_new_line(flags.contains('s'))
+            .build()
+            .ok()?;
+        Some(Self {
+            regex,
+            format,
+            global: flags.contains('g'),
+        })
+    }
+
+    /// Replace the first match (every match with the `g` flag) by the format
+    pub fn apply(&self, input: &str) -> String {
+        let limit = if self.global { 0 } else { 1 };
+        self.regex
+            .replacen(input, limit, |caps: &Captures| {
+                format_captures(&self.format, caps)
+            })
+            .into_owned()
+    }
+}
+
+/// Expand a transform format string against regex captures
+///
+/// Supports `$n`, `${n}`, `${n:/upcase}` (also `downcase`, `capitalize`,
+/// `camelcase` and `pascalcase`), `${n:+if}`, `${n:?if:else}`, `${n:-else}`
+/// and `${n:else}`.
+fn format_captures(format: &str, caps: &Captures) -> String {
+    let chars: Vec<char> = format.chars().collect();
+    let group = |n: usize| caps.get(n).map_or("", |m| m.as_str());
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                match chars[i + 1] {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    c => out.push(c),
+                }
+                i += 2;
+            }
+            '$' => {
+                let digits: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect();
+                if let Ok(n) = digits.parse::<usize>() {
+                    out.push_str(group(n));
+                    i += 1 + digits.len();
+                    continue;
+                }
+                if chars.get(i + 1) != Some(&'{') {
+                    out.push('$');
+                    i += 1;
+                    continue;
+                }
+                let Some(close) = chars[i..].iter().position(|&c| c == '}') else {
+                    out.push('$');
+                    i += 1;
+                    continue;
+                };
+                let inner: String = chars[i + 2..i + close].iter().collect();
+                i += close + 1;
+                let (number, rest) = inner.split_once(':').unwrap_or((&inner, ""));
+                let Ok(n) = number.parse::<usize>() else {
+                    continue;
+                };
+                let value = group(n);
+                if inner.len() == number.len() {
+                    out.push_str(value);
+                } else if let Some(case) = rest.strip_prefix('/') {
+                    out.push_str(&change_case(value, case));
+                } else if let Some(if_text) = rest.strip_prefix('+') {
+                    if !value.is_empty() {
+                        out.push_str(if_text);
+                    }
+                } else if let Some(branches) = rest.strip_prefix('?') {
+                    let (if_text, else_text) = branches.split_once(':').unwrap_or((branches, ""));
+                    out.push_str(if value.is_empty() { else_text } else { if_text });
+                } else {
+                    let else_text = rest.strip_prefix('-').unwrap_or(rest);
+                    out.push_str(if value.is_empty() { else_text } else { value });
+                }
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn change_case(value: &str, case: &str) -> String {
+    let capitalize = |word: &str| {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+            None => String::new(),
+        }
+    };
+    match case {
+        "upcase" => value.to_uppercase(),
+        "downcase" => value.to_lowercase(),
+        "capitalize" => capitalize(value),
+        "pascalcase" | "camelcase" => {
+            let words: Vec<String> = value
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(|word| capitalize(&word.to_lowercase()))
+                .collect();
+            let joined = words.concat();
+            if case == "camelcase" {
+                let mut chars = joined.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            } else {
+                joined
+            }
+        }
+        _ => value.to_string(),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parse until the end of input, or an unescaped `}` inside a placeholder
+    fn parse_parts(&mut self, in_placeholder: bool) -> Vec<Part> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '}' if in_placeholder => break,
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(escaped @ ('$' | '}' | '\\')) => {
+                            text.push(escaped);
+                            self.pos += 1;
+                        }
+                        _ => text.push('\\'),
+                    }
+                }
+                '$' => {
+                    let start = self.pos;
+                    match self.parse_dollar() {
+                        Some(part) => {
+                            if !text.is_empty() {
+                                parts.push(Part::Text(std::mem::take(&mut text)));
+                            }
+                            parts.push(part);
+                        }
+                        None => {
+                            self.pos = start + 1;
+                            text.push('$');
+                        }
+                    }
+                }
+                c => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        parts
+    }
+
+    fn parse_int(&mut self) -> Option<u32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn parse_name(&mut self) -> Option<String> {
+        if !self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        {
+            return None;
+        }
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            self.pos += 1;
+        }
+        Some(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Parse a construct starting at `$`, or `None` if it is malformed
+    fn parse_dollar(&mut self) -> Option<Part> {
+        self.pos += 1;
+        if !self.eat('{') {
+            if let Some(index) = self.parse_int() {
+                return Some(tabstop(index));
+            }
+            let name = self.parse_name()?;
+            return Some(Part::Variable {
+                name,
+                default: None,
+                transform: None,
+            });
+        }
+
+        if let Some(index) = self.parse_int() {
+            if self.eat('}') {
+                return Some(tabstop(index));
+            }
+            if self.eat(':') {
+                let placeholder = self.parse_parts(true);
+                return self.eat('}').then(|| Part::Tabstop {
+                    index,
+                    placeholder,
+                    choices: Vec::new(),
+                    transform: None,
+                });
+            }
+            if self.eat('|') {
+                let choices = self.parse_choices()?;
+                return Some(Part::Tabstop {
+                    index,
+                    placeholder: Vec::new(),
+                    choices,
+                    transform: None,
+                });
+            }
+            if self.eat('/') {
+                let transform = self.parse_transform()?;
+                return Some(Part::Tabstop {
+                    index,
+                    placeholder: Vec::new(),
+                    choices: Vec::new(),
+                    transform: Some(transform),
+                });
+            }
+            return None;
+        }
+
+        let name = self.parse_name()?;
+        if self.eat('}') {
+            return Some(Part::Variable {
+                name,
+                default: None,
+                transform: None,
+            });
+        }
+        if self.eat(':') {
+            let default = self.parse_parts(true);
+            return self.eat('}').then(|| Part::Variable {
+                name,
+                default: Some(default),
+                transform: None,
+            });
+        }
+        if self.eat('/') {
+            let transform = self.parse_transform()?;
+            return Some(Part::Variable {
+                name,
+                default: None,
+                transform: Some(transform),
+            });
+        }
+        None
+    }
+
+    /// Parse `a,b,c|}` after the opening `|`
+    fn parse_choices(&mut self) -> Option<Vec<String>> {
+        let mut choices = Vec::new();
+        let mut current = String::new();
+        loop {
+            match self.peek()? {
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek()? {
+                        escaped @ (',' | '|' | '\\' | '$' | '}') => current.push(escaped),
+                        other => {
+                            current.push('\\');
+                            current.push(other);
+                        }
+                    }
+                    self.pos += 1;
+                }
+                ',' => {
+                    self.pos += 1;
+                    choices.push(std::mem::take(&mut current));
+                }
+                '|' => {
+                    self.pos += 1;
+                    choices.push(current);
+                    return self.eat('}').then_some(choices);
+                }
+                c => {
+                    current.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Parse `regex/format/flags}` after the opening `/`
+    fn parse_transform(&mut self) -> Option<Transform> {
+        let pattern = self.parse_until_slash()?;
+        let format = self.parse_until_slash()?;
+        let mut flags = String::new();
+        while let Some(c) = self.peek() {
+            if c == '}' {
+                break;
+            }
+            flags.push(c);
+            self.pos += 1;
+        }
+        if !self.eat('}') {
+            return None;
+        }
+        Transform::new(&pattern, format, &flags)
+    }
+
+    /// Read up to an unescaped `/`, consuming it; `\/` becomes `/`
+    ///
+    /// Slashes inside `${...}` belong to format case changes like `${1:/upcase}`.
+    fn parse_until_slash(&mut self) -> Option<String> {
+        let mut out = String::new();
+        let mut depth = 0;
+        loop {
+            match self.peek()? {
+                '/' if depth == 0 => {
+                    self.pos += 1;
+                    return Some(out);
+                }
+                '$' if self.chars.get(self.pos + 1) == Some(&'{') => {
+                    depth += 1;
+                    out.push_str("${");
+                    self.pos += 2;
+                }
+                '}' if depth > 0 => {
+                    depth -= 1;
+                    out.push('}');
+                    self.pos += 1;
+                }
+                '\\' if self.chars.get(self.pos + 1) == Some(&'/') => {
+                    out.push('/');
+                    self.pos += 2;
+                }
+                '\\' => {
+                    out.push('\\');
+                    self.pos += 1;
+                    if let Some(c) = self.peek() {
+                        out.push(c);
+                        self.pos += 1;
+                    }
+                }
+                c => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+fn tabstop(index: u32) -> Part {
+    Part::Tabstop {
+        index,
+        placeholder: Vec::new(),
+        choices: Vec::new(),
+        transform: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(body: &str) -> Expansion {
+        let resolve = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" | "SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+        SnippetBody::parse(body).expand(&resolve, "\n")
+    }
+
+    fn field_text(expansion: &Expansion, index: u32) -> Vec<&str> {
+        expansion
+            .fields
+            .iter()
+            .filter(|field| field.index == index)
+            .map(|field| &expansion.text[field.range.clone()])
+            .collect()
+    }
+
+    #[test]
+    fn test_tabstops_and_placeholders() {
+        let expansion = expand("fn ${1:name}($2) {\n\t$0\n}");
+        assert_eq!(expansion.text, "fn name() {\n\t\n}");
+        assert_eq!(field_text(&expansion, 1), vec!["name"]);
+        assert_eq!(expansion.fields[1].range, 8..8);
+        assert_eq!(expansion.tab_order(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_implicit_final_tabstop() {
+        let expansion = expand("a$1b");
+        assert_eq!(expansion.fields.last().unwrap().range, 2..2);
+        assert_eq!(expansion.tab_order(), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_nested_placeholders() {
+        let expansion = expand("${1:outer ${2:inner}}");
+        assert_eq!(expansion.text, "outer inner");
+        assert_eq!(field_text(&expansion, 1), vec!["outer inner"]);
+        assert_eq!(field_text(&expansion, 2), vec!["inner"]);
+    }
+
+    #[test]
+    fn test_mirrors_take_primary_text() {
+        let expansion = expand("$1 ${1:x} ${1/(.*)/${1:/upcase}/}");
+        assert_eq!(expansion.text, "x x X");
+        assert!(expansion.fields[0].is_mirror);
+        assert!(!expansion.fields[1].is_mirror);
+        assert!(expansion.transform(2).is_some());
+    }
+
+    #[test]
+    fn test_choices() {
+        let expansion = expand("${1|one,two\\,three|}");
+        assert_eq!(expansion.text, "one");
+        assert_eq!(expansion.fields[0].choices, vec!["one", "two,three"]);
+    }
+
+    #[test]
+    fn test_variables() {
+        assert_eq!(expand("$TM_FILENAME").text, "main.rs");
+        assert_eq!(expand("${TM_SELECTED_TEXT:none}").text, "none");
+        assert_eq!(expand("${UNKNOWN}").text, "UNKNOWN");
+        assert_eq!(expand("${TM_FILENAME/(.*)\\.rs$/$1/}").text, "main");
+    }
+
+    #[test]
+    fn test_escapes_and_malformed() {
+        assert_eq!(expand("\\$1 \\} ${").text, "$1 } ${");
+        assert_eq!(expand("cost: $").text, "cost: $");
+    }
+
+    #[test]
+    fn test_newline_and_indent() {
+        let resolve = |_: &str| None;
+        let expansion = SnippetBody::parse("{\n\t$1\n}").expand(&resolve, "\r\n  ");
+        assert_eq!(expansion.text, "{\r\n  \t\r\n  }");
+        assert_eq!(expansion.fields[0].range, 6..6);
+    }
+
+    #[test]
+    fn test_transform_format() {
+        let transform =
+            Transform::new("(\\w+)-(\\w+)?", "${1:/capitalize}${2:?+:none}".into(), "g").unwrap();
+        assert_eq!(transform.apply("foo-bar baz-"), "Foo+ Baznone");
+
+        let case = Transform::new("^.*$", "${0:/pascalcase}".into(), "").unwrap();
+        assert_eq!(case.apply("my_snake name"), "MySnakeName");
+    }
+}
@@ -0,0 +1,301 @@
+//! Delimiter pairs for surround editing
+//!
+//! A `Pair` is what text gets wrapped in: brackets, quotes or an HTML tag.
+//! `Delimiters` locate an existing pair in the buffer. The pair enclosing a
+//! position is looked up in the syntax tree when there is one, so brackets
+//! inside strings and comments are not mistaken for delimiters; without a
+//! tree, brackets are matched by counting and quotes are paired on the line.
+
+use crate::model::buffer::Buffer;
+use crate::primitives::brackets::{find_close, find_open};
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+/// Bracket pairs found by matching. Angle brackets are left out: outside a
+/// syntax tree they are more often comparisons than delimiters.
+const BRACKETS: [(u8, u8); 3] = [(b'(', b')'), (b'[', b']'), (b'{', b'}')];
+
+/// Quote characters paired on a line when matching
+const QUOTES: [u8; 3] = [b'"', b'\'', b'`'];
+
+/// Opening and closing text to surround with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pair {
+    pub open: String,
+    pub close: String,
+}
+
+impl Pair {
+    /// Pair for what the user typed
+    ///
+    /// Either bracket of a pair gives both brackets, `<tag attr="x">` gives
+    /// the element's start and end tags, and any other single character is
+    /// used on both sides.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if let Some(tag) = input.strip_prefix('<').filter(|tag| !tag.is_empty()) {
+            let tag = tag.strip_suffix('>').unwrap_or(tag).trim();
+            let name = tag.split_whitespace().next()?;
+            let valid_name = name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+            if !valid_name {
+                return None;
+            }
+            return Some(Self {
+                open: format!("<{tag}>"),
+                close: format!("</{name}>"),
+            });
+        }
+
+        let mut chars = input.chars();
+        let ch = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+        let (open, close) = match ch {
+            '(' | ')' => ('(', ')'),
+            '[' | ']' => ('[', ']'),
+            '{' | '}' => ('{', '}'),
+            '<' | '>' => ('<', '>'),
+            _ => (ch, ch),
+        };
+        Some(Self {
+            open: open.to_string(),
+            close: close.to_string(),
+        })
+    }
+}
+
+/// Positions of the opening and closing delimiters of a pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delimiters {
+    pub open: Range<usize>,
+    pub close: Range<usize>,
+}
+
+impl Delimiters {
+    fn encloses(&self, range: &Range<usize>) -> bool {
+        self.open.start <= range.start && self.close.end >= range.end
+    }
+}
+
+/// Innermost pair in the syntax tree that encloses `range`
+///
+/// A pair is a node whose first and last children are matching delimiter
+/// tokens: brackets, identical quotes, or an element's start and end tags.
+pub fn enclosing_in_tree(tree: &Tree, buffer: &Buffer, range: Range<usize>) -> Option<Delimiters> {
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(range.start, range.end)?;
+    loop {
+        if let Some(delimiters) = node_delimiters(node, buffer) {
+            if delimiters.encloses(&range) {
+                return Some(delimiters);
+            }
+        }
+        node = node.parent()?;
+    }
+}
+
+fn node_delimiters(node: Node, buffer: &Buffer) -> Option<Delimiters> {
+    let count = node.child_count();
+    if count < 2 {
+        return None;
+    }
+    let first = node.child(0)?;
+    let last = node.child(count - 1)?;
+    let open = buffer.slice_bytes(first.byte_range());
+    let close = buffer.slice_bytes(last.byte_range());
+    is_delimiter_pair(&open, &close).then(|| Delimiters {
+        open: first.byte_range(),
+        close: last.byte_range(),
+    })
+}
+
+/// Whether `open` and `close` are the two ends of a pair
+fn is_delimiter_pair(open: &[u8], close: &[u8]) -> bool {
+    if let [o] = open {
+        if let [c] = close {
+            return QUOTES.contains(o) && o == c
+                || BRACKETS.contains(&(*o, *c))
+                || (*o, *c) == (b'<', b'>');
+        }
+    }
+    if open == close {
+        // Triple-quoted strings
+        return matches!(open, b"\"\"\"" | b"'''");
+    }
+    tag_name(open).is_some_and(|name| {
+        close
+            .strip_prefix(b"</")
+            .and_then(|rest| rest.strip_suffix(b">"))
+            == Some(name)
+    })
+}
+
+/// Name of an HTML start tag such as `<div class="x">`
+fn tag_name(tag: &[u8]) -> Option<&[u8]> {
+    let inner = tag.strip_prefix(b"<")?.strip_suffix(b">")?;
+    let len = inner
+        .iter()
+        .position(|b| b.is_ascii_whitespace() || *b == b'/')
+        .unwrap_or(inner.len());
+    (len > 0 && inner[0].is_ascii_alphabetic()).then(|| &inner[..len])
+}
+
+/// Innermost bracket pair enclosing `range`, or pair of quotes on its line,
+/// found by matching characters
+pub fn enclosing_by_matching(buffer: &Buffer, range: Range<usize>) -> Option<Delimiters> {
+    let at = if range.start < buffer.len() {
+        buffer
+            .slice_bytes(range.start..range.start + 1)
+            .first()
+            .copied()
+    } else {
+        None
+    };
+    let brackets = BRACKETS.iter().filter_map(|&(open, close)| {
+        // On an opening bracket, that bracket is the innermost candidate
+        let open_at = if at == Some(open) {
+            range.start
+        } else {
+            find_open(buffer, range.start, open, close)?
+        };
+        let close_at = find_close(buffer, open_at + 1, open, close)?;
+        Some(open_at..close_at + 1)
+    });
+
+    let line = buffer.line_of(range.start);
+    let line_start = buffer.line_start(line);
+    let line_text = buffer.slice_bytes(line_start..buffer.line_end(line).max(line_start));
+    let quotes = QUOTES.iter().filter_map(|&quote| {
+        let pair = quote_pair(&line_text, range.start - line_start, quote)?;
+        Some(pair.start + line_start..pair.end + line_start)
+    });
+
+    brackets
+        .chain(quotes)
+        .filter(|pair| pair.end >= range.end)
+        .max_by_key(|pair| pair.start)
+        .map(|pair| Delimiters {
+            open: pair.start..pair.start + 1,
+            close: pair.end - 1..pair.end,
+        })
+}
+
+/// The quoted string in `line` that contains `pos`, quotes included
+fn quote_pair(line: &[u8], pos: usize, quote: u8) -> Option<Range<usize>> {
+    let quotes: Vec<usize> = (0..line.len())
+        .filter(|&i| line[i] == quote && (i == 0 || line[i - 1] != b'\\'))
+        .collect();
+    quotes
+        .chunks_exact(2)
+        .map(|pair| pair[0]..pair[1] + 1)
+        .find(|pair| pair.start <= pos && pos < pair.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::highlighter::Language;
+    use tree_sitter::Parser;
+
+    fn parse(source: &str) -> (Buffer, Tree) {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&Language::Rust.tree_sitter_language())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        (Buffer::from_str_test(source), tree)
+    }
+
+    /// Text of the delimiters found around the first `|` in `marked`
+    fn matched(marked: &str) -> Option<String> {
+        let pos = marked.find('|').unwrap();
+        let text = marked.replacen('|', "", 1);
+        let found = enclosing_by_matching(&Buffer::from_str_test(&text), pos..pos)?;
+        Some(format!(
+            "{}{}",
+            &text[found.open.clone()],
+            &text[found.close.clone()]
+        ))
+    }
+
+    #[test]
+    fn test_parse_pair() {
+        let pair = |open: &str, close: &str| {
+            Some(Pair {
+                open: open.to_string(),
+                close: close.to_string(),
+            })
+        };
+        assert_eq!(Pair::parse("("), pair("(", ")"));
+        assert_eq!(Pair::parse("]"), pair("[", "]"));
+        assert_eq!(Pair::parse("\""), pair("\"", "\""));
+        assert_eq!(Pair::parse("*"), pair("*", "*"));
+        assert_eq!(Pair::parse("<"), pair("<", ">"));
+        assert_eq!(
+            Pair::parse("<a href=\"x\">"),
+            pair("<a href=\"x\">", "</a>")
+        );
+        assert_eq!(Pair::parse("<em"), pair("<em>", "</em>"));
+        assert_eq!(Pair::parse(""), None);
+        assert_eq!(Pair::parse("ab"), None);
+        assert_eq!(Pair::parse("<a>b"), None);
+    }
+
+    #[test]
+    fn test_matching_finds_innermost_pair() {
+        assert_eq!(matched("f(a, [b|, c])").as_deref(), Some("[]"));
+        assert_eq!(matched("f(a, [b], |c)").as_deref(), Some("()"));
+        assert_eq!(matched("{ x = \"a|b\"; }").as_deref(), Some("\"\""));
+        assert_eq!(matched("{ x = \"ab\"; |}").as_deref(), Some("{}"));
+        assert_eq!(matched("no pair |here"), None);
+    }
+
+    #[test]
+    fn test_matching_on_a_bracket_uses_that_bracket() {
+        assert_eq!(matched("(a |(b))").as_deref(), Some("()"));
+        let buffer = Buffer::from_str_test("(a (b))");
+        let found = enclosing_by_matching(&buffer, 3..3).unwrap();
+        assert_eq!((found.open, found.close), (3..4, 5..6));
+        let found = enclosing_by_matching(&buffer, 5..5).unwrap();
+        assert_eq!((found.open, found.close), (3..4, 5..6));
+    }
+
+    #[test]
+    fn test_tree_ignores_brackets_in_strings() {
+        let source = "fn main() { f(\"(\", x); }";
+        let (buffer, tree) = parse(source);
+        let x = source.find('x').unwrap();
+
+        let found = enclosing_in_tree(&tree, &buffer, x..x + 1).unwrap();
+        assert_eq!(&source[found.open.clone()], "(");
+        assert_eq!(found.open.start, source.find("f(").unwrap() + 1);
+        assert_eq!(found.close.start, source.find(");").unwrap());
+
+        let in_string = source.find("\"(").unwrap() + 1;
+        let found = enclosing_in_tree(&tree, &buffer, in_string..in_string).unwrap();
+        assert_eq!(found.open, in_string - 1..in_string);
+        assert_eq!(&source[found.close.clone()], "\"");
+    }
+
+    #[test]
+    fn test_tree_skips_nodes_that_only_start_and_end_with_brackets() {
+        let source = "fn main() { let y = (a) + (b); }";
+        let (buffer, tree) = parse(source);
+        let plus = source.find('+').unwrap();
+
+        let found = enclosing_in_tree(&tree, &buffer, plus..plus).unwrap();
+        assert_eq!(&source[found.open.clone()], "{");
+    }
+
+    #[test]
+    fn test_tag_pairs() {
+        assert!(is_delimiter_pair(b"<div class=\"x\">", b"</div>"));
+        assert!(!is_delimiter_pair(b"<div>", b"</span>"));
+        assert!(!is_delimiter_pair(b"\"", b"'"));
+        assert!(is_delimiter_pair(b"'''", b"'''"));
+    }
+}
@@ -0,0 +1,456 @@
+//! Persistent full-document syntax tree
+//!
+//! Buffers below `MAX_TREE_BYTES` keep a tree-sitter `Tree` for the whole
+//! document, so that syntax-aware features see constructs that start far
+//! outside the viewport (long block comments, raw strings, enclosing blocks).
+//!
+//! # Design
+//! - **Edits are applied immediately**: each `Insert`/`Delete` event calls
+//!   `Tree::edit`, so node positions stay aligned with the buffer even before
+//!   the reparse finishes.
+//! - **Reparsing happens off the main thread**: `update()` hands a snapshot of
+//!   the buffer and the edited tree to the buffer's parse worker, a thread
+//!   that lives as long as the tree and reparses incrementally. At most one
+//!   parse is in flight per buffer; edits made in the meantime are replayed
+//!   onto its result and trigger the next parse.
+//! - **Readers don't wait**: `tree()` returns the latest tree (possibly edited
+//!   but not yet reparsed), which is what highlighting, indentation and
+//!   structural selection use. `current_tree()` only returns a tree that
+//!   reflects the buffer exactly, and `wait_for_parse()` blocks until there
+//!   is one; neither belongs on the UI thread.
+
+use crate::config::LARGE_FILE_THRESHOLD_BYTES;
+use crate::model::buffer::Buffer;
+use crate::primitives::highlighter::Language;
+use std::ops::Range;
+use std::sync::mpsc;
+use tree_sitter::{InputEdit, Parser, Point, Tree};
+
+/// Largest buffer that gets a persistent tree; larger buffers keep using
+/// viewport-window parsing
+pub const MAX_TREE_BYTES: usize = LARGE_FILE_THRESHOLD_BYTES as usize; // 1MB
+
+/// Work for the parse worker: a snapshot of the buffer and the edited tree
+/// to reuse
+type ParseRequest = (Vec<u8>, Option<Tree>);
+
+/// Thread that parses the snapshots of one buffer, for as long as its
+/// `SyntaxTree` lives
+struct ParseWorker {
+    requests: mpsc::Sender<ParseRequest>,
+    results: mpsc::Receiver<Option<Tree>>,
+}
+
+impl ParseWorker {
+    fn spawn(language: &Language) -> Option<Self> {
+        let mut parser = SyntaxTree::create_parser(language)?;
+        let (requests, request_rx) = mpsc::channel::<ParseRequest>();
+        let (result_tx, results) = mpsc::channel();
+        std::thread::spawn(move || {
+            // Ends once the syntax tree is dropped and the channel closes
+            for (source, old_tree) in request_rx {
+                if result_tx
+                    .send(parser.parse(&source, old_tree.as_ref()))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+        Some(Self { requests, results })
+    }
+}
+
+/// Full-document syntax tree for one buffer, reparsed incrementally in the background
+pub struct SyntaxTree {
+    /// Language the buffer is parsed as
+    language: Language,
+    /// Background parser (None after it died, until the next parse restarts it)
+    worker: Option<ParseWorker>,
+    /// Latest tree, with all edits applied (None until the first parse finishes)
+    tree: Option<Tree>,
+    /// Buffer length the tree's edits account for (detects out-of-band changes)
+    tracked_len: usize,
+    /// Whether the buffer changed since the last parse was started
+    dirty: bool,
+    /// Whether the worker is parsing a snapshot whose result isn't received yet
+    in_flight: bool,
+    /// Edits made since the in-flight parse took its snapshot
+    pending_edits: Vec<InputEdit>,
+}
+
+impl SyntaxTree {
+    /// Create a syntax tree for a buffer and start the initial parse
+    ///
+    /// Returns None if the buffer is too large or the grammar can't be loaded.
+    pub fn new(language: &Language, buffer: &Buffer) -> Option<Self> {
+        if !Self::supports(buffer) {
+            return None;
+        }
+
+        let worker = ParseWorker::spawn(language)?;
+        let mut syntax_tree = Self {
+            language: *language,
+            worker: Some(worker),
+            tree: None,
+            tracked_len: buffer.len(),
+            dirty: true,
+            in_flight: false,
+            pending_edits: Vec::new(),
+        };
+        syntax_tree.update(buffer);
+        Some(syntax_tree)
+    }
+
+    fn create_parser(language: &Language) -> Option<Parser> {
+        let mut parser = Parser::new();
+        if let Err(e) = parser.set_language(&language.tree_sitter_language()) {
+            tracing::warn!("Failed to set language for syntax tree: {}", e);
+            return None;
+        }
+        Some(parser)
+    }
+
+    /// Whether a buffer is small enough (and fully loaded) to keep a tree for
+    fn supports(buffer: &Buffer) -> bool {
+        !buffer.is_large_file() && buffer.len() <= MAX_TREE_BYTES
+    }
+
+    /// Language the buffer is parsed as
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Latest tree, with all edits applied but possibly not yet reparsed
+    pub fn tree(&self) -> Option<&Tree> {
+        self.tree.as_ref()
+    }
+
+    /// Tree that exactly reflects the buffer contents, if the reparse has caught up
+    pub fn current_tree(&self) -> Option<&Tree> {
+        if self.dirty || self.in_flight {
+            return None;
+        }
+        self.tree.as_ref()
+    }
+
+    /// Record text inserted at `position`
+    ///
+    /// `start` is the (line, byte column) of `position`, taken before or after
+    /// the insert (it is the same either way).
+    pub fn edit_insert(&mut self, position: usize, start: (usize, usize), text: &str) {
+        let start_position = Point::new(start.0, start.1);
+        let new_end_position = match text.rfind('\n') {
+            Some(last_newline) => Point::new(
+                start.0 + text.matches('\n').count(),
+                text.len() - last_newline - 1,
+            ),
+            None => Point::new(start.0, start.1 + text.len()),
+        };
+
+        self.tracked_len += text.len();
+        self.apply_edit(InputEdit {
+            start_byte: position,
+            old_end_byte: position,
+            new_end_byte: position + text.len(),
+            start_position,
+            old_end_position: start_position,
+            new_end_position,
+        });
+    }
+
+    /// Record deletion of `range`
+    ///
+    /// `start` and `end` are the (line, byte column) of the range bounds,
+    /// taken before the delete.
+    pub fn edit_delete(&mut self, range: Range<usize>, start: (usize, usize), end: (usize, usize)) {
+        let start_position = Point::new(start.0, start.1);
+
+        self.tracked_len = self.tracked_len.saturating_sub(range.len());
+        self.apply_edit(InputEdit {
+            start_byte: range.start,
+            old_end_byte: range.end,
+            new_end_byte: range.start,
+            start_position,
+            old_end_position: Point::new(end.0, end.1),
+            new_end_position: start_position,
+        });
+    }
+
+    fn apply_edit(&mut self, edit: InputEdit) {
+        if let Some(tree) = &mut self.tree {
+            tree.edit(&edit);
+        }
+        if self.in_flight {
+            self.pending_edits.push(edit);
+        }
+        self.dirty = true;
+    }
+
+    /// Pick up a finished background parse and start the next one if needed
+    ///
+    /// Returns true if a newly parsed tree was adopted.
+    pub fn update(&mut self, buffer: &Buffer) -> bool {
+        let adopted = self.receive_parse(false);
+        self.start_parse(buffer);
+        adopted
+    }
+
+    /// Block until the buffer is fully parsed
+    ///
+    /// Returns true if a tree is available afterwards.
+    pub fn wait_for_parse(&mut self, buffer: &Buffer) -> bool {
+        loop {
+            self.receive_parse(true);
+            if !self.dirty {
+                return self.tree.is_some();
+            }
+            self.start_parse(buffer);
+            if !self.in_flight {
+                // Nothing could be started (buffer grew past the limit)
+                return false;
+            }
+        }
+    }
+
+    /// Adopt the result of the in-flight parse, if it has finished
+    fn receive_parse(&mut self, block: bool) -> bool {
+        let Some(worker) = self.worker.as_ref().filter(|_| self.in_flight) else {
+            return false;
+        };
+        let result = if block {
+            worker
+                .results
+                .recv()
+                .map_err(|_| mpsc::TryRecvError::Disconnected)
+        } else {
+            worker.results.try_recv()
+        };
+
+        let tree = match result {
+            Ok(tree) => tree,
+            Err(mpsc::TryRecvError::Empty) => return false,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                // The worker panicked; the next parse starts a new one from scratch
+                tracing::warn!("Syntax tree parse thread exited without a result");
+                self.worker = None;
+                self.in_flight = false;
+                self.pending_edits.clear();
+                self.tree = None;
+                self.dirty = true;
+                return false;
+            }
+        };
+
+        self.in_flight = false;
+        let edits = std::mem::take(&mut self.pending_edits);
+        match tree {
+            Some(mut tree) => {
+                // Replay edits made while the parse was running; `dirty` is
+                // still set for them, so the next update reparses
+                for edit in &edits {
+                    tree.edit(edit);
+                }
+                self.tree = Some(tree);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start a background parse if the buffer changed and none is in flight
+    fn start_parse(&mut self, buffer: &Buffer) {
+        if !self.dirty || self.in_flight {
+            return;
+        }
+        if !Self::supports(buffer) {
+            self.tree = None;
+            return;
+        }
+        if self.worker.is_none() {
+            self.worker = ParseWorker::spawn(&self.language);
+        }
+        let Some(worker) = &self.worker else {
+            return;
+        };
+
+        // The buffer was changed without going through edit_insert/edit_delete:
+        // the old tree can't be reused
+        if buffer.len() != self.tracked_len {
+            self.tree = None;
+            self.tracked_len = buffer.len();
+        }
+
+        let source = buffer.slice_bytes(0..buffer.len());
+        if worker.requests.send((source, self.tree.clone())).is_err() {
+            // The worker is gone; the next update starts a new one
+            self.worker = None;
+            return;
+        }
+
+        self.in_flight = true;
+        self.dirty = false;
+    }
+}
+
+/// Range of the smallest named node that strictly contains `range`
+///
+/// Repeated calls walk outwards one syntax level at a time, e.g.
+/// identifier → call → statement → block → function. Returns None once
+/// `range` already covers the root node.
+pub fn enclosing_node_range(tree: &Tree, range: Range<usize>) -> Option<Range<usize>> {
+    let mut node = tree
+        .root_node()
+        .named_descendant_for_byte_range(range.start, range.end)?;
+    loop {
+        let node_range = node.byte_range();
+        if node_range.start <= range.start
+            && node_range.end >= range.end
+            && node_range.len() > range.len()
+        {
+            return Some(node_range);
+        }
+        node = node.parent()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root_sexp(syntax_tree: &SyntaxTree) -> String {
+        syntax_tree.tree().unwrap().root_node().to_sexp()
+    }
+
+    #[test]
+    fn test_initial_parse_covers_whole_document() {
+        let buffer = Buffer::from_str_test("/* a\nlong\ncomment */\nfn main() {}\n");
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+
+        assert!(syntax_tree.wait_for_parse(&buffer));
+        assert!(syntax_tree.current_tree().is_some());
+        let sexp = root_sexp(&syntax_tree);
+        assert!(sexp.contains("block_comment"), "{sexp}");
+        assert!(sexp.contains("function_item"), "{sexp}");
+    }
+
+    #[test]
+    fn test_incremental_insert_and_delete() {
+        let mut buffer = Buffer::from_str_test("fn main() {}\n");
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+        syntax_tree.wait_for_parse(&buffer);
+
+        // Insert a second function on a new line
+        let text = "fn other() {}\n";
+        let position = buffer.len();
+        let start = buffer.position_to_line_col(position);
+        buffer.insert(position, text);
+        syntax_tree.edit_insert(position, start, text);
+
+        // Edited but not reparsed yet
+        assert!(syntax_tree.current_tree().is_none());
+        assert!(syntax_tree.tree().is_some());
+
+        syntax_tree.wait_for_parse(&buffer);
+        assert_eq!(root_sexp(&syntax_tree).matches("function_item").count(), 2);
+        assert_eq!(
+            syntax_tree.tree().unwrap().root_node().end_byte(),
+            buffer.len()
+        );
+
+        // Delete the first function
+        let range = 0..13;
+        let start = buffer.position_to_line_col(range.start);
+        let end = buffer.position_to_line_col(range.end);
+        buffer.delete(range.clone());
+        syntax_tree.edit_delete(range, start, end);
+
+        syntax_tree.wait_for_parse(&buffer);
+        let sexp = root_sexp(&syntax_tree);
+        assert_eq!(sexp.matches("function_item").count(), 1);
+        let function = syntax_tree
+            .tree()
+            .unwrap()
+            .root_node()
+            .named_child(0)
+            .unwrap();
+        assert_eq!(function.start_byte(), 0);
+        assert_eq!(function.start_position(), Point::new(0, 0));
+    }
+
+    #[test]
+    fn test_edits_during_parse_are_replayed() {
+        let mut buffer = Buffer::from_str_test("fn a() {}\n");
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+
+        // Edit while the initial parse may still be running
+        for name in ["b", "c"] {
+            let text = format!("fn {name}() {{}}\n");
+            let position = buffer.len();
+            let start = buffer.position_to_line_col(position);
+            buffer.insert(position, &text);
+            syntax_tree.edit_insert(position, start, &text);
+            syntax_tree.update(&buffer);
+        }
+
+        assert!(syntax_tree.wait_for_parse(&buffer));
+        assert_eq!(root_sexp(&syntax_tree).matches("function_item").count(), 3);
+    }
+
+    #[test]
+    fn test_out_of_band_change_triggers_full_reparse() {
+        let mut buffer = Buffer::from_str_test("fn a() {}\n");
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+        syntax_tree.wait_for_parse(&buffer);
+
+        // Change the buffer without telling the tree, then force a reparse
+        buffer.insert(0, "struct S;\n");
+        syntax_tree.dirty = true;
+
+        syntax_tree.wait_for_parse(&buffer);
+        let sexp = root_sexp(&syntax_tree);
+        assert!(sexp.contains("struct_item"), "{sexp}");
+        assert_eq!(
+            syntax_tree.tree().unwrap().root_node().end_byte(),
+            buffer.len()
+        );
+    }
+
+    #[test]
+    fn test_enclosing_node_range_walks_outwards() {
+        let source = "fn main() {\n    let x = foo(bar, 1);\n}\n";
+        let buffer = Buffer::from_str_test(source);
+        let mut syntax_tree = SyntaxTree::new(&Language::Rust, &buffer).unwrap();
+        syntax_tree.wait_for_parse(&buffer);
+        let tree = syntax_tree.current_tree().unwrap();
+
+        // Cursor inside "bar" without a selection
+        let bar = source.find("bar").unwrap();
+        let mut range = bar + 1..bar + 1;
+        let mut steps = Vec::new();
+        while let Some(next) = enclosing_node_range(tree, range.clone()) {
+            steps.push(&source[next.clone()]);
+            range = next;
+        }
+
+        assert_eq!(
+            steps,
+            vec![
+                "bar",
+                "(bar, 1)",
+                "foo(bar, 1)",
+                "let x = foo(bar, 1);",
+                "{\n    let x = foo(bar, 1);\n}",
+                "fn main() {\n    let x = foo(bar, 1);\n}",
+                source,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_large_buffer_has_no_tree() {
+        let content = "a".repeat(MAX_TREE_BYTES + 1);
+        let buffer = Buffer::from_str_test(&content);
+        assert!(SyntaxTree::new(&Language::Rust, &buffer).is_none());
+    }
+}
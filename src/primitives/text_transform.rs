@@ -0,0 +1,816 @@
+//! Text transforms: case conversion, encodings, math and number increment
+//!
+//! A transform maps a piece of text to its replacement, or to an error when
+//! the text can't be converted (invalid base64, a malformed expression).
+//! Incrementing finds decimal, hexadecimal and date literals in a line and
+//! steps them.
+
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use chrono::{Days, Months, NaiveDate};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+use super::word_navigation::is_word_char;
+
+/// A replacement applied to selected text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextTransform {
+    UpperCase,
+    LowerCase,
+    TitleCase,
+    SnakeCase,
+    CamelCase,
+    PascalCase,
+    KebabCase,
+    ScreamingCase,
+    ToggleCase,
+    Base64Encode,
+    Base64Decode,
+    UrlEncode,
+    UrlDecode,
+    JsonEscape,
+    JsonUnescape,
+    EvaluateMath,
+}
+
+impl TextTransform {
+    /// Parse a transform name such as `"snake_case"` or `"base64_encode"`
+    pub fn from_name(name: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+
+    /// Name shown in the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::UpperCase => "Upper case",
+            Self::LowerCase => "Lower case",
+            Self::TitleCase => "Title case",
+            Self::SnakeCase => "snake_case",
+            Self::CamelCase => "camelCase",
+            Self::PascalCase => "PascalCase",
+            Self::KebabCase => "kebab-case",
+            Self::ScreamingCase => "SCREAMING_CASE",
+            Self::ToggleCase => "Toggle case",
+            Self::Base64Encode => "Base64 encode",
+            Self::Base64Decode => "Base64 decode",
+            Self::UrlEncode => "URL encode",
+            Self::UrlDecode => "URL decode",
+            Self::JsonEscape => "JSON escape",
+            Self::JsonUnescape => "JSON unescape",
+            Self::EvaluateMath => "Evaluate math",
+        }
+    }
+
+    /// Transform `text`
+    ///
+    /// Identifier cases (snake_case and friends) convert each line on its
+    /// own and keep the line's surrounding whitespace.
+    pub fn apply(self, text: &str) -> Result<String, String> {
+        let converted = match self {
+            Self::UpperCase => text.to_uppercase(),
+            Self::LowerCase => text.to_lowercase(),
+            Self::TitleCase => title_case(text),
+            Self::SnakeCase => per_line(text, |words| join_words(words, "_", str::to_lowercase)),
+            Self::KebabCase => per_line(text, |words| join_words(words, "-", str::to_lowercase)),
+            Self::ScreamingCase => {
+                per_line(text, |words| join_words(words, "_", str::to_uppercase))
+            }
+            Self::PascalCase => per_line(text, |words| join_words(words, "", capitalize)),
+            Self::CamelCase => per_line(text, |words| {
+                let mut joined = words[0].to_lowercase();
+                joined.push_str(&join_words(&words[1..], "", capitalize));
+                joined
+            }),
+            Self::ToggleCase => text.chars().map(toggle_char_case).collect(),
+            Self::Base64Encode => base64_encode(text.as_bytes()),
+            Self::Base64Decode => String::from_utf8(base64_decode(text)?)
+                .map_err(|_| "Decoded base64 is not UTF-8 text".to_string())?,
+            Self::UrlEncode => url_encode(text),
+            Self::UrlDecode => url_decode(text)?,
+            Self::JsonEscape => {
+                let quoted = serde_json::Value::String(text.to_string()).to_string();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+            Self::JsonUnescape => json_unescape(text)?,
+            Self::EvaluateMath => {
+                let expression = text.trim();
+                let value = evaluate(expression)?;
+                let start = text.len() - text.trim_start().len();
+                format!(
+                    "{}{}{}",
+                    &text[..start],
+                    format_number(value),
+                    &text[start + expression.len()..]
+                )
+            }
+        };
+        Ok(converted)
+    }
+}
+
+/// Split an identifier or phrase into words at separators and case changes
+///
+/// "parseHTTPResponse2" gives "parse", "HTTP", "Response2".
+fn words(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Convert the words of each line with `join`, keeping line endings and the
+/// whitespace around each line; lines without words are left alone
+fn per_line(text: &str, join: impl Fn(&[String]) -> String) -> String {
+    text.split('\n')
+        .map(|line| {
+            let trimmed = line.trim();
+            let words = words(trimmed);
+            if words.is_empty() {
+                return line.to_string();
+            }
+            let start = line.len() - line.trim_start().len();
+            let end = start + trimmed.len();
+            format!("{}{}{}", &line[..start], join(&words), &line[end..])
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn join_words(words: &[String], separator: &str, case: impl Fn(&str) -> String) -> String {
+    words
+        .iter()
+        .map(|word| case(word))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Capitalize the first letter of every word and lowercase the rest
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        let starts_word = match previous {
+            Some(p) => !p.is_alphanumeric() && p != '\'',
+            None => true,
+        };
+        if starts_word {
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    result
+}
+
+fn toggle_char_case(c: char) -> String {
+    if c.is_uppercase() {
+        c.to_lowercase().collect()
+    } else {
+        c.to_uppercase().collect()
+    }
+}
+
+/// Standard base64 that decodes with or without padding
+const BASE64_LENIENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Everything but the URL unreserved characters gets percent-encoded
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+fn base64_encode(bytes: &[u8]) -> String {
+    STANDARD.encode(bytes)
+}
+
+/// Decode standard or URL-safe base64, ignoring whitespace; padding is
+/// optional
+fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+    BASE64_LENIENT
+        .decode(digits)
+        .map_err(|e| format!("Invalid base64: {}", e))
+}
+
+fn url_encode(text: &str) -> String {
+    utf8_percent_encode(text, URL_ENCODE_SET).to_string()
+}
+
+fn url_decode(text: &str) -> Result<String, String> {
+    // percent_decode_str passes malformed escapes through untouched, so
+    // reject them first rather than return half-decoded text
+    let bytes = text.as_bytes();
+    let malformed = bytes.iter().enumerate().position(|(i, &byte)| {
+        byte == b'%'
+            && !bytes
+                .get(i + 1..i + 3)
+                .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+    });
+    if let Some(i) = malformed {
+        return Err(format!("Invalid percent escape at byte {}", i));
+    }
+    percent_decode_str(text)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|_| "Decoded URL is not UTF-8 text".to_string())
+}
+
+/// Unescape the contents of a JSON string, with or without its quotes
+fn json_unescape(text: &str) -> Result<String, String> {
+    let quoted = if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        text.to_string()
+    } else {
+        format!("\"{}\"", text)
+    };
+    serde_json::from_str(&quoted).map_err(|e| format!("Invalid JSON string: {}", e))
+}
+
+/// Evaluate an arithmetic expression
+///
+/// Supports `+ - * / % ^`, parentheses, the constants `pi` and `e`, and the
+/// functions `sqrt abs exp ln log sin cos tan floor ceil round`.
+pub fn evaluate(expression: &str) -> Result<f64, String> {
+    let mut parser = MathParser {
+        text: expression,
+        pos: 0,
+    };
+    let value = parser.sum()?;
+    if let Some(c) = parser.peek() {
+        return Err(format!("Unexpected '{}' in expression", c));
+    }
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err("Result is not a finite number".to_string())
+    }
+}
+
+/// Format a result without float noise: whole numbers without a fraction,
+/// others rounded to ten decimal places
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+    if value.abs() < 1e-6 {
+        return format!("{:e}", value);
+    }
+    let rounded = format!("{:.10}", value);
+    rounded
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Recursive-descent parser that evaluates as it goes
+struct MathParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl MathParser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn sum(&mut self) -> Result<f64, String> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') {
+                value -= self.product()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') {
+                value *= self.unary()?;
+            } else if self.eat('/') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                value /= divisor;
+            } else if self.eat('%') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                value %= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Signs bind looser than `^`, so `-2^2` is -4
+    fn unary(&mut self) -> Result<f64, String> {
+        if self.eat('-') {
+            Ok(-self.unary()?)
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            Ok(base.powf(self.unary()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<f64, String> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let value = self.sum()?;
+                if !self.eat(')') {
+                    return Err("Missing ')' in expression".to_string());
+                }
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => self.name(),
+            Some(c) => Err(format!("Unexpected '{}' in expression", c)),
+            None => Err("Incomplete expression".to_string()),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let bytes = self.text.as_bytes();
+        let start = self.pos;
+        let mut end = start;
+        while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+            end += 1;
+        }
+        // Exponent, as in 1.5e3 or 2E-4
+        if end < bytes.len() && matches!(bytes[end], b'e' | b'E') {
+            let mut exponent = end + 1;
+            if exponent < bytes.len() && matches!(bytes[exponent], b'+' | b'-') {
+                exponent += 1;
+            }
+            if exponent < bytes.len() && bytes[exponent].is_ascii_digit() {
+                end = exponent;
+                while end < bytes.len() && bytes[end].is_ascii_digit() {
+                    end += 1;
+                }
+            }
+        }
+        self.pos = end;
+        let literal = &self.text[start..end];
+        literal
+            .parse()
+            .map_err(|_| format!("Invalid number '{}' in expression", literal))
+    }
+
+    fn name(&mut self) -> Result<f64, String> {
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let name = &rest[..len];
+        self.pos += len;
+        let function: fn(f64) -> f64 = match name {
+            "pi" => return Ok(std::f64::consts::PI),
+            "e" => return Ok(std::f64::consts::E),
+            "sqrt" => f64::sqrt,
+            "abs" => f64::abs,
+            "exp" => f64::exp,
+            "ln" => f64::ln,
+            "log" => f64::log10,
+            "sin" => f64::sin,
+            "cos" => f64::cos,
+            "tan" => f64::tan,
+            "floor" => f64::floor,
+            "ceil" => f64::ceil,
+            "round" => f64::round,
+            _ => return Err(format!("Unknown name '{}' in expression", name)),
+        };
+        if !self.eat('(') {
+            return Err(format!("Expected '(' after {}", name));
+        }
+        let argument = self.sum()?;
+        if !self.eat(')') {
+            return Err("Missing ')' in expression".to_string());
+        }
+        Ok(function(argument))
+    }
+}
+
+/// Kind of literal that can be incremented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Literal {
+    /// Integer with an optional minus sign
+    Decimal,
+    /// `0x` followed by hex digits
+    Hex,
+    /// `YYYY-MM-DD`
+    Date,
+}
+
+/// Incrementable literals in `text`, in order
+fn literals(text: &str) -> Vec<(Range<usize>, Literal)> {
+    let bytes = text.as_bytes();
+    let digits_from = |start: usize, hex: bool| {
+        start
+            + bytes[start..]
+                .iter()
+                .take_while(|b| {
+                    if hex {
+                        b.is_ascii_hexdigit()
+                    } else {
+                        b.is_ascii_digit()
+                    }
+                })
+                .count()
+    };
+
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let is_date = text
+            .get(i..i + 10)
+            .is_some_and(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+            && digits_from(i, false) == i + 4
+            && bytes.get(i + 10).is_none_or(|b| !b.is_ascii_digit());
+        if is_date {
+            found.push((i..i + 10, Literal::Date));
+            i += 10;
+            continue;
+        }
+        if bytes[i] == b'0'
+            && matches!(bytes.get(i + 1), Some(b'x' | b'X'))
+            && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit)
+        {
+            let end = digits_from(i + 2, true);
+            found.push((i..end, Literal::Hex));
+            i = end;
+            continue;
+        }
+        let negative = i > 0 && bytes[i - 1] == b'-' && (i < 2 || !is_word_char(bytes[i - 2]));
+        let start = if negative { i - 1 } else { i };
+        let end = digits_from(i, false);
+        found.push((start..end, Literal::Decimal));
+        i = end;
+    }
+    found
+}
+
+/// Step the first literal in `line` that ends after `column` by `delta`
+///
+/// Returns the literal's byte range in the line and its replacement. For a
+/// date, the field under `column` is stepped (year, month or day); days are
+/// stepped when `column` is before the date.
+pub fn increment_at(line: &str, column: usize, delta: i64) -> Option<(Range<usize>, String)> {
+    let (range, literal) = literals(line)
+        .into_iter()
+        .find(|(range, _)| range.end > column)?;
+    let offset = if column < range.start {
+        range.len()
+    } else {
+        column - range.start
+    };
+    let stepped = step(&line[range.clone()], literal, delta, offset)?;
+    Some((range, stepped))
+}
+
+/// Step every literal in `text` by `delta`
+pub fn increment_all(text: &str, delta: i64) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (range, literal) in literals(text) {
+        let Some(stepped) = step(&text[range.clone()], literal, delta, range.len()) else {
+            continue;
+        };
+        result.push_str(&text[copied..range.start]);
+        result.push_str(&stepped);
+        copied = range.end;
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+/// Step one literal; `offset` picks the field of a date
+fn step(text: &str, literal: Literal, delta: i64, offset: usize) -> Option<String> {
+    match literal {
+        Literal::Decimal => {
+            let value: i128 = text.parse().ok()?;
+            let next = value.checked_add(i128::from(delta))?;
+            // Keep the width of zero-padded numbers, as in "007"
+            let digits = text.trim_start_matches('-');
+            let width = if digits.len() > 1 && digits.starts_with('0') {
+                digits.len()
+            } else {
+                0
+            };
+            let sign = if next < 0 { "-" } else { "" };
+            Some(format!(
+                "{}{:0width$}",
+                sign,
+                next.unsigned_abs(),
+                width = width
+            ))
+        }
+        Literal::Hex => {
+            let (prefix, digits) = text.split_at(2);
+            let value = u64::from_str_radix(digits, 16).ok()?;
+            let next = value.wrapping_add_signed(delta);
+            let width = digits.len();
+            if digits.bytes().any(|b| b.is_ascii_uppercase()) {
+                Some(format!("{}{:0width$X}", prefix, next, width = width))
+            } else {
+                Some(format!("{}{:0width$x}", prefix, next, width = width))
+            }
+        }
+        Literal::Date => {
+            let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+            // Offsets 0-3 are the year, 5-6 the month, anything else the day
+            let months = match offset {
+                0..=3 => Some(delta.saturating_mul(12)),
+                5 | 6 => Some(delta),
+                _ => None,
+            };
+            let next = match months {
+                Some(months) => {
+                    let count = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+                    if months >= 0 {
+                        date.checked_add_months(count)
+                    } else {
+                        date.checked_sub_months(count)
+                    }
+                }
+                None if delta >= 0 => date.checked_add_days(Days::new(delta.unsigned_abs())),
+                None => date.checked_sub_days(Days::new(delta.unsigned_abs())),
+            }?;
+            Some(next.format("%Y-%m-%d").to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(transform: TextTransform, text: &str) -> String {
+        transform.apply(text).unwrap()
+    }
+
+    #[test]
+    fn test_identifier_cases() {
+        let text = "parseHTTPResponse2 value";
+        assert_eq!(
+            apply(TextTransform::SnakeCase, text),
+            "parse_http_response2_value"
+        );
+        assert_eq!(
+            apply(TextTransform::CamelCase, text),
+            "parseHttpResponse2Value"
+        );
+        assert_eq!(
+            apply(TextTransform::PascalCase, text),
+            "ParseHttpResponse2Value"
+        );
+        assert_eq!(
+            apply(TextTransform::KebabCase, text),
+            "parse-http-response2-value"
+        );
+        assert_eq!(
+            apply(TextTransform::ScreamingCase, "max-retry count"),
+            "MAX_RETRY_COUNT"
+        );
+        // Each line converts on its own, keeping its indentation
+        assert_eq!(
+            apply(TextTransform::SnakeCase, "  fooBar\n\n  BazQux "),
+            "  foo_bar\n\n  baz_qux "
+        );
+    }
+
+    #[test]
+    fn test_letter_cases() {
+        assert_eq!(apply(TextTransform::UpperCase, "héllo"), "HÉLLO");
+        assert_eq!(apply(TextTransform::LowerCase, "HÉLLO"), "héllo");
+        assert_eq!(
+            apply(TextTransform::TitleCase, "don't STOP me-now"),
+            "Don't Stop Me-Now"
+        );
+        assert_eq!(
+            apply(TextTransform::ToggleCase, "Hello World"),
+            "hELLO wORLD"
+        );
+    }
+
+    #[test]
+    fn test_encodings_round_trip() {
+        for text in ["", "f", "fo", "foo", "foob", "héllo wörld"] {
+            let encoded = apply(TextTransform::Base64Encode, text);
+            assert_eq!(apply(TextTransform::Base64Decode, &encoded), text);
+        }
+        assert_eq!(apply(TextTransform::Base64Encode, "foob"), "Zm9vYg==");
+        assert_eq!(apply(TextTransform::Base64Decode, "Zm9vYg"), "foob");
+        assert!(TextTransform::Base64Decode.apply("Zm9v!").is_err());
+
+        assert_eq!(
+            apply(TextTransform::UrlEncode, "a b&c=é"),
+            "a%20b%26c%3D%C3%A9"
+        );
+        assert_eq!(
+            apply(TextTransform::UrlDecode, "a%20b%26c%3D%C3%A9"),
+            "a b&c=é"
+        );
+        assert!(TextTransform::UrlDecode.apply("100%").is_err());
+
+        assert_eq!(
+            apply(TextTransform::JsonEscape, "say \"hi\"\n\ttab"),
+            r#"say \"hi\"\n\ttab"#
+        );
+        assert_eq!(
+            apply(TextTransform::JsonUnescape, r#"say \"hi\"\né"#),
+            "say \"hi\"\né"
+        );
+        assert_eq!(apply(TextTransform::JsonUnescape, r#""quoted""#), "quoted");
+    }
+
+    #[test]
+    fn test_base64_padding_and_invalid_input() {
+        assert_eq!(apply(TextTransform::Base64Encode, "f"), "Zg==");
+        assert_eq!(apply(TextTransform::Base64Encode, "fo"), "Zm8=");
+        assert_eq!(apply(TextTransform::Base64Encode, "foo"), "Zm9v");
+        for (encoded, text) in [("Zg", "f"), ("Zg==", "f"), ("Zm8", "fo"), ("Zm8=", "fo")] {
+            assert_eq!(apply(TextTransform::Base64Decode, encoded), text);
+        }
+        assert_eq!(apply(TextTransform::Base64Decode, "Zm9v\nYg==\n"), "foob");
+        assert_eq!(apply(TextTransform::Base64Encode, "é✓"), "w6ninJM=");
+        assert_eq!(apply(TextTransform::Base64Decode, "w6ninJM"), "é✓");
+
+        // URL-safe digits decode like their standard counterparts
+        assert_eq!(
+            base64_decode("-_8").unwrap(),
+            base64_decode("+/8=").unwrap()
+        );
+
+        for invalid in ["Zm9v!", "Z", "Zm9vY", "Zm=9", "/w=="] {
+            assert!(
+                TextTransform::Base64Decode.apply(invalid).is_err(),
+                "{:?} should not decode",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_url_decode_invalid_input() {
+        assert_eq!(apply(TextTransform::UrlEncode, "-_.~"), "-_.~");
+        assert_eq!(apply(TextTransform::UrlEncode, "✓ ok"), "%E2%9C%93%20ok");
+        assert_eq!(apply(TextTransform::UrlDecode, "é%20%e2%9c%93"), "é ✓");
+
+        for invalid in ["100%", "%4", "%4g", "%+1", "%zz", "%C3", "%FF"] {
+            assert!(
+                TextTransform::UrlDecode.apply(invalid).is_err(),
+                "{:?} should not decode",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_math() {
+        assert_eq!(apply(TextTransform::EvaluateMath, " 2 + 3 * 4 "), " 14 ");
+        assert_eq!(
+            apply(TextTransform::EvaluateMath, "(1 + 2) ^ 2 / 4"),
+            "2.25"
+        );
+        assert_eq!(apply(TextTransform::EvaluateMath, "-2^2 + 10 % 4"), "-2");
+        assert_eq!(apply(TextTransform::EvaluateMath, "0.1 + 0.2"), "0.3");
+        assert_eq!(apply(TextTransform::EvaluateMath, "sqrt(16) + 1.5e1"), "19");
+        assert_eq!(apply(TextTransform::EvaluateMath, "round(pi * 100)"), "314");
+        assert!(TextTransform::EvaluateMath.apply("1 / 0").is_err());
+        assert!(TextTransform::EvaluateMath.apply("2 +").is_err());
+        assert!(TextTransform::EvaluateMath.apply("(1").is_err());
+        assert!(TextTransform::EvaluateMath.apply("foo(1)").is_err());
+    }
+
+    #[test]
+    fn test_increment_decimal_and_hex() {
+        assert_eq!(
+            increment_at("x = 41;", 0, 1),
+            Some((4..6, "42".to_string()))
+        );
+        assert_eq!(increment_at("a 1 b 2", 3, 1), Some((6..7, "3".to_string())));
+        assert_eq!(increment_at("n = -1", 0, 2), Some((4..6, "1".to_string())));
+        assert_eq!(increment_at("n = 0", 0, -1), Some((4..5, "-1".to_string())));
+        assert_eq!(increment_at("v1-2", 0, 1), Some((1..2, "2".to_string())));
+        assert_eq!(
+            increment_at("id 007", 0, 3),
+            Some((3..6, "010".to_string()))
+        );
+        assert_eq!(
+            increment_at("0xff", 0, 1),
+            Some((0..4, "0x100".to_string()))
+        );
+        assert_eq!(increment_at("0x0F", 0, 1), Some((0..4, "0x10".to_string())));
+        assert_eq!(increment_at("none", 0, 1), None);
+        assert_eq!(increment_at("1 ", 1, 1), None);
+    }
+
+    #[test]
+    fn test_increment_dates() {
+        let line = "due 2024-01-31";
+        assert_eq!(increment_at(line, 0, 1).unwrap().1, "2024-02-01");
+        assert_eq!(increment_at(line, 5, 1).unwrap().1, "2025-01-31");
+        assert_eq!(increment_at(line, 9, 1).unwrap().1, "2024-02-29");
+        assert_eq!(increment_at(line, 12, -31).unwrap().1, "2023-12-31");
+        // Not a valid date, so its parts are plain numbers
+        assert_eq!(
+            increment_at("2024-13-01", 0, 1).unwrap(),
+            (0..4, "2025".to_string())
+        );
+    }
+
+    #[test]
+    fn test_increment_all() {
+        assert_eq!(
+            increment_all("a1 b-2 0x0a 2020-02-28", 1),
+            "a2 b-3 0x0b 2020-02-29"
+        );
+        assert_eq!(
+            TextTransform::from_name("snake_case"),
+            Some(TextTransform::SnakeCase)
+        );
+        assert_eq!(TextTransform::from_name("shout"), None);
+    }
+}
@@ -0,0 +1,356 @@
+//! Tree-sitter text objects
+//!
+//! Text objects are syntax-aware regions such as a function, class, parameter,
+//! comment or block. They are found with the per-language `textobjects.scm`
+//! queries, whose captures are named `@<object>.outer` and `@<object>.inner`.
+//!
+//! Objects are referred to by capture name (`"function.inner"`); a bare name
+//! (`"function"`) means the outer variant. Variants that a query can't express
+//! without custom predicates are derived from the captured nodes:
+//! - `parameter.outer` is `parameter.inner` plus its separating comma, so
+//!   deleting it leaves a well-formed list
+//! - a missing `<object>.inner` is the `<object>.outer` node without its
+//!   opening and closing delimiters (e.g. the inside of a block's braces)
+
+use crate::model::buffer::Buffer;
+use crate::primitives::highlighter::Language;
+use std::ops::Range;
+use tree_sitter::{Node, Query, QueryCursor, StreamingIterator, Tree};
+
+/// Compiled text object query for one language
+pub struct TextObjects {
+    query: Query,
+}
+
+impl TextObjects {
+    /// Load the bundled query for a language (None if it doesn't ship one)
+    pub fn new(language: &Language) -> Option<Self> {
+        let query_str = match language {
+            Language::Rust => include_str!("../../queries/rust/textobjects.scm"),
+            Language::Python => include_str!("../../queries/python/textobjects.scm"),
+            Language::JavaScript => include_str!("../../queries/javascript/textobjects.scm"),
+            Language::TypeScript => include_str!("../../queries/typescript/textobjects.scm"),
+            Language::C => include_str!("../../queries/c/textobjects.scm"),
+            Language::Cpp => include_str!("../../queries/cpp/textobjects.scm"),
+            Language::Go => include_str!("../../queries/go/textobjects.scm"),
+            Language::Java => include_str!("../../queries/java/textobjects.scm"),
+            _ => return None,
+        };
+
+        match Query::new(&language.tree_sitter_language(), query_str) {
+            Ok(query) => Some(Self { query }),
+            Err(e) => {
+                tracing::error!("Failed to create text object query: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// All ranges of a text object in the document, sorted by start
+    pub fn ranges(&self, tree: &Tree, buffer: &Buffer, object: &str) -> Vec<Range<usize>> {
+        let (name, variant) = object.split_once('.').unwrap_or((object, "outer"));
+        let capture = format!("{name}.{variant}");
+
+        let mut ranges: Vec<Range<usize>> = if self.query.capture_index_for_name(&capture).is_some()
+        {
+            self.nodes(tree, buffer, &capture)
+                .iter()
+                .map(|node| node.byte_range())
+                .collect()
+        } else if capture == "parameter.outer" {
+            self.nodes(tree, buffer, "parameter.inner")
+                .into_iter()
+                .map(with_separator)
+                .collect()
+        } else if variant == "inner" {
+            self.nodes(tree, buffer, &format!("{name}.outer"))
+                .into_iter()
+                .map(inside_delimiters)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        ranges.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        ranges.dedup();
+        ranges
+    }
+
+    /// Smallest text object that contains `selection` and is larger than it
+    pub fn enclosing(
+        &self,
+        tree: &Tree,
+        buffer: &Buffer,
+        object: &str,
+        selection: Range<usize>,
+    ) -> Option<Range<usize>> {
+        self.ranges(tree, buffer, object)
+            .into_iter()
+            .filter(|range| {
+                range.start <= selection.start
+                    && range.end >= selection.end
+                    && range.len() > selection.len()
+            })
+            .min_by_key(|range| range.len())
+    }
+
+    /// First text object starting after `position`
+    pub fn next(
+        &self,
+        tree: &Tree,
+        buffer: &Buffer,
+        object: &str,
+        position: usize,
+    ) -> Option<Range<usize>> {
+        self.ranges(tree, buffer, object)
+            .into_iter()
+            .find(|range| range.start > position)
+    }
+
+    /// Last text object starting before `position`
+    pub fn previous(
+        &self,
+        tree: &Tree,
+        buffer: &Buffer,
+        object: &str,
+        position: usize,
+    ) -> Option<Range<usize>> {
+        self.ranges(tree, buffer, object)
+            .into_iter()
+            .rev()
+            .find(|range| range.start < position)
+    }
+
+    /// The parameter at `position` including its separator (see
+    /// `parameter.outer`), for deleting it from its list
+    pub fn parameter_at(
+        &self,
+        tree: &Tree,
+        buffer: &Buffer,
+        position: usize,
+    ) -> Option<Range<usize>> {
+        let parameters = self.nodes(tree, buffer, "parameter.inner");
+        innermost_at(&parameters, position).map(with_separator)
+    }
+
+    /// The parameter at `position` and the one following it in the same list
+    pub fn parameter_and_next(
+        &self,
+        tree: &Tree,
+        buffer: &Buffer,
+        position: usize,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let parameters = self.nodes(tree, buffer, "parameter.inner");
+        let current = innermost_at(&parameters, position)?;
+        let parent = current.parent()?.id();
+        let next = parameters.iter().find(|node| {
+            node.start_byte() >= current.end_byte()
+                && node.parent().map(|parent| parent.id()) == Some(parent)
+        })?;
+        Some((current.byte_range(), next.byte_range()))
+    }
+
+    /// Nodes captured as `capture` anywhere in the document
+    fn nodes<'tree>(&self, tree: &'tree Tree, buffer: &Buffer, capture: &str) -> Vec<Node<'tree>> {
+        let Some(index) = self.query.capture_index_for_name(capture) else {
+            return Vec::new();
+        };
+
+        let text_provider =
+            |node: Node| std::iter::once(buffer.slice_bytes(node.start_byte()..node.end_byte()));
+        let mut query_cursor = QueryCursor::new();
+        let mut matches = query_cursor.matches(&self.query, tree.root_node(), text_provider);
+
+        let mut nodes = Vec::new();
+        while let Some(query_match) = matches.next() {
+            for captured in query_match.captures {
+                if captured.index == index {
+                    nodes.push(captured.node);
+                }
+            }
+        }
+        nodes.sort_by_key(|node| node.start_byte());
+        nodes
+    }
+}
+
+/// Smallest node touching `position`
+fn innermost_at<'tree>(nodes: &[Node<'tree>], position: usize) -> Option<Node<'tree>> {
+    nodes
+        .iter()
+        .filter(|node| node.start_byte() <= position && position <= node.end_byte())
+        .min_by_key(|node| node.end_byte() - node.start_byte())
+        .copied()
+}
+
+/// A parameter together with the comma separating it from its neighbour
+///
+/// Takes the following comma (up to the next parameter) when there is one,
+/// otherwise the preceding comma (from the previous parameter).
+fn with_separator(node: Node) -> Range<usize> {
+    let range = node.byte_range();
+    if let Some(comma) = node.next_sibling().filter(|sibling| sibling.kind() == ",") {
+        let end = match comma.next_sibling() {
+            Some(next) if next.is_named() => next.start_byte(),
+            _ => comma.end_byte(),
+        };
+        return range.start..end;
+    }
+    if let Some(comma) = node.prev_sibling().filter(|sibling| sibling.kind() == ",") {
+        let start = match comma.prev_sibling() {
+            Some(previous) if previous.is_named() => previous.end_byte(),
+            _ => comma.start_byte(),
+        };
+        return start..range.end;
+    }
+    range
+}
+
+/// A node's range without its opening and closing delimiters, if it has them
+fn inside_delimiters(node: Node) -> Range<usize> {
+    let mut tree_cursor = node.walk();
+    let mut children = node.children(&mut tree_cursor);
+    if let (Some(first), Some(last)) = (children.next(), children.last()) {
+        if !first.is_named() && !last.is_named() {
+            return first.end_byte()..last.start_byte();
+        }
+    }
+    node.byte_range()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::Parser;
+
+    fn parse(language: &Language, source: &str) -> (Buffer, Tree) {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&language.tree_sitter_language())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        (Buffer::from_str_test(source), tree)
+    }
+
+    fn texts<'a>(source: &'a str, ranges: &[Range<usize>]) -> Vec<&'a str> {
+        ranges.iter().map(|range| &source[range.clone()]).collect()
+    }
+
+    const RUST_SOURCE: &str = "\
+// helper
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+fn main() {
+    let x = add(1, 2);
+}
+";
+
+    #[test]
+    fn test_bundled_queries_compile() {
+        for language in [
+            Language::Rust,
+            Language::Python,
+            Language::JavaScript,
+            Language::TypeScript,
+            Language::C,
+            Language::Cpp,
+            Language::Go,
+            Language::Java,
+        ] {
+            assert!(
+                TextObjects::new(&language).is_some(),
+                "textobjects.scm failed to compile for {:?}",
+                language
+            );
+        }
+    }
+
+    #[test]
+    fn test_function_and_comment_objects() {
+        let (buffer, tree) = parse(&Language::Rust, RUST_SOURCE);
+        let objects = TextObjects::new(&Language::Rust).unwrap();
+
+        let functions = objects.ranges(&tree, &buffer, "function");
+        assert_eq!(functions.len(), 2);
+        assert!(RUST_SOURCE[functions[0].clone()].starts_with("fn add"));
+
+        let bodies = objects.ranges(&tree, &buffer, "function.inner");
+        assert_eq!(texts(RUST_SOURCE, &bodies)[0], "{\n    a + b\n}");
+
+        // Comments have no delimiters, so inner is the same as outer
+        let comments = objects.ranges(&tree, &buffer, "comment.inner");
+        assert_eq!(comments.len(), 1);
+        assert_eq!(RUST_SOURCE[comments[0].clone()].trim_end(), "// helper");
+    }
+
+    #[test]
+    fn test_block_inner_excludes_braces() {
+        let (buffer, tree) = parse(&Language::Rust, RUST_SOURCE);
+        let objects = TextObjects::new(&Language::Rust).unwrap();
+
+        let inside = objects.ranges(&tree, &buffer, "block.inner");
+        assert_eq!(texts(RUST_SOURCE, &inside)[0], "\n    a + b\n");
+    }
+
+    #[test]
+    fn test_parameter_outer_includes_separator() {
+        let (buffer, tree) = parse(&Language::Rust, RUST_SOURCE);
+        let objects = TextObjects::new(&Language::Rust).unwrap();
+
+        let outer = objects.ranges(&tree, &buffer, "parameter.outer");
+        // The last parameter takes the comma before it
+        assert_eq!(
+            texts(RUST_SOURCE, &outer),
+            vec!["a: i32, ", ", b: i32", "1, ", ", 2"]
+        );
+    }
+
+    #[test]
+    fn test_enclosing_next_and_previous() {
+        let (buffer, tree) = parse(&Language::Rust, RUST_SOURCE);
+        let objects = TextObjects::new(&Language::Rust).unwrap();
+        let inside_add = RUST_SOURCE.find("a + b").unwrap();
+        let main = RUST_SOURCE.find("fn main").unwrap();
+
+        let function = objects
+            .enclosing(&tree, &buffer, "function", inside_add..inside_add)
+            .unwrap();
+        assert!(RUST_SOURCE[function.clone()].starts_with("fn add"));
+
+        // Already selecting the whole function: nothing larger encloses it
+        assert_eq!(
+            objects.enclosing(&tree, &buffer, "function", function),
+            None
+        );
+
+        let next = objects
+            .next(&tree, &buffer, "function", inside_add)
+            .unwrap();
+        assert_eq!(next.start, main);
+        let previous = objects.previous(&tree, &buffer, "function", main).unwrap();
+        assert!(RUST_SOURCE[previous].starts_with("fn add"));
+        assert_eq!(objects.next(&tree, &buffer, "function", main), None);
+    }
+
+    #[test]
+    fn test_parameter_and_next_stays_in_list() {
+        let (buffer, tree) = parse(&Language::Rust, RUST_SOURCE);
+        let objects = TextObjects::new(&Language::Rust).unwrap();
+
+        let a = RUST_SOURCE.find("a: i32").unwrap();
+        let (current, next) = objects.parameter_and_next(&tree, &buffer, a).unwrap();
+        assert_eq!(&RUST_SOURCE[current], "a: i32");
+        assert_eq!(&RUST_SOURCE[next], "b: i32");
+
+        assert_eq!(
+            &RUST_SOURCE[objects.parameter_at(&tree, &buffer, a).unwrap()],
+            "a: i32, "
+        );
+
+        // The last parameter has no next one, even though arguments follow later
+        let b = RUST_SOURCE.find("b: i32").unwrap();
+        assert_eq!(objects.parameter_and_next(&tree, &buffer, b), None);
+    }
+}
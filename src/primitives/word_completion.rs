@@ -0,0 +1,202 @@
+//! Words of buffers for completion without a language server
+//!
+//! A `WordIndex` counts the words of one buffer. It is kept up to date edit
+//! by edit: the words around an edit are taken out of the index before it
+//! and counted again after it, so the buffer is only read in full once.
+//! `rank` picks the words completing a prefix from the indexes of the open
+//! buffers, those nearest the cursor first, then the most frequent.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::primitives::word_navigation::is_word_char;
+
+/// Most words offered for one completion
+pub const MAX_WORD_COMPLETIONS: usize = 50;
+
+/// Longest word indexed, in bytes; longer runs of word characters (encoded
+/// data, minified code) aren't words anyone types
+pub const MAX_WORD_LEN: usize = 256;
+
+/// Whether `byte` can be part of a word; bytes of non-ASCII characters are,
+/// so that words with them are skipped whole rather than split
+pub fn is_word_byte(byte: u8) -> bool {
+    is_word_char(byte) || !byte.is_ascii()
+}
+
+/// Words of `bytes` worth completing: ASCII words of two to `MAX_WORD_LEN`
+/// characters that don't start with a digit
+pub fn words(bytes: &[u8]) -> impl Iterator<Item = &str> {
+    bytes
+        .split(|&byte| !is_word_byte(byte))
+        .filter(|word| {
+            (2..=MAX_WORD_LEN).contains(&word.len()) && word.is_ascii() && !word[0].is_ascii_digit()
+        })
+        .filter_map(|word| std::str::from_utf8(word).ok())
+}
+
+/// How many times each word appears in a buffer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WordIndex {
+    counts: HashMap<String, usize>,
+}
+
+impl WordIndex {
+    /// Index of the words of `bytes`
+    pub fn new(bytes: &[u8]) -> Self {
+        let mut index = Self::default();
+        index.add(bytes);
+        index
+    }
+
+    /// Count the words of `bytes`
+    pub fn add(&mut self, bytes: &[u8]) {
+        for word in words(bytes) {
+            *self.counts.entry(word.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// Stop counting the words of `bytes`
+    pub fn remove(&mut self, bytes: &[u8]) {
+        for word in words(bytes) {
+            if let Some(count) = self.counts.get_mut(word) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(word);
+                }
+            }
+        }
+    }
+
+    /// Times `word` appears
+    pub fn count(&self, word: &str) -> usize {
+        self.counts.get(word).copied().unwrap_or(0)
+    }
+}
+
+/// Distance in lines from `cursor_line` to the nearest of the `lines`, given
+/// with their line numbers, that each word is on
+pub fn line_distances<'a>(
+    lines: impl IntoIterator<Item = (usize, &'a [u8])>,
+    cursor_line: usize,
+) -> HashMap<String, usize> {
+    let mut distances: HashMap<String, usize> = HashMap::new();
+    for (line, bytes) in lines {
+        let distance = line.abs_diff(cursor_line);
+        for word in words(bytes) {
+            distances
+                .entry(word.to_string())
+                .and_modify(|nearest| *nearest = (*nearest).min(distance))
+                .or_insert(distance);
+        }
+    }
+    distances
+}
+
+/// Words of `indexes` that complete `prefix`, ignoring case: those with a
+/// distance in `nearby` first, nearest first, then the rest, most frequent
+/// first; at most `MAX_WORD_COMPLETIONS`
+///
+/// `typed` is the word the cursor is in, whose own occurrence doesn't count.
+pub fn rank(
+    prefix: &str,
+    typed: &str,
+    nearby: &HashMap<String, usize>,
+    indexes: &[&WordIndex],
+) -> Vec<String> {
+    let prefix = prefix.to_lowercase();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for index in indexes {
+        for (word, count) in &index.counts {
+            if word.len() > prefix.len() && word.to_lowercase().starts_with(&prefix) {
+                *counts.entry(word).or_insert(0) += count;
+            }
+        }
+    }
+    if let Some(count) = counts.get_mut(typed) {
+        *count -= 1;
+    }
+
+    let mut ranked: Vec<(&str, usize)> =
+        counts.into_iter().filter(|(_, count)| *count > 0).collect();
+    ranked.sort_by_key(|&(word, count)| {
+        let distance = nearby.get(word).copied().unwrap_or(usize::MAX);
+        (distance, Reverse(count), word)
+    });
+    ranked
+        .into_iter()
+        .take(MAX_WORD_COMPLETIONS)
+        .map(|(word, _)| word.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        let text = b"let x_1 = foo(bar, 42); caf\xc3\xa9 _id 9lives";
+        assert_eq!(
+            words(text).collect::<Vec<_>>(),
+            vec!["let", "x_1", "foo", "bar", "_id"]
+        );
+
+        // Runs longer than a word are skipped
+        let longest = "a".repeat(MAX_WORD_LEN);
+        let text = format!("{}a ok {}", longest, longest);
+        assert_eq!(
+            words(text.as_bytes()).collect::<Vec<_>>(),
+            vec!["ok", longest.as_str()]
+        );
+    }
+
+    #[test]
+    fn test_index_add_and_remove() {
+        let mut index = WordIndex::new(b"alpha beta alpha");
+        assert_eq!(index.count("alpha"), 2);
+        assert_eq!(index.count("beta"), 1);
+
+        // An edit turning "beta" into "betamax"
+        index.remove(b"beta");
+        index.add(b"betamax");
+        assert_eq!(index.count("beta"), 0);
+        assert_eq!(index.count("betamax"), 1);
+
+        // Removing a word that isn't there leaves the counts alone
+        index.remove(b"gamma alpha");
+        assert_eq!(index.count("alpha"), 1);
+        assert_eq!(index.count("gamma"), 0);
+    }
+
+    #[test]
+    fn test_line_distances() {
+        let lines: Vec<(usize, &[u8])> = vec![(3, b"foo bar"), (5, b"bar"), (9, b"foo baz")];
+        let distances = line_distances(lines, 6);
+        assert_eq!(distances["bar"], 1);
+        assert_eq!(distances["foo"], 3);
+        assert_eq!(distances["baz"], 3);
+    }
+
+    #[test]
+    fn test_rank() {
+        let current = WordIndex::new(b"format formula former format fo");
+        let other = WordIndex::new(b"Foreign format forest");
+        let nearby = line_distances(vec![(0, b"former".as_slice())], 2);
+
+        // Nearby first, then by count across buffers, matching any case
+        assert_eq!(
+            rank("fo", "fo", &nearby, &[&current, &other]),
+            vec!["former", "format", "Foreign", "forest", "formula"]
+        );
+        assert_eq!(
+            rank("FORM", "form", &nearby, &[&current]),
+            vec!["former", "format", "formula"]
+        );
+        // The word being typed doesn't complete itself
+        assert_eq!(
+            rank("for", "formula", &HashMap::new(), &[&current]),
+            vec!["format", "former"]
+        );
+    }
+}
@@ -13,7 +13,8 @@
 use crate::services::terminal::TerminalId;
 use crate::view::file_tree::{FileTreeView, NodeId};
 use lsp_types::{
-    CodeActionOrCommand, CompletionItem, Diagnostic, InlayHint, Location, SignatureHelp,
+    CodeActionOrCommand, CompletionItem, Diagnostic, InlayHint, Location, SelectionRange,
+    SignatureHelp,
 };
 use serde_json::Value;
 use std::sync::mpsc;
@@ -74,6 +75,12 @@ pub enum AsyncMessage {
         locations: Vec<Location>,
     },
 
+    /// LSP selection range response (None if the server doesn't support it)
+    LspSelectionRange {
+        request_id: u64,
+        ranges: Option<Vec<SelectionRange>>,
+    },
+
     /// LSP signature help response
     LspSignatureHelp {
         request_id: u64,
@@ -197,6 +204,22 @@ pub enum AsyncMessage {
 
     /// Terminal process exited
     TerminalExited { terminal_id: TerminalId },
+
+    /// Debug adapter answered a request
+    DapResponse {
+        /// ID of the adapter instance (see `DapHandle::id`)
+        session_id: u64,
+        response: crate::services::dap::protocol::Response,
+    },
+
+    /// Debug adapter sent an event (stopped, output, terminated, ...)
+    DapEvent {
+        session_id: u64,
+        event: crate::services::dap::protocol::EventMessage,
+    },
+
+    /// Debug adapter failed to start or its connection was lost
+    DapError { session_id: u64, error: String },
 }
 
 /// LSP progress value types
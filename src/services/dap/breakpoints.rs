@@ -0,0 +1,278 @@
+//! Breakpoint storage
+//!
+//! Breakpoints are kept per file path rather than per buffer so they survive
+//! closing and reopening files, can be restored from the session before the
+//! file is opened, and can be sent to the adapter for files that are not open.
+
+use crate::services::dap::protocol::SourceBreakpoint;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A breakpoint on a source line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// 0-indexed line number
+    pub line: usize,
+    /// Only stop when this expression evaluates to true
+    pub condition: Option<String>,
+    /// Log this message instead of stopping (a "logpoint")
+    pub log_message: Option<String>,
+    /// Whether the adapter accepted the breakpoint in the running session
+    pub verified: bool,
+}
+
+impl Breakpoint {
+    /// Create a plain breakpoint
+    pub fn new(line: usize) -> Self {
+        Self {
+            line,
+            condition: None,
+            log_message: None,
+            verified: false,
+        }
+    }
+
+    /// Margin symbol for this breakpoint
+    pub fn symbol(&self) -> &'static str {
+        if self.log_message.is_some() {
+            "◆"
+        } else if self.condition.is_some() {
+            "◉"
+        } else {
+            "●"
+        }
+    }
+
+    fn to_source_breakpoint(&self) -> SourceBreakpoint {
+        SourceBreakpoint {
+            line: self.line as i64 + 1,
+            condition: self.condition.clone(),
+            log_message: self.log_message.clone(),
+        }
+    }
+}
+
+/// All breakpoints, grouped by file
+#[derive(Debug, Clone, Default)]
+pub struct BreakpointStore {
+    /// Breakpoints per file, sorted by line
+    by_file: BTreeMap<PathBuf, Vec<Breakpoint>>,
+}
+
+impl BreakpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a plain breakpoint at `line`, or remove the existing one
+    ///
+    /// Returns true if a breakpoint was added.
+    pub fn toggle(&mut self, path: &Path, line: usize) -> bool {
+        if self.get(path, line).is_some() {
+            self.remove(path, line);
+            false
+        } else {
+            self.set(path, Breakpoint::new(line));
+            true
+        }
+    }
+
+    /// Insert or replace the breakpoint on `breakpoint.line`
+    pub fn set(&mut self, path: &Path, breakpoint: Breakpoint) {
+        let list = self.by_file.entry(path.to_path_buf()).or_default();
+        match list.binary_search_by_key(&breakpoint.line, |b| b.line) {
+            Ok(index) => list[index] = breakpoint,
+            Err(index) => list.insert(index, breakpoint),
+        }
+    }
+
+    /// Remove the breakpoint on `line`, returning it
+    pub fn remove(&mut self, path: &Path, line: usize) -> Option<Breakpoint> {
+        let list = self.by_file.get_mut(path)?;
+        let index = list.iter().position(|b| b.line == line)?;
+        let removed = list.remove(index);
+        if list.is_empty() {
+            self.by_file.remove(path);
+        }
+        Some(removed)
+    }
+
+    /// Get the breakpoint on `line`
+    pub fn get(&self, path: &Path, line: usize) -> Option<&Breakpoint> {
+        self.for_file(path).iter().find(|b| b.line == line)
+    }
+
+    /// Breakpoints in a file, sorted by line
+    pub fn for_file(&self, path: &Path) -> &[Breakpoint] {
+        self.by_file.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Files that have at least one breakpoint
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.by_file.keys()
+    }
+
+    /// Iterate over all (path, breakpoint) pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Breakpoint)> {
+        self.by_file
+            .iter()
+            .flat_map(|(path, list)| list.iter().map(move |bp| (path, bp)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_file.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_file.is_empty()
+    }
+
+    /// Remove every breakpoint, returning the files that had any
+    pub fn clear(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.by_file).into_keys().collect()
+    }
+
+    /// Move a file's breakpoints to new lines after the buffer was edited
+    ///
+    /// `new_lines` lists the current line of each breakpoint in their existing
+    /// order (edits never reorder them). Breakpoints that collapsed onto the
+    /// same line are merged, keeping the first.
+    pub fn relocate(&mut self, path: &Path, new_lines: &[usize]) {
+        let Some(list) = self.by_file.get_mut(path) else {
+            return;
+        };
+        if list.len() != new_lines.len() {
+            return;
+        }
+        for (bp, &line) in list.iter_mut().zip(new_lines) {
+            bp.line = line;
+        }
+        list.dedup_by_key(|b| b.line);
+    }
+
+    /// Breakpoints for a file in the form `setBreakpoints` expects
+    pub fn source_breakpoints(&self, path: &Path) -> Vec<SourceBreakpoint> {
+        self.for_file(path)
+            .iter()
+            .map(Breakpoint::to_source_breakpoint)
+            .collect()
+    }
+
+    /// Record the adapter's verification result for a `setBreakpoints` call
+    ///
+    /// The adapter answers with one entry per requested breakpoint, in order.
+    pub fn set_verified(&mut self, path: &Path, verified: &[bool]) {
+        if let Some(list) = self.by_file.get_mut(path) {
+            for (bp, &ok) in list.iter_mut().zip(verified) {
+                bp.verified = ok;
+            }
+        }
+    }
+
+    /// Forget verification state (when a session ends)
+    pub fn reset_verified(&mut self) {
+        for list in self.by_file.values_mut() {
+            for bp in list {
+                bp.verified = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_adds_and_removes() {
+        let mut store = BreakpointStore::new();
+        let path = Path::new("/src/main.rs");
+
+        assert!(store.toggle(path, 4));
+        assert!(store.toggle(path, 1));
+        assert_eq!(
+            store
+                .for_file(path)
+                .iter()
+                .map(|b| b.line)
+                .collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+
+        assert!(!store.toggle(path, 4));
+        assert_eq!(store.len(), 1);
+        assert!(!store.toggle(path, 1));
+        assert!(store.is_empty());
+        assert_eq!(store.files().count(), 0);
+    }
+
+    #[test]
+    fn test_set_replaces_existing_breakpoint() {
+        let mut store = BreakpointStore::new();
+        let path = Path::new("/a.py");
+        store.toggle(path, 2);
+        store.set(
+            path,
+            Breakpoint {
+                condition: Some("i > 3".to_string()),
+                ..Breakpoint::new(2)
+            },
+        );
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(path, 2).unwrap().symbol(), "◉");
+    }
+
+    #[test]
+    fn test_source_breakpoints_are_one_based() {
+        let mut store = BreakpointStore::new();
+        let path = Path::new("/a.py");
+        store.set(
+            path,
+            Breakpoint {
+                log_message: Some("hit {i}".to_string()),
+                ..Breakpoint::new(0)
+            },
+        );
+        let sent = store.source_breakpoints(path);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].line, 1);
+        assert_eq!(sent[0].log_message.as_deref(), Some("hit {i}"));
+    }
+
+    #[test]
+    fn test_relocate_follows_edits_and_merges_collapsed_lines() {
+        let mut store = BreakpointStore::new();
+        let path = Path::new("/a.rs");
+        store.toggle(path, 2);
+        store.toggle(path, 5);
+        store.toggle(path, 9);
+
+        // Two lines inserted above the first breakpoint
+        store.relocate(path, &[4, 7, 11]);
+        assert_eq!(
+            store
+                .for_file(path)
+                .iter()
+                .map(|b| b.line)
+                .collect::<Vec<_>>(),
+            vec![4, 7, 11]
+        );
+
+        // Lines between the last two were deleted
+        store.relocate(path, &[4, 7, 7]);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_verification_state() {
+        let mut store = BreakpointStore::new();
+        let path = Path::new("/a.rs");
+        store.toggle(path, 1);
+        store.toggle(path, 3);
+        store.set_verified(path, &[true, false]);
+        assert!(store.get(path, 1).unwrap().verified);
+        assert!(!store.get(path, 3).unwrap().verified);
+        store.reset_verified();
+        assert!(!store.get(path, 1).unwrap().verified);
+    }
+}
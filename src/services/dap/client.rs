@@ -0,0 +1,250 @@
+//! Async debug adapter client
+//!
+//! - `DapHandle`: sync handle owned by the editor; allocates sequence numbers
+//!   and queues requests for the task
+//! - `DapTask`: tokio task owning the adapter process; writes requests to its
+//!   stdin and forwards every response and event to the main loop as an
+//!   [`AsyncMessage`]
+//!
+//! Unlike the LSP client, the task does not correlate responses with requests:
+//! the editor keeps the table of pending requests (see `session.rs`), which
+//! keeps this layer a thin transport.
+
+use crate::services::async_bridge::{AsyncBridge, AsyncMessage};
+use crate::services::dap::protocol::{self, ProtocolMessage, Request, Response};
+use crate::types::DebugAdapterConfig;
+use serde_json::Value;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{mpsc as std_mpsc, Arc};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::mpsc;
+
+/// Counter for generating unique debug session IDs
+static NEXT_SESSION_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Commands sent from the handle to the task
+#[derive(Debug)]
+enum DapCommand {
+    /// Write a message to the adapter
+    Send(ProtocolMessage),
+    /// Kill the adapter process and stop the task
+    Shutdown,
+}
+
+/// Synchronous handle to a running debug adapter
+pub struct DapHandle {
+    /// Unique identifier, attached to every message this adapter produces
+    id: u64,
+
+    /// Sequence counter shared with the task (reverse-request replies use it too)
+    next_seq: Arc<AtomicI64>,
+
+    /// Channel for sending commands to the task
+    command_tx: mpsc::UnboundedSender<DapCommand>,
+}
+
+impl DapHandle {
+    /// Spawn the adapter process in an async task
+    ///
+    /// Spawn failures are reported asynchronously as [`AsyncMessage::DapError`].
+    pub fn spawn(
+        runtime: &tokio::runtime::Handle,
+        config: &DebugAdapterConfig,
+        async_bridge: &AsyncBridge,
+    ) -> Self {
+        let id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let next_seq = Arc::new(AtomicI64::new(1));
+        let async_tx = async_bridge.sender();
+        let command = config.command.clone();
+        let args = config.args.clone();
+
+        let task_seq = next_seq.clone();
+        let task_command_tx = command_tx.clone();
+        runtime.spawn(async move {
+            match DapTask::spawn(&command, &args) {
+                Ok(task) => {
+                    task.run(id, command_rx, task_command_tx, task_seq, async_tx)
+                        .await;
+                }
+                Err(error) => {
+                    tracing::error!("Failed to spawn debug adapter: {}", error);
+                    let _ = async_tx.send(AsyncMessage::DapError {
+                        session_id: id,
+                        error,
+                    });
+                }
+            }
+        });
+
+        Self {
+            id,
+            next_seq,
+            command_tx,
+        }
+    }
+
+    /// Get the unique ID of this adapter instance
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Send a request and return its sequence number
+    pub fn send_request(&self, command: &str, arguments: Option<Value>) -> Result<i64, String> {
+        let seq = self.next_seq.fetch_add(1, Ordering::SeqCst);
+        let message = ProtocolMessage::Request(Request {
+            seq,
+            command: command.to_string(),
+            arguments,
+        });
+        self.command_tx
+            .send(DapCommand::Send(message))
+            .map_err(|_| "Debug adapter is not running".to_string())?;
+        Ok(seq)
+    }
+
+    /// Kill the adapter (call after `disconnect` was answered, or to force stop)
+    pub fn shutdown(&self) {
+        let _ = self.command_tx.send(DapCommand::Shutdown);
+    }
+}
+
+impl Drop for DapHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Async side of a debug adapter connection
+struct DapTask {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl DapTask {
+    fn spawn(command: &str, args: &[String]) -> Result<Self, String> {
+        tracing::info!("Spawning debug adapter: {} {:?}", command, args);
+
+        let mut process = Command::new(command)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                format!(
+                    "Failed to spawn debug adapter '{}': {}",
+                    command,
+                    match e.kind() {
+                        std::io::ErrorKind::NotFound => "executable not found in PATH".to_string(),
+                        _ => e.to_string(),
+                    }
+                )
+            })?;
+
+        let stdin = process
+            .stdin
+            .take()
+            .ok_or_else(|| "Failed to get stdin".to_string())?;
+        let stdout = BufReader::new(
+            process
+                .stdout
+                .take()
+                .ok_or_else(|| "Failed to get stdout".to_string())?,
+        );
+
+        Ok(Self {
+            process,
+            stdin,
+            stdout,
+        })
+    }
+
+    async fn run(
+        self,
+        session_id: u64,
+        mut command_rx: mpsc::UnboundedReceiver<DapCommand>,
+        command_tx: mpsc::UnboundedSender<DapCommand>,
+        next_seq: Arc<AtomicI64>,
+        async_tx: std_mpsc::Sender<AsyncMessage>,
+    ) {
+        let DapTask {
+            mut process,
+            mut stdin,
+            stdout,
+        } = self;
+
+        Self::spawn_stdout_reader(stdout, session_id, command_tx, next_seq, async_tx.clone());
+
+        while let Some(command) = command_rx.recv().await {
+            match command {
+                DapCommand::Send(message) => {
+                    let result = match protocol::encode_message(&message) {
+                        Ok(bytes) => stdin
+                            .write_all(&bytes)
+                            .await
+                            .and(stdin.flush().await)
+                            .map_err(|e| format!("Failed to write to debug adapter: {}", e)),
+                        Err(e) => Err(e),
+                    };
+                    if let Err(error) = result {
+                        tracing::error!("{}", error);
+                        let _ = async_tx.send(AsyncMessage::DapError { session_id, error });
+                        break;
+                    }
+                }
+                DapCommand::Shutdown => break,
+            }
+        }
+
+        let _ = process.kill().await;
+        tracing::info!("Debug adapter task {} exiting", session_id);
+    }
+
+    /// Read adapter output until EOF, forwarding messages to the main loop
+    fn spawn_stdout_reader(
+        mut stdout: BufReader<ChildStdout>,
+        session_id: u64,
+        command_tx: mpsc::UnboundedSender<DapCommand>,
+        next_seq: Arc<AtomicI64>,
+        async_tx: std_mpsc::Sender<AsyncMessage>,
+    ) {
+        tokio::spawn(async move {
+            loop {
+                match protocol::read_message(&mut stdout).await {
+                    Ok(ProtocolMessage::Response(response)) => {
+                        let _ = async_tx.send(AsyncMessage::DapResponse {
+                            session_id,
+                            response,
+                        });
+                    }
+                    Ok(ProtocolMessage::Event(event)) => {
+                        let _ = async_tx.send(AsyncMessage::DapEvent { session_id, event });
+                    }
+                    Ok(ProtocolMessage::Request(request)) => {
+                        // Reverse requests (runInTerminal, startDebugging) are not
+                        // supported; adapters fall back to their own console.
+                        tracing::debug!("Declining reverse DAP request '{}'", request.command);
+                        let reply = ProtocolMessage::Response(Response {
+                            seq: next_seq.fetch_add(1, Ordering::SeqCst),
+                            request_seq: request.seq,
+                            success: false,
+                            command: request.command,
+                            message: Some("not supported by this client".to_string()),
+                            body: None,
+                        });
+                        let _ = command_tx.send(DapCommand::Send(reply));
+                    }
+                    Err(error) => {
+                        tracing::info!("Debug adapter {} stream ended: {}", session_id, error);
+                        let _ = async_tx.send(AsyncMessage::DapError { session_id, error });
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,135 @@
+//! Launch/attach argument expansion
+//!
+//! Debug adapter configurations use VS Code style `${variable}` placeholders
+//! so one default configuration works for any file or project.
+
+use serde_json::Value;
+use std::path::Path;
+
+/// Values available to `${...}` placeholders
+#[derive(Debug, Clone)]
+pub struct LaunchContext<'a> {
+    /// File the session was started from
+    pub file: Option<&'a Path>,
+    /// Project root
+    pub workspace_folder: &'a Path,
+}
+
+impl LaunchContext<'_> {
+    fn lookup(&self, name: &str) -> Option<String> {
+        let file = self.file;
+        match name {
+            "file" => file.map(|f| f.to_string_lossy().to_string()),
+            "fileBasename" => file
+                .and_then(|f| f.file_name())
+                .map(|n| n.to_string_lossy().to_string()),
+            "fileBasenameNoExtension" => file
+                .and_then(|f| f.file_stem())
+                .map(|n| n.to_string_lossy().to_string()),
+            "fileDirname" => file
+                .and_then(|f| f.parent())
+                .map(|d| d.to_string_lossy().to_string()),
+            "workspaceFolder" => Some(self.workspace_folder.to_string_lossy().to_string()),
+            "workspaceFolderBasename" => self
+                .workspace_folder
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string()),
+            _ => None,
+        }
+    }
+
+    /// Expand placeholders in a single string; unknown placeholders are kept
+    pub fn expand_str(&self, input: &str) -> String {
+        let mut result = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            match after.find('}') {
+                Some(end) => {
+                    let name = &after[..end];
+                    match self.lookup(name) {
+                        Some(value) => result.push_str(&value),
+                        None => {
+                            result.push_str("${");
+                            result.push_str(name);
+                            result.push('}');
+                        }
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    result.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    /// Expand placeholders in every string inside a JSON value
+    pub fn expand(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.expand_str(s)),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.expand(v)).collect()),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.expand(v)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_expand_file_variables() {
+        let ctx = LaunchContext {
+            file: Some(Path::new("/proj/src/app.py")),
+            workspace_folder: Path::new("/proj"),
+        };
+        assert_eq!(ctx.expand_str("${file}"), "/proj/src/app.py");
+        assert_eq!(
+            ctx.expand_str("${fileDirname}/${fileBasenameNoExtension}"),
+            "/proj/src/app"
+        );
+        assert_eq!(
+            ctx.expand_str("${workspaceFolder}/target/debug/${workspaceFolderBasename}"),
+            "/proj/target/debug/proj"
+        );
+    }
+
+    #[test]
+    fn test_unknown_and_unterminated_placeholders_are_kept() {
+        let ctx = LaunchContext {
+            file: None,
+            workspace_folder: Path::new("/proj"),
+        };
+        assert_eq!(ctx.expand_str("${env:HOME}/x"), "${env:HOME}/x");
+        assert_eq!(ctx.expand_str("${file}"), "${file}");
+        assert_eq!(ctx.expand_str("a ${oops"), "a ${oops");
+    }
+
+    #[test]
+    fn test_expand_nested_json() {
+        let ctx = LaunchContext {
+            file: Some(Path::new("/p/main.py")),
+            workspace_folder: Path::new("/p"),
+        };
+        let expanded = ctx.expand(&json!({
+            "program": "${file}",
+            "args": ["--root", "${workspaceFolder}"],
+            "stopOnEntry": false
+        }));
+        assert_eq!(
+            expanded,
+            json!({"program": "/p/main.py", "args": ["--root", "/p"], "stopOnEntry": false})
+        );
+    }
+}
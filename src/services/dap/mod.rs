@@ -0,0 +1,66 @@
+//! DAP (Debug Adapter Protocol) Client
+//!
+//! This module lets the editor drive external debug adapters (debugpy,
+//! lldb-dap, ...) the same way the `lsp` module drives language servers.
+//!
+//! # Architecture Overview
+//!
+//! ```text
+//! ┌─────────────────────────────────────────────────────────────────────┐
+//! │                           Editor (App)                              │
+//! │                                                                     │
+//! │  BreakpointStore ──► margin indicators ("breakpoints" namespace)    │
+//! │  DebugSession    ──► current-line overlay, debug panel buffers      │
+//! │        ▲                                                            │
+//! │        │ AsyncMessage::DapResponse / DapEvent / DapError            │
+//! └────────┼────────────────────────────────────┬───────────────────────┘
+//!          │                                    │ DapHandle::send_request
+//!          │                                    ▼
+//! ┌─────────────────────────────────────────────────────────────────────┐
+//! │                  Tokio Runtime: DapTask (one per session)           │
+//! └──────────────────────────────┬──────────────────────────────────────┘
+//!                                │ stdin/stdout, Content-Length framing
+//!                                ▼
+//!                       ┌─────────────────┐
+//!                       │ debug adapter   │
+//!                       │ (subprocess)    │
+//!                       └─────────────────┘
+//! ```
+//!
+//! # Module Structure
+//!
+//! - **`protocol`**: message envelope, framing and the body types we read.
+//! - **`client`**: [`DapHandle`] (sync) and the tokio task owning the adapter.
+//! - **`session`**: [`DebugSession`] - run state, stack, variables, console,
+//!   and the pending-request table; renders the debug panels.
+//! - **`breakpoints`**: [`BreakpointStore`] - per-file breakpoints, including
+//!   conditional breakpoints and logpoints. Lives outside the session so
+//!   breakpoints exist (and persist in the workspace session) while no
+//!   debugger is running.
+//! - **`launch`**: `${file}`-style placeholder expansion for launch arguments.
+//!
+//! # Session Flow
+//!
+//! ```text
+//! initialize ──► launch/attach ──► (event: initialized)
+//!            ──► setBreakpoints per file ──► configurationDone
+//! (event: stopped) ──► stackTrace ──► scopes ──► variables
+//!                  └─► evaluate (one per watch expression)
+//! continue / next / stepIn / stepOut ──► (event: continued | stopped)
+//! (event: terminated | exited) ──► disconnect
+//! ```
+//!
+//! Adapters are configured per language in the `debuggers` section of
+//! config.json (see [`DebugAdapterConfig`]).
+
+pub mod breakpoints;
+pub mod client;
+pub mod launch;
+pub mod protocol;
+pub mod session;
+
+pub use breakpoints::{Breakpoint, BreakpointStore};
+pub use client::DapHandle;
+pub use session::{DebugSession, DebugState};
+
+pub use crate::types::{DebugAdapterConfig, DebugRequestKind};
@@ -0,0 +1,309 @@
+//! Debug Adapter Protocol wire format
+//!
+//! DAP uses the same `Content-Length` framing as LSP, but its messages are not
+//! JSON-RPC: every message carries a `seq` number and a `type` discriminator
+//! (`request`, `response` or `event`), and responses echo the `command` of the
+//! request they answer.
+//!
+//! Only the subset of the protocol body types the editor actually reads is
+//! modelled here; everything else is passed around as raw JSON.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
+
+/// A single DAP protocol message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProtocolMessage {
+    Request(Request),
+    Response(Response),
+    Event(EventMessage),
+}
+
+/// A request (client -> adapter, or a reverse request adapter -> client)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub seq: i64,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+}
+
+/// A response to a previously sent request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub seq: i64,
+    pub request_seq: i64,
+    pub success: bool,
+    pub command: String,
+    /// Error message when `success` is false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// An event pushed by the adapter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventMessage {
+    pub seq: i64,
+    pub event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// A source file reference
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Source {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// A breakpoint as sent in `setBreakpoints` (lines are 1-based)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceBreakpoint {
+    pub line: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_message: Option<String>,
+}
+
+/// A breakpoint as reported back by the adapter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub verified: bool,
+    #[serde(default)]
+    pub line: Option<i64>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// A thread of the debuggee
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Thread {
+    pub id: i64,
+    pub name: String,
+}
+
+/// One frame of a stack trace (lines and columns are 1-based)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StackFrame {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub source: Option<Source>,
+    pub line: i64,
+    #[serde(default)]
+    pub column: i64,
+}
+
+/// A named container of variables (locals, globals, registers, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    pub name: String,
+    pub variables_reference: i64,
+    #[serde(default)]
+    pub expensive: bool,
+}
+
+/// A variable; `variables_reference > 0` means it has children
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    #[serde(default, rename = "type")]
+    pub type_name: Option<String>,
+    #[serde(default)]
+    pub variables_reference: i64,
+}
+
+/// Body of the `stopped` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedEventBody {
+    pub reason: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub thread_id: Option<i64>,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+/// Body of the `output` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputEventBody {
+    #[serde(default)]
+    pub category: Option<String>,
+    pub output: String,
+}
+
+/// Body of the `evaluate` response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponseBody {
+    pub result: String,
+    #[serde(default, rename = "type")]
+    pub type_name: Option<String>,
+    #[serde(default)]
+    pub variables_reference: i64,
+}
+
+/// Serialize a message with its `Content-Length` header
+pub fn encode_message(message: &ProtocolMessage) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_string(message).map_err(|e| format!("Failed to serialize: {}", e))?;
+    let mut framed = format!("Content-Length: {}\r\n\r\n", json.len()).into_bytes();
+    framed.extend_from_slice(json.as_bytes());
+    Ok(framed)
+}
+
+/// Read one `Content-Length` framed message
+pub async fn read_message<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> Result<ProtocolMessage, String> {
+    let mut content_length: Option<usize> = None;
+
+    loop {
+        let mut line = String::new();
+        let bytes_read = reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| format!("Failed to read from adapter: {}", e))?;
+
+        // EOF - adapter closed stdout
+        if bytes_read == 0 {
+            return Err("Debug adapter closed stdout (EOF)".to_string());
+        }
+
+        if line == "\r\n" || line == "\n" {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            content_length = Some(
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("Invalid Content-Length: {}", e))?,
+            );
+        }
+    }
+
+    let content_length =
+        content_length.ok_or_else(|| "Missing Content-Length header".to_string())?;
+
+    let mut content = vec![0u8; content_length];
+    reader
+        .read_exact(&mut content)
+        .await
+        .map_err(|e| format!("Failed to read content: {}", e))?;
+
+    tracing::trace!(
+        "Received DAP message: {}",
+        String::from_utf8_lossy(&content)
+    );
+
+    serde_json::from_slice(&content).map_err(|e| format!("Failed to deserialize message: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_serialization() {
+        let msg = ProtocolMessage::Request(Request {
+            seq: 1,
+            command: "initialize".to_string(),
+            arguments: Some(json!({"adapterID": "lldb"})),
+        });
+        let value = serde_json::to_value(&msg).unwrap();
+        assert_eq!(value["type"], "request");
+        assert_eq!(value["seq"], 1);
+        assert_eq!(value["command"], "initialize");
+        assert_eq!(value["arguments"]["adapterID"], "lldb");
+    }
+
+    #[test]
+    fn test_response_and_event_deserialization() {
+        let response: ProtocolMessage = serde_json::from_value(json!({
+            "seq": 4, "type": "response", "request_seq": 2,
+            "success": false, "command": "launch", "message": "no program"
+        }))
+        .unwrap();
+        match response {
+            ProtocolMessage::Response(r) => {
+                assert_eq!(r.request_seq, 2);
+                assert!(!r.success);
+                assert_eq!(r.message.as_deref(), Some("no program"));
+                assert!(r.body.is_none());
+            }
+            other => panic!("expected response, got {:?}", other),
+        }
+
+        let event: ProtocolMessage = serde_json::from_value(json!({
+            "seq": 5, "type": "event", "event": "stopped",
+            "body": {"reason": "breakpoint", "threadId": 7}
+        }))
+        .unwrap();
+        match event {
+            ProtocolMessage::Event(e) => {
+                let body: StoppedEventBody = serde_json::from_value(e.body.unwrap()).unwrap();
+                assert_eq!(body.reason, "breakpoint");
+                assert_eq!(body.thread_id, Some(7));
+            }
+            other => panic!("expected event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_source_breakpoint_uses_camel_case() {
+        let bp = SourceBreakpoint {
+            line: 3,
+            condition: None,
+            log_message: Some("x = {x}".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(&bp).unwrap(),
+            json!({"line": 3, "logMessage": "x = {x}"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_framing_round_trip() {
+        let first = ProtocolMessage::Event(EventMessage {
+            seq: 1,
+            event: "initialized".to_string(),
+            body: None,
+        });
+        let second = ProtocolMessage::Response(Response {
+            seq: 2,
+            request_seq: 1,
+            success: true,
+            command: "threads".to_string(),
+            message: None,
+            body: Some(json!({"threads": [{"id": 1, "name": "main"}]})),
+        });
+
+        let mut bytes = encode_message(&first).unwrap();
+        bytes.extend(encode_message(&second).unwrap());
+
+        let mut reader = tokio::io::BufReader::new(bytes.as_slice());
+        assert_eq!(read_message(&mut reader).await.unwrap(), first);
+        assert_eq!(read_message(&mut reader).await.unwrap(), second);
+        assert!(read_message(&mut reader).await.is_err());
+    }
+}
@@ -0,0 +1,565 @@
+//! State of a running debug session
+//!
+//! `DebugSession` holds everything the editor knows about the debuggee: run
+//! state, the stack of the stopped thread, fetched variables, watch results
+//! and console output. It also tracks which request each outstanding `seq`
+//! belongs to, since DAP responses only carry the request's `seq` and command.
+//!
+//! The panel renderers at the bottom turn this state into virtual buffer
+//! content; they are pure so the layout can be unit tested without an adapter.
+
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::dap::protocol::{
+    OutputEventBody, Scope, StackFrame, StoppedEventBody, Variable,
+};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Run state of the debuggee
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugState {
+    /// Adapter is initializing / launching
+    Starting,
+    /// Debuggee is running
+    Running,
+    /// Debuggee is paused
+    Stopped {
+        thread_id: Option<i64>,
+        reason: String,
+    },
+    /// Debuggee exited or the session was ended
+    Terminated,
+}
+
+/// What an outstanding request was for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingRequest {
+    Initialize,
+    LaunchOrAttach,
+    ConfigurationDone,
+    SetBreakpoints {
+        path: PathBuf,
+    },
+    Threads,
+    StackTrace,
+    Scopes,
+    Variables {
+        reference: i64,
+    },
+    Evaluate {
+        target: EvaluateTarget,
+    },
+    /// continue / next / stepIn / stepOut / pause
+    Execution,
+    Disconnect,
+}
+
+/// Where an evaluation result goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvaluateTarget {
+    /// A watch expression
+    Watch { expression: String },
+    /// An expression typed into the debug console
+    Console,
+}
+
+/// One line of debug console output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleLine {
+    /// DAP output category ("stdout", "stderr", "console") or "input"/"result"
+    pub category: String,
+    pub text: String,
+}
+
+/// Maximum number of console lines kept
+const MAX_CONSOLE_LINES: usize = 5000;
+
+/// A running debug session
+#[derive(Debug)]
+pub struct DebugSession {
+    /// Language the adapter was chosen for
+    pub language: String,
+    pub state: DebugState,
+    /// Stack of the stopped thread, innermost first
+    pub stack_frames: Vec<StackFrame>,
+    /// Index into `stack_frames` shown in the editor and variables panel
+    pub selected_frame: usize,
+    /// Scopes of the selected frame
+    pub scopes: Vec<Scope>,
+    /// Fetched variables by `variablesReference`
+    pub variables: HashMap<i64, Vec<Variable>>,
+    /// Variable references the user expanded in the variables panel
+    pub expanded: HashSet<i64>,
+    /// Latest value (or error) of each watch expression
+    pub watch_values: HashMap<String, Result<String, String>>,
+    pub console: Vec<ConsoleLine>,
+    /// Whether the adapter wants `configurationDone` after breakpoints are set
+    pub supports_configuration_done: bool,
+    /// launch/attach command and arguments, sent once `initialize` is answered
+    pub launch: Option<(String, Value)>,
+    /// Thread that last stopped; used for `pause` while running
+    last_thread_id: Option<i64>,
+    pending: HashMap<i64, PendingRequest>,
+}
+
+impl DebugSession {
+    pub fn new(language: String) -> Self {
+        Self {
+            language,
+            state: DebugState::Starting,
+            stack_frames: Vec::new(),
+            selected_frame: 0,
+            scopes: Vec::new(),
+            variables: HashMap::new(),
+            expanded: HashSet::new(),
+            watch_values: HashMap::new(),
+            console: Vec::new(),
+            supports_configuration_done: false,
+            launch: None,
+            last_thread_id: None,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Remember what the request with `seq` was for
+    pub fn track(&mut self, seq: i64, request: PendingRequest) {
+        self.pending.insert(seq, request);
+    }
+
+    /// Take the pending entry answered by a response
+    pub fn take_pending(&mut self, request_seq: i64) -> Option<PendingRequest> {
+        self.pending.remove(&request_seq)
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        matches!(self.state, DebugState::Stopped { .. })
+    }
+
+    /// Thread to use for execution and stack requests
+    pub fn thread_id(&self) -> Option<i64> {
+        match &self.state {
+            DebugState::Stopped { thread_id, .. } => thread_id.or(self.last_thread_id),
+            _ => self.last_thread_id,
+        }
+    }
+
+    /// Record a thread learned from a `threads` response
+    pub fn set_thread_id(&mut self, id: i64) {
+        self.last_thread_id = Some(id);
+        if let DebugState::Stopped { thread_id, .. } = &mut self.state {
+            *thread_id = Some(id);
+        }
+    }
+
+    /// Handle a `stopped` event; stale stack state is dropped
+    pub fn on_stopped(&mut self, body: StoppedEventBody) {
+        if body.thread_id.is_some() {
+            self.last_thread_id = body.thread_id;
+        }
+        self.state = DebugState::Stopped {
+            thread_id: body.thread_id,
+            reason: body.reason,
+        };
+        self.clear_frame_state();
+        self.stack_frames.clear();
+        self.selected_frame = 0;
+    }
+
+    /// Handle resuming (a `continued` event or a successful execution request)
+    pub fn on_continued(&mut self) {
+        if self.state != DebugState::Terminated {
+            self.state = DebugState::Running;
+        }
+        self.stack_frames.clear();
+        self.selected_frame = 0;
+        self.clear_frame_state();
+    }
+
+    /// Store a `stackTrace` response body
+    pub fn set_stack_trace(&mut self, body: &Value) {
+        self.stack_frames = body
+            .get("stackFrames")
+            .cloned()
+            .and_then(|frames| serde_json::from_value(frames).ok())
+            .unwrap_or_default();
+        self.selected_frame = 0;
+    }
+
+    /// Select a frame; returns false if the index is out of range
+    pub fn select_frame(&mut self, index: usize) -> bool {
+        if index >= self.stack_frames.len() {
+            return false;
+        }
+        self.selected_frame = index;
+        self.clear_frame_state();
+        true
+    }
+
+    pub fn current_frame(&self) -> Option<&StackFrame> {
+        self.stack_frames.get(self.selected_frame)
+    }
+
+    /// Store a `scopes` response body; returns the references to fetch eagerly
+    pub fn set_scopes(&mut self, body: &Value) -> Vec<i64> {
+        self.scopes = body
+            .get("scopes")
+            .cloned()
+            .and_then(|scopes| serde_json::from_value(scopes).ok())
+            .unwrap_or_default();
+        self.scopes
+            .iter()
+            .filter(|s| !s.expensive && s.variables_reference > 0)
+            .map(|s| s.variables_reference)
+            .collect()
+    }
+
+    /// Store a `variables` response body
+    pub fn set_variables(&mut self, reference: i64, body: &Value) {
+        let vars = body
+            .get("variables")
+            .cloned()
+            .and_then(|vars| serde_json::from_value(vars).ok())
+            .unwrap_or_default();
+        self.variables.insert(reference, vars);
+    }
+
+    /// Expand or collapse a variable; returns true if its children must be fetched
+    pub fn toggle_expanded(&mut self, reference: i64) -> bool {
+        if self.expanded.remove(&reference) {
+            false
+        } else {
+            self.expanded.insert(reference);
+            !self.variables.contains_key(&reference)
+        }
+    }
+
+    /// Append an `output` event to the console
+    pub fn append_output(&mut self, body: OutputEventBody) {
+        let category = body.category.unwrap_or_else(|| "console".to_string());
+        // Telemetry is not meant for humans
+        if category == "telemetry" {
+            return;
+        }
+        self.push_console(&category, &body.output);
+    }
+
+    /// Append text to the console, splitting it into lines
+    pub fn push_console(&mut self, category: &str, text: &str) {
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        for line in text.split('\n') {
+            self.console.push(ConsoleLine {
+                category: category.to_string(),
+                text: line.trim_end_matches('\r').to_string(),
+            });
+        }
+        if self.console.len() > MAX_CONSOLE_LINES {
+            let excess = self.console.len() - MAX_CONSOLE_LINES;
+            self.console.drain(..excess);
+        }
+    }
+
+    fn clear_frame_state(&mut self) {
+        self.scopes.clear();
+        self.variables.clear();
+        self.expanded.clear();
+        self.watch_values.clear();
+    }
+
+    /// Short description of the state for the status bar
+    pub fn status_text(&self) -> String {
+        match &self.state {
+            DebugState::Starting => "Debug: starting".to_string(),
+            DebugState::Running => "Debug: running".to_string(),
+            DebugState::Stopped { reason, .. } => match self.current_frame() {
+                Some(frame) => format!("Debug: paused ({}) in {}", reason, frame.name),
+                None => format!("Debug: paused ({})", reason),
+            },
+            DebugState::Terminated => "Debug: terminated".to_string(),
+        }
+    }
+
+    // === Panel rendering ===
+
+    /// Call stack panel; each frame line carries a `frame` index property
+    pub fn render_call_stack(&self) -> Vec<TextPropertyEntry> {
+        if self.stack_frames.is_empty() {
+            return vec![TextPropertyEntry::text(match self.state {
+                DebugState::Stopped { .. } => "(loading stack...)\n",
+                _ => "(not paused)\n",
+            })];
+        }
+        self.stack_frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                let marker = if index == self.selected_frame {
+                    "▶"
+                } else {
+                    " "
+                };
+                let location = frame
+                    .source
+                    .as_ref()
+                    .and_then(|s| s.name.clone().or_else(|| s.path.clone()))
+                    .map(|name| format!("{}:{}", name, frame.line))
+                    .unwrap_or_else(|| "<unknown source>".to_string());
+                TextPropertyEntry::text(format!("{} {}  {}\n", marker, frame.name, location))
+                    .with_property("frame", json!(index))
+            })
+            .collect()
+    }
+
+    /// Variables panel; expandable lines carry a `variables_reference` property
+    pub fn render_variables(&self) -> Vec<TextPropertyEntry> {
+        if self.scopes.is_empty() {
+            return vec![TextPropertyEntry::text(if self.is_stopped() {
+                "(no variables)\n"
+            } else {
+                "(not paused)\n"
+            })];
+        }
+        let mut entries = Vec::new();
+        for scope in &self.scopes {
+            let expanded = !scope.expensive || self.expanded.contains(&scope.variables_reference);
+            let arrow = if expanded { "▾" } else { "▸" };
+            entries.push(
+                TextPropertyEntry::text(format!("{} {}\n", arrow, scope.name))
+                    .with_property("variables_reference", json!(scope.variables_reference)),
+            );
+            if expanded {
+                self.render_variable_children(scope.variables_reference, 1, &mut entries);
+            }
+        }
+        entries
+    }
+
+    fn render_variable_children(
+        &self,
+        reference: i64,
+        depth: usize,
+        entries: &mut Vec<TextPropertyEntry>,
+    ) {
+        let indent = "  ".repeat(depth);
+        let Some(vars) = self.variables.get(&reference) else {
+            entries.push(TextPropertyEntry::text(format!("{}  ...\n", indent)));
+            return;
+        };
+        for var in vars {
+            let type_suffix = var
+                .type_name
+                .as_deref()
+                .filter(|t| !t.is_empty())
+                .map(|t| format!(": {}", t))
+                .unwrap_or_default();
+            if var.variables_reference > 0 {
+                let expanded = self.expanded.contains(&var.variables_reference);
+                let arrow = if expanded { "▾" } else { "▸" };
+                entries.push(
+                    TextPropertyEntry::text(format!(
+                        "{}{} {}{} = {}\n",
+                        indent, arrow, var.name, type_suffix, var.value
+                    ))
+                    .with_property("variables_reference", json!(var.variables_reference)),
+                );
+                // Depth guard against self-referencing structures
+                if expanded && depth < 16 {
+                    self.render_variable_children(var.variables_reference, depth + 1, entries);
+                }
+            } else {
+                entries.push(TextPropertyEntry::text(format!(
+                    "{}  {}{} = {}\n",
+                    indent, var.name, type_suffix, var.value
+                )));
+            }
+        }
+    }
+
+    /// Watch panel; each line carries a `watch` index property
+    pub fn render_watches(&self, watches: &[String]) -> Vec<TextPropertyEntry> {
+        render_watch_lines(watches, Some(self))
+    }
+
+    /// Debug console panel
+    pub fn render_console(&self) -> Vec<TextPropertyEntry> {
+        render_console_lines(&self.console)
+    }
+}
+
+/// Watch panel content; `session` is None when no debug session is running
+pub fn render_watch_lines(
+    watches: &[String],
+    session: Option<&DebugSession>,
+) -> Vec<TextPropertyEntry> {
+    if watches.is_empty() {
+        return vec![TextPropertyEntry::text(
+            "(no watch expressions - press 'a' to add one)\n",
+        )];
+    }
+    watches
+        .iter()
+        .enumerate()
+        .map(|(index, expression)| {
+            let value = match session.and_then(|s| s.watch_values.get(expression)) {
+                Some(Ok(value)) => value.clone(),
+                Some(Err(error)) => format!("<{}>", error),
+                None => "<not available>".to_string(),
+            };
+            TextPropertyEntry::text(format!("{} = {}\n", expression, value))
+                .with_property("watch", json!(index))
+        })
+        .collect()
+}
+
+/// Debug console content
+pub fn render_console_lines(lines: &[ConsoleLine]) -> Vec<TextPropertyEntry> {
+    lines
+        .iter()
+        .map(|line| {
+            let prefix = match line.category.as_str() {
+                "input" => "> ",
+                "stderr" | "error" => "! ",
+                _ => "",
+            };
+            TextPropertyEntry::text(format!("{}{}\n", prefix, line.text))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::text_property::TextPropertyManager;
+
+    fn text_of(entries: Vec<TextPropertyEntry>) -> String {
+        TextPropertyManager::from_entries(entries).0
+    }
+
+    fn stopped_session() -> DebugSession {
+        let mut session = DebugSession::new("python".to_string());
+        session.on_stopped(StoppedEventBody {
+            reason: "breakpoint".to_string(),
+            description: None,
+            thread_id: Some(1),
+            text: None,
+        });
+        session.set_stack_trace(&json!({
+            "stackFrames": [
+                {"id": 10, "name": "inner", "line": 3, "column": 1,
+                 "source": {"name": "a.py", "path": "/p/a.py"}},
+                {"id": 11, "name": "main", "line": 9, "column": 1,
+                 "source": {"name": "a.py", "path": "/p/a.py"}}
+            ]
+        }));
+        session
+    }
+
+    #[test]
+    fn test_pending_requests_are_taken_once() {
+        let mut session = DebugSession::new("rust".to_string());
+        session.track(3, PendingRequest::StackTrace);
+        assert_eq!(session.take_pending(3), Some(PendingRequest::StackTrace));
+        assert_eq!(session.take_pending(3), None);
+    }
+
+    #[test]
+    fn test_stop_and_continue() {
+        let mut session = stopped_session();
+        assert!(session.is_stopped());
+        assert_eq!(session.thread_id(), Some(1));
+        assert_eq!(session.current_frame().unwrap().name, "inner");
+        assert_eq!(session.status_text(), "Debug: paused (breakpoint) in inner");
+
+        assert!(session.select_frame(1));
+        assert!(!session.select_frame(2));
+        assert_eq!(session.current_frame().unwrap().line, 9);
+
+        session.on_continued();
+        assert_eq!(session.state, DebugState::Running);
+        assert!(session.stack_frames.is_empty());
+        // The last stopped thread is remembered so `pause` can target it
+        assert_eq!(session.thread_id(), Some(1));
+    }
+
+    #[test]
+    fn test_scopes_skip_expensive_ones() {
+        let mut session = stopped_session();
+        let fetch = session.set_scopes(&json!({
+            "scopes": [
+                {"name": "Locals", "variablesReference": 5, "expensive": false},
+                {"name": "Globals", "variablesReference": 6, "expensive": true}
+            ]
+        }));
+        assert_eq!(fetch, vec![5]);
+    }
+
+    #[test]
+    fn test_render_variables_tree() {
+        let mut session = stopped_session();
+        session.set_scopes(&json!({
+            "scopes": [{"name": "Locals", "variablesReference": 5}]
+        }));
+        session.set_variables(
+            5,
+            &json!({"variables": [
+                {"name": "i", "value": "3", "type": "int", "variablesReference": 0},
+                {"name": "items", "value": "[1, 2]", "variablesReference": 7}
+            ]}),
+        );
+        assert_eq!(
+            text_of(session.render_variables()),
+            "▾ Locals\n    i: int = 3\n  ▸ items = [1, 2]\n"
+        );
+
+        assert!(session.toggle_expanded(7));
+        session.set_variables(
+            7,
+            &json!({"variables": [{"name": "0", "value": "1", "variablesReference": 0}]}),
+        );
+        assert_eq!(
+            text_of(session.render_variables()),
+            "▾ Locals\n    i: int = 3\n  ▾ items = [1, 2]\n      0 = 1\n"
+        );
+        assert!(!session.toggle_expanded(7));
+    }
+
+    #[test]
+    fn test_render_call_stack_marks_selected_frame() {
+        let mut session = stopped_session();
+        session.select_frame(1);
+        let entries = session.render_call_stack();
+        assert_eq!(entries[1].properties.get("frame"), Some(&json!(1)));
+        assert_eq!(text_of(entries), "  inner  a.py:3\n▶ main  a.py:9\n");
+    }
+
+    #[test]
+    fn test_watch_rendering() {
+        let mut session = stopped_session();
+        session
+            .watch_values
+            .insert("x".to_string(), Ok("42".to_string()));
+        session
+            .watch_values
+            .insert("y".to_string(), Err("name 'y' is not defined".to_string()));
+        let watches = vec!["x".to_string(), "y".to_string(), "z".to_string()];
+        assert_eq!(
+            text_of(session.render_watches(&watches)),
+            "x = 42\ny = <name 'y' is not defined>\nz = <not available>\n"
+        );
+    }
+
+    #[test]
+    fn test_console_output_is_split_into_lines() {
+        let mut session = DebugSession::new("python".to_string());
+        session.append_output(OutputEventBody {
+            category: Some("stdout".to_string()),
+            output: "hello\r\nworld\n".to_string(),
+        });
+        session.append_output(OutputEventBody {
+            category: Some("telemetry".to_string()),
+            output: "ignored".to_string(),
+        });
+        session.push_console("input", "x + 1");
+        assert_eq!(text_of(session.render_console()), "hello\nworld\n> x + 1\n");
+    }
+}
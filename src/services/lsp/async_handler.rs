@@ -311,6 +311,13 @@ enum LspCommand {
         character: u32,
     },
 
+    /// Request selection ranges (one chain per position)
+    SelectionRange {
+        request_id: u64,
+        uri: Uri,
+        positions: Vec<(u32, u32)>,
+    },
+
     /// Request code actions
     CodeActions {
         request_id: u64,
@@ -1107,6 +1114,84 @@ impl LspState {
         }
     }
 
+    /// Handle selection range request
+    async fn handle_selection_range(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        positions: Vec<(u32, u32)>,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{
+            PartialResultParams, Position, SelectionRangeParams, TextDocumentIdentifier,
+            WorkDoneProgressParams,
+        };
+
+        // Servers without selectionRangeProvider get None, so the editor falls back to tree-sitter
+        if self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.selection_range_provider.as_ref())
+            .is_none()
+        {
+            tracing::trace!(
+                "LSP: server does not support selection ranges for {}",
+                uri.as_str()
+            );
+            let _ = self.async_tx.send(AsyncMessage::LspSelectionRange {
+                request_id,
+                ranges: None,
+            });
+            return Ok(());
+        }
+
+        tracing::trace!(
+            "LSP: selection range request for {} at {} positions",
+            uri.as_str(),
+            positions.len()
+        );
+
+        let params = SelectionRangeParams {
+            text_document: TextDocumentIdentifier { uri },
+            positions: positions
+                .into_iter()
+                .map(|(line, character)| Position { line, character })
+                .collect(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        // Send request and get response
+        match self
+            .send_request_sequential::<_, Value>(
+                "textDocument/selectionRange",
+                Some(params),
+                pending,
+            )
+            .await
+        {
+            Ok(result) => {
+                // Parse the selection range response (Vec<SelectionRange> or null)
+                let ranges =
+                    serde_json::from_value::<Option<Vec<lsp_types::SelectionRange>>>(result)
+                        .unwrap_or_default();
+
+                let _ = self
+                    .async_tx
+                    .send(AsyncMessage::LspSelectionRange { request_id, ranges });
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!("Selection range request failed: {}", e);
+                let _ = self.async_tx.send(AsyncMessage::LspSelectionRange {
+                    request_id,
+                    ranges: None,
+                });
+                Err(e)
+            }
+        }
+    }
+
     /// Handle signature help request
     async fn handle_signature_help(
         &mut self,
@@ -2030,6 +2115,24 @@ impl LspTask {
                                 });
                             }
                         }
+                        LspCommand::SelectionRange {
+                            request_id,
+                            uri,
+                            positions,
+                        } => {
+                            if state.initialized {
+                                tracing::info!("Processing SelectionRange request for {}", uri.as_str());
+                                let _ = state
+                                    .handle_selection_range(request_id, uri, positions, &pending)
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot get selection ranges");
+                                let _ = state.async_tx.send(AsyncMessage::LspSelectionRange {
+                                    request_id,
+                                    ranges: None,
+                                });
+                            }
+                        }
                         LspCommand::CodeActions {
                             request_id,
                             uri,
@@ -3465,6 +3568,22 @@ impl LspHandle {
             .map_err(|_| "Failed to send references command".to_string())
     }
 
+    /// Request selection ranges for a set of positions
+    pub fn selection_range(
+        &self,
+        request_id: u64,
+        uri: Uri,
+        positions: Vec<(u32, u32)>,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::SelectionRange {
+                request_id,
+                uri,
+                positions,
+            })
+            .map_err(|_| "Failed to send selection range command".to_string())
+    }
+
     /// Request signature help
     pub fn signature_help(
         &self,
@@ -638,12 +638,18 @@ mod tests {
                 filenames: vec![],
                 grammar: "rust".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: None,
                 auto_indent: true,
                 highlighter: crate::config::HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: false,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: crate::config::CompletionTrigger::Manual,
+                completion_min_prefix: crate::config::DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![],
             },
         );
@@ -654,12 +660,18 @@ mod tests {
                 filenames: vec![],
                 grammar: "javascript".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: None,
                 auto_indent: true,
                 highlighter: crate::config::HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: false,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: crate::config::CompletionTrigger::Manual,
+                completion_min_prefix: crate::config::DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![],
             },
         );
@@ -670,12 +682,18 @@ mod tests {
                 filenames: vec![],
                 grammar: "c_sharp".to_string(),
                 comment_prefix: Some("//".to_string()),
+                block_comment: None,
                 auto_indent: true,
                 highlighter: crate::config::HighlighterPreference::Auto,
                 textmate_grammar: None,
                 show_whitespace_tabs: false,
                 use_tabs: false,
                 tab_size: None,
+                wrap_column: None,
+                hard_wrap: false,
+                word_completion: true,
+                completion_trigger: crate::config::CompletionTrigger::Manual,
+                completion_min_prefix: crate::config::DEFAULT_COMPLETION_MIN_PREFIX,
                 on_save: vec![],
             },
         );
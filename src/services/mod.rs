@@ -5,15 +5,22 @@
 
 pub mod async_bridge;
 pub mod clipboard;
+pub mod dap;
 pub mod fs;
 #[cfg(target_os = "linux")]
 pub mod gpm;
 pub mod lsp;
+pub mod occur;
 pub mod plugins;
 pub mod process_limits;
+pub mod project_search;
 pub mod recovery;
 pub mod release_checker;
+pub mod replace;
+pub mod search_lines;
 pub mod signal_handler;
+pub mod streaming_search;
+pub mod structural_search;
 pub mod styled_html;
 pub mod terminal;
 pub mod time_source;
@@ -0,0 +1,349 @@
+//! Occur: the lines of a buffer matching a regex
+//!
+//! The buffer is scanned from a [`ContentSnapshot`] one window at a time,
+//! counting newlines as it goes, so the same scan works for large files
+//! whose text was never loaded: it runs on a background thread there and
+//! inline for everything else.
+//!
+//! The occur buffer shows one matching line per line, prefixed with its
+//! line number (`  12: text`). Editing the text after the prefix and saving
+//! writes the line back to the source, so this module also parses an
+//! edited occur buffer back into line edits.
+
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use regex::bytes::Regex;
+
+use crate::model::buffer::ContentSnapshot;
+use crate::services::streaming_search::WINDOW_SIZE;
+
+/// Most matching lines collected by one scan
+pub const MAX_LINES: usize = 10_000;
+
+/// Bytes of a line kept and searched; the rest of a longer line is ignored
+pub const MAX_LINE_LEN: usize = 4096;
+
+/// A line with at least one match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OccurLine {
+    /// Zero-based line number
+    pub line: usize,
+    /// Byte offset of the start of the line in the source
+    pub offset: usize,
+    /// Text of the line, without its line ending
+    pub text: String,
+    /// Byte ranges of the matches within `text`
+    pub matches: Vec<Range<usize>>,
+    /// The line was longer than `MAX_LINE_LEN`, so `text` is only its start
+    pub truncated: bool,
+}
+
+/// Result of a scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OccurScan {
+    pub lines: Vec<OccurLine>,
+    /// The scan stopped at `MAX_LINES`
+    pub limited: bool,
+}
+
+/// A change to one source line, from an edited occur buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OccurEdit {
+    pub line: usize,
+    pub offset: usize,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+/// Handle to a scan running on a background thread
+///
+/// Dropping the handle cancels the scan.
+pub struct OccurJob {
+    receiver: Receiver<io::Result<OccurScan>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl OccurJob {
+    pub fn start(mut snapshot: ContentSnapshot, regex: Regex) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = cancelled.clone();
+        thread::spawn(move || {
+            let result = scan(&mut snapshot, &regex, WINDOW_SIZE, &worker_cancelled);
+            if !worker_cancelled.load(Ordering::Relaxed) {
+                let _ = sender.send(result);
+            }
+        });
+        Self {
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// The scan's result once it has finished, without blocking
+    pub fn poll(&self) -> Option<io::Result<OccurScan>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(io::Error::other("scan stopped"))),
+        }
+    }
+}
+
+impl Drop for OccurJob {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A line being collected by `scan`
+struct PendingLine {
+    line: usize,
+    offset: usize,
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl PendingLine {
+    fn push(&mut self, bytes: &[u8]) {
+        let room = MAX_LINE_LEN - self.bytes.len();
+        if bytes.len() > room {
+            self.truncated = true;
+        }
+        self.bytes
+            .extend_from_slice(&bytes[..bytes.len().min(room)]);
+    }
+
+    fn finish(&mut self, regex: &Regex) -> Option<OccurLine> {
+        if !self.truncated && self.bytes.last() == Some(&b'\r') {
+            self.bytes.pop();
+        }
+        let text = String::from_utf8_lossy(&self.bytes).into_owned();
+        let matches: Vec<Range<usize>> = regex
+            .find_iter(text.as_bytes())
+            .map(|m| m.range())
+            .collect();
+        let found = (!matches.is_empty()).then_some(OccurLine {
+            line: self.line,
+            offset: self.offset,
+            text,
+            matches,
+            truncated: self.truncated,
+        });
+        self.bytes.clear();
+        self.truncated = false;
+        found
+    }
+}
+
+/// Lines of `snapshot` matching `regex`, reading `window_size` bytes at a
+/// time; returns what was found so far if cancelled
+pub fn scan(
+    snapshot: &mut ContentSnapshot,
+    regex: &Regex,
+    window_size: usize,
+    cancelled: &AtomicBool,
+) -> io::Result<OccurScan> {
+    let len = snapshot.len();
+    let mut result = OccurScan::default();
+    let mut pending = PendingLine {
+        line: 0,
+        offset: 0,
+        bytes: Vec::new(),
+        truncated: false,
+    };
+    for start in (0..len).step_by(window_size) {
+        if cancelled.load(Ordering::Relaxed) {
+            return Ok(result);
+        }
+        let bytes = snapshot.read(start..start + window_size)?;
+        let mut rest = &bytes[..];
+        let mut at = start;
+        while let Some(newline) = rest.iter().position(|&b| b == b'\n') {
+            pending.push(&rest[..newline]);
+            if let Some(found) = pending.finish(regex) {
+                result.lines.push(found);
+                if result.lines.len() >= MAX_LINES {
+                    result.limited = true;
+                    return Ok(result);
+                }
+            }
+            at += newline + 1;
+            rest = &rest[newline + 1..];
+            pending.line += 1;
+            pending.offset = at;
+        }
+        pending.push(rest);
+    }
+    if pending.offset < len {
+        result.lines.extend(pending.finish(regex));
+    }
+    Ok(result)
+}
+
+/// Width of the line number column for `lines`
+pub fn number_width(lines: &[OccurLine]) -> usize {
+    lines
+        .last()
+        .map_or(1, |last| (last.line + 1).to_string().len())
+}
+
+/// The `  12: ` prefix of an occur buffer line
+pub fn line_prefix(line: &OccurLine, width: usize) -> String {
+    format!("{:>width$}: ", line.line + 1, width = width)
+}
+
+/// Header line of the occur buffer
+pub fn header(query: &str, source_name: &str, scan: &OccurScan) -> String {
+    let count = scan.lines.len();
+    let mut header = format!(
+        "{} {} matching '{}' in {}",
+        count,
+        if count == 1 { "line" } else { "lines" },
+        query,
+        source_name
+    );
+    if scan.limited {
+        header.push_str(&format!(" (stopped at {})", MAX_LINES));
+    }
+    header.push('\n');
+    header
+}
+
+/// Split an occur buffer line into its line number and text
+fn split_row(row: &str) -> Option<(usize, &str)> {
+    let (number, text) = row.trim_start().split_once(": ")?;
+    Some((number.parse().ok()?, text))
+}
+
+/// The line that the occur buffer line `row` shows, if any
+pub fn line_at<'a>(lines: &'a [OccurLine], row: &str) -> Option<&'a OccurLine> {
+    let (number, _) = split_row(row)?;
+    let index = lines
+        .binary_search_by_key(&number, |line| line.line + 1)
+        .ok()?;
+    Some(&lines[index])
+}
+
+/// Line edits made in an edited occur buffer
+///
+/// Lines are matched to results by their line number prefix; lines whose
+/// prefix was changed or removed, removed lines, and truncated lines are
+/// left alone.
+pub fn parse_edits(lines: &[OccurLine], text: &str) -> Vec<OccurEdit> {
+    let by_number: HashMap<usize, &OccurLine> =
+        lines.iter().map(|line| (line.line + 1, line)).collect();
+    let mut edits: Vec<OccurEdit> = Vec::new();
+    for row in text.lines() {
+        let Some((number, new_text)) = split_row(row) else {
+            continue;
+        };
+        let Some(line) = by_number.get(&number) else {
+            continue;
+        };
+        if line.truncated || new_text == line.text {
+            continue;
+        }
+        if edits.iter().any(|edit| edit.line == line.line) {
+            continue;
+        }
+        edits.push(OccurEdit {
+            line: line.line,
+            offset: line.offset,
+            old_text: line.text.clone(),
+            new_text: new_text.to_string(),
+        });
+    }
+    edits.sort_by_key(|edit| edit.offset);
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::buffer::TextBuffer;
+    use crate::services::streaming_search::build_regex;
+
+    fn run(text: &str, query: &str, window_size: usize) -> OccurScan {
+        let buffer = TextBuffer::from_str_test(text);
+        let regex = build_regex(query, true, false, true).unwrap();
+        scan(
+            &mut buffer.content_snapshot(),
+            &regex,
+            window_size,
+            &AtomicBool::new(false),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scan_is_the_same_for_any_window_size() {
+        let text = "fn main() {\r\n    let x = 1;\r\n}\r\nfn other() {}";
+        let expected = vec![
+            OccurLine {
+                line: 0,
+                offset: 0,
+                text: "fn main() {".to_string(),
+                matches: vec![0..2, 10..11],
+                truncated: false,
+            },
+            OccurLine {
+                line: 3,
+                offset: 32,
+                text: "fn other() {}".to_string(),
+                matches: vec![0..2, 11..12],
+                truncated: false,
+            },
+        ];
+        for window in [1, 2, 5, 64] {
+            let found = run(text, r"fn|\{", window);
+            assert_eq!(found.lines, expected, "window size {}", window);
+            assert!(!found.limited);
+        }
+    }
+
+    #[test]
+    fn test_scan_reports_every_match_on_a_line() {
+        let found = run("a b\nab ab ab\n\nb\n", "ab", 3);
+        assert_eq!(found.lines.len(), 1);
+        assert_eq!(found.lines[0].line, 1);
+        assert_eq!(found.lines[0].matches, vec![0..2, 3..5, 6..8]);
+    }
+
+    #[test]
+    fn test_long_lines_are_truncated() {
+        let text = format!("{}x\nx\n", "-".repeat(MAX_LINE_LEN));
+        let found = run(&text, "x", 1000);
+        assert_eq!(found.lines.len(), 1);
+        assert_eq!(found.lines[0].line, 1);
+        assert_eq!(found.lines[0].offset, MAX_LINE_LEN + 2);
+    }
+
+    #[test]
+    fn test_parse_edits() {
+        let found = run("one\ntwo\nthree\ntwo again\n", "two", 64);
+        let width = number_width(&found.lines);
+        assert_eq!(line_prefix(&found.lines[0], width), "2: ");
+        assert_eq!(line_at(&found.lines, "4: anything"), Some(&found.lines[1]));
+        assert_eq!(line_at(&found.lines, "3: three"), None);
+        let text = format!(
+            "{}2: TWO\n4: two again\n9: two\nfree text\n",
+            header("two", "a.txt", &found)
+        );
+        assert_eq!(
+            parse_edits(&found.lines, &text),
+            vec![OccurEdit {
+                line: 1,
+                offset: 4,
+                old_text: "two".to_string(),
+                new_text: "TWO".to_string(),
+            }]
+        );
+    }
+}
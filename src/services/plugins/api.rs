@@ -5,6 +5,7 @@
 
 use crate::input::command_registry::CommandRegistry;
 use crate::input::commands::Command;
+use crate::input::keybindings::Action;
 use crate::model::event::{BufferId, SplitId};
 use crate::services::plugins::hooks::{HookCallback, HookRegistry};
 use crate::view::overlay::{OverlayHandle, OverlayNamespace};
@@ -620,6 +621,9 @@ pub enum PluginCommand {
         /// Whether the context is active
         active: bool,
     },
+
+    /// Run a sequence of editor actions, as when playing a macro
+    ExecuteActions { actions: Vec<Action> },
 }
 
 /// Plugin API context - provides safe access to editor functionality
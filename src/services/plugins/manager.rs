@@ -90,6 +90,22 @@ impl PluginManager {
         }
     }
 
+    /// Load a single plugin file.
+    pub fn load_plugin(&self, path: &Path) -> Result<(), String> {
+        #[cfg(feature = "plugins")]
+        {
+            if let Some(ref manager) = self.inner {
+                return manager.load_plugin(path).map_err(|e| e.to_string());
+            }
+            Ok(())
+        }
+        #[cfg(not(feature = "plugins"))]
+        {
+            let _ = path;
+            Ok(())
+        }
+    }
+
     /// Run a hook (fire-and-forget).
     pub fn run_hook(&self, hook_name: &str, args: super::hooks::HookArgs) {
         #[cfg(feature = "plugins")]
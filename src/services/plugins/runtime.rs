@@ -1049,6 +1049,29 @@ fn op_fresh_set_context(state: &mut OpState, #[string] name: String, active: boo
     false
 }
 
+/// Run a sequence of editor actions, as recorded in a macro
+/// Actions use the JSON form shown by "Edit Macro" (e.g. ["MoveDown", {"InsertChar": "x"}]).
+/// @param actions - JSON array of actions
+/// @returns true if the actions were parsed and queued
+#[op2(fast)]
+fn op_fresh_execute_actions(state: &mut OpState, #[string] actions: String) -> bool {
+    let actions: Vec<crate::input::keybindings::Action> = match serde_json::from_str(&actions) {
+        Ok(actions) => actions,
+        Err(e) => {
+            tracing::warn!("executeActions: invalid actions: {}", e);
+            return false;
+        }
+    };
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::ExecuteActions { actions });
+        return result.is_ok();
+    }
+    false
+}
+
 /// Open a file in the editor, optionally at a specific location
 /// @param path - File path to open
 /// @param line - Line number to jump to (0 for no jump)
@@ -2998,6 +3021,7 @@ extension!(
         op_fresh_register_command,
         op_fresh_unregister_command,
         op_fresh_set_context,
+        op_fresh_execute_actions,
         op_fresh_open_file,
         op_fresh_get_active_split_id,
         op_fresh_open_file_in_split,
@@ -3281,6 +3305,11 @@ impl TypeScriptRuntime {
                         return core.ops.op_fresh_set_context(name, active);
                     },
 
+                    // Action playback
+                    executeActions(actions) {
+                        return core.ops.op_fresh_execute_actions(actions);
+                    },
+
                     // File operations
                     openFile(path, line = 0, column = 0) {
                         return core.ops.op_fresh_open_file(path, line, column);
@@ -0,0 +1,477 @@
+//! Project-wide search and replace
+//!
+//! Files under the project root are walked in parallel with the `ignore`
+//! crate, honouring `.gitignore` and the file explorer's custom ignore
+//! patterns. Open buffers with unsaved changes are searched as they are in
+//! the editor rather than as they are on disk.
+//!
+//! Results are shown as one line per matching line, prefixed with its file
+//! and line number (`src/main.rs:12: text`). Editing the text after the
+//! prefix and saving writes the line back to its file, so this module also
+//! parses an edited results buffer back into line edits.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use ignore::overrides::OverrideBuilder;
+use ignore::{WalkBuilder, WalkState};
+use regex::bytes::{NoExpand, Regex};
+
+use crate::model::buffer::TextBuffer;
+
+/// Most matching lines collected by one search
+pub const MAX_RESULT_LINES: usize = 10_000;
+
+/// Files larger than this are skipped
+const MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The files of a project
+pub struct ProjectFiles {
+    pub root: PathBuf,
+    pub respect_gitignore: bool,
+    /// Glob patterns of files to leave out, as in the file explorer
+    pub ignore_patterns: Vec<String>,
+    /// Text of open buffers with unsaved changes, searched instead of the
+    /// files on disk
+    pub unsaved: HashMap<PathBuf, String>,
+}
+
+/// What to search, and where
+pub struct ProjectSearch {
+    pub files: ProjectFiles,
+    pub regex: Regex,
+}
+
+/// A line with at least one match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultLine {
+    pub path: PathBuf,
+    /// Zero-based line number
+    pub line: usize,
+    /// Text of the line, without its line ending
+    pub text: String,
+    /// Byte ranges of the matches within `text`
+    pub matches: Vec<std::ops::Range<usize>>,
+}
+
+/// A change to one line of a file, from an edited results buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineEdit {
+    pub path: PathBuf,
+    pub line: usize,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+/// Matching lines of `text`
+fn search_text(path: &Path, text: &str, regex: &Regex) -> Vec<ResultLine> {
+    let mut results = Vec::new();
+    for (line, line_text) in text.lines().enumerate() {
+        let matches: Vec<_> = regex
+            .find_iter(line_text.as_bytes())
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect();
+        if !matches.is_empty() {
+            results.push(ResultLine {
+                path: path.to_path_buf(),
+                line,
+                text: line_text.to_string(),
+                matches,
+            });
+        }
+    }
+    results
+}
+
+/// Text of a file to search, or None for binary, huge or unreadable files
+fn read_text_file(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() > MAX_FILE_SIZE {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if TextBuffer::detect_binary(&bytes[..bytes.len().min(8192)]) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+impl ProjectFiles {
+    /// Call `visit` with the path and text of every text file under the
+    /// root, on as many threads as there are cores
+    ///
+    /// `visit` returns what it found in a file, if anything, and how many
+    /// results that counts as. Stops early if `cancelled` is set or `limit`
+    /// results were found. What was found is returned in no particular
+    /// order.
+    pub fn visit<T: Send>(
+        &self,
+        cancelled: &AtomicBool,
+        limit: usize,
+        visit: impl Fn(&Path, &str) -> Option<(T, usize)> + Sync,
+    ) -> Vec<T> {
+        let mut overrides = OverrideBuilder::new(&self.root);
+        for pattern in &self.ignore_patterns {
+            if let Err(e) = overrides.add(&format!("!{}", pattern)) {
+                tracing::warn!("Invalid ignore pattern {:?}: {}", pattern, e);
+            }
+        }
+        let mut walker = WalkBuilder::new(&self.root);
+        walker
+            .git_ignore(self.respect_gitignore)
+            .git_exclude(self.respect_gitignore)
+            .git_global(self.respect_gitignore)
+            .ignore(self.respect_gitignore)
+            .require_git(false);
+        if let Ok(overrides) = overrides.build() {
+            walker.overrides(overrides);
+        }
+
+        let found: Mutex<(Vec<T>, usize)> = Mutex::new((Vec::new(), 0));
+        let found_ref = &found;
+        let full = AtomicBool::new(false);
+        let full_ref = &full;
+        let visit_ref = &visit;
+        walker.build_parallel().run(|| {
+            Box::new(move |entry| {
+                if cancelled.load(Ordering::Relaxed) || full_ref.load(Ordering::Relaxed) {
+                    return WalkState::Quit;
+                }
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                    return WalkState::Continue;
+                }
+                let path = entry.path();
+                let visited = match self.unsaved.get(path) {
+                    Some(text) => visit_ref(path, text),
+                    None => match read_text_file(path) {
+                        Some(text) => visit_ref(path, &text),
+                        None => return WalkState::Continue,
+                    },
+                };
+                let Some((item, count)) = visited else {
+                    return WalkState::Continue;
+                };
+                let mut found = found_ref.lock().unwrap();
+                found.0.push(item);
+                found.1 += count;
+                if found.1 >= limit {
+                    full_ref.store(true, Ordering::Relaxed);
+                    return WalkState::Quit;
+                }
+                WalkState::Continue
+            })
+        });
+
+        let (mut found, _) = found.into_inner().unwrap();
+        // Unsaved buffers of files not yet on disk, which the walk can't find
+        for (path, text) in &self.unsaved {
+            if path.starts_with(&self.root) && !path.exists() {
+                if let Some((item, _)) = visit(path, text) {
+                    found.push(item);
+                }
+            }
+        }
+        found
+    }
+}
+
+impl ProjectSearch {
+    /// Search every file of the project
+    ///
+    /// Results are sorted by path and line. Stops early if `cancelled` is
+    /// set or `MAX_RESULT_LINES` lines match.
+    pub fn run(&self, cancelled: &AtomicBool) -> Vec<ResultLine> {
+        let found = self.files.visit(cancelled, MAX_RESULT_LINES, |path, text| {
+            let results = search_text(path, text, &self.regex);
+            let count = results.len();
+            (count > 0).then_some((results, count))
+        });
+        let mut results: Vec<ResultLine> = found.into_iter().flatten().collect();
+        results.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        results.truncate(MAX_RESULT_LINES);
+        results
+    }
+}
+
+/// `text` with every match of `regex` replaced, and the number replaced
+///
+/// With `expand`, `$1`-style capture group references in `replacement`
+/// are expanded.
+pub fn replace_all(text: &str, regex: &Regex, replacement: &str, expand: bool) -> (String, usize) {
+    let count = regex
+        .find_iter(text.as_bytes())
+        .filter(|m| !m.is_empty())
+        .count();
+    if count == 0 {
+        return (text.to_string(), 0);
+    }
+    let replaced = if expand {
+        regex.replace_all(text.as_bytes(), replacement.as_bytes())
+    } else {
+        regex.replace_all(text.as_bytes(), NoExpand(replacement.as_bytes()))
+    };
+    (String::from_utf8_lossy(&replaced).into_owned(), count)
+}
+
+/// Prefix of a result line in the results buffer
+fn result_prefix(root: &Path, result: &ResultLine) -> String {
+    let path = result.path.strip_prefix(root).unwrap_or(&result.path);
+    format!("{}:{}: ", path.display(), result.line + 1)
+}
+
+/// How many matches and files `results` cover, as "N matches in M files"
+pub fn summary(results: &[ResultLine]) -> String {
+    let files = results
+        .iter()
+        .map(|result| &result.path)
+        .collect::<HashSet<_>>()
+        .len();
+    let matches: usize = results.iter().map(|result| result.matches.len()).sum();
+    format!(
+        "{} match{} in {} file{}",
+        matches,
+        if matches == 1 { "" } else { "es" },
+        files,
+        if files == 1 { "" } else { "s" }
+    )
+}
+
+/// Text of the results buffer for `results` of searching for `query`
+pub fn format_results(root: &Path, query: &str, results: &[ResultLine]) -> String {
+    let mut text = format!(
+        "Search for '{}': {}. Edit lines and save to write them back.\n\n",
+        query,
+        summary(results)
+    );
+    let mut last_path = None;
+    for result in results {
+        if last_path.is_some_and(|path| path != &result.path) {
+            text.push('\n');
+        }
+        last_path = Some(&result.path);
+        text.push_str(&result_prefix(root, result));
+        text.push_str(&result.text);
+        text.push('\n');
+    }
+    text
+}
+
+/// The result that the results buffer line `line` shows, if any
+pub fn result_at_line<'a>(
+    root: &Path,
+    results: &'a [ResultLine],
+    line: &str,
+) -> Option<&'a ResultLine> {
+    results
+        .iter()
+        .find(|result| line.starts_with(&result_prefix(root, result)))
+}
+
+/// Line edits made in an edited results buffer
+///
+/// Lines are matched to results by their `path:line: ` prefix; lines whose
+/// prefix was changed or removed, and removed lines, are left alone.
+pub fn parse_edits(root: &Path, results: &[ResultLine], text: &str) -> Vec<LineEdit> {
+    let by_prefix: HashMap<String, &ResultLine> = results
+        .iter()
+        .map(|result| (result_prefix(root, result), result))
+        .collect();
+    let mut edits = BTreeMap::new();
+    for line in text.lines() {
+        let Some((prefix_len, result)) = line
+            .match_indices(": ")
+            .map(|(at, _)| at + 2)
+            .find_map(|len| Some((len, *by_prefix.get(&line[..len])?)))
+        else {
+            continue;
+        };
+        let new_text = &line[prefix_len..];
+        if new_text != result.text {
+            edits.insert(
+                (result.path.clone(), result.line),
+                LineEdit {
+                    path: result.path.clone(),
+                    line: result.line,
+                    old_text: result.text.clone(),
+                    new_text: new_text.to_string(),
+                },
+            );
+        }
+    }
+    edits.into_values().collect()
+}
+
+/// `text` with the line edits applied, or None if any edited line no
+/// longer has its old text
+pub fn apply_line_edits(text: &str, edits: &[&LineEdit]) -> Option<String> {
+    let mut lines: Vec<String> = text.split_inclusive('\n').map(String::from).collect();
+    for edit in edits {
+        let line = lines.get_mut(edit.line)?;
+        let content_len = line.trim_end_matches(['\n', '\r']).len();
+        if line[..content_len] != edit.old_text {
+            return None;
+        }
+        line.replace_range(..content_len, &edit.new_text);
+    }
+    Some(lines.concat())
+}
+
+/// The part of `old` that differs from `new`, and the text replacing it
+///
+/// Lets a whole-text change be applied to a buffer as one small edit, so
+/// cursors and markers outside the changed region stay where they are.
+pub fn changed_span<'a>(old: &str, new: &'a str) -> (std::ops::Range<usize>, &'a str) {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(old.len().min(new.len()) - prefix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    (prefix..old.len() - suffix, &new[prefix..new.len() - suffix])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::streaming_search::build_regex;
+    use tempfile::TempDir;
+
+    fn project(files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn search(dir: &TempDir, query: &str, unsaved: HashMap<PathBuf, String>) -> ProjectSearch {
+        ProjectSearch {
+            files: ProjectFiles {
+                root: dir.path().to_path_buf(),
+                respect_gitignore: true,
+                ignore_patterns: vec!["*.log".to_string()],
+                unsaved,
+            },
+            regex: build_regex(query, true, false, false).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_search_honours_ignores_and_unsaved_buffers() {
+        let dir = project(&[
+            (".gitignore", "target/\n"),
+            ("src/main.rs", "fn main() {\n    foo();\n}\n"),
+            ("src/lib.rs", "pub fn bar() {}\n"),
+            ("target/out.rs", "foo\n"),
+            ("debug.log", "foo\n"),
+        ]);
+        let lib = dir.path().join("src/lib.rs");
+        let unsaved = HashMap::from([(lib.clone(), "pub fn foo() {}\nfoo foo\n".to_string())]);
+        let results = search(&dir, "foo", unsaved).run(&AtomicBool::new(false));
+
+        let found: Vec<(PathBuf, usize, usize)> = results
+            .iter()
+            .map(|r| (r.path.clone(), r.line, r.matches.len()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (lib.clone(), 0, 1),
+                (lib, 1, 2),
+                (dir.path().join("src/main.rs"), 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_results_round_trip_through_edits() {
+        let root = Path::new("/project");
+        let results = vec![
+            ResultLine {
+                path: root.join("a.rs"),
+                line: 0,
+                text: "let foo = foo;".to_string(),
+                matches: vec![4..7, 10..13],
+            },
+            ResultLine {
+                path: root.join("b.rs"),
+                line: 9,
+                text: "foo(foo)".to_string(),
+                matches: vec![0..3, 4..7],
+            },
+        ];
+        let text = format_results(root, "foo", &results);
+        assert!(text.starts_with("Search for 'foo': 4 matches in 2 files."));
+        assert!(text.contains("a.rs:1: let foo = foo;\n\nb.rs:10: foo(foo)\n"));
+        assert!(parse_edits(root, &results, &text).is_empty());
+        assert_eq!(
+            result_at_line(root, &results, "b.rs:10: foo(foo)"),
+            Some(&results[1])
+        );
+
+        let edited = text.replace("let foo = foo;", "let foo = 2;");
+        let edited = edited.replace("b.rs:10: foo(foo)", "b.rs:10: baz(): bar");
+        let edits = parse_edits(root, &results, &edited);
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].new_text, "let foo = 2;");
+        assert_eq!(edits[1].path, root.join("b.rs"));
+        assert_eq!(edits[1].new_text, "baz(): bar");
+    }
+
+    #[test]
+    fn test_apply_line_edits_checks_old_text() {
+        let edit = LineEdit {
+            path: PathBuf::from("a"),
+            line: 1,
+            old_text: "two".to_string(),
+            new_text: "2".to_string(),
+        };
+        assert_eq!(
+            apply_line_edits("one\r\ntwo\r\nthree", &[&edit]),
+            Some("one\r\n2\r\nthree".to_string())
+        );
+        assert_eq!(apply_line_edits("one\nTWO\n", &[&edit]), None);
+    }
+
+    #[test]
+    fn test_replace_all_expands_only_regex_replacements() {
+        let regex = build_regex(r"(\w+)@", true, false, true).unwrap();
+        assert_eq!(
+            replace_all("a@ b@", &regex, "<$1>", true),
+            ("<a> <b>".to_string(), 2)
+        );
+        let literal = build_regex("x", true, false, false).unwrap();
+        assert_eq!(
+            replace_all("x y x", &literal, "$1", false),
+            ("$1 y $1".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn test_changed_span() {
+        assert_eq!(changed_span("a foo b", "a bar b"), (2..5, "bar"));
+        assert_eq!(changed_span("aaa", "aaaa"), (3..3, "a"));
+        assert_eq!(changed_span("same", "same"), (4..4, ""));
+        assert_eq!(changed_span("xéy", "xèy"), (1..3, "è"));
+    }
+}
@@ -0,0 +1,459 @@
+//! Replacement text for search matches
+//!
+//! With regex search on, the replacement is a template: `$1`, `${name}`
+//! and `$&` insert capture groups, `\n` and `\t` insert a newline and a
+//! tab, and `\U`, `\L` and `\E` start upper-casing, start lower-casing and
+//! stop changing the case of what follows, while `\u` and `\l` change just
+//! the next character. Otherwise the replacement is inserted as typed.
+//!
+//! With Preserve Case on, the replacement also takes the case of the text
+//! it replaces, so replacing `foo` with `bar` turns `Foo` into `Bar` and
+//! `FOO` into `BAR`.
+
+use std::io;
+use std::ops::Range;
+
+use regex::bytes::{Captures, Regex};
+
+use crate::services::streaming_search::{LOOK_BEHIND, MATCH_OVERLAP, WINDOW_SIZE};
+
+/// A match and the text that replaces it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    /// Byte range of the match
+    pub range: Range<usize>,
+    /// The matched text
+    pub matched: String,
+    /// Text to put in its place
+    pub text: String,
+}
+
+/// Case change requested by a template escape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Group(usize),
+    NamedGroup(String),
+    /// `\U` / `\L`: change the case of everything up to `\E`
+    CaseFrom(Case),
+    /// `\u` / `\l`: change the case of the next character
+    CaseNext(Case),
+    /// `\E`
+    CaseEnd,
+}
+
+/// Finds matches of a search and works out their replacements
+#[derive(Debug, Clone)]
+pub struct Replacer {
+    regex: Regex,
+    template: Vec<Piece>,
+    preserve_case: bool,
+}
+
+impl Replacer {
+    /// `replacement` is a template if `use_regex` is set, and plain text
+    /// otherwise
+    pub fn new(regex: Regex, replacement: &str, use_regex: bool, preserve_case: bool) -> Self {
+        let template = if use_regex {
+            parse_template(replacement)
+        } else {
+            vec![Piece::Text(replacement.to_string())]
+        };
+        Self {
+            regex,
+            template,
+            preserve_case,
+        }
+    }
+
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    /// The first match in `haystack` starting at or after `start`
+    pub fn find_at(&self, haystack: &[u8], start: usize) -> Option<Replacement> {
+        self.regex
+            .captures_at(haystack, start)
+            .map(|caps| self.replacement(&caps))
+    }
+
+    /// Every match in `haystack`, in order
+    pub fn find_all(&self, haystack: &[u8]) -> Vec<Replacement> {
+        self.regex
+            .captures_iter(haystack)
+            .map(|caps| self.replacement(&caps))
+            .collect()
+    }
+
+    /// The first match starting at or after `from` and before `until`, in
+    /// a text of `len` bytes read a window at a time through `read`
+    ///
+    /// With `skip_empty`, an empty match at `from` itself is passed over,
+    /// as it is when searching on from the end of the previous match.
+    pub fn find_next<R>(
+        &self,
+        from: usize,
+        until: usize,
+        len: usize,
+        skip_empty: bool,
+        mut read: R,
+    ) -> io::Result<Option<Replacement>>
+    where
+        R: FnMut(Range<usize>) -> io::Result<Vec<u8>>,
+    {
+        let mut window_start = from;
+        while window_start <= len && window_start < until {
+            let window_end = window_start + WINDOW_SIZE;
+            let base = window_start.saturating_sub(LOOK_BEHIND);
+            let bytes = read(base..(window_end + MATCH_OVERLAP).min(len))?;
+            let mut at = window_start - base;
+            while at <= bytes.len() {
+                let Some(found) = self.find_at(&bytes, at) else {
+                    break;
+                };
+                let start = base + found.range.start;
+                if start >= until {
+                    return Ok(None);
+                }
+                if start >= window_end {
+                    break;
+                }
+                if skip_empty && found.range.is_empty() && start == from {
+                    at = found.range.end + 1;
+                    continue;
+                }
+                return Ok(Some(Replacement {
+                    range: start..base + found.range.end,
+                    ..found
+                }));
+            }
+            window_start = window_end;
+        }
+        Ok(None)
+    }
+
+    /// The match starting exactly at `start` in `haystack`, if any
+    pub fn replacement_at(&self, haystack: &[u8], start: usize) -> Option<Replacement> {
+        self.find_at(haystack, start)
+            .filter(|found| found.range.start == start)
+    }
+
+    fn replacement(&self, caps: &Captures) -> Replacement {
+        let whole = caps.get(0).expect("group 0 is the whole match");
+        let matched = String::from_utf8_lossy(whole.as_bytes()).into_owned();
+        let text = self.expand(caps);
+        let text = if self.preserve_case {
+            preserve_case(&matched, &text)
+        } else {
+            text
+        };
+        Replacement {
+            range: whole.range(),
+            matched,
+            text,
+        }
+    }
+
+    fn expand(&self, caps: &Captures) -> String {
+        let mut out = String::new();
+        let mut case = None;
+        let mut next = None;
+        for piece in &self.template {
+            let group = match piece {
+                Piece::Text(text) => {
+                    push_cased(&mut out, text, case, &mut next);
+                    continue;
+                }
+                Piece::Group(index) => caps.get(*index),
+                Piece::NamedGroup(name) => caps.name(name),
+                Piece::CaseFrom(c) => {
+                    case = Some(*c);
+                    continue;
+                }
+                Piece::CaseNext(c) => {
+                    next = Some(*c);
+                    continue;
+                }
+                Piece::CaseEnd => {
+                    case = None;
+                    continue;
+                }
+            };
+            if let Some(group) = group {
+                let text = String::from_utf8_lossy(group.as_bytes());
+                push_cased(&mut out, &text, case, &mut next);
+            }
+        }
+        out
+    }
+}
+
+fn push_cased(out: &mut String, text: &str, case: Option<Case>, next: &mut Option<Case>) {
+    for ch in text.chars() {
+        match next.take().or(case) {
+            Some(Case::Upper) => out.extend(ch.to_uppercase()),
+            Some(Case::Lower) => out.extend(ch.to_lowercase()),
+            None => out.push(ch),
+        }
+    }
+}
+
+fn push_text(pieces: &mut Vec<Piece>, text: &str) {
+    if let Some(Piece::Text(last)) = pieces.last_mut() {
+        last.push_str(text);
+    } else {
+        pieces.push(Piece::Text(text.to_string()));
+    }
+}
+
+/// Parse a regex replacement template
+///
+/// Escapes and `$` references that don't mean anything are kept as typed.
+fn parse_template(template: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(ch) = rest.chars().next() {
+        rest = &rest[ch.len_utf8()..];
+        match ch {
+            '\\' => {
+                let Some(escaped) = rest.chars().next() else {
+                    push_text(&mut pieces, "\\");
+                    break;
+                };
+                rest = &rest[escaped.len_utf8()..];
+                match escaped {
+                    'U' => pieces.push(Piece::CaseFrom(Case::Upper)),
+                    'L' => pieces.push(Piece::CaseFrom(Case::Lower)),
+                    'E' => pieces.push(Piece::CaseEnd),
+                    'u' => pieces.push(Piece::CaseNext(Case::Upper)),
+                    'l' => pieces.push(Piece::CaseNext(Case::Lower)),
+                    'n' => push_text(&mut pieces, "\n"),
+                    't' => push_text(&mut pieces, "\t"),
+                    '\\' => push_text(&mut pieces, "\\"),
+                    other => {
+                        push_text(&mut pieces, "\\");
+                        push_text(&mut pieces, other.encode_utf8(&mut [0; 4]));
+                    }
+                }
+            }
+            '$' => {
+                if let Some(after) = rest.strip_prefix('$') {
+                    push_text(&mut pieces, "$");
+                    rest = after;
+                } else if let Some(after) = rest.strip_prefix('&') {
+                    pieces.push(Piece::Group(0));
+                    rest = after;
+                } else if let Some((name, after)) = rest
+                    .strip_prefix('{')
+                    .and_then(|braced| braced.split_once('}'))
+                {
+                    pieces.push(group_piece(name));
+                    rest = after;
+                } else {
+                    let name_len = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    if name_len == 0 {
+                        push_text(&mut pieces, "$");
+                    } else {
+                        pieces.push(group_piece(&rest[..name_len]));
+                        rest = &rest[name_len..];
+                    }
+                }
+            }
+            _ => push_text(&mut pieces, ch.encode_utf8(&mut [0; 4])),
+        }
+    }
+    pieces
+}
+
+fn group_piece(name: &str) -> Piece {
+    match name.parse() {
+        Ok(index) => Piece::Group(index),
+        Err(_) => Piece::NamedGroup(name.to_string()),
+    }
+}
+
+/// `replacement` in the case of `matched`: all upper-case, capitalized or
+/// all lower-case, or as it is if `matched` is none of those
+pub fn preserve_case(matched: &str, replacement: &str) -> String {
+    let mut letters = matched.chars().filter(|c| c.is_alphabetic());
+    let Some(first) = letters.next() else {
+        return replacement.to_string();
+    };
+    let rest: Vec<char> = letters.collect();
+    if first.is_uppercase() && !rest.is_empty() && rest.iter().all(|c| c.is_uppercase()) {
+        replacement.to_uppercase()
+    } else if first.is_uppercase() {
+        let mut chars = replacement.chars();
+        match chars.next() {
+            Some(head) => head.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    } else if first.is_lowercase() && rest.iter().all(|c| c.is_lowercase()) {
+        replacement.to_lowercase()
+    } else {
+        replacement.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::streaming_search::build_regex;
+
+    fn replace_all(
+        text: &str,
+        query: &str,
+        replacement: &str,
+        use_regex: bool,
+        preserve: bool,
+    ) -> String {
+        let regex = build_regex(query, !preserve, false, use_regex).unwrap();
+        let replacer = Replacer::new(regex, replacement, use_regex, preserve);
+        let mut out = text.to_string();
+        for found in replacer.find_all(text.as_bytes()).into_iter().rev() {
+            assert_eq!(found.matched, text[found.range.clone()]);
+            out.replace_range(found.range, &found.text);
+        }
+        out
+    }
+
+    #[test]
+    fn test_group_references() {
+        let text = "let a = 1; let bc = 22;";
+        assert_eq!(
+            replace_all(
+                text,
+                r"let (\w+) = (?P<value>\d+)",
+                "$1: ${value} ($&)",
+                true,
+                false
+            ),
+            "a: 1 (let a = 1); bc: 22 (let bc = 22);"
+        );
+        assert_eq!(
+            replace_all("ab", "(a)(b)", "${2}x$1$$", true, false),
+            "bxa$"
+        );
+        // Unknown groups are empty; stray `$` and escapes are kept
+        assert_eq!(replace_all("ab", "a", r"$9$ \q\", true, false), r"$ \q\b");
+    }
+
+    #[test]
+    fn test_case_conversion() {
+        let text = "hello_world";
+        assert_eq!(
+            replace_all(text, r"(\w+)_(\w+)", r"\U$1\E_$2", true, false),
+            "HELLO_world"
+        );
+        assert_eq!(
+            replace_all(text, r"(\w+)_(\w+)", r"\u$1\u$2", true, false),
+            "HelloWorld"
+        );
+        assert_eq!(
+            replace_all("Foo BAR", r"(\w+) (\w+)", r"\L$1\E \l$2", true, false),
+            "foo bAR"
+        );
+        // `\u` inside `\L` still upper-cases the next character
+        assert_eq!(replace_all("ABC", "(ABC)", r"\L\u$1", true, false), "Abc");
+    }
+
+    #[test]
+    fn test_literal_replacement_is_not_a_template() {
+        assert_eq!(replace_all("a.b", ".", r"$0\U", false, false), r"a$0\Ub");
+    }
+
+    #[test]
+    fn test_escapes_and_multi_line_matches() {
+        assert_eq!(
+            replace_all("a, b\nc", r", ", r"\n\t", true, false),
+            "a\n\tb\nc"
+        );
+        assert_eq!(
+            replace_all(
+                "fn a(\n    x,\n) {}",
+                r"\(\n\s*(\w+),\n\)",
+                "($1)",
+                true,
+                false
+            ),
+            "fn a(x) {}"
+        );
+        assert_eq!(
+            replace_all("a\r\nb\n", "$", ";", true, false),
+            "a;\r\nb;\n;"
+        );
+    }
+
+    #[test]
+    fn test_preserve_case() {
+        assert_eq!(preserve_case("foo", "bar"), "bar");
+        assert_eq!(preserve_case("Foo", "bar"), "Bar");
+        assert_eq!(preserve_case("FOO", "bar"), "BAR");
+        assert_eq!(preserve_case("fOo", "bar"), "bar");
+        assert_eq!(preserve_case("foo", "myBar"), "mybar");
+        assert_eq!(preserve_case("F", "bar"), "Bar");
+        assert_eq!(preserve_case("123", "bar"), "bar");
+        assert_eq!(
+            replace_all("foo Foo FOO", "foo", "bar", false, true),
+            "bar Bar BAR"
+        );
+    }
+
+    #[test]
+    fn test_find_next_reads_windows() {
+        let text = format!("{}ab{}ab", "-".repeat(WINDOW_SIZE - 1), "-".repeat(10));
+        let regex = build_regex("ab", true, false, false).unwrap();
+        let replacer = Replacer::new(regex, "x", false, false);
+        let reads = std::cell::Cell::new(0);
+        let mut read = |range: Range<usize>| {
+            reads.set(reads.get() + 1);
+            Ok(text.as_bytes()[range].to_vec())
+        };
+        let len = text.len();
+        let first = replacer
+            .find_next(0, len + 1, len, false, &mut read)
+            .unwrap();
+        assert_eq!(
+            first.map(|f| f.range),
+            Some(WINDOW_SIZE - 1..WINDOW_SIZE + 1)
+        );
+        let second = replacer
+            .find_next(WINDOW_SIZE + 1, len + 1, len, false, &mut read)
+            .unwrap();
+        assert_eq!(second.map(|f| f.range), Some(len - 2..len));
+        assert!(replacer
+            .find_next(len - 1, len + 1, len, false, &mut read)
+            .unwrap()
+            .is_none());
+        assert!(replacer
+            .find_next(0, WINDOW_SIZE - 1, len, false, &mut read)
+            .unwrap()
+            .is_none());
+        assert_eq!(reads.get(), 4);
+    }
+
+    #[test]
+    fn test_find_next_skips_empty_match_at_start() {
+        let regex = build_regex("^", true, false, true).unwrap();
+        let replacer = Replacer::new(regex, "> ", true, false);
+        let text = b"a\nb";
+        let read = |range: Range<usize>| Ok(text[range].to_vec());
+        let at = |from, skip| {
+            replacer
+                .find_next(from, 4, 3, skip, read)
+                .unwrap()
+                .map(|f| f.range)
+        };
+        assert_eq!(at(0, false), Some(0..0));
+        assert_eq!(at(0, true), Some(2..2));
+        assert_eq!(at(2, true), None);
+    }
+}
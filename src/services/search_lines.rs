@@ -0,0 +1,241 @@
+//! Search Lines: fuzzy-find a line of a buffer
+//!
+//! The lines are read once from a [`ContentSnapshot`] when the search
+//! starts, a window at a time so large files work too. Each query then
+//! ranks them with the fuzzy matcher the command palette uses, ignoring
+//! indentation. A query that extends the previous one can only match lines
+//! that matched before, so only those are ranked again, which keeps
+//! narrowing a search fast in long files.
+
+use std::io;
+use std::ops::Range;
+
+use crate::input::fuzzy::fuzzy_match;
+use crate::model::buffer::ContentSnapshot;
+
+/// Most lines read from a buffer
+pub const MAX_LINES: usize = 1_000_000;
+
+/// Bytes of a line kept and matched; the rest of a longer line is ignored
+pub const MAX_LINE_LEN: usize = 1024;
+
+/// Most matches returned for a query
+pub const MAX_RESULTS: usize = 1000;
+
+/// A line of the buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// Zero-based line number
+    pub line: usize,
+    /// Byte offset of the start of the line
+    pub offset: usize,
+    /// Text of the line, without its line ending
+    pub text: String,
+}
+
+/// A line matching a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMatch {
+    /// Index of the line in [`LineSearch::lines`]
+    pub index: usize,
+    pub score: i32,
+    /// Byte ranges of the matched characters within the line's text
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// The lines of a buffer, and the ones matching the last query
+pub struct LineSearch {
+    lines: Vec<Line>,
+    /// Reading stopped at `MAX_LINES`
+    limited: bool,
+    query: String,
+    /// Indices of the lines matching `query`, in line order
+    matching: Vec<usize>,
+}
+
+impl LineSearch {
+    /// Read the lines of `snapshot`, `window_size` bytes at a time
+    pub fn read(snapshot: &mut ContentSnapshot, window_size: usize) -> io::Result<Self> {
+        let len = snapshot.len();
+        let mut lines = Vec::new();
+        let mut pending = Vec::new();
+        let mut offset = 0;
+        let mut limited = false;
+        'windows: for start in (0..len).step_by(window_size) {
+            let bytes = snapshot.read(start..start + window_size)?;
+            let mut rest = &bytes[..];
+            let mut at = start;
+            while let Some(newline) = rest.iter().position(|&b| b == b'\n') {
+                push_limited(&mut pending, &rest[..newline]);
+                lines.push(finish_line(lines.len(), offset, &mut pending));
+                at += newline + 1;
+                rest = &rest[newline + 1..];
+                offset = at;
+                if lines.len() >= MAX_LINES {
+                    limited = offset < len;
+                    break 'windows;
+                }
+            }
+            push_limited(&mut pending, rest);
+        }
+        if !limited && offset < len {
+            lines.push(finish_line(lines.len(), offset, &mut pending));
+        }
+        let matching = (0..lines.len()).collect();
+        Ok(Self {
+            lines,
+            limited,
+            query: String::new(),
+            matching,
+        })
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Whether only the first `MAX_LINES` lines were read
+    pub fn limited(&self) -> bool {
+        self.limited
+    }
+
+    /// The lines matching `query`, best first and in line order among
+    /// equals, up to `MAX_RESULTS`; every line for an empty query
+    pub fn search(&mut self, query: &str) -> Vec<LineMatch> {
+        let candidates = if query.starts_with(&self.query) {
+            std::mem::take(&mut self.matching)
+        } else {
+            (0..self.lines.len()).collect()
+        };
+        let mut found = Vec::new();
+        for index in candidates {
+            let text = &self.lines[index].text;
+            let trimmed = text.trim_start();
+            let result = fuzzy_match(query, trimmed);
+            if result.matched {
+                let indent = text.len() - trimmed.len();
+                found.push(LineMatch {
+                    index,
+                    score: result.score,
+                    ranges: char_ranges(trimmed, &result.match_positions)
+                        .into_iter()
+                        .map(|range| indent + range.start..indent + range.end)
+                        .collect(),
+                });
+            }
+        }
+        self.matching = found.iter().map(|m| m.index).collect();
+        self.query = query.to_string();
+
+        found.sort_by_key(|m| std::cmp::Reverse(m.score));
+        found.truncate(MAX_RESULTS);
+        found
+    }
+}
+
+fn push_limited(pending: &mut Vec<u8>, bytes: &[u8]) {
+    let room = MAX_LINE_LEN.saturating_sub(pending.len());
+    pending.extend_from_slice(&bytes[..bytes.len().min(room)]);
+}
+
+fn finish_line(line: usize, offset: usize, pending: &mut Vec<u8>) -> Line {
+    if pending.last() == Some(&b'\r') {
+        pending.pop();
+    }
+    let text = String::from_utf8_lossy(pending).into_owned();
+    pending.clear();
+    Line { line, offset, text }
+}
+
+/// Byte ranges of the characters at `positions` (character indices, in
+/// order) in `text`, with neighbouring characters merged
+fn char_ranges(text: &str, positions: &[usize]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut positions = positions.iter().peekable();
+    for (index, (start, ch)) in text.char_indices().enumerate() {
+        if positions.peek() != Some(&&index) {
+            continue;
+        }
+        positions.next();
+        let end = start + ch.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// Suggestion text for `line`: its number, right-aligned to `width`, and
+/// its text without indentation
+pub fn line_label(line: &Line, width: usize) -> String {
+    format!(
+        "{:>width$}: {}",
+        line.line + 1,
+        line.text.trim(),
+        width = width
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::buffer::TextBuffer;
+
+    fn read(text: &str, window_size: usize) -> LineSearch {
+        let buffer = TextBuffer::from_str_test(text);
+        LineSearch::read(&mut buffer.content_snapshot(), window_size).unwrap()
+    }
+
+    #[test]
+    fn test_read_is_the_same_for_any_window_size() {
+        let text = "fn main() {\r\n    let x = 1;\n\n}";
+        for window in [1, 3, 64] {
+            let search = read(text, window);
+            let lines: Vec<(usize, usize, &str)> = search
+                .lines()
+                .iter()
+                .map(|l| (l.line, l.offset, l.text.as_str()))
+                .collect();
+            assert_eq!(
+                lines,
+                vec![
+                    (0, 0, "fn main() {"),
+                    (1, 13, "    let x = 1;"),
+                    (2, 28, ""),
+                    (3, 29, "}"),
+                ],
+                "window size {}",
+                window
+            );
+            assert!(!search.limited());
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_and_narrows() {
+        let mut search = read("let value = 1;\nfn save_value() {}\n  save();\nother\n", 64);
+        let indices = |found: &[LineMatch]| found.iter().map(|m| m.index).collect::<Vec<_>>();
+
+        assert_eq!(indices(&search.search("")), vec![0, 1, 2, 3]);
+        // Matches at the start of the (unindented) line rank first
+        let found = search.search("sav");
+        assert_eq!(indices(&found), vec![2, 1]);
+        assert_eq!(found[0].ranges, vec![2..5]);
+        assert_eq!(indices(&search.search("savev")), vec![1]);
+        // Going back to a shorter query searches every line again
+        assert_eq!(indices(&search.search("va")), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_char_ranges() {
+        assert_eq!(char_ranges("héllo", &[0, 1, 3]), vec![0..3, 4..5]);
+        assert_eq!(char_ranges("abc", &[]), Vec::<Range<usize>>::new());
+        let line = Line {
+            line: 41,
+            offset: 0,
+            text: "\tfoo  ".to_string(),
+        };
+        assert_eq!(line_label(&line, 4), "  42: foo");
+    }
+}
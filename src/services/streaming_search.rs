@@ -0,0 +1,435 @@
+//! Background search of buffers too large to search as one string
+//!
+//! The text is read from a [`ContentSnapshot`] one window at a time, so
+//! regions of a large file that were never loaded are read straight from
+//! disk and dropped again. Each window is read with a few bytes before it
+//! (for look-behind such as `\b`) and `MATCH_OVERLAP` bytes after it, so a
+//! match starting in the window is found whole even when it crosses into
+//! the next one. Only matches starting inside the window are reported.
+//!
+//! The search starts at an origin (usually the cursor) and runs forward or
+//! backward from there, wrapping around, so the nearest matches come first.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use regex::bytes::{Regex, RegexBuilder};
+
+use crate::model::buffer::ContentSnapshot;
+
+/// Bytes searched per window
+pub const WINDOW_SIZE: usize = 1024 * 1024;
+
+/// Bytes read past the end of each window; matches longer than this may
+/// be cut short
+pub const MATCH_OVERLAP: usize = 4096;
+
+/// Bytes read before each window, enough for one UTF-8 character
+pub const LOOK_BEHIND: usize = 4;
+
+/// Which way a search runs from its origin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchDirection {
+    Forward,
+    Backward,
+}
+
+/// A match, as a byte range in the document
+pub type SearchMatch = Range<usize>;
+
+/// Progress reported by a running search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchUpdate {
+    /// Matches found in the latest window, in search order, along with the
+    /// number of bytes searched so far
+    Found {
+        matches: Vec<SearchMatch>,
+        searched: usize,
+    },
+    /// Every byte was searched
+    Done,
+    /// Reading the text failed
+    Failed(String),
+}
+
+/// Build the regex for `query` with the search prompt's options
+///
+/// `^` and `$` match at the start and end of every line (ending in `\n` or
+/// `\r\n`), not just of the whole text.
+pub fn build_regex(
+    query: &str,
+    case_sensitive: bool,
+    whole_word: bool,
+    use_regex: bool,
+) -> Result<Regex, regex::Error> {
+    let pattern = if use_regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let pattern = if whole_word {
+        format!(r"\b{}\b", pattern)
+    } else {
+        pattern
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!case_sensitive)
+        .multi_line(true)
+        .crlf(true)
+        .build()
+}
+
+/// Handle to a search running on a background thread
+///
+/// Dropping the handle cancels the search.
+pub struct SearchJob {
+    receiver: Receiver<SearchUpdate>,
+    cancelled: Arc<AtomicBool>,
+    total: usize,
+}
+
+impl SearchJob {
+    /// Search `range` of `snapshot` from `origin`, in `direction`
+    pub fn start(
+        mut snapshot: ContentSnapshot,
+        regex: Regex,
+        range: Range<usize>,
+        origin: usize,
+        direction: SearchDirection,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let total = range.len();
+        let worker_cancelled = cancelled.clone();
+        thread::spawn(move || {
+            let result = search(
+                &mut snapshot,
+                &regex,
+                range,
+                origin,
+                direction,
+                WINDOW_SIZE,
+                &worker_cancelled,
+                &sender,
+            );
+            let update = match result {
+                Ok(()) => SearchUpdate::Done,
+                Err(e) => SearchUpdate::Failed(e.to_string()),
+            };
+            if !worker_cancelled.load(Ordering::Relaxed) {
+                let _ = sender.send(update);
+            }
+        });
+        Self {
+            receiver,
+            cancelled,
+            total,
+        }
+    }
+
+    /// Bytes the search covers
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Updates sent since the last poll, without blocking
+    pub fn poll(&self) -> Vec<SearchUpdate> {
+        let mut updates = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(update) => updates.push(update),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if !matches!(
+                        updates.last(),
+                        Some(SearchUpdate::Done | SearchUpdate::Failed(_))
+                    ) && !self.cancelled.load(Ordering::Relaxed)
+                    {
+                        updates.push(SearchUpdate::Failed("search stopped".to_string()));
+                    }
+                    break;
+                }
+            }
+        }
+        updates
+    }
+
+    /// Stop the search; no more updates are sent
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SearchJob {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Windows covering `range` in the order they're searched: from `origin`
+/// in `direction` to the end of the range, then wrapping around back to
+/// `origin`. Each pass is a separate list, as windows from different passes
+/// aren't adjacent.
+fn search_passes(
+    range: &Range<usize>,
+    origin: usize,
+    direction: SearchDirection,
+    window: usize,
+) -> Vec<Vec<Range<usize>>> {
+    let origin = origin.clamp(range.start, range.end);
+    let forward = |span: Range<usize>| -> Vec<Range<usize>> {
+        (span.start..span.end)
+            .step_by(window)
+            .map(|start| start..(start + window).min(span.end))
+            .collect()
+    };
+    let backward = |span: Range<usize>| -> Vec<Range<usize>> {
+        let mut windows = forward(span);
+        windows.reverse();
+        windows
+    };
+    match direction {
+        SearchDirection::Forward => vec![forward(origin..range.end), forward(range.start..origin)],
+        SearchDirection::Backward => {
+            vec![backward(range.start..origin), backward(origin..range.end)]
+        }
+    }
+}
+
+/// Matches of `regex` starting in `window`, searching from `from`
+///
+/// `bytes` holds the text of the document from `base`, including some
+/// before the window for look-behind and some after it for matches that
+/// cross its end.
+fn find_in_window(
+    regex: &Regex,
+    bytes: &[u8],
+    base: usize,
+    window: &Range<usize>,
+    from: usize,
+) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    let mut at = from.max(window.start) - base;
+    while at <= bytes.len() {
+        let Some(found) = regex.find_at(bytes, at) else {
+            break;
+        };
+        let start = base + found.start();
+        if start >= window.end {
+            break;
+        }
+        matches.push(start..base + found.end());
+        at = if found.is_empty() {
+            found.end() + 1
+        } else {
+            found.end()
+        };
+    }
+    matches
+}
+
+/// Run a search, sending each window's matches to `sender`
+#[allow(clippy::too_many_arguments)]
+fn search(
+    snapshot: &mut ContentSnapshot,
+    regex: &Regex,
+    range: Range<usize>,
+    origin: usize,
+    direction: SearchDirection,
+    window_size: usize,
+    cancelled: &AtomicBool,
+    sender: &Sender<SearchUpdate>,
+) -> std::io::Result<()> {
+    let range = range.start..range.end.min(snapshot.len());
+    let mut searched = 0;
+    for pass in search_passes(&range, origin, direction, window_size) {
+        // End of the last match found going forward, as matches don't overlap
+        let mut last_end = 0;
+        // Matches of the window after the current one going backward, held
+        // back until we know no match crosses into them
+        let mut held: Vec<SearchMatch> = Vec::new();
+        for window in pass {
+            if cancelled.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            let base = window.start.saturating_sub(LOOK_BEHIND).max(range.start);
+            let bytes = snapshot.read(base..(window.end + MATCH_OVERLAP).min(range.end))?;
+            searched += window.len();
+            let from = match direction {
+                SearchDirection::Forward => last_end,
+                SearchDirection::Backward => window.start,
+            };
+            let mut matches = find_in_window(regex, &bytes, base, &window, from);
+            let found = match direction {
+                SearchDirection::Forward => {
+                    if let Some(last) = matches.last() {
+                        last_end = last.end;
+                    }
+                    matches
+                }
+                SearchDirection::Backward => {
+                    if let Some(last) = matches.last() {
+                        held.retain(|held| held.start >= last.end);
+                    }
+                    matches.reverse();
+                    std::mem::replace(&mut held, matches)
+                }
+            };
+            if sender
+                .send(SearchUpdate::Found {
+                    matches: found,
+                    searched,
+                })
+                .is_err()
+            {
+                return Ok(());
+            }
+        }
+        if !held.is_empty() {
+            let _ = sender.send(SearchUpdate::Found {
+                matches: held,
+                searched,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::buffer::TextBuffer;
+
+    fn run(
+        text: &str,
+        regex: &Regex,
+        range: Range<usize>,
+        origin: usize,
+        direction: SearchDirection,
+        window_size: usize,
+    ) -> Vec<SearchMatch> {
+        let buffer = TextBuffer::from_str_test(text);
+        let mut snapshot = buffer.content_snapshot();
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = AtomicBool::new(false);
+        search(
+            &mut snapshot,
+            regex,
+            range,
+            origin,
+            direction,
+            window_size,
+            &cancelled,
+            &sender,
+        )
+        .unwrap();
+        drop(sender);
+        receiver
+            .iter()
+            .flat_map(|update| match update {
+                SearchUpdate::Found { matches, .. } => matches,
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_crossing_windows_are_found_once() {
+        let text = "foo bar foobar barfoo foo";
+        let regex = build_regex("foo", true, false, false).unwrap();
+        let expected = vec![0..3, 8..11, 18..21, 22..25];
+        for window in 1..8 {
+            let found = run(
+                text,
+                &regex,
+                0..text.len(),
+                0,
+                SearchDirection::Forward,
+                window,
+            );
+            assert_eq!(found, expected, "window size {}", window);
+        }
+    }
+
+    #[test]
+    fn test_regex_matches_match_whole_text_search() {
+        let text = "alpha beta\ngamma_delta epsilon\nzeta";
+        let regex = build_regex(r"\w+a\b", true, false, true).unwrap();
+        let expected: Vec<SearchMatch> = regex
+            .find_iter(text.as_bytes())
+            .map(|m| m.range())
+            .collect();
+        for window in [1, 3, 7, 64] {
+            let mut forward = run(
+                text,
+                &regex,
+                0..text.len(),
+                0,
+                SearchDirection::Forward,
+                window,
+            );
+            forward.sort_by_key(|m| m.start);
+            assert_eq!(forward, expected, "forward, window size {}", window);
+            let mut backward = run(
+                text,
+                &regex,
+                0..text.len(),
+                text.len(),
+                SearchDirection::Backward,
+                window,
+            );
+            backward.sort_by_key(|m| m.start);
+            assert_eq!(backward, expected, "backward, window size {}", window);
+        }
+    }
+
+    #[test]
+    fn test_search_order_starts_at_origin() {
+        let text = "ab ab ab ab";
+        let regex = build_regex("AB", false, false, false).unwrap();
+        let forward = run(text, &regex, 0..text.len(), 4, SearchDirection::Forward, 3);
+        assert_eq!(forward, vec![6..8, 9..11, 0..2, 3..5]);
+        let backward = run(text, &regex, 0..text.len(), 4, SearchDirection::Backward, 3);
+        assert_eq!(backward, vec![3..5, 0..2, 9..11, 6..8]);
+    }
+
+    #[test]
+    fn test_whole_word_and_range() {
+        let text = "cat concat cat scatter cat";
+        let regex = build_regex("cat", true, true, false).unwrap();
+        let found = run(text, &regex, 4..23, 4, SearchDirection::Forward, 4);
+        assert_eq!(found, vec![11..14]);
+    }
+
+    #[test]
+    fn test_job_reports_progress_and_finishes() {
+        let text = "x needle ".repeat(1000);
+        let buffer = TextBuffer::from_str_test(&text);
+        let regex = build_regex("needle", true, false, false).unwrap();
+        let job = SearchJob::start(
+            buffer.content_snapshot(),
+            regex,
+            0..text.len(),
+            0,
+            SearchDirection::Forward,
+        );
+        let mut found = 0;
+        loop {
+            let updates = job.poll();
+            for update in &updates {
+                if let SearchUpdate::Found { matches, searched } = update {
+                    found += matches.len();
+                    assert!(*searched <= job.total());
+                }
+            }
+            if updates.contains(&SearchUpdate::Done) {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(found, 1000);
+    }
+}
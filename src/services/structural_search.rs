@@ -0,0 +1,599 @@
+//! Structural search and replace
+//!
+//! A structural pattern is matched against the tree-sitter syntax tree of a
+//! file rather than its text. It is either:
+//!
+//! - **Code** in the file's language, where `$NAME` (an upper-case name)
+//!   stands for any single syntax node and `$_` for any node without
+//!   capturing it: `$X.unwrap()` matches `foo.unwrap()` and
+//!   `self.map.get(k).unwrap()`. Code matches when the trees have the same
+//!   shape and the same tokens, whatever the whitespace and comments. A
+//!   metavariable used twice must match the same text both times.
+//! - A **tree-sitter query** such as
+//!   `(call_expression function: (identifier) @name) @match`, recognised by
+//!   starting with `(` or `[` and containing a capture. Each match covers
+//!   its `@match` capture, or all of its captures.
+//!
+//! Patterns are compiled once per language, as files of each language are
+//! met. Matches never overlap: the first (outermost) match wins.
+//!
+//! A replacement template refers to captures as `$NAME` or `${NAME}`; `$$`
+//! is a literal `$`. References to captures the pattern doesn't have are
+//! left as they are, so code using `$` itself (PHP, shell) can be written
+//! as is.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+use tree_sitter::{Node, Parser, Query, QueryCursor, StreamingIterator};
+
+use crate::primitives::highlighter::Language;
+use crate::services::project_search::{ProjectFiles, ResultLine, MAX_RESULT_LINES};
+use crate::services::replace::Replacement;
+
+/// Prefix standing in for `$` in metavariables, so that code patterns
+/// parse as ordinary identifiers
+const PLACEHOLDER: &str = "__meta_";
+
+/// Name of the query capture that marks the matched node
+const MATCH_CAPTURE: &str = "match";
+
+/// A match of a structural pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuralMatch {
+    /// Byte range of the matched text
+    pub range: Range<usize>,
+    /// Byte range of each captured node, by capture name
+    pub captures: Vec<(String, Range<usize>)>,
+}
+
+/// The matches in one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMatches {
+    pub path: PathBuf,
+    /// Text of the file the matches are in
+    pub text: String,
+    pub matches: Vec<StructuralMatch>,
+}
+
+/// A node of a code pattern
+#[derive(Debug)]
+enum PatternNode {
+    /// Matches any node, capturing it under the name unless it is `_`
+    Metavariable(String),
+    /// A token, matched by kind and text
+    Leaf { kind: u16, text: String },
+    /// Matched by kind and children, extras (comments) aside
+    Branch {
+        kind: u16,
+        children: Vec<PatternNode>,
+    },
+}
+
+/// A pattern compiled for one language
+enum Matcher {
+    Code(PatternNode),
+    Query(Query),
+}
+
+/// A structural pattern, compiled for each language it is used with
+pub struct StructuralPattern {
+    source: String,
+    compiled: Mutex<HashMap<Language, Result<Arc<Matcher>, String>>>,
+}
+
+impl StructuralPattern {
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.trim().to_string(),
+            compiled: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the pattern is a tree-sitter query rather than code
+    pub fn is_query(&self) -> bool {
+        self.source.starts_with(['(', '[']) && self.source.contains('@')
+    }
+
+    /// Check that the pattern is valid for `language`
+    pub fn check(&self, language: Language) -> Result<(), String> {
+        self.matcher(language).map(|_| ())
+    }
+
+    /// Why the pattern is invalid for the languages it was compiled for
+    /// and isn't valid in
+    pub fn errors(&self) -> Vec<String> {
+        let compiled = self.compiled.lock().unwrap();
+        let mut errors: Vec<String> = compiled
+            .values()
+            .filter_map(|matcher| matcher.as_ref().err().cloned())
+            .collect();
+        errors.sort();
+        errors
+    }
+
+    fn matcher(&self, language: Language) -> Result<Arc<Matcher>, String> {
+        let mut compiled = self.compiled.lock().unwrap();
+        compiled
+            .entry(language)
+            .or_insert_with(|| self.compile(language).map(Arc::new))
+            .clone()
+    }
+
+    fn compile(&self, language: Language) -> Result<Matcher, String> {
+        let grammar = language.tree_sitter_language();
+        if self.is_query() {
+            return Query::new(&grammar, &self.source)
+                .map(Matcher::Query)
+                .map_err(|e| format!("Invalid {} query: {}", language.name(), e));
+        }
+
+        let code = metavariables_to_placeholders(&self.source);
+        let mut parser = Parser::new();
+        parser
+            .set_language(&grammar)
+            .map_err(|e| format!("Failed to load {} grammar: {}", language.name(), e))?;
+        let tree = parser
+            .parse(&code, None)
+            .ok_or_else(|| format!("Failed to parse pattern as {}", language.name()))?;
+        let root = pattern_root(tree.root_node(), &code);
+        if contains_error(root) {
+            return Err(format!("Pattern is not valid {} code", language.name()));
+        }
+        Ok(Matcher::Code(pattern_node(root, &code)))
+    }
+
+    /// Matches of the pattern in `text`, parsed as `language`, in order
+    pub fn find_all(&self, language: Language, text: &str) -> Result<Vec<StructuralMatch>, String> {
+        let matcher = self.matcher(language)?;
+        let mut parser = Parser::new();
+        parser
+            .set_language(&language.tree_sitter_language())
+            .map_err(|e| format!("Failed to load {} grammar: {}", language.name(), e))?;
+        let Some(tree) = parser.parse(text, None) else {
+            return Ok(Vec::new());
+        };
+
+        let mut matches = Vec::new();
+        match &*matcher {
+            Matcher::Code(pattern) => find_code(pattern, tree.root_node(), text, &mut matches),
+            Matcher::Query(query) => {
+                matches = find_query(query, tree.root_node(), text);
+            }
+        }
+        Ok(matches)
+    }
+}
+
+/// Files of the project with matches of `pattern`, sorted by path
+///
+/// Files without a tree-sitter grammar, or whose language the pattern isn't
+/// valid in, are skipped. Stops early if `cancelled` is set or
+/// `MAX_RESULT_LINES` matches were found.
+pub fn search_project(
+    files: &ProjectFiles,
+    pattern: &StructuralPattern,
+    cancelled: &AtomicBool,
+) -> Vec<FileMatches> {
+    let mut found = files.visit(cancelled, MAX_RESULT_LINES, |path, text| {
+        let language = Language::from_path(path)?;
+        let matches = pattern.find_all(language, text).ok()?;
+        let count = matches.len();
+        let found = FileMatches {
+            path: path.to_path_buf(),
+            text: text.to_string(),
+            matches,
+        };
+        (count > 0).then_some((found, count))
+    });
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+}
+
+/// `pattern` with each `$NAME` metavariable replaced by an identifier
+fn metavariables_to_placeholders(pattern: &str) -> String {
+    let mut code = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(dollar) = rest.find('$') {
+        code.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let name_len = metavariable_len(after);
+        if name_len > 0 {
+            code.push_str(PLACEHOLDER);
+            code.push_str(&after[..name_len]);
+        } else {
+            code.push('$');
+        }
+        rest = &after[name_len..];
+    }
+    code.push_str(rest);
+    code
+}
+
+/// Length of the metavariable name `text` starts with: an upper-case letter
+/// or `_`, then upper-case letters, digits and `_`
+fn metavariable_len(text: &str) -> usize {
+    let mut chars = text.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_ascii_uppercase() || c == '_' => {}
+        _ => return 0,
+    }
+    chars
+        .find(|(_, c)| !(c.is_ascii_uppercase() || c.is_ascii_digit() || *c == '_'))
+        .map_or(text.len(), |(at, _)| at)
+}
+
+/// Whether the tree has a syntax error (missing tokens the parser filled
+/// in, such as a statement's `;`, are fine)
+fn contains_error(node: Node) -> bool {
+    if node.is_error() {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(contains_error);
+    found
+}
+
+/// Children that take part in matching: not comments, not filled in
+/// (syntax errors count, though tree-sitter marks them as extras)
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| (!child.is_extra() || child.is_error()) && !child.is_missing())
+        .collect()
+}
+
+/// The node a pattern is about: the outermost one spanning all of its
+/// text, below the wrappers (source file, expression statement, or an
+/// error around an expression that can't stand alone) that only come from
+/// parsing the pattern on its own
+fn pattern_root<'tree>(root: Node<'tree>, code: &str) -> Node<'tree> {
+    let mut node = root;
+    loop {
+        let children = significant_children(node);
+        match children.as_slice() {
+            [only] if code[only.byte_range()] == *code[node.byte_range()].trim() => node = *only,
+            _ => return node,
+        }
+    }
+}
+
+fn pattern_node(node: Node, code: &str) -> PatternNode {
+    let text = &code[node.byte_range()];
+    if let Some(name) = text.strip_prefix(PLACEHOLDER) {
+        if metavariable_len(name) == name.len() {
+            return PatternNode::Metavariable(name.to_string());
+        }
+    }
+    let children = significant_children(node);
+    if children.is_empty() {
+        PatternNode::Leaf {
+            kind: node.kind_id(),
+            text: text.to_string(),
+        }
+    } else {
+        PatternNode::Branch {
+            kind: node.kind_id(),
+            children: children
+                .into_iter()
+                .map(|child| pattern_node(child, code))
+                .collect(),
+        }
+    }
+}
+
+/// Whether `node` matches `pattern`, adding to `captures`
+fn matches_node(
+    pattern: &PatternNode,
+    node: Node,
+    text: &str,
+    captures: &mut Vec<(String, Range<usize>)>,
+) -> bool {
+    match pattern {
+        PatternNode::Metavariable(name) => {
+            if name == "_" {
+                return true;
+            }
+            let range = node.byte_range();
+            if let Some((_, bound)) = captures.iter().find(|(bound, _)| bound == name) {
+                return text[bound.clone()] == text[range];
+            }
+            captures.push((name.clone(), range));
+            true
+        }
+        PatternNode::Leaf { kind, text: leaf } => {
+            node.kind_id() == *kind
+                && significant_children(node).is_empty()
+                && text[node.byte_range()] == *leaf
+        }
+        PatternNode::Branch { kind, children } => {
+            if node.kind_id() != *kind {
+                return false;
+            }
+            let node_children = significant_children(node);
+            node_children.len() == children.len()
+                && children
+                    .iter()
+                    .zip(node_children)
+                    .all(|(pattern, child)| matches_node(pattern, child, text, captures))
+        }
+    }
+}
+
+/// Add the outermost nodes under `node` matching `pattern`, in order
+fn find_code(pattern: &PatternNode, node: Node, text: &str, found: &mut Vec<StructuralMatch>) {
+    let mut captures = Vec::new();
+    if matches_node(pattern, node, text, &mut captures) && !node.byte_range().is_empty() {
+        found.push(StructuralMatch {
+            range: node.byte_range(),
+            captures,
+        });
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        find_code(pattern, child, text, found);
+    }
+}
+
+/// Non-overlapping matches of `query` under `root`, in order
+fn find_query(query: &Query, root: Node, text: &str) -> Vec<StructuralMatch> {
+    let names = query.capture_names();
+    let match_index = query.capture_index_for_name(MATCH_CAPTURE);
+    let mut query_cursor = QueryCursor::new();
+    let mut query_matches = query_cursor.matches(query, root, text.as_bytes());
+
+    let mut found = Vec::new();
+    while let Some(query_match) = query_matches.next() {
+        let Some(first) = query_match.captures.first() else {
+            continue;
+        };
+        let mut range = first.node.byte_range();
+        let mut captures = Vec::new();
+        for capture in query_match.captures {
+            let node_range = capture.node.byte_range();
+            if Some(capture.index) == match_index {
+                range = node_range.clone();
+            } else if match_index.is_none() {
+                range = range.start.min(node_range.start)..range.end.max(node_range.end);
+            }
+            captures.push((names[capture.index as usize].to_string(), node_range));
+        }
+        found.push(StructuralMatch { range, captures });
+    }
+
+    found.sort_by_key(|m| (m.range.start, std::cmp::Reverse(m.range.end)));
+    let mut end = 0;
+    found.retain(|m| {
+        let keep = m.range.start >= end && !m.range.is_empty();
+        if keep {
+            end = m.range.end;
+        }
+        keep
+    });
+    found
+}
+
+/// `template` with references to the captures of `found` expanded
+pub fn expand_template(template: &str, text: &str, found: &StructuralMatch) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(dollar) = rest.find('$') {
+        expanded.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        if let Some(escaped) = after.strip_prefix('$') {
+            expanded.push('$');
+            rest = escaped;
+            continue;
+        }
+
+        let (name, reference_len) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(close) => (&braced[..close], close + 2),
+                None => ("", 0),
+            }
+        } else {
+            let len = after
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..len], len)
+        };
+        let captured = found
+            .captures
+            .iter()
+            .find(|(capture, _)| capture == name && reference_len > 0);
+        match captured {
+            Some((_, range)) => {
+                expanded.push_str(&text[range.clone()]);
+                rest = &after[reference_len..];
+            }
+            None => {
+                expanded.push('$');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// The replacement of each match with `template`
+pub fn replacements(text: &str, matches: &[StructuralMatch], template: &str) -> Vec<Replacement> {
+    matches
+        .iter()
+        .map(|found| Replacement {
+            range: found.range.clone(),
+            matched: text[found.range.clone()].to_string(),
+            text: expand_template(template, text, found),
+        })
+        .collect()
+}
+
+/// `text` with each match replaced by `template`
+pub fn rewrite(text: &str, matches: &[StructuralMatch], template: &str) -> String {
+    let mut rewritten = String::with_capacity(text.len());
+    let mut end = 0;
+    for replacement in replacements(text, matches, template) {
+        rewritten.push_str(&text[end..replacement.range.start]);
+        rewritten.push_str(&replacement.text);
+        end = replacement.range.end;
+    }
+    rewritten.push_str(&text[end..]);
+    rewritten
+}
+
+/// Search results for the matches in `text`: each line a match starts on,
+/// with the part of each match on that line
+pub fn result_lines(path: &Path, text: &str, matches: &[StructuralMatch]) -> Vec<ResultLine> {
+    let mut results: Vec<ResultLine> = Vec::new();
+    let mut line = 0;
+    let mut line_start = 0;
+    for found in matches {
+        while let Some(newline) = text[line_start..found.range.start].find('\n') {
+            line += 1;
+            line_start += newline + 1;
+        }
+        let line_end = text[line_start..]
+            .find('\n')
+            .map_or(text.len(), |at| line_start + at);
+        let line_text = text[line_start..line_end].trim_end_matches('\r');
+        let range =
+            found.range.start - line_start..(found.range.end - line_start).min(line_text.len());
+        match results.last_mut() {
+            Some(last) if last.line == line => last.matches.push(range),
+            _ => results.push(ResultLine {
+                path: path.to_path_buf(),
+                line,
+                text: line_text.to_string(),
+                matches: vec![range],
+            }),
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Vec<String> {
+        StructuralPattern::new(pattern)
+            .find_all(Language::Rust, text)
+            .unwrap()
+            .iter()
+            .map(|m| text[m.range.clone()].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_code_pattern_matches_any_expression() {
+        let text = "fn f() {\n    let a = foo.unwrap();\n    let b = self.map.get(k)\n        .unwrap();\n    bar.expect(\"x\");\n}\n";
+        assert_eq!(
+            find("$X.unwrap()", text),
+            vec!["foo.unwrap()", "self.map.get(k)\n        .unwrap()"]
+        );
+        // Tokens must match, whatever the whitespace and comments
+        assert_eq!(
+            find(
+                "bar . expect ( $_ )",
+                "fn f() { bar.expect(/* why */ \"x\"); }"
+            ),
+            vec!["bar.expect(/* why */ \"x\")"]
+        );
+        assert!(find("$X.unwrap()", "fn f() { x.unwrap_or(1); }").is_empty());
+    }
+
+    #[test]
+    fn test_repeated_metavariable_must_match_the_same_text() {
+        let text = "fn f() { a == a; a == b; f(x) == f(x); }";
+        assert_eq!(find("$X == $X", text), vec!["a == a", "f(x) == f(x)"]);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let pattern = StructuralPattern::new("fn (");
+        assert!(pattern.check(Language::Rust).is_err());
+        let query = StructuralPattern::new("(no_such_node) @match");
+        assert!(query.is_query());
+        assert!(query
+            .check(Language::Rust)
+            .unwrap_err()
+            .starts_with("Invalid rust query"));
+    }
+
+    #[test]
+    fn test_query_pattern_and_rewrite() {
+        let text = "fn f() { foo(1); bar(foo(2)); }";
+        let pattern =
+            StructuralPattern::new("(call_expression function: (identifier) @name (#eq? @name \"foo\") arguments: (_) @args) @match");
+        let matches = pattern.find_all(Language::Rust, text).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(
+            rewrite(text, &matches, "baz${args} /* $name $$ $other */"),
+            "fn f() { baz(1) /* foo $ $other */; bar(baz(2) /* foo $ $other */); }"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_and_result_lines() {
+        let text = "fn f() -> Option<()> {\n    let v = a.unwrap() + b.unwrap();\n    c\n        .unwrap();\n}\n";
+        let matches = StructuralPattern::new("$X.unwrap()")
+            .find_all(Language::Rust, text)
+            .unwrap();
+        assert_eq!(
+            rewrite(text, &matches, "$X?"),
+            "fn f() -> Option<()> {\n    let v = a? + b?;\n    c?;\n}\n"
+        );
+
+        let results = result_lines(Path::new("f.rs"), text, &matches);
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            (results[0].line, &results[0].matches[..]),
+            (1, &[12..22, 25..35][..])
+        );
+        // A match running onto later lines is shown on the line it starts on
+        assert_eq!(results[1].line, 2);
+        assert_eq!(results[1].matches.first(), Some(&(4..5)));
+        assert_eq!(results[1].text, "    c");
+    }
+
+    #[test]
+    fn test_search_project_skips_other_languages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn a() { x.unwrap(); }\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "fn b() {}\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "x.unwrap()\n").unwrap();
+        let files = ProjectFiles {
+            root: dir.path().to_path_buf(),
+            respect_gitignore: true,
+            ignore_patterns: Vec::new(),
+            unsaved: HashMap::new(),
+        };
+
+        let pattern = StructuralPattern::new("$X.unwrap()");
+        let found = search_project(&files, &pattern, &AtomicBool::new(false));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, dir.path().join("a.rs"));
+        assert_eq!(found[0].matches.len(), 1);
+        assert!(pattern.errors().is_empty());
+
+        let invalid = StructuralPattern::new("fn (");
+        assert!(search_project(&files, &invalid, &AtomicBool::new(false)).is_empty());
+        assert_eq!(invalid.errors(), vec!["Pattern is not valid rust code"]);
+    }
+
+    #[test]
+    fn test_metavariables_to_placeholders() {
+        assert_eq!(
+            metavariables_to_placeholders("$X + $_ + $foo + $ + $A1"),
+            "__meta_X + __meta__ + $foo + $ + __meta_A1"
+        );
+    }
+}
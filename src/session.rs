@@ -5,6 +5,7 @@
 //! - Cursor and scroll positions per split per file
 //! - File explorer state
 //! - Search/replace history and options
+//! - Clipboard history
 //! - Bookmarks
 //!
 //! ## Storage
@@ -78,6 +79,14 @@ pub struct Session {
     #[serde(default)]
     pub terminals: Vec<SerializedTerminalSession>,
 
+    /// Debugger breakpoints
+    #[serde(default)]
+    pub breakpoints: Vec<SerializedBreakpoint>,
+
+    /// Debugger watch expressions
+    #[serde(default)]
+    pub watch_expressions: Vec<String>,
+
     /// Timestamp when session was saved (Unix epoch seconds)
     pub saved_at: u64,
 }
@@ -247,6 +256,9 @@ pub struct SessionHistories {
     pub goto_line: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub open_file: Vec<String>,
+    /// Clipboard history, newest first; each entry holds the text of every cursor
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clipboard: Vec<Vec<String>>,
 }
 
 /// Search options that persist across searches within a session
@@ -260,6 +272,8 @@ pub struct SearchOptions {
     pub use_regex: bool,
     #[serde(default)]
     pub confirm_each: bool,
+    #[serde(default)]
+    pub preserve_case: bool,
 }
 
 /// Serialized bookmark (file path + byte offset)
@@ -271,6 +285,19 @@ pub struct SerializedBookmark {
     pub position: usize,
 }
 
+/// Serialized debugger breakpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedBreakpoint {
+    /// File path (relative to working_dir when inside it)
+    pub file_path: PathBuf,
+    /// 0-indexed line
+    pub line: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_message: Option<String>,
+}
+
 /// Reference to an open tab (file path or terminal index)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SerializedTabRef {
@@ -707,6 +734,8 @@ impl Session {
             search_options: SearchOptions::default(),
             bookmarks: HashMap::new(),
             terminals: Vec::new(),
+            breakpoints: Vec::new(),
+            watch_expressions: Vec::new(),
             saved_at: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap_or_default()
@@ -910,6 +939,7 @@ mod tests {
             whole_word: true,
             use_regex: false,
             confirm_each: true,
+            preserve_case: true,
         };
 
         let json = serde_json::to_string(&options).unwrap();
@@ -919,6 +949,7 @@ mod tests {
         assert!(restored.whole_word);
         assert!(!restored.use_regex);
         assert!(restored.confirm_each);
+        assert!(restored.preserve_case);
     }
 
     #[test]
@@ -972,6 +1003,21 @@ mod tests {
         session.search_options.case_sensitive = true;
         session.search_options.use_regex = true;
 
+        // Add debugger state
+        session.breakpoints.push(SerializedBreakpoint {
+            file_path: PathBuf::from("src/main.rs"),
+            line: 12,
+            condition: Some("x > 1".to_string()),
+            log_message: None,
+        });
+        session.watch_expressions.push("self.len()".to_string());
+
+        // Add a multi-cursor clipboard history entry
+        session
+            .histories
+            .clipboard
+            .push(vec!["first".to_string(), "second".to_string()]);
+
         // Serialize and deserialize
         let json = serde_json::to_string_pretty(&session).unwrap();
         let restored: Session = serde_json::from_str(&json).unwrap();
@@ -983,6 +1029,11 @@ mod tests {
         assert!(restored.bookmarks.contains_key(&'m'));
         assert!(restored.search_options.case_sensitive);
         assert!(restored.search_options.use_regex);
+        assert_eq!(restored.breakpoints.len(), 1);
+        assert_eq!(restored.breakpoints[0].line, 12);
+        assert_eq!(restored.breakpoints[0].condition.as_deref(), Some("x > 1"));
+        assert_eq!(restored.watch_expressions, vec!["self.len()".to_string()]);
+        assert_eq!(restored.histories.clipboard, vec![vec!["first", "second"]]);
 
         // Verify split state
         let split_state = restored.split_states.get(&1).unwrap();
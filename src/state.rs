@@ -1,19 +1,23 @@
 use crate::model::buffer::{Buffer, LineNumber};
-use crate::model::cursor::{Cursor, Cursors};
+use crate::model::cursor::{Cursor, Cursors, SelectionMode};
 use crate::model::document_model::{
     DocumentCapabilities, DocumentModel, DocumentPosition, ViewportContent, ViewportLine,
 };
 use crate::model::event::{
-    Event, MarginContentData, MarginPositionData, OverlayFace as EventOverlayFace, PopupData,
-    PopupPositionData,
+    CursorId, Event, MarginContentData, MarginPositionData, OverlayFace as EventOverlayFace,
+    PopupData, PopupPositionData,
 };
 use crate::model::marker::MarkerList;
+use crate::primitives::display_width::str_width;
 use crate::primitives::grammar_registry::GrammarRegistry;
 use crate::primitives::highlight_engine::HighlightEngine;
 use crate::primitives::highlighter::Language;
 use crate::primitives::indent::IndentCalculator;
 use crate::primitives::semantic_highlight::SemanticHighlighter;
+use crate::primitives::syntax_tree::SyntaxTree;
 use crate::primitives::text_property::TextPropertyManager;
+use crate::primitives::textobjects::TextObjects;
+use crate::primitives::word_completion::{is_word_byte, WordIndex, MAX_WORD_LEN};
 use crate::view::margin::{MarginAnnotation, MarginContent, MarginManager, MarginPosition};
 use crate::view::overlay::{Overlay, OverlayFace, OverlayManager, UnderlineStyle};
 use crate::view::popup::{Popup, PopupContent, PopupListItem, PopupManager, PopupPosition};
@@ -21,6 +25,13 @@ use crate::view::virtual_text::VirtualTextManager;
 use anyhow::Result;
 use ratatui::style::{Color, Style};
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Bytes scanned on each side of an edit for the ends of the words it
+/// touches: a run of word bytes filling them is too long to be indexed, so
+/// the part outside the scan doesn't matter
+const WORD_SCAN_BYTES: usize = MAX_WORD_LEN + 1;
 
 /// Display mode for a buffer
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,9 +54,20 @@ pub struct EditorState {
     /// All cursors
     pub cursors: Cursors,
 
+    /// Selections each cursor went through while expanding, as (position, anchor)
+    /// pairs with the current selection on top, so they can be shrunk back again
+    pub selection_history: HashMap<CursorId, Vec<(usize, Option<usize>)>>,
+
     /// Syntax highlighter (tree-sitter or TextMate based on language)
     pub highlighter: HighlightEngine,
 
+    /// Full-document syntax tree, reparsed in the background after edits
+    /// (None for large files and languages without a tree-sitter grammar)
+    pub syntax_tree: Option<SyntaxTree>,
+
+    /// Text object query for structural selection and motions
+    pub textobjects: Option<TextObjects>,
+
     /// Auto-indent calculator for smart indentation (RefCell for interior mutability)
     pub indent_calculator: RefCell<IndentCalculator>,
 
@@ -96,6 +118,14 @@ pub struct EditorState {
     /// Used for visual display of tab characters and indent calculations.
     pub tab_size: usize,
 
+    /// Whether cursors can sit past the end of a line (default false).
+    /// Typing there pads the line with spaces up to the cursor.
+    pub virtual_space: bool,
+
+    /// Words of the buffer for word completion, built the first time
+    /// completion needs them and kept up to date by each edit after that
+    pub word_index: Option<WordIndex>,
+
     /// Semantic highlighter for word occurrence highlighting
     pub semantic_highlighter: SemanticHighlighter,
 
@@ -128,7 +158,10 @@ impl EditorState {
         Self {
             buffer: Buffer::new(large_file_threshold),
             cursors: Cursors::new(),
+            selection_history: HashMap::new(),
             highlighter: HighlightEngine::None, // No file path, so no syntax highlighting
+            syntax_tree: None,
+            textobjects: None,
             indent_calculator: RefCell::new(IndentCalculator::new()),
             overlays: OverlayManager::new(),
             marker_list: MarkerList::new(),
@@ -143,6 +176,8 @@ impl EditorState {
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: 4, // Default tab size
+            virtual_space: false,
+            word_index: None,
             semantic_highlighter: SemanticHighlighter::new(),
             view_mode: ViewMode::Source,
             debug_highlight_mode: false,
@@ -158,8 +193,12 @@ impl EditorState {
     pub fn set_language_from_name(&mut self, name: &str, registry: &GrammarRegistry) {
         let path = std::path::Path::new(name);
         self.highlighter = HighlightEngine::for_file(path, registry);
+        self.syntax_tree = None;
+        self.textobjects = None;
         if let Some(language) = Language::from_path(path) {
             self.semantic_highlighter.set_language(&language);
+            self.syntax_tree = SyntaxTree::new(&language, &self.buffer);
+            self.textobjects = TextObjects::new(&language);
         }
         tracing::debug!(
             "Set highlighter for virtual buffer based on name: {} (backend: {})",
@@ -196,6 +235,10 @@ impl EditorState {
             semantic_highlighter.set_language(&lang);
         }
 
+        // Start parsing the whole document in the background
+        let syntax_tree = language.and_then(|lang| SyntaxTree::new(&lang, &buffer));
+        let textobjects = language.and_then(|lang| TextObjects::new(&lang));
+
         // Initialize marker list with buffer size
         let mut marker_list = MarkerList::new();
         if buffer.len() > 0 {
@@ -209,7 +252,10 @@ impl EditorState {
         Ok(Self {
             buffer,
             cursors: Cursors::new(),
+            selection_history: HashMap::new(),
             highlighter,
+            syntax_tree,
+            textobjects,
             indent_calculator: RefCell::new(IndentCalculator::new()),
             overlays: OverlayManager::new(),
             marker_list,
@@ -224,6 +270,8 @@ impl EditorState {
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: 4, // Default tab size
+            virtual_space: false,
+            word_index: None,
             semantic_highlighter,
             view_mode: ViewMode::Source,
             debug_highlight_mode: false,
@@ -247,9 +295,22 @@ impl EditorState {
         self.marker_list.adjust_for_insert(position, text.len());
         self.margins.adjust_for_insert(position, text.len());
 
+        // Keep the syntax tree aligned (position is the same before and after the insert)
+        if let Some(syntax_tree) = &mut self.syntax_tree {
+            let start = self.buffer.position_to_line_col(position);
+            syntax_tree.edit_insert(position, start, text);
+        }
+
+        // The words around the insert are counted again after it
+        let word_span = self.unindex_words_around(position..position);
+
         // Insert text into buffer
         self.buffer.insert(position, text);
 
+        if let Some(span) = word_span {
+            self.index_words(span.start..span.end + text.len());
+        }
+
         // Invalidate highlight cache for edited range
         self.highlighter
             .invalidate_range(position..position + text.len());
@@ -260,6 +321,7 @@ impl EditorState {
         // Move the cursor that made the edit to the end of the insertion
         if let Some(cursor) = self.cursors.get_mut(cursor_id) {
             cursor.position = position + text.len();
+            cursor.sticky_column = 0;
             cursor.clear_selection();
         }
 
@@ -292,9 +354,23 @@ impl EditorState {
         self.marker_list.adjust_for_delete(range.start, len);
         self.margins.adjust_for_delete(range.start, len);
 
+        // Keep the syntax tree aligned (positions must be taken before the delete)
+        if let Some(syntax_tree) = &mut self.syntax_tree {
+            let start = self.buffer.position_to_line_col(range.start);
+            let end = self.buffer.position_to_line_col(range.end);
+            syntax_tree.edit_delete(range.clone(), start, end);
+        }
+
+        // The words around the deleted text are counted again after it
+        let word_span = self.unindex_words_around(range.clone());
+
         // Delete from buffer
         self.buffer.delete(range.clone());
 
+        if let Some(span) = word_span {
+            self.index_words(span.start..span.end - len);
+        }
+
         // Invalidate highlight cache for edited range
         self.highlighter.invalidate_range(range.clone());
 
@@ -304,6 +380,7 @@ impl EditorState {
         // Move the cursor that made the edit to the start of deletion
         if let Some(cursor) = self.cursors.get_mut(cursor_id) {
             cursor.position = range.start;
+            cursor.sticky_column = 0;
             cursor.clear_selection();
         }
 
@@ -324,6 +401,31 @@ impl EditorState {
         }
     }
 
+    /// Pick up a finished background reparse and start the next one if the buffer changed
+    ///
+    /// Returns true if a new syntax tree was adopted and the buffer should be redrawn.
+    pub fn update_syntax_tree(&mut self) -> bool {
+        let Some(syntax_tree) = &mut self.syntax_tree else {
+            return false;
+        };
+        if !syntax_tree.update(&self.buffer) {
+            return false;
+        }
+        // Cached highlights were computed from the previous tree (or window parse)
+        self.highlighter.invalidate_all();
+        true
+    }
+
+    /// Latest syntax tree, after picking up a finished reparse
+    ///
+    /// A reparse still in flight isn't waited for: text typed since the last
+    /// parse may be missing from the tree, but its nodes are where the
+    /// buffer has them. None until the first parse has finished.
+    pub fn latest_syntax_tree(&mut self) -> Option<&tree_sitter::Tree> {
+        self.update_syntax_tree();
+        self.syntax_tree.as_ref()?.tree()
+    }
+
     /// Apply an event to the state - THE ONLY WAY TO MODIFY STATE
     /// This is the heart of the event-driven architecture
     pub fn apply(&mut self, event: &Event) {
@@ -351,6 +453,10 @@ impl EditorState {
                     cursor.position = *new_position;
                     cursor.anchor = *new_anchor;
                     cursor.sticky_column = *new_sticky_column;
+                    // A move that drops the selection ends a block selection too
+                    if new_anchor.is_none() {
+                        cursor.clear_block_selection();
+                    }
                 }
 
                 // Update primary cursor line number if this is the primary cursor
@@ -718,6 +824,74 @@ fn convert_margin_content(content: &MarginContentData) -> MarginContent {
 }
 
 impl EditorState {
+    /// Index the buffer's words for word completion, if they aren't yet and
+    /// all its text is loaded
+    pub fn ensure_word_index(&mut self) {
+        if self.word_index.is_none() {
+            if let Some(text) = self.buffer.to_string() {
+                self.word_index = Some(WordIndex::new(text.as_bytes()));
+            }
+        }
+    }
+
+    /// Take the words overlapping `range`, widened to whole words, out of
+    /// the word index; returns the widened range, if there is an index
+    fn unindex_words_around(&mut self, range: Range<usize>) -> Option<Range<usize>> {
+        self.word_index.as_ref()?;
+        let before = self
+            .buffer
+            .slice_bytes(range.start.saturating_sub(WORD_SCAN_BYTES)..range.start);
+        let after = self
+            .buffer
+            .slice_bytes(range.end..(range.end + WORD_SCAN_BYTES).min(self.buffer.len()));
+        let start = range.start
+            - before
+                .iter()
+                .rev()
+                .take_while(|&&byte| is_word_byte(byte))
+                .count();
+        let end = range.end + after.iter().take_while(|&&byte| is_word_byte(byte)).count();
+        let span = start..end;
+        let bytes = self.buffer.slice_bytes(span.clone());
+        if let Some(index) = &mut self.word_index {
+            index.remove(&bytes);
+        }
+        Some(span)
+    }
+
+    /// Count the words of `range` in the word index
+    fn index_words(&mut self, range: Range<usize>) {
+        let bytes = self.buffer.slice_bytes(range);
+        if let Some(index) = &mut self.word_index {
+            index.add(&bytes);
+        }
+    }
+
+    /// Columns `cursor` sits past the end of its line
+    ///
+    /// In virtual-space mode, and always for the corner of a block selection,
+    /// a cursor at the end of a line keeps the column it was moved to as its
+    /// sticky column; elsewhere this is always 0.
+    pub fn virtual_columns(&self, cursor: &Cursor) -> usize {
+        let virtual_space = self.virtual_space || cursor.selection_mode == SelectionMode::Block;
+        if !virtual_space || cursor.sticky_column == 0 {
+            return 0;
+        }
+        let line = self.buffer.get_line_number(cursor.position);
+        let (Some(line_start), Some(content)) = (
+            self.buffer.line_start_offset(line),
+            self.buffer.get_line(line),
+        ) else {
+            return 0;
+        };
+        let content = String::from_utf8_lossy(&content);
+        let text = content.trim_end_matches(['\n', '\r']);
+        if cursor.position != line_start + text.len() {
+            return 0;
+        }
+        cursor.sticky_column.saturating_sub(str_width(text))
+    }
+
     /// Prepare viewport for rendering (called before frame render)
     ///
     /// This pre-loads all data that will be needed for rendering the current viewport,
@@ -1164,6 +1338,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_word_index_follows_edits_in_long_words() {
+        let mut state =
+            EditorState::new(80, 24, crate::config::LARGE_FILE_THRESHOLD_BYTES as usize);
+        let cursor_id = state.cursors.primary_id();
+        let long_word = "ab".repeat(150);
+        state.apply(&Event::Insert {
+            position: 0,
+            text: format!("{} end", long_word),
+            cursor_id,
+        });
+        state.ensure_word_index();
+        let expected = |state: &EditorState| WordIndex::new(&state.buffer.get_all_text().unwrap());
+        assert_eq!(state.word_index.as_ref().unwrap().count(&long_word), 0);
+
+        // Splitting a word that's too long leaves two indexable halves
+        state.apply(&Event::Insert {
+            position: 150,
+            text: " ".to_string(),
+            cursor_id,
+        });
+        assert_eq!(
+            state.word_index.as_ref().unwrap().count(&long_word[..150]),
+            2
+        );
+        assert_eq!(state.word_index.as_ref(), Some(&expected(&state)));
+
+        // Joining them again drops both
+        state.apply(&Event::Delete {
+            range: 150..151,
+            deleted_text: " ".to_string(),
+            cursor_id,
+        });
+        assert_eq!(
+            state.word_index.as_ref().unwrap().count(&long_word[..150]),
+            0
+        );
+        assert_eq!(state.word_index.as_ref(), Some(&expected(&state)));
+    }
+
     // DocumentModel trait tests
     mod document_model_tests {
         use super::*;
@@ -100,3 +100,45 @@ pub struct LspServerConfig {
     #[serde(default)]
     pub initialization_options: Option<serde_json::Value>,
 }
+
+/// How a debug session is started by the adapter
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DebugRequestKind {
+    /// The adapter launches the debuggee itself
+    #[default]
+    Launch,
+    /// The adapter attaches to an already running process
+    Attach,
+}
+
+/// Debug adapter (DAP) configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(extend("x-display-field" = "/command"))]
+pub struct DebugAdapterConfig {
+    /// Command to spawn the debug adapter (must speak DAP over stdin/stdout)
+    pub command: String,
+
+    /// Arguments to pass to the debug adapter
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Whether the adapter is enabled
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Adapter identifier sent in the `initialize` request (e.g., "python", "lldb")
+    #[serde(default)]
+    pub adapter_id: Option<String>,
+
+    /// Whether to send a `launch` or an `attach` request
+    #[serde(default)]
+    pub request: DebugRequestKind,
+
+    /// Arguments for the launch/attach request
+    /// String values may use ${file}, ${fileDirname}, ${fileBasenameNoExtension},
+    /// ${workspaceFolder} and ${workspaceFolderBasename}, which are expanded
+    /// when the session starts
+    #[serde(default)]
+    pub configuration: serde_json::Value,
+}
@@ -428,6 +428,21 @@ impl MarginManager {
         }
     }
 
+    /// Get the current byte positions of all indicators in a namespace, sorted
+    ///
+    /// Useful for owners that keep their own line-based model (e.g. breakpoints)
+    /// and need to follow the indicators after the buffer was edited.
+    pub fn line_indicator_positions(&self, namespace: &str) -> Vec<usize> {
+        let mut positions: Vec<usize> = self
+            .line_indicators
+            .iter()
+            .filter(|(_, indicators)| indicators.contains_key(namespace))
+            .filter_map(|(&marker_id, _)| self.indicator_markers.get_position(MarkerId(marker_id)))
+            .collect();
+        positions.sort_unstable();
+        positions
+    }
+
     /// Get the line indicator for a specific line number
     ///
     /// This looks up all indicators whose markers resolve to the given line.
@@ -970,6 +985,37 @@ mod tests {
         assert!(manager.get_line_indicator(3, byte_to_line).is_some());
     }
 
+    #[test]
+    fn test_line_indicator_positions_by_namespace() {
+        let mut manager = MarginManager::new();
+        manager.set_line_indicator(
+            line_to_byte(7),
+            "breakpoints".to_string(),
+            LineIndicator::new("●", Color::Red, 20),
+        );
+        manager.set_line_indicator(
+            line_to_byte(2),
+            "breakpoints".to_string(),
+            LineIndicator::new("●", Color::Red, 20),
+        );
+        manager.set_line_indicator(
+            line_to_byte(4),
+            "git-gutter".to_string(),
+            LineIndicator::new("│", Color::Green, 10),
+        );
+
+        manager.adjust_for_insert(0, 10);
+
+        assert_eq!(
+            manager.line_indicator_positions("breakpoints"),
+            vec![line_to_byte(3), line_to_byte(8)]
+        );
+        assert_eq!(
+            manager.line_indicator_positions("git-gutter"),
+            vec![line_to_byte(5)]
+        );
+    }
+
     #[test]
     fn test_multiple_indicators_shift_together() {
         let mut manager = MarginManager::new();
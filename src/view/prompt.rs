@@ -26,6 +26,34 @@ pub enum PromptType {
     QueryReplace { search: String },
     /// Query replace confirmation prompt (y/n/!/q for each match)
     QueryReplaceConfirm,
+    /// Search for text in all project files
+    ProjectSearch,
+    /// Search for text in all project files (will prompt for replacement after)
+    ProjectReplaceSearch,
+    /// Replace text in all project files - prompt for replacement text
+    ProjectReplace { search: String },
+    /// List the lines of the buffer matching a regex
+    Occur,
+    /// Fuzzy-find a line of the buffer
+    SearchLines,
+    /// Characters to jump to, then the label of the target
+    JumpHint,
+    /// Regex to add a cursor at each match of
+    AddCursorsAtRegex,
+    /// Start and step of the numbers to insert at the cursors
+    InsertSequence,
+    /// Text to fill the rectangle with
+    FillRectangle,
+    /// Text to insert on each line of the rectangle
+    InsertRectangleText,
+    /// Start and step of the numbers to insert on the rectangle's lines
+    NumberRectangleLines,
+    /// Structural search of the project, or of the buffer
+    StructuralSearch { in_buffer: bool },
+    /// Structural search to rewrite (will prompt for the template after)
+    StructuralReplaceSearch { in_buffer: bool },
+    /// Template to rewrite the matches of a structural search with
+    StructuralReplace { pattern: String, in_buffer: bool },
     /// Execute a command by name (M-x)
     Command,
     /// Go to a specific line number
@@ -49,6 +77,16 @@ pub enum PromptType {
     RecordMacro,
     /// Play a macro - prompts for register (0-9)
     PlayMacro,
+    /// Edit a macro in a buffer - prompts for register (0-9)
+    EditMacro,
+    /// Play a macro on each selected line - prompts for register (0-9)
+    PlayMacroOnLines,
+    /// Play a macro N times - prompts for register and count ("1 5")
+    PlayMacroTimes,
+    /// Play a macro until it fails - prompts for register (0-9)
+    PlayMacroUntilFailure,
+    /// Export a macro as a plugin command - prompts for register and name
+    ExportMacro,
     /// Set a bookmark - prompts for register (0-9)
     SetBookmark,
     /// Jump to a bookmark - prompts for register (0-9)
@@ -86,10 +124,38 @@ pub enum PromptType {
     },
     /// Switch to a tab by name (from the current split's open buffers)
     SwitchToTab,
+    /// Pick a kill ring entry to yank
+    BrowseKillRing,
+    /// Pick a clipboard history entry to paste
+    PasteFromHistory,
+    /// Pick a snippet to insert
+    InsertSnippet,
+    /// Pair to wrap each selection in
+    SurroundAdd,
+    /// Pair to replace the surrounding pair with
+    SurroundChange,
+    /// Separator to join lines with
+    JoinLinesSeparator,
+    /// Regex whose capture lines are sorted by
+    SortLinesPattern,
     /// Run shell command on buffer/selection
     /// If replace is true, replace the input with the output
     /// If replace is false, output goes to a new buffer
     ShellCommand { replace: bool },
+    /// Condition for a breakpoint (empty input makes it unconditional)
+    SetBreakpointCondition {
+        path: std::path::PathBuf,
+        line: usize,
+    },
+    /// Message for a logpoint (empty input turns it back into a breakpoint)
+    SetLogpointMessage {
+        path: std::path::PathBuf,
+        line: usize,
+    },
+    /// Add a debugger watch expression
+    DebugAddWatch,
+    /// Evaluate an expression in the debug console
+    DebugEvaluate,
 }
 
 /// Prompt state for the minibuffer
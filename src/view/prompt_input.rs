@@ -135,9 +135,11 @@ impl InputHandler for Prompt {
                         let new_selected = if selected == 0 { 0 } else { selected - 1 };
                         self.selected_suggestion = Some(new_selected);
                         // For non-plugin prompts, update input to match selected suggestion
+                        // (Search Lines keeps its query)
                         if !matches!(
                             self.prompt_type,
                             crate::view::prompt::PromptType::Plugin { .. }
+                                | crate::view::prompt::PromptType::SearchLines
                         ) {
                             if let Some(suggestion) = self.suggestions.get(new_selected) {
                                 self.input = suggestion.get_value().to_string();
@@ -158,9 +160,11 @@ impl InputHandler for Prompt {
                         let new_selected = (selected + 1).min(self.suggestions.len() - 1);
                         self.selected_suggestion = Some(new_selected);
                         // For non-plugin prompts, update input to match selected suggestion
+                        // (Search Lines keeps its query)
                         if !matches!(
                             self.prompt_type,
                             crate::view::prompt::PromptType::Plugin { .. }
+                                | crate::view::prompt::PromptType::SearchLines
                         ) {
                             if let Some(suggestion) = self.suggestions.get(new_selected) {
                                 self.input = suggestion.get_value().to_string();
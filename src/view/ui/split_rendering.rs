@@ -4,6 +4,7 @@ use std::collections::BTreeMap;
 
 use crate::app::types::ViewLineMapping;
 use crate::app::BufferMetadata;
+use crate::input::rectangle::{cursor_column, Rectangle};
 use crate::model::buffer::Buffer;
 use crate::model::cursor::SelectionMode;
 use crate::model::event::{BufferId, EventLog, SplitDirection};
@@ -164,9 +165,13 @@ struct ComposeLayout {
 
 struct SelectionContext {
     ranges: Vec<Range<usize>>,
-    block_rects: Vec<(usize, usize, usize, usize)>,
+    block_rects: Vec<Rectangle>,
     cursor_positions: Vec<usize>,
     primary_cursor_position: usize,
+    /// (line, column) of each secondary cursor past the end of its line
+    virtual_cursors: Vec<(usize, usize)>,
+    /// (line, column) of the primary cursor, when past the end of its line
+    primary_virtual: Option<(usize, usize)>,
 }
 
 struct DecorationContext {
@@ -1820,54 +1825,50 @@ impl SplitRenderer {
     }
 
     fn selection_context(state: &EditorState) -> SelectionContext {
+        // Block selections are drawn as rectangles, not as the text between their ends
         let ranges: Vec<Range<usize>> = state
             .cursors
             .iter()
+            .filter(|(_, cursor)| cursor.selection_mode != SelectionMode::Block)
             .filter_map(|(_, cursor)| cursor.selection_range())
             .collect();
 
-        let block_rects: Vec<(usize, usize, usize, usize)> = state
+        let block_rects: Vec<Rectangle> = state
             .cursors
             .iter()
-            .filter_map(|(_, cursor)| {
-                if cursor.selection_mode == SelectionMode::Block {
-                    if let Some(anchor) = cursor.block_anchor {
-                        // Convert cursor position to 2D coords
-                        let cur_line = state.buffer.get_line_number(cursor.position);
-                        let cur_line_start = state.buffer.line_start_offset(cur_line).unwrap_or(0);
-                        let cur_col = cursor.position.saturating_sub(cur_line_start);
-
-                        // Return normalized rectangle (min values first)
-                        Some((
-                            anchor.line.min(cur_line),
-                            anchor.column.min(cur_col),
-                            anchor.line.max(cur_line),
-                            anchor.column.max(cur_col),
-                        ))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
+            .filter_map(|(_, cursor)| Rectangle::of_block(state, cursor))
             .collect();
 
-        let cursor_positions: Vec<usize> = if state.show_cursors {
-            state
-                .cursors
-                .iter()
-                .map(|(_, cursor)| cursor.position)
-                .collect()
-        } else {
-            Vec::new()
-        };
+        // Cursors in virtual space are drawn past the end of their line
+        let primary_id = state.cursors.primary_id();
+        let mut cursor_positions = Vec::new();
+        let mut virtual_cursors = Vec::new();
+        let mut primary_virtual = None;
+        if state.show_cursors {
+            for (cursor_id, cursor) in state.cursors.iter() {
+                if state.virtual_columns(cursor) == 0 {
+                    cursor_positions.push(cursor.position);
+                    continue;
+                }
+                let place = (
+                    state.buffer.get_line_number(cursor.position),
+                    cursor_column(state, cursor),
+                );
+                if cursor_id == primary_id {
+                    primary_virtual = Some(place);
+                } else {
+                    virtual_cursors.push(place);
+                }
+            }
+        }
 
         SelectionContext {
             ranges,
             block_rects,
             cursor_positions,
             primary_cursor_position: state.cursors.primary().position,
+            virtual_cursors,
+            primary_virtual,
         }
     }
 
@@ -1887,19 +1888,24 @@ impl SplitRenderer {
             .saturating_add(viewport_size)
             .min(state.buffer.len());
 
+        // Full-document tree, if the buffer has one (may lag the latest edit by one reparse)
+        let syntax_tree = state.syntax_tree.as_ref().and_then(|tree| tree.tree());
+
         let highlight_spans = state.highlighter.highlight_viewport(
             &state.buffer,
             highlight_start,
             highlight_end,
             theme,
             highlight_context_bytes,
+            syntax_tree,
         );
 
         // Update semantic highlighter color from theme
         state.semantic_highlighter.highlight_color = theme.semantic_highlight_bg;
 
-        let semantic_spans = state.semantic_highlighter.highlight_occurrences(
+        let semantic_spans = state.semantic_highlighter.highlight_occurrences_with_tree(
             &state.buffer,
+            syntax_tree,
             primary_cursor_position,
             viewport_start,
             viewport_end,
@@ -1994,6 +2000,8 @@ impl SplitRenderer {
         let block_selections = &selection.block_rects;
         let cursor_positions = &selection.cursor_positions;
         let primary_cursor_position = selection.primary_cursor_position;
+        let virtual_cursors = &selection.virtual_cursors;
+        let primary_virtual = selection.primary_virtual;
 
         // Compute cursor line number for relative line numbers display
         let cursor_line = state.buffer.get_line_number(primary_cursor_position);
@@ -2116,9 +2124,9 @@ impl SplitRenderer {
             );
 
             // Check if this line has any selected text
-            let mut byte_index = 0; // Byte offset in line_content string
             let mut display_char_idx = 0usize; // Character index in text (for char_source_bytes)
             let mut col_offset = 0usize; // Visual column position
+            let mut eol_indicator = false; // Whether a cursor was drawn after the line's text
 
             // Performance optimization: For very long lines, only process visible characters
             // Calculate the maximum characters we might need to render based on screen width
@@ -2181,7 +2189,6 @@ impl SplitRenderer {
                                     have_cursor = true;
                                 }
                             }
-                            byte_index += ch.len_utf8();
                             display_char_idx += 1;
                             // Note: col_offset not incremented - ANSI chars have 0 visual width
                             continue;
@@ -2193,7 +2200,7 @@ impl SplitRenderer {
                 };
 
                 // Performance: skip expensive style calculations for characters beyond visible range
-                // Use visible_char_count (not byte_index) since ANSI codes don't take up visible space
+                // Use visible_char_count (not the byte offset) since ANSI codes don't take up visible space
                 if visible_char_count > max_chars_to_process {
                     // Fast path: skip remaining characters without processing
                     // This is critical for performance with very long lines (e.g., 100KB single line)
@@ -2225,14 +2232,10 @@ impl SplitRenderer {
 
                     // Check if this character is in any selection range (but not at cursor position)
                     // Also check for block/rectangular selections
-                    let is_in_block_selection = block_selections.iter().any(
-                        |(start_line, start_col, end_line, end_col)| {
-                            current_source_line_num >= *start_line
-                                && current_source_line_num <= *end_line
-                                && byte_index >= *start_col
-                                && byte_index <= *end_col
-                        },
-                    );
+                    let is_in_block_selection = block_selections.iter().any(|rect| {
+                        rect.lines().contains(&current_source_line_num)
+                            && (rect.left..rect.right).contains(&col_offset)
+                    });
 
                     let is_selected = !is_cursor
                         && byte_pos.map_or(false, |bp| {
@@ -2399,11 +2402,11 @@ impl SplitRenderer {
                                 cursor_style,
                                 byte_pos,
                             );
+                            eol_indicator = true;
                         }
                     }
                 }
 
-                byte_index += ch.len_utf8();
                 display_char_idx += 1; // Increment character index for next lookup
                                        // col_offset tracks visual column position (for indexing into visual_to_char)
                                        // visual_to_char has one entry per visual column, not per character
@@ -2487,7 +2490,72 @@ impl SplitRenderer {
                             cursor_style,
                             None,
                         );
+                        eol_indicator = true;
+                    }
+                }
+            }
+
+            // Past the end of a source line: the rest of its block selections,
+            // and cursors in virtual space, over padding spaces
+            let ends_source_line = line_has_newline
+                || view_lines
+                    .get(view_iter_idx)
+                    .is_none_or(|next| next.line_start != LineStart::AfterBreak);
+            if !is_continuation && ends_source_line {
+                let line_num = current_source_line_num;
+                let is_virtual_cursor =
+                    |column: usize| virtual_cursors.contains(&(line_num, column));
+                let virtual_end = block_selections
+                    .iter()
+                    .filter(|rect| rect.lines().contains(&line_num))
+                    .map(|rect| rect.right)
+                    .chain(
+                        virtual_cursors
+                            .iter()
+                            .chain(primary_virtual.iter())
+                            .filter(|(line, _)| *line == line_num)
+                            .map(|(_, column)| column + 1),
+                    )
+                    .max()
+                    .unwrap_or(0);
+                let mut column = col_offset + usize::from(eol_indicator);
+                while column < virtual_end {
+                    if column >= left_col {
+                        let is_primary = primary_virtual == Some((line_num, column));
+                        let in_block = block_selections.iter().any(|rect| {
+                            rect.lines().contains(&line_num)
+                                && (rect.left..rect.right).contains(&column)
+                        });
+                        let style = if is_virtual_cursor(column) || (is_primary && !is_active) {
+                            if is_active {
+                                Style::default()
+                                    .fg(theme.editor_fg)
+                                    .bg(theme.editor_bg)
+                                    .add_modifier(Modifier::REVERSED)
+                            } else {
+                                Style::default()
+                                    .fg(theme.editor_fg)
+                                    .bg(theme.inactive_cursor)
+                            }
+                        } else if in_block {
+                            Style::default().fg(theme.editor_fg).bg(theme.selection_bg)
+                        } else {
+                            Style::default()
+                        };
+                        if is_primary {
+                            cursor_screen_x = line_view_map.len() as u16;
+                            cursor_screen_y = lines.len() as u16;
+                            have_cursor = true;
+                        }
+                        push_span_with_map(
+                            &mut line_spans,
+                            &mut line_view_map,
+                            " ".to_string(),
+                            style,
+                            None,
+                        );
                     }
+                    column += 1;
                 }
             }
 
@@ -2541,7 +2609,7 @@ impl SplitRenderer {
                 if line_has_newline && line_len_chars > 0 {
                     let newline_idx = line_len_chars.saturating_sub(1);
                     if let Some(Some(src_newline)) = line_char_source_bytes.get(newline_idx) {
-                        if *src_newline == primary_cursor_position {
+                        if *src_newline == primary_cursor_position && primary_virtual.is_none() {
                             // Cursor position now includes gutter width (consistent with main cursor tracking)
                             // For empty lines (just newline), cursor should be at gutter width (after gutter)
                             // For lines with content, cursor on newline should be after the content
@@ -2758,9 +2826,10 @@ impl SplitRenderer {
             state.buffer.len()
         );
 
-        if !selection
-            .cursor_positions
-            .contains(&selection.primary_cursor_position)
+        if selection.primary_virtual.is_none()
+            && !selection
+                .cursor_positions
+                .contains(&selection.primary_cursor_position)
         {
             tracing::warn!(
                 "Primary cursor position {} not found in cursor_positions list: {:?}",
@@ -163,6 +163,7 @@ impl StatusBarRenderer {
     /// * `display_name` - The display name for the file (project-relative path)
     /// * `chord_state` - Current chord sequence state (for multi-key bindings)
     /// * `update_available` - Optional new version string if an update is available
+    /// * `mode_indicator` - Vi mode (and partially typed command), shown first
     pub fn render_status_bar(
         frame: &mut Frame,
         area: Rect,
@@ -175,6 +176,7 @@ impl StatusBarRenderer {
         keybindings: &crate::input::keybindings::KeybindingResolver,
         chord_state: &[(crossterm::event::KeyCode, crossterm::event::KeyModifiers)],
         update_available: Option<&str>,
+        mode_indicator: Option<&str>,
     ) {
         Self::render_status(
             frame,
@@ -188,6 +190,7 @@ impl StatusBarRenderer {
             keybindings,
             chord_state,
             update_available,
+            mode_indicator,
         );
     }
 
@@ -352,6 +355,7 @@ impl StatusBarRenderer {
         keybindings: &crate::input::keybindings::KeybindingResolver,
         chord_state: &[(crossterm::event::KeyCode, crossterm::event::KeyModifiers)],
         update_available: Option<&str>,
+        mode_indicator: Option<&str>,
     ) {
         // Use the pre-computed display name from buffer metadata
         let filename = display_name;
@@ -468,7 +472,10 @@ impl StatusBarRenderer {
         let base_status = format!(
             "{filename}{modified} | Ln {line}, Col {col}{diagnostics_summary}{cursor_count_indicator}{lsp_indicator}"
         );
-        let left_status = format!("{base_status}{chord_display}{message_suffix}");
+        let mode_prefix = mode_indicator
+            .map(|mode| format!("{mode} | "))
+            .unwrap_or_default();
+        let left_status = format!("{mode_prefix}{base_status}{chord_display}{message_suffix}");
 
         // Build update indicator for right side (if update available)
         let update_indicator = update_available.map(|version| format!(" Update: v{} ", version));
@@ -670,6 +677,8 @@ impl StatusBarRenderer {
     /// - Whole Word (Alt+W)
     /// - Regex (Alt+R)
     /// - Confirm Each (Alt+I) - only shown in replace mode
+    /// - Preserve Case (Alt+P) - only shown in replace mode
+    #[allow(clippy::too_many_arguments)]
     pub fn render_search_options(
         frame: &mut Frame,
         area: Rect,
@@ -677,6 +686,7 @@ impl StatusBarRenderer {
         whole_word: bool,
         use_regex: bool,
         confirm_each: Option<bool>, // None = don't show, Some(value) = show with this state
+        preserve_case: Option<bool>,
         theme: &crate::view::theme::Theme,
         keybindings: &crate::input::keybindings::KeybindingResolver,
     ) {
@@ -786,6 +796,29 @@ impl StatusBarRenderer {
             }
         }
 
+        // Preserve Case option (only shown in replace mode)
+        if let Some(preserve_value) = preserve_case {
+            let preserve_shortcut =
+                get_shortcut(&crate::input::keybindings::Action::ToggleSearchPreserveCase);
+            let preserve_checkbox = if preserve_value { "[x]" } else { "[ ]" };
+
+            // Separator
+            spans.push(Span::styled("   ", base_style));
+
+            spans.push(Span::styled(
+                preserve_checkbox,
+                if preserve_value {
+                    active_style
+                } else {
+                    base_style
+                },
+            ));
+            spans.push(Span::styled(" Preserve case", base_style));
+            if let Some(shortcut) = &preserve_shortcut {
+                spans.push(Span::styled(format!(" ({})", shortcut), shortcut_style));
+            }
+        }
+
         // Fill remaining space
         let current_width: usize = spans.iter().map(|s| s.content.len()).sum();
         let available_width = area.width as usize;
@@ -0,0 +1,144 @@
+//! Fake debug adapter for E2E testing
+//!
+//! A Bash script that speaks just enough of the Debug Adapter Protocol to
+//! drive a session: it stops at line 2 of the launched `program` once
+//! configuration is done, steps one line per `next`, and terminates on
+//! `continue`.
+
+/// A fake debug adapter script for testing
+pub struct FakeDapServer;
+
+impl FakeDapServer {
+    /// Write the adapter script to the temp directory
+    ///
+    /// The `program` launch argument is reported back as the source of the
+    /// single stack frame, so tests should launch with `{"program": "${file}"}`.
+    pub fn spawn() -> std::io::Result<Self> {
+        let script = r#"#!/bin/bash
+
+read_message() {
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+send_message() {
+    local message="$1"
+    local length=${#message}
+    echo -en "Content-Length: $length\r\n\r\n$message"
+}
+
+out_seq=1000
+respond() {
+    # respond <request_seq> <command> <body>
+    out_seq=$((out_seq + 1))
+    send_message '{"seq":'$out_seq',"type":"response","request_seq":'"$1"',"success":true,"command":"'"$2"'","body":'"$3"'}'
+}
+
+event() {
+    # event <name> <body>
+    out_seq=$((out_seq + 1))
+    send_message '{"seq":'$out_seq',"type":"event","event":"'"$1"'","body":'"$2"'}'
+}
+
+program=""
+line=2
+
+while true; do
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    command=$(echo "$msg" | grep -o '"command":"[^"]*"' | head -1 | cut -d'"' -f4)
+    seq=$(echo "$msg" | grep -o '"seq":[0-9]*' | head -1 | cut -d':' -f2)
+
+    case "$command" in
+        "initialize")
+            respond $seq initialize '{"supportsConfigurationDoneRequest":true}'
+            ;;
+        "launch"|"attach")
+            program=$(echo "$msg" | grep -o '"program":"[^"]*"' | head -1 | cut -d'"' -f4)
+            respond $seq $command '{}'
+            event output '{"category":"stdout","output":"hello from fake adapter"}'
+            event initialized '{}'
+            ;;
+        "setBreakpoints")
+            count=$(echo "$msg" | grep -o '"line":' | wc -l)
+            bps=""
+            for _ in $(seq 1 $count); do
+                bps="$bps{\"verified\":true},"
+            done
+            respond $seq setBreakpoints '{"breakpoints":['"${bps%,}"']}'
+            ;;
+        "configurationDone")
+            respond $seq configurationDone '{}'
+            event stopped '{"reason":"breakpoint","threadId":1}'
+            ;;
+        "threads")
+            respond $seq threads '{"threads":[{"id":1,"name":"main"}]}'
+            ;;
+        "stackTrace")
+            respond $seq stackTrace '{"stackFrames":[{"id":1,"name":"main","source":{"name":"'"$(basename "$program")"'","path":"'"$program"'"},"line":'$line',"column":1}],"totalFrames":1}'
+            ;;
+        "scopes")
+            respond $seq scopes '{"scopes":[{"name":"Locals","variablesReference":100,"expensive":false}]}'
+            ;;
+        "variables")
+            respond $seq variables '{"variables":[{"name":"answer","value":"'$((40 + line))'","type":"int","variablesReference":0}]}'
+            ;;
+        "evaluate")
+            respond $seq evaluate '{"result":"'$((40 + line))'","variablesReference":0}'
+            ;;
+        "next"|"stepIn"|"stepOut")
+            line=$((line + 1))
+            respond $seq $command '{}'
+            event stopped '{"reason":"step","threadId":1}'
+            ;;
+        "continue")
+            respond $seq continue '{"allThreadsContinued":true}'
+            event exited '{"exitCode":0}'
+            event terminated '{}'
+            ;;
+        "pause")
+            respond $seq pause '{}'
+            ;;
+        "disconnect")
+            respond $seq disconnect '{}'
+            break
+            ;;
+    esac
+done
+"#;
+
+        let script_path = Self::script_path();
+        std::fs::write(&script_path, script)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        Ok(Self)
+    }
+
+    /// Get the path to the fake debug adapter script
+    pub fn script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_dap_server.sh")
+    }
+}
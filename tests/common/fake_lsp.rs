@@ -829,6 +829,110 @@ done
         std::env::temp_dir().join("fake_lsp_server_inlay_hints.sh")
     }
 
+    /// Spawn a fake LSP server that supports selection ranges (textDocument/selectionRange)
+    ///
+    /// This version answers every textDocument/selectionRange request with the
+    /// same chain: (0,2)-(0,7) inside (0,0)-(0,11).
+    pub fn spawn_with_selection_range() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        // Create a Bash script that supports selection ranges
+        let script = r#"#!/bin/bash
+
+# Function to read a message
+read_message() {
+    # Read headers
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        # Empty line marks end of headers
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    # Read content
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+# Function to send a message
+send_message() {
+    local message="$1"
+    local length=${#message}
+    echo -en "Content-Length: $length\r\n\r\n$message"
+}
+
+# Main loop
+while true; do
+    # Read incoming message
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    # Extract method from JSON
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            # Send initialize response with selectionRangeProvider capability
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"selectionRangeProvider":true}}}'
+            ;;
+        "initialized")
+            # No response needed for notification
+            ;;
+        "textDocument/didOpen"|"textDocument/didChange"|"textDocument/didSave")
+            # No response for notifications
+            ;;
+        "textDocument/inlayHint")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[]}'
+            ;;
+        "textDocument/selectionRange")
+            # One chain for the (single) requested position
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{"range":{"start":{"line":0,"character":2},"end":{"line":0,"character":7}},"parent":{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":11}}}}]}'
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+    esac
+done
+"#;
+
+        // Write script to a temporary file
+        let script_path = std::env::temp_dir().join("fake_lsp_server_selection_range.sh");
+        std::fs::write(&script_path, script)?;
+
+        // Make it executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            // Wait for stop signal
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the selection range fake LSP server script
+    pub fn selection_range_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_selection_range.sh")
+    }
+
     /// Spawn a fake LSP server that logs all received methods to a file
     ///
     /// This variant logs each method name to a log file, which can be used
@@ -419,6 +419,54 @@ impl EditorTestHarness {
         )
     }
 
+    /// Create with `contents` written to `file_name` in `dir` and opened.
+    /// Language servers are left out of the default config.
+    pub fn with_file(
+        width: u16,
+        height: u16,
+        dir: &Path,
+        file_name: &str,
+        contents: &str,
+    ) -> io::Result<Self> {
+        Self::with_file_and_config(width, height, dir, file_name, contents, Config::default())
+    }
+
+    /// Create with custom config and `contents` written to `file_name` in
+    /// `dir` and opened. Language servers are left out of the config.
+    pub fn with_file_and_config(
+        width: u16,
+        height: u16,
+        dir: &Path,
+        file_name: &str,
+        contents: &str,
+        mut config: Config,
+    ) -> io::Result<Self> {
+        config.lsp.clear();
+        let mut harness = Self::with_config(width, height, config)?;
+        harness.open_new_file(dir, file_name, contents)?;
+        Ok(harness)
+    }
+
+    /// Create with custom config and `contents` written to `file_name` in
+    /// `dir` and opened, with the editor's state directories under `dir` so
+    /// a later harness on the same `dir` sees what this one saved. Language
+    /// servers are left out of the config.
+    pub fn with_file_and_shared_state(
+        width: u16,
+        height: u16,
+        dir: &Path,
+        file_name: &str,
+        contents: &str,
+        mut config: Config,
+    ) -> io::Result<Self> {
+        config.lsp.clear();
+        let dir_context = DirectoryContext::for_testing(dir);
+        let mut harness =
+            Self::with_shared_dir_context(width, height, config, dir.to_path_buf(), dir_context)?;
+        harness.open_new_file(dir, file_name, contents)?;
+        Ok(harness)
+    }
+
     /// Create new test harness with line wrapping disabled.
     pub fn new_no_wrap(width: u16, height: u16) -> io::Result<Self> {
         let mut config = Config::default();
@@ -544,6 +592,13 @@ impl EditorTestHarness {
         Ok(())
     }
 
+    /// Write `contents` to `file_name` in `dir` and open it
+    pub fn open_new_file(&mut self, dir: &Path, file_name: &str, contents: &str) -> io::Result<()> {
+        let path = dir.join(file_name);
+        std::fs::write(&path, contents)?;
+        self.open_file(&path)
+    }
+
     /// Load text content into the editor by creating a temporary file and opening it
     /// This is much faster than type_text() for large amounts of text in tests
     /// Returns a TestFixture that must be kept alive for the duration of the test
@@ -622,6 +677,21 @@ impl EditorTestHarness {
         Ok(())
     }
 
+    /// Run `command` from the command palette
+    pub fn run_command(&mut self, command: &str) -> io::Result<()> {
+        self.send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)?;
+        self.type_text(command)?;
+        self.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+        self.render()
+    }
+
+    /// Type `input` into the open prompt and confirm it
+    pub fn answer_prompt(&mut self, input: &str) -> io::Result<()> {
+        self.type_text(input)?;
+        self.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+        self.render()
+    }
+
     /// Simulate a mouse event
     pub fn send_mouse(&mut self, mouse_event: MouseEvent) -> io::Result<()> {
         // Delegate to the editor's handle_mouse method (just like main.rs does)
@@ -1434,6 +1504,17 @@ impl EditorTestHarness {
         self.wait_until(|h| !h.editor().is_prompting())
     }
 
+    /// Wait for the active buffer's syntax tree to catch up with its contents
+    pub fn wait_for_syntax_tree(&mut self) -> io::Result<()> {
+        self.wait_until(|h| {
+            h.editor()
+                .active_state()
+                .syntax_tree
+                .as_ref()
+                .is_some_and(|tree| tree.current_tree().is_some())
+        })
+    }
+
     /// Wait for screen to contain specific text
     pub fn wait_for_screen_contains(&mut self, text: &str) -> io::Result<()> {
         let text = text.to_string();
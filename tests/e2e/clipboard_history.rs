@@ -0,0 +1,174 @@
+//! End-to-end tests for clipboard history and Paste from History
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use tempfile::TempDir;
+
+/// Create a harness whose clipboard never touches the system clipboard
+fn harness_with_internal_clipboard() -> EditorTestHarness {
+    let mut harness = EditorTestHarness::new(120, 24).unwrap();
+    harness.editor_mut().set_clipboard_for_test(String::new());
+    harness
+}
+
+/// Select from the cursor to the end of its line and copy
+fn copy_to_line_end(harness: &mut EditorTestHarness) {
+    harness.send_key(KeyCode::End, KeyModifiers::SHIFT).unwrap();
+    harness
+        .send_key(KeyCode::Char('c'), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn open_history(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(
+            KeyCode::Char('v'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        )
+        .unwrap();
+}
+
+/// The picker lists earlier copies, newest first, and pastes the chosen one
+#[test]
+fn test_paste_from_history_picks_older_copy() {
+    let mut harness = harness_with_internal_clipboard();
+    let _fixture = harness
+        .load_buffer_from_text("first line\nsecond line\n")
+        .unwrap();
+
+    copy_to_line_end(&mut harness);
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    copy_to_line_end(&mut harness);
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+
+    open_history(&mut harness);
+    harness.assert_screen_contains("Paste from history:");
+    harness.assert_screen_contains("first line");
+    harness.assert_screen_contains("second line");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("first line\nsecond line\nfirst line");
+
+    // The pasted entry is the clipboard content again
+    harness
+        .send_key(KeyCode::Char('v'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("first line\nsecond line\nfirst linefirst line");
+}
+
+/// Typing in the picker filters entries by fuzzy match
+#[test]
+fn test_paste_from_history_filters() {
+    let mut harness = harness_with_internal_clipboard();
+    let _fixture = harness.load_buffer_from_text("alpha\nbeta\n").unwrap();
+
+    copy_to_line_end(&mut harness);
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    copy_to_line_end(&mut harness);
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+
+    open_history(&mut harness);
+    harness.type_text("alp").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("alpha\nbeta\nalpha");
+}
+
+/// A multi-cursor copy pasted into as many cursors puts each piece at its own cursor
+#[test]
+fn test_multi_cursor_copy_paste_distributes_pieces() {
+    let mut harness = harness_with_internal_clipboard();
+    let _fixture = harness.load_buffer_from_text("ab\ncd\n").unwrap();
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::ALT)
+        .unwrap();
+    copy_to_line_end(&mut harness);
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Char('v'), KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness.assert_buffer_content("abab\ncdcd\n");
+}
+
+/// With a different number of cursors, every cursor gets the whole entry
+#[test]
+fn test_multi_cursor_entry_into_single_cursor() {
+    let mut harness = harness_with_internal_clipboard();
+    let _fixture = harness.load_buffer_from_text("ab\ncd\n").unwrap();
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::ALT)
+        .unwrap();
+    copy_to_line_end(&mut harness);
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+
+    open_history(&mut harness);
+    harness.assert_screen_contains("2 cursors");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    harness.assert_buffer_content("ab\ncd\nab\ncd");
+}
+
+/// Clipboard history is saved in the session and restored with it
+#[test]
+fn test_clipboard_history_persists_in_session() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let file = project_dir.join("copy.txt");
+    std::fs::write(&file, "Copy me\n").unwrap();
+
+    {
+        let mut harness = EditorTestHarness::with_config_and_working_dir(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+        )
+        .unwrap();
+        harness.editor_mut().set_clipboard_for_test(String::new());
+        harness.open_file(&file).unwrap();
+
+        copy_to_line_end(&mut harness);
+        harness.editor_mut().save_session().unwrap();
+    }
+
+    {
+        let mut harness = EditorTestHarness::with_config_and_working_dir(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+        )
+        .unwrap();
+        harness.editor_mut().set_clipboard_for_test(String::new());
+        assert!(harness.editor_mut().try_restore_session().unwrap());
+
+        harness
+            .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+            .unwrap();
+        open_history(&mut harness);
+        harness
+            .send_key(KeyCode::Enter, KeyModifiers::NONE)
+            .unwrap();
+        harness.assert_buffer_content("Copy meCopy me\n");
+    }
+}
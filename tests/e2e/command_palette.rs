@@ -703,8 +703,11 @@ fn test_command_palette_shows_shortcuts() {
     // Add Cursor Below should show Ctrl+Alt+↓
     harness.assert_screen_contains("Add Cursor Below");
 
-    // Copy should show Ctrl+C (or ⌘+C on macOS)
+    // Copy should show Ctrl+C (or ⌘+C on macOS); it's past the first page
+    harness.type_text("Copy").unwrap();
+    harness.render().unwrap();
     harness.assert_screen_contains("Copy");
+    let screen = harness.screen_to_string();
     assert!(
         screen.contains("Ctrl+C") || screen.contains("⌘+C"),
         "Should show shortcut for Copy"
@@ -0,0 +1,124 @@
+//! End-to-end tests for line and block comment toggling
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+fn toggle_comment(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('/'), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn toggle_block_comment(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+}
+
+/// Line comments on the selected lines line up at the smallest indentation,
+/// and toggling again removes them
+#[test]
+fn test_line_comments_align_at_smallest_indent() {
+    let temp_dir = TempDir::new().unwrap();
+    let text = "fn f() {\n    if x {\n        y();\n    }\n}\n";
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "lib.rs", text).unwrap();
+
+    // Select the three lines of the `if`; the selection ends at the start of
+    // the closing brace's line, which isn't commented
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    toggle_comment(&mut harness);
+    harness.assert_buffer_content("fn f() {\n    // if x {\n    //     y();\n    // }\n}\n");
+
+    toggle_comment(&mut harness);
+    harness.assert_buffer_content(text);
+}
+
+/// Uncommenting recognizes block comments as well as line comments
+#[test]
+fn test_uncomment_recognizes_block_comments() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "main.c", "/* a(); */\n// b();\n")
+            .unwrap();
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    toggle_comment(&mut harness);
+    harness.assert_buffer_content("a();\nb();\n");
+}
+
+/// A block comment wraps the selection, or the cursor's line without one,
+/// and is removed by toggling again
+#[test]
+fn test_toggle_block_comment() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "lib.rs",
+        "let x = a + b;\n    y();\n",
+    )
+    .unwrap();
+
+    for _ in 0..8 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    for _ in 0..5 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    toggle_block_comment(&mut harness);
+    harness.assert_buffer_content("let x = /* a + b */;\n    y();\n");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    toggle_block_comment(&mut harness);
+    harness.assert_buffer_content("let x = /* a + b */;\n    /* y(); */\n");
+    toggle_block_comment(&mut harness);
+    harness.assert_buffer_content("let x = /* a + b */;\n    y();\n");
+}
+
+/// Languages without block comments get a line comment instead
+#[test]
+fn test_block_comment_falls_back_to_line_comment() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "script.py", "  x = 1\n").unwrap();
+
+    toggle_block_comment(&mut harness);
+    harness.assert_buffer_content("  # x = 1\n");
+}
+
+/// Code injected into another language is commented in its own syntax
+#[test]
+fn test_comment_uses_injected_language() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "page.html",
+        "<script>\n  let a = 1;\n</script>\n<p>hi</p>\n",
+    )
+    .unwrap();
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    toggle_comment(&mut harness);
+    harness.assert_buffer_content("<script>\n  // let a = 1;\n</script>\n<p>hi</p>\n");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    toggle_comment(&mut harness);
+    harness.assert_buffer_content("<script>\n  // let a = 1;\n</script>\n<!-- <p>hi</p> -->\n");
+}
@@ -0,0 +1,194 @@
+use crate::common::fake_dap::FakeDapServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let answer = 42;\n    println!(\"{}\", answer);\n}\n";
+
+/// Whether the screen row showing `text` also shows `symbol` (in the margin)
+fn row_has(harness: &EditorTestHarness, text: &str, symbol: &str) -> bool {
+    harness
+        .screen_to_string()
+        .lines()
+        .any(|line| line.contains(text) && line.contains(symbol))
+}
+
+/// Test that F9 toggles a breakpoint indicator on the cursor line
+#[test]
+fn test_toggle_breakpoint_indicator() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, SOURCE).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+
+    harness.send_key(KeyCode::F(9), KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Breakpoint set at line 2");
+    assert!(row_has(&harness, "let answer", "●"));
+    assert!(!row_has(&harness, "println!", "●"));
+
+    harness.send_key(KeyCode::F(9), KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Breakpoint removed from line 2");
+    assert!(!row_has(&harness, "let answer", "●"));
+}
+
+/// Test that breakpoints stay on their line when lines are inserted above
+#[test]
+fn test_breakpoint_follows_edits() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, SOURCE).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::F(9), KeyModifiers::NONE).unwrap();
+
+    // Insert a line at the top of the file
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(row_has(&harness, "let answer", "●"));
+
+    // The breakpoint is now on line 3
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::F(9), KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(
+        status.contains("Breakpoint removed from line 3"),
+        "{}",
+        status
+    );
+}
+
+/// Test that a conditional breakpoint is set through the prompt
+#[test]
+fn test_conditional_breakpoint_prompt() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, SOURCE).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+
+    harness.editor_mut().prompt_conditional_breakpoint();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Breakpoint condition (line 2)");
+
+    harness.type_text("answer > 1").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Conditional breakpoint set at line 2");
+    assert!(row_has(&harness, "let answer", "◉"));
+}
+
+/// Test a debug session against the fake adapter: stop at a breakpoint,
+/// step over, and stop the session
+#[test]
+#[cfg_attr(not(unix), ignore = "Fake debug adapter is a bash script")]
+fn test_debug_session_stop_step_and_stop() -> std::io::Result<()> {
+    let _fake_adapter = FakeDapServer::spawn()?;
+
+    let temp_dir = tempfile::tempdir()?;
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, SOURCE)?;
+
+    let mut config = fresh::config::Config::default();
+    config.debuggers.insert(
+        "rust".to_string(),
+        fresh::services::dap::DebugAdapterConfig {
+            command: FakeDapServer::script_path().to_string_lossy().to_string(),
+            args: vec![],
+            enabled: true,
+            adapter_id: Some("fake".to_string()),
+            request: fresh::services::dap::DebugRequestKind::Launch,
+            configuration: serde_json::json!({ "program": "${file}" }),
+        },
+    );
+
+    let mut harness = EditorTestHarness::with_config_and_working_dir(
+        120,
+        30,
+        config,
+        temp_dir.path().to_path_buf(),
+    )?;
+    harness.open_file(&file_path)?;
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE)?;
+    harness.send_key(KeyCode::F(9), KeyModifiers::NONE)?;
+
+    // F6 starts a session when none is running; the fake adapter stops at line 2
+    harness.send_key(KeyCode::F(6), KeyModifiers::NONE)?;
+    harness.wait_until(|h| h.screen_to_string().contains("paused (breakpoint) in main"))?;
+    harness.assert_screen_contains("*Debug Call Stack*");
+    harness.assert_screen_contains("▶ main  test.rs:2");
+
+    // Step over moves to line 3
+    harness.send_key(KeyCode::F(7), KeyModifiers::NONE)?;
+    harness.wait_until(|h| h.screen_to_string().contains("▶ main  test.rs:3"))?;
+    harness.assert_screen_contains("paused (step) in main");
+
+    // Shift+F6 ends the session
+    harness.send_key(KeyCode::F(6), KeyModifiers::SHIFT)?;
+    harness.render()?;
+    harness.assert_screen_contains("Debug session stopped");
+    harness.assert_screen_contains("(not paused)");
+
+    Ok(())
+}
+
+/// Test that the program running to completion ends the session
+#[test]
+#[cfg_attr(not(unix), ignore = "Fake debug adapter is a bash script")]
+fn test_debug_session_ends_when_program_exits() -> std::io::Result<()> {
+    let _fake_adapter = FakeDapServer::spawn()?;
+
+    let temp_dir = tempfile::tempdir()?;
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, SOURCE)?;
+
+    let mut config = fresh::config::Config::default();
+    config.debuggers.insert(
+        "rust".to_string(),
+        fresh::services::dap::DebugAdapterConfig {
+            command: FakeDapServer::script_path().to_string_lossy().to_string(),
+            args: vec![],
+            enabled: true,
+            adapter_id: None,
+            request: fresh::services::dap::DebugRequestKind::Launch,
+            configuration: serde_json::json!({ "program": "${file}" }),
+        },
+    );
+
+    let mut harness = EditorTestHarness::with_config_and_working_dir(
+        120,
+        30,
+        config,
+        temp_dir.path().to_path_buf(),
+    )?;
+    harness.open_file(&file_path)?;
+
+    harness.send_key(KeyCode::F(6), KeyModifiers::NONE)?;
+    harness.wait_until(|h| h.screen_to_string().contains("paused (breakpoint)"))?;
+
+    // Continue: the fake adapter exits the program
+    harness.send_key(KeyCode::F(6), KeyModifiers::NONE)?;
+    harness.wait_until(|h| h.screen_to_string().contains("Debug session ended"))?;
+
+    Ok(())
+}
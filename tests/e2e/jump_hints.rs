@@ -0,0 +1,110 @@
+//! End-to-end tests for jump hints
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::fs;
+use tempfile::TempDir;
+
+const CONTENT: &str = "alpha beta\ngamma delta\n";
+
+fn type_keys(harness: &mut EditorTestHarness, keys: &str) {
+    harness.type_text(keys).unwrap();
+    harness.render().unwrap();
+}
+
+/// Each visible occurrence gets a label, nearest the cursor first, and
+/// typing one jumps there
+#[test]
+fn test_jump_to_character() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(CONTENT).unwrap();
+
+    harness.run_command("Jump to Character").unwrap();
+    type_keys(&mut harness, "a");
+    // The a's of "gamma" are the 4th and 5th nearest: labels f and g, each
+    // drawn before its target
+    harness.assert_screen_contains("gf ammg a");
+    harness.assert_buffer_content(CONTENT);
+
+    type_keys(&mut harness, "g");
+    assert_eq!(harness.cursor_position(), CONTENT.find("a delta").unwrap());
+    harness.assert_screen_contains("gamma delta");
+    assert!(!harness.has_selection());
+}
+
+/// A label typed in upper case extends the selection to the target
+#[test]
+fn test_jump_to_line_extends_selection() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(CONTENT).unwrap();
+
+    harness.run_command("Jump to Line").unwrap();
+    type_keys(&mut harness, "S");
+    assert_eq!(harness.cursor_position(), 11);
+    assert_eq!(harness.get_selection_range(), Some(0..11));
+}
+
+/// A single match is jumped to at once, and a key that is no label is
+/// ignored
+#[test]
+fn test_jump_to_word_single_match_and_unknown_label() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(CONTENT).unwrap();
+
+    harness.run_command("Jump to Word").unwrap();
+    type_keys(&mut harness, "d");
+    assert_eq!(harness.cursor_position(), CONTENT.find("delta").unwrap());
+
+    harness.run_command("Jump to Two Characters").unwrap();
+    type_keys(&mut harness, "taz");
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("No hint labelled 'z'"), "{}", status);
+    type_keys(&mut harness, "s");
+    assert_eq!(harness.cursor_position(), CONTENT.find("ta\n").unwrap());
+}
+
+/// Each label typed adds a cursor, until Enter
+#[test]
+fn test_add_cursor_at_hint() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(CONTENT).unwrap();
+
+    // The a's are labelled a, s, d, f, g and h from the cursor on
+    harness.run_command("Add Cursor at Hint").unwrap();
+    type_keys(&mut harness, "a");
+    type_keys(&mut harness, "s");
+    assert_eq!(harness.cursor_count(), 2);
+    type_keys(&mut harness, "h");
+    assert_eq!(harness.cursor_count(), 3);
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    type_keys(&mut harness, "_");
+    harness.assert_buffer_content("_alph_a beta\ngamma delt_a\n");
+}
+
+/// Targets in other splits are labelled too, and jumping to one focuses
+/// its split
+#[test]
+fn test_jump_into_other_split() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = temp_dir.path().join("first.txt");
+    let second = temp_dir.path().join("second.txt");
+    fs::write(&first, "zebra crossing\n").unwrap();
+    fs::write(&second, CONTENT).unwrap();
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&first).unwrap();
+    harness.run_command("Split Vertical").unwrap();
+    harness.open_file(&second).unwrap();
+    harness.assert_buffer_content(CONTENT);
+
+    harness.run_command("Jump to Character").unwrap();
+    type_keys(&mut harness, "c");
+    harness.assert_buffer_content("zebra crossing\n");
+    assert_eq!(harness.cursor_position(), 6);
+}
@@ -0,0 +1,142 @@
+//! End-to-end tests for the Emacs kill ring
+//!
+//! These tests use the Emacs keybinding style, where C-k, M-d and M-DEL kill
+//! into the ring, C-y yanks and M-y cycles the yanked text.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+
+/// Create a harness with Emacs keybinding style and an isolated clipboard
+fn emacs_harness(width: u16, height: u16) -> EditorTestHarness {
+    let config = Config {
+        active_keybinding_map: "emacs".into(),
+        ..Default::default()
+    };
+    let mut harness = EditorTestHarness::with_config(width, height, config).unwrap();
+    // Keep the system clipboard of the machine running the tests out of the ring
+    harness.editor_mut().set_clipboard_for_test(String::new());
+    harness
+}
+
+fn ctrl(harness: &mut EditorTestHarness, c: char) {
+    harness
+        .send_key(KeyCode::Char(c), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn alt(harness: &mut EditorTestHarness, c: char) {
+    harness
+        .send_key(KeyCode::Char(c), KeyModifiers::ALT)
+        .unwrap();
+}
+
+/// Consecutive C-k presses build a single entry that C-y yanks back at once
+#[test]
+fn test_consecutive_kills_append() {
+    let mut harness = emacs_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("one\ntwo\nthree").unwrap();
+
+    ctrl(&mut harness, 'k');
+    ctrl(&mut harness, 'k');
+    ctrl(&mut harness, 'k');
+    harness.assert_buffer_content("\nthree");
+
+    ctrl(&mut harness, 'y');
+    harness.assert_buffer_content("one\ntwo\nthree");
+}
+
+/// A command between kills starts a new entry, and M-y cycles through them
+#[test]
+fn test_yank_pop_cycles_entries() {
+    let mut harness = emacs_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("first\nsecond\n").unwrap();
+
+    ctrl(&mut harness, 'k');
+    ctrl(&mut harness, 'n');
+    ctrl(&mut harness, 'k');
+    harness.assert_buffer_content("\n\n");
+
+    ctrl(&mut harness, 'y');
+    harness.assert_buffer_content("\nsecond\n");
+
+    alt(&mut harness, 'y');
+    harness.assert_buffer_content("\nfirst\n");
+
+    // Wraps around to the newest entry
+    alt(&mut harness, 'y');
+    harness.assert_buffer_content("\nsecond\n");
+
+    // The yank and each yank-pop undo separately
+    ctrl(&mut harness, '/');
+    harness.assert_buffer_content("\nfirst\n");
+}
+
+/// M-y only works straight after a yank
+#[test]
+fn test_yank_pop_requires_yank() {
+    let mut harness = emacs_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("text").unwrap();
+
+    ctrl(&mut harness, 'k');
+    ctrl(&mut harness, 'y');
+    ctrl(&mut harness, 'a');
+    alt(&mut harness, 'y');
+
+    harness.assert_buffer_content("text");
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("Previous command was not a yank")
+    );
+}
+
+/// Backward word kills prepend to the entry
+#[test]
+fn test_backward_kills_prepend() {
+    let mut harness = emacs_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("alpha beta").unwrap();
+
+    ctrl(&mut harness, 'e');
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::ALT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::ALT)
+        .unwrap();
+    harness.assert_buffer_content("");
+
+    ctrl(&mut harness, 'y');
+    harness.assert_buffer_content("alpha beta");
+}
+
+/// Text copied elsewhere becomes the newest entry, older kills stay reachable
+#[test]
+fn test_yank_picks_up_clipboard() {
+    let mut harness = emacs_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("killed\n").unwrap();
+
+    ctrl(&mut harness, 'k');
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("outside".to_string());
+
+    ctrl(&mut harness, 'y');
+    harness.assert_buffer_content("outside\n");
+
+    alt(&mut harness, 'y');
+    harness.assert_buffer_content("killed\n");
+}
+
+/// A multi-cursor kill keeps one piece per cursor and yanks each back in place
+#[test]
+fn test_multi_cursor_kill_and_yank() {
+    let mut harness = emacs_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("x one\ny two").unwrap();
+
+    harness.editor_mut().add_cursor_below();
+    ctrl(&mut harness, 'k');
+    harness.assert_buffer_content("\n");
+
+    ctrl(&mut harness, 'y');
+    harness.assert_buffer_content("x one\ny two");
+}
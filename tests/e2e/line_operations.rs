@@ -0,0 +1,208 @@
+//! End-to-end tests for moving, duplicating, joining and sorting lines
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+/// Moving a line keeps the cursor's column, and one undo moves it back
+#[test]
+fn test_move_line_keeps_column() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "lines.txt",
+        "alpha\nbravo\ncharlie",
+    )
+    .unwrap();
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+
+    harness.send_key(KeyCode::Down, KeyModifiers::ALT).unwrap();
+    harness.assert_buffer_content("alpha\ncharlie\nbravo");
+    assert_eq!(harness.cursor_position(), "alpha\ncharlie\nbr".len());
+
+    harness.send_key(KeyCode::Up, KeyModifiers::ALT).unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::ALT).unwrap();
+    harness.assert_buffer_content("bravo\nalpha\ncharlie");
+    assert_eq!(harness.cursor_position(), 2);
+
+    // Moving past the first line does nothing
+    harness.send_key(KeyCode::Up, KeyModifiers::ALT).unwrap();
+    harness.assert_buffer_content("bravo\nalpha\ncharlie");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("alpha\nbravo\ncharlie");
+    assert_eq!(harness.cursor_position(), "alpha\nbr".len());
+}
+
+/// A selection moves as a block and stays selected
+#[test]
+fn test_move_selected_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "lines.txt",
+        "one\ntwo\nthree\nfour",
+    )
+    .unwrap();
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::ALT).unwrap();
+    harness.assert_buffer_content("three\none\ntwo\nfour");
+
+    let cursor = *harness.editor().active_state().cursors.primary();
+    assert_eq!(cursor.anchor, Some("three\n".len()));
+    assert_eq!(cursor.position, "three\none\nt".len());
+}
+
+/// Every cursor's line is duplicated in one undo step
+#[test]
+fn test_duplicate_lines_with_multiple_cursors() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "lines.txt", "a\nb\nc").unwrap();
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::ALT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::ALT)
+        .unwrap();
+    harness
+        .send_key(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        )
+        .unwrap();
+    harness.assert_buffer_content("a\na\nb\nb\nc\nc");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("a\nb\nc");
+}
+
+/// Joining uses a space by default, or the separator given at the prompt
+#[test]
+fn test_join_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "lines.txt",
+        "one\n  two\nthree\nfour",
+    )
+    .unwrap();
+
+    harness
+        .send_key(KeyCode::Char('j'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("one two\nthree\nfour");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::SHIFT).unwrap();
+    harness.run_command("Join Lines With Separator").unwrap();
+    harness.assert_screen_contains("Join lines with:");
+    harness.answer_prompt(", ").unwrap();
+    harness.assert_buffer_content("one two\nthree, four");
+}
+
+/// Without a selection, sorting covers the whole buffer
+#[test]
+fn test_sort_lines_modes() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "lines.txt", "item10\nitem2\nItem1")
+            .unwrap();
+
+    harness.run_command("Sort Lines").unwrap();
+    harness.assert_buffer_content("Item1\nitem10\nitem2");
+
+    harness.run_command("Sort Lines Naturally").unwrap();
+    harness.assert_buffer_content("Item1\nitem2\nitem10");
+
+    harness.run_command("Sort Lines Descending").unwrap();
+    harness.assert_buffer_content("item2\nitem10\nItem1");
+
+    harness
+        .run_command("Sort Lines Case-Insensitively")
+        .unwrap();
+    harness.assert_buffer_content("Item1\nitem10\nitem2");
+}
+
+/// Sorting by regex orders lines by the first capture group, lines without a
+/// match going last
+#[test]
+fn test_sort_lines_by_regex() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "lines.txt",
+        "b = 3\na = 12\nc = 1\n",
+    )
+    .unwrap();
+
+    harness.run_command("Sort Lines by Regex").unwrap();
+    harness.assert_screen_contains("Sort lines by regex:");
+    harness.answer_prompt(r"= (\d+)").unwrap();
+    harness.assert_buffer_content("c = 1\na = 12\nb = 3\n");
+
+    harness.run_command("Sort Lines by Regex").unwrap();
+    harness.answer_prompt("(").unwrap();
+    harness.assert_buffer_content("c = 1\na = 12\nb = 3\n");
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("Invalid sort pattern"), "{}", status);
+}
+
+/// Only the selected lines are deduplicated and reversed
+#[test]
+fn test_unique_and_reverse_selected_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "lines.txt", "x\nx\ny\nx\nz")
+            .unwrap();
+
+    for _ in 0..4 {
+        harness
+            .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    harness
+        .send_key(KeyCode::Char('u'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness.assert_buffer_content("x\ny\nz");
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.run_command("Reverse Lines").unwrap();
+    harness.assert_buffer_content("z\ny\nx");
+}
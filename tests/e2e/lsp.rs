@@ -3614,3 +3614,92 @@ fn test_hover_popup_persists_within_symbol_and_popup() -> std::io::Result<()> {
 
     Ok(())
 }
+
+/// Test that ExpandSelection uses textDocument/selectionRange when the server supports it
+#[test]
+fn test_expand_selection_uses_lsp_selection_range() -> std::io::Result<()> {
+    use crate::common::fake_lsp::FakeLspServer;
+
+    let _fake_server = FakeLspServer::spawn_with_selection_range()?;
+
+    let temp_dir = tempfile::tempdir()?;
+    let test_file = temp_dir.path().join("test.rs");
+    std::fs::write(&test_file, "hello world\n")?;
+
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::selection_range_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+        },
+    );
+
+    let mut harness = EditorTestHarness::with_config_and_working_dir(
+        80,
+        24,
+        config,
+        temp_dir.path().to_path_buf(),
+    )?;
+    harness.open_file(&test_file)?;
+
+    let selected_text = |harness: &mut EditorTestHarness| {
+        let cursor = *harness.editor().active_state().cursors.primary();
+        cursor.selection_range().map(|range| {
+            harness
+                .editor_mut()
+                .active_state_mut()
+                .get_text_range(range.start, range.end)
+        })
+    };
+
+    // Until the server is initialized the syntax tree is used instead, so retry
+    let mut selected = None;
+    for _ in 0..20 {
+        harness.send_key(KeyCode::Home, KeyModifiers::NONE)?;
+        harness.send_key_repeat(KeyCode::Right, KeyModifiers::NONE, 3)?;
+        harness.send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)?;
+        harness.wait_for_async(
+            |h| {
+                h.editor()
+                    .active_state()
+                    .cursors
+                    .primary()
+                    .selection_range()
+                    .is_some()
+            },
+            1000,
+        )?;
+
+        selected = selected_text(&mut harness);
+        if selected.as_deref() == Some("llo w") {
+            break;
+        }
+    }
+    // The server's innermost range, which no syntax node matches
+    assert_eq!(selected.as_deref(), Some("llo w"));
+
+    // Expanding again walks up the server's chain
+    harness.send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)?;
+    harness.wait_for_async(
+        |h| {
+            h.editor()
+                .active_state()
+                .cursors
+                .primary()
+                .selection_range()
+                .is_some_and(|range| range.len() > 5)
+        },
+        1000,
+    )?;
+    selected = selected_text(&mut harness);
+    assert_eq!(selected.as_deref(), Some("hello world"));
+
+    Ok(())
+}
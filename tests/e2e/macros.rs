@@ -0,0 +1,241 @@
+//! End-to-end tests for saved macros: persistence, editing, and repeated,
+//! per-line, until-failure and exported playback
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use tempfile::TempDir;
+
+/// Run a palette command that prompts, answering the prompt with `input`
+fn run_prompt_command(harness: &mut EditorTestHarness, command: &str, input: &str) {
+    harness.run_command(command).unwrap();
+    harness.answer_prompt(input).unwrap();
+}
+
+/// Record macro 1 as the given keys (Alt+Shift+1 starts and stops it)
+fn record_macro(harness: &mut EditorTestHarness, keys: &[(KeyCode, KeyModifiers)]) {
+    harness
+        .send_key(KeyCode::Char('1'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    for &(code, modifiers) in keys {
+        harness.send_key(code, modifiers).unwrap();
+    }
+    harness
+        .send_key(KeyCode::Char('1'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+}
+
+fn status(harness: &EditorTestHarness) -> String {
+    harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// A recorded macro is written to the data directory and can be played in
+/// the next session
+#[test]
+fn test_macros_persist_across_sessions() {
+    let temp_dir = TempDir::new().unwrap();
+    {
+        let mut harness = EditorTestHarness::with_file_and_shared_state(
+            80,
+            24,
+            temp_dir.path(),
+            "notes.txt",
+            "a\nb",
+            Config::default(),
+        )
+        .unwrap();
+        record_macro(
+            &mut harness,
+            &[
+                (KeyCode::End, KeyModifiers::NONE),
+                (KeyCode::Char(';'), KeyModifiers::NONE),
+            ],
+        );
+        harness.assert_buffer_content("a;\nb");
+    }
+
+    let macros_path = DirectoryContext::for_testing(temp_dir.path()).macros_path();
+    assert!(std::fs::read_to_string(macros_path)
+        .unwrap()
+        .contains("MoveLineEnd"));
+
+    let mut harness = EditorTestHarness::with_file_and_shared_state(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.txt",
+        "x\ny",
+        Config::default(),
+    )
+    .unwrap();
+    harness
+        .send_key(KeyCode::Char('1'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("x;\ny");
+}
+
+/// Saving an edited macro buffer re-parses it into the register; invalid
+/// JSON is reported and the macro is left alone
+#[test]
+fn test_edit_macro_buffer() {
+    let temp_dir = TempDir::new().unwrap();
+    // Keep typed quotes and brackets from being auto-closed
+    let mut config = Config::default();
+    config.editor.auto_indent = false;
+    let mut harness = EditorTestHarness::with_file_and_shared_state(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.txt",
+        "a\nb",
+        config,
+    )
+    .unwrap();
+    record_macro(
+        &mut harness,
+        &[
+            (KeyCode::End, KeyModifiers::NONE),
+            (KeyCode::Char(';'), KeyModifiers::NONE),
+        ],
+    );
+
+    run_prompt_command(&mut harness, "Edit Macro", "1");
+    harness.assert_screen_contains("\"InsertChar\": \";\"");
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("[\"MoveLineEnd\", oops]").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(status(&harness).starts_with("Invalid macro"));
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("// Append a bang").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .type_text("[\"MoveLineEnd\", {\"InsertChar\": \"!\"}]")
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(status(&harness), "Macro '1' saved (2 actions)");
+
+    let macros_path = DirectoryContext::for_testing(temp_dir.path()).macros_path();
+    assert!(std::fs::read_to_string(macros_path)
+        .unwrap()
+        .contains("\"!\""));
+}
+
+/// Playing on selected lines starts each run at the start of the next line,
+/// even when a run adds lines
+#[test]
+fn test_play_macro_on_selected_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file_and_shared_state(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.txt",
+        "a\nb\nc",
+        Config::default(),
+    )
+    .unwrap();
+    record_macro(
+        &mut harness,
+        &[
+            (KeyCode::End, KeyModifiers::NONE),
+            (KeyCode::Enter, KeyModifiers::NONE),
+            (KeyCode::Char('+'), KeyModifiers::NONE),
+        ],
+    );
+    harness.assert_buffer_content("a\n+\nb\nc");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::SHIFT).unwrap();
+    run_prompt_command(&mut harness, "Play Macro on Selected Lines", "1");
+    harness.assert_buffer_content("a\n+\nb\n+\nc\n+");
+    assert_eq!(status(&harness), "Played macro '1' on 2 lines");
+}
+
+/// Repeating plays the macro N times; running until failure stops when a
+/// motion in it can't move
+#[test]
+fn test_play_macro_times_and_until_failure() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file_and_shared_state(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.txt",
+        "a\nb\nc\nd\ne",
+        Config::default(),
+    )
+    .unwrap();
+    record_macro(
+        &mut harness,
+        &[
+            (KeyCode::Home, KeyModifiers::NONE),
+            (KeyCode::Char('-'), KeyModifiers::NONE),
+            (KeyCode::Down, KeyModifiers::NONE),
+        ],
+    );
+    harness.assert_buffer_content("-a\nb\nc\nd\ne");
+
+    run_prompt_command(&mut harness, "Play Macro N Times", "1 2");
+    harness.assert_buffer_content("-a\n-b\n-c\nd\ne");
+    assert_eq!(status(&harness), "Played macro '1' 2 times");
+
+    run_prompt_command(&mut harness, "Play Macro Until Failure", "1");
+    harness.assert_buffer_content("-a\n-b\n-c\n-d\n-e");
+    assert_eq!(status(&harness), "Macro '1' failed on run 2");
+
+    run_prompt_command(&mut harness, "Play Macro N Times", "1 many");
+    assert_eq!(status(&harness), "Invalid repeat count: 'many'");
+}
+
+/// Exporting writes a plugin that registers the macro as a command
+#[test]
+fn test_export_macro_as_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file_and_shared_state(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.txt",
+        "a",
+        Config::default(),
+    )
+    .unwrap();
+    record_macro(
+        &mut harness,
+        &[
+            (KeyCode::End, KeyModifiers::NONE),
+            (KeyCode::Char(';'), KeyModifiers::NONE),
+        ],
+    );
+
+    run_prompt_command(&mut harness, "Export Macro as Command", "1 Add Semicolon");
+    assert!(status(&harness).starts_with("Exported macro '1' as command \"Add Semicolon\""));
+
+    let plugin_path = DirectoryContext::for_testing(temp_dir.path())
+        .plugins_dir()
+        .join("macro_add_semicolon.ts");
+    let source = std::fs::read_to_string(plugin_path).unwrap();
+    assert!(source.contains("globalThis.macro_add_semicolon = function"));
+    assert!(source.contains("\"InsertChar\": \";\""));
+}
@@ -5,8 +5,11 @@ pub mod basic;
 pub mod binary_file;
 pub mod buffer_lifecycle;
 pub mod buffer_settings_commands;
+pub mod clipboard_history;
 pub mod command_palette;
+pub mod comments;
 pub mod crlf_rendering;
+pub mod debugger;
 pub mod document_model;
 pub mod emacs_actions;
 pub mod explorer_menu;
@@ -14,12 +17,16 @@ pub mod file_browser;
 pub mod file_explorer;
 pub mod file_permissions;
 pub mod indent_dedent;
+pub mod jump_hints;
+pub mod kill_ring;
 pub mod large_file_mode;
 pub mod lifecycle;
+pub mod line_operations;
 pub mod line_wrapping;
 pub mod live_grep;
 pub mod lsp;
 pub mod lsp_order;
+pub mod macros;
 pub mod margin;
 pub mod markdown_compose;
 pub mod menu_bar;
@@ -29,6 +36,7 @@ pub mod movement;
 pub mod multi_file_opening;
 pub mod multibyte_characters;
 pub mod multicursor;
+pub mod occur;
 pub mod on_save_actions;
 pub mod open_folder;
 pub mod paste;
@@ -38,23 +46,30 @@ pub mod position_history;
 pub mod position_history_bugs;
 pub mod position_history_debug;
 pub mod position_history_truncate_debug;
+pub mod project_search;
 pub mod prompt;
 pub mod prompt_editing;
 pub mod recovery;
+pub mod rectangles;
+pub mod reflow;
 pub mod rendering;
 pub mod scroll_clearing;
 pub mod scrolling;
 pub mod search;
+pub mod search_lines;
 pub mod selection;
 pub mod session;
 pub mod settings;
 pub mod shell_command;
 pub mod slow_filesystem;
 pub mod smart_editing;
+pub mod snippets;
 pub mod split_tabs;
 pub mod split_view;
 pub mod split_view_expectations;
 pub mod stdin_input;
+pub mod structural_search;
+pub mod surround;
 pub mod tab_config;
 pub mod tab_indent_selection;
 pub mod tab_scrolling;
@@ -62,9 +77,13 @@ pub mod terminal;
 pub mod terminal_close;
 pub mod terminal_resize;
 pub mod test_scrollbar_keybinds_cursor;
+pub mod textobjects;
 pub mod theme;
+pub mod transforms;
 pub mod undo_redo;
 pub mod unicode_cursor;
 pub mod update_notification;
+pub mod vi_mode;
 pub mod virtual_lines;
 pub mod visual_regression;
+pub mod word_completion;
@@ -1335,3 +1335,205 @@ fn test_esc_returns_to_original_cursor_position() {
         "After pressing Esc, cursor should return to original position {original_position} but is at {final_position}"
     );
 }
+
+/// Ctrl+Shift+L selects every whole-word occurrence of the word under the
+/// cursor, and Ctrl+U brings back the single cursor
+#[test]
+fn test_select_all_matches_and_cursor_undo() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("foo food foo\nfoo").unwrap();
+
+    harness
+        .send_key(
+            KeyCode::Char('l'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        )
+        .unwrap();
+    assert_eq!(harness.cursor_count(), 3);
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("Selected 3 occurrences"), "{}", status);
+
+    harness
+        .send_key(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(harness.cursor_count(), 1);
+    assert_eq!(harness.cursor_position(), 0);
+
+    harness
+        .send_key(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("No cursor operation to undo"), "{}", status);
+
+    harness
+        .send_key(
+            KeyCode::Char('l'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        )
+        .unwrap();
+    harness.type_text("x").unwrap();
+    harness.assert_buffer_content("x food x\nx");
+}
+
+/// Ctrl+Alt+D moves the newest selection past the occurrence it is on
+#[test]
+fn test_skip_to_next_match() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("foo bar foo baz foo")
+        .unwrap();
+
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    harness
+        .send_key(KeyCode::Char('d'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        )
+        .unwrap();
+    assert_eq!(harness.cursor_count(), 2);
+    assert_eq!(harness.get_selection_range(), Some(16..19));
+
+    harness.type_text("X").unwrap();
+    harness.assert_buffer_content("X bar foo baz X");
+}
+
+/// Alt+Shift+I gives each selected line a cursor, leaving out the line the
+/// selection only reaches the start of
+#[test]
+fn test_split_selection_into_lines() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("one\ntwo\nthree\n").unwrap();
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('i'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    assert_eq!(harness.cursor_count(), 3);
+
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text(";").unwrap();
+    harness.assert_buffer_content("one;\ntwo;\nthree;\n");
+}
+
+/// Alt+Shift+L selects each regex match; Alt+Shift+N numbers them, padded
+/// like the start
+#[test]
+fn test_add_cursors_at_regex_and_insert_sequence() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("let a = 1;\nlet bb = 22;\nlet c = 333;\n")
+        .unwrap();
+
+    harness
+        .send_key(KeyCode::Char('l'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness.type_text(r"\d+").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(harness.cursor_count(), 3);
+
+    harness
+        .send_key(KeyCode::Char('n'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("08 -3").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("let a = 08;\nlet bb = 05;\nlet c = 02;\n");
+
+    // An invalid regex is reported and leaves the cursors alone
+    harness
+        .send_key(KeyCode::Char('l'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness.type_text("(").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("Invalid regex"), "{}", status);
+    assert_eq!(harness.cursor_count(), 3);
+}
+
+/// Alt+Shift+C pads lines so the cursors share a column, as one undo step
+#[test]
+fn test_align_cursors() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let content = "a = 1\nbbb = 2\ncc = 3\n";
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(content).unwrap();
+
+    harness
+        .send_key(KeyCode::Char('l'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness.type_text("=").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('c'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness.assert_buffer_content("a   = 1\nbbb = 2\ncc  = 3\n");
+    assert_eq!(harness.cursor_count(), 3);
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content(content);
+}
+
+/// Alt+Shift+T moves each selection's text into the next selection, and
+/// the selections follow the text so rotating again keeps going
+#[test]
+fn test_rotate_selections() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("red, green, blue").unwrap();
+
+    harness
+        .send_key(KeyCode::Char('l'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness.type_text(r"\w+").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    harness
+        .send_key(KeyCode::Char('t'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness.assert_buffer_content("blue, red, green");
+    harness
+        .send_key(KeyCode::Char('t'), KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+    harness.assert_buffer_content("green, blue, red");
+}
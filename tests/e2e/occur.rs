@@ -0,0 +1,136 @@
+//! End-to-end tests for Occur
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn a() {}\nlet x = 1;\nfn b() {}\n";
+
+fn occur(harness: &mut EditorTestHarness, query: &str) {
+    harness.run_command("Occur").unwrap();
+    harness.answer_prompt(query).unwrap();
+}
+
+/// The occur buffer lists matching lines, Enter jumps to them, and the list
+/// follows changes to the source
+#[test]
+fn test_occur_lists_lines_and_follows_source() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(100, 30, temp_dir.path(), "source.rs", SOURCE).unwrap();
+
+    occur(&mut harness, "fn");
+    let list = harness.get_buffer_content().unwrap();
+    let header = list.lines().next().unwrap_or_default();
+    assert!(
+        header.starts_with("2 lines matching 'fn' in ") && header.ends_with("source.rs"),
+        "{}",
+        list
+    );
+    assert!(list.ends_with("1: fn a() {}\n3: fn b() {}\n"), "{}", list);
+
+    // Line 3 is the third line of the list, after the header
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content(SOURCE);
+    assert_eq!(harness.cursor_position(), SOURCE.find("fn b").unwrap());
+
+    // Typing in the source updates the list
+    harness.type_text("fn c\n").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("3 lines matching 'fn' in ");
+    harness.assert_screen_contains("3: fn c");
+    harness.assert_screen_contains("4: fn b() {}");
+}
+
+/// Saving an edited occur buffer changes the source lines, as one edit
+#[test]
+fn test_occur_edits_write_back_to_source() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(100, 30, temp_dir.path(), "source.rs", SOURCE).unwrap();
+
+    occur(&mut harness, "fn");
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text(" // first").unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text(" // second").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(harness.get_status_bar().contains("Changed 2 lines"));
+
+    // The list was refreshed, so Enter still finds the line
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    let edited = "fn a() {} // first\nlet x = 1;\nfn b() {} // second\n";
+    harness.assert_buffer_content(edited);
+    assert_eq!(harness.cursor_position(), edited.find("fn b").unwrap());
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content(SOURCE);
+}
+
+/// Large files are scanned in the background through the chunked reader
+#[test]
+fn test_occur_in_large_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("large.txt");
+    let needles = [9, 20_000, 59_999];
+    let content: String = (0..60_000)
+        .map(|i| {
+            if needles.contains(&i) {
+                format!("needle {}\n", i)
+            } else {
+                format!("line {}\n", i)
+            }
+        })
+        .collect();
+    fs::write(&path, &content).unwrap();
+
+    let mut config = Config::default();
+    config.editor.large_file_threshold_bytes = 1000;
+    let mut harness = EditorTestHarness::with_config(100, 30, config).unwrap();
+    harness.open_file(&path).unwrap();
+    assert!(harness.editor().active_state().buffer.is_large_file());
+
+    occur(&mut harness, "needle");
+    harness
+        .wait_until(|h| {
+            h.get_buffer_content()
+                .is_some_and(|list| list.contains("3 lines matching 'needle'"))
+        })
+        .unwrap();
+    let list = harness.get_buffer_content().unwrap();
+    assert!(list.contains("   10: needle 9\n"), "{}", list);
+    assert!(list.contains("20001: needle 20000\n"), "{}", list);
+    assert!(list.ends_with("60000: needle 59999\n"), "{}", list);
+
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        harness.cursor_position(),
+        content.find("needle 59999").unwrap()
+    );
+}
@@ -45,12 +45,18 @@ fn test_on_save_replace_buffer_formatter() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action],
         },
     );
@@ -108,12 +114,18 @@ fn test_on_save_linter_style() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action],
         },
     );
@@ -171,12 +183,18 @@ fn test_on_save_action_failure() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action],
         },
     );
@@ -245,12 +263,18 @@ fn test_on_save_file_placeholder() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action],
         },
     );
@@ -313,12 +337,18 @@ fn test_on_save_stdin_mode() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action],
         },
     );
@@ -386,12 +416,18 @@ fn test_on_save_multiple_actions() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action1, action2],
         },
     );
@@ -458,12 +494,18 @@ fn test_on_save_stops_on_failure() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action1, action2],
         },
     );
@@ -555,12 +597,18 @@ fn test_on_save_optional_command_not_found() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action],
         },
     );
@@ -620,12 +668,18 @@ fn test_on_save_required_command_not_found() {
             filenames: vec![],
             grammar: "plaintext".to_string(),
             comment_prefix: None,
+            block_comment: None,
             auto_indent: false,
             highlighter: Default::default(),
             textmate_grammar: None,
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: None,
+            wrap_column: None,
+            hard_wrap: false,
+            word_completion: true,
+            completion_trigger: Default::default(),
+            completion_min_prefix: fresh::config::DEFAULT_COMPLETION_MIN_PREFIX,
             on_save: vec![action],
         },
     );
@@ -0,0 +1,153 @@
+//! End-to-end tests for Search in Project and Replace in Project
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+use tempfile::TempDir;
+
+/// A project with two files mentioning "needle" and one gitignored file
+fn project_harness(temp_dir: &TempDir) -> EditorTestHarness {
+    let root = temp_dir.path();
+    fs::write(root.join(".gitignore"), "ignored.txt\n").unwrap();
+    fs::write(root.join("a.txt"), "alpha needle\nbeta\n").unwrap();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/b.txt"), "one\nneedle two\n").unwrap();
+    fs::write(root.join("ignored.txt"), "needle\n").unwrap();
+
+    let mut config = Config::default();
+    config.lsp.clear();
+    EditorTestHarness::with_config_and_working_dir(100, 30, config, root.to_path_buf()).unwrap()
+}
+
+/// Results open in a buffer, and saving an edited result line writes it
+/// back to its file; Undo Last Project Replace reverts the write
+#[test]
+fn test_project_search_results_write_back() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = project_harness(&temp_dir);
+
+    harness.run_command("Search in Project").unwrap();
+    harness.type_text("needle").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            h.get_status_bar()
+                .contains("Found 2 matches in 2 files for 'needle'")
+        })
+        .unwrap();
+
+    let results = harness.get_buffer_content().unwrap();
+    assert!(results.contains("a.txt:1: alpha needle\n"));
+    assert!(results.contains("src/b.txt:2: needle two\n"));
+    assert!(!results.contains("ignored.txt"));
+
+    // The result for a.txt is the third line, after the header and a blank
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text("!").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(harness.get_status_bar().contains("Wrote 1 line to 1 file"));
+    let a = temp_dir.path().join("a.txt");
+    assert_eq!(fs::read_to_string(&a).unwrap(), "alpha needle!\nbeta\n");
+
+    // The result line now opens its file at that line
+    harness.send_key(KeyCode::Enter, KeyModifiers::ALT).unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content("alpha needle!\nbeta\n");
+
+    harness.run_command("Undo Last Project Replace").unwrap();
+    assert!(harness.get_status_bar().contains("restored 1 file"));
+    harness.assert_buffer_content("alpha needle\nbeta\n");
+    assert_eq!(fs::read_to_string(&a).unwrap(), "alpha needle\nbeta\n");
+}
+
+/// Replace in Project changes unsaved open buffers as they are in the
+/// editor, leaves them unsaved, and can be undone
+#[test]
+fn test_replace_in_project_and_undo() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = project_harness(&temp_dir);
+    let a = temp_dir.path().join("a.txt");
+    let b = temp_dir.path().join("src/b.txt");
+    harness.open_file(&a).unwrap();
+    harness.type_text("needle ").unwrap();
+
+    harness.run_command("Replace in Project").unwrap();
+    harness.type_text("needle").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("pin").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            h.get_status_bar()
+                .contains("Replaced 3 occurrences in 2 files")
+        })
+        .unwrap();
+
+    harness.assert_buffer_content("pin alpha pin\nbeta\n");
+    assert_eq!(fs::read_to_string(&a).unwrap(), "alpha needle\nbeta\n");
+    assert_eq!(fs::read_to_string(&b).unwrap(), "one\npin two\n");
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("ignored.txt")).unwrap(),
+        "needle\n"
+    );
+
+    harness.run_command("Undo Last Project Replace").unwrap();
+    assert!(harness.get_status_bar().contains("restored 2 files"));
+    harness.assert_buffer_content("needle alpha needle\nbeta\n");
+    assert_eq!(fs::read_to_string(&b).unwrap(), "one\nneedle two\n");
+}
+
+/// Undo skips files changed since the replace
+#[test]
+fn test_undo_project_replace_skips_changed_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = project_harness(&temp_dir);
+    let b = temp_dir.path().join("src/b.txt");
+
+    harness.run_command("Replace in Project").unwrap();
+    harness.type_text("needle").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("pin").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("Replaced 2 occurrences"))
+        .unwrap();
+
+    fs::write(&b, "rewritten\n").unwrap();
+    harness.run_command("Undo Last Project Replace").unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("restored 1 file"), "{}", status);
+    assert!(
+        status.contains("skipped 1 file changed since"),
+        "{}",
+        status
+    );
+    assert_eq!(fs::read_to_string(&b).unwrap(), "rewritten\n");
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(),
+        "alpha needle\nbeta\n"
+    );
+}
@@ -0,0 +1,204 @@
+//! End-to-end tests for virtual space and rectangle editing
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+fn block_select(harness: &mut EditorTestHarness, code: KeyCode) {
+    harness
+        .send_key(code, KeyModifiers::ALT | KeyModifiers::SHIFT)
+        .unwrap();
+}
+
+/// Typing into a block selection that crosses a short line pads that line
+/// out to the block's column
+#[test]
+fn test_typing_into_block_pads_short_lines() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("abcdef\nab\nabcdef\n")
+        .unwrap();
+
+    for _ in 0..4 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    block_select(&mut harness, KeyCode::Down);
+    block_select(&mut harness, KeyCode::Down);
+    harness.render().unwrap();
+    harness.type_text("|").unwrap();
+    harness.assert_buffer_content("abcd|ef\nab  |\nabcd|ef\n");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("abcdef\nab\nabcdef\n");
+}
+
+/// In virtual space the cursor keeps its column past the end of a line,
+/// and typing there pads the line with spaces
+#[test]
+fn test_virtual_space_typing_pads_line() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("abcdef\nab\n").unwrap();
+
+    harness.run_command("Toggle Virtual Space").unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("Virtual space: On"), "{}", status);
+
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    let (end_x, _) = harness.screen_cursor_position();
+
+    // Below the end of the longer line, the cursor is drawn in its column
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 9);
+    harness.render().unwrap();
+    assert_eq!(harness.screen_cursor_position().0, end_x);
+    harness.type_text("x").unwrap();
+    harness.assert_buffer_content("abcdef\nab    x\n");
+
+    // Right moves on past the end of the line too
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("y").unwrap();
+    harness.assert_buffer_content("abcdef\nab    x  y\n");
+
+    // Without virtual space, the cursor stops at the end of the line
+    harness.run_command("Toggle Virtual Space").unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("z").unwrap();
+    harness.assert_buffer_content("abcdef\nab    x  y\nz");
+}
+
+/// Killing a rectangle closes up its lines, and yanking puts it back one
+/// line at a time in the cursor's column
+#[test]
+fn test_kill_and_yank_rectangle() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("abcd\nefgh\nijkl\n").unwrap();
+
+    // A selection from (0, 1) to (1, 3) spans columns 1..3 of two lines
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.run_command("Kill Rectangle").unwrap();
+    harness.assert_buffer_content("ad\neh\nijkl\n");
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("Killed rectangle of 2 lines"), "{}", status);
+
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    harness.run_command("Yank Rectangle").unwrap();
+    harness.assert_buffer_content("abcd\nefgh\nijkl\n");
+
+    // Yanked at the end of the buffer, it adds the lines it needs
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.run_command("Yank Rectangle").unwrap();
+    harness.assert_buffer_content("abcd\nefgh\nijkl\nbc\nfg");
+    assert_eq!(harness.cursor_position(), 20);
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("abcd\nefgh\nijkl\n");
+}
+
+/// Fill replaces the block on each line with the same text
+#[test]
+fn test_fill_rectangle() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("one\ntwo\nthree\n").unwrap();
+
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    block_select(&mut harness, KeyCode::Right);
+    block_select(&mut harness, KeyCode::Down);
+    block_select(&mut harness, KeyCode::Down);
+    harness.run_command("Fill Rectangle").unwrap();
+    harness.answer_prompt("XY").unwrap();
+    harness.assert_buffer_content("oXYe\ntXYo\ntXYree\n");
+}
+
+/// Text and numbers are inserted at the left edge of the rectangle
+#[test]
+fn test_insert_text_and_number_rectangle_lines() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("one\ntwo\nthree\n").unwrap();
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .run_command("Insert Text on Rectangle Lines")
+        .unwrap();
+    harness.answer_prompt("- ").unwrap();
+    harness.assert_buffer_content("- one\n- two\n- three\n");
+
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.run_command("Number Rectangle Lines").unwrap();
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+        .unwrap();
+    harness.answer_prompt("9").unwrap();
+    harness.assert_buffer_content(" 9 - one\n10 - two\n11 - three\n");
+}
+
+/// Rectangle commands need a selection to work on
+#[test]
+fn test_rectangle_needs_selection() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("one\n").unwrap();
+
+    harness.run_command("Delete Rectangle").unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("No rectangle selected"), "{}", status);
+    harness.run_command("Yank Rectangle").unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("No rectangle to yank"), "{}", status);
+    harness.assert_buffer_content("one\n");
+}
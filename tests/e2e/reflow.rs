@@ -0,0 +1,74 @@
+//! End-to-end tests for paragraph reflow and hard wrapping while typing
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use tempfile::TempDir;
+
+fn reflow(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('q'), KeyModifiers::ALT)
+        .unwrap();
+}
+
+/// A doc comment is rewrapped at the wrap column, keeping its prefix, and
+/// the code after it is left alone; one undo restores it
+#[test]
+fn test_reflow_doc_comment() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.editor.wrap_column = 20;
+    let text = "/// one two three four five six seven\nfn f() {}\n";
+    let mut harness =
+        EditorTestHarness::with_file_and_config(80, 24, temp_dir.path(), "lib.rs", text, config)
+            .unwrap();
+
+    reflow(&mut harness);
+    harness.assert_buffer_content("/// one two three\n/// four five six\n/// seven\nfn f() {}\n");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content(text);
+}
+
+/// The language's wrap column applies, and list items keep their marker
+/// with continuation lines indented under it
+#[test]
+fn test_reflow_markdown_list_items() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.languages.get_mut("markdown").unwrap().wrap_column = Some(12);
+    let mut harness = EditorTestHarness::with_file_and_config(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.md",
+        "- alpha beta gamma\n- delta\n",
+        config,
+    )
+    .unwrap();
+
+    reflow(&mut harness);
+    harness.assert_buffer_content("- alpha beta\n  gamma\n- delta\n");
+}
+
+/// Commit messages are hard-wrapped while typing
+#[test]
+fn test_hard_wrap_while_typing() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.languages.get_mut("git-commit").unwrap().wrap_column = Some(10);
+    let mut harness = EditorTestHarness::with_file_and_config(
+        80,
+        24,
+        temp_dir.path(),
+        "COMMIT_EDITMSG",
+        "",
+        config,
+    )
+    .unwrap();
+
+    harness.type_text("one two three four").unwrap();
+    harness.assert_buffer_content("one two\nthree four");
+}
@@ -1428,3 +1428,190 @@ fn test_status_bar_hidden_during_suggestions() {
         "Status bar should show 'Palette:' indicator after closing command palette"
     );
 }
+
+/// Write a large file of filler lines with "needle" at `positions`
+fn write_large_file_with_needles(path: &std::path::Path, positions: &[usize]) -> usize {
+    let mut content = b"filler text\n".repeat(220_000);
+    for &pos in positions {
+        content[pos..pos + 6].copy_from_slice(b"needle");
+    }
+    std::fs::write(path, &content).unwrap();
+    content.len()
+}
+
+/// Large files are searched in the background, including matches that cross
+/// the windows the file is read in
+#[test]
+fn test_search_in_large_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("large.txt");
+    // The second needle crosses the first 1 MB search window
+    let positions = [100, 1024 * 1024 - 3, 2 * 1024 * 1024 + 50];
+    write_large_file_with_needles(&file_path, &positions);
+
+    let mut config = fresh::config::Config::default();
+    config.editor.large_file_threshold_bytes = 1000;
+    let mut harness = EditorTestHarness::with_config(120, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    assert!(harness.editor().active_state().buffer.is_large_file());
+
+    harness
+        .send_key(KeyCode::Char('f'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("needle").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("Found 3 matches for 'needle'"))
+        .unwrap();
+    assert_eq!(harness.cursor_position(), positions[0]);
+
+    harness.send_key(KeyCode::F(3), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), positions[1]);
+    harness.send_key(KeyCode::F(3), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), positions[2]);
+    harness
+        .send_key(KeyCode::F(3), KeyModifiers::SHIFT)
+        .unwrap();
+    assert_eq!(harness.cursor_position(), positions[1]);
+}
+
+/// Replace All in a large file replaces every match without loading the file
+#[test]
+fn test_replace_all_in_large_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("large.txt");
+    let positions = [100, 1024 * 1024 - 3, 2 * 1024 * 1024 + 50];
+    let len = write_large_file_with_needles(&file_path, &positions);
+
+    let mut config = fresh::config::Config::default();
+    config.editor.large_file_threshold_bytes = 1000;
+    let mut harness = EditorTestHarness::with_config(120, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+
+    harness
+        .send_key(KeyCode::Char('r'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("needle").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("pin").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("Replaced 3 occurrences"))
+        .unwrap();
+
+    let buffer = &mut harness.editor_mut().active_state_mut().buffer;
+    assert_eq!(buffer.len(), len - 3 * 3);
+    // Each replacement shifts the later ones back by 3 bytes
+    for (i, &pos) in positions.iter().enumerate() {
+        let text = buffer.get_text_range_mut(pos - 3 * i, 3).unwrap();
+        assert_eq!(text, b"pin");
+    }
+}
+
+/// Open `content` and start a replace (Ctrl+R) or query replace
+/// (Ctrl+Alt+R), toggling the search options given by their Alt keys
+fn start_replace(content: &str, query: bool, options: &str) -> (TempDir, EditorTestHarness) {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Wide enough for every option in the options bar
+    let mut harness = EditorTestHarness::new(140, 24).unwrap();
+    harness
+        .open_new_file(temp_dir.path(), "test.txt", content)
+        .unwrap();
+
+    let modifiers = if query {
+        KeyModifiers::CONTROL | KeyModifiers::ALT
+    } else {
+        KeyModifiers::CONTROL
+    };
+    harness.send_key(KeyCode::Char('r'), modifiers).unwrap();
+    for option in options.chars() {
+        harness
+            .send_key(KeyCode::Char(option), KeyModifiers::ALT)
+            .unwrap();
+    }
+    (temp_dir, harness)
+}
+
+fn enter(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Regex replacements can change the case of groups, and show what each
+/// visible match becomes while the replacement is typed
+#[test]
+fn test_regex_replace_with_case_conversion_and_preview() {
+    let (_temp_dir, mut harness) = start_replace("hello_world foo_bar\n", false, "r");
+    harness.type_text(r"(\w+)_(\w+)").unwrap();
+    enter(&mut harness);
+    harness.type_text(r"\u$1\U$2").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("hello_world → HelloWORLD foo_bar → FooBAR");
+
+    enter(&mut harness);
+    harness.assert_buffer_content("HelloWORLD FooBAR\n");
+    harness.assert_screen_not_contains("→ FooBAR");
+}
+
+/// Preserve Case gives each replacement the case of the text it replaces
+#[test]
+fn test_replace_preserving_case() {
+    let (_temp_dir, mut harness) = start_replace("foo Foo FOO\n", false, "cp");
+    harness.render().unwrap();
+    harness.assert_screen_contains("[x] Preserve case");
+    harness.type_text("foo").unwrap();
+    enter(&mut harness);
+    harness.type_text("bar").unwrap();
+    enter(&mut harness);
+    harness.assert_buffer_content("bar Bar BAR\n");
+    harness.assert_screen_contains("Replaced 3 occurrences");
+}
+
+/// Regex patterns can match across lines, and `\n` in the replacement
+/// inserts a line break
+#[test]
+fn test_regex_replace_across_lines() {
+    let (_temp_dir, mut harness) = start_replace("call(\n    x,\n);\nend\n", false, "r");
+    harness.type_text(r"\(\n\s*(\w+),\n\);$").unwrap();
+    enter(&mut harness);
+    harness.type_text(r"($1);\n").unwrap();
+    enter(&mut harness);
+    harness.assert_buffer_content("call(x);\n\nend\n");
+}
+
+/// Query replace previews the replacement of the current match, and each
+/// replacement is expanded for its own match
+#[test]
+fn test_query_replace_previews_current_match() {
+    let (_temp_dir, mut harness) = start_replace("foo Foo\n", true, "cp");
+    harness.type_text("foo").unwrap();
+    enter(&mut harness);
+    harness.type_text("bar").unwrap();
+    enter(&mut harness);
+    harness.assert_screen_contains("foo → bar Foo");
+
+    harness.type_text("n").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("foo Foo → Bar");
+
+    harness.type_text("y").unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content("foo Bar\n");
+    harness.assert_screen_not_contains("→ Bar");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("foo Foo\n");
+}
@@ -0,0 +1,104 @@
+//! End-to-end tests for Search Lines
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+fn search_lines(harness: &mut EditorTestHarness, query: &str) {
+    harness.run_command("Search Lines").unwrap();
+    harness.type_text(query).unwrap();
+    harness.render().unwrap();
+}
+
+/// Filler lines with two functions far down the buffer
+fn content() -> String {
+    let mut content = String::new();
+    for i in 1..=100 {
+        match i {
+            60 => content.push_str("    fn render_frame() {}\n"),
+            80 => content.push_str("fn read_file() {}\n"),
+            _ => content.push_str(&format!("filler {}\n", i)),
+        }
+    }
+    content
+}
+
+fn line_offset(content: &str, line: usize) -> usize {
+    content.lines().take(line - 1).map(|l| l.len() + 1).sum()
+}
+
+/// Matching lines are listed with their numbers, the best one is previewed
+/// as the query is typed, and Enter jumps to it
+#[test]
+fn test_search_lines_previews_and_jumps() {
+    let content = content();
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(&content).unwrap();
+
+    search_lines(&mut harness, "rdfile");
+    harness.assert_screen_contains(" 80: fn read_file() {}");
+    harness.assert_screen_not_contains("render_frame");
+    assert_eq!(harness.cursor_position(), line_offset(&content, 80));
+    assert!(harness.top_byte() > 0);
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content(&content);
+    assert_eq!(harness.cursor_position(), line_offset(&content, 80));
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("Jumped to line 80"), "{}", status);
+}
+
+/// Up and Down preview each match without replacing the query, and Escape
+/// puts the cursor and view back
+#[test]
+fn test_search_lines_selection_preview_and_escape() {
+    let content = content();
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(&content).unwrap();
+
+    // Indentation is ignored, so both functions match from their start
+    search_lines(&mut harness, "fn r");
+    harness.assert_screen_contains(" 60: fn render_frame() {}");
+    harness.assert_screen_contains(" 80: fn read_file() {}");
+    let first = harness.cursor_position();
+    assert!(first == line_offset(&content, 60) || first == line_offset(&content, 80));
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    let second = harness.cursor_position();
+    assert_ne!(first, second);
+    assert!(second == line_offset(&content, 60) || second == line_offset(&content, 80));
+    harness.assert_screen_contains("Search lines: fn r");
+
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.cursor_position(), 0);
+    assert_eq!(harness.top_byte(), 0);
+    harness.assert_screen_contains("filler 1");
+}
+
+/// A query matching no line confirms to nothing and leaves the cursor alone
+#[test]
+fn test_search_lines_without_match() {
+    let content = content();
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(&content).unwrap();
+
+    search_lines(&mut harness, "zzz");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.cursor_position(), 0);
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("No matching line"), "{}", status);
+}
@@ -344,7 +344,7 @@ fn test_select_word_multi_cursor() {
     );
 }
 
-/// Test expand selection functionality (Ctrl+Shift+Right)
+/// Test expand selection functionality (Ctrl+Shift+Up)
 #[test]
 fn test_expand_selection() {
     use crossterm::event::{KeyCode, KeyModifiers};
@@ -363,7 +363,7 @@ fn test_expand_selection() {
 
     // First expand should select from cursor to end of current word
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
 
     let cursor = harness.editor().active_state().cursors.primary();
@@ -379,7 +379,7 @@ fn test_expand_selection() {
 
     // Second expand should extend to include " world"
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
 
     let cursor = harness.editor().active_state().cursors.primary();
@@ -395,7 +395,7 @@ fn test_expand_selection() {
 
     // Third expand should extend to include " test"
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
 
     let cursor = harness.editor().active_state().cursors.primary();
@@ -428,7 +428,7 @@ fn test_expand_selection_no_initial_selection() {
 
     // Expand with no initial selection should select from cursor to end of word
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
 
     let cursor = harness.editor().active_state().cursors.primary();
@@ -476,7 +476,7 @@ fn test_expand_selection_large_buffer_performance() {
     // because it would read the entire buffer. Now it should complete quickly
     // by only reading a small window around the cursor.
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
 
     // Verify it works correctly
@@ -526,7 +526,7 @@ fn test_expand_selection_very_large_buffer() {
     }
 
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     assert!(
@@ -541,7 +541,7 @@ fn test_expand_selection_very_large_buffer() {
     }
 
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     assert!(
@@ -650,7 +650,7 @@ fn test_expand_selection_after_scrolling() {
 
     // First expand should select from cursor to end of word
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     let range = cursor.selection_range().unwrap();
@@ -665,7 +665,7 @@ fn test_expand_selection_after_scrolling() {
 
     // Second expand should extend to include " beta"
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     let range = cursor.selection_range().unwrap();
@@ -679,7 +679,7 @@ fn test_expand_selection_after_scrolling() {
     );
 }
 
-/// Test expand selection (Ctrl+Shift+Right) across line boundaries
+/// Test expand selection (Ctrl+Shift+Up) across line boundaries
 /// Ensures selection can expand from end of one line to beginning of next
 #[test]
 fn test_expand_selection_across_lines() {
@@ -703,7 +703,7 @@ fn test_expand_selection_across_lines() {
 
     // First expand: select "ending"
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     let range = cursor.selection_range().unwrap();
@@ -718,7 +718,7 @@ fn test_expand_selection_across_lines() {
 
     // Second expand: should cross the newline and select "second" on next line
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     let range = cursor.selection_range().unwrap();
@@ -733,7 +733,7 @@ fn test_expand_selection_across_lines() {
 
     // Third expand: should continue to "line"
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     let range = cursor.selection_range().unwrap();
@@ -763,7 +763,7 @@ fn test_expand_selection_from_line_end() {
 
     // First expand from end of line - should jump to next word on next line
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     let range = cursor.selection_range().unwrap();
@@ -781,7 +781,7 @@ fn test_expand_selection_from_line_end() {
 
     // Continue expanding to ensure we can reach the next line
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
     let cursor = harness.editor().active_state().cursors.primary();
     let range = cursor.selection_range().unwrap();
@@ -912,7 +912,7 @@ fn test_select_word_with_dot() {
     );
 }
 
-/// Test expand selection (Ctrl+Shift+Right) when cursor is on a non-word character
+/// Test expand selection (Ctrl+Shift+Up) when cursor is on a non-word character
 /// Should select from cursor position through the next word (like Emacs)
 #[test]
 fn test_expand_selection_on_non_word_char() {
@@ -923,9 +923,9 @@ fn test_expand_selection_on_non_word_char() {
     harness.type_text("**-word").unwrap();
     harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
 
-    // Cursor is now on the first *, press Ctrl+Shift+Right to expand selection
+    // Cursor is now on the first *, press Ctrl+Shift+Up to expand selection
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
 
     let cursor = harness.editor().active_state().cursors.primary();
@@ -934,7 +934,7 @@ fn test_expand_selection_on_non_word_char() {
     // Should select from cursor (position 0) through next word, which is "**-word"
     assert!(
         range.is_some(),
-        "Should have a selection after Ctrl+Shift+Right"
+        "Should have a selection after Ctrl+Shift+Up"
     );
 
     if let Some(range) = range {
@@ -958,9 +958,9 @@ fn test_expand_selection_on_word_char() {
     harness.type_text("hello world").unwrap();
     harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
 
-    // Cursor on 'h' in "hello", press Ctrl+Shift+Right
+    // Cursor on 'h' in "hello", press Ctrl+Shift+Up
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
 
     let cursor = harness.editor().active_state().cursors.primary();
@@ -986,9 +986,9 @@ fn test_expand_selection_from_middle_of_word() {
         .send_key(KeyCode::Right, KeyModifiers::NONE)
         .unwrap();
 
-    // Press Ctrl+Shift+Right from 'v' in "Event"
+    // Press Ctrl+Shift+Up from 'v' in "Event"
     harness
-        .send_key(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
         .unwrap();
 
     let cursor = harness.editor().active_state().cursors.primary();
@@ -1579,3 +1579,115 @@ fn test_select_at_file_boundaries() {
     // Just verify we can get selected text without panicking
     // The test validates that boundary operations don't crash
 }
+
+/// Selected text of every cursor, in buffer order
+fn selected_texts(harness: &mut EditorTestHarness) -> Vec<String> {
+    let mut ranges: Vec<_> = harness
+        .editor()
+        .active_state()
+        .cursors
+        .iter()
+        .filter_map(|(_, cursor)| cursor.selection_range())
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+        .into_iter()
+        .map(|range| {
+            harness
+                .editor_mut()
+                .active_state_mut()
+                .get_text_range(range.start, range.end)
+        })
+        .collect()
+}
+
+/// Test expand selection by syntax node and shrinking back (Ctrl+Shift+Up / Ctrl+Shift+Down)
+#[test]
+fn test_expand_and_shrink_selection_by_syntax_node() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("main.rs");
+    std::fs::write(&file_path, "fn main() {\n    let a = foo(bar);\n}\n").unwrap();
+
+    // Without a language server the expansion is applied synchronously
+    let mut config = fresh::config::Config::default();
+    config.lsp.clear();
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+
+    // Cursor inside "bar"
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key_repeat(KeyCode::Right, KeyModifiers::NONE, 17)
+        .unwrap();
+    let original = harness.editor().active_state().cursors.primary().position;
+
+    let expand = |harness: &mut EditorTestHarness| {
+        harness
+            .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+            .unwrap();
+    };
+    let shrink = |harness: &mut EditorTestHarness| {
+        harness
+            .send_key(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+            .unwrap();
+    };
+
+    expand(&mut harness);
+    assert_eq!(selected_texts(&mut harness), vec!["bar"]);
+    expand(&mut harness);
+    assert_eq!(selected_texts(&mut harness), vec!["(bar)"]);
+    expand(&mut harness);
+    assert_eq!(selected_texts(&mut harness), vec!["foo(bar)"]);
+    expand(&mut harness);
+    assert_eq!(selected_texts(&mut harness), vec!["let a = foo(bar);"]);
+
+    shrink(&mut harness);
+    assert_eq!(selected_texts(&mut harness), vec!["foo(bar)"]);
+    shrink(&mut harness);
+    shrink(&mut harness);
+    assert_eq!(selected_texts(&mut harness), vec!["bar"]);
+
+    // Shrinking past the first expansion restores the original cursor
+    shrink(&mut harness);
+    let cursor = *harness.editor().active_state().cursors.primary();
+    assert!(cursor.selection_range().is_none());
+    assert_eq!(cursor.position, original);
+}
+
+/// Test that structural selection works for every cursor independently
+#[test]
+fn test_expand_selection_by_syntax_node_multi_cursor() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("main.rs");
+    std::fs::write(
+        &file_path,
+        "fn main() {\n    let a = foo(bar);\n    let b = foo(baz, 1);\n}\n",
+    )
+    .unwrap();
+
+    // Without a language server the expansion is applied synchronously
+    let mut config = fresh::config::Config::default();
+    config.lsp.clear();
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+
+    // Cursors inside "bar" and "baz"
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key_repeat(KeyCode::Right, KeyModifiers::NONE, 17)
+        .unwrap();
+    harness.editor_mut().add_cursor_below();
+    assert_eq!(harness.editor().active_state().cursors.count(), 2);
+
+    for _ in 0..2 {
+        harness
+            .send_key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    assert_eq!(selected_texts(&mut harness), vec!["(bar)", "(baz, 1)"]);
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        .unwrap();
+    assert_eq!(selected_texts(&mut harness), vec!["bar", "baz"]);
+}
@@ -2051,8 +2051,9 @@ fn test_entry_dialog_delete_textlist_item() {
     harness.assert_screen_contains("Edit Value");
 
     // Navigate to Extensions section which has existing items
-    // Fields in order: Key, Auto Indent, Comment Prefix, Extensions (3 downs)
-    for _ in 0..3 {
+    // Fields in order: Key, Auto Indent, Block Comment, Comment Prefix,
+    // Completion Min Prefix, Completion Trigger, Extensions (6 downs)
+    for _ in 0..6 {
         harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
     }
     harness.render().unwrap();
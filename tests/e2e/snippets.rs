@@ -0,0 +1,197 @@
+//! End-to-end tests for user-defined snippets
+//!
+//! Snippets are read from the project's `.fresh/snippets/` directory here;
+//! the user config directory works the same way.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Create a project with the given snippet files and an empty `file_name`,
+/// and open that file
+fn snippet_harness(
+    snippet_files: &[(&str, &str)],
+    file_name: &str,
+) -> (EditorTestHarness, TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let snippets_dir = project_dir.join(".fresh").join("snippets");
+    std::fs::create_dir_all(&snippets_dir).unwrap();
+    for (name, contents) in snippet_files {
+        std::fs::write(snippets_dir.join(name), contents).unwrap();
+    }
+    let file = project_dir.join(file_name);
+    std::fs::write(&file, "").unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, Config::default(), project_dir)
+            .unwrap();
+    harness.editor_mut().set_clipboard_for_test(String::new());
+    harness.open_file(&file).unwrap();
+    (harness, temp_dir, file)
+}
+
+const RUST_SNIPPETS: &str = r#"{
+    // Comments are allowed, as in VS Code snippet files
+    "Function": {
+        "prefix": "fn",
+        "body": ["fn ${1:name}($2) {", "\t$0", "}"],
+        "description": "Function definition"
+    }
+}"#;
+
+/// The picker inserts a snippet, and Tab walks its fields to the final cursor
+#[test]
+fn test_insert_snippet_and_tab_through_fields() {
+    let (mut harness, _temp, _file) = snippet_harness(&[("rust.json", RUST_SNIPPETS)], "main.rs");
+
+    harness.run_command("Insert Snippet").unwrap();
+    harness.assert_screen_contains("Insert snippet:");
+    harness.assert_screen_contains("Function");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("fn name() {\n\t\n}");
+
+    // The placeholder is selected, so typing replaces it
+    harness.type_text("main").unwrap();
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness.type_text("args").unwrap();
+    harness
+        .send_key(KeyCode::BackTab, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.type_text("run").unwrap();
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness.type_text("body").unwrap();
+    harness.assert_buffer_content("fn run(args) {\n\tbody\n}");
+
+    // The snippet is done: Tab inserts a tab again
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    assert_ne!(
+        harness.get_buffer_content().unwrap(),
+        "fn run(args) {\n\tbody\n}"
+    );
+}
+
+/// Typing a prefix and opening completion offers the snippet
+#[test]
+fn test_snippet_prefix_in_completion_popup() {
+    let (mut harness, _temp, _file) = snippet_harness(&[("rust.json", RUST_SNIPPETS)], "main.rs");
+
+    harness.type_text("f").unwrap();
+    harness
+        .send_key(KeyCode::Char(' '), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Function definition");
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("fn name() {\n\t\n}");
+    harness.type_text("go").unwrap();
+    harness.assert_buffer_content("fn go() {\n\t\n}");
+}
+
+/// Mirrors follow the field as you type, through their transforms
+#[test]
+fn test_mirrors_and_transforms_update_while_typing() {
+    let snippets = r#"{
+        "Struct": {
+            "prefix": "st",
+            "body": ["struct ${1:Name};", "impl $1 {}", "// ${1/(.*)/${1:/upcase}/}"]
+        }
+    }"#;
+    let (mut harness, _temp, _file) = snippet_harness(&[("rust.json", snippets)], "main.rs");
+
+    harness.run_command("Insert Snippet").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("struct Name;\nimpl Name {}\n// NAME");
+
+    harness.type_text("Point").unwrap();
+    harness.assert_buffer_content("struct Point;\nimpl Point {}\n// POINT");
+
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("struct Poin;\nimpl Poin {}\n// POIN");
+}
+
+/// A choice field offers its alternatives in a popup
+#[test]
+fn test_choice_field_popup() {
+    let snippets = r#"{ "Level": { "prefix": "lvl", "body": "level: ${1|low,high|};$0" } }"#;
+    let (mut harness, _temp, _file) =
+        snippet_harness(&[("all.code-snippets", snippets)], "notes.txt");
+
+    harness.run_command("Insert Snippet").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content("level: low;");
+    harness.assert_screen_contains("high");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("level: high;");
+
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness.type_text("!").unwrap();
+    harness.assert_buffer_content("level: high;!");
+}
+
+/// File name, date and clipboard variables are filled in
+#[test]
+fn test_snippet_variables() {
+    let snippets =
+        r#"{ "Header": { "prefix": "hdr", "body": "$TM_FILENAME $CURRENT_YEAR $CLIPBOARD" } }"#;
+    let (mut harness, _temp, _file) =
+        snippet_harness(&[("all.code-snippets", snippets)], "notes.txt");
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("copied".to_string());
+
+    harness.run_command("Insert Snippet").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    let year = chrono::Local::now().format("%Y").to_string();
+    harness.assert_buffer_content(&format!("notes.txt {} copied", year));
+}
+
+/// A snippet using the selected text surrounds the selection
+#[test]
+fn test_snippet_surrounds_selection() {
+    let snippets = r#"{ "Bold": { "prefix": "b", "body": "<b>$TM_SELECTED_TEXT</b>" } }"#;
+    let (mut harness, _temp, _file) =
+        snippet_harness(&[("all.code-snippets", snippets)], "notes.txt");
+
+    harness.type_text("a word").unwrap();
+    harness
+        .send_key(KeyCode::Left, KeyModifiers::SHIFT | KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness.run_command("Insert Snippet").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("a <b>word</b>");
+}
+
+/// Snippets for other languages are not offered
+#[test]
+fn test_snippets_are_per_language() {
+    let (mut harness, _temp, _file) = snippet_harness(&[("rust.json", RUST_SNIPPETS)], "notes.txt");
+
+    harness.run_command("Insert Snippet").unwrap();
+    harness.assert_screen_contains("No snippets for this buffer");
+}
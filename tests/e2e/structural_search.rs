@@ -0,0 +1,141 @@
+//! End-to-end tests for structural search and replace
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+use tempfile::TempDir;
+
+const MAIN: &str = "fn main() -> Option<u8> {\n    let x = foo.unwrap();\n    let y = bar(1).unwrap_or(2);\n    Some(x + y)\n}\n";
+
+/// A project with a Rust file calling `unwrap`, and a text file that
+/// mentions it but isn't parsed
+fn project_harness(temp_dir: &TempDir) -> EditorTestHarness {
+    let root = temp_dir.path();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), MAIN).unwrap();
+    fs::write(root.join("notes.txt"), "x.unwrap()\n").unwrap();
+
+    let mut config = Config::default();
+    config.lsp.clear();
+    EditorTestHarness::with_config_and_working_dir(100, 30, config, root.to_path_buf()).unwrap()
+}
+
+/// Matches of a code pattern across the project open in the results buffer
+#[test]
+fn test_structural_search_in_project() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = project_harness(&temp_dir);
+
+    harness.run_command("Structural Search in Project").unwrap();
+    harness.answer_prompt("$X.unwrap()").unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|status| status.contains("Found 1 match in 1 file for '$X.unwrap()'"))
+        })
+        .unwrap();
+
+    let results = harness.get_buffer_content().unwrap();
+    assert!(
+        results.contains("src/main.rs:2:     let x = foo.unwrap();\n"),
+        "{}",
+        results
+    );
+    assert!(!results.contains("notes.txt"), "{}", results);
+    assert!(!results.contains("unwrap_or"), "{}", results);
+}
+
+/// A project replace rewrites each match from the template, and Undo Last
+/// Project Replace reverts it
+#[test]
+fn test_structural_replace_in_project_and_undo() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = project_harness(&temp_dir);
+    let main = temp_dir.path().join("src/main.rs");
+
+    harness
+        .run_command("Structural Replace in Project")
+        .unwrap();
+    harness.answer_prompt("$X.unwrap()").unwrap();
+    harness.answer_prompt("$X?").unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|status| status.contains("Replaced 1 occurrence in 1 file"))
+        })
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(&main).unwrap(),
+        MAIN.replace("foo.unwrap()", "foo?")
+    );
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("notes.txt")).unwrap(),
+        "x.unwrap()\n"
+    );
+
+    harness.run_command("Undo Last Project Replace").unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("restored 1 file"), "{}", status);
+    assert_eq!(fs::read_to_string(&main).unwrap(), MAIN);
+}
+
+/// A buffer replace is one undoable edit; repeated metavariables must
+/// match the same code
+#[test]
+fn test_structural_replace_in_buffer() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("math.rs");
+    let content = "fn f(a: u8, b: u8) -> u8 {\n    (a + a) * (a + b) * (g(b) + g(b))\n}\n";
+    fs::write(&path, content).unwrap();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    harness.open_file(&path).unwrap();
+
+    harness.run_command("Structural Replace in Buffer").unwrap();
+    harness.answer_prompt("$A + $A").unwrap();
+    harness.answer_prompt("2 * $A").unwrap();
+    harness.assert_buffer_content(
+        "fn f(a: u8, b: u8) -> u8 {\n    (2 * a) * (a + b) * (2 * g(b))\n}\n",
+    );
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("Replaced 2 occurrences"), "{}", status);
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content(content);
+}
+
+/// A pattern that isn't code of the buffer's language is reported
+#[test]
+fn test_structural_search_invalid_pattern() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("main.rs");
+    fs::write(&path, MAIN).unwrap();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    harness.open_file(&path).unwrap();
+
+    harness.run_command("Structural Search in Buffer").unwrap();
+    harness.answer_prompt("fn (").unwrap();
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(
+        status.contains("Pattern is not valid rust code"),
+        "{}",
+        status
+    );
+    harness.assert_buffer_content(MAIN);
+}
@@ -0,0 +1,122 @@
+//! End-to-end tests for adding, changing and deleting surrounding pairs
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+/// Move the cursor to the first occurrence of `needle`
+fn move_to(harness: &mut EditorTestHarness, needle: &str) {
+    let content = harness.get_buffer_content().unwrap();
+    let offset = content.find(needle).unwrap();
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    for _ in 0..content[..offset].chars().count() {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+}
+
+/// Every selection is wrapped, and one undo unwraps them all
+#[test]
+fn test_surround_selections_in_one_undo() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "notes.txt", "one two one").unwrap();
+
+    // Select both "one"s
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    harness
+        .send_key(KeyCode::Char('d'), KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness.run_command("Surround Selection").unwrap();
+    harness.assert_screen_contains("Surround with");
+    harness.answer_prompt("(").unwrap();
+    harness.assert_buffer_content("(one) two (one)");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("one two one");
+}
+
+/// A tag typed at the prompt wraps the selection in start and end tags
+#[test]
+fn test_surround_with_tag() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "notes.txt", "hello world").unwrap();
+
+    move_to(&mut harness, "world");
+    harness.send_key(KeyCode::End, KeyModifiers::SHIFT).unwrap();
+
+    harness.run_command("Surround Selection").unwrap();
+    harness.answer_prompt("<a href=\"#\">").unwrap();
+    harness.assert_buffer_content("hello <a href=\"#\">world</a>");
+}
+
+/// Deleting uses the syntax tree, so a bracket inside a string is skipped
+#[test]
+fn test_delete_surround_uses_syntax_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "main.rs",
+        "fn main() {\n    f(\"(\", [x]);\n}\n",
+    )
+    .unwrap();
+    harness.wait_for_syntax_tree().unwrap();
+
+    move_to(&mut harness, "x]");
+    harness.run_command("Delete Surround").unwrap();
+    harness.assert_buffer_content("fn main() {\n    f(\"(\", x);\n}\n");
+
+    harness.wait_for_syntax_tree().unwrap();
+    move_to(&mut harness, "\"(");
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness.run_command("Delete Surround").unwrap();
+    harness.assert_buffer_content("fn main() {\n    f((, x);\n}\n");
+}
+
+/// Changing replaces the innermost pair found by bracket matching
+#[test]
+fn test_change_surround_by_bracket_matching() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "notes.txt", "call(a, [b, c])")
+            .unwrap();
+
+    move_to(&mut harness, "c]");
+    harness.run_command("Change Surround").unwrap();
+    harness.assert_screen_contains("Change surrounding pair to:");
+    harness.answer_prompt("{").unwrap();
+    harness.assert_buffer_content("call(a, {b, c})");
+
+    move_to(&mut harness, "a,");
+    harness.run_command("Change Surround").unwrap();
+    harness.answer_prompt("\"").unwrap();
+    harness.assert_buffer_content("call\"a, {b, c}\"");
+}
+
+/// Without an enclosing pair nothing changes
+#[test]
+fn test_delete_surround_without_pair() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "notes.txt", "plain text").unwrap();
+
+    move_to(&mut harness, "text");
+    harness.run_command("Delete Surround").unwrap();
+    harness.assert_buffer_content("plain text");
+    harness.assert_screen_contains("No surrounding pair");
+}
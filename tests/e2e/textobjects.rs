@@ -0,0 +1,174 @@
+//! Tests for tree-sitter text objects and structural motions
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, Keybinding};
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+const SOURCE: &str = "\
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+fn main() {
+    let x = add(1, 2);
+    let y = add(3, 4);
+}
+";
+
+/// Bind a text object action to a function key
+fn bind(config: &mut Config, key: &str, action: &str, object: Option<&str>) {
+    let mut args = HashMap::new();
+    if let Some(object) = object {
+        args.insert("object".to_string(), serde_json::json!(object));
+    }
+    config.keybindings.push(Keybinding {
+        key: key.to_string(),
+        modifiers: vec![],
+        keys: vec![],
+        action: action.to_string(),
+        args,
+        when: Some("normal".to_string()),
+    });
+}
+
+/// Open SOURCE as a Rust file with text object actions on F5-F8
+fn harness_with_textobjects(temp_dir: &TempDir) -> EditorTestHarness {
+    let mut config = Config::default();
+    bind(&mut config, "F5", "select_textobject", Some("function"));
+    bind(&mut config, "F6", "goto_next_textobject", Some("function"));
+    bind(
+        &mut config,
+        "F7",
+        "goto_previous_textobject",
+        Some("function"),
+    );
+    bind(
+        &mut config,
+        "F8",
+        "select_next_textobject",
+        Some("parameter.inner"),
+    );
+
+    // Without a language server everything is resolved from the syntax tree
+    EditorTestHarness::with_file_and_config(80, 24, temp_dir.path(), "main.rs", SOURCE, config)
+        .unwrap()
+}
+
+fn selected_text(harness: &mut EditorTestHarness) -> Option<String> {
+    let range = harness
+        .editor()
+        .active_state()
+        .cursors
+        .primary()
+        .selection_range()?;
+    Some(
+        harness
+            .editor_mut()
+            .active_state_mut()
+            .get_text_range(range.start, range.end),
+    )
+}
+
+fn press(harness: &mut EditorTestHarness, key: u8) {
+    harness
+        .send_key(KeyCode::F(key), KeyModifiers::NONE)
+        .unwrap();
+}
+
+/// Test selecting a function and jumping between functions
+#[test]
+fn test_select_function_and_function_motions() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_textobjects(&temp_dir);
+
+    // Cursor inside the body of `add`
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key_repeat(KeyCode::Right, KeyModifiers::NONE, 4)
+        .unwrap();
+
+    press(&mut harness, 5);
+    assert_eq!(
+        selected_text(&mut harness).as_deref(),
+        Some("fn add(a: i32, b: i32) -> i32 {\n    a + b\n}")
+    );
+
+    press(&mut harness, 6);
+    let cursor = *harness.editor().active_state().cursors.primary();
+    assert_eq!(cursor.position, SOURCE.find("fn main").unwrap());
+    assert!(cursor.selection_range().is_none());
+
+    // There is no function after `main`
+    press(&mut harness, 6);
+    assert_eq!(
+        harness.editor().active_state().cursors.primary().position,
+        SOURCE.find("fn main").unwrap()
+    );
+
+    press(&mut harness, 7);
+    assert_eq!(
+        harness.editor().active_state().cursors.primary().position,
+        0
+    );
+}
+
+/// Test stepping through parameters with "select next parameter"
+#[test]
+fn test_select_next_parameter() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_textobjects(&temp_dir);
+
+    press(&mut harness, 8);
+    assert_eq!(selected_text(&mut harness).as_deref(), Some("a: i32"));
+    press(&mut harness, 8);
+    assert_eq!(selected_text(&mut harness).as_deref(), Some("b: i32"));
+    press(&mut harness, 8);
+    assert_eq!(selected_text(&mut harness).as_deref(), Some("1"));
+}
+
+/// Test swapping and deleting arguments with several cursors at once
+#[test]
+fn test_swap_and_delete_argument_multi_cursor() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("main.rs");
+    std::fs::write(&file_path, SOURCE).unwrap();
+
+    let mut config = Config::default();
+    config.lsp.clear();
+    bind(&mut config, "F9", "swap_parameter_next", None);
+    bind(&mut config, "F4", "delete_parameter", None);
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+
+    // Cursors on the first argument of both calls
+    harness
+        .send_key_repeat(KeyCode::Down, KeyModifiers::NONE, 5)
+        .unwrap();
+    harness
+        .send_key_repeat(KeyCode::Right, KeyModifiers::NONE, 16)
+        .unwrap();
+    harness.editor_mut().add_cursor_below();
+    assert_eq!(harness.editor().active_state().cursors.count(), 2);
+
+    press(&mut harness, 9);
+    let content = harness.get_buffer_content().unwrap();
+    assert!(content.contains("let x = add(2, 1);"), "{}", content);
+    assert!(content.contains("let y = add(4, 3);"), "{}", content);
+
+    // The cursors moved with the swapped arguments, so they now delete
+    // the last argument together with the comma before it
+    press(&mut harness, 4);
+    let content = harness.get_buffer_content().unwrap();
+    assert!(content.contains("let x = add(2);"), "{}", content);
+    assert!(content.contains("let y = add(4);"), "{}", content);
+
+    // Both deletions are undone together
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    let content = harness.get_buffer_content().unwrap();
+    assert!(content.contains("let x = add(2, 1);"), "{}", content);
+    assert!(content.contains("let y = add(4, 3);"), "{}", content);
+}
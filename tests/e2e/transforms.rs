@@ -0,0 +1,160 @@
+//! End-to-end tests for the Transform commands
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+/// Every selection is converted, and one undo restores them all
+#[test]
+fn test_transform_every_selection() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "notes.txt", "user name, user name")
+            .unwrap();
+
+    for _ in 0..9 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    harness
+        .send_key(KeyCode::Char('d'), KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness.run_command("Transform: camelCase").unwrap();
+    harness.assert_buffer_content("userName, userName");
+    let mut selections: Vec<_> = harness
+        .editor()
+        .active_state()
+        .cursors
+        .iter()
+        .filter_map(|(_, cursor)| cursor.selection_range())
+        .collect();
+    selections.sort_by_key(|range| range.start);
+    assert_eq!(selections, vec![0..8, 10..18]);
+
+    harness.run_command("Transform: SCREAMING_CASE").unwrap();
+    harness.assert_buffer_content("USER_NAME, USER_NAME");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("user name, user name");
+}
+
+/// Without a selection, the word under the cursor is transformed
+#[test]
+fn test_transform_word_under_cursor() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.txt",
+        "let parseHttpResponse = 1;",
+    )
+    .unwrap();
+
+    for _ in 0..7 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    harness.run_command("Transform: snake_case").unwrap();
+    harness.assert_buffer_content("let parse_http_response = 1;");
+    assert_eq!(harness.cursor_position(), 7);
+
+    harness.run_command("Transform: Toggle Case").unwrap();
+    harness.assert_buffer_content("let PARSE_HTTP_RESPONSE = 1;");
+}
+
+/// Incrementing steps the number after the cursor and moves onto its last
+/// character, so it can be stepped again
+#[test]
+fn test_increment_and_decrement_numbers() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.txt",
+        "retries = 9\ndue 2024-02-28",
+    )
+    .unwrap();
+
+    harness.run_command("Transform: Increment Number").unwrap();
+    harness.assert_buffer_content("retries = 10\ndue 2024-02-28");
+    assert_eq!(harness.cursor_position(), "retries = 1".len());
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    harness.run_command("Transform: Increment Number").unwrap();
+    harness.run_command("Transform: Increment Number").unwrap();
+    harness.assert_buffer_content("retries = 10\ndue 2024-03-01");
+
+    harness.run_command("Transform: Decrement Number").unwrap();
+    harness.assert_buffer_content("retries = 10\ndue 2024-02-29");
+}
+
+/// Math is evaluated in place, on the cursor's line without a selection
+#[test]
+fn test_evaluate_math_expression() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "notes.txt", "  2 * (3 + 4)\n1 / 0")
+            .unwrap();
+
+    harness
+        .run_command("Transform: Evaluate Math Expression")
+        .unwrap();
+    harness.assert_buffer_content("  14\n1 / 0");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .run_command("Transform: Evaluate Math Expression")
+        .unwrap();
+    harness.assert_buffer_content("  14\n1 / 0");
+    harness.assert_screen_contains("Division by zero");
+}
+
+/// Encodings round-trip, and invalid input leaves the text alone
+#[test]
+fn test_encode_and_decode() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness =
+        EditorTestHarness::with_file(80, 24, temp_dir.path(), "notes.txt", "a b&c").unwrap();
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.run_command("Transform: URL Encode").unwrap();
+    harness.assert_buffer_content("a%20b%26c");
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.run_command("Transform: Base64 Encode").unwrap();
+    harness.assert_buffer_content("YSUyMGIlMjZj");
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.run_command("Transform: Base64 Decode").unwrap();
+    harness.run_command("Transform: URL Decode").unwrap();
+    harness.assert_buffer_content("a b&c");
+
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.run_command("Transform: Base64 Decode").unwrap();
+    harness.assert_buffer_content("a b&c");
+    let status = harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default();
+    assert!(status.contains("Invalid base64"), "{}", status);
+}
@@ -0,0 +1,141 @@
+//! End-to-end tests for vi modal editing
+//!
+//! These tests use the "vi" keybinding style, which starts every buffer in
+//! Normal mode and parses operators, motions and counts in front of the keymap.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+
+/// Create a harness with the vi keybinding style
+fn vi_harness(width: u16, height: u16) -> EditorTestHarness {
+    let config = Config {
+        active_keybinding_map: "vi".into(),
+        ..Default::default()
+    };
+    EditorTestHarness::with_config(width, height, config).unwrap()
+}
+
+fn escape(harness: &mut EditorTestHarness) {
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+}
+
+/// Normal mode is shown in the status bar and typed keys are commands, not text
+#[test]
+fn test_vi_starts_in_normal_mode() {
+    let mut harness = vi_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("hello world").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("NORMAL");
+
+    harness.type_text("l").unwrap();
+    harness.assert_buffer_content("hello world");
+    assert_eq!(harness.cursor_position(), 1);
+}
+
+/// `i` enters Insert mode, typed text is inserted, Esc returns to Normal mode
+#[test]
+fn test_vi_insert_and_escape() {
+    let mut harness = vi_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("world").unwrap();
+
+    harness.type_text("i").unwrap();
+    harness.assert_screen_contains("INSERT");
+    harness.type_text("hello ").unwrap();
+    escape(&mut harness);
+    harness.render().unwrap();
+
+    harness.assert_buffer_content("hello world");
+    harness.assert_screen_contains("NORMAL");
+    // Leaving Insert mode steps back onto the last inserted character
+    assert_eq!(harness.cursor_position(), 5);
+}
+
+/// Operators combine with counted motions
+#[test]
+fn test_vi_delete_word_with_count() {
+    let mut harness = vi_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("one two three four").unwrap();
+
+    harness.type_text("dw").unwrap();
+    harness.assert_buffer_content("two three four");
+
+    harness.type_text("d2w").unwrap();
+    harness.assert_buffer_content("four");
+}
+
+/// `dd` with a count deletes whole lines, and `p` puts them back linewise
+#[test]
+fn test_vi_delete_lines_and_put() {
+    let mut harness = vi_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("a\nb\nc\nd").unwrap();
+
+    harness.type_text("2dd").unwrap();
+    harness.assert_buffer_content("c\nd");
+
+    harness.type_text("p").unwrap();
+    harness.assert_buffer_content("c\na\nb\nd");
+}
+
+/// `ci"` changes the inside of a quoted string and `.` repeats the change
+#[test]
+fn test_vi_change_inside_quotes_and_repeat() {
+    let mut harness = vi_harness(80, 24);
+    let _fixture = harness
+        .load_buffer_from_text("let a = \"old\";\nlet b = \"old\";")
+        .unwrap();
+
+    harness.type_text("fo").unwrap();
+    harness.type_text("ci\"new").unwrap();
+    escape(&mut harness);
+    harness.assert_buffer_content("let a = \"new\";\nlet b = \"old\";");
+
+    harness.type_text("j.").unwrap();
+    harness.assert_buffer_content("let a = \"new\";\nlet b = \"new\";");
+}
+
+/// Visual line mode selects whole lines for the operator
+#[test]
+fn test_vi_visual_line_delete() {
+    let mut harness = vi_harness(80, 24);
+    let _fixture = harness
+        .load_buffer_from_text("keep\ndrop 1\ndrop 2\nkeep")
+        .unwrap();
+
+    harness.type_text("jV").unwrap();
+    harness.assert_screen_contains("V-LINE");
+    harness.type_text("jd").unwrap();
+
+    harness.assert_buffer_content("keep\nkeep");
+    harness.assert_screen_contains("NORMAL");
+}
+
+/// A vi operator is a single undo step
+#[test]
+fn test_vi_undo_restores_operator() {
+    let mut harness = vi_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("one two three").unwrap();
+
+    harness.type_text("d2w").unwrap();
+    harness.assert_buffer_content("three");
+
+    harness.type_text("u").unwrap();
+    harness.assert_buffer_content("one two three");
+}
+
+/// Normal-mode commands apply to every cursor
+#[test]
+fn test_vi_multi_cursor_delete_char() {
+    let mut harness = vi_harness(80, 24);
+    let _fixture = harness.load_buffer_from_text("xa\nxb\nxc").unwrap();
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::ALT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::ALT)
+        .unwrap();
+    harness.type_text("x").unwrap();
+
+    harness.assert_buffer_content("a\nb\nc");
+}
@@ -0,0 +1,129 @@
+//! End-to-end tests for completing words from the open buffers
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{CompletionTrigger, Config};
+use tempfile::TempDir;
+
+fn complete(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char(' '), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn completion_open(harness: &EditorTestHarness) -> bool {
+    harness
+        .editor()
+        .active_state()
+        .popups
+        .top()
+        .is_some_and(|popup| popup.title.as_deref() == Some("Completion"))
+}
+
+/// Words of the buffer complete the word before the cursor, those on
+/// nearby lines first
+#[test]
+fn test_complete_nearest_buffer_word() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let far_away = "x\n".repeat(150);
+    let text = format!("formula\n{}former\nfo", far_away);
+    let _fixture = harness.load_buffer_from_text(&text).unwrap();
+
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    complete(&mut harness);
+    harness.assert_screen_contains("former");
+    harness.assert_screen_contains("formula");
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content(&format!("formula\n{}former\nformer", far_away));
+
+    // The index follows edits, so a word just typed completes too
+    harness.type_text(" zebra ze").unwrap();
+    complete(&mut harness);
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content(&format!("formula\n{}former\nformer zebra zebra", far_away));
+}
+
+/// Words of other open buffers complete too
+#[test]
+fn test_complete_word_from_other_buffer() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness
+        .open_new_file(temp_dir.path(), "notes.txt", "serendipity\n")
+        .unwrap();
+    harness
+        .open_new_file(temp_dir.path(), "draft.txt", "")
+        .unwrap();
+
+    harness.type_text("ser").unwrap();
+    complete(&mut harness);
+    harness.assert_screen_contains("serendipity");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("serendipity");
+}
+
+/// Nothing is offered when no word completes the prefix
+#[test]
+fn test_no_completion_without_matching_word() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("alpha beta\nzz").unwrap();
+
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    complete(&mut harness);
+    assert!(!completion_open(&harness));
+}
+
+/// A language set to complete while typing opens the popup once the word
+/// is long enough, and typing goes on through the popup
+#[test]
+fn test_completion_while_typing() {
+    let mut config = Config::default();
+    let markdown = config.languages.get_mut("markdown").unwrap();
+    markdown.completion_trigger = CompletionTrigger::Typing;
+    markdown.completion_min_prefix = 2;
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::with_file_and_config(
+        80,
+        24,
+        temp_dir.path(),
+        "notes.md",
+        "Paragraph about parsing\n\n",
+        config,
+    )
+    .unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness.type_text("p").unwrap();
+    assert!(!completion_open(&harness));
+    harness.type_text("a").unwrap();
+    assert!(completion_open(&harness));
+    harness.render().unwrap();
+    harness.assert_screen_contains("parsing");
+
+    // Typing through narrows the words, ignoring case
+    harness.type_text("ra").unwrap();
+    harness.assert_buffer_content("Paragraph about parsing\n\npara");
+    assert!(completion_open(&harness));
+    harness.render().unwrap();
+    harness.assert_screen_contains("Paragraph");
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("Paragraph about parsing\n\nParagraph");
+}